@@ -0,0 +1,4 @@
+/// Long-running services shared by the transports, such as the poller.
+pub mod services;
+/// Use cases run on behalf of clients.
+pub mod use_cases;
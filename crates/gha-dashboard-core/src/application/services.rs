@@ -0,0 +1,51 @@
+/// Watches the quota and token expiry GitHub reports.
+pub mod api_status_monitor;
+/// Recomputes the daily stats of the stored runs on a schedule.
+pub mod daily_stats_aggregator;
+/// Latest deployment of each environment of the polled repositories.
+pub mod deployment_inventory;
+/// Numbered recent poller events, for clients resuming after a disconnect.
+pub mod event_log;
+/// Compacts the run store on a schedule.
+pub mod history_compactor;
+/// Latest job states pushed by webhooks.
+pub mod job_tracker;
+/// Follows the log of a running job.
+pub mod log_tail;
+/// Runs clients pinned, polled however old they get.
+pub mod pinned_runs;
+/// Health of the polling loop.
+pub mod poller_status;
+/// Comparison of webhook-updated runs with a later poll.
+pub mod reconciliation;
+/// Repositories appearing in or leaving those the token can see.
+pub mod repository_discovery;
+/// Required checks of the default branches.
+pub mod required_checks;
+/// Notifications of runs whose status changed.
+pub mod run_notifier;
+/// Self-hosted runners of the polled repositories.
+pub mod runner_inventory;
+/// The polling loop and the snapshots it shares with every client.
+pub mod shared_poller;
+/// The latest snapshot and the recent events, shared by the poller and its readers.
+pub mod snapshot_store;
+/// Workflows of the polled repositories and their schedules.
+pub mod workflow_inventory;
+
+pub use api_status_monitor::ApiStatusMonitor;
+pub use daily_stats_aggregator::DailyStatsAggregator;
+pub use deployment_inventory::{DeploymentBoard, DeploymentInventory};
+pub use event_log::{CatchUp, EventLog, SequencedEvent};
+pub use history_compactor::HistoryCompactor;
+pub use job_tracker::JobTracker;
+pub use log_tail::{LogEndReason, LogTailFrame, LogTailLimits, tail_job_logs};
+pub use pinned_runs::{PinnedRun, PinnedRuns};
+pub use poller_status::{PollerError, PollerState, PollerStatus, RepositoryStatus};
+pub use reconciliation::{ReconciliationReport, StatusMismatch};
+pub use repository_discovery::{Discovery, RepositoryDiscovery};
+pub use run_notifier::{NotificationFilter, RunNotifier};
+pub use runner_inventory::{RunnerFleet, RunnerInventory};
+pub use shared_poller::{Notice, NoticeLevel, PollerEvent, SharedPoller, panic_message};
+pub use snapshot_store::{LatestSnapshot, SnapshotStore};
+pub use workflow_inventory::{WorkflowInventory, WorkflowInventoryEntry};
@@ -0,0 +1,147 @@
+use super::shared_poller::{Notice, SharedPoller};
+use crate::domain::external_apis::github::{GitHubApi, GitHubApiStatus};
+use chrono::{DateTime, TimeDelta, Utc};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// Interval between two checks of the GitHub API status (seconds)
+const CHECK_INTERVAL_SECONDS: u64 = 300;
+
+/// Clients are warned once the token expires within this many hours
+const TOKEN_EXPIRY_WARNING_HOURS: i64 = 24;
+
+/// Minimum interval between two token expiry warnings (hours)
+const TOKEN_EXPIRY_REPEAT_HOURS: i64 = 1;
+
+/// Clients are warned once fewer than this percentage of API calls remain
+const RATE_LIMIT_WARNING_PERCENT: u64 = 10;
+
+/// Turns what the GitHub API reports about credentials and quota into client notices.
+#[derive(Debug, Default)]
+pub struct ApiStatusMonitor {
+    last_token_warning_at: Option<DateTime<Utc>>,
+    rate_limit_low: bool,
+}
+
+impl ApiStatusMonitor {
+    /// Returns the notices to broadcast for `status`, without repeating recent ones.
+    pub fn check(&mut self, status: &GitHubApiStatus, now: DateTime<Utc>) -> Vec<Notice> {
+        let mut notices = Vec::new();
+
+        if let Some(expires_at) = status.token_expires_at {
+            let remaining = expires_at - now;
+            let recently_warned = self.last_token_warning_at.is_some_and(|warned_at| {
+                now - warned_at < TimeDelta::hours(TOKEN_EXPIRY_REPEAT_HOURS)
+            });
+            if remaining <= TimeDelta::hours(TOKEN_EXPIRY_WARNING_HOURS) && !recently_warned {
+                self.last_token_warning_at = Some(now);
+                notices.push(Notice::warning(if remaining > TimeDelta::zero() {
+                    format!("GitHub token expires in {}h", remaining.num_hours().max(1))
+                } else {
+                    "GitHub token has expired, updates will stop".to_string()
+                }));
+            }
+        }
+
+        if let (Some(remaining), Some(limit)) =
+            (status.rate_limit_remaining, status.rate_limit_limit)
+        {
+            let low = remaining * 100 < limit * RATE_LIMIT_WARNING_PERCENT;
+            // Only announce the transition, not every check while it lasts
+            if low && !self.rate_limit_low {
+                notices.push(Notice::warning(format!(
+                    "GitHub API rate limit nearly exhausted ({remaining} of {limit} left), updates slowing down"
+                )));
+            } else if !low && self.rate_limit_low {
+                notices.push(Notice::info("GitHub API rate limit recovered"));
+            }
+            self.rate_limit_low = low;
+        }
+
+        notices
+    }
+
+    /// Periodically checks `github_api` and broadcasts the resulting notices through `poller`.
+    pub fn spawn(
+        mut self,
+        github_api: Arc<dyn GitHubApi + Send + Sync>,
+        poller: Arc<SharedPoller>,
+    ) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(CHECK_INTERVAL_SECONDS));
+            loop {
+                interval.tick().await;
+                for notice in self.check(&github_api.api_status(), Utc::now()) {
+                    poller.notify_clients(notice);
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::services::NoticeLevel;
+    use crate::test_support::base_time;
+
+    fn expiring_in(hours: i64) -> GitHubApiStatus {
+        GitHubApiStatus {
+            token_expires_at: Some(base_time() + TimeDelta::hours(hours)),
+            ..GitHubApiStatus::default()
+        }
+    }
+
+    #[test]
+    fn test_token_expiring_within_a_day_is_warned_hourly() {
+        let mut monitor = ApiStatusMonitor::default();
+        let status = expiring_in(20);
+
+        let first = monitor.check(&status, base_time());
+        let soon_after = monitor.check(&status, base_time() + TimeDelta::minutes(5));
+        let an_hour_later = monitor.check(&status, base_time() + TimeDelta::hours(1));
+
+        assert_eq!(first, vec![Notice::warning("GitHub token expires in 20h")]);
+        assert!(soon_after.is_empty());
+        assert_eq!(
+            an_hour_later,
+            vec![Notice::warning("GitHub token expires in 19h")]
+        );
+    }
+
+    #[test]
+    fn test_distant_or_unknown_expiry_is_not_warned() {
+        let mut monitor = ApiStatusMonitor::default();
+
+        assert!(monitor.check(&expiring_in(48), base_time()).is_empty());
+        assert!(
+            monitor
+                .check(&GitHubApiStatus::default(), base_time())
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_rate_limit_warning_is_sent_on_transition_only() {
+        let mut monitor = ApiStatusMonitor::default();
+        let quota = |remaining| GitHubApiStatus {
+            rate_limit_remaining: Some(remaining),
+            rate_limit_limit: Some(5_000),
+            ..GitHubApiStatus::default()
+        };
+
+        assert!(monitor.check(&quota(4_000), base_time()).is_empty());
+        let low = monitor.check(&quota(100), base_time());
+        assert!(monitor.check(&quota(50), base_time()).is_empty());
+        let recovered = monitor.check(&quota(5_000), base_time());
+
+        assert_eq!(low.len(), 1);
+        assert_eq!(low[0].level, NoticeLevel::Warning);
+        assert!(low[0].message.contains("100 of 5000"));
+        assert_eq!(
+            recovered,
+            vec![Notice::info("GitHub API rate limit recovered")]
+        );
+    }
+}
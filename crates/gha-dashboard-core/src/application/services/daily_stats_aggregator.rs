@@ -0,0 +1,52 @@
+use crate::domain::repositories::{RunRepository, RunRepositoryError};
+use chrono::{TimeDelta, Utc};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// Interval between two scheduled aggregations of the daily stats
+const AGGREGATION_INTERVAL: Duration = Duration::from_mins(10);
+
+/// Days recomputed by each aggregation, counting today, so runs finishing after midnight still
+/// land in the day they were created
+const RECOMPUTED_DAYS: i64 = 2;
+
+/// Rolls stored runs up into the daily stats behind `/trends`.
+pub struct DailyStatsAggregator {
+    run_repository: Arc<dyn RunRepository + Send + Sync>,
+}
+
+impl DailyStatsAggregator {
+    /// Aggregates the stats of the runs stored in `run_repository`.
+    #[must_use]
+    pub fn new(run_repository: Arc<dyn RunRepository + Send + Sync>) -> Self {
+        Self { run_repository }
+    }
+
+    /// Recomputes the stats of the last two UTC days.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the run store fails.
+    pub async fn aggregate(&self) -> Result<(), RunRepositoryError> {
+        let since = Utc::now().date_naive() - TimeDelta::days(RECOMPUTED_DAYS - 1);
+        self.run_repository.aggregate_daily_stats(since).await?;
+        tracing::debug!("Aggregated daily run stats since {}", since);
+        Ok(())
+    }
+
+    /// Aggregates right away and then every ten minutes.
+    #[must_use]
+    pub fn spawn(self: &Arc<Self>) -> JoinHandle<()> {
+        let aggregator = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(AGGREGATION_INTERVAL);
+            loop {
+                interval.tick().await;
+                if let Err(e) = aggregator.aggregate().await {
+                    tracing::warn!("Failed to aggregate daily run stats: {}", e);
+                }
+            }
+        })
+    }
+}
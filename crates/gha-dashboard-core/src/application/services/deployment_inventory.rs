@@ -0,0 +1,233 @@
+use super::shared_poller::SharedPoller;
+use crate::domain::external_apis::github::{GitHubApi, GitHubApiError, Repository};
+use crate::domain::models::deployment::{Deployment, DeploymentState};
+use crate::domain::models::ids::RepoFullName;
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+use tokio::sync::{broadcast, watch};
+use tokio::task::JoinHandle;
+
+/// Interval between two refreshes of the deployments; they change far less often than runs
+/// (seconds)
+const REFRESH_INTERVAL_SECONDS: u64 = 600;
+
+/// Latest deployment to each environment of the polled repositories, as of `updated_at`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeploymentBoard {
+    /// By repository, then environment; environments never deployed to are left out
+    pub environments: Vec<DeploymentState>,
+    /// When the deployments were last fetched
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Keeps track of what is deployed to the environments of the polled repositories.
+///
+/// Repositories whose deployments the token may not list are not asked again.
+pub struct DeploymentInventory {
+    github_api: Arc<dyn GitHubApi + Send + Sync>,
+    board: watch::Sender<Option<Arc<DeploymentBoard>>>,
+    /// Repositories skipped by every further refresh
+    skipped: Mutex<HashSet<RepoFullName>>,
+}
+
+impl DeploymentInventory {
+    /// An inventory listing the deployments `github_api` can see, empty until spawned.
+    #[must_use]
+    pub fn new(github_api: Arc<dyn GitHubApi + Send + Sync>) -> Self {
+        Self {
+            github_api,
+            board: watch::channel(None).0,
+            skipped: Mutex::default(),
+        }
+    }
+
+    /// The last refresh; `None` until the first one completes.
+    #[must_use]
+    pub fn board(&self) -> Option<Arc<DeploymentBoard>> {
+        self.board.borrow().clone()
+    }
+
+    fn skipped(&self) -> MutexGuard<'_, HashSet<RepoFullName>> {
+        self.skipped.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Looks up the latest deployment to each environment of `repositories` at `now`.
+    ///
+    /// A repository whose deployments cannot be listed keeps those of the last refresh.
+    pub async fn refresh(&self, repositories: &[RepoFullName], now: DateTime<Utc>) {
+        let previous = self.board();
+        let mut environments = Vec::new();
+        for repository in repositories {
+            if self.skipped().contains(repository) {
+                continue;
+            }
+            match self.latest_deployments(repository).await {
+                Ok(states) => environments.extend(states),
+                Err(e @ (GitHubApiError::NotFound { .. } | GitHubApiError::Forbidden { .. })) => {
+                    tracing::warn!("{}, not listing the deployments of {} again", e, repository);
+                    self.skipped().insert(repository.clone());
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to list deployments: {}", e);
+                    environments.extend(
+                        previous
+                            .iter()
+                            .flat_map(|board| &board.environments)
+                            .filter(|state| state.repository == *repository)
+                            .cloned(),
+                    );
+                }
+            }
+        }
+        environments
+            .sort_by(|a, b| (&a.repository, &a.environment).cmp(&(&b.repository, &b.environment)));
+        self.board.send_replace(Some(Arc::new(DeploymentBoard {
+            environments,
+            updated_at: now,
+        })));
+    }
+
+    async fn latest_deployments(
+        &self,
+        repository: &RepoFullName,
+    ) -> Result<Vec<DeploymentState>, GitHubApiError> {
+        let mut latest: BTreeMap<String, Deployment> = BTreeMap::new();
+        for deployment in self.github_api.fetch_deployments(repository, None).await? {
+            match latest.get(&deployment.environment) {
+                Some(newer) if newer.created_at >= deployment.created_at => {}
+                _ => {
+                    latest.insert(deployment.environment.clone(), deployment);
+                }
+            }
+        }
+        let mut states = Vec::with_capacity(latest.len());
+        for deployment in latest.into_values() {
+            let statuses = self
+                .github_api
+                .fetch_deployment_statuses(repository, deployment.id)
+                .await?;
+            states.push(DeploymentState::new(
+                repository.clone(),
+                deployment,
+                &statuses,
+            ));
+        }
+        Ok(states)
+    }
+
+    /// Refreshes the deployments of the repositories of `poller`'s latest snapshot every ten
+    /// minutes, starting with the first snapshot.
+    pub fn spawn(self: Arc<Self>, poller: Arc<SharedPoller>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut events = poller.subscribe();
+            let mut interval = tokio::time::interval(Duration::from_secs(REFRESH_INTERVAL_SECONDS));
+            loop {
+                interval.tick().await;
+                let latest = loop {
+                    if let Some(latest) = poller.snapshots().latest() {
+                        break latest;
+                    }
+                    if let Err(broadcast::error::RecvError::Closed) = events.recv().await {
+                        return;
+                    }
+                };
+                let repositories: Vec<RepoFullName> = latest
+                    .repositories
+                    .iter()
+                    .map(Repository::full_name)
+                    .cloned()
+                    .collect();
+                self.refresh(&repositories, Utc::now()).await;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::deployment::{DeploymentStatus, DeploymentStatusState};
+    use crate::domain::models::ids::DeploymentId;
+    use crate::test_support::{base_time, repo_full_name};
+    use crate::testing::ScriptedGitHubApi;
+    use chrono::TimeDelta;
+
+    fn deployment(id: u64, environment: &str, minutes: i64) -> Deployment {
+        Deployment {
+            id: DeploymentId(id),
+            environment: environment.to_string(),
+            git_ref: "main".to_string(),
+            sha: format!("sha{id}"),
+            creator: None,
+            created_at: base_time() + TimeDelta::minutes(minutes),
+            updated_at: base_time() + TimeDelta::minutes(minutes),
+        }
+    }
+
+    fn status(state: DeploymentStatusState) -> DeploymentStatus {
+        DeploymentStatus {
+            state,
+            target_url: None,
+            created_at: base_time(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_the_latest_deployment_of_each_environment_is_kept() -> anyhow::Result<()> {
+        let (deployed, private) = (repo_full_name("owner/app"), repo_full_name("owner/private"));
+        let github_api = Arc::new(ScriptedGitHubApi::new());
+        github_api
+            .deployments(
+                &deployed,
+                Ok(vec![
+                    deployment(3, "staging", 20),
+                    deployment(2, "production", 10),
+                    deployment(1, "production", 0),
+                ]),
+            )
+            .deployment_statuses(
+                &deployed,
+                DeploymentId(3),
+                Ok(vec![status(DeploymentStatusState::Failure)]),
+            )
+            .deployment_statuses(&deployed, DeploymentId(2), Ok(Vec::new()))
+            .deployments(
+                &private,
+                GitHubApiError::NotFound {
+                    resource: "deployments of owner/private".to_string(),
+                },
+            );
+        let inventory = DeploymentInventory::new(Arc::clone(&github_api) as _);
+        let repositories = [deployed.clone(), private];
+
+        inventory.refresh(&repositories, base_time()).await;
+        inventory.refresh(&repositories, base_time()).await;
+
+        let board = inventory
+            .board()
+            .ok_or_else(|| anyhow::anyhow!("no refresh"))?;
+        let environments: Vec<_> = board
+            .environments
+            .iter()
+            .map(|state| (state.environment.as_str(), state.sha.as_str(), state.state))
+            .collect();
+        assert_eq!(
+            environments,
+            [
+                ("production", "sha2", DeploymentStatusState::Pending),
+                ("staging", "sha3", DeploymentStatusState::Failure),
+            ]
+        );
+        assert_eq!(
+            github_api
+                .calls()
+                .iter()
+                .filter(|call| call.ends_with("owner/private"))
+                .count(),
+            1
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,211 @@
+use super::shared_poller::{EVENT_CHANNEL_CAPACITY, PollerEvent};
+use std::collections::VecDeque;
+use std::sync::{Mutex, PoisonError};
+use tokio::sync::broadcast;
+
+/// Number of recent events kept for clients resuming after a disconnect
+pub const EVENT_LOG_CAPACITY: usize = 128;
+
+/// A poller event numbered in the order it was published, starting at 1.
+#[derive(Debug, Clone)]
+pub struct SequencedEvent {
+    /// Sequence number, 1 for the first event recorded
+    pub seq: u64,
+    /// The event as published
+    pub event: PollerEvent,
+}
+
+/// What a client has to be sent before it follows live events.
+#[derive(Debug)]
+pub enum CatchUp {
+    /// Every event published after the client's sequence number, oldest first
+    Replay(Vec<SequencedEvent>),
+    /// The latest snapshot, if any; `resync` is set when the client asked to resume from a
+    /// sequence number that is no longer buffered, or was never published by this server
+    Snapshot {
+        /// The last snapshot recorded
+        latest: Option<SequencedEvent>,
+        /// Whether the client missed events it can no longer be sent
+        resync: bool,
+    },
+}
+
+#[derive(Debug, Default)]
+struct EventLogState {
+    /// Sequence number of the last recorded event, 0 before the first one
+    last_seq: u64,
+    /// The most recent events, oldest first
+    entries: VecDeque<SequencedEvent>,
+    latest_snapshot: Option<SequencedEvent>,
+}
+
+/// Numbers poller events and keeps the most recent ones, so a client that reconnects can
+/// be sent what it missed instead of only the latest snapshot.
+pub struct EventLog {
+    capacity: usize,
+    state: Mutex<EventLogState>,
+    events: broadcast::Sender<SequencedEvent>,
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        Self::new(EVENT_LOG_CAPACITY)
+    }
+}
+
+impl EventLog {
+    /// Creates a log keeping the last `capacity` events.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        Self {
+            capacity,
+            state: Mutex::default(),
+            events,
+        }
+    }
+
+    /// Numbers `event`, keeps it and sends it to every subscriber; returns its sequence number.
+    pub fn record(&self, event: PollerEvent) -> u64 {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.last_seq += 1;
+        let event = SequencedEvent {
+            seq: state.last_seq,
+            event,
+        };
+        if matches!(event.event, PollerEvent::Snapshot(_)) {
+            state.latest_snapshot = Some(event.clone());
+        }
+        state.entries.push_back(event.clone());
+        while state.entries.len() > self.capacity {
+            state.entries.pop_front();
+        }
+        // Sent while locked, so `attach` never sees an event both buffered and in the channel
+        let _ = self.events.send(event);
+        state.last_seq
+    }
+
+    /// Subscribes to events recorded after this call, along with what the client must be sent
+    /// first: the events after `since` when resuming, otherwise the latest snapshot.
+    #[must_use]
+    pub fn attach(&self, since: Option<u64>) -> (broadcast::Receiver<SequencedEvent>, CatchUp) {
+        let state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let receiver = self.events.subscribe();
+        let buffered = |since: u64| {
+            since <= state.last_seq
+                && state
+                    .entries
+                    .front()
+                    .map_or(since == state.last_seq, |oldest| oldest.seq <= since + 1)
+        };
+        let catch_up = match since {
+            Some(since) if buffered(since) => CatchUp::Replay(
+                state
+                    .entries
+                    .iter()
+                    .filter(|entry| entry.seq > since)
+                    .cloned()
+                    .collect(),
+            ),
+            since => CatchUp::Snapshot {
+                latest: state.latest_snapshot.clone(),
+                resync: since.is_some(),
+            },
+        };
+        (receiver, catch_up)
+    }
+
+    /// The last `n` snapshots still buffered, oldest first.
+    #[must_use]
+    pub fn recent_snapshots(&self, n: usize) -> Vec<SequencedEvent> {
+        let state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut snapshots: Vec<SequencedEvent> = state
+            .entries
+            .iter()
+            .rev()
+            .filter(|entry| matches!(entry.event, PollerEvent::Snapshot(_)))
+            .take(n)
+            .cloned()
+            .collect();
+        snapshots.reverse();
+        snapshots
+    }
+
+    /// Number of subscribers following the log.
+    #[must_use]
+    pub fn receiver_count(&self) -> usize {
+        self.events.receiver_count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::services::Notice;
+    use crate::application::use_cases::StreamGitHubActionsRunsUseCaseOutput;
+    use crate::test_support::base_time;
+    use std::sync::Arc;
+
+    fn snapshot() -> PollerEvent {
+        PollerEvent::Snapshot(Arc::new(StreamGitHubActionsRunsUseCaseOutput::new(
+            Vec::new(),
+            base_time(),
+        )))
+    }
+
+    fn notice(message: &str) -> PollerEvent {
+        PollerEvent::Notice(Arc::new(Notice::info(message)))
+    }
+
+    fn replayed(catch_up: CatchUp) -> Option<Vec<u64>> {
+        match catch_up {
+            CatchUp::Replay(events) => Some(events.iter().map(|event| event.seq).collect()),
+            CatchUp::Snapshot { .. } => None,
+        }
+    }
+
+    #[test]
+    fn test_resuming_from_the_last_event_replays_nothing() {
+        let log = EventLog::new(4);
+        log.record(snapshot());
+        let last = log.record(notice("hello"));
+
+        assert_eq!(replayed(log.attach(Some(last)).1), Some(Vec::new()));
+    }
+
+    #[test]
+    fn test_resuming_within_the_buffer_replays_the_missed_events() {
+        let log = EventLog::new(4);
+        for _ in 0..6 {
+            log.record(snapshot());
+        }
+
+        // Events 3 to 6 are buffered, so a client that saw event 2 misses nothing
+        assert_eq!(replayed(log.attach(Some(2)).1), Some(vec![3, 4, 5, 6]));
+        assert_eq!(replayed(log.attach(Some(4)).1), Some(vec![5, 6]));
+    }
+
+    #[tokio::test]
+    async fn test_resuming_from_an_evicted_or_unknown_event_resyncs() -> anyhow::Result<()> {
+        let log = EventLog::new(2);
+        log.record(snapshot());
+        log.record(snapshot());
+        log.record(notice("first"));
+        log.record(notice("second"));
+
+        for since in [1, 5] {
+            match log.attach(Some(since)).1 {
+                CatchUp::Snapshot {
+                    latest: Some(latest),
+                    resync: true,
+                } => assert_eq!(latest.seq, 2),
+                other => anyhow::bail!("expected a resync from {since}, got {other:?}"),
+            }
+        }
+        let (mut receiver, catch_up) = log.attach(None);
+        assert!(matches!(catch_up, CatchUp::Snapshot { resync: false, .. }));
+        log.record(notice("third"));
+        assert_eq!(receiver.recv().await?.seq, 5);
+        Ok(())
+    }
+}
@@ -0,0 +1,67 @@
+use crate::domain::repositories::{
+    CompactionReport, RetentionPolicy, RunRepository, RunRepositoryError,
+};
+use chrono::Utc;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// Interval between two scheduled compactions of the run store (seconds)
+const COMPACTION_INTERVAL_SECONDS: u64 = 3600;
+
+/// Enforces a [`RetentionPolicy`] on the run store, on a schedule or on demand.
+pub struct HistoryCompactor {
+    run_repository: Arc<dyn RunRepository + Send + Sync>,
+    policy: RetentionPolicy,
+}
+
+impl HistoryCompactor {
+    /// Compacts `run_repository` down to what `policy` retains.
+    #[must_use]
+    pub fn new(
+        run_repository: Arc<dyn RunRepository + Send + Sync>,
+        policy: RetentionPolicy,
+    ) -> Self {
+        Self {
+            run_repository,
+            policy,
+        }
+    }
+
+    /// Deletes the runs the policy no longer retains.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the run store fails.
+    pub async fn compact(&self) -> Result<CompactionReport, RunRepositoryError> {
+        let report = self
+            .run_repository
+            .compact(&self.policy, Utc::now())
+            .await?;
+        tracing::info!(
+            "Compacted run history: {} deleted, {} remaining",
+            report.deleted,
+            report.remaining
+        );
+        Ok(report)
+    }
+
+    /// Compacts right away and then every hour, passing each report to `on_compacted`.
+    pub fn spawn<F>(self: &Arc<Self>, on_compacted: F) -> JoinHandle<()>
+    where
+        F: Fn(&CompactionReport) + Send + 'static,
+    {
+        let compactor = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(COMPACTION_INTERVAL_SECONDS));
+            loop {
+                interval.tick().await;
+                match compactor.compact().await {
+                    Ok(report) => on_compacted(&report),
+                    Err(e) => tracing::warn!("Failed to compact run history: {}", e),
+                }
+            }
+        })
+    }
+}
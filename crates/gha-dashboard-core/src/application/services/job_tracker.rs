@@ -0,0 +1,300 @@
+use crate::domain::models::ids::{JobId, RunId};
+use crate::domain::models::job::Job;
+use crate::domain::models::run::WorkflowRun;
+use crate::domain::models::status::{RunConclusion, RunStatus};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, PoisonError};
+
+/// Jobs remembered, the least recently updated dropped first
+const TRACKED_JOBS: usize = 4096;
+
+#[derive(Debug, Default)]
+struct TrackedJobs {
+    jobs: HashMap<JobId, Job>,
+    /// Job IDs, least recently updated first
+    order: VecDeque<JobId>,
+}
+
+/// The latest state of jobs pushed by `workflow_job` webhooks, for views that would
+/// otherwise have to ask GitHub.
+#[derive(Debug, Default)]
+pub struct JobTracker {
+    tracked: Mutex<TrackedJobs>,
+}
+
+/// How far along a job is; a delivery never moves a job back.
+fn progress(job: &Job) -> (u8, bool) {
+    let status = match job.status {
+        RunStatus::Requested
+        | RunStatus::Pending
+        | RunStatus::Waiting
+        | RunStatus::Queued
+        | RunStatus::Unknown => 0,
+        RunStatus::InProgress => 1,
+        RunStatus::Completed => 2,
+    };
+    (status, job.completed_at.is_some())
+}
+
+impl JobTracker {
+    /// Remembers `job`, unless the job is already known to be further along.
+    ///
+    /// Returns whether it was remembered.
+    pub fn record(&self, job: Job) -> bool {
+        let mut tracked = self.tracked.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(known) = tracked.jobs.get(&job.id)
+            && progress(known) > progress(&job)
+        {
+            return false;
+        }
+        let id = job.id;
+        if tracked.jobs.insert(id, job).is_some() {
+            tracked.order.retain(|tracked| *tracked != id);
+        }
+        tracked.order.push_back(id);
+        if tracked.order.len() > TRACKED_JOBS
+            && let Some(oldest) = tracked.order.pop_front()
+        {
+            tracked.jobs.remove(&oldest);
+        }
+        true
+    }
+
+    /// The latest state of a job, if a webhook reported it.
+    #[must_use]
+    pub fn job(&self, job_id: JobId) -> Option<Job> {
+        self.tracked
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .jobs
+            .get(&job_id)
+            .cloned()
+    }
+
+    /// Jobs of a run reported by webhooks, in ID order.
+    #[must_use]
+    pub fn jobs_of(&self, run_id: RunId) -> Vec<Job> {
+        let mut jobs: Vec<Job> = self
+            .tracked
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .jobs
+            .values()
+            .filter(|job| job.run_id == run_id)
+            .cloned()
+            .collect();
+        jobs.sort_by_key(|job| job.id);
+        jobs
+    }
+
+    /// `fetched`, the jobs of a run as GitHub listed them, with the jobs webhooks reported
+    /// further along since, and those not listed yet appended in ID order.
+    #[must_use]
+    pub fn overlay(&self, run_id: RunId, mut fetched: Vec<Job>) -> Vec<Job> {
+        for pushed in self.jobs_of(run_id) {
+            match fetched.iter_mut().find(|job| job.id == pushed.id) {
+                Some(job) if progress(&pushed) > progress(job) => *job = pushed,
+                Some(_) => {}
+                None => fetched.push(pushed),
+            }
+        }
+        fetched
+    }
+
+    /// Names of the jobs of a run whose latest attempt ended in a problem, sorted.
+    ///
+    /// A job re-run under the same name replaces the earlier attempt.
+    #[must_use]
+    pub fn failed_jobs(&self, run_id: RunId) -> Vec<String> {
+        failed_jobs(self.jobs_of(run_id))
+    }
+
+    /// Page of the failed job of a run to look at first, see [`failed_job_url`].
+    #[must_use]
+    pub fn failed_job_url(&self, run_id: RunId) -> Option<String> {
+        failed_job_url(&failed_attempts(self.jobs_of(run_id)))
+    }
+
+    /// Sets the failed jobs, and the page of the first one, of each of `runs` the tracker
+    /// knows jobs of; other runs keep theirs, e.g. from the snapshot of another replica.
+    pub fn annotate(&self, runs: &mut [WorkflowRun]) {
+        for run in runs {
+            let jobs = self.jobs_of(run.id);
+            if !jobs.is_empty() {
+                let failed = failed_attempts(jobs);
+                run.failed_job_url = failed_job_url(&failed);
+                run.failed_jobs = failed_names(failed);
+            }
+        }
+    }
+}
+
+/// The latest attempt of each job of `jobs`, given in ID order, that ended in a problem.
+fn failed_attempts(jobs: Vec<Job>) -> Vec<Job> {
+    // In ID order, so later attempts replace earlier ones
+    let mut latest: HashMap<String, Job> = HashMap::new();
+    for job in jobs {
+        latest.insert(job.name.clone(), job);
+    }
+    latest
+        .into_values()
+        .filter(|job| job.conclusion.is_some_and(RunConclusion::is_problem))
+        .collect()
+}
+
+fn failed_names(failed: Vec<Job>) -> Vec<String> {
+    let mut names: Vec<String> = failed.into_iter().map(|job| job.name).collect();
+    names.sort();
+    names
+}
+
+fn failed_jobs(jobs: Vec<Job>) -> Vec<String> {
+    failed_names(failed_attempts(jobs))
+}
+
+/// Page of the job to look at first among `failed`: a `failure` before a cancelled or
+/// timed out job, which often only follow from it, then the one started earliest.
+///
+/// Jobs without a page are skipped.
+fn failed_job_url(failed: &[Job]) -> Option<String> {
+    failed
+        .iter()
+        .filter(|job| !job.html_url.is_empty())
+        .min_by_key(|job| {
+            (
+                job.conclusion != Some(RunConclusion::Failure),
+                job.started_at.is_none(),
+                job.started_at,
+                job.id,
+            )
+        })
+        .map(|job| job.html_url.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{base_time, workflow_run};
+    use chrono::TimeDelta;
+
+    fn job(id: u64, name: &str, conclusion: Option<RunConclusion>) -> Job {
+        Job {
+            id: JobId(id),
+            run_id: RunId(1),
+            name: name.to_string(),
+            status: if conclusion.is_some() {
+                RunStatus::Completed
+            } else {
+                RunStatus::InProgress
+            },
+            conclusion,
+            started_at: Some(base_time()),
+            completed_at: conclusion.map(|_| base_time()),
+            runner_name: None,
+            labels: Vec::new(),
+            html_url: String::new(),
+            steps: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_late_deliveries_do_not_move_a_job_back() {
+        let tracker = JobTracker::default();
+
+        assert!(tracker.record(job(7, "build", Some(RunConclusion::Failure))));
+        assert!(!tracker.record(job(7, "build", None)));
+
+        assert_eq!(
+            tracker.job(JobId(7)).and_then(|job| job.conclusion),
+            Some(RunConclusion::Failure)
+        );
+    }
+
+    #[test]
+    fn test_failed_jobs_follow_the_latest_attempt() {
+        let tracker = JobTracker::default();
+        tracker.record(job(1, "build", Some(RunConclusion::Failure)));
+        tracker.record(job(2, "test", Some(RunConclusion::TimedOut)));
+        tracker.record(job(3, "lint", Some(RunConclusion::Success)));
+        let mut runs = vec![workflow_run("owner/repo", 1, "failure")];
+
+        tracker.annotate(&mut runs);
+        assert_eq!(runs[0].failed_jobs, ["build", "test"]);
+
+        // The re-run of build succeeds
+        tracker.record(job(4, "build", Some(RunConclusion::Success)));
+        tracker.annotate(&mut runs);
+        assert_eq!(runs[0].failed_jobs, ["test"]);
+        assert_eq!(tracker.jobs_of(RunId(1)).len(), 4);
+        assert!(tracker.jobs_of(RunId(2)).is_empty());
+    }
+
+    #[test]
+    fn test_failed_job_url_prefers_failures_started_first() {
+        let failed = |id: u64, conclusion, started_minutes: Option<i64>| Job {
+            started_at: started_minutes.map(|minutes| base_time() + TimeDelta::minutes(minutes)),
+            html_url: format!("https://github.com/owner/repo/actions/runs/1/job/{id}"),
+            ..job(id, &format!("job {id}"), Some(conclusion))
+        };
+        let url = |jobs: &[Job]| {
+            failed_job_url(jobs).map(|url| url.rsplit('/').next().unwrap_or_default().to_string())
+        };
+
+        // A failure wins over a cancellation or time out started before it
+        let jobs = [
+            failed(1, RunConclusion::Cancelled, Some(0)),
+            failed(2, RunConclusion::TimedOut, Some(1)),
+            failed(3, RunConclusion::Failure, Some(5)),
+            failed(4, RunConclusion::Failure, Some(2)),
+        ];
+        assert_eq!(url(&jobs).as_deref(), Some("4"));
+        // Among cancellations and time outs, the earliest started wins
+        assert_eq!(url(&jobs[..2]).as_deref(), Some("1"));
+        // Jobs that never started come last
+        let jobs = [
+            failed(5, RunConclusion::Failure, None),
+            failed(6, RunConclusion::Failure, Some(9)),
+        ];
+        assert_eq!(url(&jobs).as_deref(), Some("6"));
+        assert_eq!(url(&jobs[..1]).as_deref(), Some("5"));
+        // Jobs without a page are skipped
+        let mut no_page = failed(7, RunConclusion::Failure, Some(0));
+        no_page.html_url = String::new();
+        assert_eq!(url(&[no_page.clone()]), None);
+        assert_eq!(url(&[]), None);
+    }
+
+    #[test]
+    fn test_annotated_runs_link_to_their_first_failed_job() {
+        let tracker = JobTracker::default();
+        let mut runs = vec![workflow_run("owner/repo", 1, "failure")];
+        tracker.annotate(&mut runs);
+        assert_eq!(runs[0].failed_job_url, None);
+
+        let mut build = job(1, "build", Some(RunConclusion::Failure));
+        build.html_url = "https://github.com/owner/repo/actions/runs/1/job/1".to_string();
+        tracker.record(build.clone());
+        tracker.annotate(&mut runs);
+        assert_eq!(runs[0].failed_job_url, Some(build.html_url.clone()));
+        assert_eq!(tracker.failed_job_url(RunId(1)), Some(build.html_url));
+
+        // The re-run of build succeeds
+        tracker.record(job(2, "build", Some(RunConclusion::Success)));
+        tracker.annotate(&mut runs);
+        assert_eq!(runs[0].failed_job_url, None);
+    }
+
+    #[test]
+    fn test_overlay_updates_and_appends_fetched_jobs() {
+        let tracker = JobTracker::default();
+        tracker.record(job(1, "build", Some(RunConclusion::Success)));
+        tracker.record(job(3, "deploy", None));
+        let fetched = vec![job(1, "build", None), job(2, "test", None)];
+
+        let jobs = tracker.overlay(RunId(1), fetched);
+
+        let ids: Vec<_> = jobs.iter().map(|job| job.id.0).collect();
+        assert_eq!(ids, [1, 2, 3]);
+        assert_eq!(jobs[0].conclusion, Some(RunConclusion::Success));
+    }
+}
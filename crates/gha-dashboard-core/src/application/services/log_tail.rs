@@ -0,0 +1,308 @@
+use super::job_tracker::JobTracker;
+use crate::domain::external_apis::github::{GitHubApi, GitHubApiError};
+use crate::domain::models::ids::{JobId, RepoFullName};
+use crate::domain::models::status::RunStatus;
+use futures_util::Stream;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::MissedTickBehavior;
+
+/// Bytes of log sent in one `log_chunk` frame unless configured otherwise
+pub const DEFAULT_LOG_TAIL_CHUNK_BYTES: usize = 16 * 1024;
+
+/// Bytes of log sent for one tail unless configured otherwise
+pub const DEFAULT_LOG_TAIL_MAX_BYTES: usize = 1024 * 1024;
+
+/// Interval between two downloads of the log of a running job
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Bounds on what a log tail sends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogTailLimits {
+    /// Largest `data` of a `log_chunk` frame, in bytes
+    pub chunk_bytes: usize,
+    /// Bytes of log after which the tail ends, whether or not the job has completed
+    pub max_bytes: usize,
+    /// Interval between two downloads while the job runs
+    pub poll_interval: Duration,
+}
+
+impl Default for LogTailLimits {
+    fn default() -> Self {
+        Self {
+            chunk_bytes: DEFAULT_LOG_TAIL_CHUNK_BYTES,
+            max_bytes: DEFAULT_LOG_TAIL_MAX_BYTES,
+            poll_interval: POLL_INTERVAL,
+        }
+    }
+}
+
+/// Why a log tail ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogEndReason {
+    /// The job completed and its whole log was sent
+    Completed,
+    /// [`LogTailLimits::max_bytes`] were sent
+    BudgetExhausted,
+    /// GitHub failed to give the job or its log
+    Error,
+}
+
+/// A frame of a log tail, sent to websocket clients as is.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LogTailFrame {
+    /// Log of the job from byte `offset` on
+    LogChunk {
+        /// The job the log belongs to
+        #[serde(rename = "jobId")]
+        job_id: JobId,
+        /// Offset of `data` in the log, in bytes
+        offset: usize,
+        /// Log text, cut between characters
+        data: String,
+    },
+    /// Last frame of the tail
+    LogEnd {
+        /// The job the log belongs to
+        #[serde(rename = "jobId")]
+        job_id: JobId,
+        /// Why no more chunks follow
+        reason: LogEndReason,
+        /// What went wrong, for [`LogEndReason::Error`]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
+}
+
+/// Follows the log of job `job_id` of `repo` until the job completes.
+///
+/// The log is downloaded again every [`LogTailLimits::poll_interval`] while the job runs, and
+/// only once for a completed job; what was not sent yet goes out in chunks of at most
+/// [`LogTailLimits::chunk_bytes`], cut between characters. The stream ends after a `log_end`
+/// frame; dropping it stops the polling.
+///
+/// GitHub is not asked whether the job completed once a webhook said so through `jobs`.
+pub fn tail_job_logs(
+    github_api: Arc<dyn GitHubApi + Send + Sync>,
+    jobs: Arc<JobTracker>,
+    repo: RepoFullName,
+    job_id: JobId,
+    limits: LogTailLimits,
+) -> impl Stream<Item = LogTailFrame> + Send + 'static {
+    let end = move |reason, message| LogTailFrame::LogEnd {
+        job_id,
+        reason,
+        message,
+    };
+    async_stream::stream! {
+        let mut offset = 0;
+        let mut interval = tokio::time::interval(limits.poll_interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            // The status is read first, so the log of a completed job is downloaded whole
+            let pushed = jobs.job(job_id).is_some_and(|job| job.status == RunStatus::Completed);
+            let completed = if pushed {
+                true
+            } else {
+                match github_api.fetch_workflow_job(&repo, job_id).await {
+                    Ok(job) => job.status == RunStatus::Completed,
+                    Err(e) => {
+                        yield end(LogEndReason::Error, Some(e.to_string()));
+                        return;
+                    }
+                }
+            };
+            let logs = match github_api.fetch_job_logs(&repo, job_id).await {
+                Ok(logs) => logs,
+                // Nothing written yet
+                Err(GitHubApiError::NotFound { .. }) if !completed => continue,
+                Err(e) => {
+                    yield end(LogEndReason::Error, Some(e.to_string()));
+                    return;
+                }
+            };
+            // A log that no longer starts with what was sent has nothing new to send
+            let mut rest = logs.get(offset..).unwrap_or_default();
+            while !rest.is_empty() {
+                let budget = limits.max_bytes.saturating_sub(offset);
+                let len = match rest.floor_char_boundary(limits.chunk_bytes.min(budget)) {
+                    // A character wider than a chunk goes out whole
+                    0 => rest.ceil_char_boundary(1),
+                    len => len,
+                };
+                if len > budget {
+                    yield end(LogEndReason::BudgetExhausted, None);
+                    return;
+                }
+                let (chunk, tail) = rest.split_at(len);
+                yield LogTailFrame::LogChunk {
+                    job_id,
+                    offset,
+                    data: chunk.to_string(),
+                };
+                offset += len;
+                rest = tail;
+            }
+            if completed {
+                yield end(LogEndReason::Completed, None);
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::ids::RunId;
+    use crate::domain::models::job::Job;
+    use crate::domain::models::status::RunConclusion;
+    use crate::test_support::repo_full_name;
+    use crate::testing::ScriptedGitHubApi;
+    use futures_util::StreamExt;
+
+    fn job(status: RunStatus) -> Job {
+        Job {
+            id: JobId(7),
+            run_id: RunId(1),
+            name: "build".to_string(),
+            status,
+            conclusion: (status == RunStatus::Completed).then_some(RunConclusion::Success),
+            started_at: None,
+            completed_at: None,
+            runner_name: None,
+            labels: Vec::new(),
+            html_url: "https://github.com/owner/repo/actions/runs/1/job/7".to_string(),
+            steps: Vec::new(),
+        }
+    }
+
+    fn chunk(offset: usize, data: &str) -> LogTailFrame {
+        LogTailFrame::LogChunk {
+            job_id: JobId(7),
+            offset,
+            data: data.to_string(),
+        }
+    }
+
+    fn end(reason: LogEndReason) -> LogTailFrame {
+        LogTailFrame::LogEnd {
+            job_id: JobId(7),
+            reason,
+            message: None,
+        }
+    }
+
+    fn limits(chunk_bytes: usize, max_bytes: usize) -> LogTailLimits {
+        LogTailLimits {
+            chunk_bytes,
+            max_bytes,
+            poll_interval: Duration::from_millis(1),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_appended_log_is_sent_from_the_last_offset() -> anyhow::Result<()> {
+        let github_api = Arc::new(ScriptedGitHubApi::new());
+        github_api
+            .workflow_job(JobId(7), Ok(job(RunStatus::InProgress)))
+            .workflow_job(JobId(7), Ok(job(RunStatus::InProgress)))
+            .workflow_job(JobId(7), Ok(job(RunStatus::InProgress)))
+            .workflow_job(JobId(7), Ok(job(RunStatus::Completed)))
+            .job_logs(
+                JobId(7),
+                GitHubApiError::NotFound {
+                    resource: "logs".to_string(),
+                },
+            )
+            .job_logs(JobId(7), Ok("line1\n".to_string()))
+            .job_logs(JobId(7), Ok("line1\nline2 xé\n".to_string()))
+            .job_logs(JobId(7), Ok("line1\nline2 xé\ndone\n".to_string()));
+
+        let frames: Vec<LogTailFrame> = tail_job_logs(
+            github_api,
+            Arc::default(),
+            repo_full_name("owner/repo"),
+            JobId(7),
+            limits(4, 1024),
+        )
+        .collect()
+        .await;
+
+        assert_eq!(
+            frames,
+            [
+                chunk(0, "line"),
+                chunk(4, "1\n"),
+                chunk(6, "line"),
+                chunk(10, "2 x"),
+                chunk(13, "é\n"),
+                chunk(16, "done"),
+                chunk(20, "\n"),
+                end(LogEndReason::Completed),
+            ]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_tail_ends_once_the_budget_is_spent() -> anyhow::Result<()> {
+        let github_api = Arc::new(ScriptedGitHubApi::new());
+        github_api
+            .workflow_job(JobId(7), Ok(job(RunStatus::Completed)))
+            .job_logs(JobId(7), Ok("0123456789".to_string()));
+
+        let frames: Vec<LogTailFrame> = tail_job_logs(
+            Arc::clone(&github_api) as _,
+            Arc::default(),
+            repo_full_name("owner/repo"),
+            JobId(7),
+            limits(4, 6),
+        )
+        .collect()
+        .await;
+
+        assert_eq!(
+            frames,
+            [
+                chunk(0, "0123"),
+                chunk(4, "45"),
+                end(LogEndReason::BudgetExhausted)
+            ]
+        );
+        assert_eq!(
+            github_api.calls(),
+            [
+                "fetch_workflow_job owner/repo 7",
+                "fetch_job_logs owner/repo 7"
+            ]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_job_completed_by_a_webhook_is_not_asked_about() -> anyhow::Result<()> {
+        let github_api = Arc::new(ScriptedGitHubApi::new());
+        github_api.job_logs(JobId(7), Ok("done\n".to_string()));
+        let jobs = Arc::new(JobTracker::default());
+        jobs.record(job(RunStatus::Completed));
+
+        let frames: Vec<LogTailFrame> = tail_job_logs(
+            Arc::clone(&github_api) as _,
+            jobs,
+            repo_full_name("owner/repo"),
+            JobId(7),
+            limits(1024, 1024),
+        )
+        .collect()
+        .await;
+
+        assert_eq!(frames, [chunk(0, "done\n"), end(LogEndReason::Completed)]);
+        assert_eq!(github_api.calls(), ["fetch_job_logs owner/repo 7"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,122 @@
+use crate::application::use_cases::stream_github_actions_runs::config::{
+    API_CALL_BUDGET_PER_HOUR, StreamConfig,
+};
+use crate::domain::models::ids::{RepoFullName, RunId};
+use std::collections::BTreeMap;
+use std::sync::{Mutex, PoisonError};
+
+/// A run a client keeps an eye on, however old it gets.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PinnedRun {
+    /// Repository the run belongs to
+    pub repo: RepoFullName,
+    /// ID of the run within GitHub
+    pub run_id: RunId,
+}
+
+/// Runs pinned by any client, fetched one by one on every iteration of the polling loop
+/// when they are not among the latest runs of their repository.
+///
+/// Pins are counted, so a run stays pinned until every client that pinned it unpinned it.
+#[derive(Debug, Default)]
+pub struct PinnedRuns {
+    pins: Mutex<BTreeMap<PinnedRun, usize>>,
+}
+
+impl PinnedRuns {
+    /// Pins `run` once more, unless it is not pinned yet and fetching one more run would
+    /// take the polling loop over [`API_CALL_BUDGET_PER_HOUR`] with `config`.
+    ///
+    /// `config` must be the one in effect until the pin is taken, which
+    /// [`SharedPoller::pin_run`](super::SharedPoller::pin_run) ensures.
+    ///
+    /// # Errors
+    ///
+    /// Returns the GitHub API calls per hour the polling loop would make with `run` pinned.
+    pub(crate) fn pin(&self, run: PinnedRun, config: &StreamConfig) -> Result<(), u64> {
+        let mut pins = self.pins.lock().unwrap_or_else(PoisonError::into_inner);
+        if !pins.contains_key(&run) {
+            let api_calls_per_hour = config.estimated_api_calls_per_hour_with_pins(pins.len() + 1);
+            if api_calls_per_hour > API_CALL_BUDGET_PER_HOUR {
+                return Err(api_calls_per_hour);
+            }
+        }
+        *pins.entry(run).or_default() += 1;
+        Ok(())
+    }
+
+    /// Takes back one pin of `run`; no-op for runs that are not pinned.
+    pub fn unpin(&self, run: &PinnedRun) {
+        let mut pins = self.pins.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(count) = pins.get_mut(run) {
+            *count -= 1;
+            if *count == 0 {
+                pins.remove(run);
+            }
+        }
+    }
+
+    /// Number of distinct pinned runs, each fetched on its own by the polling loop.
+    #[must_use]
+    pub fn count(&self) -> usize {
+        self.pins
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    /// Every pinned run, once however many clients pinned it.
+    #[must_use]
+    pub fn runs(&self) -> Vec<PinnedRun> {
+        self.pins
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .keys()
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::repo_full_name;
+
+    #[test]
+    fn test_runs_stay_pinned_until_every_pin_is_taken_back() {
+        let pins = PinnedRuns::default();
+        let run = PinnedRun {
+            repo: repo_full_name("owner/repo"),
+            run_id: RunId(1),
+        };
+
+        let config = StreamConfig::default();
+
+        assert_eq!(pins.pin(run.clone(), &config), Ok(()));
+        assert_eq!(pins.pin(run.clone(), &config), Ok(()));
+        pins.unpin(&run);
+        assert_eq!(pins.runs(), std::slice::from_ref(&run));
+
+        pins.unpin(&run);
+        pins.unpin(&run);
+        assert!(pins.runs().is_empty());
+    }
+
+    #[test]
+    fn test_runs_beyond_the_api_call_budget_are_not_pinned() {
+        let pins = PinnedRuns::default();
+        let config = StreamConfig::default();
+        let run = |id| PinnedRun {
+            repo: repo_full_name("owner/repo"),
+            run_id: RunId(id),
+        };
+        for id in 0..22 {
+            assert_eq!(pins.pin(run(id), &config), Ok(()));
+        }
+
+        assert_eq!(pins.pin(run(22), &config), Err(4_025));
+        // Pinning a run again fetches nothing more
+        assert_eq!(pins.pin(run(0), &config), Ok(()));
+        assert_eq!(pins.count(), 22);
+    }
+}
@@ -0,0 +1,159 @@
+use crate::application::use_cases::stream_github_actions_runs::{
+    StreamGitHubActionsRunsError, StreamGitHubActionsRunsUseCaseOutput,
+};
+use crate::domain::external_apis::github::calls::ApiCallCounts;
+use crate::domain::models::ids::RepoFullName;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+/// Longest error message kept in the status (characters)
+const MAX_ERROR_MESSAGE_CHARS: usize = 300;
+
+/// What the polling loop is doing.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum PollerState {
+    /// Polling on schedule
+    #[default]
+    Running,
+    /// Waiting to restart after an error or a panic, until the next snapshot
+    BackingOff,
+}
+
+/// An error seen by the polling loop.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PollerError {
+    /// e.g. `rate_limited`, as the `kind` of a GitHub API error
+    pub kind: Cow<'static, str>,
+    /// Truncated to a few hundred characters
+    pub message: String,
+    /// When the error was seen
+    #[serde(rename = "occurredAt")]
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl PollerError {
+    fn new(kind: &'static str, message: &str, occurred_at: DateTime<Utc>) -> Self {
+        let mut chars = message.chars();
+        let mut message: String = chars.by_ref().take(MAX_ERROR_MESSAGE_CHARS).collect();
+        if chars.next().is_some() {
+            message.push('…');
+        }
+        Self {
+            kind: Cow::Borrowed(kind),
+            message,
+            occurred_at,
+        }
+    }
+}
+
+/// The outcome of the last fetches of one polled repository.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RepositoryStatus {
+    /// The polled repository
+    pub repository: RepoFullName,
+    /// When the runs of the repository were last fetched
+    #[serde(rename = "lastSuccessAt")]
+    pub last_success_at: Option<DateTime<Utc>>,
+    /// Cleared by the next successful fetch
+    #[serde(rename = "lastError")]
+    pub last_error: Option<PollerError>,
+}
+
+/// Health of the polling loop, updated after each iteration.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct PollerStatus {
+    /// Whether the loop polls on schedule or waits to restart
+    pub state: PollerState,
+    /// When the last polled snapshot was generated
+    #[serde(rename = "lastSuccessAt")]
+    pub last_success_at: Option<DateTime<Utc>>,
+    /// How long fetching the last polled snapshot took
+    #[serde(rename = "lastDurationMs")]
+    pub last_duration_ms: Option<u64>,
+    /// GitHub API requests made for the last polled snapshot
+    #[serde(rename = "lastGitHubCalls")]
+    pub last_github_calls: Option<ApiCallCounts>,
+    /// Kept after later successes, so a past failure can still be looked up
+    #[serde(rename = "lastError")]
+    pub last_error: Option<PollerError>,
+    /// Repositories of the last snapshot, plus any that failed since
+    pub repositories: Vec<RepositoryStatus>,
+}
+
+impl PollerStatus {
+    /// Records a polled snapshot: every repository in it was fetched successfully.
+    pub fn record_success(&mut self, output: &StreamGitHubActionsRunsUseCaseOutput) {
+        self.state = PollerState::Running;
+        self.last_success_at = Some(output.generated_at);
+        self.last_duration_ms = output
+            .fetch_duration
+            .map(|duration| u64::try_from(duration.as_millis()).unwrap_or(u64::MAX));
+        self.last_github_calls.clone_from(&output.github_calls);
+        self.repositories = output
+            .repositories
+            .iter()
+            .map(|repository| RepositoryStatus {
+                repository: repository.full_name().clone(),
+                last_success_at: Some(output.generated_at),
+                last_error: None,
+            })
+            .collect();
+    }
+
+    /// Records an error ending the use case stream, and which repository it concerns if any.
+    pub fn record_error(&mut self, error: &StreamGitHubActionsRunsError, at: DateTime<Utc>) {
+        let (kind, repository) = match error {
+            StreamGitHubActionsRunsError::GitHubApi(source) => (source.kind(), None),
+            StreamGitHubActionsRunsError::Repository { repository, source } => {
+                (source.kind(), Some(repository))
+            }
+        };
+        let error = PollerError::new(kind, &error.to_string(), at);
+        if let Some(repository) = repository {
+            match self
+                .repositories
+                .iter_mut()
+                .find(|status| status.repository == *repository)
+            {
+                Some(status) => status.last_error = Some(error.clone()),
+                None => self.repositories.push(RepositoryStatus {
+                    repository: repository.clone(),
+                    last_success_at: None,
+                    last_error: Some(error.clone()),
+                }),
+            }
+        }
+        self.last_error = Some(error);
+    }
+
+    /// Records a panic of the polling loop.
+    pub fn record_panic(&mut self, message: &str, at: DateTime<Utc>) {
+        self.state = PollerState::BackingOff;
+        self.last_error = Some(PollerError::new("panic", message, at));
+    }
+
+    /// Marks the loop as waiting before it restarts.
+    pub fn back_off(&mut self) {
+        self.state = PollerState::BackingOff;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::base_time;
+
+    #[test]
+    fn test_long_error_messages_are_truncated() {
+        let error = PollerError::new("other", &"x".repeat(1000), base_time());
+
+        assert_eq!(error.message.chars().count(), MAX_ERROR_MESSAGE_CHARS + 1);
+        assert!(error.message.ends_with('…'));
+        assert_eq!(
+            PollerError::new("other", "short", base_time()).message,
+            "short"
+        );
+    }
+}
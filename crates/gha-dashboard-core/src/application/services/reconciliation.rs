@@ -0,0 +1,132 @@
+use crate::domain::models::ids::{RepoFullName, RunId};
+use crate::domain::models::run::WorkflowRun;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// A run shown with a different status than a later poll found.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct StatusMismatch {
+    /// The run that differs
+    #[serde(rename = "runId")]
+    pub run_id: RunId,
+    /// Repository of the run
+    pub repository: RepoFullName,
+    /// Display status the dashboard showed
+    pub shown: &'static str,
+    /// Display status the poll found
+    pub polled: &'static str,
+}
+
+/// Differences between the runs shown, as last updated by webhooks, and a fresh poll.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ReconciliationReport {
+    /// When the poll was compared
+    #[serde(rename = "checkedAt")]
+    pub checked_at: DateTime<Utc>,
+    /// Polled runs compared against the runs shown
+    #[serde(rename = "comparedRuns")]
+    pub compared_runs: usize,
+    /// Polled runs the dashboard did not show, e.g. because their webhook was lost
+    #[serde(rename = "missingRuns")]
+    pub missing_runs: Vec<RunId>,
+    /// Runs shown with another status than the poll found
+    #[serde(rename = "statusMismatches")]
+    pub status_mismatches: Vec<StatusMismatch>,
+}
+
+impl ReconciliationReport {
+    /// Compares the runs `shown` with the runs `polled` at `checked_at`.
+    ///
+    /// A run shown with a newer `updated_at` than the poll is not a mismatch: the poll
+    /// raced a webhook and the shown run is the current one.
+    #[must_use]
+    pub fn compare(
+        shown: &[WorkflowRun],
+        polled: &[WorkflowRun],
+        checked_at: DateTime<Utc>,
+    ) -> Self {
+        let shown: HashMap<RunId, &WorkflowRun> = shown.iter().map(|run| (run.id, run)).collect();
+        let mut missing_runs = Vec::new();
+        let mut status_mismatches = Vec::new();
+        for run in polled {
+            match shown.get(&run.id) {
+                None => missing_runs.push(run.id),
+                Some(current)
+                    if current.updated_at <= run.updated_at
+                        && (current.status, current.conclusion) != (run.status, run.conclusion) =>
+                {
+                    status_mismatches.push(StatusMismatch {
+                        run_id: run.id,
+                        repository: run.repository_name.clone(),
+                        shown: current.display_status(),
+                        polled: run.display_status(),
+                    });
+                }
+                Some(_) => {}
+            }
+        }
+        Self {
+            checked_at,
+            compared_runs: polled.len(),
+            missing_runs,
+            status_mismatches,
+        }
+    }
+
+    /// Whether the runs shown agreed with the poll.
+    #[must_use]
+    pub fn is_clean(&self) -> bool {
+        self.missing_runs.is_empty() && self.status_mismatches.is_empty()
+    }
+}
+
+/// Replaces each polled run with the shown one when a webhook updated it after the poll,
+/// so a poll racing a webhook never regresses a run's status.
+pub fn keep_newer_shown_runs(polled: &mut [WorkflowRun], shown: &[WorkflowRun]) {
+    let shown: HashMap<RunId, &WorkflowRun> = shown.iter().map(|run| (run.id, run)).collect();
+    for run in polled {
+        if let Some(current) = shown.get(&run.id)
+            && current.updated_at > run.updated_at
+        {
+            run.clone_from(current);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{repo_full_name, workflow_run};
+    use chrono::TimeDelta;
+
+    #[test]
+    fn test_compare_reports_missing_runs_and_stale_statuses() {
+        let shown_running = workflow_run("owner/repo", 1, "in_progress");
+        let mut raced = workflow_run("owner/repo", 2, "in_progress");
+        let shown = vec![
+            shown_running.clone(),
+            workflow_run("owner/repo", 2, "success"),
+        ];
+        let mut finished = workflow_run("owner/repo", 1, "failure");
+        finished.updated_at = shown_running.updated_at + TimeDelta::minutes(5);
+        // The poll saw run 2 before the webhook that completed it
+        raced.updated_at = shown[1].updated_at - TimeDelta::minutes(1);
+        let polled = vec![finished, raced, workflow_run("owner/repo", 3, "queued")];
+
+        let report = ReconciliationReport::compare(&shown, &polled, shown_running.created_at);
+
+        assert_eq!(report.compared_runs, 3);
+        assert_eq!(report.missing_runs, vec![RunId(3)]);
+        assert_eq!(
+            report.status_mismatches,
+            vec![StatusMismatch {
+                run_id: RunId(1),
+                repository: repo_full_name("owner/repo"),
+                shown: "in_progress",
+                polled: "failure",
+            }]
+        );
+        assert!(!report.is_clean());
+    }
+}
@@ -0,0 +1,359 @@
+use crate::domain::external_apis::github::{GitHubApi, GitHubApiError, Repository};
+use crate::domain::models::ids::RepoFullName;
+use crate::domain::models::repository_change::RepositoryChange;
+use crate::domain::repositories::DiscoveredRepositoryRepository;
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+#[derive(Debug, Default)]
+struct DiscoveryState {
+    /// Every repository seen; `None` until the store was read
+    seen: Option<HashSet<RepoFullName>>,
+    /// The repositories of the last listing, checked again when they drop out of it
+    listed: Vec<RepoFullName>,
+    /// Polled with auto-monitoring; empty until the first listing
+    monitored: Vec<Repository>,
+}
+
+/// What a listing of the repositories the token can see revealed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Discovery {
+    /// The repositories to poll
+    pub repositories: Vec<Repository>,
+    /// Repositories that appeared or went away since the previous listing
+    pub changes: Vec<RepositoryChange>,
+}
+
+/// Tracks the repositories listed across iterations of the polling loop, telling which are
+/// new and which were deleted or renamed.
+///
+/// Polls whatever was listed unless auto-monitoring is on. Then the polled repositories only
+/// change when a repository appears, taking the place of the least recently pushed one once
+/// `maxRepositories` are polled, or when one is deleted or renamed.
+#[derive(Default)]
+pub struct RepositoryDiscovery {
+    auto_monitor: bool,
+    store: Option<Arc<dyn DiscoveredRepositoryRepository + Send + Sync>>,
+    state: Mutex<DiscoveryState>,
+}
+
+impl fmt::Debug for RepositoryDiscovery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RepositoryDiscovery")
+            .field("auto_monitor", &self.auto_monitor)
+            .field("store", &self.store.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl RepositoryDiscovery {
+    /// Adds new repositories to the polled ones when `auto_monitor` is set.
+    #[must_use]
+    pub fn new(auto_monitor: bool) -> Self {
+        Self {
+            auto_monitor,
+            ..Self::default()
+        }
+    }
+
+    /// Remembers the repositories seen in `store`, so the first listing after a restart still
+    /// reports the repositories created meanwhile.
+    #[must_use]
+    pub fn with_store(
+        mut self,
+        store: Arc<dyn DiscoveredRepositoryRepository + Send + Sync>,
+    ) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Compares `listed` with the repositories seen before, asking `github_api` about the
+    /// ones that dropped out of the listing since the previous call.
+    ///
+    /// The first listing ever only records what it saw, so an empty store does not announce
+    /// every repository at once.
+    pub async fn observe<G: GitHubApi + Send + Sync + ?Sized>(
+        &self,
+        github_api: &G,
+        listed: Vec<Repository>,
+        max_repositories: u8,
+    ) -> Discovery {
+        let mut state = self.state.lock().await;
+        let seen = match state.seen.take() {
+            Some(seen) => seen,
+            None => self.load().await,
+        };
+        let first = seen.is_empty();
+        let mut seen = seen;
+        let mut changes = Vec::new();
+        let names: HashSet<&RepoFullName> = listed.iter().map(Repository::full_name).collect();
+
+        // Repositories that only fell behind more recently pushed ones are left alone
+        let mut unverified = Vec::new();
+        let dropped: Vec<RepoFullName> = state
+            .listed
+            .iter()
+            .filter(|repo| !names.contains(repo))
+            .cloned()
+            .collect();
+        for repo in dropped {
+            match github_api.fetch_repository(&repo).await {
+                Ok(found) if found.full_name() == &repo => {}
+                Ok(found) => {
+                    self.forget(&mut seen, &repo).await;
+                    self.remember(&mut seen, found.full_name()).await;
+                    for monitored in &mut state.monitored {
+                        if monitored.full_name() == &repo {
+                            *monitored = found.clone();
+                        }
+                    }
+                    changes.push(RepositoryChange::Renamed {
+                        from: repo,
+                        to: found.full_name().clone(),
+                    });
+                }
+                Err(GitHubApiError::NotFound { .. }) => {
+                    self.forget(&mut seen, &repo).await;
+                    state
+                        .monitored
+                        .retain(|monitored| monitored.full_name() != &repo);
+                    changes.push(RepositoryChange::Removed { repository: repo });
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to check repository {} that is no longer listed: {}",
+                        repo,
+                        e
+                    );
+                    unverified.push(repo);
+                }
+            }
+        }
+
+        let mut discovered = Vec::new();
+        for repository in &listed {
+            if !seen.contains(repository.full_name()) {
+                self.remember(&mut seen, repository.full_name()).await;
+                if !first {
+                    discovered.push(repository.clone());
+                    changes.push(RepositoryChange::Discovered {
+                        repository: repository.full_name().clone(),
+                    });
+                }
+            }
+        }
+
+        state.seen = Some(seen);
+        state.listed = listed.iter().map(|repo| repo.full_name().clone()).collect();
+        state.listed.extend(unverified);
+        let repositories = if self.auto_monitor {
+            monitor(&mut state.monitored, listed, &discovered, max_repositories);
+            state.monitored.clone()
+        } else {
+            listed
+        };
+        Discovery {
+            repositories,
+            changes,
+        }
+    }
+
+    async fn load(&self) -> HashSet<RepoFullName> {
+        let Some(store) = &self.store else {
+            return HashSet::new();
+        };
+        match store.discovered_repositories().await {
+            Ok(repositories) => repositories.into_iter().collect(),
+            Err(e) => {
+                tracing::warn!("Failed to read the repositories seen before: {}", e);
+                HashSet::new()
+            }
+        }
+    }
+
+    async fn remember(&self, seen: &mut HashSet<RepoFullName>, repo: &RepoFullName) {
+        if !seen.insert(repo.clone()) {
+            return;
+        }
+        if let Some(store) = &self.store
+            && let Err(e) = store.save_discovered_repository(repo).await
+        {
+            tracing::warn!("Failed to store repository {} as seen: {}", repo, e);
+        }
+    }
+
+    async fn forget(&self, seen: &mut HashSet<RepoFullName>, repo: &RepoFullName) {
+        seen.remove(repo);
+        if let Some(store) = &self.store
+            && let Err(e) = store.remove_discovered_repository(repo).await
+        {
+            tracing::warn!("Failed to forget repository {}: {}", repo, e);
+        }
+    }
+}
+
+/// Updates the auto-monitored repositories with a listing: refreshes the ones listed, adds
+/// the `discovered` ones, tops them up with listed ones until `max_repositories` are
+/// monitored, then drops the least recently pushed beyond it.
+fn monitor(
+    monitored: &mut Vec<Repository>,
+    listed: Vec<Repository>,
+    discovered: &[Repository],
+    max_repositories: u8,
+) {
+    let max_repositories = usize::from(max_repositories);
+    for repository in &listed {
+        if let Some(known) = monitored
+            .iter_mut()
+            .find(|known| known.full_name() == repository.full_name())
+        {
+            known.clone_from(repository);
+        }
+    }
+    monitored.extend(discovered.iter().cloned());
+    for repository in listed {
+        if monitored.len() >= max_repositories {
+            break;
+        }
+        if monitored
+            .iter()
+            .all(|known| known.full_name() != repository.full_name())
+        {
+            monitored.push(repository);
+        }
+    }
+    // Unknown push times sort last, so they are dropped before any known one
+    monitored.sort_by_key(|repository| Reverse(repository.pushed_at));
+    monitored.truncate(max_repositories);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{base_time, repo_full_name};
+    use crate::testing::ScriptedGitHubApi;
+    use chrono::TimeDelta;
+
+    /// `owner/<name>`, last pushed `hours_ago`.
+    fn repository(name: &str, hours_ago: i64) -> Repository {
+        Repository::from_full_name(&repo_full_name(&format!("owner/{name}")))
+            .with_pushed_at(Some(base_time() - TimeDelta::hours(hours_ago)))
+    }
+
+    fn names(repositories: &[Repository]) -> Vec<String> {
+        repositories
+            .iter()
+            .map(|repository| repository.name().to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_new_deleted_and_renamed_repositories_are_reported() -> anyhow::Result<()> {
+        let github_api = ScriptedGitHubApi::new();
+        github_api
+            .repository(&repo_full_name("owner/a"), Ok(repository("a", 1)))
+            .repository(
+                &repo_full_name("owner/b"),
+                Err(GitHubApiError::NotFound {
+                    resource: "repository owner/b".to_string(),
+                }),
+            )
+            .repository(&repo_full_name("owner/c"), Ok(repository("renamed", 3)));
+        let discovery = RepositoryDiscovery::default();
+
+        let first = discovery
+            .observe(
+                &github_api,
+                vec![repository("a", 1), repository("b", 2), repository("c", 3)],
+                5,
+            )
+            .await;
+        // `a` merely fell behind other pushes
+        let second = discovery
+            .observe(
+                &github_api,
+                vec![repository("new", 0), repository("renamed", 3)],
+                5,
+            )
+            .await;
+
+        assert!(first.changes.is_empty());
+        assert_eq!(names(&first.repositories), ["a", "b", "c"]);
+        assert_eq!(
+            second.changes,
+            vec![
+                RepositoryChange::Removed {
+                    repository: repo_full_name("owner/b"),
+                },
+                RepositoryChange::Renamed {
+                    from: repo_full_name("owner/c"),
+                    to: repo_full_name("owner/renamed"),
+                },
+                RepositoryChange::Discovered {
+                    repository: repo_full_name("owner/new"),
+                },
+            ]
+        );
+        assert_eq!(
+            second.changes[2].to_string(),
+            "new repository detected: owner/new"
+        );
+        assert_eq!(names(&second.repositories), ["new", "renamed"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_auto_monitoring_evicts_the_least_recently_pushed() -> anyhow::Result<()> {
+        let github_api = ScriptedGitHubApi::new();
+        let discovery = RepositoryDiscovery::new(true);
+
+        // Listings are sorted by push, most recent first
+        discovery
+            .observe(
+                &github_api,
+                vec![
+                    repository("a", 1),
+                    repository("c", 3),
+                    repository("b", 5),
+                    repository("d", 7),
+                ],
+                3,
+            )
+            .await;
+        // A known repository pushed since does not displace a monitored one
+        let known = discovery
+            .observe(
+                &github_api,
+                vec![repository("d", 0), repository("a", 1), repository("c", 3)],
+                3,
+            )
+            .await;
+        let first_new = discovery
+            .observe(
+                &github_api,
+                vec![repository("new", 0), repository("a", 1), repository("c", 3)],
+                3,
+            )
+            .await;
+        let second_new = discovery
+            .observe(
+                &github_api,
+                vec![
+                    repository("newer", 0),
+                    repository("new", 0),
+                    repository("a", 2),
+                ],
+                3,
+            )
+            .await;
+
+        assert!(known.changes.is_empty());
+        assert_eq!(names(&known.repositories), ["a", "c", "b"]);
+        assert_eq!(names(&first_new.repositories), ["new", "a", "c"]);
+        assert_eq!(names(&second_new.repositories), ["new", "newer", "a"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,160 @@
+use crate::domain::external_apis::github::{GitHubApi, GitHubApiError};
+use crate::domain::models::ids::RepoFullName;
+use chrono::{DateTime, TimeDelta, Utc};
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+
+/// How long the required checks of a branch are reused; branch protection rarely changes (seconds)
+const REQUIRED_CHECKS_TTL_SECONDS: i64 = 3600;
+
+#[derive(Debug, Clone)]
+struct Entry {
+    /// `None` when the token may not read the branch protection
+    checks: Option<Vec<String>>,
+    fetched_at: DateTime<Utc>,
+}
+
+/// Required status checks per repository branch, fetched at most once an hour.
+#[derive(Debug, Default)]
+pub struct RequiredChecksCache {
+    entries: RwLock<HashMap<(RepoFullName, String), Entry>>,
+}
+
+impl RequiredChecksCache {
+    /// The names of the checks required to merge into `branch` of `repo`, empty for an
+    /// unprotected branch, or `None` when they cannot be known.
+    ///
+    /// A token without admin access to `repo` is not asked again until the entry expires; other
+    /// failures fall back to the last known checks and are retried on the next call.
+    pub async fn get<G: GitHubApi + Send + Sync + ?Sized>(
+        &self,
+        github_api: &G,
+        repo: &RepoFullName,
+        branch: &str,
+        now: DateTime<Utc>,
+    ) -> Option<Vec<String>> {
+        let key = (repo.clone(), branch.to_string());
+        let cached = self.entries.read().await.get(&key).cloned();
+        if let Some(entry) = &cached
+            && now - entry.fetched_at < TimeDelta::seconds(REQUIRED_CHECKS_TTL_SECONDS)
+        {
+            return entry.checks.clone();
+        }
+
+        let checks = match github_api
+            .fetch_branch_protection_required_checks(repo, branch)
+            .await
+        {
+            Ok(checks) => Some(checks),
+            Err(GitHubApiError::NotFound { .. }) => Some(Vec::new()),
+            Err(e @ (GitHubApiError::Forbidden { .. } | GitHubApiError::Unauthorized { .. })) => {
+                tracing::info!("{}, not marking required checks of {}", e, repo);
+                None
+            }
+            Err(e) => {
+                tracing::warn!("Failed to fetch required checks of {}: {}", repo, e);
+                return cached.and_then(|entry| entry.checks);
+            }
+        };
+        self.entries.write().await.insert(
+            key,
+            Entry {
+                checks: checks.clone(),
+                fetched_at: now,
+            },
+        );
+        checks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{base_time, repo_full_name};
+    use crate::testing::ScriptedGitHubApi;
+
+    #[tokio::test]
+    async fn test_required_checks_are_cached_per_branch() {
+        let (protected, unprotected, private) = (
+            repo_full_name("owner/protected"),
+            repo_full_name("owner/unprotected"),
+            repo_full_name("owner/private"),
+        );
+        let github_api = ScriptedGitHubApi::new();
+        github_api
+            .required_checks(&protected, "main", Ok(vec!["CI".to_string()]))
+            .required_checks(
+                &unprotected,
+                "main",
+                GitHubApiError::NotFound {
+                    resource: "required checks of owner/unprotected branch main".to_string(),
+                },
+            )
+            .required_checks(
+                &private,
+                "main",
+                GitHubApiError::Forbidden {
+                    resource: "required checks of owner/private branch main".to_string(),
+                    message: "Resource not accessible by integration".to_string(),
+                },
+            );
+        let cache = RequiredChecksCache::default();
+
+        for _ in 0..2 {
+            assert_eq!(
+                cache
+                    .get(&github_api, &protected, "main", base_time())
+                    .await,
+                Some(vec!["CI".to_string()])
+            );
+            assert_eq!(
+                cache
+                    .get(&github_api, &unprotected, "main", base_time())
+                    .await,
+                Some(Vec::new())
+            );
+            assert_eq!(
+                cache.get(&github_api, &private, "main", base_time()).await,
+                None
+            );
+        }
+        let expired = base_time() + TimeDelta::seconds(REQUIRED_CHECKS_TTL_SECONDS);
+        cache.get(&github_api, &protected, "main", expired).await;
+
+        assert_eq!(
+            github_api.calls(),
+            [
+                "fetch_branch_protection_required_checks owner/protected main",
+                "fetch_branch_protection_required_checks owner/unprotected main",
+                "fetch_branch_protection_required_checks owner/private main",
+                "fetch_branch_protection_required_checks owner/protected main",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_network_failures_keep_the_last_known_checks() {
+        let repo = repo_full_name("owner/repo");
+        let github_api = ScriptedGitHubApi::new();
+        github_api
+            .required_checks(&repo, "main", Ok(vec!["CI".to_string()]))
+            .required_checks(
+                &repo,
+                "main",
+                GitHubApiError::Network {
+                    resource: "required checks of owner/repo branch main".to_string(),
+                    message: "timed out".to_string(),
+                },
+            );
+        let cache = RequiredChecksCache::default();
+        let expired = base_time() + TimeDelta::seconds(REQUIRED_CHECKS_TTL_SECONDS);
+
+        cache.get(&github_api, &repo, "main", base_time()).await;
+        let stale = cache.get(&github_api, &repo, "main", expired).await;
+        let retried = cache.get(&github_api, &repo, "main", expired).await;
+
+        assert_eq!(stale, Some(vec!["CI".to_string()]));
+        assert_eq!(retried, stale);
+        assert_eq!(github_api.calls().len(), 3);
+    }
+}
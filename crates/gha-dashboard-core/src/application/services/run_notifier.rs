@@ -0,0 +1,326 @@
+use crate::domain::external_apis::notifications::{NotificationError, NotificationPort};
+use crate::domain::models::ids::{RepoFullName, WorkflowId};
+use crate::domain::models::repository_change::RepositoryChange;
+use crate::domain::models::transition::RunTransition;
+use futures_util::future::join_all;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::future::Future;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+/// Attempts per notification, including the first
+const MAX_ATTEMPTS: u32 = 3;
+
+/// Wait before the first retry, doubled for each further one (milliseconds)
+const RETRY_DELAY_MILLIS: u64 = 1000;
+
+/// Which transitions a target is told about.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct NotificationFilter {
+    /// Only runs that ended in a state needing attention
+    pub failures_only: bool,
+    /// Empty means every repository
+    pub repos: Vec<RepoFullName>,
+    /// Empty means every branch
+    pub branches: Vec<String>,
+    /// Minimum time between two notifications about the same workflow
+    pub cooldown: Duration,
+}
+
+impl NotificationFilter {
+    fn matches(&self, transition: &RunTransition) -> bool {
+        let run = &transition.run;
+        (!self.failures_only || transition.is_failure())
+            && (self.repos.is_empty() || self.repos.contains(&run.repository_name))
+            && (self.branches.is_empty()
+                || run
+                    .head_branch
+                    .as_ref()
+                    .is_some_and(|branch| self.branches.contains(branch)))
+    }
+}
+
+struct NotificationTarget {
+    port: Arc<dyn NotificationPort + Send + Sync>,
+    filter: NotificationFilter,
+}
+
+/// Sends run transitions to every target whose filter they pass, in the background.
+pub struct RunNotifier {
+    targets: Vec<NotificationTarget>,
+    /// When each target last notified about a workflow
+    notified_at: Mutex<HashMap<(usize, RepoFullName, WorkflowId), Instant>>,
+    retry_delay: Duration,
+}
+
+impl Default for RunNotifier {
+    fn default() -> Self {
+        Self {
+            targets: Vec::new(),
+            notified_at: Mutex::default(),
+            retry_delay: Duration::from_millis(RETRY_DELAY_MILLIS),
+        }
+    }
+}
+
+impl RunNotifier {
+    /// Adds a target told about the transitions passing `filter`.
+    #[must_use]
+    pub fn with_target(
+        mut self,
+        port: Arc<dyn NotificationPort + Send + Sync>,
+        filter: NotificationFilter,
+    ) -> Self {
+        self.targets.push(NotificationTarget { port, filter });
+        self
+    }
+
+    /// Whether no target is configured, so transitions need not be detected.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.targets.is_empty()
+    }
+
+    /// Pairs each target with the transitions it should hear about at `now`, starting the
+    /// cooldown of every workflow notified.
+    fn select(
+        &self,
+        transitions: &[RunTransition],
+        now: Instant,
+    ) -> Vec<(Arc<dyn NotificationPort + Send + Sync>, RunTransition)> {
+        let mut notified_at = self
+            .notified_at
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let mut selected = Vec::new();
+        for (index, target) in self.targets.iter().enumerate() {
+            for transition in transitions {
+                if !target.filter.matches(transition) {
+                    continue;
+                }
+                let key = (
+                    index,
+                    transition.run.repository_name.clone(),
+                    transition.run.workflow_id,
+                );
+                if notified_at
+                    .get(&key)
+                    .is_some_and(|at| now.duration_since(*at) < target.filter.cooldown)
+                {
+                    tracing::debug!(
+                        "Suppressed notification for run {} during cooldown",
+                        transition.run.id
+                    );
+                    continue;
+                }
+                notified_at.insert(key, now);
+                selected.push((Arc::clone(&target.port), transition.clone()));
+            }
+        }
+        selected
+    }
+
+    /// Delivers `transitions` without waiting for the targets, retrying failed deliveries a
+    /// few times.
+    pub fn dispatch(&self, transitions: &[RunTransition]) -> JoinHandle<()> {
+        let selected = self.select(transitions, Instant::now());
+        let retry_delay = self.retry_delay;
+        tokio::spawn(async move {
+            join_all(
+                selected
+                    .iter()
+                    .map(|(port, transition)| deliver(port.as_ref(), transition, retry_delay)),
+            )
+            .await;
+        })
+    }
+
+    /// Delivers `changes` to every target without a repository filter, or whose filter lists
+    /// the repository, like [`Self::dispatch`]; failure and branch filters concern runs only.
+    pub fn dispatch_repository_changes(&self, changes: &[RepositoryChange]) -> JoinHandle<()> {
+        let selected: Vec<_> = self
+            .targets
+            .iter()
+            .flat_map(|target| {
+                changes
+                    .iter()
+                    .filter(|change| {
+                        target.filter.repos.is_empty()
+                            || target.filter.repos.contains(change.repository())
+                    })
+                    .map(|change| (Arc::clone(&target.port), change.clone()))
+            })
+            .collect();
+        let retry_delay = self.retry_delay;
+        tokio::spawn(async move {
+            join_all(selected.iter().map(|(port, change)| {
+                with_retries(|| port.notify_repository(change), change, retry_delay)
+            }))
+            .await;
+        })
+    }
+}
+
+async fn deliver(
+    port: &(dyn NotificationPort + Send + Sync),
+    transition: &RunTransition,
+    retry_delay: Duration,
+) {
+    with_retries(
+        || port.notify(transition),
+        format!("run {}", transition.run.id),
+        retry_delay,
+    )
+    .await;
+}
+
+/// Calls `send` until it succeeds, at most [`MAX_ATTEMPTS`] times while its errors are
+/// retryable; `subject` names what the notification is about in the logs.
+async fn with_retries<F, Fut>(send: F, subject: impl Display, retry_delay: Duration)
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<(), NotificationError>>,
+{
+    let mut delay = retry_delay;
+    for attempt in 1..=MAX_ATTEMPTS {
+        match send().await {
+            Ok(()) => return,
+            Err(e) if e.is_retryable() && attempt < MAX_ATTEMPTS => {
+                tracing::warn!("Notification attempt {} failed, retrying: {}", attempt, e);
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            }
+            Err(e) => {
+                tracing::error!("Dropped notification for {}: {}", subject, e);
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::external_apis::notifications::NotificationError;
+    use crate::test_support::{repo_full_name, workflow_run};
+    use async_trait::async_trait;
+
+    /// Records delivered run ids, failing the first `failures` attempts.
+    #[derive(Default)]
+    struct RecordingPort {
+        delivered: Mutex<Vec<u64>>,
+        failures: Mutex<u32>,
+    }
+
+    #[async_trait]
+    impl NotificationPort for RecordingPort {
+        async fn notify(&self, event: &RunTransition) -> Result<(), NotificationError> {
+            let mut failures = self.failures.lock().unwrap_or_else(PoisonError::into_inner);
+            if *failures > 0 {
+                *failures -= 1;
+                return Err(NotificationError::Rejected {
+                    target: "recorder".to_string(),
+                    status: 503,
+                });
+            }
+            self.delivered
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(event.run.id.0);
+            Ok(())
+        }
+    }
+
+    impl RecordingPort {
+        fn delivered(&self) -> Vec<u64> {
+            self.delivered
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clone()
+        }
+    }
+
+    fn failed(repo: &str, id: u64) -> RunTransition {
+        RunTransition {
+            run: workflow_run(repo, id, "failure"),
+            previous_status: None,
+            previous_conclusion: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_filters_pick_failures_of_listed_repositories() -> anyhow::Result<()> {
+        let port = Arc::new(RecordingPort::default());
+        let notifier = RunNotifier::default().with_target(
+            Arc::clone(&port) as _,
+            NotificationFilter {
+                failures_only: true,
+                repos: vec![repo_full_name("owner/repo")],
+                branches: vec!["main".to_string()],
+                ..NotificationFilter::default()
+            },
+        );
+        let succeeded = RunTransition {
+            run: workflow_run("owner/repo", 1, "success"),
+            previous_status: None,
+            previous_conclusion: None,
+        };
+        let mut on_branch = failed("owner/repo", 4);
+        on_branch.run.head_branch = Some("feature".to_string());
+
+        notifier
+            .dispatch(&[
+                succeeded,
+                failed("owner/repo", 2),
+                failed("owner/other", 3),
+                on_branch,
+            ])
+            .await?;
+
+        assert_eq!(port.delivered(), vec![2]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cooldown_suppresses_repeats_per_workflow() -> anyhow::Result<()> {
+        let port = Arc::new(RecordingPort::default());
+        let notifier = RunNotifier::default().with_target(
+            Arc::clone(&port) as _,
+            NotificationFilter {
+                cooldown: Duration::from_mins(10),
+                ..NotificationFilter::default()
+            },
+        );
+        let now = Instant::now();
+
+        let first = notifier.select(&[failed("owner/repo", 1), failed("owner/other", 2)], now);
+        let storm = notifier.select(&[failed("owner/repo", 3)], now + Duration::from_mins(1));
+        let later = notifier.select(&[failed("owner/repo", 4)], now + Duration::from_mins(10));
+
+        assert_eq!(first.len(), 2);
+        assert!(storm.is_empty());
+        assert_eq!(later.len(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_retryable_failures_are_retried_a_bounded_number_of_times() -> anyhow::Result<()> {
+        let port = Arc::new(RecordingPort {
+            failures: Mutex::new(2),
+            ..RecordingPort::default()
+        });
+        let mut notifier = RunNotifier::default()
+            .with_target(Arc::clone(&port) as _, NotificationFilter::default());
+        notifier.retry_delay = Duration::from_millis(1);
+
+        notifier.dispatch(&[failed("owner/repo", 1)]).await?;
+        *port.failures.lock().unwrap_or_else(PoisonError::into_inner) = MAX_ATTEMPTS;
+        notifier.dispatch(&[failed("owner/repo", 2)]).await?;
+
+        assert_eq!(port.delivered(), vec![1]);
+        Ok(())
+    }
+}
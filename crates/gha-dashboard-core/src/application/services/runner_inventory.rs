@@ -0,0 +1,268 @@
+use super::shared_poller::{Notice, SharedPoller};
+use crate::domain::external_apis::github::{GitHubApi, GitHubApiError, Repository};
+use crate::domain::models::ids::RepoFullName;
+use crate::domain::models::runner::{RepositoryRunners, RunnerStatus};
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+use tokio::sync::{broadcast, watch};
+use tokio::task::JoinHandle;
+
+/// Interval between two inventories of the self-hosted runners (seconds)
+const INVENTORY_INTERVAL_SECONDS: u64 = 300;
+
+/// Self-hosted runners of the polled repositories, as of `updated_at`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunnerFleet {
+    /// Repositories with self-hosted runners, by name
+    pub repositories: Vec<RepositoryRunners>,
+    /// When the runners were last fetched
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Keeps track of the self-hosted runners of the polled repositories and warns clients when
+/// one goes offline.
+///
+/// Repositories are not listed again once they turn out to have no runners, or the token
+/// may not list them, so repositories using GitHub-hosted runners only cost one call.
+pub struct RunnerInventory {
+    github_api: Arc<dyn GitHubApi + Send + Sync>,
+    fleet: watch::Sender<Option<Arc<RunnerFleet>>>,
+    /// Repositories skipped by every further inventory
+    skipped: Mutex<HashSet<RepoFullName>>,
+}
+
+impl RunnerInventory {
+    /// An inventory listing the runners `github_api` can see, empty until spawned.
+    #[must_use]
+    pub fn new(github_api: Arc<dyn GitHubApi + Send + Sync>) -> Self {
+        Self {
+            github_api,
+            fleet: watch::channel(None).0,
+            skipped: Mutex::default(),
+        }
+    }
+
+    /// The last inventory; `None` until the first one completes.
+    #[must_use]
+    pub fn fleet(&self) -> Option<Arc<RunnerFleet>> {
+        self.fleet.borrow().clone()
+    }
+
+    fn skipped(&self) -> MutexGuard<'_, HashSet<RepoFullName>> {
+        self.skipped.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Lists the runners of `repositories` at `now` and returns a warning for each runner
+    /// that was online in the last inventory and is offline now.
+    ///
+    /// A repository whose runners cannot be listed keeps those of the last inventory.
+    pub async fn refresh(&self, repositories: &[RepoFullName], now: DateTime<Utc>) -> Vec<Notice> {
+        let previous = self.fleet();
+        let known = |repository: &RepoFullName| {
+            previous
+                .iter()
+                .flat_map(|fleet| &fleet.repositories)
+                .find(|runners| runners.repository == *repository)
+        };
+
+        let mut listed = Vec::new();
+        for repository in repositories {
+            if self.skipped().contains(repository) {
+                continue;
+            }
+            match self.github_api.fetch_runners(repository).await {
+                Ok(runners) if runners.is_empty() && known(repository).is_none() => {
+                    tracing::info!(
+                        "{} has no self-hosted runners, not listing them again",
+                        repository
+                    );
+                    self.skipped().insert(repository.clone());
+                }
+                Ok(runners) => listed.push(RepositoryRunners::new(repository.clone(), runners)),
+                Err(e @ (GitHubApiError::NotFound { .. } | GitHubApiError::Forbidden { .. })) => {
+                    tracing::warn!("{}, not listing the runners of {} again", e, repository);
+                    self.skipped().insert(repository.clone());
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to list self-hosted runners: {}", e);
+                    listed.extend(known(repository).cloned());
+                }
+            }
+        }
+        listed.sort_by(|a, b| a.repository.cmp(&b.repository));
+
+        let notices = listed
+            .iter()
+            .flat_map(|runners| {
+                let before = known(&runners.repository);
+                runners.runners.iter().filter_map(move |runner| {
+                    let was_online = before
+                        .iter()
+                        .flat_map(|before| &before.runners)
+                        .any(|old| old.id == runner.id && old.status == RunnerStatus::Online);
+                    (was_online && runner.status == RunnerStatus::Offline).then(|| {
+                        tracing::warn!(
+                            "Self-hosted runner {} of {} went offline",
+                            runner.name,
+                            runners.repository
+                        );
+                        Notice::warning(format!(
+                            "Self-hosted runner {} of {} went offline",
+                            runner.name, runners.repository
+                        ))
+                    })
+                })
+            })
+            .collect();
+        self.fleet.send_replace(Some(Arc::new(RunnerFleet {
+            repositories: listed,
+            updated_at: now,
+        })));
+        notices
+    }
+
+    /// Takes an inventory of the repositories of `poller`'s latest snapshot every five
+    /// minutes, starting with the first snapshot, and broadcasts its notices through it.
+    pub fn spawn(self: Arc<Self>, poller: Arc<SharedPoller>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut events = poller.subscribe();
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(INVENTORY_INTERVAL_SECONDS));
+            loop {
+                interval.tick().await;
+                let latest = loop {
+                    if let Some(latest) = poller.snapshots().latest() {
+                        break latest;
+                    }
+                    if let Err(broadcast::error::RecvError::Closed) = events.recv().await {
+                        return;
+                    }
+                };
+                let repositories: Vec<RepoFullName> = latest
+                    .repositories
+                    .iter()
+                    .map(Repository::full_name)
+                    .cloned()
+                    .collect();
+                for notice in self.refresh(&repositories, Utc::now()).await {
+                    poller.notify_clients(notice);
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::ids::RunnerId;
+    use crate::domain::models::runner::SelfHostedRunner;
+    use crate::test_support::{base_time, repo_full_name};
+    use crate::testing::ScriptedGitHubApi;
+
+    fn runner(id: u64, status: RunnerStatus) -> SelfHostedRunner {
+        SelfHostedRunner {
+            id: RunnerId(id),
+            name: format!("build-{id}"),
+            os: "Linux".to_string(),
+            status,
+            busy: false,
+            labels: vec!["self-hosted".to_string()],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_a_runner_going_offline_is_warned_about_once() -> anyhow::Result<()> {
+        let repo = repo_full_name("owner/repo");
+        let github_api = Arc::new(ScriptedGitHubApi::new());
+        github_api
+            .runners(
+                &repo,
+                Ok(vec![
+                    runner(1, RunnerStatus::Online),
+                    runner(2, RunnerStatus::Online),
+                ]),
+            )
+            .runners(
+                &repo,
+                Ok(vec![
+                    runner(1, RunnerStatus::Online),
+                    runner(2, RunnerStatus::Offline),
+                ]),
+            );
+        let inventory = RunnerInventory::new(github_api);
+        let repositories = [repo.clone()];
+
+        assert!(
+            inventory
+                .refresh(&repositories, base_time())
+                .await
+                .is_empty()
+        );
+        let went_offline = inventory.refresh(&repositories, base_time()).await;
+        let still_offline = inventory.refresh(&repositories, base_time()).await;
+
+        assert_eq!(
+            went_offline,
+            vec![Notice::warning(
+                "Self-hosted runner build-2 of owner/repo went offline"
+            )]
+        );
+        assert!(still_offline.is_empty());
+        let fleet = inventory
+            .fleet()
+            .ok_or_else(|| anyhow::anyhow!("no inventory"))?;
+        assert_eq!(
+            (
+                fleet.repositories[0].online,
+                fleet.repositories[0].offline,
+                fleet.repositories[0].busy
+            ),
+            (1, 1, 0)
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_repositories_without_runners_are_not_listed_again() -> anyhow::Result<()> {
+        let (hosted, self_hosted, private) = (
+            repo_full_name("owner/hosted"),
+            repo_full_name("owner/self-hosted"),
+            repo_full_name("owner/private"),
+        );
+        let github_api = Arc::new(ScriptedGitHubApi::new());
+        github_api
+            .runners(&hosted, Ok(Vec::new()))
+            .runners(&self_hosted, Ok(vec![runner(1, RunnerStatus::Online)]))
+            .runners(
+                &private,
+                GitHubApiError::Forbidden {
+                    resource: "runners of owner/private".to_string(),
+                    message: "Resource not accessible by personal access token".to_string(),
+                },
+            );
+        let inventory = RunnerInventory::new(Arc::clone(&github_api) as _);
+        let repositories = [hosted, self_hosted.clone(), private];
+
+        inventory.refresh(&repositories, base_time()).await;
+        inventory.refresh(&repositories, base_time()).await;
+
+        assert_eq!(
+            github_api.calls(),
+            [
+                "fetch_runners owner/hosted",
+                "fetch_runners owner/self-hosted",
+                "fetch_runners owner/private",
+                "fetch_runners owner/self-hosted",
+            ]
+        );
+        let fleet = inventory
+            .fleet()
+            .ok_or_else(|| anyhow::anyhow!("no inventory"))?;
+        assert_eq!(fleet.repositories.len(), 1);
+        assert_eq!(fleet.repositories[0].repository, self_hosted);
+        Ok(())
+    }
+}
@@ -0,0 +1,1309 @@
+use super::job_tracker::JobTracker;
+use super::pinned_runs::{PinnedRun, PinnedRuns};
+use super::poller_status::{PollerStatus, RepositoryStatus};
+use super::reconciliation::{ReconciliationReport, keep_newer_shown_runs};
+use super::repository_discovery::RepositoryDiscovery;
+use super::required_checks::RequiredChecksCache;
+use super::run_notifier::RunNotifier;
+use super::snapshot_store::SnapshotStore;
+use super::workflow_inventory::WorkflowInventory;
+use crate::application::use_cases::stream_github_actions_runs::config::{
+    InvalidStreamConfig, StreamConfig, StreamConfigPatch,
+};
+use crate::application::use_cases::stream_github_actions_runs::{
+    StreamGitHubActionsRunsUseCase, StreamGitHubActionsRunsUseCaseInput,
+    StreamGitHubActionsRunsUseCaseOutput, sort_runs_newest_first,
+};
+use crate::domain::external_apis::github::Repository;
+use crate::domain::models::bot_group::BotRunGroup;
+use crate::domain::models::job::Job;
+use crate::domain::models::repository_change::RepositoryChange;
+use crate::domain::models::run::WorkflowRun;
+use crate::domain::models::status::RunStatus;
+use crate::domain::models::transition::RunTransition;
+use crate::domain::repositories::RunRepository;
+use chrono::{DateTime, TimeDelta, Utc};
+use futures_util::{FutureExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+use std::time::Duration;
+use tokio::sync::{Notify, broadcast, watch};
+use tokio::task::JoinHandle;
+
+/// Wait time before restarting the use case stream after it ends (seconds)
+const RESTART_WAIT_SECONDS: u64 = 30;
+
+/// Wait before restarting the polling loop after its first panic (seconds), doubled for
+/// each further panic until a snapshot is published again
+const PANIC_BACKOFF_INITIAL_SECONDS: u64 = 1;
+
+/// Longest wait before restarting the polling loop after a panic (seconds)
+const PANIC_BACKOFF_MAX_SECONDS: u64 = 60;
+
+/// Number of recent run transitions kept for `/export`
+const RECENT_TRANSITIONS_CAPACITY: usize = 100;
+
+/// Number of events buffered for slow subscribers before they start lagging
+pub(super) const EVENT_CHANNEL_CAPACITY: usize = 16;
+
+/// Severity of a server-initiated notice.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NoticeLevel {
+    /// Informational, e.g. a planned restart
+    Info,
+    /// Something clients should act on or show prominently
+    Warning,
+}
+
+/// A message the server sends to clients on its own initiative, e.g. before a restart.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Notice {
+    /// Serialized as `type`
+    #[serde(rename = "type")]
+    pub level: NoticeLevel,
+    /// Text shown to users
+    pub message: String,
+}
+
+impl Notice {
+    /// A notice of level [`NoticeLevel::Info`].
+    #[must_use]
+    pub fn info(message: impl Into<String>) -> Self {
+        Self {
+            level: NoticeLevel::Info,
+            message: message.into(),
+        }
+    }
+
+    /// A notice of level [`NoticeLevel::Warning`].
+    #[must_use]
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self {
+            level: NoticeLevel::Warning,
+            message: message.into(),
+        }
+    }
+}
+
+/// An update published by the shared poller to every subscriber.
+#[derive(Debug, Clone)]
+pub enum PollerEvent {
+    /// A new snapshot of the runs
+    Snapshot(Arc<StreamGitHubActionsRunsUseCaseOutput>),
+    /// An error that ended the use case stream, as shown to users
+    Error(Arc<str>),
+    /// A message from the server itself
+    Notice(Arc<Notice>),
+    /// The server is shutting down; subscribers should close their connection
+    Shutdown,
+}
+
+/// Runs a single use case stream in the background and fans its output out to
+/// all connected clients, so the GitHub API is polled once regardless of how
+/// many clients are connected.
+pub struct SharedPoller {
+    events: broadcast::Sender<PollerEvent>,
+    /// The latest snapshot and every event again, numbered, for websocket clients that
+    /// resume after a disconnect
+    snapshots: Arc<SnapshotStore>,
+    refresh: Arc<Notify>,
+    config: watch::Sender<StreamConfig>,
+    workflows: Arc<WorkflowInventory>,
+    /// Outlives restarts of the polling loop, so branch protection is not fetched again
+    required_checks: Arc<RequiredChecksCache>,
+    /// Outlives restarts of the polling loop, so repositories seen are not announced again
+    discovery: Arc<RepositoryDiscovery>,
+    /// Jobs pushed by webhooks, whose failures annotate every snapshot published
+    jobs: Arc<JobTracker>,
+    /// Runs websocket clients pinned, fetched by the polling loop whatever their age
+    pinned_runs: Arc<PinnedRuns>,
+    /// Stores every snapshot when set, and supplies the first one after a restart
+    run_repository: Option<Arc<dyn RunRepository + Send + Sync>>,
+    /// When a webhook last pushed a run, read by the polling loop to slow down
+    last_push: watch::Sender<Option<DateTime<Utc>>>,
+    /// Told about runs that changed between two snapshots this poller took; replaced when
+    /// the configuration is reloaded
+    notifier: RwLock<Option<Arc<RunNotifier>>>,
+    /// Outcome of the last comparison of webhook-updated runs with a poll
+    last_reconciliation: watch::Sender<Option<Arc<ReconciliationReport>>>,
+    /// Called with every reconciliation report, e.g. to count discrepancies
+    on_reconciled: Option<ReconciliationHook>,
+    /// Set when the polling loop panicked, until a snapshot is published again
+    degraded: AtomicBool,
+    /// Called each time the polling loop panics, e.g. to count panics
+    on_panic: Option<PanicHook>,
+    /// Health of the polling loop, updated after each iteration
+    status: watch::Sender<PollerStatus>,
+    /// The most recent runs that changed between two snapshots this poller took, oldest first
+    transitions: Mutex<VecDeque<RunTransition>>,
+}
+
+/// Callback receiving each [`ReconciliationReport`].
+type ReconciliationHook = Arc<dyn Fn(&ReconciliationReport) + Send + Sync>;
+
+/// Callback run when the polling loop panics.
+type PanicHook = Arc<dyn Fn() + Send + Sync>;
+
+/// The message a panic was raised with, when it is a string.
+#[must_use]
+pub fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("non-string panic payload")
+}
+
+impl SharedPoller {
+    /// A poller with the default settings, woken early by `refresh`; call [`Self::spawn`] to
+    /// start polling.
+    #[must_use]
+    pub fn new(refresh: Arc<Notify>) -> Self {
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        Self {
+            events,
+            snapshots: Arc::default(),
+            refresh,
+            config: watch::Sender::new(StreamConfig::default()),
+            workflows: Arc::default(),
+            required_checks: Arc::default(),
+            discovery: Arc::default(),
+            jobs: Arc::default(),
+            pinned_runs: Arc::default(),
+            run_repository: None,
+            last_push: watch::Sender::new(None),
+            notifier: RwLock::new(None),
+            last_reconciliation: watch::Sender::new(None),
+            on_reconciled: None,
+            degraded: AtomicBool::new(false),
+            on_panic: None,
+            status: watch::Sender::new(PollerStatus::default()),
+            transitions: Mutex::default(),
+        }
+    }
+
+    /// Saves each snapshot to `run_repository` and starts from the last saved one.
+    #[must_use]
+    pub fn with_run_repository(
+        mut self,
+        run_repository: Arc<dyn RunRepository + Send + Sync>,
+    ) -> Self {
+        self.run_repository = Some(run_repository);
+        self
+    }
+
+    /// Tells apart the repositories listed with `discovery` instead of the default, which
+    /// keeps no record across restarts and polls whatever is listed.
+    #[must_use]
+    pub fn with_repository_discovery(mut self, discovery: RepositoryDiscovery) -> Self {
+        self.discovery = Arc::new(discovery);
+        self
+    }
+
+    /// Sends the run transitions between consecutive snapshots to `notifier`.
+    #[must_use]
+    pub fn with_notifier(self, notifier: Arc<RunNotifier>) -> Self {
+        self.replace_notifier(Some(notifier));
+        self
+    }
+
+    /// Sends later run transitions to `notifier` instead, or to nobody when `None`; the
+    /// deliveries already under way finish.
+    pub fn replace_notifier(&self, notifier: Option<Arc<RunNotifier>>) {
+        *self
+            .notifier
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = notifier;
+    }
+
+    /// Passes each reconciliation report to `on_reconciled`.
+    #[must_use]
+    pub fn with_reconciliation_hook<F>(mut self, on_reconciled: F) -> Self
+    where
+        F: Fn(&ReconciliationReport) + Send + Sync + 'static,
+    {
+        self.on_reconciled = Some(Arc::new(on_reconciled));
+        self
+    }
+
+    /// Runs `on_panic` each time the polling loop panics.
+    #[must_use]
+    pub fn with_panic_hook<F>(mut self, on_panic: F) -> Self
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        self.on_panic = Some(Arc::new(on_panic));
+        self
+    }
+
+    /// Workflows of the polled repositories, refreshed along with the repository list.
+    #[must_use]
+    pub fn workflows(&self) -> &Arc<WorkflowInventory> {
+        &self.workflows
+    }
+
+    /// Starts the poller with `config` instead of the defaults.
+    #[must_use]
+    pub fn with_config(self, config: StreamConfig) -> Self {
+        self.config.send_replace(config);
+        self
+    }
+
+    /// Spawns the polling loop onto the tokio runtime, restarting it whenever it panics.
+    pub fn spawn<U>(self: &Arc<Self>, use_case: Arc<U>) -> JoinHandle<()>
+    where
+        U: StreamGitHubActionsRunsUseCase + Send + Sync + 'static,
+    {
+        let poller = Arc::clone(self);
+        tokio::spawn(async move { poller.supervise(use_case.as_ref()).await })
+    }
+
+    /// Whether the polling loop panicked and has not published a snapshot since.
+    #[must_use]
+    pub fn is_degraded(&self) -> bool {
+        self.degraded.load(Ordering::Relaxed)
+    }
+
+    /// Runs the polling loop, restarting it with a growing backoff after each panic.
+    ///
+    /// The panic is caught on the same task, so aborting the spawned task still stops the
+    /// loop. Its backtrace is logged by the panic hook installed with the telemetry.
+    async fn supervise<U: StreamGitHubActionsRunsUseCase>(&self, use_case: &U) {
+        let mut backoff = Duration::from_secs(PANIC_BACKOFF_INITIAL_SECONDS);
+        loop {
+            let Err(panic) = AssertUnwindSafe(self.run(use_case)).catch_unwind().await else {
+                return;
+            };
+            // A snapshot published since the previous panic means the loop had recovered
+            if !self.degraded.swap(true, Ordering::Relaxed) {
+                backoff = Duration::from_secs(PANIC_BACKOFF_INITIAL_SECONDS);
+            }
+            let message = panic_message(panic.as_ref());
+            tracing::error!(
+                "Polling loop panicked: {}, restarting in {:?}",
+                message,
+                backoff
+            );
+            self.status
+                .send_modify(|status| status.record_panic(message, Utc::now()));
+            if let Some(on_panic) = &self.on_panic {
+                on_panic();
+            }
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(Duration::from_secs(PANIC_BACKOFF_MAX_SECONDS));
+        }
+    }
+
+    /// Subscribes to snapshots and errors published after this call.
+    #[must_use]
+    pub fn subscribe(&self) -> broadcast::Receiver<PollerEvent> {
+        self.events.subscribe()
+    }
+
+    /// The snapshots this poller publishes, with the numbered recent events websocket clients
+    /// follow so they can resume.
+    #[must_use]
+    pub fn snapshots(&self) -> &Arc<SnapshotStore> {
+        &self.snapshots
+    }
+
+    /// Returns the health of the polling loop as of its last iteration.
+    #[must_use]
+    pub fn status(&self) -> PollerStatus {
+        self.status.borrow().clone()
+    }
+
+    /// The most recent runs that changed between two snapshots, oldest first.
+    #[must_use]
+    pub fn recent_transitions(&self) -> Vec<RunTransition> {
+        let transitions = self
+            .transitions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        transitions.iter().cloned().collect()
+    }
+
+    /// Replaces the state shown to clients with one exported from another server, e.g. to
+    /// reproduce what its dashboard showed; the polling loop overwrites it on its next
+    /// snapshot. Without `output`, the latest snapshot is kept.
+    pub fn import(
+        &self,
+        output: Option<StreamGitHubActionsRunsUseCaseOutput>,
+        repositories: Vec<RepositoryStatus>,
+        transitions: Vec<RunTransition>,
+    ) {
+        self.status
+            .send_modify(|status| status.repositories = repositories);
+        *self
+            .transitions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = transitions.into();
+        if let Some(output) = output {
+            self.publish(output);
+        }
+    }
+
+    /// Number of websocket and SSE clients subscribed to updates.
+    #[must_use]
+    pub fn client_count(&self) -> usize {
+        self.events.receiver_count() + self.snapshots.receiver_count()
+    }
+
+    /// Returns the report of the last reconciliation, if webhooks ever pushed a run.
+    #[must_use]
+    pub fn last_reconciliation(&self) -> Option<Arc<ReconciliationReport>> {
+        self.last_reconciliation.borrow().clone()
+    }
+
+    /// Returns the settings the polling loop currently runs with.
+    #[must_use]
+    pub fn config(&self) -> StreamConfig {
+        self.config.borrow().clone()
+    }
+
+    /// Validates `patch` against the current settings and applies it atomically, keeping the
+    /// runs pinned now within the API call budget.
+    ///
+    /// The polling loop picks the new settings up at its next iteration.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InvalidStreamConfig`] listing each violated constraint; the
+    /// current settings are left unchanged.
+    pub fn update_config(
+        &self,
+        patch: &StreamConfigPatch,
+    ) -> Result<StreamConfig, InvalidStreamConfig> {
+        let mut result = None;
+        // Validating inside the closure keeps concurrent patches from overwriting each other
+        self.config.send_if_modified(|current| {
+            match current.apply_with_pins(patch, self.pinned_runs.count()) {
+                Ok(updated) => {
+                    tracing::info!("Stream config changed from {:?} to {:?}", current, updated);
+                    let modified = *current != updated;
+                    *current = updated.clone();
+                    result = Some(Ok(updated));
+                    modified
+                }
+                Err(e) => {
+                    result = Some(Err(e));
+                    false
+                }
+            }
+        });
+        result.unwrap_or_else(|| Ok(self.config()))
+    }
+
+    /// Replaces every setting at once, e.g. with those of a reloaded config file; `config` is
+    /// expected to be validated already, except against the runs pinned now.
+    ///
+    /// The polling loop picks the new settings up at its next iteration.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InvalidStreamConfig`] when `config` with the runs pinned now would exceed
+    /// the API call budget; the current settings are left unchanged.
+    pub fn replace_config(&self, config: StreamConfig) -> Result<(), InvalidStreamConfig> {
+        let mut result = Ok(());
+        self.config.send_if_modified(|current| {
+            if *current == config {
+                return false;
+            }
+            if let Err(e) = config.check_budget(self.pinned_runs.count()) {
+                result = Err(e);
+                return false;
+            }
+            tracing::info!("Stream config changed from {:?} to {:?}", current, config);
+            *current = config;
+            true
+        });
+        result
+    }
+
+    /// Pins `run` for the polling loop to fetch, checked against the API call budget under
+    /// the same lock as config changes, so neither can slip past the other.
+    ///
+    /// # Errors
+    ///
+    /// Returns the GitHub API calls per hour the polling loop would make with `run` pinned.
+    pub fn pin_run(&self, run: PinnedRun) -> Result<(), u64> {
+        let mut result = Ok(());
+        self.config.send_if_modified(|current| {
+            result = self.pinned_runs.pin(run, current);
+            false
+        });
+        result
+    }
+
+    #[tracing::instrument(name = "SharedPoller::run", skip_all)]
+    async fn run<U: StreamGitHubActionsRunsUseCase>(&self, use_case: &U) {
+        self.restore().await;
+        loop {
+            let input = StreamGitHubActionsRunsUseCaseInput {
+                refresh: Arc::clone(&self.refresh),
+                config: self.config.subscribe(),
+                workflows: Arc::clone(&self.workflows),
+                required_checks: Arc::clone(&self.required_checks),
+                last_push: self.last_push.subscribe(),
+                discovery: Arc::clone(&self.discovery),
+                pinned_runs: Arc::clone(&self.pinned_runs),
+            };
+            let stream = use_case.execute(input);
+            tokio::pin!(stream);
+
+            while let Some(result) = stream.next().await {
+                match result {
+                    Ok(mut output) => {
+                        self.status
+                            .send_modify(|status| status.record_success(&output));
+                        let repository_changes = std::mem::take(&mut output.repository_changes);
+                        let output = self.reconcile(output);
+                        let output = self.retain_inactive_repo_runs(output);
+                        self.save(&output).await;
+                        self.publish_changes(output);
+                        self.announce_repository_changes(&repository_changes);
+                    }
+                    Err(e) => {
+                        tracing::error!("Error from use case stream: {:?}", e);
+                        self.status
+                            .send_modify(|status| status.record_error(&e, Utc::now()));
+                        // Sending fails only when nobody is subscribed, which is fine
+                        self.broadcast(PollerEvent::Error(e.to_string().into()));
+                    }
+                }
+            }
+
+            tracing::warn!(
+                "Use case stream ended, restarting in {} seconds",
+                RESTART_WAIT_SECONDS
+            );
+            self.status.send_modify(PollerStatus::back_off);
+            tokio::select! {
+                () = tokio::time::sleep(Duration::from_secs(RESTART_WAIT_SECONDS)) => {}
+                () = self.refresh.notified() => {}
+            }
+        }
+    }
+
+    /// Keeps the shown runs of repositories that dropped out of the polled ones in `polled`,
+    /// marked inactive, until they are older than `inactiveRepoRunMaxAgeHours` or more than
+    /// `maxInactiveRepoRuns` newer such runs are kept.
+    ///
+    /// Repositories only drop out of an allowlist on purpose, so their runs are dropped at once.
+    fn retain_inactive_repo_runs(
+        &self,
+        mut polled: StreamGitHubActionsRunsUseCaseOutput,
+    ) -> StreamGitHubActionsRunsUseCaseOutput {
+        let config = self.config();
+        if !config.repo_allowlist.is_empty() {
+            return polled;
+        }
+        let Some(latest) = self.snapshots.latest() else {
+            return polled;
+        };
+
+        let max_age = TimeDelta::hours(i64::from(config.inactive_repo_run_max_age_hours));
+        let polled_repositories: HashSet<_> = polled
+            .repositories
+            .iter()
+            .map(Repository::full_name)
+            .chain(polled.runs.iter().map(|run| &run.repository_name))
+            .collect();
+        let mut retained: Vec<WorkflowRun> = latest
+            .runs
+            .iter()
+            .filter(|run| {
+                !polled_repositories.contains(&run.repository_name)
+                    && polled.generated_at - run.updated_at <= max_age
+                    && (!config.problem_only || run.is_problem())
+                    && config
+                        .workflow_filter_for(&run.repository_name)
+                        .allows(&run.workflow_name)
+            })
+            .cloned()
+            .collect();
+        sort_runs_newest_first(&mut retained);
+        retained.truncate(usize::try_from(config.max_inactive_repo_runs).unwrap_or(usize::MAX));
+        if retained.is_empty() {
+            return polled;
+        }
+
+        for run in &mut retained {
+            run.repository_active = false;
+        }
+        polled.runs.append(&mut retained);
+        sort_runs_newest_first(&mut polled.runs);
+        polled
+    }
+
+    /// Publishes the last stored snapshot so clients have data before the first poll completes.
+    async fn restore(&self) {
+        let Some(run_repository) = &self.run_repository else {
+            return;
+        };
+        match run_repository.latest_snapshot().await {
+            Ok(Some(snapshot)) if self.snapshots.latest().is_none() => {
+                tracing::info!("Restored {} runs from the run store", snapshot.runs.len());
+                let mut runs = snapshot.runs;
+                let bot_runs = if self.config().group_bot_runs {
+                    runs.extract_if(.., |run| BotRunGroup::collapses(run))
+                        .collect()
+                } else {
+                    Vec::new()
+                };
+                self.publish(
+                    StreamGitHubActionsRunsUseCaseOutput::new(runs, snapshot.generated_at)
+                        .with_bot_runs(bot_runs),
+                );
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Failed to restore the last snapshot: {}", e),
+        }
+    }
+
+    /// Compares the runs shown with a fresh poll once webhooks have pushed runs, and
+    /// repairs what the webhooks missed.
+    ///
+    /// While webhooks arrive, polls slow down to reconciliation passes, so each poll is
+    /// checked. The poll replaces the runs shown except where a webhook updated a run after
+    /// it, so lost deliveries are repaired without regressing runs the poll raced.
+    fn reconcile(
+        &self,
+        mut polled: StreamGitHubActionsRunsUseCaseOutput,
+    ) -> StreamGitHubActionsRunsUseCaseOutput {
+        if self.last_push.borrow().is_none() {
+            return polled;
+        }
+        let Some(latest) = self.snapshots.latest() else {
+            return polled;
+        };
+
+        let span = tracing::info_span!("reconciliation");
+        let _entered = span.enter();
+        let report = ReconciliationReport::compare(&latest.runs, &polled.runs, polled.generated_at);
+        for run_id in &report.missing_runs {
+            tracing::warn!("Run {} was polled but never pushed by a webhook", run_id);
+        }
+        for mismatch in &report.status_mismatches {
+            tracing::warn!(
+                "Run {} of {} was shown as {} but polled as {}",
+                mismatch.run_id,
+                mismatch.repository,
+                mismatch.shown,
+                mismatch.polled
+            );
+        }
+        tracing::info!(
+            "Reconciled {} runs: {} missing, {} status mismatches",
+            report.compared_runs,
+            report.missing_runs.len(),
+            report.status_mismatches.len()
+        );
+
+        keep_newer_shown_runs(&mut polled.runs, &latest.runs);
+        let mut bot_runs = std::mem::take(&mut polled.bot_runs);
+        keep_newer_shown_runs(&mut bot_runs, &latest.bot_runs);
+        let polled = polled.with_bot_runs(bot_runs);
+        if let Some(on_reconciled) = &self.on_reconciled {
+            on_reconciled(&report);
+        }
+        self.last_reconciliation
+            .send_replace(Some(Arc::new(report)));
+        polled
+    }
+
+    /// Stores the runs of `output`, grouped runs of bots included; a failure only costs
+    /// history, so it is logged and ignored.
+    async fn save(&self, output: &StreamGitHubActionsRunsUseCaseOutput) {
+        let Some(run_repository) = &self.run_repository else {
+            return;
+        };
+        let runs: Vec<WorkflowRun> = output
+            .runs
+            .iter()
+            .chain(&output.bot_runs)
+            .cloned()
+            .collect();
+        if let Err(e) = run_repository.save_runs(&runs, output.generated_at).await {
+            tracing::warn!("Failed to save runs: {}", e);
+        }
+    }
+
+    /// Merges a run pushed by a webhook into the latest snapshot and publishes the result.
+    ///
+    /// Runs of repositories the snapshot does not cover and updates older than the run
+    /// already shown are ignored; the current settings apply as they do to polled runs.
+    /// Returns whether a snapshot was published.
+    pub async fn push_run(&self, mut run: WorkflowRun) -> bool {
+        self.last_push.send_replace(Some(Utc::now()));
+        let Some(latest) = self.snapshots.latest() else {
+            return false;
+        };
+        if !latest
+            .repositories
+            .iter()
+            .any(|repo| *repo.full_name() == run.repository_name)
+        {
+            return false;
+        }
+        if latest
+            .runs
+            .iter()
+            .chain(&latest.bot_runs)
+            .any(|shown| shown.id == run.id && shown.updated_at > run.updated_at)
+        {
+            return false;
+        }
+
+        let config = self.config();
+        if !config
+            .workflow_filter_for(&run.repository_name)
+            .allows(&run.workflow_name)
+        {
+            return false;
+        }
+        let mut runs: Vec<WorkflowRun> = latest
+            .runs
+            .iter()
+            .filter(|shown| shown.id != run.id)
+            .cloned()
+            .collect();
+        let mut bot_runs: Vec<WorkflowRun> = latest
+            .bot_runs
+            .iter()
+            .filter(|shown| shown.id != run.id)
+            .cloned()
+            .collect();
+        // A run leaves the queue as soon as a webhook says it started
+        let mut queued_runs: Vec<WorkflowRun> = latest
+            .queued_runs
+            .iter()
+            .filter(|queued| queued.id != run.id)
+            .cloned()
+            .collect();
+        if run.status == RunStatus::Queued {
+            queued_runs.push(run.clone());
+        }
+        if !config.problem_only || run.is_problem() {
+            config.label(&mut run);
+            if config.group_bot_runs && BotRunGroup::collapses(&run) {
+                bot_runs.push(run);
+            } else {
+                runs.push(run);
+            }
+        }
+        sort_runs_newest_first(&mut runs);
+        sort_runs_newest_first(&mut bot_runs);
+        // Keep as many runs per repository as a poll would return
+        let mut kept = HashMap::new();
+        runs.retain(|run| {
+            let count = kept.entry(run.repository_name.clone()).or_insert(0);
+            *count += 1;
+            *count <= usize::from(config.max_runs_per_repo)
+        });
+
+        let output = StreamGitHubActionsRunsUseCaseOutput::new(runs, Utc::now())
+            .with_repositories(latest.repositories.clone())
+            .with_queued_runs(queued_runs)
+            .with_upcoming(latest.upcoming.clone())
+            .with_external_checks(latest.external_checks.clone())
+            .with_bot_runs(bot_runs);
+        self.save(&output).await;
+        self.publish_changes(output);
+        true
+    }
+
+    /// Jobs pushed by webhooks through [`Self::push_job`].
+    #[must_use]
+    pub fn jobs(&self) -> &Arc<JobTracker> {
+        &self.jobs
+    }
+
+    /// Runs pinned by clients, polled on every iteration.
+    #[must_use]
+    pub fn pinned_runs(&self) -> &Arc<PinnedRuns> {
+        &self.pinned_runs
+    }
+
+    /// Records a job pushed by a webhook and, when its run is shown, publishes the snapshot
+    /// again with the run's failed jobs updated.
+    ///
+    /// Updates older than the job already recorded are ignored. Returns whether a snapshot
+    /// was published.
+    pub fn push_job(&self, job: Job) -> bool {
+        let run_id = job.run_id;
+        if !self.jobs.record(job) {
+            return false;
+        }
+        let Some(latest) = self.snapshots.latest() else {
+            return false;
+        };
+        let Some(run) = latest
+            .runs
+            .iter()
+            .chain(&latest.bot_runs)
+            .find(|run| run.id == run_id)
+        else {
+            return false;
+        };
+        if run.failed_jobs == self.jobs.failed_jobs(run_id)
+            && run.failed_job_url == self.jobs.failed_job_url(run_id)
+        {
+            return false;
+        }
+        self.publish_changes(StreamGitHubActionsRunsUseCaseOutput::clone(&latest));
+        true
+    }
+
+    /// Sends `notice` to every connected client.
+    pub fn notify_clients(&self, notice: Notice) {
+        tracing::info!("Broadcasting {:?} notice: {}", notice.level, notice.message);
+        self.broadcast(PollerEvent::Notice(Arc::new(notice)));
+    }
+
+    /// Asks every connected client to disconnect, e.g. before a graceful shutdown.
+    pub fn disconnect_clients(&self) {
+        self.broadcast(PollerEvent::Shutdown);
+    }
+
+    /// Publishes `output`, then records and notifies of the runs that changed since the latest
+    /// snapshot.
+    ///
+    /// Only snapshots this poller took go through here, so replicas following a leader
+    /// through Redis do not repeat its notifications.
+    fn publish_changes(&self, output: StreamGitHubActionsRunsUseCaseOutput) {
+        let transitions = match self.snapshots.latest() {
+            Some(previous) => RunTransition::detect(&previous.runs, &output.runs),
+            None => Vec::new(),
+        };
+        self.publish(output);
+        if transitions.is_empty() {
+            return;
+        }
+        {
+            let mut recent = self
+                .transitions
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            recent.extend(transitions.iter().cloned());
+            let excess = recent.len().saturating_sub(RECENT_TRANSITIONS_CAPACITY);
+            recent.drain(..excess);
+        }
+        let notifier = self
+            .notifier
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        if let Some(notifier) = notifier {
+            // Delivery runs on its own task so a slow webhook never delays the stream
+            notifier.dispatch(&transitions);
+        }
+    }
+
+    /// Tells clients and the notification targets about repositories that appeared or went
+    /// away.
+    fn announce_repository_changes(&self, changes: &[RepositoryChange]) {
+        if changes.is_empty() {
+            return;
+        }
+        for change in changes {
+            self.notify_clients(Notice::info(change.to_string()));
+        }
+        let notifier = self
+            .notifier
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        if let Some(notifier) = notifier {
+            notifier.dispatch_repository_changes(changes);
+        }
+    }
+
+    /// Stores `output` as the latest snapshot and sends it to every subscriber, with the
+    /// failed jobs webhooks reported for its runs.
+    pub fn publish(&self, mut output: StreamGitHubActionsRunsUseCaseOutput) {
+        self.jobs.annotate(&mut output.runs);
+        self.jobs.annotate(&mut output.bot_runs);
+        self.degraded.store(false, Ordering::Relaxed);
+        let latest = self.snapshots.publish(output);
+        let _ = self.events.send(PollerEvent::Snapshot(latest.output));
+    }
+
+    /// Records `event` in the event log and sends it to every subscriber.
+    fn broadcast(&self, event: PollerEvent) {
+        self.snapshots.record(event.clone());
+        let _ = self.events.send(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::services::NotificationFilter;
+    use crate::application::use_cases::{
+        StreamGitHubActionsRunsError, StreamGitHubActionsRunsInteractor,
+    };
+    use crate::domain::external_apis::notifications::{NotificationError, NotificationPort};
+    use crate::domain::models::ids::RunId;
+    use crate::domain::models::status::{RunConclusion, RunStatus};
+    use crate::test_support::{StubGitHubApi, base_time, repo_full_name, workflow_run};
+    use tokio::time::timeout;
+
+    async fn next_snapshot(
+        events: &mut broadcast::Receiver<PollerEvent>,
+    ) -> anyhow::Result<Arc<StreamGitHubActionsRunsUseCaseOutput>> {
+        loop {
+            if let PollerEvent::Snapshot(output) = events.recv().await? {
+                return Ok(output);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_refresh_wakes_poller_before_iteration_wait_elapses() -> anyhow::Result<()> {
+        // Given: a poller that has published its first snapshot and is now waiting
+        let refresh = Arc::new(Notify::new());
+        let poller = Arc::new(SharedPoller::new(Arc::clone(&refresh)));
+        let mut events = poller.subscribe();
+        let use_case = Arc::new(StreamGitHubActionsRunsInteractor::new(Arc::new(
+            StubGitHubApi::with_repository("owner", "repo"),
+        )));
+        let handle = poller.spawn(use_case);
+        let first = timeout(Duration::from_secs(5), next_snapshot(&mut events)).await??;
+
+        // When: a refresh is requested
+        refresh.notify_one();
+
+        // Then: the next snapshot arrives well before the 30 second iteration wait
+        let second = timeout(Duration::from_secs(5), next_snapshot(&mut events)).await??;
+        assert!(second.generated_at >= first.generated_at);
+        assert!(poller.snapshots().latest().is_some());
+
+        handle.abort();
+        Ok(())
+    }
+
+    /// Panics while polling the first time it runs, then polls normally.
+    #[derive(Default)]
+    struct PoisonedUseCase {
+        executions: std::sync::atomic::AtomicUsize,
+    }
+
+    impl StreamGitHubActionsRunsUseCase for PoisonedUseCase {
+        fn execute(
+            &self,
+            _input: StreamGitHubActionsRunsUseCaseInput,
+        ) -> impl futures_util::Stream<
+            Item = Result<StreamGitHubActionsRunsUseCaseOutput, StreamGitHubActionsRunsError>,
+        > + Send {
+            let poisoned = self.executions.fetch_add(1, Ordering::Relaxed) == 0;
+            futures_util::stream::once(async move {
+                assert!(!poisoned, "poisoned mock");
+                Ok(StreamGitHubActionsRunsUseCaseOutput::new(
+                    vec![workflow_run("owner/repo", 1, "success")],
+                    base_time(),
+                ))
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_poller_restarts_after_a_panic() -> anyhow::Result<()> {
+        let panics = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counted = Arc::clone(&panics);
+        let poller = Arc::new(SharedPoller::new(Arc::new(Notify::new())).with_panic_hook(
+            move || {
+                counted.fetch_add(1, Ordering::Relaxed);
+            },
+        ));
+        let mut events = poller.subscribe();
+        let use_case = Arc::new(PoisonedUseCase::default());
+
+        let handle = poller.spawn(Arc::clone(&use_case));
+
+        let snapshot = timeout(Duration::from_secs(5), next_snapshot(&mut events)).await??;
+        assert_eq!(snapshot.runs.len(), 1);
+        assert_eq!(use_case.executions.load(Ordering::Relaxed), 2);
+        assert_eq!(panics.load(Ordering::Relaxed), 1);
+        assert!(!poller.is_degraded());
+        assert!(!handle.is_finished());
+        handle.abort();
+        Ok(())
+    }
+
+    /// Polls each of its snapshots once, then nothing more.
+    struct ScriptedUseCase(Vec<StreamGitHubActionsRunsUseCaseOutput>);
+
+    impl StreamGitHubActionsRunsUseCase for ScriptedUseCase {
+        fn execute(
+            &self,
+            _input: StreamGitHubActionsRunsUseCaseInput,
+        ) -> impl futures_util::Stream<
+            Item = Result<StreamGitHubActionsRunsUseCaseOutput, StreamGitHubActionsRunsError>,
+        > + Send {
+            futures_util::stream::iter(self.0.clone().into_iter().map(Ok))
+                .chain(futures_util::stream::pending())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_runs_of_repositories_no_longer_polled_are_kept_until_evicted()
+    -> anyhow::Result<()> {
+        let at = |minutes| base_time() + chrono::TimeDelta::minutes(minutes);
+        let run = |repository: &str, id, minutes| {
+            let mut run = workflow_run(&format!("owner/{repository}"), id, "success");
+            run.created_at = at(minutes);
+            run.updated_at = at(minutes);
+            run
+        };
+        let poll = |repositories: [&str; 2], runs, minutes| {
+            StreamGitHubActionsRunsUseCaseOutput::new(runs, at(minutes)).with_repositories(
+                repositories
+                    .iter()
+                    .map(|name| {
+                        Repository::from_full_name(&repo_full_name(&format!("owner/{name}")))
+                    })
+                    .collect(),
+            )
+        };
+        // The recently updated repositories rotate, and `a` comes back in the fourth poll
+        let use_case = Arc::new(ScriptedUseCase(vec![
+            poll(
+                ["a", "b"],
+                vec![run("b", 3, -1), run("a", 2, -5), run("a", 1, -10)],
+                0,
+            ),
+            poll(["b", "c"], vec![run("c", 4, 5), run("b", 3, -1)], 10),
+            poll(["c", "d"], vec![run("d", 5, 15), run("c", 4, 5)], 20),
+            poll(["a", "d"], vec![run("a", 6, 50), run("d", 5, 15)], 56),
+            poll(["a", "d"], vec![run("a", 6, 50), run("d", 5, 15)], 65),
+        ]));
+        let poller = Arc::new(SharedPoller::new(Arc::new(Notify::new())).with_config(
+            StreamConfig {
+                inactive_repo_run_max_age_hours: 1,
+                max_inactive_repo_runs: 2,
+                ..StreamConfig::default()
+            },
+        ));
+        let mut events = poller.subscribe();
+        let handle = poller.spawn(use_case);
+
+        let mut shown = Vec::new();
+        for _ in 0..5 {
+            let snapshot = timeout(Duration::from_secs(5), next_snapshot(&mut events)).await??;
+            shown.push(
+                snapshot
+                    .runs
+                    .iter()
+                    .map(|run| (run.id.0, run.repository_active))
+                    .collect::<Vec<_>>(),
+            );
+        }
+        handle.abort();
+
+        assert_eq!(
+            shown,
+            [
+                vec![(3, true), (2, true), (1, true)],
+                // `a` dropped out, its runs stay
+                vec![(4, true), (3, true), (2, false), (1, false)],
+                // `b` dropped out too; only the two newest runs no longer polled stay
+                vec![(5, true), (4, true), (3, false), (2, false)],
+                // `a` is polled again, so its fresh runs replace the kept ones
+                vec![(6, true), (5, true), (4, false), (3, false)],
+                // Run 3 was last updated more than an hour ago, run 4 exactly an hour ago
+                vec![(6, true), (5, true), (4, false)],
+            ]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_runs_of_repositories_dropped_from_the_allowlist_are_not_kept()
+    -> anyhow::Result<()> {
+        let poll = |repository: &str, id| {
+            StreamGitHubActionsRunsUseCaseOutput::new(
+                vec![workflow_run(repository, id, "success")],
+                base_time(),
+            )
+            .with_repositories(vec![Repository::from_full_name(&repo_full_name(
+                repository,
+            ))])
+        };
+        let use_case = Arc::new(ScriptedUseCase(vec![
+            poll("owner/a", 1),
+            poll("owner/b", 2),
+        ]));
+        let poller = Arc::new(SharedPoller::new(Arc::new(Notify::new())).with_config(
+            StreamConfig {
+                repo_allowlist: vec![repo_full_name("owner/b")],
+                ..StreamConfig::default()
+            },
+        ));
+        let mut events = poller.subscribe();
+        let handle = poller.spawn(use_case);
+
+        timeout(Duration::from_secs(5), next_snapshot(&mut events)).await??;
+        let second = timeout(Duration::from_secs(5), next_snapshot(&mut events)).await??;
+        handle.abort();
+
+        assert_eq!(second.runs, vec![workflow_run("owner/b", 2, "success")]);
+        Ok(())
+    }
+
+    /// Forwards every notified transition to a channel.
+    struct ChannelPort(tokio::sync::mpsc::UnboundedSender<RunTransition>);
+
+    #[async_trait::async_trait]
+    impl NotificationPort for ChannelPort {
+        async fn notify(&self, event: &RunTransition) -> Result<(), NotificationError> {
+            let _ = self.0.send(event.clone());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_changed_runs_are_notified_in_the_background() -> anyhow::Result<()> {
+        let (sender, mut transitions) = tokio::sync::mpsc::unbounded_channel();
+        let notifier = RunNotifier::default()
+            .with_target(Arc::new(ChannelPort(sender)), NotificationFilter::default());
+        let poller = SharedPoller::new(Arc::new(Notify::new())).with_notifier(Arc::new(notifier));
+        let running = workflow_run("owner/repo", 1, "in_progress");
+        poller.publish(
+            StreamGitHubActionsRunsUseCaseOutput::new(vec![running.clone()], base_time())
+                .with_repositories(StubGitHubApi::with_repository("owner", "repo").repositories),
+        );
+
+        let mut failed = workflow_run("owner/repo", 1, "failure");
+        failed.updated_at = running.updated_at + chrono::TimeDelta::minutes(5);
+        assert!(poller.push_run(failed.clone()).await);
+
+        let transition = timeout(Duration::from_secs(5), transitions.recv())
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("notifier dropped"))?;
+        assert_eq!(transition.run, failed);
+        assert_eq!(transition.previous_status, Some(RunStatus::InProgress));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_changed_runs_are_kept_without_a_notifier() -> anyhow::Result<()> {
+        let poller = SharedPoller::new(Arc::new(Notify::new()));
+        let running = workflow_run("owner/repo", 1, "in_progress");
+        poller.publish(
+            StreamGitHubActionsRunsUseCaseOutput::new(vec![running.clone()], base_time())
+                .with_repositories(StubGitHubApi::with_repository("owner", "repo").repositories),
+        );
+        assert!(poller.recent_transitions().is_empty());
+
+        let mut failed = workflow_run("owner/repo", 1, "failure");
+        failed.updated_at = running.updated_at + chrono::TimeDelta::minutes(5);
+        assert!(poller.push_run(failed.clone()).await);
+
+        let transitions = poller.recent_transitions();
+        let [transition] = transitions.as_slice() else {
+            anyhow::bail!("expected one transition, got {transitions:?}");
+        };
+        assert_eq!(transition.run, failed);
+        assert_eq!(transition.previous_status, Some(RunStatus::InProgress));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pushed_runs_are_merged_into_the_latest_snapshot() -> anyhow::Result<()> {
+        let poller = SharedPoller::new(Arc::new(Notify::new()));
+        let running = workflow_run("owner/repo", 1, "in_progress");
+        // Nothing to merge into before the first snapshot
+        assert!(!poller.push_run(running.clone()).await);
+
+        poller.publish(
+            StreamGitHubActionsRunsUseCaseOutput::new(
+                vec![running.clone(), workflow_run("owner/repo", 0, "success")],
+                base_time(),
+            )
+            .with_repositories(StubGitHubApi::with_repository("owner", "repo").repositories),
+        );
+        let mut completed = running.clone();
+        completed.status = RunStatus::Completed;
+        completed.conclusion = Some(RunConclusion::Failure);
+        completed.updated_at = running.updated_at + chrono::TimeDelta::minutes(5);
+        assert!(poller.push_run(completed.clone()).await);
+        let latest = poller
+            .snapshots()
+            .latest()
+            .ok_or_else(|| anyhow::anyhow!("no snapshot"))?;
+        assert_eq!(
+            latest.runs,
+            vec![completed.clone(), workflow_run("owner/repo", 0, "success")]
+        );
+
+        // A new run pushes the oldest one out, as a poll limited to two runs would
+        let new = workflow_run("owner/repo", 2, "queued");
+        assert!(poller.push_run(new.clone()).await);
+        let latest = poller
+            .snapshots()
+            .latest()
+            .ok_or_else(|| anyhow::anyhow!("no snapshot"))?;
+        assert_eq!(latest.runs, vec![new, completed]);
+
+        // Late deliveries and other repositories leave the snapshot alone
+        assert!(!poller.push_run(running).await);
+        assert!(
+            !poller
+                .push_run(workflow_run("owner/other", 3, "failure"))
+                .await
+        );
+        assert_eq!(
+            poller.snapshots().latest().map(|latest| latest.runs.len()),
+            Some(2)
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reconciliation_repairs_lost_webhooks_and_ignores_stale_ones() -> anyhow::Result<()>
+    {
+        let reports = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&reports);
+        let poller = SharedPoller::new(Arc::new(Notify::new())).with_reconciliation_hook(
+            move |report: &ReconciliationReport| {
+                if let Ok(mut reports) = recorded.lock() {
+                    reports.push(report.clone());
+                }
+            },
+        );
+        let repositories = StubGitHubApi::with_repository("owner", "repo").repositories;
+        let running = workflow_run("owner/repo", 1, "in_progress");
+        let first_poll =
+            StreamGitHubActionsRunsUseCaseOutput::new(vec![running.clone()], base_time())
+                .with_repositories(repositories.clone());
+        // Without webhooks there is nothing to reconcile
+        poller.publish(poller.reconcile(first_poll));
+        assert!(poller.last_reconciliation().is_none());
+
+        // A webhook completes run 2, but the poll taken meanwhile still sees it running
+        let mut pushed = workflow_run("owner/repo", 2, "success");
+        pushed.updated_at = running.updated_at + chrono::TimeDelta::minutes(10);
+        assert!(poller.push_run(pushed.clone()).await);
+        let mut raced = workflow_run("owner/repo", 2, "in_progress");
+        raced.updated_at = pushed.updated_at - chrono::TimeDelta::minutes(1);
+        // The webhook completing run 1 was lost
+        let mut failed = workflow_run("owner/repo", 1, "failure");
+        failed.updated_at = running.updated_at + chrono::TimeDelta::minutes(5);
+        let second_poll = StreamGitHubActionsRunsUseCaseOutput::new(
+            vec![
+                raced,
+                failed.clone(),
+                workflow_run("owner/repo", 0, "success"),
+            ],
+            base_time() + chrono::TimeDelta::minutes(15),
+        )
+        .with_repositories(repositories);
+        poller.publish(poller.reconcile(second_poll));
+
+        let report = poller
+            .last_reconciliation()
+            .ok_or_else(|| anyhow::anyhow!("no reconciliation"))?;
+        assert_eq!(report.missing_runs, vec![RunId(0)]);
+        assert_eq!(report.status_mismatches.len(), 1);
+        assert_eq!(report.status_mismatches[0].run_id, RunId(1));
+        assert_eq!(reports.lock().map(|reports| reports.len()).ok(), Some(1));
+        let repaired = vec![
+            pushed.clone(),
+            failed.clone(),
+            workflow_run("owner/repo", 0, "success"),
+        ];
+        assert_eq!(
+            poller
+                .snapshots()
+                .latest()
+                .map(|latest| latest.runs.clone()),
+            Some(repaired.clone())
+        );
+
+        // A webhook delivered after the newer poll leaves the failure shown
+        assert!(!poller.push_run(running).await);
+        assert_eq!(
+            poller
+                .snapshots()
+                .latest()
+                .map(|latest| latest.runs.clone()),
+            Some(repaired)
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_notice_and_shutdown_reach_subscribers_in_order() -> anyhow::Result<()> {
+        let poller = SharedPoller::new(Arc::new(Notify::new()));
+        let mut events = poller.subscribe();
+
+        poller.notify_clients(Notice::warning("Server draining for deploy"));
+        poller.disconnect_clients();
+
+        match events.recv().await? {
+            PollerEvent::Notice(notice) => {
+                assert_eq!(*notice, Notice::warning("Server draining for deploy"));
+            }
+            event => anyhow::bail!("unexpected event: {event:?}"),
+        }
+        assert!(matches!(events.recv().await?, PollerEvent::Shutdown));
+        Ok(())
+    }
+
+    #[test]
+    fn test_notice_serializes_with_type_tag() -> anyhow::Result<()> {
+        let json = serde_json::to_value(Notice::info("hello"))?;
+        assert_eq!(
+            json,
+            serde_json::json!({"type": "info", "message": "hello"})
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_latest_is_none_before_first_snapshot() {
+        let poller = SharedPoller::new(Arc::new(Notify::new()));
+        assert!(poller.snapshots().latest().is_none());
+    }
+
+    #[test]
+    fn test_pins_and_config_changes_share_the_api_call_budget() -> anyhow::Result<()> {
+        let poller = SharedPoller::new(Arc::new(Notify::new()));
+        let pin = |run_id| {
+            poller.pin_run(PinnedRun {
+                repo: repo_full_name("owner/repo"),
+                run_id: RunId(run_id),
+            })
+        };
+        for run_id in 0..22 {
+            pin(run_id).map_err(|calls| anyhow::anyhow!("{calls} calls per hour"))?;
+        }
+
+        // A shorter interval would fetch the 22 pinned runs too often
+        let patch = StreamConfigPatch {
+            poll_interval_seconds: Some(15),
+            ..StreamConfigPatch::default()
+        };
+        assert!(poller.update_config(&patch).is_err());
+        assert!(
+            poller
+                .replace_config(StreamConfig {
+                    poll_interval_seconds: 15,
+                    ..poller.config()
+                })
+                .is_err()
+        );
+        assert_eq!(poller.config().poll_interval_seconds, 30);
+
+        // The current interval has no room left for another pin
+        assert_eq!(pin(22), Err(4_025));
+        assert_eq!(poller.pinned_runs().count(), 22);
+        Ok(())
+    }
+}
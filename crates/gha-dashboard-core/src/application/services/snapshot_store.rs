@@ -0,0 +1,240 @@
+use super::event_log::{CatchUp, EventLog, SequencedEvent};
+use super::shared_poller::PollerEvent;
+use crate::application::use_cases::stream_github_actions_runs::StreamGitHubActionsRunsUseCaseOutput;
+use sha2::{Digest, Sha256};
+use std::fmt::Write;
+use std::sync::{Arc, Mutex, PoisonError};
+use tokio::sync::{broadcast, watch};
+
+/// The latest snapshot together with a digest of its serialized form.
+#[derive(Debug, Clone)]
+pub struct LatestSnapshot {
+    /// The snapshot as published
+    pub output: Arc<StreamGitHubActionsRunsUseCaseOutput>,
+    /// Hex-encoded SHA-256 of the JSON serialization, computed once per snapshot
+    pub digest: Arc<str>,
+    /// Sequence number of the snapshot in the event log
+    pub seq: u64,
+}
+
+/// Hashes the JSON serialization of `output`, so any change in content or order changes it.
+///
+/// Repositories are hashed as well since the grouped representation embeds them.
+fn snapshot_digest(output: &StreamGitHubActionsRunsUseCaseOutput) -> Arc<str> {
+    let mut hasher = Sha256::new();
+    match serde_json::to_vec(&(output, &output.repositories)) {
+        Ok(json) => hasher.update(&json),
+        Err(e) => {
+            // Fall back to a digest that is unique per snapshot
+            tracing::error!("Failed to serialize output for digest: {:?}", e);
+            hasher.update(output.generated_at.to_rfc3339().as_bytes());
+        }
+    }
+    hasher
+        .finalize()
+        .iter()
+        .fold(String::with_capacity(64), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        })
+        .into()
+}
+
+/// The latest snapshot and the recent events, numbered, shared by the poller that publishes
+/// them and every handler and stream reading them.
+///
+/// Readers only clone an `Arc` under a lock held for as long, so they never hold up a
+/// publish; publishes are serialized so snapshots are numbered in the order they become the
+/// latest.
+#[derive(Default)]
+pub struct SnapshotStore {
+    latest: watch::Sender<Option<LatestSnapshot>>,
+    /// Every event, numbered, for clients resuming after a disconnect
+    event_log: EventLog,
+    /// Held while publishing
+    publishing: Mutex<()>,
+}
+
+impl SnapshotStore {
+    /// A store keeping the last `capacity` events for clients resuming after a disconnect.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            latest: watch::Sender::default(),
+            event_log: EventLog::new(capacity),
+            publishing: Mutex::default(),
+        }
+    }
+
+    /// Makes `output` the latest snapshot and sends it to every subscriber; returns it, with
+    /// its sequence number.
+    pub fn publish(&self, output: StreamGitHubActionsRunsUseCaseOutput) -> LatestSnapshot {
+        let digest = snapshot_digest(&output);
+        let output = Arc::new(output);
+        let _publishing = self
+            .publishing
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let seq = self
+            .event_log
+            .record(PollerEvent::Snapshot(Arc::clone(&output)));
+        let latest = LatestSnapshot {
+            output,
+            digest,
+            seq,
+        };
+        self.latest.send_replace(Some(latest.clone()));
+        latest
+    }
+
+    /// Numbers an event other than a snapshot, e.g. a notice, keeps it and sends it to every
+    /// client following the log; returns its sequence number.
+    pub fn record(&self, event: PollerEvent) -> u64 {
+        let _publishing = self
+            .publishing
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        self.event_log.record(event)
+    }
+
+    /// Returns the most recently published snapshot, if any.
+    #[must_use]
+    pub fn latest(&self) -> Option<Arc<StreamGitHubActionsRunsUseCaseOutput>> {
+        self.latest
+            .borrow()
+            .as_ref()
+            .map(|latest| Arc::clone(&latest.output))
+    }
+
+    /// Returns the most recently published snapshot along with its digest, if any.
+    #[must_use]
+    pub fn latest_with_digest(&self) -> Option<LatestSnapshot> {
+        self.latest.borrow().clone()
+    }
+
+    /// Follows the latest snapshot; a receiver that falls behind only sees the newest.
+    #[must_use]
+    pub fn subscribe(&self) -> watch::Receiver<Option<LatestSnapshot>> {
+        self.latest.subscribe()
+    }
+
+    /// The last `n` snapshots still in the event log, oldest first.
+    #[must_use]
+    pub fn recent(&self, n: usize) -> Vec<SequencedEvent> {
+        self.event_log.recent_snapshots(n)
+    }
+
+    /// Subscribes to events recorded after this call, along with what the client must be sent
+    /// first: the events after `since` when resuming, otherwise the latest snapshot.
+    #[must_use]
+    pub fn attach(&self, since: Option<u64>) -> (broadcast::Receiver<SequencedEvent>, CatchUp) {
+        self.event_log.attach(since)
+    }
+
+    /// Number of clients following the event log.
+    #[must_use]
+    pub fn receiver_count(&self) -> usize {
+        self.event_log.receiver_count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::status::RunConclusion;
+    use crate::test_support::{base_time, workflow_run};
+    use chrono::TimeDelta;
+    use std::thread;
+
+    fn snapshot(minutes: i64) -> StreamGitHubActionsRunsUseCaseOutput {
+        StreamGitHubActionsRunsUseCaseOutput::new(
+            Vec::new(),
+            base_time() + TimeDelta::minutes(minutes),
+        )
+    }
+
+    #[test]
+    fn test_digest_changes_with_content_and_order() {
+        let a = workflow_run("owner/repo", 1, "success");
+        let b = workflow_run("owner/repo", 2, "failure");
+        let output = |runs| StreamGitHubActionsRunsUseCaseOutput::new(runs, base_time());
+
+        let original = snapshot_digest(&output(vec![a.clone(), b.clone()]));
+        let same = snapshot_digest(&output(vec![a.clone(), b.clone()]));
+        let reordered = snapshot_digest(&output(vec![b.clone(), a.clone()]));
+        let mut changed_run = b.clone();
+        changed_run.conclusion = Some(RunConclusion::Success);
+        let changed = snapshot_digest(&output(vec![a, changed_run]));
+
+        assert_eq!(original, same);
+        assert_eq!(original.len(), 64);
+        assert_ne!(original, reordered);
+        assert_ne!(original, changed);
+    }
+
+    #[test]
+    fn test_readers_see_snapshots_in_publish_order() -> anyhow::Result<()> {
+        let store = Arc::new(SnapshotStore::default());
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let store = Arc::clone(&store);
+                thread::spawn(move || {
+                    let mut last_seq = 0;
+                    while last_seq < 500 {
+                        if let Some(latest) = store.latest_with_digest() {
+                            // Never older than a snapshot seen before, and always whole
+                            assert!(latest.seq >= last_seq);
+                            assert_eq!(
+                                latest.output.generated_at,
+                                base_time() + TimeDelta::minutes(i64::try_from(latest.seq)? - 1)
+                            );
+                            last_seq = latest.seq;
+                        }
+                    }
+                    anyhow::Ok(())
+                })
+            })
+            .collect();
+
+        for minutes in 0..500 {
+            store.publish(snapshot(minutes));
+        }
+
+        for reader in readers {
+            reader
+                .join()
+                .map_err(|_| anyhow::anyhow!("reader panicked"))??;
+        }
+        assert_eq!(
+            store.latest().map(|latest| latest.generated_at),
+            Some(base_time() + TimeDelta::minutes(499))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_lagging_subscribers_skip_ahead_without_holding_up_publishes() -> anyhow::Result<()> {
+        let store = SnapshotStore::new(4);
+        let mut latest = store.subscribe();
+        let (mut events, _) = store.attach(None);
+
+        for minutes in 0..100 {
+            store.publish(snapshot(minutes));
+        }
+
+        // The watch only keeps the newest, the event channel drops the oldest
+        assert!(latest.has_changed()?);
+        assert_eq!(
+            latest.borrow_and_update().as_ref().map(|latest| latest.seq),
+            Some(100)
+        );
+        assert!(matches!(
+            events.try_recv(),
+            Err(broadcast::error::TryRecvError::Lagged(_))
+        ));
+        let recent: Vec<u64> = store.recent(3).iter().map(|event| event.seq).collect();
+        assert_eq!(recent, [98, 99, 100]);
+        assert_eq!(store.recent(10).len(), 4);
+        Ok(())
+    }
+}
@@ -0,0 +1,337 @@
+use crate::domain::external_apis::github::{GitHubApi, GitHubApiError};
+use crate::domain::models::ids::{RepoFullName, WorkflowId};
+use crate::domain::models::schedule::{UpcomingRun, WorkflowSchedule};
+use crate::domain::models::workflow::Workflow;
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use tokio::sync::RwLock;
+
+/// How long the schedule read from a workflow file is reused (seconds)
+const SCHEDULE_TTL_SECONDS: i64 = 3600;
+
+/// Directory of the workflow files; workflows GitHub defines itself, such as the default
+/// `CodeQL` setup, live elsewhere and have no file to read
+const WORKFLOWS_DIRECTORY: &str = ".github/workflows/";
+
+/// Workflows of one repository as last fetched from GitHub.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct WorkflowInventoryEntry {
+    /// Every workflow of the repository, whatever its state
+    pub workflows: Vec<Workflow>,
+    /// When the workflows were fetched
+    #[serde(rename = "fetchedAt")]
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// A workflow with the next run its `schedule` trigger starts.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ScheduledWorkflow {
+    /// The workflow as listed by GitHub
+    #[serde(flatten)]
+    pub workflow: Workflow,
+    /// `None` for workflows without a schedule, and inactive ones
+    #[serde(rename = "nextScheduledAt")]
+    pub next_scheduled_at: Option<DateTime<Utc>>,
+    /// Why the schedule, or some of its cron expressions, could not be read
+    #[serde(rename = "scheduleError", skip_serializing_if = "Option::is_none")]
+    pub schedule_error: Option<String>,
+}
+
+/// A [`WorkflowInventoryEntry`] with the next scheduled run of each workflow.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ScheduledWorkflowInventory {
+    /// Every workflow of the repository, whatever its state
+    pub workflows: Vec<ScheduledWorkflow>,
+    /// When the workflows were fetched
+    #[serde(rename = "fetchedAt")]
+    pub fetched_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+struct ScheduleEntry {
+    /// `Err` with the reason when the workflow file cannot be read
+    schedule: Result<WorkflowSchedule, String>,
+    fetched_at: DateTime<Utc>,
+}
+
+/// Workflows per repository, refreshed by the poller whenever it lists repositories.
+#[derive(Debug, Default)]
+pub struct WorkflowInventory {
+    entries: RwLock<HashMap<RepoFullName, WorkflowInventoryEntry>>,
+    /// Schedules by repository and workflow file, read at most once an hour
+    schedules: RwLock<HashMap<(RepoFullName, String), ScheduleEntry>>,
+}
+
+impl WorkflowInventory {
+    /// The workflows of `repo` as last fetched, if they ever were.
+    pub async fn get(&self, repo: &RepoFullName) -> Option<WorkflowInventoryEntry> {
+        self.entries.read().await.get(repo).cloned()
+    }
+
+    /// Records the workflows of `repo` fetched at `fetched_at`, replacing earlier ones.
+    pub async fn insert(
+        &self,
+        repo: RepoFullName,
+        workflows: Vec<Workflow>,
+        fetched_at: DateTime<Utc>,
+    ) -> WorkflowInventoryEntry {
+        let entry = WorkflowInventoryEntry {
+            workflows,
+            fetched_at,
+        };
+        self.entries.write().await.insert(repo, entry.clone());
+        entry
+    }
+
+    /// Ids of every known workflow that is disabled or deleted.
+    pub async fn inactive_workflow_ids(&self) -> HashSet<WorkflowId> {
+        self.entries
+            .read()
+            .await
+            .values()
+            .flat_map(|entry| &entry.workflows)
+            .filter(|workflow| !workflow.is_active())
+            .map(|workflow| workflow.id)
+            .collect()
+    }
+
+    /// The `schedule` trigger of `workflow`, or why its workflow file cannot be read.
+    ///
+    /// Inactive workflows and workflows without a file have no schedule. Failures other than
+    /// an invalid file fall back to the last known schedule and are retried on the next call.
+    async fn schedule<G: GitHubApi + Send + Sync + ?Sized>(
+        &self,
+        github_api: &G,
+        repo: &RepoFullName,
+        workflow: &Workflow,
+        now: DateTime<Utc>,
+    ) -> Result<WorkflowSchedule, String> {
+        if !workflow.is_active() || !workflow.path.starts_with(WORKFLOWS_DIRECTORY) {
+            return Ok(WorkflowSchedule::default());
+        }
+        let key = (repo.clone(), workflow.path.clone());
+        let cached = self.schedules.read().await.get(&key).cloned();
+        if let Some(entry) = &cached
+            && now - entry.fetched_at < TimeDelta::seconds(SCHEDULE_TTL_SECONDS)
+        {
+            return entry.schedule.clone();
+        }
+
+        let schedule = match github_api
+            .fetch_workflow_definition(repo, &workflow.path, None)
+            .await
+        {
+            Ok(definition) => Ok(WorkflowSchedule::parse(&definition.schedules)),
+            // Deleted since the workflows were listed
+            Err(GitHubApiError::NotFound { .. }) => Ok(WorkflowSchedule::default()),
+            Err(e @ GitHubApiError::InvalidWorkflow { .. }) => Err(e.to_string()),
+            Err(e) => {
+                tracing::warn!("Failed to read the schedule of {}: {}", workflow.path, e);
+                return cached.map_or_else(|| Err(e.to_string()), |entry| entry.schedule);
+            }
+        };
+        self.schedules.write().await.insert(
+            key,
+            ScheduleEntry {
+                schedule: schedule.clone(),
+                fetched_at: now,
+            },
+        );
+        schedule
+    }
+
+    /// `entry` of `repo` with the next run each workflow's schedule starts after `now`.
+    pub async fn scheduled<G: GitHubApi + Send + Sync + ?Sized>(
+        &self,
+        github_api: &G,
+        repo: &RepoFullName,
+        entry: WorkflowInventoryEntry,
+        now: DateTime<Utc>,
+    ) -> ScheduledWorkflowInventory {
+        let mut workflows = Vec::with_capacity(entry.workflows.len());
+        for workflow in entry.workflows {
+            let (next_scheduled_at, schedule_error) =
+                match self.schedule(github_api, repo, &workflow, now).await {
+                    Ok(schedule) => (schedule.next_after(now), schedule.error()),
+                    Err(e) => (None, Some(e)),
+                };
+            workflows.push(ScheduledWorkflow {
+                workflow,
+                next_scheduled_at,
+                schedule_error,
+            });
+        }
+        ScheduledWorkflowInventory {
+            workflows,
+            fetched_at: entry.fetched_at,
+        }
+    }
+
+    /// The next scheduled run of every workflow of `repos` in the inventory, soonest first.
+    pub async fn upcoming_runs<G: GitHubApi + Send + Sync + ?Sized>(
+        &self,
+        github_api: &G,
+        repos: &[RepoFullName],
+        now: DateTime<Utc>,
+    ) -> Vec<UpcomingRun> {
+        let mut upcoming = Vec::new();
+        for repo in repos {
+            let Some(entry) = self.get(repo).await else {
+                continue;
+            };
+            for workflow in &entry.workflows {
+                if let Ok(schedule) = self.schedule(github_api, repo, workflow, now).await
+                    && let Some(scheduled_at) = schedule.next_after(now)
+                {
+                    upcoming.push(UpcomingRun {
+                        repository_name: repo.clone(),
+                        workflow_id: workflow.id,
+                        workflow_name: workflow.name.clone(),
+                        scheduled_at,
+                    });
+                }
+            }
+        }
+        upcoming.sort_by_key(|run| (run.scheduled_at, run.workflow_id));
+        upcoming
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::workflow::WorkflowState;
+    use crate::domain::models::workflow_definition::WorkflowDefinition;
+    use crate::test_support::{base_time, repo_full_name, workflow};
+    use crate::testing::ScriptedGitHubApi;
+
+    fn schedules(crons: &[&str]) -> WorkflowDefinition {
+        WorkflowDefinition {
+            schedules: crons.iter().map(ToString::to_string).collect(),
+            ..WorkflowDefinition::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_inactive_workflows_across_repositories() {
+        let inventory = WorkflowInventory::default();
+        inventory
+            .insert(
+                repo_full_name("owner/repo"),
+                vec![
+                    workflow(1, WorkflowState::Active),
+                    workflow(2, WorkflowState::DisabledManually),
+                ],
+                base_time(),
+            )
+            .await;
+        inventory
+            .insert(
+                repo_full_name("owner/other"),
+                vec![workflow(3, WorkflowState::DisabledInactivity)],
+                base_time(),
+            )
+            .await;
+
+        assert_eq!(
+            inventory.inactive_workflow_ids().await,
+            HashSet::from([WorkflowId(2), WorkflowId(3)])
+        );
+        assert_eq!(
+            inventory
+                .get(&repo_full_name("owner/repo"))
+                .await
+                .map(|entry| entry.workflows.len()),
+            Some(2)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_schedules_are_predicted_and_reported_per_workflow() -> anyhow::Result<()> {
+        let repo = repo_full_name("owner/repo");
+        let github_api = ScriptedGitHubApi::new();
+        github_api
+            .workflow_definition(
+                &repo,
+                ".github/workflows/workflow-1.yml",
+                Ok(schedules(&["0 12 * * *", "30 10 * * *"])),
+            )
+            .workflow_definition(
+                &repo,
+                ".github/workflows/workflow-2.yml",
+                Ok(schedules(&["0 11 * * *", "61 * * * *"])),
+            )
+            .workflow_definition(
+                &repo,
+                ".github/workflows/workflow-3.yml",
+                GitHubApiError::InvalidWorkflow {
+                    resource: ".github/workflows/workflow-3.yml of owner/repo".to_string(),
+                    message: "line 2: unexpected indentation".to_string(),
+                },
+            );
+        let inventory = WorkflowInventory::default();
+        let entry = inventory
+            .insert(
+                repo.clone(),
+                vec![
+                    workflow(1, WorkflowState::Active),
+                    workflow(2, WorkflowState::Active),
+                    workflow(3, WorkflowState::Active),
+                    workflow(4, WorkflowState::DisabledManually),
+                ],
+                base_time(),
+            )
+            .await;
+
+        let scheduled = inventory
+            .scheduled(&github_api, &repo, entry, base_time())
+            .await;
+        let upcoming = inventory
+            .upcoming_runs(&github_api, std::slice::from_ref(&repo), base_time())
+            .await;
+
+        let next = |hour: i64, minute: i64| {
+            Some(base_time() + TimeDelta::hours(hour) + TimeDelta::minutes(minute))
+        };
+        let predictions: Vec<_> = scheduled
+            .workflows
+            .iter()
+            .map(|workflow| {
+                (
+                    workflow.next_scheduled_at,
+                    workflow.schedule_error.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            predictions,
+            [
+                (next(0, 30), None),
+                (
+                    next(1, 0),
+                    Some(
+                        r#"invalid cron expression "61 * * * *": minute 61 is not between 0 and 59"#
+                    )
+                ),
+                (
+                    None,
+                    Some(
+                        "Invalid workflow file .github/workflows/workflow-3.yml of owner/repo: line 2: unexpected indentation"
+                    )
+                ),
+                (None, None),
+            ]
+        );
+        assert_eq!(
+            upcoming
+                .iter()
+                .map(|run| (run.workflow_id, Some(run.scheduled_at)))
+                .collect::<Vec<_>>(),
+            [(WorkflowId(1), next(0, 30)), (WorkflowId(2), next(1, 0))]
+        );
+        // Each file is read once within the hour
+        assert_eq!(github_api.calls().len(), 3);
+        Ok(())
+    }
+}
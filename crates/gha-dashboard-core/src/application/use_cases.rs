@@ -0,0 +1,25 @@
+/// Actions minutes and cost of recent runs.
+pub mod cost_estimation;
+/// Weekly digests of the run history.
+pub mod generate_digest;
+/// Compliance of the service level objectives over the run history.
+pub mod slo_evaluation;
+/// Snapshots of the latest runs of the polled repositories.
+pub mod stream_github_actions_runs;
+
+pub use cost_estimation::{
+    CostEstimationInput, CostEstimationInteractor, CostEstimationOutput, CostEstimationUseCase,
+    PriceTable,
+};
+pub use generate_digest::{
+    GenerateDigestInput, GenerateDigestInteractor, GenerateDigestUseCase, digest,
+};
+pub use slo_evaluation::{
+    SloCompliance, SloEvaluationInput, SloEvaluationInteractor, SloEvaluationOutput,
+    SloEvaluationUseCase, SloStatus, SloViolation, ViolationReason,
+};
+pub use stream_github_actions_runs::{
+    SCHEMA_VERSION, StreamGitHubActionsRunsError, StreamGitHubActionsRunsInteractor,
+    StreamGitHubActionsRunsUseCase, StreamGitHubActionsRunsUseCaseInput,
+    StreamGitHubActionsRunsUseCaseOutput,
+};
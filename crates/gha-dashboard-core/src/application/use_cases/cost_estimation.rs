@@ -0,0 +1,506 @@
+use crate::domain::external_apis::github::{GitHubApi, GitHubApiError};
+use crate::domain::models::ids::{JobId, RepoFullName, RunId, WorkflowId};
+use crate::domain::models::job::Job;
+use crate::domain::models::run::WorkflowRun;
+use crate::domain::models::status::RunStatus;
+use crate::domain::models::timing::{RunTiming, RunnerOs};
+use async_trait::async_trait;
+use futures_util::{StreamExt, stream};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// GitHub bills every job by the minute, rounding up
+const MILLIS_PER_MINUTE: u64 = 60_000;
+
+/// Runs whose timing and jobs are fetched at the same time
+const CONCURRENT_FETCHES: usize = 4;
+
+/// Runner label GitHub gives every self-hosted runner
+const SELF_HOSTED_LABEL: &str = "self-hosted";
+
+/// Price per minute of each kind of runner, in dollars; the `[costs]` section of the config.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize)]
+#[serde(default)]
+pub struct PriceTable {
+    /// Linux runners
+    pub ubuntu: f64,
+    /// Windows runners
+    pub windows: f64,
+    /// macOS runners
+    pub macos: f64,
+    /// Attributes the cost of your own runners; GitHub does not bill them
+    pub self_hosted: f64,
+}
+
+impl Default for PriceTable {
+    /// GitHub's list prices for standard hosted runners.
+    fn default() -> Self {
+        Self {
+            ubuntu: 0.008,
+            windows: 0.016,
+            macos: 0.08,
+            self_hosted: 0.0,
+        }
+    }
+}
+
+impl PriceTable {
+    fn per_minute(&self, os: RunnerOs) -> f64 {
+        match os {
+            RunnerOs::Ubuntu => self.ubuntu,
+            RunnerOs::Windows => self.windows,
+            RunnerOs::Macos => self.macos,
+        }
+    }
+}
+
+/// `minutes` at `price` per minute; minute counts stay far below `u32::MAX`.
+fn cost(minutes: u64, price: f64) -> f64 {
+    f64::from(u32::try_from(minutes).unwrap_or(u32::MAX)) * price
+}
+
+/// Minutes a run was billed for, and minutes it spent on self-hosted runners.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RunnerMinutes {
+    /// Minutes on GitHub-hosted runners, each job rounded up to a whole minute
+    #[serde(rename = "billableMinutes")]
+    pub billable: BTreeMap<RunnerOs, u64>,
+    /// Minutes on self-hosted runners, which GitHub does not bill
+    #[serde(rename = "selfHostedMinutes")]
+    pub self_hosted: u64,
+}
+
+impl RunnerMinutes {
+    /// Splits `timing` by the runners `jobs` ran on.
+    ///
+    /// Jobs labelled `self-hosted` are never billed, even if the timing lists them.
+    #[must_use]
+    pub fn new(timing: &RunTiming, jobs: &[Job]) -> Self {
+        let self_hosted_jobs: HashSet<JobId> = jobs
+            .iter()
+            .filter(|job| {
+                job.labels
+                    .iter()
+                    .any(|label| label.eq_ignore_ascii_case(SELF_HOSTED_LABEL))
+            })
+            .map(|job| job.id)
+            .collect();
+        let billable = timing
+            .billable
+            .iter()
+            .map(|(os, time)| {
+                let minutes = if time.job_runs.is_empty() {
+                    time.total_ms.div_ceil(MILLIS_PER_MINUTE)
+                } else {
+                    time.job_runs
+                        .iter()
+                        .filter(|job_run| !self_hosted_jobs.contains(&job_run.job_id))
+                        .map(|job_run| job_run.duration_ms.div_ceil(MILLIS_PER_MINUTE))
+                        .sum()
+                };
+                (*os, minutes)
+            })
+            .filter(|(_, minutes)| *minutes > 0)
+            .collect();
+        let self_hosted = jobs
+            .iter()
+            .filter(|job| self_hosted_jobs.contains(&job.id))
+            .filter_map(Job::duration)
+            .map(|duration| {
+                u64::try_from(duration.as_millis())
+                    .unwrap_or(u64::MAX)
+                    .div_ceil(MILLIS_PER_MINUTE)
+            })
+            .sum();
+        Self {
+            billable,
+            self_hosted,
+        }
+    }
+
+    fn add(&mut self, other: &Self) {
+        for (os, minutes) in &other.billable {
+            *self.billable.entry(*os).or_default() += minutes;
+        }
+        self.self_hosted += other.self_hosted;
+    }
+
+    fn cost(&self, prices: &PriceTable) -> f64 {
+        self.billable
+            .iter()
+            .map(|(os, minutes)| cost(*minutes, prices.per_minute(*os)))
+            .sum::<f64>()
+            + cost(self.self_hosted, prices.self_hosted)
+    }
+}
+
+/// Estimated spend of one workflow.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WorkflowCost {
+    /// The workflow the runs belong to
+    #[serde(rename = "workflowId")]
+    pub workflow_id: WorkflowId,
+    /// Name of the workflow in its newest run
+    #[serde(rename = "workflowName")]
+    pub workflow_name: String,
+    /// Runs estimated
+    pub runs: u64,
+    /// Minutes of the runs estimated
+    #[serde(flatten)]
+    pub minutes: RunnerMinutes,
+    /// In dollars
+    #[serde(rename = "estimatedCost")]
+    pub estimated_cost: f64,
+}
+
+/// Estimated spend of one repository, with its workflows by name.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RepositoryCost {
+    /// The repository the workflows belong to
+    pub repository: RepoFullName,
+    /// Minutes of every workflow of the repository
+    #[serde(flatten)]
+    pub minutes: RunnerMinutes,
+    /// In dollars
+    #[serde(rename = "estimatedCost")]
+    pub estimated_cost: f64,
+    /// By name
+    pub workflows: Vec<WorkflowCost>,
+}
+
+/// Runs to estimate; runs that have not completed yet are skipped.
+#[derive(Debug, Clone, Default)]
+pub struct CostEstimationInput {
+    /// Runs of any repository, in any order
+    pub runs: Vec<WorkflowRun>,
+}
+
+/// Estimated spend of the runs of a [`CostEstimationInput`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CostEstimationOutput {
+    /// Repositories by name
+    pub repositories: Vec<RepositoryCost>,
+    /// Total of the repositories, in dollars
+    #[serde(rename = "estimatedCost")]
+    pub estimated_cost: f64,
+    /// Completed runs whose timing or jobs GitHub could not provide, left out of the totals
+    #[serde(rename = "unestimatedRuns")]
+    pub unestimated_runs: u64,
+}
+
+/// Estimates the Actions spend of runs.
+#[async_trait]
+pub trait CostEstimationUseCase {
+    /// Prices the completed runs of `input`.
+    async fn execute(&self, input: CostEstimationInput) -> CostEstimationOutput;
+}
+
+/// Estimates spend from GitHub's run timing, remembering the minutes of every run it has
+/// seen since completed runs never change.
+pub struct CostEstimationInteractor<G: GitHubApi + Send + Sync + ?Sized + 'static> {
+    github_api: Arc<G>,
+    prices: PriceTable,
+    minutes: Mutex<HashMap<RunId, RunnerMinutes>>,
+}
+
+impl<G: GitHubApi + Send + Sync + ?Sized + 'static> CostEstimationInteractor<G> {
+    /// Prices the runs `github_api` times at `prices`.
+    pub fn new(github_api: Arc<G>, prices: PriceTable) -> Self {
+        Self {
+            github_api,
+            prices,
+            minutes: Mutex::default(),
+        }
+    }
+
+    async fn run_minutes(&self, run: &WorkflowRun) -> Result<RunnerMinutes, GitHubApiError> {
+        let cached = self
+            .minutes
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&run.id)
+            .cloned();
+        if let Some(minutes) = cached {
+            return Ok(minutes);
+        }
+        let repo = &run.repository_name;
+        let (timing, jobs) = tokio::try_join!(
+            self.github_api.fetch_run_timing(repo, run.id),
+            self.github_api.fetch_workflow_jobs(repo, run.id),
+        )?;
+        let minutes = RunnerMinutes::new(&timing, &jobs);
+        self.minutes
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(run.id, minutes.clone());
+        Ok(minutes)
+    }
+}
+
+#[async_trait]
+impl<G: GitHubApi + Send + Sync + ?Sized + 'static> CostEstimationUseCase
+    for CostEstimationInteractor<G>
+{
+    async fn execute(&self, input: CostEstimationInput) -> CostEstimationOutput {
+        let mut completed = input.runs;
+        completed.retain(|run| run.status == RunStatus::Completed);
+        let estimated: Vec<_> = stream::iter(completed)
+            .map(|run| async move {
+                let minutes = self.run_minutes(&run).await;
+                (run, minutes)
+            })
+            .buffer_unordered(CONCURRENT_FETCHES)
+            .collect()
+            .await;
+
+        let mut unestimated_runs = 0;
+        let mut workflows: BTreeMap<(RepoFullName, WorkflowId), WorkflowCost> = BTreeMap::new();
+        for (run, minutes) in estimated {
+            match minutes {
+                Ok(minutes) => {
+                    let workflow = workflows
+                        .entry((run.repository_name.clone(), run.workflow_id))
+                        .or_insert_with(|| WorkflowCost {
+                            workflow_id: run.workflow_id,
+                            workflow_name: run.workflow_name.clone(),
+                            runs: 0,
+                            minutes: RunnerMinutes::default(),
+                            estimated_cost: 0.0,
+                        });
+                    workflow.runs += 1;
+                    workflow.minutes.add(&minutes);
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to estimate the cost of run {}: {}", run.id, e);
+                    unestimated_runs += 1;
+                }
+            }
+        }
+        self.summarize(workflows, unestimated_runs)
+    }
+}
+
+impl<G: GitHubApi + Send + Sync + ?Sized + 'static> CostEstimationInteractor<G> {
+    /// Prices `workflows` and groups them by repository.
+    fn summarize(
+        &self,
+        workflows: BTreeMap<(RepoFullName, WorkflowId), WorkflowCost>,
+        unestimated_runs: u64,
+    ) -> CostEstimationOutput {
+        let mut repositories: Vec<RepositoryCost> = Vec::new();
+        for ((repository, _), mut workflow) in workflows {
+            workflow.estimated_cost = workflow.minutes.cost(&self.prices);
+            match repositories.last_mut() {
+                Some(last) if last.repository == repository => last.workflows.push(workflow),
+                _ => repositories.push(RepositoryCost {
+                    repository,
+                    minutes: RunnerMinutes::default(),
+                    estimated_cost: 0.0,
+                    workflows: vec![workflow],
+                }),
+            }
+        }
+        for repository in &mut repositories {
+            for workflow in &repository.workflows {
+                repository.minutes.add(&workflow.minutes);
+            }
+            repository.estimated_cost = repository.minutes.cost(&self.prices);
+            repository
+                .workflows
+                .sort_by(|a, b| a.workflow_name.cmp(&b.workflow_name));
+        }
+        CostEstimationOutput {
+            estimated_cost: repositories
+                .iter()
+                .map(|repository| repository.estimated_cost)
+                .sum(),
+            repositories,
+            unestimated_runs,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::status::RunConclusion;
+    use crate::domain::models::timing::{BillableTime, JobTiming};
+    use crate::test_support::base_time;
+    use crate::testing::{ScriptedGitHubApi, WorkflowRunBuilder};
+    use chrono::TimeDelta;
+
+    fn job(id: u64, run_id: u64, label: &str, seconds: i64) -> Job {
+        Job {
+            id: JobId(id),
+            run_id: RunId(run_id),
+            name: format!("job-{id}"),
+            status: RunStatus::Completed,
+            conclusion: Some(RunConclusion::Success),
+            started_at: Some(base_time()),
+            completed_at: Some(base_time() + TimeDelta::seconds(seconds)),
+            runner_name: Some("runner".to_string()),
+            labels: vec![label.to_string()],
+            html_url: String::new(),
+            steps: Vec::new(),
+        }
+    }
+
+    fn billable(job_runs: &[(u64, u64)]) -> BillableTime {
+        BillableTime {
+            total_ms: job_runs.iter().map(|(_, duration_ms)| duration_ms).sum(),
+            job_runs: job_runs
+                .iter()
+                .map(|(job_id, duration_ms)| JobTiming {
+                    job_id: JobId(*job_id),
+                    duration_ms: *duration_ms,
+                })
+                .collect(),
+        }
+    }
+
+    fn run(repository: &str, id: u64, workflow: (u64, &str)) -> WorkflowRun {
+        WorkflowRunBuilder::new(repository)
+            .id(id)
+            .workflow(workflow.0, workflow.1)
+            .conclusion(RunConclusion::Success)
+            .build()
+    }
+
+    /// Run 1 uses Ubuntu and Windows, run 2 macOS and a self-hosted runner that the timing
+    /// lists under Ubuntu, and the timing of run 3 is unavailable.
+    fn github_api() -> ScriptedGitHubApi {
+        let github_api = ScriptedGitHubApi::new();
+        github_api
+            .run_timing(
+                RunId(1),
+                Ok(RunTiming {
+                    billable: [
+                        (RunnerOs::Ubuntu, billable(&[(11, 61_000), (12, 30_000)])),
+                        (RunnerOs::Windows, billable(&[(13, 120_000)])),
+                    ]
+                    .into(),
+                    run_duration_ms: Some(130_000),
+                }),
+            )
+            .workflow_jobs(
+                RunId(1),
+                Ok(vec![
+                    job(11, 1, "ubuntu-latest", 61),
+                    job(12, 1, "ubuntu-latest", 30),
+                    job(13, 1, "windows-latest", 120),
+                ]),
+            )
+            .run_timing(
+                RunId(2),
+                Ok(RunTiming {
+                    billable: [
+                        (
+                            RunnerOs::Macos,
+                            BillableTime {
+                                total_ms: 90_000,
+                                job_runs: Vec::new(),
+                            },
+                        ),
+                        (RunnerOs::Ubuntu, billable(&[(21, 150_000)])),
+                    ]
+                    .into(),
+                    run_duration_ms: Some(150_000),
+                }),
+            )
+            .workflow_jobs(
+                RunId(2),
+                Ok(vec![
+                    job(21, 2, "self-hosted", 150),
+                    job(22, 2, "macos-latest", 90),
+                ]),
+            )
+            .run_timing(
+                RunId(3),
+                GitHubApiError::NotFound {
+                    resource: "timing".to_string(),
+                },
+            )
+            .workflow_jobs(RunId(3), Ok(Vec::new()));
+        github_api
+    }
+
+    #[test]
+    fn test_each_job_is_rounded_up_to_a_whole_minute() {
+        let timing = RunTiming {
+            billable: [(
+                RunnerOs::Ubuntu,
+                billable(&[(1, 60_000), (2, 60_001), (3, 1)]),
+            )]
+            .into(),
+            run_duration_ms: None,
+        };
+
+        let minutes = RunnerMinutes::new(&timing, &[]);
+
+        assert_eq!(minutes.billable, [(RunnerOs::Ubuntu, 4)].into());
+        assert_eq!(minutes.self_hosted, 0);
+    }
+
+    #[tokio::test]
+    async fn test_costs_are_priced_per_os_and_exclude_self_hosted_runners() -> anyhow::Result<()> {
+        let interactor =
+            CostEstimationInteractor::new(Arc::new(github_api()), PriceTable::default());
+        let in_progress = WorkflowRunBuilder::new("owner/repo")
+            .id(4)
+            .status(RunStatus::InProgress)
+            .build();
+
+        let output = interactor
+            .execute(CostEstimationInput {
+                runs: vec![
+                    run("owner/repo", 1, (1, "CI")),
+                    run("owner/repo", 2, (1, "CI")),
+                    run("owner/other", 3, (2, "Deploy")),
+                    in_progress,
+                ],
+            })
+            .await;
+
+        assert_eq!(output.unestimated_runs, 1);
+        assert_eq!(output.repositories.len(), 1);
+        let repository = &output.repositories[0];
+        assert_eq!(repository.workflows[0].runs, 2);
+        assert_eq!(
+            repository.minutes.billable,
+            [
+                (RunnerOs::Ubuntu, 3),
+                (RunnerOs::Windows, 2),
+                (RunnerOs::Macos, 2)
+            ]
+            .into()
+        );
+        assert_eq!(repository.minutes.self_hosted, 3);
+        // 3 Ubuntu minutes at 0.008, 2 Windows at 0.016 and 2 macOS at 0.08
+        assert!((repository.estimated_cost - 0.216).abs() < 1e-9);
+        assert!((output.estimated_cost - repository.estimated_cost).abs() < f64::EPSILON);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_self_hosted_minutes_are_priced_when_configured() -> anyhow::Result<()> {
+        let github_api = Arc::new(github_api());
+        let prices = PriceTable {
+            self_hosted: 0.01,
+            ..PriceTable::default()
+        };
+        let interactor = CostEstimationInteractor::new(Arc::clone(&github_api), prices);
+        let input = CostEstimationInput {
+            runs: vec![run("owner/repo", 2, (1, "CI"))],
+        };
+
+        let output = interactor.execute(input.clone()).await;
+        interactor.execute(input).await;
+
+        // 2 macOS minutes at 0.08 and 3 self-hosted at 0.01
+        assert!((output.estimated_cost - 0.19).abs() < 1e-9);
+        // The second estimate reuses the minutes of the completed run
+        assert_eq!(github_api.calls().len(), 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,322 @@
+use crate::domain::models::digest::{
+    Digest, IsoWeek, Regression, RegressionKind, RepositoryDigest, WorkflowDigest,
+};
+use crate::domain::models::ids::{RepoFullName, WorkflowId};
+use crate::domain::models::run_comparison::DEFAULT_REGRESSION_THRESHOLD_PERCENT;
+use crate::domain::models::status::RunConclusion;
+use crate::domain::repositories::{DailyStats, RunRepository, RunRepositoryError};
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Workflows listed as the slowest of a repository
+const MAX_SLOWEST_WORKFLOWS: usize = 3;
+
+/// Runs a workflow needs in each week to be compared, so one failure of a rare workflow is
+/// not a regression
+const MIN_COMPARED_RUNS: u64 = 3;
+
+/// Rise of the failure rate, from 0 to 1, flagged as a regression
+const FAILURE_RATE_REGRESSION: f64 = 0.1;
+
+/// `runs` as a float; run counts stay far below `u32::MAX`.
+fn float(runs: u64) -> f64 {
+    f64::from(u32::try_from(runs).unwrap_or(u32::MAX))
+}
+
+/// The daily stats of one workflow summed over a week.
+#[derive(Debug, Default)]
+struct WorkflowTotals {
+    name: String,
+    runs: u64,
+    failed_runs: u64,
+    duration_seconds: f64,
+}
+
+impl WorkflowTotals {
+    fn add(&mut self, stats: &DailyStats) {
+        // Stats are oldest first, so the latest name wins
+        self.name.clone_from(&stats.workflow_name);
+        self.runs += stats.runs;
+        self.failed_runs += stats
+            .conclusions
+            .iter()
+            .filter(|(conclusion, _)| RunConclusion::is_problem(**conclusion))
+            .map(|(_, runs)| runs)
+            .sum::<u64>();
+        self.duration_seconds += stats.mean_duration_seconds * float(stats.runs);
+    }
+
+    fn failure_rate(&self) -> f64 {
+        float(self.failed_runs) / float(self.runs)
+    }
+
+    fn mean_duration_seconds(&self) -> f64 {
+        self.duration_seconds / float(self.runs)
+    }
+}
+
+/// Sums the stats of the days of `week` per workflow.
+fn totals(week: IsoWeek, stats: &[DailyStats]) -> BTreeMap<WorkflowId, WorkflowTotals> {
+    let mut totals: BTreeMap<WorkflowId, WorkflowTotals> = BTreeMap::new();
+    for stats in stats
+        .iter()
+        .filter(|stats| week.contains(stats.day) && stats.runs > 0)
+    {
+        totals.entry(stats.workflow_id).or_default().add(stats);
+    }
+    totals
+}
+
+/// How the workflow fared in `current` compared with `previous`, if notably worse.
+fn regressions(
+    workflow_id: WorkflowId,
+    current: &WorkflowTotals,
+    previous: &WorkflowTotals,
+) -> Vec<Regression> {
+    if current.runs < MIN_COMPARED_RUNS || previous.runs < MIN_COMPARED_RUNS {
+        return Vec::new();
+    }
+    let mut regressions = Vec::new();
+    let (before, now) = (previous.failure_rate(), current.failure_rate());
+    if now - before >= FAILURE_RATE_REGRESSION {
+        regressions.push(Regression {
+            workflow_id,
+            workflow_name: current.name.clone(),
+            kind: RegressionKind::FailureRate,
+            previous: before,
+            current: now,
+        });
+    }
+    let (before, now) = (
+        previous.mean_duration_seconds(),
+        current.mean_duration_seconds(),
+    );
+    if before > 0.0 && (now - before) * 100.0 / before > DEFAULT_REGRESSION_THRESHOLD_PERCENT {
+        regressions.push(Regression {
+            workflow_id,
+            workflow_name: current.name.clone(),
+            kind: RegressionKind::Duration,
+            previous: before,
+            current: now,
+        });
+    }
+    regressions
+}
+
+/// Summarizes `repository` over `week` out of its daily stats of that week and the one
+/// before; `None` without runs during the week.
+fn repository_digest(
+    week: IsoWeek,
+    repository: RepoFullName,
+    stats: &[DailyStats],
+) -> Option<RepositoryDigest> {
+    let current = totals(week, stats);
+    if current.is_empty() {
+        return None;
+    }
+    let previous = totals(week.previous(), stats);
+
+    let runs = current.values().map(|totals| totals.runs).sum();
+    let failed_runs = current.values().map(|totals| totals.failed_runs).sum();
+    let mut slowest_workflows: Vec<WorkflowDigest> = current
+        .iter()
+        .map(|(&workflow_id, totals)| WorkflowDigest {
+            workflow_id,
+            workflow_name: totals.name.clone(),
+            runs: totals.runs,
+            failure_rate: totals.failure_rate(),
+            mean_duration_seconds: totals.mean_duration_seconds(),
+        })
+        .collect();
+    slowest_workflows.sort_by(|a, b| {
+        b.mean_duration_seconds
+            .total_cmp(&a.mean_duration_seconds)
+            .then_with(|| a.workflow_name.cmp(&b.workflow_name))
+    });
+    slowest_workflows.truncate(MAX_SLOWEST_WORKFLOWS);
+    let regressions = current
+        .iter()
+        .filter_map(|(workflow_id, totals)| {
+            let previous = previous.get(workflow_id)?;
+            Some(regressions(*workflow_id, totals, previous))
+        })
+        .flatten()
+        .collect();
+    Some(RepositoryDigest {
+        repository,
+        runs,
+        failed_runs,
+        failure_rate: float(failed_runs) / float(runs),
+        slowest_workflows,
+        regressions,
+    })
+}
+
+/// Summarizes `week` out of the daily stats of each repository over that week and the one
+/// before.
+#[must_use]
+pub fn digest(week: IsoWeek, stats: Vec<(RepoFullName, Vec<DailyStats>)>) -> Digest {
+    let repositories: Vec<RepositoryDigest> = stats
+        .into_iter()
+        .filter_map(|(repository, stats)| repository_digest(week, repository, &stats))
+        .collect();
+    let runs = repositories.iter().map(|repository| repository.runs).sum();
+    let failed_runs = repositories
+        .iter()
+        .map(|repository| repository.failed_runs)
+        .sum();
+    Digest {
+        week,
+        since: week.start(),
+        until: week.end(),
+        runs,
+        failed_runs,
+        failure_rate: (runs > 0).then(|| float(failed_runs) / float(runs)),
+        repositories,
+    }
+}
+
+/// The week to summarize.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GenerateDigestInput {
+    /// ISO week, in UTC
+    pub week: IsoWeek,
+}
+
+/// Summarizes a week of the run history.
+#[async_trait]
+pub trait GenerateDigestUseCase {
+    /// Summarizes the week of `input`, comparing it with the week before.
+    async fn execute(&self, input: GenerateDigestInput) -> Result<Digest, RunRepositoryError>;
+}
+
+/// Summarizes weeks out of the daily stats of the stored run history, which outlive the runs
+/// compacted away.
+pub struct GenerateDigestInteractor<R: RunRepository + Send + Sync + ?Sized> {
+    run_repository: Arc<R>,
+}
+
+impl<R: RunRepository + Send + Sync + ?Sized> GenerateDigestInteractor<R> {
+    /// Reads the stats from `run_repository`.
+    pub fn new(run_repository: Arc<R>) -> Self {
+        Self { run_repository }
+    }
+}
+
+#[async_trait]
+impl<R: RunRepository + Send + Sync + ?Sized> GenerateDigestUseCase
+    for GenerateDigestInteractor<R>
+{
+    async fn execute(&self, input: GenerateDigestInput) -> Result<Digest, RunRepositoryError> {
+        let since = input.week.previous().monday();
+        let repositories = self.run_repository.stats_repositories(since).await?;
+        let mut stats = Vec::with_capacity(repositories.len());
+        for repository in repositories {
+            let daily_stats = self.run_repository.daily_stats(&repository, since).await?;
+            stats.push((repository, daily_stats));
+        }
+        Ok(digest(input.week, stats))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Days, NaiveDate};
+    use std::collections::HashMap;
+
+    fn stats(
+        week: IsoWeek,
+        workflow: u64,
+        runs: u64,
+        failed: u64,
+        mean_duration_seconds: f64,
+    ) -> DailyStats {
+        DailyStats {
+            day: week.monday() + Days::new(2),
+            workflow_id: WorkflowId(workflow),
+            workflow_name: format!("Workflow {workflow}"),
+            runs,
+            conclusions: HashMap::from([
+                (RunConclusion::Success, runs - failed),
+                (RunConclusion::Failure, failed),
+            ]),
+            success_rate: float(runs - failed) / float(runs),
+            mean_duration_seconds,
+        }
+    }
+
+    #[test]
+    fn test_digest_sums_the_week_and_flags_regressions() -> anyhow::Result<()> {
+        let week: IsoWeek = "2024-W32".parse()?;
+        let before = week.previous();
+        let mut monday = stats(week, 1, 4, 0, 100.0);
+        monday.day = week.monday();
+        let next_week = stats(week, 1, 50, 50, 900.0);
+        let next_week = DailyStats {
+            day: week.monday() + Days::new(7),
+            ..next_week
+        };
+        let repo_stats = vec![
+            stats(before, 1, 4, 0, 100.0),
+            stats(before, 2, 10, 0, 600.0),
+            stats(before, 3, 1, 0, 60.0),
+            monday,
+            stats(week, 1, 4, 2, 100.0),
+            stats(week, 2, 10, 0, 800.0),
+            stats(week, 3, 1, 1, 300.0),
+            stats(week, 4, 2, 0, 30.0),
+            next_week,
+        ];
+
+        let digest = digest(
+            week,
+            vec![
+                ("owner/repo".parse()?, repo_stats),
+                ("owner/idle".parse()?, vec![stats(before, 1, 3, 0, 10.0)]),
+            ],
+        );
+
+        assert_eq!((digest.runs, digest.failed_runs), (21, 3));
+        assert_eq!(digest.repositories.len(), 1);
+        let repo = &digest.repositories[0];
+        let slowest: Vec<(u64, u64)> = repo
+            .slowest_workflows
+            .iter()
+            .map(|workflow| (workflow.workflow_id.0, workflow.runs))
+            .collect();
+        assert_eq!(slowest, [(2, 10), (3, 1), (1, 8)]);
+        let regressions: Vec<(u64, RegressionKind)> = repo
+            .regressions
+            .iter()
+            .map(|regression| (regression.workflow_id.0, regression.kind))
+            .collect();
+        // Workflow 3 ran too rarely to be compared
+        assert_eq!(
+            regressions,
+            [
+                (1, RegressionKind::FailureRate),
+                (2, RegressionKind::Duration)
+            ]
+        );
+        assert!((repo.regressions[0].current - 0.25).abs() < f64::EPSILON);
+        Ok(())
+    }
+
+    #[test]
+    fn test_weeks_without_stats_have_an_empty_digest() -> anyhow::Result<()> {
+        let week: IsoWeek = "2024-W01".parse()?;
+
+        let digest = digest(week, Vec::new());
+
+        assert_eq!(digest.runs, 0);
+        assert_eq!(digest.failure_rate, None);
+        assert!(digest.repositories.is_empty());
+        assert_eq!(
+            digest.since.date_naive(),
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap_or_default()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,365 @@
+use crate::domain::models::ids::RunId;
+use crate::domain::models::run::WorkflowRun;
+use crate::domain::models::slo::{Slo, SloOutcome};
+use crate::domain::models::status::{RunConclusion, RunStatus};
+use crate::domain::repositories::{RunQuery, RunRepository, RunRepositoryError};
+use async_trait::async_trait;
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::sync::Arc;
+
+/// Most stored runs of one repository read per objective
+const MAX_EVALUATED_RUNS: usize = 50_000;
+
+/// Violations listed per objective, newest first
+const MAX_RECENT_VIOLATIONS: usize = 10;
+
+/// Whether an objective holds over its window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SloStatus {
+    /// No run in the window counts towards the objective
+    NoData,
+    /// At least the target share of runs met the objective
+    Met,
+    /// Fewer than the target share of runs met the objective
+    Violated,
+}
+
+/// Why a run did not meet an objective.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ViolationReason {
+    /// It completed without succeeding
+    Failed,
+    /// It succeeded after the target duration
+    Slow,
+}
+
+/// A run that did not meet an objective.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SloViolation {
+    /// The run
+    #[serde(rename = "runId")]
+    pub run_id: RunId,
+    /// Number of the run within its workflow
+    #[serde(rename = "runNumber")]
+    pub run_number: u64,
+    /// Name of the workflow at the time of the run
+    #[serde(rename = "workflowName")]
+    pub workflow_name: String,
+    /// Branch the run checked out
+    #[serde(rename = "headBranch")]
+    pub head_branch: Option<String>,
+    /// How the run ended
+    pub conclusion: Option<RunConclusion>,
+    /// From creation to completion
+    #[serde(rename = "durationSeconds")]
+    pub duration_seconds: Option<u64>,
+    /// When the run was created
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    /// Page of the run on GitHub
+    #[serde(rename = "htmlUrl")]
+    pub html_url: String,
+    /// Failed or too slow
+    pub reason: ViolationReason,
+}
+
+/// How one objective fares over its window.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SloCompliance {
+    /// The objective evaluated
+    #[serde(flatten)]
+    pub slo: Slo,
+    /// Start of the window, which ends at the evaluation time
+    pub since: DateTime<Utc>,
+    /// Whether the objective holds
+    pub status: SloStatus,
+    /// Runs in the window counting towards the objective
+    pub runs: u64,
+    /// Of `runs`, those that met the objective
+    #[serde(rename = "goodRuns")]
+    pub good_runs: u64,
+    /// Share of `runs` that met the objective, from 0 to 1; `None` without runs
+    pub compliance: Option<f64>,
+    /// Share of the failures the target tolerates still left, from 1 when no run failed,
+    /// negative once overspent; `None` without runs
+    #[serde(rename = "errorBudgetRemaining")]
+    pub error_budget_remaining: Option<f64>,
+    /// The latest runs that did not meet the objective, newest first
+    #[serde(rename = "recentViolations")]
+    pub recent_violations: Vec<SloViolation>,
+}
+
+/// `runs` as a float; run counts stay far below `u32::MAX`.
+fn float(runs: u64) -> f64 {
+    f64::from(u32::try_from(runs).unwrap_or(u32::MAX))
+}
+
+impl SloCompliance {
+    /// Evaluates `slo` over `runs`, which may include runs outside its filters and window
+    /// starting at `since`; those count neither way.
+    #[must_use]
+    pub fn evaluate(slo: &Slo, runs: &[WorkflowRun], since: DateTime<Utc>) -> Self {
+        let mut counted: Vec<(&WorkflowRun, SloOutcome)> = runs
+            .iter()
+            .filter(|run| run.created_at >= since)
+            .map(|run| (run, slo.outcome(run)))
+            .filter(|(_, outcome)| *outcome != SloOutcome::Excluded)
+            .collect();
+        counted.sort_by_key(|(run, _)| Reverse(run.created_at));
+        let total = counted.len() as u64;
+        let good = counted
+            .iter()
+            .filter(|(_, outcome)| *outcome == SloOutcome::Met)
+            .count() as u64;
+
+        let (status, compliance, error_budget_remaining) = if total == 0 {
+            (SloStatus::NoData, None, None)
+        } else {
+            let compliance = float(good) / float(total);
+            let status = if compliance >= slo.target_success_rate {
+                SloStatus::Met
+            } else {
+                SloStatus::Violated
+            };
+            // Failures tolerated out of `total` runs, positive since the target is below 1
+            let tolerated = (1.0 - slo.target_success_rate) * float(total);
+            let remaining = 1.0 - float(total - good) / tolerated;
+            (status, Some(compliance), Some(remaining))
+        };
+        let recent_violations = counted
+            .iter()
+            .filter_map(|(run, outcome)| {
+                let reason = match outcome {
+                    SloOutcome::Failed => ViolationReason::Failed,
+                    SloOutcome::Slow => ViolationReason::Slow,
+                    SloOutcome::Met | SloOutcome::Excluded => return None,
+                };
+                Some(SloViolation {
+                    run_id: run.id,
+                    run_number: run.run_number,
+                    workflow_name: run.workflow_name.clone(),
+                    head_branch: run.head_branch.clone(),
+                    conclusion: run.conclusion,
+                    duration_seconds: run.duration_seconds(),
+                    created_at: run.created_at,
+                    html_url: run.html_url.clone(),
+                    reason,
+                })
+            })
+            .take(MAX_RECENT_VIOLATIONS)
+            .collect();
+        Self {
+            slo: slo.clone(),
+            since,
+            status,
+            runs: total,
+            good_runs: good,
+            compliance,
+            error_budget_remaining,
+            recent_violations,
+        }
+    }
+}
+
+/// Objectives to evaluate, each over its own window ending at `now`.
+#[derive(Debug, Clone)]
+pub struct SloEvaluationInput {
+    /// The configured objectives
+    pub slos: Vec<Slo>,
+    /// End of every window
+    pub now: DateTime<Utc>,
+}
+
+/// Compliance of every objective of a [`SloEvaluationInput`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SloEvaluationOutput {
+    /// In the order of the input
+    pub slos: Vec<SloCompliance>,
+    /// End of every window
+    #[serde(rename = "evaluatedAt")]
+    pub evaluated_at: DateTime<Utc>,
+}
+
+/// Evaluates service level objectives.
+#[async_trait]
+pub trait SloEvaluationUseCase {
+    /// Measures every objective of `input` against the runs of its window.
+    async fn execute(
+        &self,
+        input: SloEvaluationInput,
+    ) -> Result<SloEvaluationOutput, RunRepositoryError>;
+}
+
+/// Evaluates objectives against the stored run history.
+pub struct SloEvaluationInteractor<R: RunRepository + Send + Sync + ?Sized> {
+    run_repository: Arc<R>,
+}
+
+impl<R: RunRepository + Send + Sync + ?Sized> SloEvaluationInteractor<R> {
+    /// Reads the runs from `run_repository`.
+    pub fn new(run_repository: Arc<R>) -> Self {
+        Self { run_repository }
+    }
+}
+
+#[async_trait]
+impl<R: RunRepository + Send + Sync + ?Sized> SloEvaluationUseCase for SloEvaluationInteractor<R> {
+    async fn execute(
+        &self,
+        input: SloEvaluationInput,
+    ) -> Result<SloEvaluationOutput, RunRepositoryError> {
+        let mut slos = Vec::with_capacity(input.slos.len());
+        for slo in &input.slos {
+            let since = input.now - TimeDelta::days(slo.window_days.into());
+            let runs = self
+                .run_repository
+                .query_runs(&RunQuery {
+                    repository: Some(slo.repo.clone()),
+                    since: Some(since),
+                    until: Some(input.now),
+                    status: Some(RunStatus::Completed),
+                    limit: MAX_EVALUATED_RUNS,
+                    ..RunQuery::default()
+                })
+                .await?;
+            slos.push(SloCompliance::evaluate(slo, &runs, since));
+        }
+        Ok(SloEvaluationOutput {
+            slos,
+            evaluated_at: input.now,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::base_time;
+    use crate::testing::WorkflowRunBuilder;
+
+    fn slo() -> anyhow::Result<Slo> {
+        Ok(Slo {
+            repo: "owner/repo".parse()?,
+            workflow: Some("CI".parse()?),
+            branch: Some("main".parse()?),
+            target_duration_minutes: 30,
+            target_success_rate: 0.9,
+            window_days: 7,
+        })
+    }
+
+    /// Run `id` of CI on main, created `id` minutes after the base time and taking `minutes`.
+    fn run(id: u64, minutes: i64, conclusion: RunConclusion) -> WorkflowRun {
+        let created_at = base_time() + TimeDelta::minutes(id.try_into().unwrap_or_default());
+        WorkflowRunBuilder::new("owner/repo")
+            .id(id)
+            .conclusion(conclusion)
+            .created_at(created_at)
+            .updated_at(created_at + TimeDelta::minutes(minutes))
+            .build()
+    }
+
+    /// Nine runs within 30 minutes, then `last`.
+    fn history(last: WorkflowRun) -> Vec<WorkflowRun> {
+        (1..=9)
+            .map(|id| run(id, 30, RunConclusion::Success))
+            .chain([last])
+            .collect()
+    }
+
+    #[test]
+    fn test_a_window_at_the_target_is_met() -> anyhow::Result<()> {
+        let runs = history(run(10, 31, RunConclusion::Success));
+
+        let compliance = SloCompliance::evaluate(&slo()?, &runs, base_time());
+
+        assert_eq!(compliance.status, SloStatus::Met);
+        assert_eq!((compliance.runs, compliance.good_runs), (10, 9));
+        assert_eq!(compliance.compliance, Some(0.9));
+        let remaining = compliance.error_budget_remaining.unwrap_or(f64::NAN);
+        assert!(remaining.abs() < 1e-9, "{remaining}");
+        let [violation] = compliance.recent_violations.as_slice() else {
+            anyhow::bail!("expected one violation, got {compliance:?}");
+        };
+        assert_eq!(
+            (violation.run_id, violation.reason),
+            (RunId(10), ViolationReason::Slow)
+        );
+        assert_eq!(violation.duration_seconds, Some(31 * 60));
+        Ok(())
+    }
+
+    #[test]
+    fn test_a_window_just_below_the_target_is_violated() -> anyhow::Result<()> {
+        let mut runs = history(run(10, 5, RunConclusion::Failure));
+        runs.push(run(11, 31, RunConclusion::Success));
+
+        let compliance = SloCompliance::evaluate(&slo()?, &runs, base_time());
+
+        assert_eq!(compliance.status, SloStatus::Violated);
+        assert_eq!((compliance.runs, compliance.good_runs), (11, 9));
+        assert!(
+            compliance
+                .error_budget_remaining
+                .is_some_and(|remaining| remaining < 0.0)
+        );
+        let reasons: Vec<_> = compliance
+            .recent_violations
+            .iter()
+            .map(|violation| (violation.run_id, violation.reason))
+            .collect();
+        assert_eq!(
+            reasons,
+            [
+                (RunId(11), ViolationReason::Slow),
+                (RunId(10), ViolationReason::Failed)
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_excluded_runs_count_neither_way() -> anyhow::Result<()> {
+        let mut feature = run(10, 5, RunConclusion::Failure);
+        feature.head_branch = Some("feature".to_string());
+        let mut lint = run(11, 5, RunConclusion::Failure);
+        lint.workflow_name = "Lint".to_string();
+        let before_window = run(0, 5, RunConclusion::Failure);
+        let cancelled = run(12, 5, RunConclusion::Cancelled);
+        let runs = history(feature)
+            .into_iter()
+            .chain([lint, before_window, cancelled])
+            .collect::<Vec<_>>();
+
+        let compliance =
+            SloCompliance::evaluate(&slo()?, &runs, base_time() + TimeDelta::seconds(30));
+
+        assert_eq!((compliance.runs, compliance.good_runs), (9, 9));
+        assert_eq!(compliance.error_budget_remaining, Some(1.0));
+        assert!(compliance.recent_violations.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_a_window_without_matching_runs_has_no_data() -> anyhow::Result<()> {
+        let mut lint = run(1, 5, RunConclusion::Success);
+        lint.workflow_name = "Lint".to_string();
+
+        let compliance = SloCompliance::evaluate(&slo()?, &[lint], base_time());
+
+        assert_eq!(compliance.status, SloStatus::NoData);
+        assert_eq!(compliance.runs, 0);
+        assert_eq!(compliance.compliance, None);
+        assert_eq!(compliance.error_budget_remaining, None);
+        let json = serde_json::to_value(&compliance)?;
+        assert_eq!(json["status"], "noData");
+        assert_eq!(json["compliance"], serde_json::Value::Null);
+        assert_eq!(json["repository"], "owner/repo");
+        assert_eq!(json["targetDurationMinutes"], 30);
+        Ok(())
+    }
+}
@@ -0,0 +1,1706 @@
+/// Runtime-adjustable settings of the polling loop.
+pub mod config;
+/// Serialized form a snapshot is rebuilt from.
+pub mod document;
+/// Status of the runs a client is sent, at a glance.
+pub mod summary;
+
+use crate::application::services::pinned_runs::{PinnedRun, PinnedRuns};
+use crate::application::services::repository_discovery::RepositoryDiscovery;
+use crate::application::services::required_checks::RequiredChecksCache;
+use crate::application::services::workflow_inventory::WorkflowInventory;
+use crate::domain::external_apis::github::calls::{ApiCallCounts, ApiCallRecorder};
+use crate::domain::external_apis::github::{GitHubApi, GitHubApiError, Repository};
+use crate::domain::models::bot_group::BotRunGroup;
+use crate::domain::models::commit_status::ExternalCheck;
+use crate::domain::models::ids::{RepoFullName, WorkflowId};
+use crate::domain::models::queue::RepositoryQueue;
+use crate::domain::models::repository_change::RepositoryChange;
+use crate::domain::models::run::WorkflowRun;
+use crate::domain::models::schedule::UpcomingRun;
+use crate::domain::models::status::RunStatus;
+use crate::domain::models::workflow_filter::WorkflowFilter;
+use async_stream::try_stream;
+use async_trait::async_trait;
+use chrono::{DateTime, TimeDelta, Utc};
+use config::StreamConfig;
+use futures_util::Stream;
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{Notify, watch};
+
+/// リポジトリの最大取得数
+const MAX_REPOSITORIES_TO_FETCH: u8 = 5;
+
+/// リポジトリが見つからない場合の待機時間（秒）
+const RETRY_WAIT_SECONDS: u64 = 60;
+
+/// ワークフローランの取得イテレーション回数
+const FETCH_ITERATIONS: usize = 2;
+
+/// 各リポジトリから取得するワークフローランの最大数
+const MAX_WORKFLOW_RUNS_PER_REPO: u8 = 2;
+
+/// Most queued runs listed per repository, GitHub's largest page
+const MAX_QUEUED_RUNS_PER_REPO: u8 = 100;
+
+/// Runs listed per repository when a workflow filter may hide some, GitHub's largest page
+const MAX_FILTERED_RUNS_PER_REPO: u8 = 100;
+
+/// イテレーション間の待機時間（秒）
+const ITERATION_WAIT_SECONDS: u64 = 30;
+
+/// Wait between snapshots while webhooks keep runs up to date, at least the poll interval (seconds)
+const RECONCILIATION_INTERVAL_SECONDS: u64 = 300;
+
+/// Webhooks count as flowing while the last one arrived within this window (seconds)
+const WEBHOOK_ACTIVE_WINDOW_SECONDS: i64 = 600;
+
+/// Runs of disabled or deleted workflows older than this are left out of snapshots (days)
+const INACTIVE_WORKFLOW_RUN_MAX_AGE_DAYS: i64 = 7;
+
+/// Runs of repositories that are no longer polled stay shown until they are this old (hours)
+const INACTIVE_REPO_RUN_MAX_AGE_HOURS: u32 = 24;
+
+/// Most runs of repositories that are no longer polled kept in a snapshot
+const MAX_INACTIVE_REPO_RUNS: u32 = 20;
+
+/// What the polling loop reads while it runs.
+#[derive(Debug, Clone)]
+pub struct StreamGitHubActionsRunsUseCaseInput {
+    /// Signal that cuts short the current wait and starts the next iteration immediately
+    pub refresh: Arc<Notify>,
+    /// Current settings, re-read at the start of every iteration
+    pub config: watch::Receiver<StreamConfig>,
+    /// Refreshed with the workflows of each repository whenever repositories are listed
+    pub workflows: Arc<WorkflowInventory>,
+    /// Required checks of the default branches, for marking the runs on them
+    pub required_checks: Arc<RequiredChecksCache>,
+    /// When a webhook last pushed a run; polling slows to a reconciliation interval while recent
+    pub last_push: watch::Receiver<Option<DateTime<Utc>>>,
+    /// Tells the repositories listed apart from those seen before, and picks the polled ones
+    pub discovery: Arc<RepositoryDiscovery>,
+    /// Runs clients pinned, fetched one by one when they are not among the runs listed
+    pub pinned_runs: Arc<PinnedRuns>,
+}
+
+impl Default for StreamGitHubActionsRunsUseCaseInput {
+    fn default() -> Self {
+        Self {
+            refresh: Arc::default(),
+            config: watch::channel(StreamConfig::default()).1,
+            workflows: Arc::default(),
+            required_checks: Arc::default(),
+            last_push: watch::channel(None).1,
+            discovery: Arc::default(),
+            pinned_runs: Arc::default(),
+        }
+    }
+}
+
+/// Version of the serialized [`StreamGitHubActionsRunsUseCaseOutput`].
+///
+/// Bump whenever a field is renamed, removed or changes meaning so clients can detect
+/// an incompatible server on connect.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// A snapshot of the latest runs, as sent to every client.
+#[derive(Serialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct StreamGitHubActionsRunsUseCaseOutput {
+    /// Always [`SCHEMA_VERSION`]
+    #[serde(rename = "schemaVersion")]
+    pub schema_version: u32,
+    /// Runs shown, newest first
+    pub runs: Vec<WorkflowRun>,
+    /// When the snapshot was taken
+    #[serde(rename = "generatedAt")]
+    pub generated_at: DateTime<Utc>,
+    /// Every polled repository, for joining runs on `repositoryName`; only the grouped
+    /// representation serializes it
+    #[serde(skip)]
+    pub repositories: Vec<Repository>,
+    /// Queued runs of every polled repository, including those beyond the runs shown
+    #[serde(skip)]
+    pub queued_runs: Vec<WorkflowRun>,
+    /// Derived from `queued_runs` as of `generatedAt`
+    pub queue: Vec<RepositoryQueue>,
+    /// Next run of each scheduled workflow, soonest first; only listed when
+    /// `upcomingScheduledRuns` is set
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub upcoming: Vec<UpcomingRun>,
+    /// Commit statuses on the head commit of each repository named in `commitStatusRepos`,
+    /// leaving out those reported by its workflows
+    #[serde(
+        rename = "externalChecks",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub external_checks: Vec<ExternalCheck>,
+    /// Pull request runs of bots, one group per repository; only listed when `groupBotRuns`
+    /// is set, and derived from `bot_runs`
+    #[serde(rename = "botGroups", default, skip_serializing_if = "Vec::is_empty")]
+    pub bot_groups: Vec<BotRunGroup>,
+    /// The runs `botGroups` collapse, newest first, listed on demand
+    #[serde(skip)]
+    pub bot_runs: Vec<WorkflowRun>,
+    /// How long fetching the runs took; unset for snapshots that were not polled
+    #[serde(skip)]
+    pub fetch_duration: Option<Duration>,
+    /// GitHub API requests made for this snapshot; unset for snapshots that were not polled
+    #[serde(skip)]
+    pub github_calls: Option<ApiCallCounts>,
+    /// Repositories that appeared or went away since the repositories were last listed, on
+    /// the first snapshot after listing them
+    #[serde(skip)]
+    pub repository_changes: Vec<RepositoryChange>,
+    /// Latest state of every pinned run, whether it is among `runs` or too old to be; only
+    /// the clients that pinned a run are sent it
+    #[serde(skip)]
+    pub pinned_runs: Vec<WorkflowRun>,
+}
+
+impl StreamGitHubActionsRunsUseCaseOutput {
+    /// A snapshot of `runs` taken at `generated_at`, with nothing else set.
+    #[must_use]
+    pub fn new(runs: Vec<WorkflowRun>, generated_at: DateTime<Utc>) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            runs,
+            generated_at,
+            repositories: Vec::new(),
+            queued_runs: Vec::new(),
+            queue: Vec::new(),
+            upcoming: Vec::new(),
+            external_checks: Vec::new(),
+            bot_groups: Vec::new(),
+            bot_runs: Vec::new(),
+            fetch_duration: None,
+            github_calls: None,
+            repository_changes: Vec::new(),
+            pinned_runs: Vec::new(),
+        }
+    }
+
+    /// Sets the polled repositories; call before [`Self::with_queued_runs`] so their empty
+    /// queues are listed.
+    #[must_use]
+    pub fn with_repositories(mut self, repositories: Vec<Repository>) -> Self {
+        self.repositories = repositories;
+        self
+    }
+
+    /// Sets the next run of each scheduled workflow.
+    #[must_use]
+    pub fn with_upcoming(mut self, upcoming: Vec<UpcomingRun>) -> Self {
+        self.upcoming = upcoming;
+        self
+    }
+
+    /// Sets the commit statuses reported by services other than GitHub Actions.
+    #[must_use]
+    pub fn with_external_checks(mut self, external_checks: Vec<ExternalCheck>) -> Self {
+        self.external_checks = external_checks;
+        self
+    }
+
+    /// Sets the collapsed runs of bots and derives their groups from them.
+    #[must_use]
+    pub fn with_bot_runs(mut self, bot_runs: Vec<WorkflowRun>) -> Self {
+        self.bot_groups = BotRunGroup::per_repository(&bot_runs);
+        self.bot_runs = bot_runs;
+        self
+    }
+
+    /// Sets how long polling the snapshot took.
+    #[must_use]
+    pub fn with_fetch_duration(mut self, fetch_duration: Duration) -> Self {
+        self.fetch_duration = Some(fetch_duration);
+        self
+    }
+
+    /// Sets the GitHub API requests made for the snapshot.
+    #[must_use]
+    pub fn with_github_calls(mut self, github_calls: ApiCallCounts) -> Self {
+        self.github_calls = Some(github_calls);
+        self
+    }
+
+    /// Sets the repositories that appeared or went away.
+    #[must_use]
+    pub fn with_repository_changes(mut self, repository_changes: Vec<RepositoryChange>) -> Self {
+        self.repository_changes = repository_changes;
+        self
+    }
+
+    /// Sets the latest state of the pinned runs.
+    #[must_use]
+    pub fn with_pinned_runs(mut self, pinned_runs: Vec<WorkflowRun>) -> Self {
+        self.pinned_runs = pinned_runs;
+        self
+    }
+
+    /// Sets the queued runs and derives the queue of each repository from them.
+    #[must_use]
+    pub fn with_queued_runs(mut self, queued_runs: Vec<WorkflowRun>) -> Self {
+        self.queue = RepositoryQueue::per_repository(
+            self.repositories.iter().map(Repository::full_name),
+            &queued_runs,
+            self.generated_at,
+        );
+        self.queued_runs = queued_runs;
+        self
+    }
+}
+
+/// Sleeps for `duration`, returning early if `refresh` is notified.
+async fn wait_or_refresh(duration: Duration, refresh: &Notify) {
+    tokio::select! {
+        () = tokio::time::sleep(duration) => {}
+        () = refresh.notified() => {
+            tracing::info!("Refresh requested, skipping the remaining wait");
+        }
+    }
+}
+
+/// Wait before the next snapshot: the poll interval, stretched to the reconciliation
+/// interval while webhooks are flowing.
+fn poll_wait(
+    poll_interval_seconds: u64,
+    last_push: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Duration {
+    let webhooks_flowing = last_push.is_some_and(|last_push| {
+        now - last_push < TimeDelta::seconds(WEBHOOK_ACTIVE_WINDOW_SECONDS)
+    });
+    if webhooks_flowing {
+        Duration::from_secs(poll_interval_seconds.max(RECONCILIATION_INTERVAL_SECONDS))
+    } else {
+        Duration::from_secs(poll_interval_seconds)
+    }
+}
+
+/// Orders runs newest first; runs created in the same millisecond fall back to the run number.
+pub(crate) fn sort_runs_newest_first(runs: &mut [WorkflowRun]) {
+    runs.sort_by_key(|run| (run.created_at.timestamp_millis(), run.run_number));
+    runs.reverse();
+}
+
+/// Removes runs of disabled or deleted workflows that are too old to be news.
+fn drop_old_runs_of_inactive_workflows(
+    runs: &mut Vec<WorkflowRun>,
+    inactive_workflow_ids: &HashSet<WorkflowId>,
+    now: DateTime<Utc>,
+) {
+    let max_age = TimeDelta::days(INACTIVE_WORKFLOW_RUN_MAX_AGE_DAYS);
+    runs.retain(|run| {
+        !inactive_workflow_ids.contains(&run.workflow_id) || now - run.created_at <= max_age
+    });
+}
+
+/// Leaves out the runs `config` hides, and old runs of inactive workflows, and labels the rest.
+async fn filter_and_label(
+    runs: &mut Vec<WorkflowRun>,
+    config: &StreamConfig,
+    workflows: &WorkflowInventory,
+) {
+    if config.problem_only {
+        runs.retain(WorkflowRun::is_problem);
+    }
+    drop_old_runs_of_inactive_workflows(runs, &workflows.inactive_workflow_ids().await, Utc::now());
+    for run in runs {
+        config.label(run);
+    }
+}
+
+/// The latest `count` runs of `repo` shown by `filter`, and, when `group_bot_runs` is set,
+/// the pull request runs of bots among the latest page taken out of them.
+///
+/// When the filter resolves to a single known workflow, only that workflow's runs are listed;
+/// otherwise a full page is listed so that hidden runs do not take the place of shown ones.
+/// Bot runs are taken out before the runs are cut to `count`, so a storm of them does not
+/// push the runs of people out.
+async fn fetch_shown_runs<G: GitHubApi + Send + Sync + ?Sized>(
+    github_api: &G,
+    workflows: &WorkflowInventory,
+    repo: &RepoFullName,
+    filter: &WorkflowFilter,
+    count: u8,
+    group_bot_runs: bool,
+) -> Result<(Vec<WorkflowRun>, Vec<WorkflowRun>), GitHubApiError> {
+    if filter.is_empty() && !group_bot_runs {
+        let runs = github_api.fetch_workflow_runs(repo, count).await?;
+        return Ok((runs, Vec::new()));
+    }
+    let single_workflow = workflows.get(repo).await.and_then(|entry| {
+        filter
+            .single_workflow(&entry.workflows)
+            .map(|workflow| workflow.id)
+    });
+    let listed = if group_bot_runs {
+        MAX_FILTERED_RUNS_PER_REPO
+    } else {
+        count
+    };
+    let mut runs = match single_workflow {
+        Some(workflow_id) => {
+            github_api
+                .fetch_workflow_runs_of_workflow(repo, workflow_id, listed)
+                .await?
+        }
+        None => {
+            github_api
+                .fetch_workflow_runs(repo, MAX_FILTERED_RUNS_PER_REPO)
+                .await?
+        }
+    };
+    runs.retain(|run| filter.allows(&run.workflow_name));
+    let bot_runs = if group_bot_runs {
+        runs.extract_if(.., |run| BotRunGroup::collapses(run))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    runs.truncate(usize::from(count));
+    Ok((runs, bot_runs))
+}
+
+/// Every queued run of `repo`, or the queued ones among its latest `runs` when GitHub cannot
+/// list them; the queue is only a derived view, so it never fails the snapshot.
+async fn fetch_queued_runs<G: GitHubApi + Send + Sync + ?Sized>(
+    github_api: &G,
+    repo: &RepoFullName,
+    runs: &[WorkflowRun],
+) -> Vec<WorkflowRun> {
+    match github_api
+        .fetch_workflow_runs_by_status(repo, RunStatus::Queued, MAX_QUEUED_RUNS_PER_REPO)
+        .await
+    {
+        Ok(queued) => queued,
+        Err(e) => {
+            tracing::warn!("Failed to fetch queued runs of {}: {}", repo, e);
+            runs.iter()
+                .filter(|run| run.status == RunStatus::Queued)
+                .cloned()
+                .collect()
+        }
+    }
+}
+
+/// The queued runs of `repo` among `previous`, listed earlier in the loop, brought up to date
+/// with its latest `runs`: those that started since are dropped and those queued since added.
+fn update_queued_runs(
+    previous: &[WorkflowRun],
+    repo: &RepoFullName,
+    runs: &[WorkflowRun],
+) -> Vec<WorkflowRun> {
+    let mut queued: Vec<WorkflowRun> = previous
+        .iter()
+        .filter(|run| run.repository_name == *repo)
+        .filter_map(|run| match runs.iter().find(|latest| latest.id == run.id) {
+            Some(latest) => (latest.status == RunStatus::Queued).then(|| latest.clone()),
+            None => Some(run.clone()),
+        })
+        .collect();
+    for run in runs {
+        if run.status == RunStatus::Queued && !queued.iter().any(|known| known.id == run.id) {
+            queued.push(run.clone());
+        }
+    }
+    queued
+}
+
+/// Runs of every repository in `repositories`, as listed for a snapshot.
+#[derive(Debug, Clone, Default)]
+struct FetchedRuns {
+    shown: Vec<WorkflowRun>,
+    queued: Vec<WorkflowRun>,
+    /// Pull request runs of bots, when `groupBotRuns` is set
+    bots: Vec<WorkflowRun>,
+}
+
+impl FetchedRuns {
+    /// Adds the runs of `repo` in `previous`, e.g. when GitHub is too slow to list them again.
+    fn extend_from(&mut self, previous: &Self, repo: &RepoFullName) {
+        let of_repo = |runs: &[WorkflowRun]| {
+            runs.iter()
+                .filter(|run| run.repository_name == *repo)
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        self.shown.extend(of_repo(&previous.shown));
+        self.queued.extend(of_repo(&previous.queued));
+        self.bots.extend(of_repo(&previous.bots));
+    }
+}
+
+/// Lists the workflows of every repository in `repositories` into `workflows`.
+async fn fetch_workflows<G: GitHubApi + Send + Sync + ?Sized>(
+    github_api: &G,
+    workflows: &WorkflowInventory,
+    repositories: &[RepoFullName],
+) {
+    for repo in repositories {
+        // A stale inventory only lets a few old runs through, so don't fail the snapshot
+        match github_api.fetch_workflows(repo).await {
+            Ok(list) => {
+                workflows.insert(repo.clone(), list, Utc::now()).await;
+            }
+            Err(e) => tracing::warn!("Failed to fetch workflows of {}: {:?}", repo, e),
+        }
+    }
+}
+
+/// The shown runs, the queued runs and the runs of bots of every repository in `repositories`.
+///
+/// A repository whose runs GitHub did not list before the retry deadline keeps its runs of
+/// `previous`: the next poll tries again, so the snapshot goes stale rather than failing.
+///
+/// Queued runs are only listed when `list_queued` is set, once per loop; otherwise the queued
+/// runs of `previous` are updated from the latest runs.
+async fn fetch_runs<G: GitHubApi + Send + Sync + ?Sized>(
+    github_api: &G,
+    workflows: &WorkflowInventory,
+    repositories: &[RepoFullName],
+    config: &StreamConfig,
+    previous: &FetchedRuns,
+    list_queued: bool,
+) -> Result<FetchedRuns, StreamGitHubActionsRunsError> {
+    let mut fetched = FetchedRuns::default();
+    for repo in repositories {
+        tracing::debug!("Fetching runs for {}", repo);
+        let filter = config.workflow_filter_for(repo);
+        let (runs, bot_runs) = match fetch_shown_runs(
+            github_api,
+            workflows,
+            repo,
+            &filter,
+            config.max_runs_per_repo,
+            config.group_bot_runs,
+        )
+        .await
+        {
+            Ok(runs) => runs,
+            Err(e @ GitHubApiError::Timeout { .. }) => {
+                tracing::warn!("Keeping the previous runs of {}: {}", repo, e);
+                fetched.extend_from(previous, repo);
+                continue;
+            }
+            Err(source) => {
+                return Err(StreamGitHubActionsRunsError::Repository {
+                    repository: repo.clone(),
+                    source,
+                });
+            }
+        };
+        let queued = if list_queued {
+            fetch_queued_runs(github_api, repo, &runs).await
+        } else {
+            update_queued_runs(&previous.queued, repo, &runs)
+        };
+        fetched.queued.extend(
+            queued
+                .into_iter()
+                .filter(|run| filter.allows(&run.workflow_name)),
+        );
+        fetched.shown.extend(runs);
+        fetched.bots.extend(bot_runs);
+    }
+    warn_of_clock_skew(&fetched, Utc::now());
+    Ok(fetched)
+}
+
+/// Warns once when any of `fetched` has timestamps that cannot be right as of `now`; their
+/// durations, queue times and ages are clamped at zero rather than shown negative.
+fn warn_of_clock_skew(fetched: &FetchedRuns, now: DateTime<Utc>) {
+    let skewed: Vec<_> = fetched
+        .shown
+        .iter()
+        .chain(&fetched.queued)
+        .chain(&fetched.bots)
+        .filter(|run| run.has_clock_skew(now))
+        .collect();
+    if let Some(first) = skewed.first() {
+        tracing::warn!(
+            "Clock skew: {} runs were updated before they were created or created in the future, e.g. run {} of {}",
+            skewed.len(),
+            first.id,
+            first.repository_name
+        );
+    }
+}
+
+/// Marks whether each run on the default branch of its repository reports a required check.
+///
+/// Runs of repositories whose default branch is unknown, or whose branch protection the token
+/// may not read, are left unmarked.
+async fn mark_required_checks<G: GitHubApi + Send + Sync + ?Sized>(
+    github_api: &G,
+    required_checks: &RequiredChecksCache,
+    repositories: &[Repository],
+    runs: &mut [WorkflowRun],
+) {
+    for repository in repositories {
+        let Some(branch) = &repository.default_branch else {
+            continue;
+        };
+        let on_default_branch: Vec<usize> = (0..runs.len())
+            .filter(|&i| {
+                runs[i].repository_name == *repository.full_name()
+                    && runs[i].head_branch.as_ref() == Some(branch)
+            })
+            .collect();
+        if on_default_branch.is_empty() {
+            continue;
+        }
+        let Some(checks) = required_checks
+            .get(github_api, repository.full_name(), branch, Utc::now())
+            .await
+        else {
+            continue;
+        };
+        for i in on_default_branch {
+            runs[i].is_required_check = Some(runs[i].reports_required_check(&checks));
+        }
+    }
+}
+
+/// Commit statuses on the commit of the latest of `runs` in each of `repositories`; those
+/// without runs, e.g. because they are not polled, are skipped.
+///
+/// Statuses are extras next to the runs, so a repository whose statuses cannot be listed is
+/// left out rather than failing the snapshot.
+async fn fetch_external_checks<G: GitHubApi + Send + Sync + ?Sized>(
+    github_api: &G,
+    repositories: &[RepoFullName],
+    runs: &[WorkflowRun],
+) -> Vec<ExternalCheck> {
+    let mut checks = Vec::new();
+    for repo in repositories {
+        let Some(latest) = runs
+            .iter()
+            .filter(|run| run.repository_name == *repo)
+            .max_by_key(|run| run.created_at)
+        else {
+            continue;
+        };
+        match github_api
+            .fetch_combined_status(repo, &latest.head_sha)
+            .await
+        {
+            Ok(statuses) => checks.extend(ExternalCheck::from_commit_statuses(
+                repo,
+                &latest.head_sha,
+                statuses,
+                runs,
+            )),
+            Err(e) => tracing::warn!("Failed to fetch commit statuses of {}: {}", repo, e),
+        }
+    }
+    checks
+}
+
+/// The latest state of each of `pinned`: the one in `runs` when it is listed, else fetched
+/// on its own.
+///
+/// A pinned run that cannot be fetched, e.g. because it was deleted, is left out rather than
+/// failing the snapshot.
+async fn fetch_pinned_runs<G: GitHubApi + Send + Sync + ?Sized>(
+    github_api: &G,
+    pinned: &[PinnedRun],
+    runs: &[WorkflowRun],
+) -> Vec<WorkflowRun> {
+    let mut pinned_runs = Vec::new();
+    for pin in pinned {
+        if let Some(run) = runs
+            .iter()
+            .find(|run| run.repository_name == pin.repo && run.id == pin.run_id)
+        {
+            pinned_runs.push(run.clone());
+            continue;
+        }
+        match github_api.fetch_workflow_run(&pin.repo, pin.run_id).await {
+            Ok(run) => pinned_runs.push(run),
+            Err(e) => tracing::warn!(
+                "Failed to fetch pinned run {} of {}: {}",
+                pin.run_id,
+                pin.repo,
+                e
+            ),
+        }
+    }
+    pinned_runs
+}
+
+/// Why a snapshot could not be produced. The stream ends after yielding it.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum StreamGitHubActionsRunsError {
+    /// Listing repositories failed
+    #[error(transparent)]
+    GitHubApi(#[from] GitHubApiError),
+    /// Fetching the runs of `repository` failed
+    #[error("{source}")]
+    Repository {
+        /// The repository whose runs could not be fetched
+        repository: RepoFullName,
+        /// The error GitHub answered with
+        source: GitHubApiError,
+    },
+}
+
+/// Takes snapshots of the runs of the polled repositories.
+pub trait StreamGitHubActionsRunsUseCase {
+    /// Polls GitHub until the stream is dropped, yielding a snapshot per iteration.
+    ///
+    /// The stream ends after the first error, to be restarted by the caller.
+    fn execute(
+        &self,
+        input: StreamGitHubActionsRunsUseCaseInput,
+    ) -> impl Stream<
+        Item = Result<StreamGitHubActionsRunsUseCaseOutput, StreamGitHubActionsRunsError>,
+    > + Send;
+}
+
+/// Polls the runs through a [`GitHubApi`].
+pub struct StreamGitHubActionsRunsInteractor<G: GitHubApi + Send + Sync + ?Sized + 'static> {
+    github_api: Arc<G>,
+}
+
+impl<G: GitHubApi + Send + Sync + ?Sized + 'static> StreamGitHubActionsRunsInteractor<G> {
+    /// Polls through `github_api`.
+    pub fn new(github_api: Arc<G>) -> Self {
+        Self { github_api }
+    }
+}
+
+#[async_trait]
+impl<G: GitHubApi + Send + Sync + ?Sized + 'static> StreamGitHubActionsRunsUseCase
+    for StreamGitHubActionsRunsInteractor<G>
+{
+    fn execute(
+        &self,
+        input: StreamGitHubActionsRunsUseCaseInput,
+    ) -> impl Stream<
+        Item = Result<StreamGitHubActionsRunsUseCaseOutput, StreamGitHubActionsRunsError>,
+    > + Send {
+        let github_api = self.github_api.clone();
+        let refresh = input.refresh;
+        let config = input.config;
+        let workflows = input.workflows;
+        let required_checks = input.required_checks;
+        let last_push = input.last_push;
+        let discovery = input.discovery;
+        let pinned_runs = input.pinned_runs;
+
+        try_stream! {
+            // Runs of the last snapshot, reused for repositories GitHub is too slow to list
+            let mut previous = FetchedRuns::default();
+            loop {
+                // Requests for the repositories and workflows count towards the first snapshot
+                let mut calls = ApiCallRecorder::default();
+                let mut repository_changes = Vec::new();
+                let (repositories, records) = {
+                    let current = config.borrow().clone();
+                    if current.repo_allowlist.is_empty() {
+                        tracing::info!("Fetching repositories...");
+                        let listed = calls
+                            .scope(github_api.fetch_repositories(current.max_repositories))
+                            .await?;
+                        tracing::info!("Fetched {} repositories", listed.len());
+                        let discovered = calls
+                            .scope(discovery.observe(github_api.as_ref(), listed, current.max_repositories))
+                            .await;
+                        repository_changes = discovered.changes;
+                        let fetched = discovered.repositories;
+                        let full_names = fetched.iter().map(|repo| repo.full_name().clone()).collect();
+                        (full_names, fetched)
+                    } else {
+                        let records = current.repo_allowlist.iter().map(Repository::from_full_name).collect();
+                        (current.repo_allowlist, records)
+                    }
+                };
+
+                if repositories.is_empty() {
+                    tracing::warn!("No repositories found, waiting before retrying...");
+                    wait_or_refresh(Duration::from_secs(RETRY_WAIT_SECONDS), &refresh).await;
+                    continue;
+                }
+
+                calls.scope(fetch_workflows(github_api.as_ref(), &workflows, &repositories)).await;
+
+                for i in 0..FETCH_ITERATIONS {
+                    let current = config.borrow().clone();
+                    let started = Instant::now();
+                    tracing::info!("Fetching workflow runs (iteration {}/{})...", i + 1, FETCH_ITERATIONS);
+                    let fetched = calls
+                        .scope(fetch_runs(github_api.as_ref(), &workflows, &repositories, &current, &previous, i == 0))
+                        .await?;
+                    previous.clone_from(&fetched);
+                    let FetchedRuns { shown: mut all_runs, queued: queued_runs, bots: mut bot_runs } = fetched;
+
+                    let external_checks = calls
+                        .scope(fetch_external_checks(
+                            github_api.as_ref(),
+                            &current.commit_status_repos,
+                            &all_runs,
+                        ))
+                        .await;
+                    filter_and_label(&mut all_runs, &current, &workflows).await;
+                    filter_and_label(&mut bot_runs, &current, &workflows).await;
+                    calls
+                        .scope(mark_required_checks(
+                            github_api.as_ref(),
+                            &required_checks,
+                            &records,
+                            &mut all_runs,
+                        ))
+                        .await;
+                    sort_runs_newest_first(&mut all_runs);
+                    sort_runs_newest_first(&mut bot_runs);
+                    let pinned = calls.scope(fetch_pinned_runs(github_api.as_ref(), &pinned_runs.runs(), &all_runs)).await;
+                    let upcoming = if current.upcoming_scheduled_runs {
+                        calls
+                            .scope(workflows.upcoming_runs(github_api.as_ref(), &repositories, Utc::now()))
+                            .await
+                    } else {
+                        Vec::new()
+                    };
+
+                    tracing::info!("Yielding {} workflow runs", all_runs.len());
+                    yield StreamGitHubActionsRunsUseCaseOutput::new(all_runs, Utc::now())
+                        .with_repositories(records.clone())
+                        .with_queued_runs(queued_runs)
+                        .with_upcoming(upcoming)
+                        .with_external_checks(external_checks)
+                        .with_bot_runs(bot_runs)
+                        .with_fetch_duration(started.elapsed())
+                        .with_github_calls(calls.counts())
+                        .with_repository_changes(std::mem::take(&mut repository_changes))
+                        .with_pinned_runs(pinned);
+                    calls = ApiCallRecorder::default();
+
+                    // Read again so an interval changed while the snapshot was consumed applies to this wait
+                    let poll_interval_seconds = config.borrow().poll_interval_seconds;
+                    let wait = poll_wait(poll_interval_seconds, *last_push.borrow(), Utc::now());
+                    tracing::debug!("Waiting for {:?}...", wait);
+                    wait_or_refresh(wait, &refresh).await;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::commit_status::{CommitStatus, CommitStatusState};
+    use crate::domain::models::ids::RunId;
+    use crate::domain::models::label::{LabelConditions, LabelRule};
+    use crate::domain::models::status::RunConclusion;
+    use crate::domain::models::workflow::{Workflow, WorkflowState};
+    use crate::domain::models::workflow_definition::WorkflowDefinition;
+    use crate::test_support::{base_time, repo_full_name, workflow, workflow_run};
+    use crate::testing::{ScriptedGitHubApi, WorkflowRunBuilder};
+    use futures_util::StreamExt;
+    use tokio::time::timeout;
+
+    fn input(
+        config: StreamConfig,
+    ) -> (
+        watch::Sender<StreamConfig>,
+        StreamGitHubActionsRunsUseCaseInput,
+    ) {
+        let (sender, receiver) = watch::channel(config);
+        let input = StreamGitHubActionsRunsUseCaseInput {
+            refresh: Arc::default(),
+            config: receiver,
+            ..StreamGitHubActionsRunsUseCaseInput::default()
+        };
+        (sender, input)
+    }
+
+    fn repository() -> Repository {
+        Repository::from_full_name(&repo_full_name("owner/repo")).with_owner_avatar_url(Some(
+            "https://avatars.githubusercontent.com/owner".to_string(),
+        ))
+    }
+
+    /// Lists `owner/repo` whose latest runs are `runs`.
+    fn github_api(runs: Vec<WorkflowRun>) -> Arc<ScriptedGitHubApi> {
+        let github_api = ScriptedGitHubApi::new();
+        github_api
+            .repositories(Ok(vec![repository()]))
+            .workflow_runs(&repo_full_name("owner/repo"), Ok(runs));
+        Arc::new(github_api)
+    }
+
+    #[tokio::test]
+    async fn test_interval_change_applies_to_next_wait() -> anyhow::Result<()> {
+        let interactor = StreamGitHubActionsRunsInteractor::new(github_api(Vec::new()));
+        let (config, input) = input(StreamConfig {
+            poll_interval_seconds: 3_600,
+            ..StreamConfig::default()
+        });
+        let stream = interactor.execute(input);
+        tokio::pin!(stream);
+        stream
+            .next()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("stream ended"))??;
+
+        // Without the change the next snapshot would take an hour
+        config.send_modify(|config| config.poll_interval_seconds = 0);
+
+        let second = timeout(Duration::from_secs(5), stream.next()).await?;
+        assert!(matches!(second, Some(Ok(_))));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_runs_created_at_the_same_time_are_ordered_by_run_number() -> anyhow::Result<()> {
+        let run = |id, run_number| {
+            WorkflowRunBuilder::new("owner/repo")
+                .id(id)
+                .run_number(run_number)
+                .created_at(base_time())
+                .build()
+        };
+        let interactor =
+            StreamGitHubActionsRunsInteractor::new(github_api(vec![run(2, 8), run(1, 7)]));
+        let (_config, input) = input(StreamConfig::default());
+        let stream = interactor.execute(input);
+        tokio::pin!(stream);
+
+        let output = stream
+            .next()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("stream ended"))??;
+
+        let run_numbers: Vec<u64> = output.runs.iter().map(|run| run.run_number).collect();
+        assert_eq!(run_numbers, vec![8, 7]);
+        // Listed repositories are kept with their avatar for the grouped representation
+        assert_eq!(output.repositories, vec![repository()]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pinned_runs_are_fetched_when_no_longer_listed() -> anyhow::Result<()> {
+        let run = |id| WorkflowRunBuilder::new("owner/repo").id(id).build();
+        let github_api = github_api(vec![run(2)]);
+        github_api.workflow_run(RunId(1), Ok(run(1)));
+        let interactor = StreamGitHubActionsRunsInteractor::new(Arc::clone(&github_api));
+        let (_config, input) = input(StreamConfig::default());
+        for run_id in [RunId(1), RunId(2)] {
+            let pin = PinnedRun {
+                repo: repo_full_name("owner/repo"),
+                run_id,
+            };
+            input
+                .pinned_runs
+                .pin(pin, &StreamConfig::default())
+                .map_err(|calls| anyhow::anyhow!("{calls} calls per hour"))?;
+        }
+        let stream = interactor.execute(input);
+        tokio::pin!(stream);
+
+        let output = stream
+            .next()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("stream ended"))??;
+
+        let shown: Vec<RunId> = output.runs.iter().map(|run| run.id).collect();
+        assert_eq!(shown, [RunId(2)]);
+        let pinned: Vec<RunId> = output.pinned_runs.iter().map(|run| run.id).collect();
+        assert_eq!(pinned, [RunId(1), RunId(2)]);
+        // Only the run that is not listed costs a request of its own
+        let fetched: Vec<String> = github_api
+            .calls()
+            .into_iter()
+            .filter(|call| call.starts_with("fetch_workflow_run "))
+            .collect();
+        assert_eq!(fetched, ["fetch_workflow_run owner/repo 1"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_allowlist_and_problem_only_filter_runs() -> anyhow::Result<()> {
+        let run = |repository, id, conclusion| {
+            WorkflowRunBuilder::new(repository)
+                .id(id)
+                .conclusion(conclusion)
+                .build()
+        };
+        let github_api = Arc::new(ScriptedGitHubApi::new());
+        github_api
+            .workflow_runs(
+                &repo_full_name("owner/repo"),
+                Ok(vec![
+                    run("owner/repo", 1, RunConclusion::Success),
+                    run("owner/repo", 2, RunConclusion::Failure),
+                ]),
+            )
+            .workflow_runs(
+                &repo_full_name("owner/other"),
+                Ok(vec![run("owner/other", 3, RunConclusion::Failure)]),
+            );
+        let interactor = StreamGitHubActionsRunsInteractor::new(Arc::clone(&github_api));
+        let (_config, input) = input(StreamConfig {
+            repo_allowlist: vec![repo_full_name("owner/repo")],
+            problem_only: true,
+            ..StreamConfig::default()
+        });
+        let stream = interactor.execute(input);
+        tokio::pin!(stream);
+
+        let output = stream
+            .next()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("stream ended"))??;
+
+        let ids: Vec<RunId> = output.runs.iter().map(|run| run.id).collect();
+        assert_eq!(ids, vec![RunId(2)]);
+        assert_eq!(
+            output.repositories,
+            vec![Repository::from_full_name(&repo_full_name("owner/repo"))]
+        );
+        // Only the allowlisted repository is polled, without listing repositories
+        assert_eq!(
+            github_api.calls(),
+            vec![
+                "fetch_workflows owner/repo",
+                "fetch_workflow_runs owner/repo 2",
+                "fetch_workflow_runs_by_status owner/repo queued 100"
+            ]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ignored_workflows_do_not_count_toward_the_per_repo_cap() -> anyhow::Result<()> {
+        let run = |id, workflow_id, name| {
+            WorkflowRunBuilder::new("owner/repo")
+                .id(id)
+                .workflow(workflow_id, name)
+                .build()
+        };
+        let repo = repo_full_name("owner/repo");
+        let github_api = Arc::new(ScriptedGitHubApi::new());
+        github_api.workflows(&repo, Ok(Vec::new())).workflow_runs(
+            &repo,
+            Ok(vec![
+                run(5, 2, "stale-bot"),
+                run(4, 2, "stale-bot"),
+                run(3, 1, "CI"),
+                run(2, 1, "CI"),
+                run(1, 1, "CI"),
+            ]),
+        );
+        let interactor = StreamGitHubActionsRunsInteractor::new(Arc::clone(&github_api));
+        let (_config, input) = input(StreamConfig {
+            repo_allowlist: vec![repo.clone()],
+            workflow_filter: WorkflowFilter {
+                ignore: vec!["stale*".parse()?],
+                only: Vec::new(),
+            },
+            ..StreamConfig::default()
+        });
+        let stream = interactor.execute(input);
+        tokio::pin!(stream);
+
+        let output = stream
+            .next()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("stream ended"))??;
+
+        let ids: Vec<RunId> = output.runs.iter().map(|run| run.id).collect();
+        assert_eq!(ids, vec![RunId(3), RunId(2)]);
+        assert!(
+            github_api
+                .calls()
+                .contains(&"fetch_workflow_runs owner/repo 100".to_string())
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_bot_pull_request_runs_are_grouped_without_evicting_human_runs()
+    -> anyhow::Result<()> {
+        let bot_run = |id, minutes, conclusion| {
+            WorkflowRunBuilder::new("owner/repo")
+                .id(id)
+                .event("pull_request")
+                .actor("dependabot[bot]")
+                .conclusion(conclusion)
+                .created_at(base_time() + TimeDelta::minutes(minutes))
+                .build()
+        };
+        let human_run = |id, minutes, event| {
+            WorkflowRunBuilder::new("owner/repo")
+                .id(id)
+                .event(event)
+                .created_at(base_time() + TimeDelta::minutes(minutes))
+                .build()
+        };
+        let repo = repo_full_name("owner/repo");
+        let github_api = Arc::new(ScriptedGitHubApi::new());
+        github_api.workflow_runs(
+            &repo,
+            Ok(vec![
+                bot_run(6, 6, RunConclusion::Failure),
+                bot_run(5, 5, RunConclusion::Success),
+                bot_run(4, 4, RunConclusion::Success),
+                human_run(3, 3, "pull_request"),
+                bot_run(2, 2, RunConclusion::Success),
+                human_run(1, 1, "push"),
+            ]),
+        );
+        let interactor = StreamGitHubActionsRunsInteractor::new(Arc::clone(&github_api));
+        let (_config, input) = input(StreamConfig {
+            repo_allowlist: vec![repo.clone()],
+            group_bot_runs: true,
+            ..StreamConfig::default()
+        });
+        let stream = interactor.execute(input);
+        tokio::pin!(stream);
+
+        let output = stream
+            .next()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("stream ended"))??;
+
+        let ids: Vec<RunId> = output.runs.iter().map(|run| run.id).collect();
+        assert_eq!(ids, vec![RunId(3), RunId(1)]);
+        let [group] = output.bot_groups.as_slice() else {
+            anyhow::bail!("expected one bot group, got {:?}", output.bot_groups);
+        };
+        assert_eq!(group.repository_name, repo);
+        assert_eq!((group.count, group.failing), (4, 1));
+        assert_eq!(group.newest_at, base_time() + TimeDelta::minutes(6));
+        assert_eq!(group.run_ids, vec![RunId(6), RunId(5), RunId(4), RunId(2)]);
+        assert_eq!(output.bot_runs.len(), 4);
+        // Bot runs could fill the first `maxRunsPerRepo` runs, so a full page is listed
+        assert!(
+            github_api
+                .calls()
+                .contains(&"fetch_workflow_runs owner/repo 100".to_string())
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_published_runs_carry_the_labels_they_match() -> anyhow::Result<()> {
+        let run = |id, branch| {
+            WorkflowRunBuilder::new("owner/repo")
+                .id(id)
+                .branch(Some(branch))
+                .build()
+        };
+        let interactor = StreamGitHubActionsRunsInteractor::new(github_api(vec![
+            run(2, "release/1.0"),
+            run(1, "main"),
+        ]));
+        let (_config, input) = input(StreamConfig {
+            labels: vec![LabelRule {
+                name: "release".to_string(),
+                conditions: LabelConditions {
+                    branch: Some("release/*".parse()?),
+                    ..LabelConditions::default()
+                },
+            }],
+            ..StreamConfig::default()
+        });
+        let stream = interactor.execute(input);
+        tokio::pin!(stream);
+
+        let output = stream
+            .next()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("stream ended"))??;
+
+        assert_eq!(output.runs[0].labels, vec!["release"]);
+        assert!(output.runs[1].labels.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_runs_on_the_default_branch_are_marked_by_required_checks() -> anyhow::Result<()> {
+        let (protected, private) = (
+            repo_full_name("owner/repo"),
+            repo_full_name("owner/private"),
+        );
+        let run = |repository, id, workflow, branch| {
+            WorkflowRunBuilder::new(repository)
+                .id(id)
+                .run_number(id)
+                .workflow(id, workflow)
+                .branch(Some(branch))
+                .build()
+        };
+        let github_api = Arc::new(ScriptedGitHubApi::new());
+        github_api
+            .repositories(Ok(vec![
+                repository().with_default_branch(Some("main".to_string())),
+                Repository::from_full_name(&private).with_default_branch(Some("main".to_string())),
+            ]))
+            .workflow_runs(
+                &protected,
+                Ok(vec![
+                    run("owner/repo", 3, "CI", "main"),
+                    run("owner/repo", 2, "Deploy", "main"),
+                    run("owner/repo", 1, "CI", "feature"),
+                ]),
+            )
+            .workflow_runs(&private, Ok(vec![run("owner/private", 4, "CI", "main")]))
+            .required_checks(&protected, "main", Ok(vec!["CI / build".to_string()]))
+            .required_checks(
+                &private,
+                "main",
+                GitHubApiError::Forbidden {
+                    resource: "required checks of owner/private branch main".to_string(),
+                    message: "Must have admin rights to Repository.".to_string(),
+                },
+            );
+        let interactor = StreamGitHubActionsRunsInteractor::new(github_api);
+        let (_config, input) = input(StreamConfig::default());
+        let stream = interactor.execute(input);
+        tokio::pin!(stream);
+
+        let output = stream
+            .next()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("stream ended"))??;
+
+        let marks: Vec<(RunId, Option<bool>)> = output
+            .runs
+            .iter()
+            .map(|run| (run.id, run.is_required_check))
+            .collect();
+        assert_eq!(
+            marks,
+            [
+                (RunId(4), None),
+                (RunId(3), Some(true)),
+                (RunId(2), Some(false)),
+                (RunId(1), None),
+            ]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_scheduled_workflows_are_listed_as_upcoming_runs() -> anyhow::Result<()> {
+        let repo = repo_full_name("owner/repo");
+        let github_api = github_api(Vec::new());
+        github_api
+            .workflows(
+                &repo,
+                Ok(vec![
+                    workflow(1, WorkflowState::Active),
+                    workflow(2, WorkflowState::Active),
+                ]),
+            )
+            .workflow_definition(
+                &repo,
+                ".github/workflows/workflow-1.yml",
+                Ok(WorkflowDefinition {
+                    schedules: vec!["*/5 * * * *".to_string()],
+                    ..WorkflowDefinition::default()
+                }),
+            )
+            .workflow_definition(
+                &repo,
+                ".github/workflows/workflow-2.yml",
+                Ok(WorkflowDefinition::default()),
+            );
+        let interactor = StreamGitHubActionsRunsInteractor::new(github_api);
+        let (_config, input) = input(StreamConfig {
+            upcoming_scheduled_runs: true,
+            ..StreamConfig::default()
+        });
+        let stream = interactor.execute(input);
+        tokio::pin!(stream);
+
+        let output = stream
+            .next()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("stream ended"))??;
+
+        let [upcoming] = output.upcoming.as_slice() else {
+            anyhow::bail!("expected one upcoming run, got {:?}", output.upcoming);
+        };
+        assert_eq!(upcoming.workflow_id, WorkflowId(1));
+        assert!(upcoming.scheduled_at <= output.generated_at + TimeDelta::minutes(5));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_commit_statuses_of_the_latest_commit_are_listed_as_external_checks()
+    -> anyhow::Result<()> {
+        let repo = repo_full_name("owner/repo");
+        let (older, latest) = (
+            WorkflowRunBuilder::new("owner/repo")
+                .id(1)
+                .created_at(base_time())
+                .build(),
+            WorkflowRunBuilder::new("owner/repo")
+                .id(2)
+                .created_at(base_time() + TimeDelta::minutes(1))
+                .build(),
+        );
+        let status = |context: &str, state| CommitStatus {
+            context: context.to_string(),
+            state,
+            description: None,
+            target_url: None,
+            created_at: base_time(),
+            updated_at: base_time(),
+        };
+        let github_api = github_api(vec![latest.clone(), older]);
+        github_api.combined_status(
+            &repo,
+            &latest.head_sha,
+            Ok(vec![
+                status("jenkins/build", CommitStatusState::Success),
+                status("jenkins/deploy", CommitStatusState::Pending),
+                status("jenkins/lint", CommitStatusState::Failure),
+                status("CI", CommitStatusState::Success),
+            ]),
+        );
+        let interactor = StreamGitHubActionsRunsInteractor::new(Arc::clone(&github_api));
+        let (_config, input) = input(StreamConfig {
+            commit_status_repos: vec![repo.clone()],
+            ..StreamConfig::default()
+        });
+        let stream = interactor.execute(input);
+        tokio::pin!(stream);
+
+        let output = stream
+            .next()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("stream ended"))??;
+
+        let checks: Vec<_> = output
+            .external_checks
+            .iter()
+            .map(|check| (check.context.as_str(), check.state))
+            .collect();
+        assert_eq!(
+            checks,
+            [
+                ("jenkins/build", CommitStatusState::Success),
+                ("jenkins/deploy", CommitStatusState::Pending),
+                ("jenkins/lint", CommitStatusState::Failure),
+            ]
+        );
+        assert!(
+            output
+                .external_checks
+                .iter()
+                .all(|check| check.head_sha == latest.head_sha)
+        );
+        assert_eq!(
+            github_api
+                .calls()
+                .iter()
+                .filter(|call| call.starts_with("fetch_combined_status"))
+                .count(),
+            1
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_single_shown_workflow_is_listed_on_its_own() -> anyhow::Result<()> {
+        let repo = repo_full_name("owner/repo");
+        let named = |id, name: &str| Workflow {
+            name: name.to_string(),
+            ..workflow(id, WorkflowState::Active)
+        };
+        let github_api = Arc::new(ScriptedGitHubApi::new());
+        github_api
+            .workflows(&repo, Ok(vec![named(1, "CI"), named(2, "stale-bot")]))
+            .workflow_runs_of_workflow(
+                &repo,
+                WorkflowId(1),
+                Ok(vec![
+                    WorkflowRunBuilder::new("owner/repo")
+                        .workflow(1, "CI")
+                        .build(),
+                ]),
+            );
+        let interactor = StreamGitHubActionsRunsInteractor::new(Arc::clone(&github_api));
+        let (_config, input) = input(StreamConfig {
+            repo_allowlist: vec![repo.clone()],
+            repo_workflow_filters: [(
+                repo.clone(),
+                WorkflowFilter {
+                    ignore: Vec::new(),
+                    only: vec!["C*".parse()?],
+                },
+            )]
+            .into(),
+            ..StreamConfig::default()
+        });
+        let stream = interactor.execute(input);
+        tokio::pin!(stream);
+
+        let output = stream
+            .next()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("stream ended"))??;
+
+        assert_eq!(output.runs.len(), 1);
+        assert_eq!(
+            github_api.calls()[1],
+            "fetch_workflow_runs_of_workflow owner/repo 1 2"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_queued_runs_beyond_the_per_repo_cap_are_counted() -> anyhow::Result<()> {
+        let queued = |id| {
+            WorkflowRunBuilder::new("owner/repo")
+                .id(id)
+                .status(RunStatus::Queued)
+                .build()
+        };
+        let github_api = github_api(vec![queued(3), queued(2)]);
+        github_api.workflow_runs_by_status(
+            &repo_full_name("owner/repo"),
+            RunStatus::Queued,
+            Ok(vec![queued(3), queued(2), queued(1)]),
+        );
+        let interactor = StreamGitHubActionsRunsInteractor::new(github_api);
+        let stream = interactor.execute(input(StreamConfig::default()).1);
+        tokio::pin!(stream);
+
+        let output = stream
+            .next()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("stream ended"))??;
+
+        assert_eq!(output.runs.len(), 2);
+        assert_eq!(output.queue.len(), 1);
+        assert_eq!(output.queue[0].queued, 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_queued_runs_are_listed_once_per_loop() -> anyhow::Result<()> {
+        let repo = repo_full_name("owner/repo");
+        let run = |id, status| {
+            WorkflowRunBuilder::new("owner/repo")
+                .id(id)
+                .status(status)
+                .build()
+        };
+        let github_api = github_api(vec![
+            run(2, RunStatus::InProgress),
+            run(1, RunStatus::Queued),
+        ]);
+        github_api
+            .workflow_runs(
+                &repo,
+                Ok(vec![
+                    run(3, RunStatus::Queued),
+                    run(1, RunStatus::InProgress),
+                ]),
+            )
+            .workflow_runs_by_status(
+                &repo,
+                RunStatus::Queued,
+                Ok(vec![run(1, RunStatus::Queued), run(0, RunStatus::Queued)]),
+            );
+        let interactor = StreamGitHubActionsRunsInteractor::new(Arc::clone(&github_api));
+        let stream = interactor.execute(
+            input(StreamConfig {
+                poll_interval_seconds: 0,
+                ..StreamConfig::default()
+            })
+            .1,
+        );
+        tokio::pin!(stream);
+
+        let mut queues = Vec::new();
+        for _ in 0..FETCH_ITERATIONS {
+            let output = stream
+                .next()
+                .await
+                .ok_or_else(|| anyhow::anyhow!("stream ended"))??;
+            let mut run_ids = output.queue[0].run_ids.clone();
+            run_ids.sort();
+            queues.push(run_ids);
+        }
+
+        // Run 1 started and run 3 got queued in between, while run 0 stays queued
+        assert_eq!(queues[0], [RunId(0), RunId(1)]);
+        assert_eq!(queues[1], [RunId(0), RunId(3)]);
+        let listings = github_api
+            .calls()
+            .iter()
+            .filter(|call| call.starts_with("fetch_workflow_runs_by_status"))
+            .count();
+        assert_eq!(listings, 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_queue_falls_back_to_shown_runs_when_github_cannot_filter() -> anyhow::Result<()> {
+        let queued = WorkflowRunBuilder::new("owner/repo")
+            .status(RunStatus::Queued)
+            .build();
+        // Nothing scripted for the status filter, so listing queued runs fails
+        let interactor = StreamGitHubActionsRunsInteractor::new(github_api(vec![queued.clone()]));
+        let stream = interactor.execute(input(StreamConfig::default()).1);
+        tokio::pin!(stream);
+
+        let output = stream
+            .next()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("stream ended"))??;
+
+        assert_eq!(output.queue[0].run_ids, vec![queued.id]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_old_runs_of_disabled_workflows_are_dropped() -> anyhow::Result<()> {
+        let run = |id: u64, workflow_id| {
+            WorkflowRunBuilder::new("owner/repo")
+                .id(id)
+                .workflow(workflow_id, "CI")
+                .created_at(base_time() + TimeDelta::minutes(i64::try_from(id).unwrap_or_default()))
+        };
+        let github_api = github_api(vec![
+            run(1, 1).build(),
+            run(2, 2).build(),
+            run(3, 2).created_minutes_ago(0).build(),
+        ]);
+        github_api.workflows(
+            &repo_full_name("owner/repo"),
+            Ok(vec![
+                workflow(1, WorkflowState::Active),
+                workflow(2, WorkflowState::DisabledManually),
+            ]),
+        );
+        let interactor = StreamGitHubActionsRunsInteractor::new(github_api);
+        let (_config, input) = input(StreamConfig::default());
+        let workflows = Arc::clone(&input.workflows);
+        let stream = interactor.execute(input);
+        tokio::pin!(stream);
+
+        let output = stream
+            .next()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("stream ended"))??;
+
+        let ids: Vec<RunId> = output.runs.iter().map(|run| run.id).collect();
+        assert_eq!(ids, vec![RunId(3), RunId(1)]);
+        let entry = workflows
+            .get(&repo_full_name("owner/repo"))
+            .await
+            .ok_or_else(|| anyhow::anyhow!("inventory not refreshed"))?;
+        assert_eq!(entry.workflows.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_inactive_workflow_runs_are_kept_up_to_max_age() {
+        let mut run = workflow_run("owner/repo", 1, "failure");
+        run.workflow_id = WorkflowId(2);
+        let inactive = HashSet::from([WorkflowId(2)]);
+
+        let mut runs = vec![run.clone()];
+        let now = run.created_at + TimeDelta::days(INACTIVE_WORKFLOW_RUN_MAX_AGE_DAYS);
+        drop_old_runs_of_inactive_workflows(&mut runs, &inactive, now);
+        assert_eq!(runs.len(), 1);
+
+        drop_old_runs_of_inactive_workflows(&mut runs, &inactive, now + TimeDelta::seconds(1));
+        assert!(runs.is_empty());
+    }
+
+    /// GitHub APIのレート制限（認証済みリクエストの場合）
+    const GITHUB_API_RATE_LIMIT_PER_HOUR: u32 = 5_000;
+    const GITHUB_API_RATE_LIMIT_ENTERPRISE_PER_HOUR: u32 = 15_000;
+
+    /// `FETCH_ITERATIONS` を u64 として扱うための定数（u8/u32 範囲内に収まることが保証されている）
+    const FETCH_ITERATIONS_U64: u64 = FETCH_ITERATIONS as u64;
+
+    /// 1時間あたりのAPI呼び出し回数を計算する（すべて u64 で計算）
+    const fn calc_api_calls_per_hour_u64() -> u64 {
+        let api_calls_per_iteration = 1u64 + 2 * (MAX_REPOSITORIES_TO_FETCH as u64);
+        let api_calls_per_loop = api_calls_per_iteration * FETCH_ITERATIONS_U64;
+        let seconds_per_loop = FETCH_ITERATIONS_U64 * ITERATION_WAIT_SECONDS;
+        let loops_per_hour = 3600 / seconds_per_loop;
+        api_calls_per_loop * loops_per_hour
+    }
+
+    #[test]
+    fn test_api_calls_do_not_exceed_rate_limit() {
+        let max_api_calls_per_hour = calc_api_calls_per_hour_u64();
+        let rate_limit = u64::from(GITHUB_API_RATE_LIMIT_PER_HOUR);
+
+        // 標準のレート制限（5,000リクエスト/時間）を超えないことを確認
+        assert!(
+            max_api_calls_per_hour <= rate_limit,
+            "API呼び出し回数（{max_api_calls_per_hour}回/時間）がGitHubのレート制限（{rate_limit}回/時間）を超えています",
+        );
+
+        // デバッグ情報を出力
+        let api_calls_per_iteration = 1u64 + 2 * u64::from(MAX_REPOSITORIES_TO_FETCH);
+        let api_calls_per_loop = api_calls_per_iteration * FETCH_ITERATIONS_U64;
+        let seconds_per_loop = FETCH_ITERATIONS_U64 * ITERATION_WAIT_SECONDS;
+        let loops_per_hour = 3600 / seconds_per_loop;
+        println!("=== GitHub APIレート制限チェック ===");
+        println!("1イテレーションあたりのAPI呼び出し: {api_calls_per_iteration}回");
+        println!("1ループあたりのAPI呼び出し: {api_calls_per_loop}回");
+        println!("1ループの所要時間: {seconds_per_loop}秒");
+        println!("1時間あたりのループ回数: {loops_per_hour}回");
+        println!("1時間あたりの最大API呼び出し: {max_api_calls_per_hour}回");
+        println!("GitHubレート制限（標準）: {rate_limit}回/時間");
+        let margin = rate_limit - max_api_calls_per_hour;
+        let margin_pct = margin * 100 / rate_limit;
+        println!("安全マージン: {margin}回/時間（{margin_pct}%）");
+    }
+
+    #[test]
+    fn test_api_calls_efficiency() {
+        // 効率性の確認：レート制限の80%以下の使用率を推奨
+        // 80% = 4/5 なので整数演算で計算
+        let recommended_max = u64::from(GITHUB_API_RATE_LIMIT_PER_HOUR) * 4 / 5;
+
+        let max_api_calls_per_hour = calc_api_calls_per_hour_u64();
+
+        assert!(
+            max_api_calls_per_hour <= recommended_max,
+            "API呼び出し回数（{max_api_calls_per_hour}回/時間）が推奨される使用率（レート制限の80%: {recommended_max}回/時間）を超えています",
+        );
+    }
+
+    #[test]
+    fn test_api_calls_calculation_breakdown() {
+        // 計算の内訳を詳細に検証
+        assert_eq!(
+            MAX_REPOSITORIES_TO_FETCH, 5,
+            "MAX_REPOSITORIES_TO_FETCHが変更されています"
+        );
+        assert_eq!(FETCH_ITERATIONS, 2, "FETCH_ITERATIONSが変更されています");
+        assert_eq!(
+            ITERATION_WAIT_SECONDS, 30,
+            "ITERATION_WAIT_SECONDSが変更されています"
+        );
+
+        // 期待される値
+        let expected_api_calls_per_iteration = 11u64; // 1 + 2 * 5（ラン一覧とキュー中のラン一覧）
+        let expected_api_calls_per_loop = 22u64; // 11 * 2
+        let expected_seconds_per_loop = 60u64; // 2 * 30
+        let expected_loops_per_hour = 60u64; // 3600 / 60
+        let expected_max_api_calls_per_hour = 1320u64; // 22 * 60
+
+        // 実際の計算
+        let api_calls_per_iteration = 1u64 + 2 * u64::from(MAX_REPOSITORIES_TO_FETCH);
+        let api_calls_per_loop = api_calls_per_iteration * FETCH_ITERATIONS_U64;
+        let seconds_per_loop = FETCH_ITERATIONS_U64 * ITERATION_WAIT_SECONDS;
+        let loops_per_hour = 3600 / seconds_per_loop;
+        let max_api_calls_per_hour = api_calls_per_loop * loops_per_hour;
+
+        // 検証
+        assert_eq!(api_calls_per_iteration, expected_api_calls_per_iteration);
+        assert_eq!(api_calls_per_loop, expected_api_calls_per_loop);
+        assert_eq!(seconds_per_loop, expected_seconds_per_loop);
+        assert_eq!(loops_per_hour, expected_loops_per_hour);
+        assert_eq!(max_api_calls_per_hour, expected_max_api_calls_per_hour);
+
+        // 最終確認：1,320回/時間 << 5,000回/時間
+        assert!(max_api_calls_per_hour < u64::from(GITHUB_API_RATE_LIMIT_PER_HOUR));
+    }
+
+    #[test]
+    fn test_enterprise_rate_limit_compliance() {
+        // Enterprise Cloudのレート制限（15,000リクエスト/時間）でも問題ないことを確認
+        let max_api_calls_per_hour = calc_api_calls_per_hour_u64();
+        let enterprise_limit = u64::from(GITHUB_API_RATE_LIMIT_ENTERPRISE_PER_HOUR);
+
+        assert!(
+            max_api_calls_per_hour <= enterprise_limit,
+            "API呼び出し回数（{max_api_calls_per_hour}回/時間）がGitHub Enterprise Cloudのレート制限（{enterprise_limit}回/時間）を超えています",
+        );
+    }
+
+    #[tokio::test]
+    async fn test_github_errors_end_the_stream() -> anyhow::Result<()> {
+        let error = GitHubApiError::Unauthorized {
+            resource: "repositories".to_string(),
+        };
+        let github_api = ScriptedGitHubApi::new();
+        github_api.repositories(error.clone());
+        let interactor = StreamGitHubActionsRunsInteractor::new(Arc::new(github_api));
+        let (_config, input) = input(StreamConfig::default());
+        let stream = interactor.execute(input);
+        tokio::pin!(stream);
+
+        let first = stream.next().await;
+
+        assert_eq!(
+            first.and_then(Result::err),
+            Some(StreamGitHubActionsRunsError::GitHubApi(error))
+        );
+        assert!(stream.next().await.is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_runs_timing_out_keep_the_previous_ones() -> anyhow::Result<()> {
+        let repo = repo_full_name("owner/repo");
+        let github_api = ScriptedGitHubApi::new();
+        github_api
+            .repositories(Ok(vec![repository()]))
+            .workflow_runs(&repo, Ok(vec![workflow_run("owner/repo", 1, "success")]))
+            .workflow_runs(
+                &repo,
+                GitHubApiError::Timeout {
+                    resource: "workflow runs for owner/repo".to_string(),
+                    deadline_ms: 20_000,
+                },
+            );
+        let interactor = StreamGitHubActionsRunsInteractor::new(Arc::new(github_api));
+        let (_config, input) = input(StreamConfig {
+            poll_interval_seconds: 0,
+            ..StreamConfig::default()
+        });
+        let stream = interactor.execute(input);
+        tokio::pin!(stream);
+
+        for _ in 0..2 {
+            let output = timeout(Duration::from_secs(5), stream.next())
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("stream ended"))??;
+            let ids: Vec<u64> = output.runs.iter().map(|run| run.id.0).collect();
+            assert_eq!(ids, [1]);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_polling_slows_down_while_webhooks_flow() {
+        let now = base_time();
+
+        assert_eq!(poll_wait(30, None, now), Duration::from_secs(30));
+        assert_eq!(
+            poll_wait(30, Some(now - TimeDelta::minutes(1)), now),
+            Duration::from_secs(RECONCILIATION_INTERVAL_SECONDS)
+        );
+        // Webhooks stopped arriving, so polling resumes its normal pace
+        assert_eq!(
+            poll_wait(30, Some(now - TimeDelta::minutes(30)), now),
+            Duration::from_secs(30)
+        );
+        // A poll interval longer than the reconciliation interval is kept
+        assert_eq!(poll_wait(3_600, Some(now), now), Duration::from_hours(1));
+    }
+}
@@ -0,0 +1,548 @@
+use super::{
+    FETCH_ITERATIONS, INACTIVE_REPO_RUN_MAX_AGE_HOURS, ITERATION_WAIT_SECONDS,
+    MAX_INACTIVE_REPO_RUNS, MAX_REPOSITORIES_TO_FETCH, MAX_WORKFLOW_RUNS_PER_REPO,
+};
+use crate::domain::models::glob::GlobPattern;
+use crate::domain::models::ids::RepoFullName;
+use crate::domain::models::label::{self, LabelRule};
+use crate::domain::models::run::WorkflowRun;
+use crate::domain::models::workflow_filter::WorkflowFilter;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// GitHub API calls per hour the poller may spend, 80% of the authenticated rate limit
+pub const API_CALL_BUDGET_PER_HOUR: u64 = 4_000;
+
+/// Bounds of the poll interval (seconds)
+const MIN_POLL_INTERVAL_SECONDS: u64 = 5;
+const MAX_POLL_INTERVAL_SECONDS: u64 = 3_600;
+
+/// Largest page size accepted by the GitHub API
+const MAX_PER_PAGE: u64 = 100;
+
+/// Runtime-adjustable settings of the polling loop.
+///
+/// Holds no credentials, so it can be returned to operators as is.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct StreamConfig {
+    /// Wait between two snapshots
+    #[serde(rename = "pollIntervalSeconds")]
+    pub poll_interval_seconds: u64,
+    /// Number of recently updated repositories polled when the allowlist is empty
+    #[serde(rename = "maxRepositories")]
+    pub max_repositories: u8,
+    /// Runs shown per repository
+    #[serde(rename = "maxRunsPerRepo")]
+    pub max_runs_per_repo: u8,
+    /// `owner/name` of the repositories to poll; empty polls the most recently updated ones
+    #[serde(rename = "repoAllowlist")]
+    pub repo_allowlist: Vec<RepoFullName>,
+    /// Only publish runs that failed or need attention
+    #[serde(rename = "problemOnly")]
+    pub problem_only: bool,
+    /// Collapse the pull request runs bots such as Dependabot start into one entry of
+    /// `botGroups` per repository; they no longer count toward `maxRunsPerRepo`
+    #[serde(rename = "groupBotRuns")]
+    pub group_bot_runs: bool,
+    /// List the next run of each scheduled workflow in snapshots, as `upcoming`
+    #[serde(rename = "upcomingScheduledRuns")]
+    pub upcoming_scheduled_runs: bool,
+    /// Repositories whose commit statuses, e.g. from Jenkins, are listed next to their runs as
+    /// `externalChecks`, at one more GitHub API call per repository and snapshot
+    #[serde(rename = "commitStatusRepos")]
+    pub commit_status_repos: Vec<RepoFullName>,
+    /// Workflows shown in every repository
+    #[serde(rename = "workflowFilter")]
+    pub workflow_filter: WorkflowFilter,
+    /// Refinements of `workflowFilter` for individual repositories
+    #[serde(rename = "repoWorkflowFilters")]
+    pub repo_workflow_filters: BTreeMap<RepoFullName, WorkflowFilter>,
+    /// Rules labelling the published runs; set at startup only
+    pub labels: Vec<LabelRule>,
+    /// Runs of repositories that dropped out of the polled ones stay shown until they are
+    /// this old, measured from their last update; set at startup only
+    #[serde(rename = "inactiveRepoRunMaxAgeHours")]
+    pub inactive_repo_run_max_age_hours: u32,
+    /// Most runs of repositories that dropped out of the polled ones kept shown, newest
+    /// first; set at startup only
+    #[serde(rename = "maxInactiveRepoRuns")]
+    pub max_inactive_repo_runs: u32,
+}
+
+impl Default for StreamConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval_seconds: ITERATION_WAIT_SECONDS,
+            max_repositories: MAX_REPOSITORIES_TO_FETCH,
+            max_runs_per_repo: MAX_WORKFLOW_RUNS_PER_REPO,
+            repo_allowlist: Vec::new(),
+            problem_only: false,
+            group_bot_runs: false,
+            upcoming_scheduled_runs: false,
+            commit_status_repos: Vec::new(),
+            workflow_filter: WorkflowFilter::default(),
+            repo_workflow_filters: BTreeMap::new(),
+            labels: Vec::new(),
+            inactive_repo_run_max_age_hours: INACTIVE_REPO_RUN_MAX_AGE_HOURS,
+            max_inactive_repo_runs: MAX_INACTIVE_REPO_RUNS,
+        }
+    }
+}
+
+/// Partial update of a [`StreamConfig`]; absent fields are left unchanged.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct StreamConfigPatch {
+    /// See [`StreamConfig::poll_interval_seconds`]
+    #[serde(rename = "pollIntervalSeconds")]
+    pub poll_interval_seconds: Option<u64>,
+    /// See [`StreamConfig::max_runs_per_repo`]
+    #[serde(rename = "maxRunsPerRepo")]
+    pub max_runs_per_repo: Option<u64>,
+    /// See [`StreamConfig::repo_allowlist`]
+    #[serde(rename = "repoAllowlist")]
+    pub repo_allowlist: Option<Vec<String>>,
+    /// See [`StreamConfig::problem_only`]
+    #[serde(rename = "problemOnly")]
+    pub problem_only: Option<bool>,
+    /// See [`StreamConfig::group_bot_runs`]
+    #[serde(rename = "groupBotRuns")]
+    pub group_bot_runs: Option<bool>,
+    /// See [`StreamConfig::upcoming_scheduled_runs`]
+    #[serde(rename = "upcomingScheduledRuns")]
+    pub upcoming_scheduled_runs: Option<bool>,
+    /// See [`StreamConfig::commit_status_repos`]
+    #[serde(rename = "commitStatusRepos")]
+    pub commit_status_repos: Option<Vec<String>>,
+    /// Replaces the `ignore` patterns of [`StreamConfig::workflow_filter`]
+    #[serde(rename = "ignoreWorkflows")]
+    pub ignore_workflows: Option<Vec<String>>,
+    /// Replaces the `only` patterns of [`StreamConfig::workflow_filter`]
+    #[serde(rename = "onlyWorkflows")]
+    pub only_workflows: Option<Vec<String>>,
+    /// Replaces the filters of every repository, keyed by `owner/name`
+    #[serde(rename = "repoWorkflowFilters")]
+    pub repo_workflow_filters: Option<BTreeMap<String, WorkflowFilterPatch>>,
+}
+
+/// Workflow filter of one repository in a [`StreamConfigPatch`].
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct WorkflowFilterPatch {
+    /// Workflow names or patterns whose runs are hidden
+    #[serde(rename = "ignoreWorkflows", default)]
+    pub ignore_workflows: Vec<String>,
+    /// Workflow names or patterns whose runs are the only ones shown
+    #[serde(rename = "onlyWorkflows", default)]
+    pub only_workflows: Vec<String>,
+}
+
+/// Parses the workflow patterns of the patch field `field`, reporting each invalid one.
+fn workflow_patterns(
+    field: &str,
+    values: &[String],
+    violations: &mut Vec<String>,
+) -> Vec<GlobPattern> {
+    values
+        .iter()
+        .filter_map(|value| {
+            value
+                .parse()
+                .inspect_err(|e| violations.push(format!("{field} entry is invalid: {e}")))
+                .ok()
+        })
+        .collect()
+}
+
+/// Parses the `owner/name` entries of the patch field `field`, reporting each invalid one.
+fn repo_names(field: &str, values: &[String], violations: &mut Vec<String>) -> Vec<RepoFullName> {
+    values
+        .iter()
+        .filter_map(|value| {
+            value
+                .parse()
+                .inspect_err(|_| {
+                    violations.push(format!(
+                        "{field} entry {value:?} must be of the form owner/name"
+                    ));
+                })
+                .ok()
+        })
+        .collect()
+}
+
+/// A config patch that violates one or more constraints.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Invalid stream config: {}", .violations.join("; "))]
+pub struct InvalidStreamConfig {
+    /// One message per constraint violated
+    pub violations: Vec<String>,
+}
+
+impl StreamConfig {
+    /// The workflow filter applied to the runs of `repo`.
+    #[must_use]
+    pub fn workflow_filter_for(&self, repo: &RepoFullName) -> WorkflowFilter {
+        match self.repo_workflow_filters.get(repo) {
+            Some(filter) => self.workflow_filter.refined_by(filter),
+            None => self.workflow_filter.clone(),
+        }
+    }
+
+    /// Sets the labels of `run` from the label rules.
+    pub fn label(&self, run: &mut WorkflowRun) {
+        run.labels = label::labels(&self.labels, run);
+    }
+
+    /// Estimate of the GitHub API calls per hour made with this config, with no runs pinned.
+    #[must_use]
+    pub fn estimated_api_calls_per_hour(&self) -> u64 {
+        self.estimated_api_calls_per_hour_with_pins(0)
+    }
+
+    /// Estimate of the GitHub API calls per hour made with this config while `pinned_runs`
+    /// runs are pinned.
+    ///
+    /// Besides the calls of every loop, it counts those cached for an hour: the required
+    /// checks of each repository's default branch and, with `upcoming_scheduled_runs`, the
+    /// definition of one workflow per repository. Left out are the calls the config cannot
+    /// tell the number of: the definitions of further workflows, and the checks of
+    /// repositories that dropped out of the listing. Grouping bot runs lists a larger page of
+    /// runs rather than another one, so it costs nothing.
+    #[must_use]
+    pub fn estimated_api_calls_per_hour_with_pins(&self, pinned_runs: usize) -> u64 {
+        let (repository_calls, repositories) = if self.repo_allowlist.is_empty() {
+            (1, u64::from(self.max_repositories))
+        } else {
+            (0, self.repo_allowlist.len() as u64)
+        };
+        let iterations = FETCH_ITERATIONS as u64;
+        // Each repository's workflows and queued runs are listed once per loop, then its runs
+        // every iteration, along with the commit statuses of the opted-in ones and each
+        // pinned run, at worst fetched on its own
+        let calls_per_loop = repository_calls
+            + repositories * (iterations + 2)
+            + (self.commit_status_repos.len() + pinned_runs) as u64 * iterations;
+        let seconds_per_loop = (iterations * self.poll_interval_seconds).max(1);
+        let hourly_calls = if self.upcoming_scheduled_runs {
+            repositories * 2
+        } else {
+            repositories
+        };
+        // Round up so partial loops at the end of the hour are counted
+        (calls_per_loop * 3_600).div_ceil(seconds_per_loop) + hourly_calls
+    }
+
+    /// Checks that polling with this config while `pinned_runs` runs are pinned stays within
+    /// [`API_CALL_BUDGET_PER_HOUR`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InvalidStreamConfig`] telling how many calls per hour would be made.
+    pub fn check_budget(&self, pinned_runs: usize) -> Result<(), InvalidStreamConfig> {
+        let api_calls_per_hour = self.estimated_api_calls_per_hour_with_pins(pinned_runs);
+        if api_calls_per_hour <= API_CALL_BUDGET_PER_HOUR {
+            return Ok(());
+        }
+        Err(InvalidStreamConfig {
+            violations: vec![format!(
+                "configuration would make {api_calls_per_hour} GitHub API calls per hour, exceeding the budget of {API_CALL_BUDGET_PER_HOUR}"
+            )],
+        })
+    }
+
+    /// Returns this config with `patch` applied, or every constraint the result violates.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InvalidStreamConfig`] listing each violated constraint.
+    pub fn apply(&self, patch: &StreamConfigPatch) -> Result<Self, InvalidStreamConfig> {
+        self.apply_with_pins(patch, 0)
+    }
+
+    /// Like [`Self::apply`], keeping the API calls of `pinned_runs` pinned runs within budget.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InvalidStreamConfig`] listing each violated constraint.
+    pub fn apply_with_pins(
+        &self,
+        patch: &StreamConfigPatch,
+        pinned_runs: usize,
+    ) -> Result<Self, InvalidStreamConfig> {
+        let mut violations = Vec::new();
+        let mut config = self.clone();
+
+        if let Some(poll_interval_seconds) = patch.poll_interval_seconds {
+            if (MIN_POLL_INTERVAL_SECONDS..=MAX_POLL_INTERVAL_SECONDS)
+                .contains(&poll_interval_seconds)
+            {
+                config.poll_interval_seconds = poll_interval_seconds;
+            } else {
+                violations.push(format!(
+                    "pollIntervalSeconds must be between {MIN_POLL_INTERVAL_SECONDS} and {MAX_POLL_INTERVAL_SECONDS}"
+                ));
+            }
+        }
+
+        if let Some(max_runs_per_repo) = patch.max_runs_per_repo {
+            match u8::try_from(max_runs_per_repo) {
+                Ok(value) if value >= 1 && u64::from(value) <= MAX_PER_PAGE => {
+                    config.max_runs_per_repo = value;
+                }
+                _ => violations.push(format!(
+                    "maxRunsPerRepo must be between 1 and {MAX_PER_PAGE}"
+                )),
+            }
+        }
+
+        if let Some(repo_allowlist) = &patch.repo_allowlist {
+            config.repo_allowlist = repo_names("repoAllowlist", repo_allowlist, &mut violations);
+        }
+
+        if let Some(problem_only) = patch.problem_only {
+            config.problem_only = problem_only;
+        }
+        if let Some(group_bot_runs) = patch.group_bot_runs {
+            config.group_bot_runs = group_bot_runs;
+        }
+        if let Some(upcoming_scheduled_runs) = patch.upcoming_scheduled_runs {
+            config.upcoming_scheduled_runs = upcoming_scheduled_runs;
+        }
+        if let Some(commit_status_repos) = &patch.commit_status_repos {
+            config.commit_status_repos =
+                repo_names("commitStatusRepos", commit_status_repos, &mut violations);
+        }
+
+        if let Some(ignore_workflows) = &patch.ignore_workflows {
+            config.workflow_filter.ignore =
+                workflow_patterns("ignoreWorkflows", ignore_workflows, &mut violations);
+        }
+        if let Some(only_workflows) = &patch.only_workflows {
+            config.workflow_filter.only =
+                workflow_patterns("onlyWorkflows", only_workflows, &mut violations);
+        }
+        if let Some(repo_workflow_filters) = &patch.repo_workflow_filters {
+            config.repo_workflow_filters = repo_workflow_filters
+                .iter()
+                .filter_map(|(repo, filter)| {
+                    let filter = WorkflowFilter {
+                        ignore: workflow_patterns(
+                            &format!("repoWorkflowFilters.{repo}.ignoreWorkflows"),
+                            &filter.ignore_workflows,
+                            &mut violations,
+                        ),
+                        only: workflow_patterns(
+                            &format!("repoWorkflowFilters.{repo}.onlyWorkflows"),
+                            &filter.only_workflows,
+                            &mut violations,
+                        ),
+                    };
+                    let repo = repo
+                        .parse()
+                        .inspect_err(|_| {
+                            violations.push(format!(
+                                "repoWorkflowFilters key {repo:?} must be of the form owner/name"
+                            ));
+                        })
+                        .ok()?;
+                    Some((repo, filter))
+                })
+                .collect();
+        }
+
+        // Only meaningful once the individual fields are valid
+        if violations.is_empty()
+            && let Err(e) = config.check_budget(pinned_runs)
+        {
+            violations.extend(e.violations);
+        }
+
+        if violations.is_empty() {
+            Ok(config)
+        } else {
+            Err(InvalidStreamConfig { violations })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::repo_full_name;
+
+    #[test]
+    fn test_default_config_is_within_budget() {
+        let config = StreamConfig::default();
+        // 1 repository listing + 5 repositories * (workflows + queued runs + 2 * runs)
+        // every 2 * 30 seconds, and the required checks of the 5 repositories once an hour
+        assert_eq!(config.estimated_api_calls_per_hour(), 1_265);
+        assert!(config.estimated_api_calls_per_hour() <= API_CALL_BUDGET_PER_HOUR);
+    }
+
+    #[test]
+    fn test_patch_updates_only_given_fields() -> anyhow::Result<()> {
+        let patch = StreamConfigPatch {
+            poll_interval_seconds: Some(60),
+            repo_allowlist: Some(vec!["owner/repo".to_string()]),
+            ..StreamConfigPatch::default()
+        };
+
+        let config = StreamConfig::default().apply(&patch)?;
+
+        assert_eq!(config.poll_interval_seconds, 60);
+        assert_eq!(config.repo_allowlist, vec!["owner/repo".parse()?]);
+        assert_eq!(config.max_runs_per_repo, MAX_WORKFLOW_RUNS_PER_REPO);
+        assert!(!config.problem_only);
+        Ok(())
+    }
+
+    #[test]
+    fn test_every_violated_constraint_is_reported() {
+        let patch = StreamConfigPatch {
+            poll_interval_seconds: Some(1),
+            max_runs_per_repo: Some(1_000),
+            repo_allowlist: Some(vec!["not-a-full-name".to_string()]),
+            ..StreamConfigPatch::default()
+        };
+
+        let error = StreamConfig::default().apply(&patch);
+
+        assert!(matches!(error, Err(InvalidStreamConfig { violations }) if violations.len() == 3));
+    }
+
+    #[test]
+    fn test_workflow_filters_are_parsed_and_refined_per_repository() -> anyhow::Result<()> {
+        let patch = StreamConfigPatch {
+            ignore_workflows: Some(vec!["stale*".to_string()]),
+            repo_workflow_filters: Some(BTreeMap::from([(
+                "owner/repo".to_string(),
+                WorkflowFilterPatch {
+                    only_workflows: vec!["CI".to_string()],
+                    ..WorkflowFilterPatch::default()
+                },
+            )])),
+            ..StreamConfigPatch::default()
+        };
+
+        let config = StreamConfig::default().apply(&patch)?;
+
+        let filter = config.workflow_filter_for(&"owner/repo".parse()?);
+        assert!(filter.allows("CI"));
+        assert!(!filter.allows("Deploy"));
+        let other = config.workflow_filter_for(&"owner/other".parse()?);
+        assert!(!other.allows("stale-bot"));
+        assert!(other.allows("Deploy"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_workflow_patterns_are_violations() {
+        let patch = StreamConfigPatch {
+            only_workflows: Some(vec!["build-[ab]".to_string()]),
+            repo_workflow_filters: Some(BTreeMap::from([(
+                "not-a-full-name".to_string(),
+                WorkflowFilterPatch {
+                    ignore_workflows: vec![String::new()],
+                    ..WorkflowFilterPatch::default()
+                },
+            )])),
+            ..StreamConfigPatch::default()
+        };
+
+        let error = StreamConfig::default().apply(&patch);
+
+        assert!(matches!(error, Err(InvalidStreamConfig { violations }) if violations.len() == 3));
+    }
+
+    #[test]
+    fn test_over_budget_patch_is_rejected() {
+        // 20 repositories' workflows and queued runs, then their runs twice, every 2 * 10
+        // seconds, and their required checks: 14420 calls per hour
+        let patch = StreamConfigPatch {
+            poll_interval_seconds: Some(10),
+            repo_allowlist: Some((0..20).map(|i| format!("owner/repo{i}")).collect()),
+            ..StreamConfigPatch::default()
+        };
+
+        let result = StreamConfig::default().apply(&patch);
+
+        assert!(matches!(
+            result,
+            Err(InvalidStreamConfig { violations })
+                if violations.len() == 1 && violations[0].contains("14420")
+        ));
+    }
+
+    #[test]
+    fn test_queued_run_listings_count_toward_the_budget() {
+        // Without the queued runs, 10 repositories every 2 * 15 seconds would make 3610 calls,
+        // with them 4810
+        let patch = StreamConfigPatch {
+            poll_interval_seconds: Some(15),
+            repo_allowlist: Some((0..10).map(|i| format!("owner/repo{i}")).collect()),
+            ..StreamConfigPatch::default()
+        };
+
+        let result = StreamConfig::default().apply(&patch);
+
+        assert!(matches!(
+            result,
+            Err(InvalidStreamConfig { violations })
+                if violations.len() == 1 && violations[0].contains("4810")
+        ));
+    }
+
+    #[test]
+    fn test_pinned_runs_count_toward_the_budget() {
+        let config = StreamConfig::default();
+        // Each pinned run is fetched twice every 2 * 30 seconds
+        assert_eq!(
+            config.estimated_api_calls_per_hour_with_pins(22),
+            1_265 + 22 * 120
+        );
+
+        let patch = StreamConfigPatch::default();
+        assert!(config.apply_with_pins(&patch, 22).is_ok());
+        assert!(matches!(
+            config.apply_with_pins(&patch, 23),
+            Err(InvalidStreamConfig { violations })
+                if violations.len() == 1 && violations[0].contains("4025")
+        ));
+    }
+
+    #[test]
+    fn test_optional_features_are_estimated() {
+        let config = StreamConfig::default();
+        let base = config.estimated_api_calls_per_hour();
+
+        // One workflow definition per repository once an hour
+        let upcoming = StreamConfig {
+            upcoming_scheduled_runs: true,
+            ..config.clone()
+        };
+        assert_eq!(upcoming.estimated_api_calls_per_hour(), base + 5);
+
+        // One commit status listing per iteration, every 30 seconds
+        let commit_statuses = StreamConfig {
+            commit_status_repos: vec![repo_full_name("owner/repo")],
+            ..config.clone()
+        };
+        assert_eq!(commit_statuses.estimated_api_calls_per_hour(), base + 120);
+
+        // Bot runs come out of the page of runs already listed
+        let bot_runs = StreamConfig {
+            group_bot_runs: true,
+            ..config.clone()
+        };
+        assert_eq!(bot_runs.estimated_api_calls_per_hour(), base);
+
+        // An allowlist skips the repository listing, not the required checks
+        let allowlisted = StreamConfig {
+            repo_allowlist: vec![repo_full_name("owner/repo")],
+            ..config
+        };
+        // (1 * (workflows + queued runs + 2 * runs)) every 2 * 30 seconds + 1 required checks
+        assert_eq!(allowlisted.estimated_api_calls_per_hour(), 241);
+    }
+}
@@ -0,0 +1,57 @@
+use super::StreamGitHubActionsRunsUseCaseOutput;
+use crate::domain::external_apis::github::Repository;
+use crate::domain::models::commit_status::ExternalCheck;
+use crate::domain::models::run::WorkflowRun;
+use crate::domain::models::schedule::UpcomingRun;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// The parts of a [`StreamGitHubActionsRunsUseCaseOutput`] a snapshot is rebuilt from, e.g.
+/// after crossing to another replica; derived fields such as the queue are left out.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SnapshotDocument {
+    /// Runs shown, newest first
+    pub runs: Vec<WorkflowRun>,
+    /// Every polled repository
+    pub repositories: Vec<Repository>,
+    /// When the snapshot was taken
+    #[serde(rename = "generatedAt")]
+    pub generated_at: DateTime<Utc>,
+    /// Missing from documents written by an older version
+    #[serde(rename = "queuedRuns", default)]
+    pub queued_runs: Vec<WorkflowRun>,
+    /// Missing from documents written by an older version
+    #[serde(default)]
+    pub upcoming: Vec<UpcomingRun>,
+    /// Missing from documents written by an older version
+    #[serde(rename = "externalChecks", default)]
+    pub external_checks: Vec<ExternalCheck>,
+    /// Missing from documents written by an older version
+    #[serde(rename = "botRuns", default)]
+    pub bot_runs: Vec<WorkflowRun>,
+}
+
+impl From<&StreamGitHubActionsRunsUseCaseOutput> for SnapshotDocument {
+    fn from(output: &StreamGitHubActionsRunsUseCaseOutput) -> Self {
+        Self {
+            runs: output.runs.clone(),
+            repositories: output.repositories.clone(),
+            generated_at: output.generated_at,
+            queued_runs: output.queued_runs.clone(),
+            upcoming: output.upcoming.clone(),
+            external_checks: output.external_checks.clone(),
+            bot_runs: output.bot_runs.clone(),
+        }
+    }
+}
+
+impl From<SnapshotDocument> for StreamGitHubActionsRunsUseCaseOutput {
+    fn from(document: SnapshotDocument) -> Self {
+        Self::new(document.runs, document.generated_at)
+            .with_repositories(document.repositories)
+            .with_queued_runs(document.queued_runs)
+            .with_upcoming(document.upcoming)
+            .with_external_checks(document.external_checks)
+            .with_bot_runs(document.bot_runs)
+    }
+}
@@ -0,0 +1,232 @@
+use crate::domain::models::ids::RepoFullName;
+use crate::domain::models::run::WorkflowRun;
+use crate::domain::models::status::{RunConclusion, RunStatus};
+use chrono::{DateTime, Utc};
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Status a run counts as in a [`RunSummary`], from the least to the most pressing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum SummaryStatus {
+    /// Completed successfully
+    Success,
+    /// Queued, waiting for an approval, a concurrency group, or created but not yet queued
+    Queued,
+    /// Running on a runner
+    InProgress,
+    /// Completed with a conclusion that needs attention, e.g. failed or timed out
+    Failure,
+}
+
+impl SummaryStatus {
+    /// The status `run` counts as; `None` for runs that neither succeeded nor failed, e.g.
+    /// skipped ones, and statuses GitHub added since.
+    #[must_use]
+    pub fn of(run: &WorkflowRun) -> Option<Self> {
+        match run.status {
+            RunStatus::Completed => match run.conclusion {
+                Some(RunConclusion::Success) => Some(Self::Success),
+                Some(conclusion) if conclusion.is_problem() => Some(Self::Failure),
+                _ => None,
+            },
+            RunStatus::InProgress => Some(Self::InProgress),
+            RunStatus::Queued | RunStatus::Waiting | RunStatus::Requested | RunStatus::Pending => {
+                Some(Self::Queued)
+            }
+            RunStatus::Unknown => None,
+        }
+    }
+}
+
+/// Runs by the status they count as.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct StatusCounts {
+    /// Runs that count as [`SummaryStatus::Success`]
+    pub success: u64,
+    /// Runs that count as [`SummaryStatus::Failure`]
+    pub failure: u64,
+    /// Runs that count as [`SummaryStatus::InProgress`]
+    #[serde(rename = "inProgress")]
+    pub in_progress: u64,
+    /// Runs that count as [`SummaryStatus::Queued`]
+    pub queued: u64,
+    /// Runs that count as none of the others, e.g. skipped ones
+    pub other: u64,
+}
+
+/// The repository whose latest completed runs have been failing for the longest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct FailingRepository {
+    /// Repository in `owner/name` form
+    #[serde(rename = "repositoryName")]
+    pub repository_name: RepoFullName,
+    /// When the oldest of the failures since its last success completed
+    #[serde(rename = "failingSince")]
+    pub failing_since: DateTime<Utc>,
+}
+
+/// The runs a client is sent at a glance, e.g. for one big traffic light.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct RunSummary {
+    /// Runs by the status they count as
+    pub counts: StatusCounts,
+    /// The most pressing status of any run: failure, then in progress, then queued, then
+    /// success; `None` when no run counts as one of them
+    pub overall: Option<SummaryStatus>,
+    /// `None` when the latest completed run of every repository did not fail
+    #[serde(rename = "longestFailing")]
+    pub longest_failing: Option<FailingRepository>,
+    /// The latest update of any run; `None` without runs
+    #[serde(rename = "lastChangedAt")]
+    pub last_changed_at: Option<DateTime<Utc>>,
+}
+
+/// Summarizes `runs`, which should be those a client is sent, after its own filters.
+///
+/// A repository is failing when its latest completed run that succeeded or failed, by
+/// creation, failed; it has been failing since the oldest failure newer than its latest
+/// success among `runs` completed.
+#[must_use]
+pub fn summarize(runs: &[WorkflowRun]) -> RunSummary {
+    let mut counts = StatusCounts::default();
+    for run in runs {
+        let count = match SummaryStatus::of(run) {
+            Some(SummaryStatus::Success) => &mut counts.success,
+            Some(SummaryStatus::Failure) => &mut counts.failure,
+            Some(SummaryStatus::InProgress) => &mut counts.in_progress,
+            Some(SummaryStatus::Queued) => &mut counts.queued,
+            None => &mut counts.other,
+        };
+        *count += 1;
+    }
+
+    let mut completed: BTreeMap<&RepoFullName, Vec<(&WorkflowRun, SummaryStatus)>> =
+        BTreeMap::new();
+    for run in runs {
+        if let Some(status @ (SummaryStatus::Success | SummaryStatus::Failure)) =
+            SummaryStatus::of(run)
+        {
+            completed
+                .entry(&run.repository_name)
+                .or_default()
+                .push((run, status));
+        }
+    }
+    let longest_failing = completed
+        .into_iter()
+        .filter_map(|(repository_name, mut runs)| {
+            runs.sort_by_key(|(run, _)| std::cmp::Reverse(run.created_at));
+            let failing_since = runs
+                .iter()
+                .take_while(|(_, status)| *status == SummaryStatus::Failure)
+                .map(|(run, _)| run.updated_at)
+                .min()?;
+            Some(FailingRepository {
+                repository_name: repository_name.clone(),
+                failing_since,
+            })
+        })
+        .min_by_key(|failing| failing.failing_since);
+
+    RunSummary {
+        counts,
+        overall: runs.iter().filter_map(SummaryStatus::of).max(),
+        longest_failing,
+        last_changed_at: runs.iter().map(|run| run.updated_at).max(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{base_time, repo_full_name, workflow_run};
+    use chrono::Duration;
+
+    /// A run of `repo` created `minutes` after the base time and updated a minute later.
+    fn run(repo: &str, id: u64, status: &str, minutes: i64) -> WorkflowRun {
+        let mut run = workflow_run(repo, id, status);
+        run.created_at = base_time() + Duration::minutes(minutes);
+        run.updated_at = run.created_at + Duration::minutes(1);
+        run
+    }
+
+    #[test]
+    fn test_empty_snapshot_has_nothing_to_report() {
+        assert_eq!(summarize(&[]), RunSummary::default());
+    }
+
+    #[test]
+    fn test_mixed_runs_are_counted_and_the_worst_status_wins() {
+        let runs = [
+            run("owner/a", 1, "success", 0),
+            run("owner/a", 2, "queued", 1),
+            run("owner/b", 3, "in_progress", 2),
+            run("owner/b", 4, "skipped", 3),
+            run("owner/c", 5, "timed_out", 4),
+        ];
+
+        let summary = summarize(&runs);
+
+        assert_eq!(
+            summary.counts,
+            StatusCounts {
+                success: 1,
+                failure: 1,
+                in_progress: 1,
+                queued: 1,
+                other: 1,
+            }
+        );
+        assert_eq!(summary.overall, Some(SummaryStatus::Failure));
+        assert_eq!(
+            summary.last_changed_at,
+            Some(base_time() + Duration::minutes(5))
+        );
+        assert_eq!(
+            summarize(&runs[..3]).overall,
+            Some(SummaryStatus::InProgress)
+        );
+        assert_eq!(summarize(&runs[..2]).overall, Some(SummaryStatus::Queued));
+        assert_eq!(summarize(&runs[..1]).overall, Some(SummaryStatus::Success));
+        assert_eq!(summarize(&runs[3..4]).overall, None);
+    }
+
+    #[test]
+    fn test_the_repository_failing_the_longest_is_reported() {
+        let runs = [
+            // Failing since its second run, the first one after its last success
+            run("owner/a", 1, "success", 0),
+            run("owner/a", 2, "failure", 10),
+            run("owner/a", 3, "skipped", 15),
+            run("owner/a", 4, "failure", 20),
+            // Failing for longer, but fixed since
+            run("owner/b", 5, "failure", 1),
+            run("owner/b", 6, "success", 30),
+            // Failing, though not for as long
+            run("owner/c", 7, "in_progress", 40),
+            run("owner/c", 8, "cancelled", 12),
+        ];
+
+        let summary = summarize(&runs);
+
+        assert_eq!(
+            summary.longest_failing,
+            Some(FailingRepository {
+                repository_name: repo_full_name("owner/a"),
+                failing_since: base_time() + Duration::minutes(11),
+            })
+        );
+        assert_eq!(
+            summarize(&runs[4..6]).longest_failing,
+            None,
+            "a repository that recovered is not failing"
+        );
+    }
+}
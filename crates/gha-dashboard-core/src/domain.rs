@@ -0,0 +1,6 @@
+/// Ports to the services outside the dashboard.
+pub mod external_apis;
+/// Runs, jobs, workflows and what the dashboard derives from them.
+pub mod models;
+/// Ports to where runs are stored.
+pub mod repositories;
@@ -0,0 +1,8 @@
+/// The GitHub REST API.
+pub mod github;
+/// Delivery of run notifications to chat and webhooks.
+pub mod notifications;
+
+pub use github::calls::{ApiCallCounts, ApiCallRecorder, GitHubOperation};
+pub use github::{GitHubApi, GitHubApiError, GitHubApiStatus, Repository};
+pub use notifications::{NotificationError, NotificationPort};
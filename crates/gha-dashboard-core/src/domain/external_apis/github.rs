@@ -0,0 +1,500 @@
+/// Counts of the GitHub API requests made on behalf of one task.
+pub mod calls;
+
+use crate::domain::models::commit_status::CommitStatus;
+use crate::domain::models::deployment::{Deployment, DeploymentStatus};
+use crate::domain::models::ids::{
+    DeploymentId, InvalidRepoFullName, JobId, RepoFullName, RunId, WorkflowId,
+};
+use crate::domain::models::job::Job;
+use crate::domain::models::run::WorkflowRun;
+use crate::domain::models::runner::SelfHostedRunner;
+use crate::domain::models::status::RunStatus;
+use crate::domain::models::timing::RunTiming;
+use crate::domain::models::workflow::Workflow;
+use crate::domain::models::workflow_definition::WorkflowDefinition;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A repository the token can see.
+///
+/// Serialized with its `fullName` alongside the owner and name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Repository {
+    full_name: RepoFullName,
+    /// Avatar of the owning user or organization, exactly as GitHub serves it
+    pub owner_avatar_url: Option<String>,
+    /// Page of the repository on GitHub
+    pub html_url: String,
+    /// Branch pull requests target by default; unknown for repositories known only by name
+    pub default_branch: Option<String>,
+    /// When a commit was last pushed to any branch; unknown for repositories known only by name
+    pub pushed_at: Option<DateTime<Utc>>,
+}
+
+impl Repository {
+    /// Builds a repository from its owner and name, linking to its page on github.com.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InvalidRepoFullName`] if either part is empty or contains a slash,
+    /// whitespace or any other character GitHub does not allow in names.
+    pub fn new(
+        owner: impl Into<String>,
+        name: impl Into<String>,
+    ) -> Result<Self, InvalidRepoFullName> {
+        RepoFullName::new(owner, name).map(|full_name| Self::from_full_name(&full_name))
+    }
+
+    /// A repository known only by name, such as an allowlist entry.
+    ///
+    /// The page URL follows from the name; the owner's avatar is unknown.
+    #[must_use]
+    pub fn from_full_name(full_name: &RepoFullName) -> Self {
+        Self {
+            full_name: full_name.clone(),
+            owner_avatar_url: None,
+            html_url: format!("https://github.com/{full_name}"),
+            default_branch: None,
+            pushed_at: None,
+        }
+    }
+
+    /// Sets the avatar of the owning user or organization.
+    #[must_use]
+    pub fn with_owner_avatar_url(mut self, owner_avatar_url: Option<String>) -> Self {
+        self.owner_avatar_url = owner_avatar_url;
+        self
+    }
+
+    /// Replaces the derived page URL, e.g. with the one GitHub Enterprise reports.
+    #[must_use]
+    pub fn with_html_url(mut self, html_url: String) -> Self {
+        self.html_url = html_url;
+        self
+    }
+
+    /// Sets the branch pull requests target by default.
+    #[must_use]
+    pub fn with_default_branch(mut self, default_branch: Option<String>) -> Self {
+        self.default_branch = default_branch;
+        self
+    }
+
+    /// Sets when a commit was last pushed.
+    #[must_use]
+    pub fn with_pushed_at(mut self, pushed_at: Option<DateTime<Utc>>) -> Self {
+        self.pushed_at = pushed_at;
+        self
+    }
+
+    /// The `owner/name` of the repository.
+    #[must_use]
+    pub fn full_name(&self) -> &RepoFullName {
+        &self.full_name
+    }
+
+    /// The user or organization owning the repository.
+    #[must_use]
+    pub fn owner(&self) -> &str {
+        self.full_name.owner()
+    }
+
+    /// The name of the repository within its owner.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        self.full_name.name()
+    }
+}
+
+#[derive(Serialize)]
+struct RepositoryJson<'a> {
+    #[serde(rename = "fullName")]
+    full_name: &'a RepoFullName,
+    owner: &'a str,
+    name: &'a str,
+    #[serde(rename = "ownerAvatarUrl")]
+    owner_avatar_url: Option<&'a str>,
+    #[serde(rename = "htmlUrl")]
+    html_url: &'a str,
+    #[serde(rename = "defaultBranch", skip_serializing_if = "Option::is_none")]
+    default_branch: Option<&'a str>,
+    #[serde(rename = "pushedAt", skip_serializing_if = "Option::is_none")]
+    pushed_at: Option<DateTime<Utc>>,
+}
+
+impl Serialize for Repository {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        RepositoryJson {
+            full_name: &self.full_name,
+            owner: self.owner(),
+            name: self.name(),
+            owner_avatar_url: self.owner_avatar_url.as_deref(),
+            html_url: &self.html_url,
+            default_branch: self.default_branch.as_deref(),
+            pushed_at: self.pushed_at,
+        }
+        .serialize(serializer)
+    }
+}
+
+/// The fields [`Repository`] is read back from; `owner` and `name` follow from `fullName`.
+#[derive(Deserialize)]
+struct RepositoryFields {
+    #[serde(rename = "fullName")]
+    full_name: RepoFullName,
+    #[serde(rename = "ownerAvatarUrl")]
+    owner_avatar_url: Option<String>,
+    #[serde(rename = "htmlUrl")]
+    html_url: String,
+    #[serde(rename = "defaultBranch", default)]
+    default_branch: Option<String>,
+    #[serde(rename = "pushedAt", default)]
+    pushed_at: Option<DateTime<Utc>>,
+}
+
+impl<'de> Deserialize<'de> for Repository {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let fields = RepositoryFields::deserialize(deserializer)?;
+        Ok(Self {
+            full_name: fields.full_name,
+            owner_avatar_url: fields.owner_avatar_url,
+            html_url: fields.html_url,
+            default_branch: fields.default_branch,
+            pushed_at: fields.pushed_at,
+        })
+    }
+}
+
+/// Why a GitHub API call failed.
+///
+/// Each variant names the operation that failed, e.g. `workflow runs for owner/repo`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, thiserror::Error)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum GitHubApiError {
+    /// The resource does not exist, or Actions is disabled for it; never retried
+    #[error("GitHub resource not found: {resource}")]
+    NotFound {
+        /// Operation that failed, e.g. `workflow runs for owner/repo`
+        resource: String,
+    },
+    /// The rate limit is exhausted until `reset`, when GitHub reports it
+    #[error("GitHub rate limit exceeded for {resource}")]
+    RateLimited {
+        /// Operation that failed, e.g. `workflow runs for owner/repo`
+        resource: String,
+        /// When the rate limit resets
+        reset: Option<DateTime<Utc>>,
+    },
+    /// The token is missing, expired or revoked
+    #[error("GitHub rejected the token for {resource}")]
+    Unauthorized {
+        /// Operation that failed, e.g. `workflow runs for owner/repo`
+        resource: String,
+    },
+    /// The token may not access the resource
+    #[error("GitHub denied access to {resource}: {message}")]
+    Forbidden {
+        /// Operation that failed, e.g. `workflow runs for owner/repo`
+        resource: String,
+        /// What GitHub or the client reported
+        message: String,
+    },
+    /// GitHub could not be reached
+    #[error("Failed to reach GitHub for {resource}: {message}")]
+    Network {
+        /// Operation that failed, e.g. `workflow runs for owner/repo`
+        resource: String,
+        /// What GitHub or the client reported
+        message: String,
+    },
+    /// GitHub answered with a body that does not match the expected shape
+    #[error("Failed to decode GitHub response for {resource}: {message}")]
+    Decode {
+        /// Operation that failed, e.g. `workflow runs for owner/repo`
+        resource: String,
+        /// What GitHub or the client reported
+        message: String,
+    },
+    /// The workflow file was downloaded but is not valid; never retried
+    #[error("Invalid workflow file {resource}: {message}")]
+    InvalidWorkflow {
+        /// Operation that failed, e.g. `workflow runs for owner/repo`
+        resource: String,
+        /// What GitHub or the client reported
+        message: String,
+    },
+    /// The retries of the operation outlasted their deadline; transient, the next poll tries
+    /// again
+    #[error("GitHub did not answer {resource} within {deadline_ms} ms")]
+    Timeout {
+        /// Operation that failed, e.g. `workflow runs for owner/repo`
+        resource: String,
+        /// Time given to all the attempts together
+        deadline_ms: u64,
+    },
+    /// Any other failure, such as an unexpected status
+    #[error("GitHub API error for {resource}: {message}")]
+    Other {
+        /// Operation that failed, e.g. `workflow runs for owner/repo`
+        resource: String,
+        /// What GitHub or the client reported
+        message: String,
+    },
+}
+
+impl GitHubApiError {
+    /// The variant as serialized in `kind`, e.g. `rate_limited`.
+    #[must_use]
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::NotFound { .. } => "not_found",
+            Self::RateLimited { .. } => "rate_limited",
+            Self::Unauthorized { .. } => "unauthorized",
+            Self::Forbidden { .. } => "forbidden",
+            Self::Network { .. } => "network",
+            Self::Decode { .. } => "decode",
+            Self::InvalidWorkflow { .. } => "invalid_workflow",
+            Self::Timeout { .. } => "timeout",
+            Self::Other { .. } => "other",
+        }
+    }
+}
+
+/// Credential and quota information reported by the most recent GitHub responses.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GitHubApiStatus {
+    /// Expiry of the token; only fine-grained personal access tokens report one
+    pub token_expires_at: Option<DateTime<Utc>>,
+    /// Requests left in the current rate limit window
+    pub rate_limit_remaining: Option<u64>,
+    /// Requests allowed per rate limit window
+    pub rate_limit_limit: Option<u64>,
+}
+
+/// The core REST API rate limit of the token.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimit {
+    /// Requests allowed per hour
+    pub limit: u64,
+    /// Requests left until `reset`
+    pub remaining: u64,
+    /// Requests made since the last reset
+    pub used: u64,
+    /// When `remaining` is reset to `limit`
+    pub reset: DateTime<Utc>,
+}
+
+/// The GitHub REST API, as far as the dashboard uses it.
+///
+/// Every call names what it fetched in the [`GitHubApiError`] it fails with.
+#[async_trait]
+pub trait GitHubApi {
+    /// Up to `count` repositories the token can see, most recently pushed first.
+    async fn fetch_repositories(&self, count: u8) -> Result<Vec<Repository>, GitHubApiError>;
+    /// `repo` as GitHub knows it now: under its new name when it was renamed or transferred.
+    ///
+    /// Fails with `NotFound` when it does not exist or the token cannot see it.
+    async fn fetch_repository(&self, repo: &RepoFullName) -> Result<Repository, GitHubApiError>;
+    /// Up to `count` of the latest runs of `repo`, newest first.
+    async fn fetch_workflow_runs(
+        &self,
+        repo: &RepoFullName,
+        count: u8,
+    ) -> Result<Vec<WorkflowRun>, GitHubApiError>;
+    /// Up to `count` of the latest runs of one workflow of `repo`, however many newer runs
+    /// other workflows have.
+    async fn fetch_workflow_runs_of_workflow(
+        &self,
+        repo: &RepoFullName,
+        workflow_id: WorkflowId,
+        count: u8,
+    ) -> Result<Vec<WorkflowRun>, GitHubApiError>;
+    /// Up to `count` of the latest runs of `repo` in `status`, however many newer runs are in
+    /// other states.
+    async fn fetch_workflow_runs_by_status(
+        &self,
+        repo: &RepoFullName,
+        status: RunStatus,
+        count: u8,
+    ) -> Result<Vec<WorkflowRun>, GitHubApiError>;
+    /// Up to `count` of the latest runs of `repo` on `branch`, however many newer runs other
+    /// branches have.
+    async fn fetch_workflow_runs_on_branch(
+        &self,
+        repo: &RepoFullName,
+        branch: &str,
+        count: u8,
+    ) -> Result<Vec<WorkflowRun>, GitHubApiError>;
+    /// One run of `repo`, however many newer runs it has.
+    async fn fetch_workflow_run(
+        &self,
+        repo: &RepoFullName,
+        run_id: RunId,
+    ) -> Result<WorkflowRun, GitHubApiError>;
+    /// The jobs of a run of `repo`, including matrix legs.
+    async fn fetch_workflow_jobs(
+        &self,
+        repo: &RepoFullName,
+        run_id: RunId,
+    ) -> Result<Vec<Job>, GitHubApiError>;
+    /// One job of `repo`, wherever its run is at.
+    async fn fetch_workflow_job(
+        &self,
+        repo: &RepoFullName,
+        job_id: JobId,
+    ) -> Result<Job, GitHubApiError>;
+    /// The log of a job of `repo` so far, as plain text.
+    ///
+    /// Fails with `NotFound` until the job has written any.
+    async fn fetch_job_logs(
+        &self,
+        repo: &RepoFullName,
+        job_id: JobId,
+    ) -> Result<String, GitHubApiError>;
+    /// The workflows defined in `repo`, enabled or not.
+    async fn fetch_workflows(&self, repo: &RepoFullName) -> Result<Vec<Workflow>, GitHubApiError>;
+    /// Time the run spent on GitHub-hosted runners, by operating system.
+    async fn fetch_run_timing(
+        &self,
+        repo: &RepoFullName,
+        run_id: RunId,
+    ) -> Result<RunTiming, GitHubApiError>;
+    /// Self-hosted runners registered to `repo`; listing them needs admin access to it.
+    async fn fetch_runners(
+        &self,
+        repo: &RepoFullName,
+    ) -> Result<Vec<SelfHostedRunner>, GitHubApiError>;
+    /// The latest deployments of `repo`, newest first, to `environment` or to any of them.
+    async fn fetch_deployments(
+        &self,
+        repo: &RepoFullName,
+        environment: Option<&str>,
+    ) -> Result<Vec<Deployment>, GitHubApiError>;
+    /// Statuses reported on a deployment of `repo`, newest first.
+    async fn fetch_deployment_statuses(
+        &self,
+        repo: &RepoFullName,
+        deployment_id: DeploymentId,
+    ) -> Result<Vec<DeploymentStatus>, GitHubApiError>;
+    /// The latest status of each context reported on commit `sha` of `repo` through the
+    /// commit status API, which GitHub Actions does not use.
+    async fn fetch_combined_status(
+        &self,
+        repo: &RepoFullName,
+        sha: &str,
+    ) -> Result<Vec<CommitStatus>, GitHubApiError>;
+    /// Names of the status checks that must pass before merging into `branch` of `repo`.
+    ///
+    /// Fails with `NotFound` when the branch is not protected and with `Forbidden` when the
+    /// token lacks admin access to `repo`.
+    async fn fetch_branch_protection_required_checks(
+        &self,
+        repo: &RepoFullName,
+        branch: &str,
+    ) -> Result<Vec<String>, GitHubApiError>;
+    /// Downloads the workflow file at `path` of `repo`, as of `git_ref` or else the default
+    /// branch, and reads the inputs of its `workflow_dispatch` trigger and the cron expressions
+    /// of its `schedule` trigger.
+    async fn fetch_workflow_definition(
+        &self,
+        repo: &RepoFullName,
+        path: &str,
+        git_ref: Option<&str>,
+    ) -> Result<WorkflowDefinition, GitHubApiError>;
+    /// Asks GitHub for the rate limit, which does not count against it.
+    async fn fetch_rate_limit(&self) -> Result<RateLimit, GitHubApiError>;
+
+    /// Returns what GitHub last reported about the token and the rate limit.
+    fn api_status(&self) -> GitHubApiStatus {
+        GitHubApiStatus::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_kind_matches_its_serialized_tag() -> anyhow::Result<()> {
+        let resource = "rate limit".to_string();
+        for error in [
+            GitHubApiError::NotFound {
+                resource: resource.clone(),
+            },
+            GitHubApiError::RateLimited {
+                resource: resource.clone(),
+                reset: None,
+            },
+            GitHubApiError::Network {
+                resource,
+                message: "timed out".to_string(),
+            },
+        ] {
+            assert_eq!(serde_json::to_value(&error)?["kind"], error.kind());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_repository_links_to_its_page() -> anyhow::Result<()> {
+        let repository = Repository::new("octocat", "Hello-World")?;
+
+        assert_eq!(repository.full_name().to_string(), "octocat/Hello-World");
+        assert_eq!(
+            repository.html_url,
+            "https://github.com/octocat/Hello-World"
+        );
+        assert_eq!(
+            serde_json::to_value(&repository)?,
+            serde_json::json!({
+                "fullName": "octocat/Hello-World",
+                "owner": "octocat",
+                "name": "Hello-World",
+                "ownerAvatarUrl": null,
+                "htmlUrl": "https://github.com/octocat/Hello-World",
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<Repository>(serde_json::to_value(&repository)?)?,
+            repository
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_repository_rejects_empty_segments() {
+        assert!(Repository::new("", "api").is_err());
+        assert!(Repository::new("me", "").is_err());
+    }
+
+    #[test]
+    fn test_repository_rejects_slashes() {
+        assert!(Repository::new("me", "api/").is_err());
+        assert!(Repository::new("me/", "api").is_err());
+        assert!(Repository::new("me", "api/actions").is_err());
+    }
+
+    #[test]
+    fn test_repository_rejects_whitespace() {
+        for name in [" api", "api ", "a pi", "api\t", "api\n"] {
+            assert!(Repository::new("me", name).is_err(), "{name:?} accepted");
+        }
+    }
+
+    #[test]
+    fn test_repository_rejects_disallowed_characters() {
+        for name in ["api?per_page=1", "api#1", "api%2F", "ap:i", "apí", "api@v2"] {
+            assert!(Repository::new("me", name).is_err(), "{name:?} accepted");
+        }
+    }
+
+    #[test]
+    fn test_repository_rejects_dot_segments_and_long_names() {
+        assert!(Repository::new(".", "api").is_err());
+        assert!(Repository::new("me", "..").is_err());
+        assert!(Repository::new("me", "a".repeat(101)).is_err());
+        assert!(Repository::new("me", "a".repeat(100)).is_ok());
+    }
+}
@@ -0,0 +1,181 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// A kind of GitHub API request, as labelled in metrics and call counts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GitHubOperation {
+    /// Listing the repositories the token can see
+    Repositories,
+    /// Fetching one repository
+    Repository,
+    /// Listing the workflows of a repository
+    Workflows,
+    /// Listing workflow runs
+    WorkflowRuns,
+    /// Listing or fetching the jobs of a run
+    WorkflowJobs,
+    /// Downloading a job log
+    JobLogs,
+    /// Fetching the billable time of a run
+    RunTiming,
+    /// Listing self-hosted runners
+    Runners,
+    /// Listing deployments
+    Deployments,
+    /// Listing the statuses of a deployment
+    DeploymentStatuses,
+    /// Listing the commit statuses of a ref
+    CommitStatuses,
+    /// Downloading a workflow file
+    WorkflowDefinition,
+    /// Fetching the required checks of a branch
+    RequiredChecks,
+    /// Fetching the rate limit of the token
+    RateLimit,
+}
+
+impl GitHubOperation {
+    /// Every operation, in the order they are reported.
+    pub const ALL: [Self; 14] = [
+        Self::Repositories,
+        Self::Repository,
+        Self::Workflows,
+        Self::WorkflowRuns,
+        Self::WorkflowJobs,
+        Self::JobLogs,
+        Self::RunTiming,
+        Self::Runners,
+        Self::Deployments,
+        Self::DeploymentStatuses,
+        Self::CommitStatuses,
+        Self::WorkflowDefinition,
+        Self::RequiredChecks,
+        Self::RateLimit,
+    ];
+
+    /// The label of the operation in metrics, e.g. `workflow_runs`.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Repositories => "repositories",
+            Self::Repository => "repository",
+            Self::Workflows => "workflows",
+            Self::WorkflowRuns => "workflow_runs",
+            Self::WorkflowJobs => "workflow_jobs",
+            Self::JobLogs => "job_logs",
+            Self::RunTiming => "run_timing",
+            Self::Runners => "runners",
+            Self::Deployments => "deployments",
+            Self::DeploymentStatuses => "deployment_statuses",
+            Self::CommitStatuses => "commit_statuses",
+            Self::WorkflowDefinition => "workflow_definition",
+            Self::RequiredChecks => "required_checks",
+            Self::RateLimit => "rate_limit",
+        }
+    }
+}
+
+/// Serialized as its metrics label.
+impl Serialize for GitHubOperation {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+tokio::task_local! {
+    /// Recorder of the poll iteration or HTTP request the current task works for
+    static CURRENT: ApiCallRecorder;
+}
+
+/// Counts the GitHub API requests made for one poll iteration or one HTTP request.
+///
+/// Adapters call [`ApiCallRecorder::record`] for every request they send, retries included,
+/// which counts it in the recorder of the enclosing [`ApiCallRecorder::scope`].
+#[derive(Debug, Clone, Default)]
+pub struct ApiCallRecorder(Arc<[AtomicU64; GitHubOperation::ALL.len()]>);
+
+impl ApiCallRecorder {
+    /// Runs `future`, counting the requests it makes in `self`.
+    pub async fn scope<F: Future>(&self, future: F) -> F::Output {
+        CURRENT.scope(self.clone(), future).await
+    }
+
+    /// Counts a request in the recorder of the current scope; outside any, e.g. in background
+    /// tasks, it is not counted.
+    pub fn record(operation: GitHubOperation) {
+        let _ = CURRENT.try_with(|recorder| recorder.add(operation, 1));
+    }
+
+    fn add(&self, operation: GitHubOperation, calls: u64) {
+        self.0[operation as usize].fetch_add(calls, Ordering::Relaxed);
+    }
+
+    /// Adds `counts` to those of `self`, e.g. a request's to the totals of the server.
+    pub fn add_counts(&self, counts: &ApiCallCounts) {
+        for operation in GitHubOperation::ALL {
+            if let Some(&calls) = counts.by_operation.get(operation.as_str()) {
+                self.add(operation, calls);
+            }
+        }
+    }
+
+    /// The requests counted so far.
+    #[must_use]
+    pub fn counts(&self) -> ApiCallCounts {
+        let by_operation: BTreeMap<&'static str, u64> = GitHubOperation::ALL
+            .into_iter()
+            .map(|operation| {
+                let calls = self.0[operation as usize].load(Ordering::Relaxed);
+                (operation.as_str(), calls)
+            })
+            .filter(|&(_, calls)| calls > 0)
+            .collect();
+        ApiCallCounts {
+            total: by_operation.values().sum(),
+            by_operation,
+        }
+    }
+}
+
+/// GitHub API requests counted by an [`ApiCallRecorder`].
+#[derive(Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ApiCallCounts {
+    /// Requests across all operations
+    pub total: u64,
+    /// Requests by operation, leaving out operations with none
+    #[serde(rename = "byOperation")]
+    pub by_operation: BTreeMap<&'static str, u64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_requests_are_counted_in_the_enclosing_scope_only() {
+        let (outer, inner) = (ApiCallRecorder::default(), ApiCallRecorder::default());
+
+        ApiCallRecorder::record(GitHubOperation::Repositories);
+        outer
+            .scope(async {
+                ApiCallRecorder::record(GitHubOperation::WorkflowRuns);
+                inner
+                    .scope(async { ApiCallRecorder::record(GitHubOperation::WorkflowJobs) })
+                    .await;
+                ApiCallRecorder::record(GitHubOperation::WorkflowRuns);
+            })
+            .await;
+        outer.add_counts(&inner.counts());
+
+        assert_eq!(
+            outer.counts(),
+            ApiCallCounts {
+                total: 3,
+                by_operation: BTreeMap::from([("workflow_jobs", 1), ("workflow_runs", 2)]),
+            }
+        );
+        assert_eq!(inner.counts().total, 1);
+    }
+}
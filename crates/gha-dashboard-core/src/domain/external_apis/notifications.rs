@@ -0,0 +1,50 @@
+use crate::domain::models::repository_change::RepositoryChange;
+use crate::domain::models::transition::RunTransition;
+use async_trait::async_trait;
+
+/// Why a notification could not be delivered.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum NotificationError {
+    /// The endpoint could not be reached or timed out
+    #[error("Failed to reach {target}: {message}")]
+    Unreachable {
+        /// Endpoint the notification was sent to
+        target: String,
+        /// What the client reported
+        message: String,
+    },
+    /// The endpoint answered with a non-success status
+    #[error("{target} rejected the notification with status {status}")]
+    Rejected {
+        /// Endpoint the notification was sent to
+        target: String,
+        /// HTTP status the endpoint answered with
+        status: u16,
+    },
+}
+
+impl NotificationError {
+    /// Whether sending again may succeed: network failures, throttling and server errors.
+    #[must_use]
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Unreachable { .. } => true,
+            Self::Rejected { status, .. } => *status == 429 || *status >= 500,
+        }
+    }
+}
+
+/// Somewhere run transitions are reported to, such as a chat webhook.
+#[async_trait]
+pub trait NotificationPort {
+    /// Delivers one notification about `event`, without retrying.
+    async fn notify(&self, event: &RunTransition) -> Result<(), NotificationError>;
+
+    /// Delivers one notification about a repository appearing or leaving, without retrying.
+    ///
+    /// Targets made only for run transitions ignore it.
+    async fn notify_repository(&self, change: &RepositoryChange) -> Result<(), NotificationError> {
+        tracing::debug!("Ignoring repository change: {}", change);
+        Ok(())
+    }
+}
@@ -0,0 +1,82 @@
+/// Accounts that start runs.
+pub mod actor;
+/// Bot pull request runs collapsed per repository.
+pub mod bot_group;
+/// Commit statuses reported by services other than GitHub Actions.
+pub mod commit_status;
+/// Deployments and their statuses.
+pub mod deployment;
+/// Weekly summaries of the run history.
+pub mod digest;
+/// Inputs of `workflow_dispatch` triggers.
+pub mod dispatch;
+/// Name patterns with `*` and `?` wildcards.
+pub mod glob;
+/// Identifiers of GitHub resources.
+pub mod ids;
+/// Jobs of a run and their steps.
+pub mod job;
+/// Labels attached to runs by configured rules.
+pub mod label;
+/// Jobs of a run grouped by matrix.
+pub mod matrix;
+/// Browser subscriptions to Web Push notifications.
+pub mod push_subscription;
+/// Queued runs waiting for a runner.
+pub mod queue;
+/// Repositories appearing in or leaving those the token can see.
+pub mod repository_change;
+/// Workflow runs.
+pub mod run;
+/// Differences between the jobs of two runs.
+pub mod run_comparison;
+/// Self-hosted runners.
+pub mod runner;
+/// Cron schedules of workflows and their next runs.
+pub mod schedule;
+/// Service level objectives measured over the run history.
+pub mod slo;
+/// Statuses and conclusions of runs.
+pub mod status;
+/// Billable time of runs.
+pub mod timing;
+/// Runs whose status changed between two snapshots.
+pub mod transition;
+/// Workflows of a repository.
+pub mod workflow;
+/// Triggers read from workflow files.
+pub mod workflow_definition;
+/// Which workflows' runs are shown.
+pub mod workflow_filter;
+mod yaml;
+
+pub use actor::RunActor;
+pub use bot_group::BotRunGroup;
+pub use commit_status::{CheckSource, CommitStatus, CommitStatusState, ExternalCheck};
+pub use deployment::{Deployment, DeploymentState, DeploymentStatus, DeploymentStatusState};
+pub use digest::{
+    Digest, InvalidIsoWeek, IsoWeek, Regression, RegressionKind, RepositoryDigest, WorkflowDigest,
+};
+pub use dispatch::{
+    DispatchInput, DispatchInputType, DispatchValue, InvalidWorkflowFile, WorkflowDispatchInputs,
+};
+pub use glob::{GlobPattern, InvalidGlobPattern};
+pub use ids::{
+    DeploymentId, InvalidRepoFullName, JobId, RepoFullName, RunId, RunnerId, WorkflowId,
+};
+pub use job::{Job, Step};
+pub use label::{LabelConditions, LabelRule};
+pub use matrix::{MatrixCell, MatrixGroup, MatrixRollup};
+pub use push_subscription::{PushFilter, PushKeys, PushSubscription};
+pub use queue::RepositoryQueue;
+pub use run::WorkflowRun;
+pub use run_comparison::{DurationChange, JobComparison, RunComparison, StepChange};
+pub use runner::{RepositoryRunners, RunnerStatus, SelfHostedRunner};
+pub use schedule::{CronSchedule, InvalidCronExpression, UpcomingRun, WorkflowSchedule};
+pub use slo::{Slo, SloOutcome};
+pub use status::{RunConclusion, RunStatus};
+pub use timing::{BillableTime, JobTiming, RunTiming, RunnerOs};
+pub use transition::RunTransition;
+pub use workflow::{Workflow, WorkflowState};
+pub use workflow_definition::WorkflowDefinition;
+pub use workflow_filter::WorkflowFilter;
@@ -0,0 +1,101 @@
+#[cfg(feature = "schema")]
+use schemars::{JsonSchema, Schema, SchemaGenerator};
+use serde::{Deserialize, Serialize, Serializer};
+#[cfg(feature = "schema")]
+use std::borrow::Cow;
+
+/// Suffix GitHub appends to the login of app and bot accounts
+const BOT_LOGIN_SUFFIX: &str = "[bot]";
+
+/// The GitHub account behind a workflow run.
+///
+/// Serialized with an extra computed `isBot` field.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct RunActor {
+    /// Username of the account
+    pub login: String,
+    /// Avatar image of the account
+    #[serde(rename = "avatarUrl")]
+    pub avatar_url: String,
+    /// Profile page of the account
+    #[serde(rename = "htmlUrl")]
+    pub html_url: String,
+}
+
+impl RunActor {
+    /// Whether the account is a bot, e.g. `dependabot[bot]`.
+    #[must_use]
+    pub fn is_bot(&self) -> bool {
+        self.login.ends_with(BOT_LOGIN_SUFFIX)
+    }
+}
+
+/// Serialized form of [`RunActor`], borrowing its fields.
+#[derive(Serialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+struct RunActorJson<'a> {
+    login: &'a str,
+    #[serde(rename = "avatarUrl")]
+    avatar_url: &'a str,
+    #[serde(rename = "htmlUrl")]
+    html_url: &'a str,
+    #[serde(rename = "isBot")]
+    is_bot: bool,
+}
+
+impl Serialize for RunActor {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        RunActorJson {
+            login: &self.login,
+            avatar_url: &self.avatar_url,
+            html_url: &self.html_url,
+            is_bot: self.is_bot(),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Describes the serialized form, including the computed field.
+#[cfg(feature = "schema")]
+impl JsonSchema for RunActor {
+    fn schema_name() -> Cow<'static, str> {
+        "RunActor".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        RunActorJson::json_schema(generator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::run_actor as actor;
+
+    #[test]
+    fn test_bot_accounts_are_detected_by_login_suffix() {
+        assert!(actor("dependabot[bot]").is_bot());
+        assert!(!actor("octocat").is_bot());
+        assert!(!actor("bot").is_bot());
+    }
+
+    #[test]
+    fn test_serialized_actor_includes_is_bot() -> anyhow::Result<()> {
+        let json = serde_json::to_value(actor("renovate[bot]"))?;
+
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "login": "renovate[bot]",
+                "avatarUrl": "https://avatars.githubusercontent.com/renovate[bot]",
+                "htmlUrl": "https://github.com/renovate[bot]",
+                "isBot": true
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<RunActor>(json)?,
+            actor("renovate[bot]")
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,131 @@
+use super::actor::RunActor;
+use super::ids::{RepoFullName, RunId};
+use super::run::WorkflowRun;
+use chrono::{DateTime, Utc};
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Event of the runs a bot's pull requests trigger
+const PULL_REQUEST_EVENT: &str = "pull_request";
+
+/// The pull request runs of one repository started by bots such as Dependabot or Renovate,
+/// collapsed into a single entry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct BotRunGroup {
+    /// Repository the bot runs belong to
+    #[serde(rename = "repositoryName")]
+    pub repository_name: RepoFullName,
+    /// Number of runs in the group
+    pub count: u64,
+    /// Runs that ended in a state needing attention
+    pub failing: u64,
+    /// Creation time of the newest run
+    #[serde(rename = "newestAt")]
+    pub newest_at: DateTime<Utc>,
+    /// Newest first
+    #[serde(rename = "runIds")]
+    pub run_ids: Vec<RunId>,
+}
+
+impl BotRunGroup {
+    /// Whether `run` is collapsed into a group: a pull request run started by a bot.
+    ///
+    /// Runs people start, re-runs of bot runs included, are never grouped.
+    #[must_use]
+    pub fn collapses(run: &WorkflowRun) -> bool {
+        run.event == PULL_REQUEST_EVENT
+            && run.actor.is_bot()
+            && run.triggering_actor.as_ref().is_none_or(RunActor::is_bot)
+    }
+
+    /// One group per repository of `runs`, by name; `runs` are expected newest first.
+    #[must_use]
+    pub fn per_repository(runs: &[WorkflowRun]) -> Vec<Self> {
+        let mut grouped: BTreeMap<&RepoFullName, Vec<&WorkflowRun>> = BTreeMap::new();
+        for run in runs {
+            grouped.entry(&run.repository_name).or_default().push(run);
+        }
+        grouped
+            .into_iter()
+            .filter_map(|(repository, runs)| {
+                Some(Self {
+                    repository_name: repository.clone(),
+                    count: runs.len() as u64,
+                    failing: runs.iter().filter(|run| run.is_problem()).count() as u64,
+                    newest_at: runs.iter().map(|run| run.created_at).max()?,
+                    run_ids: runs.iter().map(|run| run.id).collect(),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{base_time, repo_full_name, run_actor, workflow_run};
+    use chrono::TimeDelta;
+
+    fn bot_run(repo: &str, id: u64, status: &str, minutes: i64) -> WorkflowRun {
+        let mut run = workflow_run(repo, id, status);
+        run.event = PULL_REQUEST_EVENT.to_string();
+        run.actor = run_actor("dependabot[bot]");
+        run.triggering_actor = Some(run.actor.clone());
+        run.created_at = base_time() + TimeDelta::minutes(minutes);
+        run
+    }
+
+    #[test]
+    fn test_only_pull_request_runs_of_bots_are_collapsed() {
+        let mut human = bot_run("owner/repo", 1, "success", 0);
+        human.actor = run_actor("octocat");
+        let mut push = bot_run("owner/repo", 2, "success", 0);
+        push.event = "push".to_string();
+        let mut rerun = bot_run("owner/repo", 3, "success", 0);
+        rerun.triggering_actor = Some(run_actor("octocat"));
+
+        assert!(BotRunGroup::collapses(&bot_run(
+            "owner/repo",
+            4,
+            "success",
+            0
+        )));
+        assert!(!BotRunGroup::collapses(&human));
+        assert!(!BotRunGroup::collapses(&push));
+        assert!(!BotRunGroup::collapses(&rerun));
+    }
+
+    #[test]
+    fn test_groups_summarize_the_runs_of_each_repository() {
+        let runs = [
+            bot_run("owner/repo", 3, "failure", 20),
+            bot_run("owner/app", 2, "success", 10),
+            bot_run("owner/repo", 1, "success", 0),
+        ];
+
+        let groups = BotRunGroup::per_repository(&runs);
+
+        assert_eq!(
+            groups,
+            [
+                BotRunGroup {
+                    repository_name: repo_full_name("owner/app"),
+                    count: 1,
+                    failing: 0,
+                    newest_at: base_time() + TimeDelta::minutes(10),
+                    run_ids: vec![RunId(2)],
+                },
+                BotRunGroup {
+                    repository_name: repo_full_name("owner/repo"),
+                    count: 2,
+                    failing: 1,
+                    newest_at: base_time() + TimeDelta::minutes(20),
+                    run_ids: vec![RunId(3), RunId(1)],
+                },
+            ]
+        );
+    }
+}
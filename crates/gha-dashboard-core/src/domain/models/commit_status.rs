@@ -0,0 +1,189 @@
+use super::ids::RepoFullName;
+use super::run::WorkflowRun;
+use chrono::{DateTime, Utc};
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// State of a commit status, as in GitHub's `state` field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum CommitStatusState {
+    /// The reporter failed to run the check
+    Error,
+    /// The check ran and failed
+    Failure,
+    /// The check has not finished yet
+    Pending,
+    /// The check ran and passed
+    Success,
+}
+
+/// The latest status reported on a commit under one context through the commit status API,
+/// e.g. by Jenkins.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitStatus {
+    /// e.g. `continuous-integration/jenkins`
+    pub context: String,
+    /// Outcome of the check
+    pub state: CommitStatusState,
+    /// Short text the reporter attached to the status
+    pub description: Option<String>,
+    /// Page with the details of the check
+    #[serde(rename = "targetUrl")]
+    pub target_url: Option<String>,
+    /// When the status was first reported
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    /// When the status was last reported
+    #[serde(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+}
+
+/// What reported an [`ExternalCheck`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum CheckSource {
+    /// The commit status API
+    CommitStatus,
+}
+
+/// A check reported on the head commit of a repository by something other than GitHub
+/// Actions, shown next to its runs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct ExternalCheck {
+    /// Repository the commit belongs to
+    #[serde(rename = "repositoryName")]
+    pub repository_name: RepoFullName,
+    /// Commit the check ran on
+    #[serde(rename = "headSha")]
+    pub head_sha: String,
+    /// Integration that reported the check
+    pub source: CheckSource,
+    /// Name the check is reported under
+    pub context: String,
+    /// Outcome of the check
+    pub state: CommitStatusState,
+    /// Short text the reporter attached to the check
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Page with the details of the check
+    #[serde(rename = "targetUrl")]
+    pub target_url: Option<String>,
+    /// When the check was first reported
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    /// When the check was last reported
+    #[serde(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ExternalCheck {
+    /// The `statuses` of commit `head_sha` of `repository`, leaving out those `runs` already
+    /// show.
+    ///
+    /// Workflows sometimes report a commit status of their own, under their name or as
+    /// `Workflow / job`, or linking to their run; such a status duplicates a run on the same
+    /// commit and is dropped.
+    #[must_use]
+    pub fn from_commit_statuses(
+        repository: &RepoFullName,
+        head_sha: &str,
+        statuses: Vec<CommitStatus>,
+        runs: &[WorkflowRun],
+    ) -> Vec<Self> {
+        let runs: Vec<&WorkflowRun> = runs
+            .iter()
+            .filter(|run| run.repository_name == *repository && run.head_sha == head_sha)
+            .collect();
+        statuses
+            .into_iter()
+            .filter(|status| !runs.iter().any(|run| reported_by(status, run)))
+            .map(|status| Self {
+                repository_name: repository.clone(),
+                head_sha: head_sha.to_string(),
+                source: CheckSource::CommitStatus,
+                context: status.context,
+                state: status.state,
+                description: status.description,
+                target_url: status.target_url,
+                created_at: status.created_at,
+                updated_at: status.updated_at,
+            })
+            .collect()
+    }
+}
+
+/// Whether `status` was reported by the workflow of `run`.
+fn reported_by(status: &CommitStatus, run: &WorkflowRun) -> bool {
+    let by_name = status
+        .context
+        .strip_prefix(run.workflow_name.as_str())
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(" / "));
+    let by_link = status.target_url.as_deref().is_some_and(|url| {
+        url.strip_prefix(run.html_url.as_str())
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(['/', '?', '#']))
+    });
+    by_name || by_link
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{base_time, repo_full_name};
+    use crate::testing::WorkflowRunBuilder;
+
+    fn status(context: &str, target_url: Option<&str>) -> CommitStatus {
+        CommitStatus {
+            context: context.to_string(),
+            state: CommitStatusState::Success,
+            description: None,
+            target_url: target_url.map(str::to_string),
+            created_at: base_time(),
+            updated_at: base_time(),
+        }
+    }
+
+    #[test]
+    fn test_statuses_of_workflows_on_the_same_commit_are_dropped() {
+        let repo = repo_full_name("owner/repo");
+        let run = WorkflowRunBuilder::new("owner/repo").build();
+        let other_commit = WorkflowRunBuilder::new("owner/repo")
+            .id(10)
+            .workflow(2, "Deploy")
+            .build();
+        let statuses = vec![
+            status("continuous-integration/jenkins", None),
+            status("CI", None),
+            status("CI / build", None),
+            status("CIrcle", None),
+            status("lint", Some(&format!("{}/job/7", run.html_url))),
+            status("coverage", Some(&other_commit.html_url)),
+            status("Deploy", None),
+        ];
+
+        let checks = ExternalCheck::from_commit_statuses(
+            &repo,
+            &run.head_sha,
+            statuses,
+            &[run.clone(), other_commit],
+        );
+
+        let contexts: Vec<&str> = checks.iter().map(|check| check.context.as_str()).collect();
+        assert_eq!(
+            contexts,
+            [
+                "continuous-integration/jenkins",
+                "CIrcle",
+                "coverage",
+                "Deploy"
+            ]
+        );
+        assert!(checks.iter().all(
+            |check| check.head_sha == run.head_sha && check.source == CheckSource::CommitStatus
+        ));
+    }
+}
@@ -0,0 +1,176 @@
+use super::actor::RunActor;
+use super::ids::{DeploymentId, RepoFullName};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// State a deployment status reports, as in GitHub's `state` field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeploymentStatusState {
+    /// The deployment could not be carried out
+    Error,
+    /// The deployment was carried out and failed
+    Failure,
+    /// Superseded by a later deployment to the same environment, or deactivated by hand
+    Inactive,
+    /// The deployment is being carried out
+    InProgress,
+    /// The deployment is waiting for a runner
+    Queued,
+    /// The deployment has been requested but not started
+    Pending,
+    /// The deployment succeeded
+    Success,
+    /// A value GitHub added after this enum was written
+    #[serde(other)]
+    Unknown,
+}
+
+/// A deployment of a repository to one of its environments.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Deployment {
+    /// ID of the deployment within GitHub
+    pub id: DeploymentId,
+    /// e.g. `staging` or `production`
+    pub environment: String,
+    /// Branch, tag or commit that was asked for
+    #[serde(rename = "ref")]
+    pub git_ref: String,
+    /// Commit that was deployed
+    pub sha: String,
+    /// `None` when the account was deleted
+    pub creator: Option<RunActor>,
+    /// When the deployment was requested
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    /// When the deployment was last changed
+    #[serde(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A status reported on a deployment.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeploymentStatus {
+    /// Outcome reported by the status
+    pub state: DeploymentStatusState,
+    /// Where the deployment can be seen: the environment's URL, or else the status's target
+    /// URL, if the deployer set either
+    #[serde(rename = "targetUrl")]
+    pub target_url: Option<String>,
+    /// When the status was reported
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+}
+
+/// The latest deployment to an environment of a repository.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DeploymentState {
+    /// Repository that was deployed
+    pub repository: RepoFullName,
+    /// Environment deployed to, e.g. `production`
+    pub environment: String,
+    /// Branch, tag or SHA that was deployed
+    #[serde(rename = "ref")]
+    pub git_ref: String,
+    /// Commit that was deployed
+    pub sha: String,
+    /// Account that started the deployment; `None` when it was deleted
+    pub creator: Option<RunActor>,
+    /// Latest reported state of the deployment
+    pub state: DeploymentStatusState,
+    /// When `state` was reported, or the deployment was last updated if it has no status
+    #[serde(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+    /// Where the deployment can be seen
+    #[serde(rename = "targetUrl")]
+    pub target_url: Option<String>,
+}
+
+impl DeploymentState {
+    /// The state of `deployment` from its `statuses`, in any order.
+    ///
+    /// The latest status decides, except that an `inactive` one does not hide the outcome
+    /// before it: GitHub marks a deployment inactive when another one to the same environment
+    /// succeeds, which does not make it any less deployed. A deployment without statuses is
+    /// pending.
+    #[must_use]
+    pub fn new(
+        repository: RepoFullName,
+        deployment: Deployment,
+        statuses: &[DeploymentStatus],
+    ) -> Self {
+        let latest = |active: bool| {
+            statuses
+                .iter()
+                .filter(|status| !active || status.state != DeploymentStatusState::Inactive)
+                .max_by_key(|status| status.created_at)
+        };
+        let status = latest(true).or_else(|| latest(false));
+        Self {
+            repository,
+            environment: deployment.environment,
+            git_ref: deployment.git_ref,
+            sha: deployment.sha,
+            creator: deployment.creator,
+            state: status.map_or(DeploymentStatusState::Pending, |status| status.state),
+            updated_at: status.map_or(deployment.updated_at, |status| status.created_at),
+            target_url: status.and_then(|status| status.target_url.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{base_time, repo_full_name};
+    use chrono::TimeDelta;
+
+    fn deployment() -> Deployment {
+        Deployment {
+            id: DeploymentId(1),
+            environment: "production".to_string(),
+            git_ref: "v1.2.0".to_string(),
+            sha: "abc123".to_string(),
+            creator: None,
+            created_at: base_time(),
+            updated_at: base_time(),
+        }
+    }
+
+    fn status(state: DeploymentStatusState, minutes: i64) -> DeploymentStatus {
+        DeploymentStatus {
+            state,
+            target_url: Some(format!("https://example.com/{minutes}")),
+            created_at: base_time() + TimeDelta::minutes(minutes),
+        }
+    }
+
+    #[test]
+    fn test_inactive_statuses_do_not_hide_the_outcome_before_them() {
+        use DeploymentStatusState::{Failure, InProgress, Inactive, Pending, Queued, Success};
+        for (statuses, state, minutes) in [
+            (
+                vec![status(Queued, 1), status(InProgress, 2), status(Success, 3)],
+                Success,
+                3,
+            ),
+            (vec![status(Inactive, 9), status(Success, 3)], Success, 3),
+            (vec![status(Success, 3), status(Failure, 5)], Failure, 5),
+            (vec![status(Inactive, 9), status(Inactive, 4)], Inactive, 9),
+            (Vec::new(), Pending, 0),
+        ] {
+            let deployed =
+                DeploymentState::new(repo_full_name("owner/repo"), deployment(), &statuses);
+
+            assert_eq!(deployed.state, state, "{statuses:?}");
+            assert_eq!(
+                deployed.updated_at,
+                base_time() + TimeDelta::minutes(minutes)
+            );
+            assert_eq!(
+                deployed.target_url,
+                (!statuses.is_empty()).then(|| format!("https://example.com/{minutes}"))
+            );
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use super::ids::{RepoFullName, WorkflowId};
+use chrono::{DateTime, Datelike, Days, NaiveDate, NaiveTime, TimeDelta, Utc, Weekday};
+use serde::{Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// An ISO 8601 week, from Monday 00:00 UTC to the next Monday, written `2024-W32`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct IsoWeek {
+    year: i32,
+    week: u32,
+}
+
+impl IsoWeek {
+    /// The week `date` falls in.
+    #[must_use]
+    pub fn of(date: NaiveDate) -> Self {
+        let week = date.iso_week();
+        Self {
+            year: week.year(),
+            week: week.week(),
+        }
+    }
+
+    /// Its Monday.
+    #[must_use]
+    pub fn monday(self) -> NaiveDate {
+        // Both were validated when the week was built
+        NaiveDate::from_isoywd_opt(self.year, self.week, Weekday::Mon).unwrap_or_default()
+    }
+
+    /// Its first instant, Monday 00:00 UTC.
+    #[must_use]
+    pub fn start(self) -> DateTime<Utc> {
+        self.monday().and_time(NaiveTime::MIN).and_utc()
+    }
+
+    /// The first instant of the next week, which this one ends before.
+    #[must_use]
+    pub fn end(self) -> DateTime<Utc> {
+        self.start() + TimeDelta::weeks(1)
+    }
+
+    /// Whether `day` falls in the week.
+    #[must_use]
+    pub fn contains(self, day: NaiveDate) -> bool {
+        Self::of(day) == self
+    }
+
+    /// The week before.
+    #[must_use]
+    pub fn previous(self) -> Self {
+        Self::of(self.monday() - Days::new(7))
+    }
+}
+
+impl fmt::Display for IsoWeek {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-W{:02}", self.year, self.week)
+    }
+}
+
+/// Why a string is not an [`IsoWeek`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Invalid ISO week {0:?}, expected e.g. 2024-W32")]
+pub struct InvalidIsoWeek(String);
+
+impl FromStr for IsoWeek {
+    type Err = InvalidIsoWeek;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidIsoWeek(value.to_string());
+        let (year, week) = value.split_once("-W").ok_or_else(invalid)?;
+        let year = year.parse().map_err(|_| invalid())?;
+        let week = week.parse().map_err(|_| invalid())?;
+        // Years have 52 or 53 weeks
+        NaiveDate::from_isoywd_opt(year, week, Weekday::Mon).ok_or_else(invalid)?;
+        Ok(Self { year, week })
+    }
+}
+
+/// Serialized as written, e.g. `2024-W32`.
+impl Serialize for IsoWeek {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// A workflow of a repository over one week.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WorkflowDigest {
+    /// The workflow
+    #[serde(rename = "workflowId")]
+    pub workflow_id: WorkflowId,
+    /// Name of the workflow in its latest stats
+    #[serde(rename = "workflowName")]
+    pub workflow_name: String,
+    /// Completed runs
+    pub runs: u64,
+    /// Share of the runs whose conclusion needs attention, from 0 to 1
+    #[serde(rename = "failureRate")]
+    pub failure_rate: f64,
+    /// Mean time from creation to completion
+    #[serde(rename = "meanDurationSeconds")]
+    pub mean_duration_seconds: f64,
+}
+
+/// What got worse in a workflow since the week before.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RegressionKind {
+    /// More of its runs failed
+    FailureRate,
+    /// Its runs took longer
+    Duration,
+}
+
+/// A workflow that did notably worse than the week before.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Regression {
+    /// The workflow
+    #[serde(rename = "workflowId")]
+    pub workflow_id: WorkflowId,
+    /// Name of the workflow in its latest stats
+    #[serde(rename = "workflowName")]
+    pub workflow_name: String,
+    /// What got worse
+    pub kind: RegressionKind,
+    /// The failure rate or mean duration in seconds the week before
+    pub previous: f64,
+    /// The failure rate or mean duration in seconds during the week
+    pub current: f64,
+}
+
+/// One repository over one week.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RepositoryDigest {
+    /// The repository
+    pub repository: RepoFullName,
+    /// Completed runs
+    pub runs: u64,
+    /// Of `runs`, those whose conclusion needs attention
+    #[serde(rename = "failedRuns")]
+    pub failed_runs: u64,
+    /// `failed_runs` out of `runs`, from 0 to 1
+    #[serde(rename = "failureRate")]
+    pub failure_rate: f64,
+    /// Its workflows taking longest on average, slowest first
+    #[serde(rename = "slowestWorkflows")]
+    pub slowest_workflows: Vec<WorkflowDigest>,
+    /// Its workflows doing notably worse than the week before
+    pub regressions: Vec<Regression>,
+}
+
+/// Summary of the runs of one week, per repository.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Digest {
+    /// The week summarized
+    pub week: IsoWeek,
+    /// Its first instant
+    pub since: DateTime<Utc>,
+    /// The first instant after it
+    pub until: DateTime<Utc>,
+    /// Completed runs across the repositories
+    pub runs: u64,
+    /// Of `runs`, those whose conclusion needs attention
+    #[serde(rename = "failedRuns")]
+    pub failed_runs: u64,
+    /// `failed_runs` out of `runs`, from 0 to 1; `None` without runs
+    #[serde(rename = "failureRate")]
+    pub failure_rate: Option<f64>,
+    /// The repositories with runs during the week, in name order; empty without any
+    pub repositories: Vec<RepositoryDigest>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weeks_parse_and_span_monday_to_monday() -> anyhow::Result<()> {
+        let week: IsoWeek = "2024-W32".parse()?;
+
+        assert_eq!(week.to_string(), "2024-W32");
+        assert_eq!(week.start().to_rfc3339(), "2024-08-05T00:00:00+00:00");
+        assert_eq!(week.end().to_rfc3339(), "2024-08-12T00:00:00+00:00");
+        assert!(week.contains(NaiveDate::from_ymd_opt(2024, 8, 11).unwrap_or_default()));
+        assert!(!week.contains(NaiveDate::from_ymd_opt(2024, 8, 12).unwrap_or_default()));
+        // ISO years start on the Monday of the week holding January 4th
+        assert_eq!(
+            "2025-W01".parse::<IsoWeek>()?.previous().to_string(),
+            "2024-W52"
+        );
+        assert_eq!(
+            IsoWeek::of(NaiveDate::from_ymd_opt(2024, 12, 30).unwrap_or_default()).to_string(),
+            "2025-W01"
+        );
+        for value in ["2024-W00", "2024-W53", "2024-32", "2024-W3x", ""] {
+            assert!(value.parse::<IsoWeek>().is_err(), "{value:?} accepted");
+        }
+        assert!("2020-W53".parse::<IsoWeek>().is_ok());
+        Ok(())
+    }
+}
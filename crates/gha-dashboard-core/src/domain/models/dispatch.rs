@@ -0,0 +1,316 @@
+use super::yaml::{self, Yaml};
+use serde::{Deserialize, Serialize};
+
+/// Type of a `workflow_dispatch` input, as declared with `type:`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DispatchInputType {
+    /// Free-form text
+    #[default]
+    String,
+    /// A checkbox
+    Boolean,
+    /// One of the `options`
+    Choice,
+    /// A number, integer or not
+    Number,
+    /// One of the repository's deployment environments
+    Environment,
+}
+
+/// Default value of an input, typed after the input.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DispatchValue {
+    /// The value of a `boolean` input
+    Boolean(bool),
+    /// The value of a `number` input
+    Number(serde_json::Number),
+    /// The value of any other input
+    String(String),
+}
+
+/// An input of a `workflow_dispatch` trigger.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DispatchInput {
+    /// Name the input is passed under
+    pub name: String,
+    /// Help text shown next to the input
+    pub description: Option<String>,
+    /// Kind of value the input takes
+    #[serde(rename = "type")]
+    pub input_type: DispatchInputType,
+    /// Whether the workflow cannot be dispatched without the input
+    pub required: bool,
+    /// Value used when the input is left out
+    pub default: Option<DispatchValue>,
+    /// Values a `choice` input takes, in the order they are declared
+    pub options: Vec<String>,
+}
+
+/// The inputs a workflow is dispatched with, as declared in its workflow file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkflowDispatchInputs {
+    /// Whether the workflow has a `workflow_dispatch` trigger; `inputs` is empty without one
+    pub dispatchable: bool,
+    /// In the order they are declared
+    pub inputs: Vec<DispatchInput>,
+}
+
+/// Why a workflow file could not be read.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("{0}")]
+pub struct InvalidWorkflowFile(pub String);
+
+impl From<yaml::YamlError> for InvalidWorkflowFile {
+    fn from(error: yaml::YamlError) -> Self {
+        Self(error.to_string())
+    }
+}
+
+impl WorkflowDispatchInputs {
+    /// Reads the `on.workflow_dispatch.inputs` section of a workflow file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `source` is not YAML or declares an input GitHub would reject,
+    /// e.g. a `choice` without options.
+    pub fn from_yaml(source: &str) -> Result<Self, InvalidWorkflowFile> {
+        Self::from_document(&yaml::parse(source)?)
+    }
+
+    pub(super) fn from_document(document: &Yaml) -> Result<Self, InvalidWorkflowFile> {
+        let trigger = match document.get("on") {
+            Some(Yaml::Mapping(triggers)) => {
+                match triggers
+                    .iter()
+                    .find(|(name, _)| name == "workflow_dispatch")
+                {
+                    Some((_, trigger)) => trigger,
+                    None => return Ok(Self::default()),
+                }
+            }
+            Some(Yaml::Sequence(triggers)) if triggers.iter().any(is_dispatch) => &Yaml::Null,
+            Some(trigger) if is_dispatch(trigger) => &Yaml::Null,
+            _ => return Ok(Self::default()),
+        };
+        let inputs = match trigger.get("inputs") {
+            None | Some(Yaml::Null) => Vec::new(),
+            Some(Yaml::Mapping(inputs)) => inputs
+                .iter()
+                .map(|(name, input)| DispatchInput::from_yaml(name, input))
+                .collect::<Result<_, _>>()?,
+            Some(_) => {
+                return Err(InvalidWorkflowFile(
+                    "`workflow_dispatch.inputs` must be a mapping".to_string(),
+                ));
+            }
+        };
+        Ok(Self {
+            dispatchable: true,
+            inputs,
+        })
+    }
+}
+
+fn is_dispatch(trigger: &Yaml) -> bool {
+    trigger.as_str() == Some("workflow_dispatch")
+}
+
+impl DispatchInput {
+    fn from_yaml(name: &str, input: &Yaml) -> Result<Self, InvalidWorkflowFile> {
+        let invalid = |message: String| InvalidWorkflowFile(format!("input `{name}`: {message}"));
+        let input_type = match input.get("type").and_then(Yaml::as_str) {
+            None | Some("string") => DispatchInputType::String,
+            Some("boolean") => DispatchInputType::Boolean,
+            Some("choice") => DispatchInputType::Choice,
+            Some("number") => DispatchInputType::Number,
+            Some("environment") => DispatchInputType::Environment,
+            Some(other) => return Err(invalid(format!("unknown type `{other}`"))),
+        };
+        let required = match input.get("required") {
+            None | Some(Yaml::Null) => false,
+            Some(required) => boolean(required)
+                .ok_or_else(|| invalid("`required` must be true or false".to_string()))?,
+        };
+        let options = match input.get("options") {
+            None | Some(Yaml::Null) => Vec::new(),
+            Some(Yaml::Sequence(options)) => options
+                .iter()
+                .map(|option| option.as_str().map(str::to_string))
+                .collect::<Option<_>>()
+                .ok_or_else(|| invalid("`options` must be a list of strings".to_string()))?,
+            Some(_) => return Err(invalid("`options` must be a list".to_string())),
+        };
+        if input_type == DispatchInputType::Choice && options.is_empty() {
+            return Err(invalid("a choice input needs `options`".to_string()));
+        }
+        let default = match input.get("default") {
+            None | Some(Yaml::Null) => None,
+            Some(default) => Some(match (input_type, default.as_str()) {
+                (DispatchInputType::Boolean, _) => DispatchValue::Boolean(
+                    boolean(default)
+                        .ok_or_else(|| invalid("`default` must be true or false".to_string()))?,
+                ),
+                (DispatchInputType::Number, Some(number)) => {
+                    DispatchValue::Number(number.parse().map_err(|_| {
+                        invalid(format!("`default` must be a number, found `{number}`"))
+                    })?)
+                }
+                (_, Some(value)) => DispatchValue::String(value.to_string()),
+                (_, None) => return Err(invalid("`default` must be a scalar".to_string())),
+            }),
+        };
+        Ok(Self {
+            name: name.to_string(),
+            description: input
+                .get("description")
+                .and_then(Yaml::as_str)
+                .map(str::to_string),
+            input_type,
+            required,
+            default,
+            options,
+        })
+    }
+}
+
+/// A YAML boolean, quoted or not: GitHub accepts `default: 'true'` for boolean inputs.
+fn boolean(value: &Yaml) -> Option<bool> {
+    match value.as_str()? {
+        "true" | "True" | "TRUE" => Some(true),
+        "false" | "False" | "FALSE" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{DISPATCH_WORKFLOW, WRAPPED_WORKFLOW};
+
+    #[test]
+    fn test_dispatch_inputs_are_read_with_their_types_and_defaults() -> anyhow::Result<()> {
+        let schema = WorkflowDispatchInputs::from_yaml(DISPATCH_WORKFLOW)?;
+
+        assert!(schema.dispatchable);
+        assert_eq!(
+            schema.inputs,
+            [
+                DispatchInput {
+                    name: "environment".to_string(),
+                    description: Some("Where to deploy".to_string()),
+                    input_type: DispatchInputType::Choice,
+                    required: true,
+                    default: Some(DispatchValue::String("staging".to_string())),
+                    options: vec!["staging".to_string(), "production".to_string()],
+                },
+                DispatchInput {
+                    name: "dry_run".to_string(),
+                    description: Some("Plan only: nothing is applied".to_string()),
+                    input_type: DispatchInputType::Boolean,
+                    required: false,
+                    default: Some(DispatchValue::Boolean(false)),
+                    options: Vec::new(),
+                },
+                DispatchInput {
+                    name: "replicas".to_string(),
+                    description: None,
+                    input_type: DispatchInputType::Number,
+                    required: false,
+                    default: Some(DispatchValue::Number(3.into())),
+                    options: Vec::new(),
+                },
+                DispatchInput {
+                    name: "ref".to_string(),
+                    description: Some("Branch or tag to deploy\n".to_string()),
+                    input_type: DispatchInputType::String,
+                    required: false,
+                    default: Some(DispatchValue::String("main".to_string())),
+                    options: Vec::new(),
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_wrapped_lines_are_folded() -> anyhow::Result<()> {
+        let schema = WorkflowDispatchInputs::from_yaml(WRAPPED_WORKFLOW)?;
+
+        assert!(schema.dispatchable);
+        assert_eq!(
+            schema.inputs,
+            [DispatchInput {
+                name: "version".to_string(),
+                description: Some(
+                    "Version to release, without the leading v and without a build suffix"
+                        .to_string()
+                ),
+                input_type: DispatchInputType::String,
+                required: true,
+                default: None,
+                options: Vec::new(),
+            }]
+        );
+        let document = yaml::parse(WRAPPED_WORKFLOW)?;
+        let run = document
+            .get("jobs")
+            .and_then(|jobs| jobs.get("release"))
+            .and_then(|release| release.get("steps"))
+            .and_then(|steps| match steps {
+                Yaml::Sequence(steps) => steps.get(1),
+                _ => None,
+            })
+            .and_then(|step| step.get("run"))
+            .and_then(Yaml::as_str);
+        assert_eq!(run, Some("cargo build --release --locked"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_workflows_without_a_dispatch_trigger_have_no_inputs() -> anyhow::Result<()> {
+        for (source, dispatchable) in [
+            ("on: push\n", false),
+            ("on: [push, pull_request]\n", false),
+            ("on:\n  push:\n    branches: [main]\n", false),
+            ("name: No triggers\n", false),
+            ("on: workflow_dispatch\n", true),
+            ("on: [push, workflow_dispatch]\n", true),
+            ("on:\n  workflow_dispatch:\n", true),
+        ] {
+            assert_eq!(
+                WorkflowDispatchInputs::from_yaml(source)?,
+                WorkflowDispatchInputs {
+                    dispatchable,
+                    inputs: Vec::new()
+                },
+                "{source}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_workflow_files_are_rejected() {
+        for (source, error) in [
+            (
+                "on:\n  workflow_dispatch:\n    inputs:\n      env:\n        type: choice\n",
+                "input `env`: a choice input needs `options`",
+            ),
+            (
+                "on:\n  workflow_dispatch:\n    inputs:\n      n:\n        type: number\n        default: many\n",
+                "input `n`: `default` must be a number, found `many`",
+            ),
+            (
+                "on:\n  push\n  branches: [main]\n",
+                "line 2: expected `key: value`, found `push`",
+            ),
+        ] {
+            assert_eq!(
+                WorkflowDispatchInputs::from_yaml(source),
+                Err(InvalidWorkflowFile(error.to_string()))
+            );
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// A name, or a pattern where `*` matches any characters, `/` included, and `?` matches one.
+///
+/// A backslash makes the next character literal, e.g. `*\[bot\]` for bot accounts.
+/// Serialized as the pattern string.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct GlobPattern {
+    source: String,
+    tokens: Vec<Token>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token {
+    /// `*`
+    Any,
+    /// `?`
+    One,
+    Literal(char),
+}
+
+/// Why a string is not a [`GlobPattern`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum InvalidGlobPattern {
+    /// The pattern is an empty string
+    #[error("pattern must not be empty")]
+    Empty,
+    /// The pattern uses a wildcard other than `*` and `?`
+    #[error("pattern {0:?} uses {1:?}, but only `*` and `?` are supported; escape it with `\\`")]
+    Unsupported(String, char),
+    /// The pattern ends in a backslash escaping nothing
+    #[error("pattern {0:?} ends in an escaping backslash")]
+    TrailingEscape(String),
+}
+
+impl GlobPattern {
+    /// Whether the whole of `name` matches, case-sensitively.
+    #[must_use]
+    pub fn matches(&self, name: &str) -> bool {
+        let pattern = &self.tokens;
+        let name: Vec<char> = name.chars().collect();
+        let (mut p, mut n) = (0, 0);
+        // Where the last `*` was, and the name position it currently stands for up to
+        let mut star: Option<(usize, usize)> = None;
+        while n < name.len() {
+            match pattern.get(p) {
+                Some(Token::Any) => {
+                    star = Some((p, n));
+                    p += 1;
+                }
+                Some(Token::One) => {
+                    p += 1;
+                    n += 1;
+                }
+                Some(Token::Literal(c)) if *c == name[n] => {
+                    p += 1;
+                    n += 1;
+                }
+                _ => match star {
+                    // Let the last `*` swallow one more character and try again
+                    Some((star_p, star_n)) => {
+                        star = Some((star_p, star_n + 1));
+                        p = star_p + 1;
+                        n = star_n + 1;
+                    }
+                    None => return false,
+                },
+            }
+        }
+        pattern[p..].iter().all(|token| *token == Token::Any)
+    }
+
+    /// The pattern as written.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+}
+
+impl FromStr for GlobPattern {
+    type Err = InvalidGlobPattern;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().is_empty() {
+            return Err(InvalidGlobPattern::Empty);
+        }
+        let mut tokens = Vec::new();
+        let mut chars = s.chars();
+        while let Some(c) = chars.next() {
+            tokens.push(match c {
+                '*' => Token::Any,
+                '?' => Token::One,
+                '\\' => Token::Literal(
+                    chars
+                        .next()
+                        .ok_or_else(|| InvalidGlobPattern::TrailingEscape(s.to_string()))?,
+                ),
+                // Reserved so that character classes and alternatives are not mistaken for names
+                '[' | ']' | '{' | '}' => {
+                    return Err(InvalidGlobPattern::Unsupported(s.to_string(), c));
+                }
+                c => Token::Literal(c),
+            });
+        }
+        Ok(Self {
+            source: s.to_string(),
+            tokens,
+        })
+    }
+}
+
+impl fmt::Display for GlobPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl TryFrom<String> for GlobPattern {
+    type Error = InvalidGlobPattern;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<GlobPattern> for String {
+    fn from(pattern: GlobPattern) -> Self {
+        pattern.source
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_patterns_match_whole_names() -> anyhow::Result<()> {
+        let stale: GlobPattern = "stale*".parse()?;
+        assert!(stale.matches("stale"));
+        assert!(stale.matches("stale-bot"));
+        assert!(!stale.matches("not-stale"));
+
+        let middle: GlobPattern = "*-?-*".parse()?;
+        assert!(middle.matches("deploy-a-prod"));
+        assert!(!middle.matches("deploy-ab-prod"));
+
+        let literal: GlobPattern = "CI".parse()?;
+        assert!(literal.matches("CI"));
+        assert!(!literal.matches("ci"));
+        assert!(!literal.matches("CI / lint"));
+
+        let bots: GlobPattern = r"*\[bot\]".parse()?;
+        assert!(bots.matches("dependabot[bot]"));
+        assert!(!bots.matches("dependabot"));
+        let escaped: GlobPattern = r"release\*".parse()?;
+        assert!(escaped.matches("release*"));
+        assert!(!escaped.matches("release/1.0"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_patterns_are_rejected() {
+        assert_eq!("".parse::<GlobPattern>(), Err(InvalidGlobPattern::Empty));
+        assert!(matches!(
+            "build-[ab]".parse::<GlobPattern>(),
+            Err(InvalidGlobPattern::Unsupported(_, '['))
+        ));
+        assert!("{ci,cd}".parse::<GlobPattern>().is_err());
+        assert!(matches!(
+            r"ci\".parse::<GlobPattern>(),
+            Err(InvalidGlobPattern::TrailingEscape(_))
+        ));
+    }
+}
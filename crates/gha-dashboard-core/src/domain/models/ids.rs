@@ -0,0 +1,237 @@
+#[cfg(feature = "schema")]
+use schemars::{JsonSchema, Schema, SchemaGenerator, json_schema};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "schema")]
+use std::borrow::Cow;
+use std::fmt;
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+/// Longest owner or repository name GitHub accepts
+const MAX_SEGMENT_LENGTH: usize = 100;
+
+/// Defines a numeric GitHub identifier serialized as the bare number.
+macro_rules! numeric_id {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+        #[cfg_attr(feature = "schema", derive(JsonSchema))]
+        #[serde(transparent)]
+        pub struct $name(pub u64);
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = ParseIntError;
+
+            fn from_str(value: &str) -> Result<Self, Self::Err> {
+                value.parse().map(Self)
+            }
+        }
+    };
+}
+
+numeric_id!(
+    /// Identifier of a workflow run.
+    RunId
+);
+numeric_id!(
+    /// Identifier of a job within a workflow run.
+    JobId
+);
+numeric_id!(
+    /// Identifier of a workflow, stable across renames of its file.
+    WorkflowId
+);
+numeric_id!(
+    /// Identifier of a self-hosted runner within the repository it is registered to.
+    RunnerId
+);
+numeric_id!(
+    /// Identifier of a deployment to an environment.
+    DeploymentId
+);
+
+/// A repository name that is not a valid `owner/name` pair.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Invalid repository name {value:?}, expected owner/name")]
+pub struct InvalidRepoFullName {
+    /// The rejected input
+    pub value: String,
+}
+
+/// `owner/name` of a repository, validated so both parts are safe to embed in a URL path.
+///
+/// Serialized as the `owner/name` string.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct RepoFullName {
+    owner: String,
+    name: String,
+}
+
+impl RepoFullName {
+    /// Builds a full name from its parts.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InvalidRepoFullName`] unless both parts are non-empty, at most 100
+    /// characters of ASCII letters, digits, `-`, `_` and `.`, and neither is `.` nor `..`.
+    pub fn new(
+        owner: impl Into<String>,
+        name: impl Into<String>,
+    ) -> Result<Self, InvalidRepoFullName> {
+        let (owner, name) = (owner.into(), name.into());
+        if is_valid_segment(&owner) && is_valid_segment(&name) {
+            Ok(Self { owner, name })
+        } else {
+            Err(InvalidRepoFullName {
+                value: format!("{owner}/{name}"),
+            })
+        }
+    }
+
+    /// The user or organization owning the repository.
+    #[must_use]
+    pub fn owner(&self) -> &str {
+        &self.owner
+    }
+
+    /// The name of the repository within its owner.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Compares ignoring ASCII case, as GitHub does for owner and repository names.
+    #[must_use]
+    pub fn eq_ignore_ascii_case(&self, other: &Self) -> bool {
+        self.owner.eq_ignore_ascii_case(&other.owner) && self.name.eq_ignore_ascii_case(&other.name)
+    }
+}
+
+fn is_valid_segment(segment: &str) -> bool {
+    !segment.is_empty()
+        && segment.len() <= MAX_SEGMENT_LENGTH
+        && segment != "."
+        && segment != ".."
+        && segment
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+impl fmt::Display for RepoFullName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.owner, self.name)
+    }
+}
+
+impl FromStr for RepoFullName {
+    type Err = InvalidRepoFullName;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (owner, name) = value.split_once('/').ok_or_else(|| InvalidRepoFullName {
+            value: value.to_string(),
+        })?;
+        Self::new(owner, name)
+    }
+}
+
+impl TryFrom<String> for RepoFullName {
+    type Error = InvalidRepoFullName;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<RepoFullName> for String {
+    fn from(full_name: RepoFullName) -> Self {
+        full_name.to_string()
+    }
+}
+
+#[cfg(feature = "schema")]
+impl JsonSchema for RepoFullName {
+    fn schema_name() -> Cow<'static, str> {
+        "RepoFullName".into()
+    }
+
+    fn json_schema(_generator: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "string",
+            "pattern": "^[A-Za-z0-9._-]+/[A-Za-z0-9._-]+$",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repo_full_name_round_trips() -> anyhow::Result<()> {
+        for value in ["takumi3488/gha-dashboard", "my-org/gha-dashboard.rs_v2"] {
+            let full_name: RepoFullName = value.parse()?;
+            assert_eq!(full_name.to_string(), value);
+            assert_eq!(serde_json::to_value(&full_name)?, serde_json::json!(value));
+            assert_eq!(
+                serde_json::from_value::<RepoFullName>(serde_json::json!(value))?,
+                full_name
+            );
+        }
+        let full_name = RepoFullName::new("owner", "repo")?;
+        assert_eq!((full_name.owner(), full_name.name()), ("owner", "repo"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_malformed_repo_full_names_are_rejected() {
+        for value in [
+            "",
+            "owner",
+            "owner/",
+            "/repo",
+            "owner/repo/extra",
+            "owner/..",
+            "./repo",
+            "owner/re po",
+            "owner/repo?per_page=100",
+            "owner/repo#1",
+        ] {
+            assert!(value.parse::<RepoFullName>().is_err(), "{value} accepted");
+        }
+        assert!(RepoFullName::new("owner", "a".repeat(101)).is_err());
+        assert!(serde_json::from_value::<RepoFullName>(serde_json::json!("owner")).is_err());
+    }
+
+    #[test]
+    fn test_repo_full_names_compare_ignoring_case() -> anyhow::Result<()> {
+        let lower: RepoFullName = "owner/repo".parse()?;
+        let mixed: RepoFullName = "Owner/Repo".parse()?;
+
+        assert_ne!(lower, mixed);
+        assert!(lower.eq_ignore_ascii_case(&mixed));
+        Ok(())
+    }
+
+    #[test]
+    fn test_ids_round_trip() -> anyhow::Result<()> {
+        let run_id: RunId = "42".parse()?;
+        assert_eq!(run_id, RunId(42));
+        assert_eq!(run_id.to_string(), "42");
+        assert_eq!(serde_json::to_value(run_id)?, serde_json::json!(42));
+        assert_eq!(
+            serde_json::from_value::<JobId>(serde_json::json!(7))?,
+            JobId(7)
+        );
+        assert_eq!(JobId(7).to_string(), "7");
+        assert!("-1".parse::<RunId>().is_err());
+        assert!("abc".parse::<JobId>().is_err());
+        Ok(())
+    }
+}
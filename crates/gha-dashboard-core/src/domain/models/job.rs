@@ -0,0 +1,180 @@
+use super::ids::{JobId, RunId};
+use super::status::{RunConclusion, RunStatus};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// A job of a workflow run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Job {
+    /// ID of the job within GitHub
+    pub id: JobId,
+    /// Run the job belongs to
+    #[serde(rename = "runId")]
+    pub run_id: RunId,
+    /// Name of the job, including matrix values, e.g. `test (ubuntu-latest)`
+    pub name: String,
+    /// Where the job is at
+    pub status: RunStatus,
+    /// Set once the job has completed
+    pub conclusion: Option<RunConclusion>,
+    /// When a runner started the job
+    #[serde(rename = "startedAt")]
+    pub started_at: Option<DateTime<Utc>>,
+    /// When the job completed
+    #[serde(rename = "completedAt")]
+    pub completed_at: Option<DateTime<Utc>>,
+    /// `None` until a runner has picked up the job
+    #[serde(rename = "runnerName")]
+    pub runner_name: Option<String>,
+    /// Labels the job requested from `runs-on`, e.g. `ubuntu-latest`
+    pub labels: Vec<String>,
+    /// Page of the job on GitHub
+    #[serde(rename = "htmlUrl")]
+    pub html_url: String,
+    /// Steps of the job in the order they run
+    pub steps: Vec<Step>,
+}
+
+/// A step of a job.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Step {
+    /// 1-based position of the step within its job
+    pub number: u32,
+    /// Name of the step as written in the workflow, or generated by GitHub
+    pub name: String,
+    /// Where the step is at
+    pub status: RunStatus,
+    /// Set once the step has completed
+    pub conclusion: Option<RunConclusion>,
+    /// When the step started
+    #[serde(rename = "startedAt")]
+    pub started_at: Option<DateTime<Utc>>,
+    /// When the step completed
+    #[serde(rename = "completedAt")]
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// Time between `started_at` and `completed_at`; zero if GitHub reports completion before start.
+fn elapsed(
+    started_at: Option<DateTime<Utc>>,
+    completed_at: Option<DateTime<Utc>>,
+) -> Option<Duration> {
+    let (started_at, completed_at) = (started_at?, completed_at?);
+    Some((completed_at - started_at).to_std().unwrap_or_default())
+}
+
+impl Job {
+    /// How long the job ran, `None` until it has both started and completed.
+    #[must_use]
+    pub fn duration(&self) -> Option<Duration> {
+        elapsed(self.started_at, self.completed_at)
+    }
+
+    /// The completed step that took longest, if any.
+    #[must_use]
+    pub fn slowest_step(&self) -> Option<(&Step, Duration)> {
+        self.steps
+            .iter()
+            .filter_map(|step| step.duration().map(|duration| (step, duration)))
+            .max_by_key(|(_, duration)| *duration)
+    }
+}
+
+impl Step {
+    /// How long the step ran, `None` until it has both started and completed.
+    #[must_use]
+    pub fn duration(&self) -> Option<Duration> {
+        elapsed(self.started_at, self.completed_at)
+    }
+}
+
+/// Finds the slowest step across the jobs of any number of runs, with the job it belongs to.
+pub fn slowest_step<'a>(
+    jobs: impl IntoIterator<Item = &'a Job>,
+) -> Option<(&'a Job, &'a Step, Duration)> {
+    jobs.into_iter()
+        .filter_map(|job| {
+            job.slowest_step()
+                .map(|(step, duration)| (job, step, duration))
+        })
+        .max_by_key(|(_, _, duration)| *duration)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::base_time;
+    use chrono::TimeDelta;
+
+    fn step(number: u32, seconds: i64) -> Step {
+        Step {
+            number,
+            name: format!("step {number}"),
+            status: RunStatus::Completed,
+            conclusion: Some(RunConclusion::Success),
+            started_at: Some(base_time()),
+            completed_at: Some(base_time() + TimeDelta::seconds(seconds)),
+        }
+    }
+
+    fn job(id: u64, steps: Vec<Step>) -> Job {
+        Job {
+            id: JobId(id),
+            run_id: RunId(42),
+            name: "build".to_string(),
+            status: RunStatus::Completed,
+            conclusion: Some(RunConclusion::Success),
+            started_at: Some(base_time()),
+            completed_at: Some(base_time() + TimeDelta::seconds(150)),
+            runner_name: Some("GitHub Actions 2".to_string()),
+            labels: vec!["ubuntu-latest".to_string()],
+            html_url: format!("https://github.com/owner/repo/actions/runs/42/job/{id}"),
+            steps,
+        }
+    }
+
+    #[test]
+    fn test_duration_of_completed_job() {
+        assert_eq!(
+            job(1, Vec::new()).duration(),
+            Some(Duration::from_secs(150))
+        );
+    }
+
+    #[test]
+    fn test_duration_is_none_without_completion() {
+        let mut running = job(1, Vec::new());
+        running.completed_at = None;
+        assert_eq!(running.duration(), None);
+
+        let mut queued = step(1, 10);
+        queued.started_at = None;
+        assert_eq!(queued.duration(), None);
+    }
+
+    #[test]
+    fn test_duration_is_clamped_when_completed_before_started() {
+        let mut skewed = job(1, Vec::new());
+        skewed.completed_at = Some(base_time() - TimeDelta::seconds(60));
+        assert_eq!(skewed.duration(), Some(Duration::ZERO));
+        assert_eq!(step(1, -5).duration(), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_slowest_step_across_jobs() {
+        let mut running = step(3, 0);
+        running.completed_at = None;
+        let jobs = [
+            job(1, vec![step(1, 5), step(2, 40)]),
+            job(2, vec![step(1, 90), running]),
+            job(3, Vec::new()),
+        ];
+
+        let slowest =
+            slowest_step(&jobs).map(|(job, step, duration)| (job.id, step.number, duration));
+
+        assert_eq!(slowest, Some((JobId(2), 1, Duration::from_secs(90))));
+        assert_eq!(slowest_step(&[job(4, Vec::new())]), None);
+    }
+}
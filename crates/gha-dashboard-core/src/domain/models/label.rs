@@ -0,0 +1,189 @@
+use super::glob::GlobPattern;
+use super::run::WorkflowRun;
+use serde::{Deserialize, Serialize};
+
+/// Conditions a run must all meet for a [`LabelRule`] to apply.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(deny_unknown_fields)]
+pub struct LabelConditions {
+    /// Head branch; runs without one, e.g. of some tag pushes, never match
+    pub branch: Option<GlobPattern>,
+    /// Workflow name
+    pub workflow: Option<GlobPattern>,
+    /// Triggering event, e.g. `push` or `pull_request`
+    pub event: Option<GlobPattern>,
+    /// Login of the account that started the run, e.g. `dependabot\[bot\]`
+    pub actor: Option<GlobPattern>,
+}
+
+impl LabelConditions {
+    /// Whether no condition is set.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Whether `run` meets every condition that is set.
+    #[must_use]
+    pub fn matches(&self, run: &WorkflowRun) -> bool {
+        let holds = |pattern: &Option<GlobPattern>, value: Option<&str>| {
+            pattern
+                .as_ref()
+                .is_none_or(|pattern| value.is_some_and(|value| pattern.matches(value)))
+        };
+        holds(&self.branch, run.head_branch.as_deref())
+            && holds(&self.workflow, Some(&run.workflow_name))
+            && holds(&self.event, Some(&run.event))
+            && holds(&self.actor, Some(&run.actor.login))
+    }
+}
+
+/// Gives the label `name` to every run meeting its conditions.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct LabelRule {
+    /// Label shown on matching runs
+    pub name: String,
+    /// Conditions a run must all meet to be labelled
+    #[serde(rename = "match")]
+    pub conditions: LabelConditions,
+}
+
+impl LabelRule {
+    /// Checks what deserialization cannot: the rule has a name and at least one condition.
+    ///
+    /// # Errors
+    ///
+    /// Returns a description of the first problem found.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("name must not be empty".to_string());
+        }
+        if self.conditions.is_empty() {
+            return Err(format!(
+                "label {:?} needs at least one condition in match",
+                self.name
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Names of the rules `run` matches, sorted and without duplicates, so the order of the
+/// rules does not matter.
+#[must_use]
+pub fn labels(rules: &[LabelRule], run: &WorkflowRun) -> Vec<String> {
+    let mut labels: Vec<String> = rules
+        .iter()
+        .filter(|rule| rule.conditions.matches(run))
+        .map(|rule| rule.name.clone())
+        .collect();
+    labels.sort();
+    labels.dedup();
+    labels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::WorkflowRunBuilder;
+
+    fn rule(name: &str, conditions: LabelConditions) -> LabelRule {
+        LabelRule {
+            name: name.to_string(),
+            conditions,
+        }
+    }
+
+    fn rules() -> anyhow::Result<Vec<LabelRule>> {
+        Ok(vec![
+            rule(
+                "release",
+                LabelConditions {
+                    branch: Some("release/*".parse()?),
+                    ..LabelConditions::default()
+                },
+            ),
+            rule(
+                "deploy",
+                LabelConditions {
+                    workflow: Some("deploy*".parse()?),
+                    ..LabelConditions::default()
+                },
+            ),
+            rule(
+                "bot",
+                LabelConditions {
+                    actor: Some(r"dependabot\[bot\]".parse()?),
+                    ..LabelConditions::default()
+                },
+            ),
+            rule(
+                "deploy",
+                LabelConditions {
+                    event: Some("workflow_dispatch".parse()?),
+                    branch: Some("main".parse()?),
+                    ..LabelConditions::default()
+                },
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_overlapping_rules_give_every_matching_label_once() -> anyhow::Result<()> {
+        let run = WorkflowRunBuilder::new("owner/repo")
+            .workflow(1, "deploy-production")
+            .branch(Some("release/1.2"))
+            .actor("dependabot[bot]")
+            .build();
+
+        assert_eq!(labels(&rules()?, &run), vec!["bot", "deploy", "release"]);
+        let mut reversed = rules()?;
+        reversed.reverse();
+        assert_eq!(labels(&reversed, &run), vec!["bot", "deploy", "release"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_conditions_of_a_rule_must_all_hold() -> anyhow::Result<()> {
+        let run = |event: &str, branch: &str| {
+            WorkflowRunBuilder::new("owner/repo")
+                .workflow(1, "CI")
+                .event(event)
+                .branch(Some(branch))
+                .build()
+        };
+
+        assert_eq!(
+            labels(&rules()?, &run("workflow_dispatch", "main")),
+            vec!["deploy"]
+        );
+        assert!(labels(&rules()?, &run("workflow_dispatch", "develop")).is_empty());
+        assert!(labels(&rules()?, &run("push", "main")).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_runs_without_a_branch_do_not_match_branch_conditions() -> anyhow::Result<()> {
+        let run = WorkflowRunBuilder::new("owner/repo").branch(None).build();
+
+        assert!(labels(&rules()?, &run).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_rules_need_a_name_and_a_condition() -> anyhow::Result<()> {
+        assert!(
+            rule("release", LabelConditions::default())
+                .validate()
+                .is_err()
+        );
+        assert!(rules()?[0].validate().is_ok());
+        assert!(
+            rule(" ", rules()?[0].conditions.clone())
+                .validate()
+                .is_err()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,248 @@
+use super::ids::JobId;
+use super::job::Job;
+use super::status::{RunConclusion, RunStatus};
+use std::time::Duration;
+
+/// A job of a matrix, named `name (value, ...)` after the values of its cell.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatrixCell {
+    /// Job running the cell
+    pub job_id: JobId,
+    /// Values of the cell, in the order of the matrix keys
+    pub values: Vec<String>,
+    /// Where the job is at
+    pub status: RunStatus,
+    /// Set once the job has completed
+    pub conclusion: Option<RunConclusion>,
+    /// `None` until the job has both started and completed
+    pub duration: Option<Duration>,
+}
+
+impl MatrixCell {
+    /// Whether the cell completed without needing attention; skipped cells count as passed.
+    #[must_use]
+    pub fn passed(&self) -> bool {
+        self.status == RunStatus::Completed
+            && self
+                .conclusion
+                .is_some_and(|conclusion| !conclusion.is_problem())
+    }
+
+    /// Whether the cell ended in a state needing attention.
+    #[must_use]
+    pub fn failed(&self) -> bool {
+        self.conclusion.is_some_and(RunConclusion::is_problem)
+    }
+}
+
+/// Summary of the cells of a [`MatrixGroup`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatrixRollup {
+    /// Every cell completed without needing attention
+    pub all_passed: bool,
+    /// Cells that ended in a state needing attention
+    pub failed: usize,
+    /// The completed cell that took longest
+    pub slowest: Option<JobId>,
+    /// Sum of the durations of the completed cells
+    pub total_duration: Duration,
+}
+
+/// The jobs of a run that are cells of one matrix, grouped under the job's base name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatrixGroup {
+    /// Name of the job without the values of its cells
+    pub name: String,
+    /// In the order GitHub lists the jobs
+    pub cells: Vec<MatrixCell>,
+}
+
+impl MatrixGroup {
+    /// The matrices among `jobs`, in the order of their first cell.
+    ///
+    /// A job whose name only looks like a cell, e.g. `Deploy (production)`, is grouped only
+    /// when another job shares its base name, so single jobs stay on their own.
+    #[must_use]
+    pub fn from_jobs(jobs: &[Job]) -> Vec<Self> {
+        let mut groups: Vec<Self> = Vec::new();
+        for job in jobs {
+            let Some((name, values)) = parse_cell_name(&job.name) else {
+                continue;
+            };
+            let cell = MatrixCell {
+                job_id: job.id,
+                values,
+                status: job.status,
+                conclusion: job.conclusion,
+                duration: job.duration(),
+            };
+            match groups.iter_mut().find(|group| group.name == name) {
+                Some(group) => group.cells.push(cell),
+                None => groups.push(Self {
+                    name: name.to_string(),
+                    cells: vec![cell],
+                }),
+            }
+        }
+        groups.retain(|group| group.cells.len() > 1);
+        groups
+    }
+
+    /// Counts of the cells by outcome.
+    #[must_use]
+    pub fn rollup(&self) -> MatrixRollup {
+        MatrixRollup {
+            all_passed: self.cells.iter().all(MatrixCell::passed),
+            failed: self.cells.iter().filter(|cell| cell.failed()).count(),
+            slowest: self
+                .cells
+                .iter()
+                .filter_map(|cell| cell.duration.map(|duration| (cell.job_id, duration)))
+                .max_by_key(|(_, duration)| *duration)
+                .map(|(job_id, _)| job_id),
+            total_duration: self.cells.iter().filter_map(|cell| cell.duration).sum(),
+        }
+    }
+}
+
+/// Splits `build (ubuntu-latest, 18)` into `build` and its values; `None` for names not ending
+/// in a parenthesized list. Parentheses within the values are kept balanced.
+fn parse_cell_name(name: &str) -> Option<(&str, Vec<String>)> {
+    let inner_end = name.strip_suffix(')')?.len();
+    let mut depth = 0_usize;
+    let open = name[..inner_end].char_indices().rev().find_map(|(i, c)| {
+        match c {
+            ')' => depth += 1,
+            '(' if depth == 0 => return Some(i),
+            '(' => depth -= 1,
+            _ => {}
+        }
+        None
+    })?;
+    let base = name[..open].strip_suffix(' ')?.trim_end();
+    let inner = &name[open + 1..inner_end];
+    if base.is_empty() || inner.trim().is_empty() {
+        return None;
+    }
+    Some((base, inner.split(", ").map(str::to_string).collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::ids::RunId;
+    use crate::test_support::base_time;
+    use chrono::TimeDelta;
+
+    fn job(id: u64, name: &str, conclusion: RunConclusion, seconds: i64) -> Job {
+        Job {
+            id: JobId(id),
+            run_id: RunId(42),
+            name: name.to_string(),
+            status: RunStatus::Completed,
+            conclusion: Some(conclusion),
+            started_at: Some(base_time()),
+            completed_at: Some(base_time() + TimeDelta::seconds(seconds)),
+            runner_name: None,
+            labels: Vec::new(),
+            html_url: format!("https://github.com/owner/repo/actions/runs/42/job/{id}"),
+            steps: Vec::new(),
+        }
+    }
+
+    /// A 3×2 matrix of `test` whose `macos-latest, 20` cell failed, between two single jobs.
+    fn jobs() -> Vec<Job> {
+        vec![
+            job(1, "lint", RunConclusion::Success, 30),
+            job(2, "test (ubuntu-latest, 18)", RunConclusion::Success, 100),
+            job(3, "test (ubuntu-latest, 20)", RunConclusion::Success, 110),
+            job(4, "test (windows-latest, 18)", RunConclusion::Success, 300),
+            job(5, "test (windows-latest, 20)", RunConclusion::Success, 280),
+            job(6, "test (macos-latest, 18)", RunConclusion::Success, 200),
+            job(7, "test (macos-latest, 20)", RunConclusion::Failure, 50),
+            job(8, "Deploy (production)", RunConclusion::Skipped, 0),
+        ]
+    }
+
+    #[test]
+    fn test_matrix_cells_are_grouped_under_their_base_name() -> anyhow::Result<()> {
+        let groups = MatrixGroup::from_jobs(&jobs());
+
+        let [group] = groups.as_slice() else {
+            anyhow::bail!("expected one matrix, got {groups:?}");
+        };
+        assert_eq!(group.name, "test");
+        let cells: Vec<(JobId, Vec<&str>)> = group
+            .cells
+            .iter()
+            .map(|cell| {
+                (
+                    cell.job_id,
+                    cell.values.iter().map(String::as_str).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            cells,
+            [
+                (JobId(2), vec!["ubuntu-latest", "18"]),
+                (JobId(3), vec!["ubuntu-latest", "20"]),
+                (JobId(4), vec!["windows-latest", "18"]),
+                (JobId(5), vec!["windows-latest", "20"]),
+                (JobId(6), vec!["macos-latest", "18"]),
+                (JobId(7), vec!["macos-latest", "20"]),
+            ]
+        );
+        assert_eq!(
+            group.rollup(),
+            MatrixRollup {
+                all_passed: false,
+                failed: 1,
+                slowest: Some(JobId(4)),
+                total_duration: Duration::from_secs(1040),
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_running_cells_have_not_passed_yet() -> anyhow::Result<()> {
+        let mut jobs = jobs();
+        jobs[6].conclusion = Some(RunConclusion::Success);
+        jobs[1].status = RunStatus::InProgress;
+        jobs[1].conclusion = None;
+        jobs[1].completed_at = None;
+
+        let groups = MatrixGroup::from_jobs(&jobs);
+        let [running] = groups.as_slice() else {
+            anyhow::bail!("expected one matrix");
+        };
+        let rollup = running.rollup();
+        assert_eq!((rollup.all_passed, rollup.failed), (false, 0));
+        assert_eq!(rollup.total_duration, Duration::from_secs(940));
+
+        jobs[1] = job(2, "test (ubuntu-latest, 18)", RunConclusion::Success, 100);
+        let groups = MatrixGroup::from_jobs(&jobs);
+        let [passed] = groups.as_slice() else {
+            anyhow::bail!("expected one matrix");
+        };
+        assert!(passed.rollup().all_passed);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cell_names_are_parsed_with_nested_parentheses() {
+        assert_eq!(
+            parse_cell_name("build (linux (arm64), 1.80)"),
+            Some((
+                "build",
+                vec!["linux (arm64)".to_string(), "1.80".to_string()]
+            ))
+        );
+        assert_eq!(parse_cell_name("build"), None);
+        assert_eq!(parse_cell_name("(x)"), None);
+        assert_eq!(parse_cell_name("build ()"), None);
+        assert_eq!(parse_cell_name("build(x)"), None);
+        assert_eq!(parse_cell_name("build x)"), None);
+    }
+}
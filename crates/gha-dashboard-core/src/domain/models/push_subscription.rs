@@ -0,0 +1,123 @@
+use super::ids::RepoFullName;
+use super::transition::RunTransition;
+use serde::{Deserialize, Serialize};
+
+/// A browser's Web Push subscription, as its `PushSubscription.toJSON()` describes it, with
+/// the transitions it wants to hear about.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PushSubscription {
+    /// URL of the push service the notifications are posted to
+    pub endpoint: String,
+    /// Keys the payload is encrypted with
+    pub keys: PushKeys,
+    /// Which transitions are pushed; failures only when omitted
+    #[serde(default)]
+    pub filter: PushFilter,
+}
+
+/// Encryption keys of a [`PushSubscription`], base64url encoded.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PushKeys {
+    /// Public key of the browser on the P-256 curve
+    pub p256dh: String,
+    /// Authentication secret shared with the browser
+    pub auth: String,
+}
+
+/// Which transitions a [`PushSubscription`] is told about.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PushFilter {
+    /// Only runs that ended in a state needing attention
+    pub failures_only: bool,
+    /// Empty means every repository
+    pub repos: Vec<RepoFullName>,
+    /// Empty means every branch
+    pub branches: Vec<String>,
+}
+
+impl Default for PushFilter {
+    fn default() -> Self {
+        Self {
+            failures_only: true,
+            repos: Vec::new(),
+            branches: Vec::new(),
+        }
+    }
+}
+
+impl PushFilter {
+    /// Whether `transition` passes every filter.
+    #[must_use]
+    pub fn matches(&self, transition: &RunTransition) -> bool {
+        let run = &transition.run;
+        (!self.failures_only || transition.is_failure())
+            && (self.repos.is_empty()
+                || self
+                    .repos
+                    .iter()
+                    .any(|repo| run.repository_name.eq_ignore_ascii_case(repo)))
+            && (self.branches.is_empty()
+                || run
+                    .head_branch
+                    .as_ref()
+                    .is_some_and(|branch| self.branches.contains(branch)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::status::RunStatus;
+    use crate::test_support::workflow_run;
+
+    fn transition(repo: &str, conclusion: &str) -> RunTransition {
+        RunTransition {
+            run: workflow_run(repo, 1, conclusion),
+            previous_status: Some(RunStatus::InProgress),
+            previous_conclusion: None,
+        }
+    }
+
+    #[test]
+    fn test_subscriptions_default_to_failures_only() -> anyhow::Result<()> {
+        let subscription: PushSubscription = serde_json::from_value(serde_json::json!({
+            "endpoint": "https://push.example.com/send/abc",
+            "expirationTime": null,
+            "keys": {"p256dh": "BKey", "auth": "secret"},
+        }))?;
+
+        assert_eq!(subscription.filter, PushFilter::default());
+        assert!(
+            subscription
+                .filter
+                .matches(&transition("owner/repo", "failure"))
+        );
+        assert!(
+            !subscription
+                .filter
+                .matches(&transition("owner/repo", "success"))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_filter_matches_repos_and_branches() -> anyhow::Result<()> {
+        let filter = PushFilter {
+            failures_only: false,
+            repos: vec!["Owner/Repo".parse()?],
+            branches: vec!["main".to_string()],
+        };
+        let mut on_main = transition("owner/repo", "success");
+        on_main.run.head_branch = Some("main".to_string());
+        let mut on_feature = on_main.clone();
+        on_feature.run.head_branch = Some("feature".to_string());
+        let mut elsewhere = on_main.clone();
+        elsewhere.run.repository_name = "owner/other".parse()?;
+
+        assert!(filter.matches(&on_main));
+        assert!(!filter.matches(&on_feature));
+        assert!(!filter.matches(&elsewhere));
+        Ok(())
+    }
+}
@@ -0,0 +1,111 @@
+use super::ids::{RepoFullName, RunId};
+use super::run::WorkflowRun;
+use super::status::RunStatus;
+use chrono::{DateTime, Utc};
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The runs of one repository waiting for a runner.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct RepositoryQueue {
+    /// Repository the runs are queued in
+    pub repository: RepoFullName,
+    /// Number of queued runs
+    pub queued: u64,
+    /// Time the longest-waiting run has been queued, counted from its creation; `None`
+    /// when nothing is queued
+    #[serde(rename = "oldestAgeSeconds")]
+    pub oldest_age_seconds: Option<u64>,
+    /// Queued runs, longest-waiting first
+    #[serde(rename = "runIds")]
+    pub run_ids: Vec<RunId>,
+}
+
+impl RepositoryQueue {
+    /// The queue of every repository of `repositories` or `runs` at `now`, by name.
+    ///
+    /// Repositories without queued runs are listed with an empty queue, so a drained
+    /// queue reads as zero rather than as missing.
+    #[must_use]
+    pub fn per_repository<'a>(
+        repositories: impl IntoIterator<Item = &'a RepoFullName>,
+        runs: &[WorkflowRun],
+        now: DateTime<Utc>,
+    ) -> Vec<Self> {
+        let mut queued: BTreeMap<&RepoFullName, Vec<&WorkflowRun>> = repositories
+            .into_iter()
+            .map(|repository| (repository, Vec::new()))
+            .collect();
+        for run in runs.iter().filter(|run| run.status == RunStatus::Queued) {
+            queued.entry(&run.repository_name).or_default().push(run);
+        }
+        queued
+            .into_iter()
+            .map(|(repository, mut runs)| {
+                runs.sort_by_key(|run| (run.created_at, run.id));
+                runs.dedup_by_key(|run| run.id);
+                Self {
+                    repository: repository.clone(),
+                    queued: runs.len() as u64,
+                    oldest_age_seconds: runs.first().map(|run| {
+                        u64::try_from((now - run.created_at).num_seconds()).unwrap_or_default()
+                    }),
+                    run_ids: runs.iter().map(|run| run.id).collect(),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{base_time, repo_full_name, workflow_run};
+    use chrono::TimeDelta;
+
+    #[test]
+    fn test_queues_count_queued_runs_per_repository() {
+        let repositories = [repo_full_name("owner/idle"), repo_full_name("owner/repo")];
+        let runs = vec![
+            workflow_run("owner/repo", 3, "queued"),
+            workflow_run("owner/repo", 1, "queued"),
+            workflow_run("owner/repo", 2, "in_progress"),
+            workflow_run("owner/idle", 4, "success"),
+            workflow_run("owner/other", 5, "queued"),
+            workflow_run("owner/repo", 1, "queued"),
+        ];
+
+        let queues = RepositoryQueue::per_repository(
+            &repositories,
+            &runs,
+            base_time() + TimeDelta::hours(1),
+        );
+
+        assert_eq!(
+            queues,
+            vec![
+                RepositoryQueue {
+                    repository: repo_full_name("owner/idle"),
+                    queued: 0,
+                    oldest_age_seconds: None,
+                    run_ids: Vec::new(),
+                },
+                RepositoryQueue {
+                    repository: repo_full_name("owner/other"),
+                    queued: 1,
+                    oldest_age_seconds: Some(55 * 60),
+                    run_ids: vec![RunId(5)],
+                },
+                RepositoryQueue {
+                    repository: repo_full_name("owner/repo"),
+                    queued: 2,
+                    oldest_age_seconds: Some(59 * 60),
+                    run_ids: vec![RunId(1), RunId(3)],
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,47 @@
+use super::ids::RepoFullName;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A repository appearing in or leaving the repositories the token can see.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RepositoryChange {
+    /// Listed for the first time
+    Discovered {
+        /// The new repository
+        repository: RepoFullName,
+    },
+    /// No longer found, e.g. because it was deleted or made inaccessible
+    Removed {
+        /// The repository as it was last listed
+        repository: RepoFullName,
+    },
+    /// Now found under another name
+    Renamed {
+        /// The name it was last listed under
+        from: RepoFullName,
+        /// The name GitHub redirects to
+        to: RepoFullName,
+    },
+}
+
+impl RepositoryChange {
+    /// The repository as it is named now, or was named last when removed.
+    #[must_use]
+    pub fn repository(&self) -> &RepoFullName {
+        match self {
+            Self::Discovered { repository } | Self::Removed { repository } => repository,
+            Self::Renamed { to, .. } => to,
+        }
+    }
+}
+
+impl fmt::Display for RepositoryChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Discovered { repository } => write!(f, "new repository detected: {repository}"),
+            Self::Removed { repository } => write!(f, "repository removed: {repository}"),
+            Self::Renamed { from, to } => write!(f, "repository renamed: {from} -> {to}"),
+        }
+    }
+}
@@ -0,0 +1,384 @@
+use super::actor::RunActor;
+use super::ids::{RepoFullName, RunId, WorkflowId};
+use super::status::{RunConclusion, RunStatus};
+use chrono::{DateTime, Utc};
+#[cfg(feature = "schema")]
+use schemars::{JsonSchema, Schema, SchemaGenerator};
+use serde::{Deserialize, Serialize, Serializer};
+#[cfg(feature = "schema")]
+use std::borrow::Cow;
+use std::num::NonZeroUsize;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Appended to display titles that were shortened
+const ELLIPSIS: &str = "\u{2026}";
+
+/// A workflow run.
+///
+/// Serialized with extra computed `displayStatus` and `durationSeconds` fields; update
+/// [`WorkflowRunJson`] along with the fields here.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct WorkflowRun {
+    /// Repository the run belongs to
+    #[serde(rename = "repositoryName")]
+    pub repository_name: RepoFullName,
+    /// ID of the run within GitHub
+    pub id: RunId,
+    /// Sequential number of the run within its workflow, shown as `#1234`
+    #[serde(rename = "runNumber")]
+    pub run_number: u64,
+    /// Workflow the run belongs to
+    #[serde(rename = "workflowId")]
+    pub workflow_id: WorkflowId,
+    /// Name of the workflow at the time of the run
+    #[serde(rename = "workflowName")]
+    pub workflow_name: String,
+    /// Title of the run, usually the commit message or pull request title
+    #[serde(rename = "displayTitle")]
+    pub display_title: String,
+    /// The original title when `display_title` was truncated
+    #[serde(rename = "fullDisplayTitle", default)]
+    pub full_display_title: Option<String>,
+    /// Event that triggered the run, e.g. `push`
+    pub event: String,
+    /// `None` for events without a branch, e.g. some tag pushes
+    #[serde(rename = "headBranch")]
+    pub head_branch: Option<String>,
+    /// Commit the run checked out
+    #[serde(rename = "headSha")]
+    pub head_sha: String,
+    /// Account that started the first attempt of the run
+    pub actor: RunActor,
+    /// Account that started the latest attempt; differs from `actor` for re-runs
+    #[serde(rename = "triggeringActor")]
+    pub triggering_actor: Option<RunActor>,
+    /// Where the run is at
+    pub status: RunStatus,
+    /// Set once the run has completed
+    pub conclusion: Option<RunConclusion>,
+    /// When the run was created
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    /// When the run last changed
+    #[serde(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+    /// Page of the run on GitHub
+    #[serde(rename = "htmlUrl")]
+    pub html_url: String,
+    /// Names of the configured label rules the run matches, sorted
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// Whether the run's repository is still polled; `false` for runs kept after their
+    /// repository dropped out of the polled ones
+    #[serde(rename = "repositoryActive", default = "active")]
+    pub repository_active: bool,
+    /// Whether the run reports a required status check of its branch; only set for runs on
+    /// the default branch of a repository whose branch protection the token can read
+    #[serde(rename = "isRequiredCheck", default)]
+    pub is_required_check: Option<bool>,
+    /// Names of the jobs whose latest attempt failed, sorted; only known from `workflow_job`
+    /// webhooks, so empty without them
+    #[serde(rename = "failedJobs", default)]
+    pub failed_jobs: Vec<String>,
+    /// Page of the job to look at first among `failed_jobs`: a failed one before a cancelled
+    /// or timed out one, the one started earliest among those
+    #[serde(rename = "failedJobUrl", default)]
+    pub failed_job_url: Option<String>,
+}
+
+/// Runs read without `repositoryActive` come from a polled repository.
+fn active() -> bool {
+    true
+}
+
+impl WorkflowRun {
+    /// The conclusion of a completed run, otherwise its status.
+    ///
+    /// This is what the single `status` string used to hold before the two were split.
+    #[must_use]
+    pub fn display_status(&self) -> &'static str {
+        match (self.status, self.conclusion) {
+            (RunStatus::Completed, Some(conclusion)) => conclusion.as_str(),
+            (status, _) => status.as_str(),
+        }
+    }
+
+    /// Whether the run ended in a state that needs attention.
+    #[must_use]
+    pub fn is_problem(&self) -> bool {
+        self.conclusion.is_some_and(RunConclusion::is_problem)
+    }
+
+    /// Seconds from creation to the last update of a completed run, `None` while it runs.
+    ///
+    /// Clamped to zero when clock skew or a re-run makes `updated_at` precede `created_at`.
+    #[must_use]
+    pub fn duration_seconds(&self) -> Option<u64> {
+        (self.status == RunStatus::Completed).then(|| {
+            u64::try_from((self.updated_at - self.created_at).num_seconds()).unwrap_or_default()
+        })
+    }
+
+    /// Whether the timestamps of the run cannot be right as of `now`: updated before it was
+    /// created, or created in the future, as when the clocks of GitHub and this host disagree.
+    #[must_use]
+    pub fn has_clock_skew(&self, now: DateTime<Utc>) -> bool {
+        self.updated_at < self.created_at || self.created_at > now
+    }
+
+    /// Whether the run reports one of `required_checks`, the required status checks of its
+    /// branch: a check named after its workflow, or after one of its jobs as `Workflow / job`.
+    #[must_use]
+    pub fn reports_required_check(&self, required_checks: &[String]) -> bool {
+        required_checks.iter().any(|check| {
+            check
+                .strip_prefix(self.workflow_name.as_str())
+                .is_some_and(|job| job.is_empty() || job.starts_with(" / "))
+        })
+    }
+
+    /// Shortens `display_title` to at most `max_length` grapheme clusters, ending in an
+    /// ellipsis, and keeps the original in `full_display_title`.
+    ///
+    /// Cuts only between grapheme clusters, so emoji sequences and letters with combining
+    /// marks are never split.
+    pub fn truncate_display_title(&mut self, max_length: NonZeroUsize) {
+        if let Some(truncated) = truncate_graphemes(&self.display_title, max_length) {
+            self.full_display_title = Some(std::mem::replace(&mut self.display_title, truncated));
+        }
+    }
+}
+
+/// `text` cut to `max_length` grapheme clusters including a trailing ellipsis, or `None` if
+/// it already fits.
+fn truncate_graphemes(text: &str, max_length: NonZeroUsize) -> Option<String> {
+    let max_length = max_length.get();
+    let mut clusters = text.grapheme_indices(true).map(|(index, _)| index);
+    // Start of the cluster the ellipsis replaces; the text must also have one past the limit
+    let cut = clusters.nth(max_length - 1)?;
+    clusters.next()?;
+    let mut truncated = text[..cut].trim_end().to_string();
+    truncated.push_str(ELLIPSIS);
+    Some(truncated)
+}
+
+/// Serialized form of [`WorkflowRun`], borrowing its fields.
+#[derive(Serialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+struct WorkflowRunJson<'a> {
+    #[serde(rename = "repositoryName")]
+    repository_name: &'a RepoFullName,
+    id: RunId,
+    #[serde(rename = "runNumber")]
+    run_number: u64,
+    #[serde(rename = "workflowId")]
+    workflow_id: WorkflowId,
+    #[serde(rename = "workflowName")]
+    workflow_name: &'a str,
+    #[serde(rename = "displayTitle")]
+    display_title: &'a str,
+    #[serde(rename = "fullDisplayTitle")]
+    full_display_title: Option<&'a str>,
+    event: &'a str,
+    #[serde(rename = "headBranch")]
+    head_branch: Option<&'a str>,
+    #[serde(rename = "headSha")]
+    head_sha: &'a str,
+    actor: &'a RunActor,
+    #[serde(rename = "triggeringActor")]
+    triggering_actor: Option<&'a RunActor>,
+    status: RunStatus,
+    conclusion: Option<RunConclusion>,
+    #[serde(rename = "displayStatus")]
+    display_status: &'a str,
+    #[serde(rename = "durationSeconds")]
+    duration_seconds: Option<u64>,
+    #[serde(rename = "createdAt")]
+    created_at: DateTime<Utc>,
+    #[serde(rename = "updatedAt")]
+    updated_at: DateTime<Utc>,
+    #[serde(rename = "htmlUrl")]
+    html_url: &'a str,
+    labels: &'a [String],
+    #[serde(rename = "repositoryActive")]
+    repository_active: bool,
+    #[serde(rename = "isRequiredCheck", skip_serializing_if = "Option::is_none")]
+    is_required_check: Option<bool>,
+    #[serde(
+        rename = "failedJobs",
+        default,
+        skip_serializing_if = "<[_]>::is_empty"
+    )]
+    failed_jobs: &'a [String],
+    #[serde(rename = "failedJobUrl", skip_serializing_if = "Option::is_none")]
+    failed_job_url: Option<&'a str>,
+}
+
+impl Serialize for WorkflowRun {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        WorkflowRunJson {
+            repository_name: &self.repository_name,
+            id: self.id,
+            run_number: self.run_number,
+            workflow_id: self.workflow_id,
+            workflow_name: &self.workflow_name,
+            display_title: &self.display_title,
+            full_display_title: self.full_display_title.as_deref(),
+            event: &self.event,
+            head_branch: self.head_branch.as_deref(),
+            head_sha: &self.head_sha,
+            actor: &self.actor,
+            triggering_actor: self.triggering_actor.as_ref(),
+            status: self.status,
+            conclusion: self.conclusion,
+            display_status: self.display_status(),
+            duration_seconds: self.duration_seconds(),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            html_url: &self.html_url,
+            labels: &self.labels,
+            repository_active: self.repository_active,
+            is_required_check: self.is_required_check,
+            failed_jobs: &self.failed_jobs,
+            failed_job_url: self.failed_job_url.as_deref(),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Describes the serialized form, including the computed fields.
+#[cfg(feature = "schema")]
+impl JsonSchema for WorkflowRun {
+    fn schema_name() -> Cow<'static, str> {
+        "WorkflowRun".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        WorkflowRunJson::json_schema(generator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::workflow_run;
+    use chrono::Duration;
+
+    fn length(value: usize) -> anyhow::Result<NonZeroUsize> {
+        NonZeroUsize::new(value).ok_or_else(|| anyhow::anyhow!("zero length"))
+    }
+
+    #[test]
+    fn test_truncation_keeps_grapheme_clusters_whole() -> anyhow::Result<()> {
+        let titles = [
+            "Fix the flaky integration test",
+            "🚀 Release v1.2.3 🎉",
+            // Family emoji joined with zero width joiners, and flags made of two code points
+            "👨‍👩‍👧‍👦 family 🇯🇵🇺🇸 flags",
+            "依存関係を更新してテストを修正",
+            // Letters followed by combining accents
+            "Cafe\u{301} naïve re\u{301}sume\u{301}",
+            "👍🏽 skin tones 👍🏿",
+            "",
+        ];
+        for title in titles {
+            let clusters: Vec<&str> = title.graphemes(true).collect();
+            for max in 1..=clusters.len() + 1 {
+                let mut run = workflow_run("owner/repo", 1, "success");
+                run.display_title = title.to_string();
+                run.truncate_display_title(length(max)?);
+
+                let truncated: Vec<&str> = run.display_title.graphemes(true).collect();
+                assert!(truncated.len() <= max, "{title:?} cut to {max}");
+                if clusters.len() <= max {
+                    assert_eq!(run.display_title, title);
+                    assert_eq!(run.full_display_title, None);
+                    continue;
+                }
+                assert_eq!(run.full_display_title.as_deref(), Some(title));
+                let kept = run
+                    .display_title
+                    .strip_suffix(ELLIPSIS)
+                    .ok_or_else(|| anyhow::anyhow!("{title:?} cut to {max} lacks an ellipsis"))?;
+                // Every kept cluster is one of the original's, not a fragment of it
+                let kept: Vec<&str> = kept.graphemes(true).collect();
+                assert_eq!(kept, clusters[..kept.len()], "{title:?} cut to {max}");
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_truncation_drops_trailing_whitespace() -> anyhow::Result<()> {
+        let mut run = workflow_run("owner/repo", 1, "success");
+        run.display_title = "Fix bug in parser".to_string();
+        run.truncate_display_title(length(5)?);
+        assert_eq!(run.display_title, "Fix\u{2026}");
+        Ok(())
+    }
+
+    #[test]
+    fn test_display_status_prefers_conclusion_of_completed_runs() {
+        let mut run = workflow_run("owner/repo", 1, "in_progress");
+        assert_eq!(run.display_status(), "in_progress");
+
+        run.status = RunStatus::Completed;
+        assert_eq!(run.display_status(), "completed");
+
+        run.conclusion = Some(RunConclusion::TimedOut);
+        assert_eq!(run.display_status(), "timed_out");
+    }
+
+    #[test]
+    fn test_serialized_run_round_trips_with_display_status() -> anyhow::Result<()> {
+        let run = workflow_run("owner/repo", 1, "failure");
+
+        let json = serde_json::to_value(&run)?;
+
+        assert_eq!(json["status"], "completed");
+        assert_eq!(json["conclusion"], "failure");
+        assert_eq!(json["displayStatus"], "failure");
+        assert_eq!(serde_json::from_value::<WorkflowRun>(json)?, run);
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_field_names_snapshot() {
+        insta::assert_json_snapshot!(workflow_run("owner/repo", 42, "success"));
+    }
+
+    #[test]
+    fn test_duration_is_clamped_to_zero() {
+        let mut run = workflow_run("owner/repo", 1, "success");
+        run.updated_at = run.created_at - Duration::seconds(30);
+
+        assert_eq!(run.duration_seconds(), Some(0));
+    }
+
+    #[test]
+    fn test_clock_skew_is_detected() {
+        let mut run = workflow_run("owner/repo", 1, "success");
+        let now = run.updated_at;
+        assert!(!run.has_clock_skew(now));
+
+        assert!(run.has_clock_skew(run.created_at - Duration::seconds(1)));
+        run.updated_at = run.created_at - Duration::seconds(30);
+        assert!(run.has_clock_skew(now));
+    }
+
+    #[test]
+    fn test_completed_run_json_snapshot() {
+        let mut run = workflow_run("owner/repo", 7, "failure");
+        run.updated_at = run.created_at + Duration::seconds(205);
+
+        insta::assert_json_snapshot!(run);
+    }
+
+    #[test]
+    fn test_in_progress_run_json_snapshot() {
+        let mut run = workflow_run("owner/repo", 8, "in_progress");
+        run.updated_at = run.created_at + Duration::seconds(90);
+
+        insta::assert_json_snapshot!(run);
+    }
+}
@@ -0,0 +1,318 @@
+use super::job::Job;
+use super::status::RunConclusion;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Slowdown, in percent of the base duration, flagged as a regression unless asked otherwise
+pub const DEFAULT_REGRESSION_THRESHOLD_PERCENT: f64 = 20.0;
+
+/// How long something took in each run.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DurationChange {
+    /// `None` unless it completed in the base run
+    #[serde(rename = "baseSeconds")]
+    pub base_seconds: Option<u64>,
+    /// `None` unless it completed in the head run
+    #[serde(rename = "headSeconds")]
+    pub head_seconds: Option<u64>,
+    /// Head minus base; `None` unless both are known
+    #[serde(rename = "deltaSeconds")]
+    pub delta_seconds: Option<i64>,
+    /// The delta in percent of the base; `None` unless the base is known and not zero
+    #[serde(rename = "deltaPercent")]
+    pub delta_percent: Option<f64>,
+    /// Whether the head is slower than the base by more than the threshold
+    pub regression: bool,
+}
+
+impl DurationChange {
+    /// Compares `base` with `head`, flagging slowdowns above `threshold_percent`.
+    #[must_use]
+    pub fn new(base: Option<Duration>, head: Option<Duration>, threshold_percent: f64) -> Self {
+        let signed = |duration: Duration| i64::try_from(duration.as_secs()).unwrap_or(i64::MAX);
+        let delta_seconds = base
+            .zip(head)
+            .map(|(base, head)| signed(head).saturating_sub(signed(base)));
+        let delta_percent =
+            base.zip(head)
+                .filter(|(base, _)| !base.is_zero())
+                .map(|(base, head)| {
+                    (head.as_secs_f64() - base.as_secs_f64()) * 100.0 / base.as_secs_f64()
+                });
+        Self {
+            base_seconds: base.map(|base| base.as_secs()),
+            head_seconds: head.map(|head| head.as_secs()),
+            delta_seconds,
+            delta_percent,
+            regression: delta_percent.is_some_and(|percent| percent > threshold_percent),
+        }
+    }
+}
+
+/// A step that ended differently in the two runs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StepChange {
+    /// Name of the step
+    pub name: String,
+    /// How it ended in the base run; `None` while it had not
+    pub base: Option<RunConclusion>,
+    /// How it ended in the head run; `None` while it had not
+    pub head: Option<RunConclusion>,
+}
+
+/// A job found in both runs.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JobComparison {
+    /// Name of the job, matrix values included
+    pub name: String,
+    /// How it ended in the base run
+    #[serde(rename = "baseConclusion")]
+    pub base_conclusion: Option<RunConclusion>,
+    /// How it ended in the head run
+    #[serde(rename = "headConclusion")]
+    pub head_conclusion: Option<RunConclusion>,
+    /// How long it took in each run
+    pub duration: DurationChange,
+    /// Steps of the same name found in both runs that ended differently, in the head's order
+    #[serde(rename = "changedSteps")]
+    pub changed_steps: Vec<StepChange>,
+}
+
+/// Differences between the jobs of two runs of a workflow: a base, usually the last good one,
+/// and a head.
+///
+/// Jobs are matched by name, so a renamed job counts as one removed and one added. Jobs
+/// sharing a name are matched in the order GitHub lists them.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RunComparison {
+    /// How long the whole run took in each
+    pub total: DurationChange,
+    /// Jobs found in both runs, in the head's order
+    pub jobs: Vec<JobComparison>,
+    /// Names of jobs only in the head run
+    pub added: Vec<String>,
+    /// Names of jobs only in the base run
+    pub removed: Vec<String>,
+    /// Jobs slower than the threshold allows, by name
+    pub regressions: Vec<String>,
+    /// Whether the whole run or any job is slower than the threshold allows
+    pub regression: bool,
+}
+
+impl RunComparison {
+    /// Compares the jobs of two runs, and their totals when known.
+    ///
+    /// A total that is not known is taken from the jobs, from the first start to the last
+    /// completion.
+    #[must_use]
+    pub fn new(
+        base: &[Job],
+        head: &[Job],
+        (base_total, head_total): (Option<Duration>, Option<Duration>),
+        threshold_percent: f64,
+    ) -> Self {
+        let mut unmatched: HashMap<&str, Vec<&Job>> = HashMap::new();
+        for job in base.iter().rev() {
+            unmatched.entry(&job.name).or_default().push(job);
+        }
+        let mut jobs = Vec::new();
+        let mut added = Vec::new();
+        for head_job in head {
+            match unmatched.get_mut(head_job.name.as_str()).and_then(Vec::pop) {
+                Some(base_job) => jobs.push(compare_jobs(base_job, head_job, threshold_percent)),
+                None => added.push(head_job.name.clone()),
+            }
+        }
+        let removed = base
+            .iter()
+            .filter(|job| {
+                let left = unmatched.get(job.name.as_str());
+                left.is_some_and(|left| left.iter().any(|left| std::ptr::eq(*left, *job)))
+            })
+            .map(|job| job.name.clone())
+            .collect();
+        let total = DurationChange::new(
+            base_total.or_else(|| span(base)),
+            head_total.or_else(|| span(head)),
+            threshold_percent,
+        );
+        let regressions: Vec<String> = jobs
+            .iter()
+            .filter(|job| job.duration.regression)
+            .map(|job| job.name.clone())
+            .collect();
+        Self {
+            regression: total.regression || !regressions.is_empty(),
+            total,
+            jobs,
+            added,
+            removed,
+            regressions,
+        }
+    }
+}
+
+fn compare_jobs(base: &Job, head: &Job, threshold_percent: f64) -> JobComparison {
+    let changed_steps = head
+        .steps
+        .iter()
+        .filter_map(|head_step| {
+            let base_step = base.steps.iter().find(|step| step.name == head_step.name)?;
+            (base_step.conclusion != head_step.conclusion).then(|| StepChange {
+                name: head_step.name.clone(),
+                base: base_step.conclusion,
+                head: head_step.conclusion,
+            })
+        })
+        .collect();
+    JobComparison {
+        name: head.name.clone(),
+        base_conclusion: base.conclusion,
+        head_conclusion: head.conclusion,
+        duration: DurationChange::new(base.duration(), head.duration(), threshold_percent),
+        changed_steps,
+    }
+}
+
+/// From the first job's start to the last job's completion; `None` unless every job completed.
+fn span(jobs: &[Job]) -> Option<Duration> {
+    let started_at = jobs.iter().map(|job| job.started_at).min()??;
+    let completed_at = jobs
+        .iter()
+        .map(|job| job.completed_at)
+        .collect::<Option<Vec<_>>>()?
+        .into_iter()
+        .max()?;
+    Some((completed_at - started_at).to_std().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::ids::{JobId, RunId};
+    use crate::domain::models::job::Step;
+    use crate::domain::models::status::RunStatus;
+    use crate::test_support::base_time;
+    use chrono::TimeDelta;
+
+    fn step(name: &str, conclusion: RunConclusion) -> Step {
+        Step {
+            number: 1,
+            name: name.to_string(),
+            status: RunStatus::Completed,
+            conclusion: Some(conclusion),
+            started_at: Some(base_time()),
+            completed_at: Some(base_time()),
+        }
+    }
+
+    fn job(id: u64, name: &str, start: i64, seconds: i64, steps: Vec<Step>) -> Job {
+        Job {
+            id: JobId(id),
+            run_id: RunId(1),
+            name: name.to_string(),
+            status: RunStatus::Completed,
+            conclusion: Some(RunConclusion::Success),
+            started_at: Some(base_time() + TimeDelta::seconds(start)),
+            completed_at: Some(base_time() + TimeDelta::seconds(start + seconds)),
+            runner_name: None,
+            labels: Vec::new(),
+            html_url: String::new(),
+            steps,
+        }
+    }
+
+    #[test]
+    fn test_reordered_jobs_are_matched_by_name() {
+        let base = [
+            job(1, "lint", 0, 60, Vec::new()),
+            job(
+                2,
+                "test",
+                0,
+                100,
+                vec![step("cargo test", RunConclusion::Success)],
+            ),
+        ];
+        let head = [
+            job(
+                3,
+                "test",
+                0,
+                130,
+                vec![step("cargo test", RunConclusion::Failure)],
+            ),
+            job(4, "lint", 0, 66, Vec::new()),
+        ];
+
+        let comparison = RunComparison::new(&base, &head, (None, None), 20.0);
+
+        let names: Vec<_> = comparison
+            .jobs
+            .iter()
+            .map(|job| job.name.as_str())
+            .collect();
+        assert_eq!(names, ["test", "lint"]);
+        assert_eq!(comparison.jobs[0].duration.delta_seconds, Some(30));
+        assert_eq!(comparison.jobs[0].duration.delta_percent, Some(30.0));
+        assert_eq!(
+            comparison.jobs[0].changed_steps,
+            [StepChange {
+                name: "cargo test".to_string(),
+                base: Some(RunConclusion::Success),
+                head: Some(RunConclusion::Failure),
+            }]
+        );
+        // 10% slower is within the threshold
+        assert!(!comparison.jobs[1].duration.regression);
+        assert_eq!(comparison.regressions, ["test"]);
+        assert!(comparison.added.is_empty() && comparison.removed.is_empty());
+        assert_eq!(comparison.total.delta_seconds, Some(30));
+        assert!(comparison.regression);
+    }
+
+    #[test]
+    fn test_renamed_jobs_are_added_and_removed() {
+        let base = [
+            job(1, "build (ubuntu)", 0, 100, Vec::new()),
+            job(2, "build (ubuntu)", 0, 200, Vec::new()),
+            job(3, "test", 0, 100, Vec::new()),
+        ];
+        let head = [
+            job(4, "build (ubuntu)", 0, 100, Vec::new()),
+            job(5, "unit tests", 0, 100, Vec::new()),
+        ];
+
+        let comparison = RunComparison::new(
+            &base,
+            &head,
+            (Some(Duration::from_mins(5)), Some(Duration::from_mins(4))),
+            20.0,
+        );
+
+        assert_eq!(comparison.jobs.len(), 1);
+        assert_eq!(comparison.jobs[0].duration.delta_seconds, Some(0));
+        assert_eq!(comparison.added, ["unit tests"]);
+        assert_eq!(comparison.removed, ["build (ubuntu)", "test"]);
+        assert_eq!(comparison.total.delta_seconds, Some(-60));
+        assert!(!comparison.regression);
+    }
+
+    #[test]
+    fn test_unknown_durations_are_never_regressions() {
+        let mut running = job(2, "test", 0, 100, Vec::new());
+        running.completed_at = None;
+
+        let comparison = RunComparison::new(
+            &[job(1, "test", 0, 0, Vec::new())],
+            &[running],
+            (None, None),
+            20.0,
+        );
+
+        assert_eq!(comparison.jobs[0].duration.delta_seconds, None);
+        assert_eq!(comparison.total.head_seconds, None);
+        assert!(!comparison.regression);
+    }
+}
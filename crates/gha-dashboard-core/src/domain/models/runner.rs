@@ -0,0 +1,73 @@
+use super::ids::{RepoFullName, RunnerId};
+use serde::{Deserialize, Serialize};
+
+/// Whether a self-hosted runner is connected to GitHub.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RunnerStatus {
+    /// Connected to GitHub
+    Online,
+    /// Not connected to GitHub
+    Offline,
+}
+
+impl RunnerStatus {
+    /// The status as GitHub reports it, e.g. `online`.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Online => "online",
+            Self::Offline => "offline",
+        }
+    }
+}
+
+/// A self-hosted runner registered to a repository.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SelfHostedRunner {
+    /// ID of the runner within GitHub
+    pub id: RunnerId,
+    /// Name the runner was registered with
+    pub name: String,
+    /// Operating system as the runner reports it, e.g. `Linux`
+    pub os: String,
+    /// Whether the runner is connected
+    pub status: RunnerStatus,
+    /// Whether the runner is running a job
+    pub busy: bool,
+    /// Labels jobs select the runner by, e.g. `self-hosted` and `linux`
+    pub labels: Vec<String>,
+}
+
+/// The self-hosted runners of one repository, counted by status.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RepositoryRunners {
+    /// Repository the runners are registered to
+    pub repository: RepoFullName,
+    /// Connected runners, busy or idle
+    pub online: u64,
+    /// Disconnected runners
+    pub offline: u64,
+    /// Runners running a job
+    pub busy: u64,
+    /// Sorted by name
+    pub runners: Vec<SelfHostedRunner>,
+}
+
+impl RepositoryRunners {
+    /// Summarizes `runners`, sorting them by name.
+    #[must_use]
+    pub fn new(repository: RepoFullName, mut runners: Vec<SelfHostedRunner>) -> Self {
+        runners.sort_by(|a, b| a.name.cmp(&b.name).then(a.id.cmp(&b.id)));
+        let count = |matches: fn(&SelfHostedRunner) -> bool| {
+            runners.iter().filter(|runner| matches(runner)).count() as u64
+        };
+        Self {
+            repository,
+            online: count(|runner| runner.status == RunnerStatus::Online),
+            offline: count(|runner| runner.status == RunnerStatus::Offline),
+            busy: count(|runner| runner.busy),
+            runners,
+        }
+    }
+}
@@ -0,0 +1,335 @@
+use super::ids::{RepoFullName, WorkflowId};
+use chrono::{DateTime, Datelike, NaiveDate, TimeDelta, Timelike, Utc};
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Years searched for the next fire time; leap days are at most eight years apart
+const MAX_SEARCH_YEARS: i32 = 8;
+
+const MONTH_NAMES: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+
+const DAY_NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+/// A five-field cron expression of a `schedule` trigger, evaluated in UTC as GitHub does.
+///
+/// Fields accept `*`, values, `a-b` ranges, `/step` and comma-separated lists; months and
+/// days of the week also accept their English abbreviations, and 7 is Sunday like 0. When
+/// both the day of the month and the day of the week are restricted, a day matching either
+/// fires.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    /// One bit per allowed value of each field
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    /// Whether a day matching either day field fires, rather than one matching both
+    either_day: bool,
+}
+
+/// Why a string is not a [`CronSchedule`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("invalid cron expression {expression:?}: {message}")]
+pub struct InvalidCronExpression {
+    /// The rejected expression
+    pub expression: String,
+    /// Why it was rejected
+    pub message: String,
+}
+
+impl FromStr for CronSchedule {
+    type Err = InvalidCronExpression;
+
+    fn from_str(expression: &str) -> Result<Self, Self::Err> {
+        let invalid = |message| InvalidCronExpression {
+            expression: expression.to_string(),
+            message,
+        };
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day_of_month, month, weekday] = fields[..] else {
+            return Err(invalid(format!(
+                "expected 5 fields, found {}",
+                fields.len()
+            )));
+        };
+        let mut days_of_week =
+            parse_field(weekday, "day of week", 0, 7, &DAY_NAMES).map_err(invalid)?;
+        if bit(days_of_week, 7) {
+            days_of_week = days_of_week & !(1 << 7) | 1;
+        }
+        Ok(Self {
+            minutes: parse_field(minute, "minute", 0, 59, &[]).map_err(invalid)?,
+            hours: parse_field(hour, "hour", 0, 23, &[]).map_err(invalid)?,
+            days_of_month: parse_field(day_of_month, "day of month", 1, 31, &[])
+                .map_err(invalid)?,
+            months: parse_field(month, "month", 1, 12, &MONTH_NAMES).map_err(invalid)?,
+            days_of_week,
+            either_day: !day_of_month.starts_with('*') && !weekday.starts_with('*'),
+        })
+    }
+}
+
+impl CronSchedule {
+    /// The first time after `after` the expression fires, or `None` if it never does, e.g.
+    /// on February 30th.
+    #[must_use]
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        // Schedules fire on whole minutes, and never at `after` itself
+        let start = after.with_second(0)?.with_nanosecond(0)? + TimeDelta::minutes(1);
+        let mut date = start.date_naive();
+        let (mut hour, mut minute) = (start.hour(), start.minute());
+        while date.year() <= start.year() + MAX_SEARCH_YEARS {
+            if self.fires_on(date)
+                && let Some((hour, minute)) = self.first_time_from(hour, minute)
+            {
+                return date.and_hms_opt(hour, minute, 0).map(|time| time.and_utc());
+            }
+            date = date.succ_opt()?;
+            (hour, minute) = (0, 0);
+        }
+        None
+    }
+
+    fn fires_on(&self, date: NaiveDate) -> bool {
+        let day_of_month = bit(self.days_of_month, date.day());
+        let day_of_week = bit(self.days_of_week, date.weekday().num_days_from_sunday());
+        let day = if self.either_day {
+            day_of_month || day_of_week
+        } else {
+            day_of_month && day_of_week
+        };
+        day && bit(self.months, date.month())
+    }
+
+    /// The first hour and minute the expression fires at from `hour:minute` to the end of a day.
+    fn first_time_from(&self, hour: u32, minute: u32) -> Option<(u32, u32)> {
+        (hour..24).filter(|&h| bit(self.hours, h)).find_map(|h| {
+            let from = if h == hour { minute } else { 0 };
+            (from..60).find(|&m| bit(self.minutes, m)).map(|m| (h, m))
+        })
+    }
+}
+
+fn bit(bits: u64, value: u32) -> bool {
+    bits & (1 << value) != 0
+}
+
+/// The values of `min..=max` a cron field allows, one bit each; `names` stand for the values
+/// from `min` on.
+fn parse_field(field: &str, name: &str, min: u32, max: u32, names: &[&str]) -> Result<u64, String> {
+    let value = |text: &str| {
+        let value = match names.iter().position(|n| n.eq_ignore_ascii_case(text)) {
+            Some(index) => min + u32::try_from(index).unwrap_or_default(),
+            None => text
+                .parse()
+                .map_err(|_| format!("`{text}` is not a valid {name}"))?,
+        };
+        if (min..=max).contains(&value) {
+            Ok(value)
+        } else {
+            Err(format!("{name} {value} is not between {min} and {max}"))
+        }
+    };
+
+    let mut bits = 0;
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => match step.parse::<u32>() {
+                Ok(step) if step > 0 => (range, Some(step)),
+                _ => return Err(format!("`{step}` is not a valid step of the {name} field")),
+            },
+            None => (item, None),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (value(start)?, value(end)?)
+        } else {
+            let start = value(range)?;
+            // `5/15` steps from 5 to the end of the field
+            (start, if step.is_some() { max } else { start })
+        };
+        if start > end {
+            return Err(format!("range `{range}` of the {name} field is reversed"));
+        }
+        let step = step.unwrap_or(1);
+        for value in (start..=end).filter(|value| (value - start).is_multiple_of(step)) {
+            bits |= 1 << value;
+        }
+    }
+    Ok(bits)
+}
+
+/// The `schedule` trigger of a workflow, which fires on each of its cron expressions.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorkflowSchedule {
+    crons: Vec<CronSchedule>,
+    /// Expressions GitHub would not accept either; kept aside so the valid ones still count
+    invalid: Vec<InvalidCronExpression>,
+}
+
+impl WorkflowSchedule {
+    /// Parses the `schedule` cron expressions of a workflow, keeping the invalid ones as errors.
+    #[must_use]
+    pub fn parse(expressions: &[String]) -> Self {
+        let mut schedule = Self::default();
+        for expression in expressions {
+            match expression.parse() {
+                Ok(cron) => schedule.crons.push(cron),
+                Err(e) => schedule.invalid.push(e),
+            }
+        }
+        schedule
+    }
+
+    /// The earliest time after `after` one of the valid expressions fires.
+    #[must_use]
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.crons
+            .iter()
+            .filter_map(|cron| cron.next_after(after))
+            .min()
+    }
+
+    /// Why some of the expressions were left out, if any were.
+    #[must_use]
+    pub fn error(&self) -> Option<String> {
+        (!self.invalid.is_empty()).then(|| {
+            self.invalid
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("; ")
+        })
+    }
+}
+
+/// A run a `schedule` trigger is due to start, shown before GitHub creates it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct UpcomingRun {
+    /// Repository of the scheduled workflow
+    #[serde(rename = "repositoryName")]
+    pub repository_name: RepoFullName,
+    /// The scheduled workflow
+    #[serde(rename = "workflowId")]
+    pub workflow_id: WorkflowId,
+    /// Name of the scheduled workflow
+    #[serde(rename = "workflowName")]
+    pub workflow_name: String,
+    /// When the schedule fires; GitHub often starts scheduled runs some minutes later
+    #[serde(rename = "scheduledAt")]
+    pub scheduled_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> Option<DateTime<Utc>> {
+        Utc.with_ymd_and_hms(year, month, day, hour, minute, 0)
+            .single()
+    }
+
+    fn next(
+        expression: &str,
+        after: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<Option<DateTime<Utc>>> {
+        let after = after.ok_or_else(|| anyhow::anyhow!("invalid time"))?;
+        Ok(expression.parse::<CronSchedule>()?.next_after(after))
+    }
+
+    #[test]
+    fn test_next_fire_times_cross_utc_boundaries() -> anyhow::Result<()> {
+        for (expression, after, expected) in [
+            // Strictly after, on the next whole minute
+            ("* * * * *", at(2024, 8, 1, 10, 0), at(2024, 8, 1, 10, 1)),
+            (
+                "*/15 * * * *",
+                at(2024, 8, 1, 10, 59),
+                at(2024, 8, 1, 11, 0),
+            ),
+            // Across the end of a day, a month and a year
+            ("30 5 * * *", at(2024, 8, 1, 5, 30), at(2024, 8, 2, 5, 30)),
+            ("0 0 1 * *", at(2024, 1, 31, 23, 59), at(2024, 2, 1, 0, 0)),
+            ("0 0 * * *", at(2024, 12, 31, 23, 59), at(2025, 1, 1, 0, 0)),
+            ("0 12 31 * *", at(2024, 4, 1, 0, 0), at(2024, 5, 31, 12, 0)),
+            // Leap days
+            ("0 0 29 2 *", at(2024, 3, 1, 0, 0), at(2028, 2, 29, 0, 0)),
+            ("0 0 29 FEB *", at(2096, 3, 1, 0, 0), at(2104, 2, 29, 0, 0)),
+            // Weekdays, with names and 7 as Sunday; 2024-08-03 is a Saturday
+            ("0 9 * * 1-5", at(2024, 8, 2, 9, 0), at(2024, 8, 5, 9, 0)),
+            ("0 9 * * sun", at(2024, 8, 3, 0, 0), at(2024, 8, 4, 9, 0)),
+            ("0 9 * * 7", at(2024, 8, 3, 0, 0), at(2024, 8, 4, 9, 0)),
+            // Either day field when both are restricted, both when one is `*`
+            ("0 0 15 * 1", at(2024, 8, 6, 0, 0), at(2024, 8, 12, 0, 0)),
+            ("0 0 */10 * *", at(2024, 8, 1, 0, 0), at(2024, 8, 11, 0, 0)),
+            // Lists and steps from a value
+            (
+                "5,50 8/12 * * *",
+                at(2024, 8, 1, 8, 5),
+                at(2024, 8, 1, 8, 50),
+            ),
+            (
+                "5,50 8/12 * * *",
+                at(2024, 8, 1, 8, 50),
+                at(2024, 8, 1, 20, 5),
+            ),
+            // Never
+            ("0 0 30 2 *", at(2024, 1, 1, 0, 0), None),
+        ] {
+            assert_eq!(next(expression, after)?, expected, "{expression}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_expressions_are_rejected() {
+        for (expression, message) in [
+            ("* * * *", "expected 5 fields, found 4"),
+            ("60 * * * *", "minute 60 is not between 0 and 59"),
+            ("* * 0 * *", "day of month 0 is not between 1 and 31"),
+            ("* * * JUNE *", "`JUNE` is not a valid month"),
+            ("*/0 * * * *", "`0` is not a valid step of the minute field"),
+            ("* 20-4 * * *", "range `20-4` of the hour field is reversed"),
+            ("@daily", "expected 5 fields, found 1"),
+        ] {
+            assert_eq!(
+                expression.parse::<CronSchedule>(),
+                Err(InvalidCronExpression {
+                    expression: expression.to_string(),
+                    message: message.to_string(),
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn test_a_workflow_fires_on_its_earliest_valid_expression() -> anyhow::Result<()> {
+        let invalid_time = || anyhow::anyhow!("invalid time");
+        let (noon, late) = (
+            at(2024, 12, 31, 12, 0).ok_or_else(invalid_time)?,
+            at(2024, 12, 31, 23, 30).ok_or_else(invalid_time)?,
+        );
+        let schedule = WorkflowSchedule::parse(&[
+            "0 6 * * *".to_string(),
+            "not a cron".to_string(),
+            "30 23 31 12 *".to_string(),
+        ]);
+
+        assert_eq!(schedule.next_after(noon), Some(late));
+        assert_eq!(schedule.next_after(late), at(2025, 1, 1, 6, 0));
+        assert_eq!(
+            schedule.error().as_deref(),
+            Some(r#"invalid cron expression "not a cron": expected 5 fields, found 3"#)
+        );
+        assert_eq!(WorkflowSchedule::parse(&[]).next_after(noon), None);
+        Ok(())
+    }
+}
@@ -0,0 +1,201 @@
+use super::glob::GlobPattern;
+use super::ids::RepoFullName;
+use super::run::WorkflowRun;
+use super::status::{RunConclusion, RunStatus};
+use serde::{Deserialize, Serialize};
+
+/// A service level objective, e.g. CI on `main` green within 30 minutes 95% of the time
+/// over the last 7 days; one `[[slos]]` entry of the config.
+///
+/// Serialized in camelCase, with `repo` as `repository`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Slo {
+    /// Repository whose runs are measured
+    #[serde(rename(serialize = "repository"))]
+    pub repo: RepoFullName,
+    /// Workflow name; all workflows of the repository when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workflow: Option<GlobPattern>,
+    /// Head branch; all branches when unset, and runs without one never match when set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<GlobPattern>,
+    /// Longest a run may take from creation to completion and still meet the objective
+    #[serde(rename(serialize = "targetDurationMinutes"))]
+    pub target_duration_minutes: u32,
+    /// Share of the runs that must meet the objective, strictly between 0 and 1
+    #[serde(rename(serialize = "targetSuccessRate"))]
+    pub target_success_rate: f64,
+    /// Days of runs measured, ending now
+    #[serde(rename(serialize = "windowDays"))]
+    pub window_days: u32,
+}
+
+/// How one run fares against an [`Slo`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SloOutcome {
+    /// Not measured: another workflow or branch, not completed yet, or cancelled, skipped or
+    /// superseded, which says nothing about the health of the workflow
+    Excluded,
+    /// Succeeded within the target duration
+    Met,
+    /// Completed without succeeding
+    Failed,
+    /// Succeeded, but took longer than the target duration
+    Slow,
+}
+
+impl Slo {
+    /// Checks what deserialization cannot: the targets and the window are in range.
+    ///
+    /// # Errors
+    ///
+    /// Returns a description of the first problem found.
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.target_success_rate > 0.0 && self.target_success_rate < 1.0) {
+            return Err(format!(
+                "target_success_rate must be between 0 and 1 exclusive, e.g. 0.95, got {}",
+                self.target_success_rate
+            ));
+        }
+        if self.target_duration_minutes == 0 {
+            return Err("target_duration_minutes must be at least 1".to_string());
+        }
+        if self.window_days == 0 {
+            return Err("window_days must be at least 1".to_string());
+        }
+        Ok(())
+    }
+
+    /// Whether `run` is one of the runs the objective is about, whatever its outcome.
+    #[must_use]
+    pub fn matches(&self, run: &WorkflowRun) -> bool {
+        run.repository_name.eq_ignore_ascii_case(&self.repo)
+            && self
+                .workflow
+                .as_ref()
+                .is_none_or(|pattern| pattern.matches(&run.workflow_name))
+            && self.branch.as_ref().is_none_or(|pattern| {
+                run.head_branch
+                    .as_deref()
+                    .is_some_and(|branch| pattern.matches(branch))
+            })
+    }
+
+    /// How `run` fares against the objective.
+    #[must_use]
+    pub fn outcome(&self, run: &WorkflowRun) -> SloOutcome {
+        if !self.matches(run) || run.status != RunStatus::Completed {
+            return SloOutcome::Excluded;
+        }
+        match run.conclusion {
+            Some(RunConclusion::Cancelled | RunConclusion::Skipped | RunConclusion::Stale) => {
+                SloOutcome::Excluded
+            }
+            Some(RunConclusion::Success) => {
+                let target_seconds = u64::from(self.target_duration_minutes) * 60;
+                if run.duration_seconds().unwrap_or_default() <= target_seconds {
+                    SloOutcome::Met
+                } else {
+                    SloOutcome::Slow
+                }
+            }
+            _ => SloOutcome::Failed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::base_time;
+    use crate::testing::WorkflowRunBuilder;
+    use chrono::TimeDelta;
+
+    fn slo() -> anyhow::Result<Slo> {
+        Ok(Slo {
+            repo: "owner/repo".parse()?,
+            workflow: Some("CI".parse()?),
+            branch: Some("main".parse()?),
+            target_duration_minutes: 30,
+            target_success_rate: 0.95,
+            window_days: 7,
+        })
+    }
+
+    /// A run of CI on main taking `minutes` to end with `conclusion`.
+    fn run(minutes: i64, conclusion: RunConclusion) -> WorkflowRun {
+        WorkflowRunBuilder::new("owner/repo")
+            .conclusion(conclusion)
+            .created_at(base_time())
+            .updated_at(base_time() + TimeDelta::minutes(minutes))
+            .build()
+    }
+
+    #[test]
+    fn test_outcome_straddles_the_target_duration() -> anyhow::Result<()> {
+        let slo = slo()?;
+
+        assert_eq!(
+            slo.outcome(&run(30, RunConclusion::Success)),
+            SloOutcome::Met
+        );
+        assert_eq!(
+            slo.outcome(&run(31, RunConclusion::Success)),
+            SloOutcome::Slow
+        );
+        assert_eq!(
+            slo.outcome(&run(5, RunConclusion::Failure)),
+            SloOutcome::Failed
+        );
+        assert_eq!(
+            slo.outcome(&run(5, RunConclusion::Cancelled)),
+            SloOutcome::Excluded
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_runs_outside_the_filters_are_excluded() -> anyhow::Result<()> {
+        let slo = slo()?;
+        let mut other_branch = run(5, RunConclusion::Failure);
+        other_branch.head_branch = Some("feature".to_string());
+        let mut no_branch = run(5, RunConclusion::Failure);
+        no_branch.head_branch = None;
+        let mut other_workflow = run(5, RunConclusion::Failure);
+        other_workflow.workflow_name = "Lint".to_string();
+        let mut other_repo = run(5, RunConclusion::Failure);
+        other_repo.repository_name = "owner/other".parse()?;
+        let mut running = run(5, RunConclusion::Failure);
+        running.status = RunStatus::InProgress;
+
+        for run in [other_branch, no_branch, other_workflow, other_repo, running] {
+            assert_eq!(slo.outcome(&run), SloOutcome::Excluded, "{run:?}");
+        }
+        let mut other_case = run(5, RunConclusion::Failure);
+        other_case.repository_name = "Owner/Repo".parse()?;
+        assert_eq!(slo.outcome(&other_case), SloOutcome::Failed);
+        Ok(())
+    }
+
+    #[test]
+    fn test_targets_out_of_range_are_rejected() -> anyhow::Result<()> {
+        for rate in [0.0, 1.0, 95.0, f64::NAN] {
+            let slo = Slo {
+                target_success_rate: rate,
+                ..slo()?
+            };
+            assert!(slo.validate().is_err(), "{rate} accepted");
+        }
+        assert!(
+            Slo {
+                window_days: 0,
+                ..slo()?
+            }
+            .validate()
+            .is_err()
+        );
+        assert!(slo()?.validate().is_ok());
+        Ok(())
+    }
+}
@@ -0,0 +1,216 @@
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Lifecycle state of a workflow run, as reported in GitHub's `status` field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    /// Waiting for a runner
+    Queued,
+    /// Running on a runner
+    InProgress,
+    /// Finished, with a conclusion
+    Completed,
+    /// Waiting for an environment to be approved
+    Waiting,
+    /// Created but not yet queued
+    Requested,
+    /// Waiting for a concurrency group to free up
+    Pending,
+    /// A value GitHub added after this enum was written
+    #[serde(other)]
+    Unknown,
+}
+
+/// Outcome of a completed workflow run, as reported in GitHub's `conclusion` field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum RunConclusion {
+    /// Every job succeeded
+    Success,
+    /// A job failed
+    Failure,
+    /// Cancelled before it finished
+    Cancelled,
+    /// Its conditions were not met
+    Skipped,
+    /// A job ran out of time
+    TimedOut,
+    /// Waiting for a manual action, e.g. a first-time contributor approval
+    ActionRequired,
+    /// Neither succeeded nor failed
+    Neutral,
+    /// Superseded before it could finish
+    Stale,
+    /// The workflow file could not be run
+    StartupFailure,
+    /// A value GitHub added after this enum was written
+    #[serde(other)]
+    Unknown,
+}
+
+impl RunStatus {
+    /// Every value GitHub documents, in declaration order.
+    pub const ALL: [Self; 6] = [
+        Self::Queued,
+        Self::InProgress,
+        Self::Completed,
+        Self::Waiting,
+        Self::Requested,
+        Self::Pending,
+    ];
+
+    /// The GitHub API spelling of the status.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Queued => "queued",
+            Self::InProgress => "in_progress",
+            Self::Completed => "completed",
+            Self::Waiting => "waiting",
+            Self::Requested => "requested",
+            Self::Pending => "pending",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+impl RunConclusion {
+    /// Every value GitHub documents, in declaration order.
+    pub const ALL: [Self; 9] = [
+        Self::Success,
+        Self::Failure,
+        Self::Cancelled,
+        Self::Skipped,
+        Self::TimedOut,
+        Self::ActionRequired,
+        Self::Neutral,
+        Self::Stale,
+        Self::StartupFailure,
+    ];
+
+    /// The GitHub API spelling of the conclusion.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::Failure => "failure",
+            Self::Cancelled => "cancelled",
+            Self::Skipped => "skipped",
+            Self::TimedOut => "timed_out",
+            Self::ActionRequired => "action_required",
+            Self::Neutral => "neutral",
+            Self::Stale => "stale",
+            Self::StartupFailure => "startup_failure",
+            Self::Unknown => "unknown",
+        }
+    }
+
+    /// Whether the outcome needs someone's attention.
+    #[must_use]
+    pub fn is_problem(self) -> bool {
+        matches!(
+            self,
+            Self::Failure
+                | Self::TimedOut
+                | Self::StartupFailure
+                | Self::Cancelled
+                | Self::ActionRequired
+        )
+    }
+}
+
+/// Parses the GitHub spelling; unrecognized values map to `Unknown` like in serde.
+impl FromStr for RunStatus {
+    type Err = std::convert::Infallible;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Ok(Self::ALL
+            .into_iter()
+            .find(|status| status.as_str() == value)
+            .unwrap_or(Self::Unknown))
+    }
+}
+
+/// Parses the GitHub spelling; unrecognized values map to `Unknown` like in serde.
+impl FromStr for RunConclusion {
+    type Err = std::convert::Infallible;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Ok(Self::ALL
+            .into_iter()
+            .find(|conclusion| conclusion.as_str() == value)
+            .unwrap_or(Self::Unknown))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GITHUB_STATUSES: [&str; 6] = [
+        "queued",
+        "in_progress",
+        "completed",
+        "waiting",
+        "requested",
+        "pending",
+    ];
+
+    const GITHUB_CONCLUSIONS: [&str; 9] = [
+        "success",
+        "failure",
+        "cancelled",
+        "skipped",
+        "timed_out",
+        "action_required",
+        "neutral",
+        "stale",
+        "startup_failure",
+    ];
+
+    #[test]
+    fn test_every_github_status_round_trips() -> anyhow::Result<()> {
+        for value in GITHUB_STATUSES {
+            let status: RunStatus = serde_json::from_value(serde_json::json!(value))?;
+            assert_ne!(status, RunStatus::Unknown, "{value} is not recognized");
+            assert_eq!(serde_json::to_value(status)?, serde_json::json!(value));
+            assert_eq!(status.as_str(), value);
+            assert_eq!(value.parse::<RunStatus>(), Ok(status));
+        }
+        assert_eq!(RunStatus::ALL.len(), GITHUB_STATUSES.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_every_github_conclusion_round_trips() -> anyhow::Result<()> {
+        for value in GITHUB_CONCLUSIONS {
+            let conclusion: RunConclusion = serde_json::from_value(serde_json::json!(value))?;
+            assert_ne!(
+                conclusion,
+                RunConclusion::Unknown,
+                "{value} is not recognized"
+            );
+            assert_eq!(serde_json::to_value(conclusion)?, serde_json::json!(value));
+            assert_eq!(conclusion.as_str(), value);
+            assert_eq!(value.parse::<RunConclusion>(), Ok(conclusion));
+        }
+        assert_eq!(RunConclusion::ALL.len(), GITHUB_CONCLUSIONS.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_unrecognized_values_fall_back_to_unknown() -> anyhow::Result<()> {
+        let status: RunStatus = serde_json::from_value(serde_json::json!("paused"))?;
+        let conclusion: RunConclusion = serde_json::from_value(serde_json::json!("exploded"))?;
+
+        assert_eq!(status, RunStatus::Unknown);
+        assert_eq!(conclusion, RunConclusion::Unknown);
+        assert_eq!("paused".parse::<RunStatus>(), Ok(RunStatus::Unknown));
+        Ok(())
+    }
+}
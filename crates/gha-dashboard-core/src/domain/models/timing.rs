@@ -0,0 +1,60 @@
+use super::ids::JobId;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Operating system of a GitHub-hosted runner, as GitHub bills it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RunnerOs {
+    /// Linux runners
+    Ubuntu,
+    /// Windows runners
+    Windows,
+    /// macOS runners
+    Macos,
+}
+
+impl RunnerOs {
+    /// Parses a key of the timing API, e.g. `UBUNTU`.
+    #[must_use]
+    pub fn from_billable_key(key: &str) -> Option<Self> {
+        match key {
+            "UBUNTU" => Some(Self::Ubuntu),
+            "WINDOWS" => Some(Self::Windows),
+            "MACOS" => Some(Self::Macos),
+            _ => None,
+        }
+    }
+}
+
+/// Time one job spent on a GitHub-hosted runner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobTiming {
+    /// The job the time was billed for
+    #[serde(rename = "jobId")]
+    pub job_id: JobId,
+    /// Billed time of the job
+    #[serde(rename = "durationMs")]
+    pub duration_ms: u64,
+}
+
+/// Time a run spent on GitHub-hosted runners of one operating system.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct BillableTime {
+    /// Billed time of all jobs on the operating system
+    #[serde(rename = "totalMs")]
+    pub total_ms: u64,
+    /// Empty when GitHub only reports the total
+    #[serde(rename = "jobRuns")]
+    pub job_runs: Vec<JobTiming>,
+}
+
+/// Billable time of a workflow run, as reported by GitHub's run timing endpoint.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct RunTiming {
+    /// Billed time by runner operating system
+    pub billable: BTreeMap<RunnerOs, BillableTime>,
+    /// Wall-clock time of the run; `None` while it is in progress
+    #[serde(rename = "runDurationMs")]
+    pub run_duration_ms: Option<u64>,
+}
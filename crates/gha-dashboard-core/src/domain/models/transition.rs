@@ -0,0 +1,106 @@
+use super::run::WorkflowRun;
+use super::status::{RunConclusion, RunStatus};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// A run whose status or conclusion changed between two snapshots.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RunTransition {
+    /// The run after the change
+    pub run: WorkflowRun,
+    /// `None` for a run the earlier snapshot did not show
+    #[serde(rename = "previousStatus")]
+    pub previous_status: Option<RunStatus>,
+    /// Conclusion of the run in the previous snapshot
+    #[serde(rename = "previousConclusion")]
+    pub previous_conclusion: Option<RunConclusion>,
+}
+
+impl RunTransition {
+    /// The runs of `current` whose status or conclusion differs from `previous`.
+    ///
+    /// Runs missing from `previous` count only when it showed runs of their repository, so
+    /// a repository added to the polled set does not report its whole history at once.
+    #[must_use]
+    pub fn detect(previous: &[WorkflowRun], current: &[WorkflowRun]) -> Vec<Self> {
+        let shown: HashMap<_, _> = previous.iter().map(|run| (run.id, run)).collect();
+        let repositories: HashSet<_> = previous.iter().map(|run| &run.repository_name).collect();
+        current
+            .iter()
+            .filter_map(|run| match shown.get(&run.id) {
+                Some(before)
+                    if (before.status, before.conclusion) == (run.status, run.conclusion) =>
+                {
+                    None
+                }
+                Some(before) => Some(Self {
+                    run: run.clone(),
+                    previous_status: Some(before.status),
+                    previous_conclusion: before.conclusion,
+                }),
+                None if repositories.contains(&run.repository_name) => Some(Self {
+                    run: run.clone(),
+                    previous_status: None,
+                    previous_conclusion: None,
+                }),
+                None => None,
+            })
+            .collect()
+    }
+
+    /// What [`WorkflowRun::display_status`] returned before the change.
+    #[must_use]
+    pub fn previous_display_status(&self) -> Option<&'static str> {
+        match (self.previous_status?, self.previous_conclusion) {
+            (RunStatus::Completed, Some(conclusion)) => Some(conclusion.as_str()),
+            (status, _) => Some(status.as_str()),
+        }
+    }
+
+    /// Whether the run has just ended in a state that needs attention.
+    #[must_use]
+    pub fn is_failure(&self) -> bool {
+        self.run.is_problem()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::workflow_run;
+
+    #[test]
+    fn test_status_and_conclusion_changes_are_detected() {
+        let previous = vec![
+            workflow_run("owner/repo", 1, "in_progress"),
+            workflow_run("owner/repo", 2, "success"),
+        ];
+        let current = vec![
+            workflow_run("owner/repo", 1, "failure"),
+            workflow_run("owner/repo", 2, "success"),
+        ];
+
+        let transitions = RunTransition::detect(&previous, &current);
+
+        assert_eq!(transitions.len(), 1);
+        assert_eq!(transitions[0].run.id, previous[0].id);
+        assert_eq!(transitions[0].previous_status, Some(RunStatus::InProgress));
+        assert!(transitions[0].is_failure());
+    }
+
+    #[test]
+    fn test_new_runs_count_only_for_repositories_already_shown() {
+        let previous = vec![workflow_run("owner/repo", 1, "success")];
+        let current = vec![
+            workflow_run("owner/repo", 2, "failure"),
+            workflow_run("owner/other", 3, "failure"),
+            workflow_run("owner/repo", 1, "success"),
+        ];
+
+        let transitions = RunTransition::detect(&previous, &current);
+
+        assert_eq!(transitions.len(), 1);
+        assert_eq!(transitions[0].run.id, current[0].id);
+        assert_eq!(transitions[0].previous_status, None);
+    }
+}
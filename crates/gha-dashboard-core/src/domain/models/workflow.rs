@@ -0,0 +1,88 @@
+use super::ids::WorkflowId;
+use serde::{Deserialize, Serialize};
+
+/// Whether a workflow can run, as reported in GitHub's `state` field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkflowState {
+    /// Runs when triggered
+    Active,
+    /// The workflow file was deleted
+    Deleted,
+    /// Disabled because the repository is a fork
+    DisabledFork,
+    /// Disabled by GitHub after 60 days without repository activity
+    DisabledInactivity,
+    /// Disabled by hand
+    DisabledManually,
+    /// A value GitHub added after this enum was written
+    #[serde(other)]
+    Unknown,
+}
+
+/// A workflow defined in a repository.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Workflow {
+    /// ID of the workflow within GitHub
+    pub id: WorkflowId,
+    /// Name of the workflow, or its path when it has none
+    pub name: String,
+    /// Path of the workflow file, e.g. `.github/workflows/ci.yml`
+    pub path: String,
+    /// Whether the workflow runs when triggered
+    pub state: WorkflowState,
+    /// Page of the workflow file on GitHub
+    #[serde(rename = "htmlUrl")]
+    pub html_url: String,
+    /// URL of GitHub's own status badge for the workflow
+    #[serde(rename = "badgeUrl")]
+    pub badge_url: String,
+}
+
+impl Workflow {
+    /// Whether the workflow runs on its triggers.
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        self.state == WorkflowState::Active
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_workflow_states_deserialize() -> anyhow::Result<()> {
+        for (value, state) in [
+            ("active", WorkflowState::Active),
+            ("deleted", WorkflowState::Deleted),
+            ("disabled_fork", WorkflowState::DisabledFork),
+            ("disabled_inactivity", WorkflowState::DisabledInactivity),
+            ("disabled_manually", WorkflowState::DisabledManually),
+            ("paused", WorkflowState::Unknown),
+        ] {
+            assert_eq!(
+                serde_json::from_value::<WorkflowState>(serde_json::json!(value))?,
+                state
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_only_active_workflows_are_active() {
+        let mut workflow = Workflow {
+            id: WorkflowId(1),
+            name: "CI".to_string(),
+            path: ".github/workflows/ci.yml".to_string(),
+            state: WorkflowState::Active,
+            html_url: "https://github.com/owner/repo/blob/main/.github/workflows/ci.yml"
+                .to_string(),
+            badge_url: "https://github.com/owner/repo/workflows/CI/badge.svg".to_string(),
+        };
+        assert!(workflow.is_active());
+
+        workflow.state = WorkflowState::DisabledInactivity;
+        assert!(!workflow.is_active());
+    }
+}
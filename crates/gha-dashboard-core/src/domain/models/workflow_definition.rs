@@ -0,0 +1,75 @@
+use super::dispatch::{InvalidWorkflowFile, WorkflowDispatchInputs};
+use super::yaml::{self, Yaml};
+use serde::{Deserialize, Serialize};
+
+/// The triggers of a workflow file the dashboard shows.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkflowDefinition {
+    /// Inputs of the `workflow_dispatch` trigger
+    pub dispatch: WorkflowDispatchInputs,
+    /// Cron expressions of the `schedule` trigger as written, checked only when the next run
+    /// is predicted so that one invalid expression does not hide the rest of the file
+    pub schedules: Vec<String>,
+}
+
+impl WorkflowDefinition {
+    /// Reads the `workflow_dispatch` and `schedule` triggers of a workflow file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `source` is not YAML, declares an input GitHub would reject, or has
+    /// a `schedule` that is not a list of `cron` entries.
+    pub fn from_yaml(source: &str) -> Result<Self, InvalidWorkflowFile> {
+        let document = yaml::parse(source)?;
+        Ok(Self {
+            dispatch: WorkflowDispatchInputs::from_document(&document)?,
+            schedules: schedules(&document)?,
+        })
+    }
+}
+
+fn schedules(document: &Yaml) -> Result<Vec<String>, InvalidWorkflowFile> {
+    let invalid = || InvalidWorkflowFile("`schedule` must be a list of `cron` entries".to_string());
+    match document
+        .get("on")
+        .and_then(|triggers| triggers.get("schedule"))
+    {
+        None | Some(Yaml::Null) => Ok(Vec::new()),
+        Some(Yaml::Sequence(entries)) => entries
+            .iter()
+            .map(|entry| {
+                entry
+                    .get("cron")
+                    .and_then(Yaml::as_str)
+                    .map(str::to_string)
+                    .ok_or_else(invalid)
+            })
+            .collect(),
+        Some(_) => Err(invalid()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::DISPATCH_WORKFLOW;
+
+    #[test]
+    fn test_schedules_are_read_next_to_the_dispatch_inputs() -> anyhow::Result<()> {
+        let definition = WorkflowDefinition::from_yaml(DISPATCH_WORKFLOW)?;
+
+        assert_eq!(definition.dispatch.inputs.len(), 4);
+        assert_eq!(definition.schedules, ["30 5 * * 1-5", "0 */6 * * *"]);
+        assert_eq!(
+            WorkflowDefinition::from_yaml("on: [push, schedule]\n")?.schedules,
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            WorkflowDefinition::from_yaml("on:\n  schedule: '0 0 * * *'\n"),
+            Err(InvalidWorkflowFile(
+                "`schedule` must be a list of `cron` entries".to_string()
+            ))
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,151 @@
+use super::glob::GlobPattern;
+use super::workflow::Workflow;
+use serde::Serialize;
+
+/// Which workflows' runs are shown, by name.
+///
+/// A workflow matching `ignore` is hidden even when it also matches `only`; otherwise a
+/// non-empty `only` hides every workflow it does not match.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct WorkflowFilter {
+    /// Workflows whose runs are hidden
+    #[serde(rename = "ignoreWorkflows")]
+    pub ignore: Vec<GlobPattern>,
+    /// Workflows whose runs are shown, when not empty
+    #[serde(rename = "onlyWorkflows")]
+    pub only: Vec<GlobPattern>,
+}
+
+impl WorkflowFilter {
+    /// Whether the filter lets every workflow through.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.ignore.is_empty() && self.only.is_empty()
+    }
+
+    /// Whether runs of the workflow called `name` are shown.
+    #[must_use]
+    pub fn allows(&self, name: &str) -> bool {
+        !self.ignore.iter().any(|pattern| pattern.matches(name))
+            && (self.only.is_empty() || self.only.iter().any(|pattern| pattern.matches(name)))
+    }
+
+    /// `self` refined by the filter of one repository: its ignore patterns are added, and its
+    /// `only` patterns, if any, replace these.
+    #[must_use]
+    pub fn refined_by(&self, repository: &Self) -> Self {
+        Self {
+            ignore: self
+                .ignore
+                .iter()
+                .chain(&repository.ignore)
+                .cloned()
+                .collect(),
+            only: if repository.only.is_empty() {
+                self.only.clone()
+            } else {
+                repository.only.clone()
+            },
+        }
+    }
+
+    /// The one workflow among `workflows` that can have shown runs, when `only` is a single
+    /// pattern that resolves to exactly one of them.
+    #[must_use]
+    pub fn single_workflow<'a>(&self, workflows: &'a [Workflow]) -> Option<&'a Workflow> {
+        let [pattern] = self.only.as_slice() else {
+            return None;
+        };
+        let mut matching = workflows
+            .iter()
+            .filter(|workflow| pattern.matches(&workflow.name));
+        match (matching.next(), matching.next()) {
+            (Some(workflow), None) if self.allows(&workflow.name) => Some(workflow),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::workflow::WorkflowState;
+    use crate::test_support::workflow;
+
+    fn patterns(values: &[&str]) -> anyhow::Result<Vec<GlobPattern>> {
+        Ok(values
+            .iter()
+            .map(|value| value.parse())
+            .collect::<Result<_, _>>()?)
+    }
+
+    #[test]
+    fn test_ignore_takes_precedence_over_only() -> anyhow::Result<()> {
+        let filter = WorkflowFilter {
+            ignore: patterns(&["stale*"])?,
+            only: patterns(&["s*", "CI"])?,
+        };
+
+        assert!(!filter.allows("stale-bot"));
+        assert!(filter.allows("security"));
+        assert!(filter.allows("CI"));
+        assert!(!filter.allows("Deploy"));
+        assert!(WorkflowFilter::default().allows("anything"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_repository_filter_adds_ignores_and_replaces_only() -> anyhow::Result<()> {
+        let global = WorkflowFilter {
+            ignore: patterns(&["stale*"])?,
+            only: patterns(&["CI"])?,
+        };
+        let repository = WorkflowFilter {
+            ignore: patterns(&["Release"])?,
+            only: patterns(&["Release", "Deploy"])?,
+        };
+
+        let refined = global.refined_by(&repository);
+
+        assert!(!refined.allows("stale-bot"));
+        assert!(!refined.allows("Release"));
+        assert!(refined.allows("Deploy"));
+        assert!(!refined.allows("CI"));
+        assert_eq!(global.refined_by(&WorkflowFilter::default()), global);
+        Ok(())
+    }
+
+    #[test]
+    fn test_single_workflow_resolves_one_match_only() -> anyhow::Result<()> {
+        let workflows: Vec<Workflow> = ["CI", "Deploy staging", "Deploy production"]
+            .into_iter()
+            .zip(1..)
+            .map(|(name, id)| Workflow {
+                name: name.to_string(),
+                ..workflow(id, WorkflowState::Active)
+            })
+            .collect();
+        let only = |values: &[&str]| -> anyhow::Result<WorkflowFilter> {
+            Ok(WorkflowFilter {
+                ignore: Vec::new(),
+                only: patterns(values)?,
+            })
+        };
+
+        assert_eq!(
+            only(&["C*"])?
+                .single_workflow(&workflows)
+                .map(|workflow| workflow.name.as_str()),
+            Some("CI")
+        );
+        assert_eq!(only(&["Deploy*"])?.single_workflow(&workflows), None);
+        assert_eq!(only(&["CI", "Lint"])?.single_workflow(&workflows), None);
+        assert_eq!(only(&["Lint"])?.single_workflow(&workflows), None);
+        let ignored = WorkflowFilter {
+            ignore: patterns(&["CI"])?,
+            ..only(&["CI"])?
+        };
+        assert_eq!(ignored.single_workflow(&workflows), None);
+        Ok(())
+    }
+}
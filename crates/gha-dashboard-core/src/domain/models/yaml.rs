@@ -0,0 +1,554 @@
+/// A node of a YAML document, as far as workflow files need.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Yaml {
+    /// `~`, `null` or nothing at all
+    Null,
+    /// A scalar as written; quoted scalars are never read as booleans or numbers
+    Scalar {
+        value: String,
+        quoted: bool,
+    },
+    Sequence(Vec<Yaml>),
+    /// Entries in the order they are written
+    Mapping(Vec<(String, Yaml)>),
+}
+
+impl Yaml {
+    /// The value of `key` when `self` is a mapping.
+    pub fn get(&self, key: &str) -> Option<&Self> {
+        match self {
+            Self::Mapping(entries) => entries
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::Scalar { value, .. } => Some(value),
+            _ => None,
+        }
+    }
+
+    fn plain(value: &str) -> Self {
+        match value {
+            "" | "~" | "null" | "Null" | "NULL" => Self::Null,
+            value => Self::Scalar {
+                value: value.to_string(),
+                quoted: false,
+            },
+        }
+    }
+}
+
+/// Why a document could not be read.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("line {line}: {message}")]
+pub struct YamlError {
+    pub line: usize,
+    pub message: String,
+}
+
+/// Reads the block mappings and sequences, flow collections, quoted scalars and block
+/// scalars workflow files are written with.
+///
+/// Scalars and flow collections may continue on the following lines indented deeper than
+/// their key or sequence indicator, and are folded into one line. Anchors and tags are not
+/// supported.
+pub fn parse(source: &str) -> Result<Yaml, YamlError> {
+    let mut parser = Parser {
+        lines: source.lines().collect(),
+        pos: 0,
+        pending: None,
+    };
+    let document = match parser.peek()? {
+        Some((indent, _)) => parser.block(indent)?,
+        None => Yaml::Null,
+    };
+    if parser.peek()?.is_some() {
+        return Err(parser.error("unexpected content after the document"));
+    }
+    Ok(document)
+}
+
+struct Parser<'a> {
+    lines: Vec<&'a str>,
+    /// Index of the current line
+    pos: usize,
+    /// What is left of the current line after a sequence indicator, and its column
+    pending: Option<(usize, &'a str)>,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: impl Into<String>) -> YamlError {
+        YamlError {
+            line: self.pos + 1,
+            message: message.into(),
+        }
+    }
+
+    /// Indentation and content of the current line, skipping blank lines, comments and
+    /// document markers.
+    fn peek(&mut self) -> Result<Option<(usize, &'a str)>, YamlError> {
+        if let Some(pending) = self.pending {
+            return Ok(Some(pending));
+        }
+        while let Some(&line) = self.lines.get(self.pos) {
+            let content = line.trim_start_matches(' ');
+            if content.starts_with('\t') {
+                return Err(self.error("tabs are not allowed for indentation"));
+            }
+            let text = strip_comment(content).trim_end();
+            if text.is_empty() || text == "---" || text == "..." {
+                self.pos += 1;
+                continue;
+            }
+            return Ok(Some((line.len() - content.len(), text)));
+        }
+        Ok(None)
+    }
+
+    fn advance(&mut self) {
+        self.pending = None;
+        self.pos += 1;
+    }
+
+    /// The mapping or sequence starting at the current line, at `indent`.
+    fn block(&mut self, indent: usize) -> Result<Yaml, YamlError> {
+        match self.peek()? {
+            Some((_, text)) if is_sequence_item(text) => self.sequence(indent),
+            _ => self.mapping(indent),
+        }
+    }
+
+    fn sequence(&mut self, indent: usize) -> Result<Yaml, YamlError> {
+        let mut items = Vec::new();
+        while let Some((column, text)) = self.peek()? {
+            if column < indent || (column == indent && !is_sequence_item(text)) {
+                break;
+            }
+            if column > indent || !is_sequence_item(text) {
+                return Err(self.error("unexpected indentation"));
+            }
+            let rest = text[1..].trim_start_matches(' ');
+            if rest.is_empty() {
+                self.advance();
+                items.push(self.nested(indent)?);
+            } else if rest.starts_with(['[', '{']) || split_key(rest).is_none() {
+                items.push(self.flow_lines(indent, rest)?);
+            } else {
+                let column = column + text.len() - rest.len();
+                self.pending = Some((column, rest));
+                items.push(self.mapping(column)?);
+            }
+        }
+        Ok(Yaml::Sequence(items))
+    }
+
+    fn mapping(&mut self, indent: usize) -> Result<Yaml, YamlError> {
+        let mut entries: Vec<(String, Yaml)> = Vec::new();
+        while let Some((column, text)) = self.peek()? {
+            if column < indent {
+                break;
+            }
+            if column > indent {
+                return Err(self.error("unexpected indentation"));
+            }
+            let Some((key, value)) = split_key(text) else {
+                return Err(self.error(format!("expected `key: value`, found `{text}`")));
+            };
+            if entries.iter().any(|(name, _)| *name == key) {
+                return Err(self.error(format!("duplicate key `{key}`")));
+            }
+            let value = if value.is_empty() {
+                self.advance();
+                match self.peek()? {
+                    Some((column, text)) if column == indent && is_sequence_item(text) => {
+                        self.sequence(indent)?
+                    }
+                    _ => self.nested(indent)?,
+                }
+            } else if let Some(style) = block_scalar_style(value) {
+                self.advance();
+                self.block_scalar(indent, style)
+            } else {
+                self.flow_lines(indent, value)?
+            };
+            entries.push((key, value));
+        }
+        Ok(Yaml::Mapping(entries))
+    }
+
+    /// The block indented deeper than `indent` at the current line, if any.
+    fn nested(&mut self, indent: usize) -> Result<Yaml, YamlError> {
+        match self.peek()? {
+            Some((column, _)) if column > indent => self.block(column),
+            _ => Ok(Yaml::Null),
+        }
+    }
+
+    /// The lines of a `|` or `>` scalar under a key at `indent`, starting at the current line.
+    fn block_scalar(&mut self, indent: usize, (folded, chomping): (bool, Option<char>)) -> Yaml {
+        let mut content: Vec<&str> = Vec::new();
+        let mut block_indent = None;
+        while let Some(&line) = self.lines.get(self.pos) {
+            let column = line.len() - line.trim_start_matches(' ').len();
+            if line.trim().is_empty() {
+                content.push("");
+            } else {
+                let block_indent = *block_indent.get_or_insert(column);
+                if column <= indent || column < block_indent {
+                    break;
+                }
+                content.push(&line[block_indent..]);
+            }
+            self.pos += 1;
+        }
+        let trailing = content
+            .iter()
+            .rev()
+            .take_while(|line| line.is_empty())
+            .count();
+        content.truncate(content.len() - trailing);
+
+        let mut value = if folded {
+            content
+                .split(|line| line.is_empty())
+                .map(|paragraph| paragraph.join(" "))
+                .collect::<Vec<_>>()
+                .join("\n")
+        } else {
+            content.join("\n")
+        };
+        match chomping {
+            Some('-') => {}
+            Some(_) => value.push_str(&"\n".repeat(trailing + 1)),
+            None if !value.is_empty() => value.push('\n'),
+            None => {}
+        }
+        Yaml::Scalar {
+            value,
+            quoted: true,
+        }
+    }
+
+    /// The value starting with `first` on the current line, folded with the following lines
+    /// indented deeper than `indent`, such as a wrapped description or `[main,` then
+    /// `develop]`.
+    fn flow_lines(&mut self, indent: usize, first: &str) -> Result<Yaml, YamlError> {
+        let line = self.pos + 1;
+        let mut text = first.to_string();
+        self.advance();
+        while let Some((column, continued)) = self.peek()? {
+            if column <= indent {
+                break;
+            }
+            text.push(' ');
+            text.push_str(continued);
+            self.advance();
+        }
+        let error = |message| YamlError { line, message };
+        let mut flow = Flow { rest: &text };
+        let value = flow.value(false).map_err(error)?;
+        if !flow.rest.trim().is_empty() {
+            return Err(error(format!("unexpected `{}`", flow.rest.trim())));
+        }
+        Ok(value)
+    }
+}
+
+/// Cursor over a value written on one line: a scalar, `[a, b]` or `{a: b}`.
+struct Flow<'a> {
+    rest: &'a str,
+}
+
+impl Flow<'_> {
+    fn skip_spaces(&mut self) {
+        self.rest = self.rest.trim_start();
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_spaces();
+        self.rest.strip_prefix(c).is_some_and(|rest| {
+            self.rest = rest;
+            true
+        })
+    }
+
+    /// A value, ending at `,`, `]` or `}` when `in_collection`.
+    fn value(&mut self, in_collection: bool) -> Result<Yaml, String> {
+        self.skip_spaces();
+        if self.eat('[') {
+            let mut items = Vec::new();
+            while !self.eat(']') {
+                if self.rest.is_empty() {
+                    return Err("unterminated flow sequence".to_string());
+                }
+                items.push(self.value(true)?);
+                if !self.eat(',') && !self.rest.starts_with(']') {
+                    return Err(if self.rest.is_empty() {
+                        "unterminated flow sequence".to_string()
+                    } else {
+                        format!("expected `,` or `]`, found `{}`", self.rest)
+                    });
+                }
+            }
+            return Ok(Yaml::Sequence(items));
+        }
+        if self.eat('{') {
+            let mut entries = Vec::new();
+            while !self.eat('}') {
+                if self.rest.is_empty() {
+                    return Err("unterminated flow mapping".to_string());
+                }
+                let Yaml::Scalar { value: key, .. } = self.scalar(true, true)? else {
+                    return Err("expected a key".to_string());
+                };
+                let value = if self.eat(':') {
+                    self.value(true)?
+                } else {
+                    Yaml::Null
+                };
+                entries.push((key, value));
+                if !self.eat(',') && !self.rest.starts_with('}') {
+                    return Err(if self.rest.is_empty() {
+                        "unterminated flow mapping".to_string()
+                    } else {
+                        format!("expected `,` or `}}`, found `{}`", self.rest)
+                    });
+                }
+            }
+            return Ok(Yaml::Mapping(entries));
+        }
+        self.scalar(in_collection, false)
+    }
+
+    fn scalar(&mut self, in_collection: bool, is_key: bool) -> Result<Yaml, String> {
+        self.skip_spaces();
+        let Some(quote) = self.rest.chars().next().filter(|c| matches!(c, '"' | '\'')) else {
+            let end = self
+                .rest
+                .char_indices()
+                .find(|&(i, c)| {
+                    (in_collection && matches!(c, ',' | ']' | '}'))
+                        || (is_key && c == ':' && self.rest[i + 1..].starts_with([' ', ',', '}']))
+                        || (is_key && c == ':' && i + 1 == self.rest.len())
+                })
+                .map_or(self.rest.len(), |(i, _)| i);
+            let (value, rest) = self.rest.split_at(end);
+            self.rest = rest;
+            return Ok(Yaml::plain(value.trim_end()));
+        };
+        let (value, rest) = unquote(self.rest, quote)?;
+        self.rest = rest;
+        Ok(Yaml::Scalar {
+            value,
+            quoted: true,
+        })
+    }
+}
+
+/// The scalar `text` starts with, quoted with `quote`, and what follows it.
+fn unquote(text: &str, quote: char) -> Result<(String, &str), String> {
+    let mut value = String::new();
+    let mut chars = text.char_indices().skip(1);
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\'' if quote == '\'' => {
+                if text[i + 1..].starts_with('\'') {
+                    chars.next();
+                    value.push('\'');
+                } else {
+                    return Ok((value, &text[i + 1..]));
+                }
+            }
+            '"' if quote == '"' => return Ok((value, &text[i + 1..])),
+            '\\' if quote == '"' => match chars.next().map(|(_, c)| c) {
+                Some('n') => value.push('\n'),
+                Some('t') => value.push('\t'),
+                Some('0') => value.push('\0'),
+                Some(c) => value.push(c),
+                None => break,
+            },
+            c => value.push(c),
+        }
+    }
+    Err("unterminated quoted scalar".to_string())
+}
+
+fn is_sequence_item(text: &str) -> bool {
+    text == "-" || text.starts_with("- ")
+}
+
+/// The key of a `key: value` line and the value written after it.
+fn split_key(text: &str) -> Option<(String, &str)> {
+    let (key, rest) = match text.chars().next()? {
+        quote @ ('"' | '\'') => unquote(text, quote).ok()?,
+        '[' | '{' => return None,
+        _ => {
+            let end = text
+                .char_indices()
+                .find(|&(i, c)| {
+                    c == ':' && (i + 1 == text.len() || text[i + 1..].starts_with(' '))
+                })?
+                .0;
+            (text[..end].trim_end().to_string(), &text[end..])
+        }
+    };
+    let value = rest.trim_start().strip_prefix(':')?;
+    (value.is_empty() || value.starts_with(' ')).then(|| (key, value.trim_start()))
+}
+
+/// Whether `value` introduces a folded (`>`) or literal (`|`) block scalar, and its
+/// chomping indicator.
+fn block_scalar_style(value: &str) -> Option<(bool, Option<char>)> {
+    let mut chars = value.chars();
+    let folded = match chars.next()? {
+        '>' => true,
+        '|' => false,
+        _ => return None,
+    };
+    match (chars.next(), chars.next()) {
+        (None, _) => Some((folded, None)),
+        (Some(chomping @ ('-' | '+')), None) => Some((folded, Some(chomping))),
+        _ => None,
+    }
+}
+
+/// `text` up to a comment, i.e. a `#` outside quotes at its start or after a space.
+fn strip_comment(text: &str) -> &str {
+    let mut quote = None;
+    let mut previous = ' ';
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (None, '#') if previous == ' ' => return &text[..i],
+            (None, '"' | '\'') if matches!(previous, ' ' | '[' | '{' | ',' | ':' | '-') => {
+                quote = Some(c);
+            }
+            (Some(open), c) if c == open => quote = None,
+            _ => {}
+        }
+        previous = c;
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scalar(value: &str) -> Yaml {
+        Yaml::plain(value)
+    }
+
+    #[test]
+    fn test_block_and_flow_collections_are_read() -> anyhow::Result<()> {
+        let document = parse(
+            "\
+---
+# A comment
+name: CI  # trailing comment
+on:
+  push:
+    branches: [main, 'release/*']
+  pull_request:
+jobs:
+  build:
+    steps:
+      - uses: actions/checkout@v4
+        with: {fetch-depth: 0}
+      - run: |
+          cargo build
+          cargo test
+      - \"quoted: item\"
+",
+        )?;
+
+        assert_eq!(document.get("name"), Some(&scalar("CI")));
+        let on = document.get("on").ok_or_else(|| anyhow::anyhow!("no on"))?;
+        assert_eq!(
+            on.get("push").and_then(|push| push.get("branches")),
+            Some(&Yaml::Sequence(vec![
+                scalar("main"),
+                Yaml::Scalar {
+                    value: "release/*".to_string(),
+                    quoted: true
+                }
+            ]))
+        );
+        assert_eq!(on.get("pull_request"), Some(&Yaml::Null));
+        let steps = document
+            .get("jobs")
+            .and_then(|jobs| jobs.get("build"))
+            .and_then(|build| build.get("steps"));
+        let Some(Yaml::Sequence(steps)) = steps else {
+            anyhow::bail!("steps are not a sequence: {steps:?}");
+        };
+        assert_eq!(steps.len(), 3);
+        assert_eq!(
+            steps[0]
+                .get("with")
+                .and_then(|with| with.get("fetch-depth")),
+            Some(&scalar("0"))
+        );
+        assert_eq!(
+            steps[1].get("run").and_then(Yaml::as_str),
+            Some("cargo build\ncargo test\n")
+        );
+        assert_eq!(steps[2].as_str(), Some("quoted: item"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_values_continue_on_deeper_lines() -> anyhow::Result<()> {
+        let document = parse(
+            "\
+description: Deploys the app
+  to every region
+branches: [main,
+  'release/*']
+steps:
+  - run: cargo build
+      --release
+  - a plain
+    item
+",
+        )?;
+
+        assert_eq!(
+            document.get("description"),
+            Some(&scalar("Deploys the app to every region"))
+        );
+        let Some(Yaml::Sequence(branches)) = document.get("branches") else {
+            anyhow::bail!("branches are not a sequence");
+        };
+        assert_eq!(branches.len(), 2);
+        let Some(Yaml::Sequence(steps)) = document.get("steps") else {
+            anyhow::bail!("steps are not a sequence");
+        };
+        assert_eq!(steps[0].get("run"), Some(&scalar("cargo build --release")));
+        assert_eq!(steps[1], scalar("a plain item"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_errors_name_their_line() {
+        assert_eq!(
+            parse("on:\n  push:\n\tbranches: [main]\n"),
+            Err(YamlError {
+                line: 3,
+                message: "tabs are not allowed for indentation".to_string()
+            })
+        );
+        assert_eq!(
+            parse("on: [push\n").map_err(|e| e.to_string()),
+            Err("line 1: unterminated flow sequence".to_string())
+        );
+        assert_eq!(
+            parse("a: 1\na: 2\n").map_err(|e| e.to_string()),
+            Err("line 2: duplicate key `a`".to_string())
+        );
+    }
+}
@@ -0,0 +1,16 @@
+/// Storage of the repositories seen when listing them.
+pub mod discovered_repository_repository;
+/// Storage of the pseudonyms of anonymized repositories.
+pub mod pseudonym_repository;
+/// Storage of browser subscriptions to Web Push notifications.
+pub mod push_subscription_repository;
+/// Storage of past runs and their daily stats.
+pub mod run_repository;
+
+pub use discovered_repository_repository::DiscoveredRepositoryRepository;
+pub use pseudonym_repository::PseudonymRepository;
+pub use push_subscription_repository::PushSubscriptionRepository;
+pub use run_repository::{
+    CompactionReport, DailyStats, HistoricalSnapshot, RetentionPolicy, RunCursor, RunQuery,
+    RunRepository, RunRepositoryError, SnapshotRange, SnapshotStatusCounts, StoredSnapshot,
+};
@@ -0,0 +1,23 @@
+use super::run_repository::RunRepositoryError;
+use crate::domain::models::ids::RepoFullName;
+use async_trait::async_trait;
+
+/// Storage of the repositories seen when listing them, so repositories created while the
+/// server was down are still reported as new after a restart.
+#[async_trait]
+pub trait DiscoveredRepositoryRepository {
+    /// Every repository seen so far.
+    async fn discovered_repositories(&self) -> Result<Vec<RepoFullName>, RunRepositoryError>;
+
+    /// Records that `repository` was seen; recording it again does nothing.
+    async fn save_discovered_repository(
+        &self,
+        repository: &RepoFullName,
+    ) -> Result<(), RunRepositoryError>;
+
+    /// Forgets `repository`, e.g. after it was deleted or renamed.
+    async fn remove_discovered_repository(
+        &self,
+        repository: &RepoFullName,
+    ) -> Result<(), RunRepositoryError>;
+}
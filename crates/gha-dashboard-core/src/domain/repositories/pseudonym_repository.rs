@@ -0,0 +1,20 @@
+use super::run_repository::RunRepositoryError;
+use crate::domain::models::ids::RepoFullName;
+use async_trait::async_trait;
+
+/// Numbers standing in for repository names in anonymized deployments, stored so a public
+/// demo does not shuffle the names on restart.
+#[async_trait]
+pub trait PseudonymRepository {
+    /// Every stored repository with its number, in the order they were numbered.
+    async fn pseudonyms(&self) -> Result<Vec<(RepoFullName, u64)>, RunRepositoryError>;
+
+    /// Records that `repository` goes by `number`.
+    ///
+    /// A repository keeps the number it was stored with first.
+    async fn save_pseudonym(
+        &self,
+        repository: &RepoFullName,
+        number: u64,
+    ) -> Result<(), RunRepositoryError>;
+}
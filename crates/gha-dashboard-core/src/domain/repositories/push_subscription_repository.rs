@@ -0,0 +1,22 @@
+use super::run_repository::RunRepositoryError;
+use crate::domain::models::push_subscription::PushSubscription;
+use async_trait::async_trait;
+
+/// Browser subscriptions to Web Push notifications, kept next to the run history so they
+/// outlive restarts.
+#[async_trait]
+pub trait PushSubscriptionRepository {
+    /// Stores `subscription`, replacing the one with the same endpoint if any.
+    async fn save_subscription(
+        &self,
+        subscription: &PushSubscription,
+    ) -> Result<(), RunRepositoryError>;
+
+    /// Every stored subscription, oldest first.
+    async fn subscriptions(&self) -> Result<Vec<PushSubscription>, RunRepositoryError>;
+
+    /// Forgets the subscription of `endpoint`, e.g. once its push service reports it expired.
+    ///
+    /// Returns whether one was stored.
+    async fn delete_subscription(&self, endpoint: &str) -> Result<bool, RunRepositoryError>;
+}
@@ -0,0 +1,369 @@
+use crate::domain::models::ids::{RepoFullName, RunId, WorkflowId};
+use crate::domain::models::run::WorkflowRun;
+use crate::domain::models::status::{RunConclusion, RunStatus};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
+
+/// Why reading or writing stored runs failed.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Run store error: {message}")]
+pub struct RunRepositoryError {
+    /// What the store reported
+    pub message: String,
+}
+
+/// Filters for [`RunRepository::query_runs`]; `None` matches everything.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunQuery {
+    /// Matched ignoring ASCII case, as GitHub does
+    pub repository: Option<RepoFullName>,
+    /// Runs created at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Runs created before this time
+    pub until: Option<DateTime<Utc>>,
+    /// Only runs in this state
+    pub status: Option<RunStatus>,
+    /// Only runs with this conclusion
+    pub conclusion: Option<RunConclusion>,
+    /// Only runs of the workflow with this name
+    pub workflow: Option<String>,
+    /// Only runs started by the account with this login, matched ignoring ASCII case
+    pub actor: Option<String>,
+    /// Only runs of this head branch
+    pub branch: Option<String>,
+    /// Only runs whose title contains this text, matched ignoring ASCII case
+    pub text: Option<String>,
+    /// Only runs older than this position, to continue where the previous page ended
+    pub before: Option<RunCursor>,
+    /// Only runs with one of these IDs; empty matches every run
+    pub ids: Vec<RunId>,
+    /// Most runs to return
+    pub limit: usize,
+}
+
+impl RunQuery {
+    /// Whether `run` passes every filter, for runs held in memory rather than in a store.
+    #[must_use]
+    pub fn matches(&self, run: &WorkflowRun) -> bool {
+        let contains = |title: &str, text: &str| {
+            title
+                .to_ascii_lowercase()
+                .contains(&text.to_ascii_lowercase())
+        };
+        self.repository
+            .as_ref()
+            .is_none_or(|repository| run.repository_name.eq_ignore_ascii_case(repository))
+            && self.since.is_none_or(|since| run.created_at >= since)
+            && self.until.is_none_or(|until| run.created_at < until)
+            && self.status.is_none_or(|status| run.status == status)
+            && self
+                .conclusion
+                .is_none_or(|conclusion| run.conclusion == Some(conclusion))
+            && self
+                .workflow
+                .as_ref()
+                .is_none_or(|workflow| run.workflow_name == *workflow)
+            && self
+                .actor
+                .as_ref()
+                .is_none_or(|actor| run.actor.login.eq_ignore_ascii_case(actor))
+            && self
+                .branch
+                .as_ref()
+                .is_none_or(|branch| run.head_branch.as_ref() == Some(branch))
+            && self.text.as_ref().is_none_or(|text| {
+                contains(&run.display_title, text)
+                    || run
+                        .full_display_title
+                        .as_ref()
+                        .is_some_and(|title| contains(title, text))
+            })
+            && self.before.is_none_or(|before| RunCursor::of(run) < before)
+            && (self.ids.is_empty() || self.ids.contains(&run.id))
+    }
+}
+
+impl Default for RunQuery {
+    fn default() -> Self {
+        Self {
+            repository: None,
+            since: None,
+            until: None,
+            status: None,
+            conclusion: None,
+            workflow: None,
+            actor: None,
+            branch: None,
+            text: None,
+            before: None,
+            ids: Vec::new(),
+            limit: 100,
+        }
+    }
+}
+
+/// Position of a run in the newest-first order of [`RunRepository::query_runs`], which
+/// sorts by creation time and then by id.
+///
+/// Written as `<creation time in epoch milliseconds>-<id>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct RunCursor {
+    /// When the run was created
+    pub created_at: DateTime<Utc>,
+    /// ID of the run
+    pub id: RunId,
+}
+
+impl RunCursor {
+    /// The position of `run`.
+    #[must_use]
+    pub fn of(run: &WorkflowRun) -> Self {
+        Self {
+            created_at: run.created_at,
+            id: run.id,
+        }
+    }
+}
+
+impl fmt::Display for RunCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.created_at.timestamp_millis(), self.id)
+    }
+}
+
+impl FromStr for RunCursor {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid cursor {value:?}");
+        let (millis, id) = value.split_once('-').ok_or_else(invalid)?;
+        Ok(Self {
+            created_at: millis
+                .parse()
+                .ok()
+                .and_then(DateTime::from_timestamp_millis)
+                .ok_or_else(invalid)?,
+            id: id.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// The runs of the most recently saved snapshot, in their original order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredSnapshot {
+    /// Runs of the snapshot
+    pub runs: Vec<WorkflowRun>,
+    /// When the snapshot was taken
+    pub generated_at: DateTime<Utc>,
+}
+
+/// A snapshot saved by [`RunRepository::save_runs`], with its runs as they were then.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoricalSnapshot {
+    /// Position of the snapshot among every one saved, increasing with each
+    pub sequence: u64,
+    /// When the snapshot was taken
+    pub generated_at: DateTime<Utc>,
+    /// Runs of the snapshot, in their original order
+    pub runs: Vec<WorkflowRun>,
+}
+
+/// How many runs of a saved snapshot were in each state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotStatusCounts {
+    /// Position of the snapshot among every one saved
+    pub sequence: u64,
+    /// When the snapshot was taken
+    pub generated_at: DateTime<Utc>,
+    /// Runs per display status, a state such as `queued` or the conclusion of completed runs
+    pub counts: BTreeMap<String, u64>,
+}
+
+/// When the oldest and newest snapshots still stored were taken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnapshotRange {
+    /// When the oldest snapshot kept was taken
+    pub oldest: DateTime<Utc>,
+    /// When the latest snapshot was taken
+    pub newest: DateTime<Utc>,
+}
+
+/// Which stored runs and snapshots [`RunRepository::compact`] deletes.
+///
+/// The latest run of each repository and the latest snapshot are always kept, however old.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RetentionPolicy {
+    /// Runs created and snapshots taken longer ago than this are deleted
+    pub max_age: Option<TimeDelta>,
+    /// Only this many of the newest runs, and of the newest snapshots, are kept
+    pub max_rows: Option<usize>,
+}
+
+/// Outcome of a [`RunRepository::compact`] call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct CompactionReport {
+    /// Runs removed from the store
+    pub deleted: u64,
+    /// Runs left in the store
+    pub remaining: u64,
+}
+
+/// The completed runs of one workflow created on one UTC day.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DailyStats {
+    /// Day the runs were created, in UTC
+    pub day: NaiveDate,
+    /// Workflow the runs belong to
+    pub workflow_id: WorkflowId,
+    /// Name of the workflow in its latest run of the day
+    pub workflow_name: String,
+    /// Number of runs
+    pub runs: u64,
+    /// How many of the runs ended with each conclusion
+    pub conclusions: HashMap<RunConclusion, u64>,
+    /// Share of the runs that succeeded, from 0 to 1
+    pub success_rate: f64,
+    /// Mean time from creation to completion of the completed runs
+    pub mean_duration_seconds: f64,
+}
+
+/// Persistent history of the runs seen by the poller.
+#[async_trait]
+pub trait RunRepository {
+    /// Stores the runs of a snapshot generated at `generated_at`, and the state each of them
+    /// was in for [`RunRepository::snapshot_at`].
+    ///
+    /// A run already stored is replaced unless the stored copy was updated later.
+    async fn save_runs(
+        &self,
+        runs: &[WorkflowRun],
+        generated_at: DateTime<Utc>,
+    ) -> Result<(), RunRepositoryError>;
+
+    /// Stored runs matching `query`, newest first.
+    async fn query_runs(&self, query: &RunQuery) -> Result<Vec<WorkflowRun>, RunRepositoryError>;
+
+    /// The snapshot of the last [`RunRepository::save_runs`] call, if any.
+    async fn latest_snapshot(&self) -> Result<Option<StoredSnapshot>, RunRepositoryError>;
+
+    /// The latest snapshot taken at or before `at`, with the state each run was in then.
+    ///
+    /// Runs deleted since are left out; their other fields are as last stored.
+    async fn snapshot_at(
+        &self,
+        at: DateTime<Utc>,
+    ) -> Result<Option<HistoricalSnapshot>, RunRepositoryError>;
+
+    /// When the oldest and newest stored snapshots were taken; `None` before the first.
+    async fn snapshot_range(&self) -> Result<Option<SnapshotRange>, RunRepositoryError>;
+
+    /// The status counts of the latest snapshot of each `step` long interval from `from`
+    /// until `to`, oldest first; intervals without snapshots are skipped.
+    async fn snapshot_series(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        step: TimeDelta,
+    ) -> Result<Vec<SnapshotStatusCounts>, RunRepositoryError>;
+
+    /// Deletes the runs and snapshots `policy` no longer retains at `now` and reclaims their
+    /// space.
+    async fn compact(
+        &self,
+        policy: &RetentionPolicy,
+        now: DateTime<Utc>,
+    ) -> Result<CompactionReport, RunRepositoryError>;
+
+    /// Recomputes the [`DailyStats`] of every day from `since` on out of the stored runs.
+    ///
+    /// Earlier days are left as they are, so their stats outlive the runs compacted away.
+    async fn aggregate_daily_stats(&self, since: NaiveDate) -> Result<(), RunRepositoryError>;
+
+    /// The stats of `repository` from `since` on, oldest day first.
+    async fn daily_stats(
+        &self,
+        repository: &RepoFullName,
+        since: NaiveDate,
+    ) -> Result<Vec<DailyStats>, RunRepositoryError>;
+
+    /// The repositories with stats from `since` on, in name order.
+    async fn stats_repositories(
+        &self,
+        since: NaiveDate,
+    ) -> Result<Vec<RepoFullName>, RunRepositoryError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::base_time;
+    use crate::testing::WorkflowRunBuilder;
+    use chrono::TimeDelta;
+
+    #[test]
+    fn test_cursor_round_trips_and_rejects_garbage() {
+        let cursor = RunCursor {
+            created_at: base_time(),
+            id: RunId(42),
+        };
+
+        assert_eq!(cursor.to_string().parse::<RunCursor>(), Ok(cursor));
+        for value in ["", "42", "x-42", "1700000000000-", "1700000000000--1"] {
+            assert!(value.parse::<RunCursor>().is_err(), "{value:?} accepted");
+        }
+    }
+
+    #[test]
+    fn test_matches_applies_every_filter() -> anyhow::Result<()> {
+        let mut run = WorkflowRunBuilder::new("owner/repo")
+            .id(2)
+            .created_at(base_time())
+            .branch(Some("main"))
+            .build();
+        run.display_title = "Fix flaky Login test".to_string();
+        let matching = RunQuery {
+            repository: Some("Owner/Repo".parse()?),
+            workflow: Some(run.workflow_name.clone()),
+            actor: Some(run.actor.login.to_ascii_uppercase()),
+            branch: Some("main".to_string()),
+            text: Some("login".to_string()),
+            since: Some(base_time()),
+            ..RunQuery::default()
+        };
+        assert!(matching.matches(&run));
+
+        for query in [
+            RunQuery {
+                text: Some("logout".to_string()),
+                ..matching.clone()
+            },
+            RunQuery {
+                branch: Some("develop".to_string()),
+                ..matching.clone()
+            },
+            RunQuery {
+                until: Some(base_time()),
+                ..matching.clone()
+            },
+            RunQuery {
+                before: Some(RunCursor::of(&run)),
+                ..matching.clone()
+            },
+        ] {
+            assert!(!query.matches(&run), "{query:?}");
+        }
+        let next_page = RunQuery {
+            before: Some(RunCursor {
+                created_at: base_time() + TimeDelta::seconds(1),
+                id: RunId(1),
+            }),
+            ..matching
+        };
+        assert!(next_page.matches(&run));
+        Ok(())
+    }
+}
@@ -0,0 +1,18 @@
+//! Domain models, ports and use cases of the GitHub Actions dashboard, without any HTTP
+//! server.
+//!
+//! The `schema` feature, on by default, derives the JSON Schema of the snapshot types.
+#![deny(missing_docs)]
+
+/// Services and use cases built on the domain, independent of any transport.
+pub mod application;
+/// Models of GitHub Actions and the ports the dashboard reaches them through.
+pub mod domain;
+/// Builders and a scripted fake `GitHubApi` for code built on this crate.
+#[cfg(any(test, feature = "test-util"))]
+pub mod testing;
+
+/// Fixtures shared by the tests of this crate and of the dashboard.
+#[cfg(any(test, feature = "test-util"))]
+#[doc(hidden)]
+pub mod test_support;
@@ -0,0 +1,377 @@
+use crate::domain::external_apis::github::{GitHubApi, GitHubApiError, RateLimit, Repository};
+use crate::domain::models::commit_status::CommitStatus;
+use crate::domain::models::deployment::{Deployment, DeploymentStatus};
+use crate::domain::models::ids::{DeploymentId, JobId, RepoFullName, RunId, WorkflowId};
+use crate::domain::models::job::Job;
+use crate::domain::models::run::WorkflowRun;
+use crate::domain::models::runner::SelfHostedRunner;
+use crate::domain::models::status::{RunConclusion, RunStatus};
+use crate::domain::models::timing::RunTiming;
+use crate::domain::models::workflow::{Workflow, WorkflowState};
+use crate::domain::models::workflow_definition::WorkflowDefinition;
+pub use crate::testing::run_actor;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+
+/// A `workflow_run` webhook delivery as sent by GitHub when octo-org/octo-repo's run 30433642 failed.
+pub const WORKFLOW_RUN_EVENT: &str = include_str!("fixtures/workflow_run_event.json");
+
+/// A `workflow_job` webhook delivery as sent by GitHub when job 21400001 of that run failed.
+pub const WORKFLOW_JOB_EVENT: &str = include_str!("fixtures/workflow_job_event.json");
+
+/// A deploy workflow that can be dispatched with choice, boolean, number and string inputs.
+pub const DISPATCH_WORKFLOW: &str = include_str!("fixtures/dispatch_workflow.yml");
+
+/// A release workflow whose description, branch list and build command wrap onto more lines.
+pub const WRAPPED_WORKFLOW: &str = include_str!("fixtures/wrapped_workflow.yml");
+
+/// Fixed point in time used as the base of fixture timestamps.
+#[must_use]
+pub fn base_time() -> DateTime<Utc> {
+    DateTime::from_timestamp(1_722_506_400, 0).unwrap_or_default() // 2024-08-01T10:00:00Z
+}
+
+/// Parses a fixture `owner/name`.
+///
+/// # Panics
+///
+/// Panics if `full_name` is malformed, which is a bug in the test.
+#[must_use]
+pub fn repo_full_name(full_name: &str) -> RepoFullName {
+    match full_name.parse() {
+        Ok(full_name) => full_name,
+        Err(e) => panic!("{e}"),
+    }
+}
+
+/// Builds a workflow run of `repository_name` created `id` minutes after `base_time()`.
+///
+/// `display_status` is either a conclusion, which makes the run completed, or a status.
+#[must_use]
+pub fn workflow_run(repository_name: &str, id: u64, display_status: &str) -> WorkflowRun {
+    let created_at = base_time() + Duration::minutes(i64::try_from(id).unwrap_or_default());
+    let (status, conclusion) = match display_status.parse() {
+        Ok(RunConclusion::Unknown) | Err(_) => {
+            (display_status.parse().unwrap_or(RunStatus::Unknown), None)
+        }
+        Ok(conclusion) => (RunStatus::Completed, Some(conclusion)),
+    };
+    WorkflowRun {
+        repository_name: repo_full_name(repository_name),
+        id: RunId(id),
+        run_number: id,
+        workflow_id: WorkflowId(1),
+        workflow_name: "CI".to_string(),
+        display_title: "Fix bug".to_string(),
+        full_display_title: None,
+        event: "push".to_string(),
+        head_branch: Some("main".to_string()),
+        head_sha: format!("{id:040x}"),
+        actor: run_actor("octocat"),
+        triggering_actor: Some(run_actor("octocat")),
+        status,
+        conclusion,
+        created_at,
+        updated_at: created_at,
+        html_url: format!("https://github.com/{repository_name}/actions/runs/{id}"),
+        labels: Vec::new(),
+        repository_active: true,
+        is_required_check: None,
+        failed_jobs: Vec::new(),
+        failed_job_url: None,
+    }
+}
+
+/// Builds the workflow `id` of `owner/repo`, named after its id.
+#[must_use]
+pub fn workflow(id: u64, state: WorkflowState) -> Workflow {
+    Workflow {
+        id: WorkflowId(id),
+        name: format!("Workflow {id}"),
+        path: format!(".github/workflows/workflow-{id}.yml"),
+        state,
+        html_url: format!(
+            "https://github.com/owner/repo/blob/main/.github/workflows/workflow-{id}.yml"
+        ),
+        badge_url: format!(
+            "https://github.com/owner/repo/actions/workflows/workflow-{id}.yml/badge.svg"
+        ),
+    }
+}
+
+/// `GitHubApi` returning fixed data, for tests that don't care about the network.
+#[derive(Default)]
+pub struct StubGitHubApi {
+    pub repositories: Vec<Repository>,
+    pub runs: Vec<WorkflowRun>,
+    /// Runs only `fetch_workflow_run` returns, as if too old to be among the latest ones
+    pub older_runs: Vec<WorkflowRun>,
+    /// Jobs returned for any run; `None` makes `fetch_workflow_jobs` fail with `NotFound`
+    pub jobs: Option<Vec<Job>>,
+    /// Log of any job; `None` makes `fetch_job_logs` fail with `NotFound`
+    pub job_logs: Option<String>,
+    /// Workflows returned for any repository
+    pub workflows: Vec<Workflow>,
+    /// Timing returned for any run; `None` makes `fetch_run_timing` fail with `NotFound`
+    pub timing: Option<RunTiming>,
+    /// Self-hosted runners returned for any repository
+    pub runners: Vec<SelfHostedRunner>,
+    /// Deployments returned for any repository
+    pub deployments: Vec<Deployment>,
+    /// Statuses returned for any deployment
+    pub deployment_statuses: Vec<DeploymentStatus>,
+    /// Commit statuses of any commit
+    pub commit_statuses: Vec<CommitStatus>,
+    /// Required checks of any branch; `None` makes `fetch_branch_protection_required_checks`
+    /// fail with `NotFound`, as for unprotected branches
+    pub required_checks: Option<Vec<String>>,
+    /// Definition of any workflow file; `None` makes `fetch_workflow_definition` fail with
+    /// `NotFound`
+    pub workflow_definition: Option<WorkflowDefinition>,
+    pub rate_limit: RateLimit,
+    /// Returned by every call instead of the data above when set
+    pub error: Option<GitHubApiError>,
+    /// How long every call takes before it returns
+    pub delay: std::time::Duration,
+}
+
+impl StubGitHubApi {
+    #[must_use]
+    pub fn with_repository(owner: &str, name: &str) -> Self {
+        Self {
+            repositories: vec![
+                Repository::from_full_name(&repo_full_name(&format!("{owner}/{name}")))
+                    .with_owner_avatar_url(Some(format!(
+                        "https://avatars.githubusercontent.com/{owner}"
+                    ))),
+            ],
+            ..Self::default()
+        }
+    }
+
+    async fn fail(&self) -> Result<(), GitHubApiError> {
+        if !self.delay.is_zero() {
+            tokio::time::sleep(self.delay).await;
+        }
+        self.error.clone().map_or(Ok(()), Err)
+    }
+}
+
+#[async_trait]
+impl GitHubApi for StubGitHubApi {
+    async fn fetch_repositories(&self, _count: u8) -> Result<Vec<Repository>, GitHubApiError> {
+        self.fail().await?;
+        Ok(self.repositories.clone())
+    }
+
+    async fn fetch_repository(&self, repo: &RepoFullName) -> Result<Repository, GitHubApiError> {
+        self.fail().await?;
+        self.repositories
+            .iter()
+            .find(|repository| repository.full_name() == repo)
+            .cloned()
+            .ok_or_else(|| GitHubApiError::NotFound {
+                resource: format!("repository {repo}"),
+            })
+    }
+
+    async fn fetch_workflow_runs(
+        &self,
+        repo: &RepoFullName,
+        _count: u8,
+    ) -> Result<Vec<WorkflowRun>, GitHubApiError> {
+        self.fail().await?;
+        Ok(self
+            .runs
+            .iter()
+            .filter(|run| run.repository_name == *repo)
+            .cloned()
+            .collect())
+    }
+
+    async fn fetch_workflow_runs_of_workflow(
+        &self,
+        repo: &RepoFullName,
+        workflow_id: WorkflowId,
+        _count: u8,
+    ) -> Result<Vec<WorkflowRun>, GitHubApiError> {
+        self.fail().await?;
+        Ok(self
+            .runs
+            .iter()
+            .filter(|run| run.repository_name == *repo && run.workflow_id == workflow_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn fetch_workflow_runs_by_status(
+        &self,
+        repo: &RepoFullName,
+        status: RunStatus,
+        _count: u8,
+    ) -> Result<Vec<WorkflowRun>, GitHubApiError> {
+        self.fail().await?;
+        Ok(self
+            .runs
+            .iter()
+            .filter(|run| run.repository_name == *repo && run.status == status)
+            .cloned()
+            .collect())
+    }
+
+    async fn fetch_workflow_runs_on_branch(
+        &self,
+        repo: &RepoFullName,
+        branch: &str,
+        _count: u8,
+    ) -> Result<Vec<WorkflowRun>, GitHubApiError> {
+        self.fail().await?;
+        Ok(self
+            .runs
+            .iter()
+            .filter(|run| {
+                run.repository_name == *repo && run.head_branch.as_deref() == Some(branch)
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn fetch_workflow_run(
+        &self,
+        repo: &RepoFullName,
+        run_id: RunId,
+    ) -> Result<WorkflowRun, GitHubApiError> {
+        self.fail().await?;
+        self.runs
+            .iter()
+            .chain(&self.older_runs)
+            .find(|run| run.repository_name == *repo && run.id == run_id)
+            .cloned()
+            .ok_or_else(|| GitHubApiError::NotFound {
+                resource: format!("run {run_id} of {repo}"),
+            })
+    }
+
+    async fn fetch_workflow_jobs(
+        &self,
+        repo: &RepoFullName,
+        run_id: RunId,
+    ) -> Result<Vec<Job>, GitHubApiError> {
+        self.fail().await?;
+        self.jobs.clone().ok_or_else(|| GitHubApiError::NotFound {
+            resource: format!("jobs for {repo} run {run_id}"),
+        })
+    }
+
+    async fn fetch_workflow_job(
+        &self,
+        repo: &RepoFullName,
+        job_id: JobId,
+    ) -> Result<Job, GitHubApiError> {
+        self.fail().await?;
+        self.jobs
+            .iter()
+            .flatten()
+            .find(|job| job.id == job_id)
+            .cloned()
+            .ok_or_else(|| GitHubApiError::NotFound {
+                resource: format!("job {job_id} of {repo}"),
+            })
+    }
+
+    async fn fetch_job_logs(
+        &self,
+        repo: &RepoFullName,
+        job_id: JobId,
+    ) -> Result<String, GitHubApiError> {
+        self.fail().await?;
+        self.job_logs
+            .clone()
+            .ok_or_else(|| GitHubApiError::NotFound {
+                resource: format!("logs of job {job_id} of {repo}"),
+            })
+    }
+
+    async fn fetch_workflows(&self, _repo: &RepoFullName) -> Result<Vec<Workflow>, GitHubApiError> {
+        self.fail().await?;
+        Ok(self.workflows.clone())
+    }
+
+    async fn fetch_run_timing(
+        &self,
+        repo: &RepoFullName,
+        run_id: RunId,
+    ) -> Result<RunTiming, GitHubApiError> {
+        self.fail().await?;
+        self.timing.clone().ok_or_else(|| GitHubApiError::NotFound {
+            resource: format!("timing of {repo} run {run_id}"),
+        })
+    }
+
+    async fn fetch_runners(
+        &self,
+        _repo: &RepoFullName,
+    ) -> Result<Vec<SelfHostedRunner>, GitHubApiError> {
+        self.fail().await?;
+        Ok(self.runners.clone())
+    }
+
+    async fn fetch_deployments(
+        &self,
+        _repo: &RepoFullName,
+        _environment: Option<&str>,
+    ) -> Result<Vec<Deployment>, GitHubApiError> {
+        self.fail().await?;
+        Ok(self.deployments.clone())
+    }
+
+    async fn fetch_deployment_statuses(
+        &self,
+        _repo: &RepoFullName,
+        _deployment_id: DeploymentId,
+    ) -> Result<Vec<DeploymentStatus>, GitHubApiError> {
+        self.fail().await?;
+        Ok(self.deployment_statuses.clone())
+    }
+
+    async fn fetch_combined_status(
+        &self,
+        _repo: &RepoFullName,
+        _sha: &str,
+    ) -> Result<Vec<CommitStatus>, GitHubApiError> {
+        self.fail().await?;
+        Ok(self.commit_statuses.clone())
+    }
+
+    async fn fetch_branch_protection_required_checks(
+        &self,
+        repo: &RepoFullName,
+        branch: &str,
+    ) -> Result<Vec<String>, GitHubApiError> {
+        self.fail().await?;
+        self.required_checks
+            .clone()
+            .ok_or_else(|| GitHubApiError::NotFound {
+                resource: format!("required checks of {repo} branch {branch}"),
+            })
+    }
+
+    async fn fetch_workflow_definition(
+        &self,
+        repo: &RepoFullName,
+        path: &str,
+        _git_ref: Option<&str>,
+    ) -> Result<WorkflowDefinition, GitHubApiError> {
+        self.fail().await?;
+        self.workflow_definition
+            .clone()
+            .ok_or_else(|| GitHubApiError::NotFound {
+                resource: format!("{path} of {repo}"),
+            })
+    }
+
+    async fn fetch_rate_limit(&self) -> Result<RateLimit, GitHubApiError> {
+        self.fail().await?;
+        Ok(self.rate_limit.clone())
+    }
+}
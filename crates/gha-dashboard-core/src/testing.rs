@@ -0,0 +1,7 @@
+/// Builders of domain models.
+pub mod builders;
+/// A fake `GitHubApi` answering with scripted responses.
+pub mod scripted;
+
+pub use builders::{WorkflowRunBuilder, run_actor};
+pub use scripted::{Scripted, ScriptedGitHubApi};
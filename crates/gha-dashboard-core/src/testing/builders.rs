@@ -0,0 +1,183 @@
+use crate::domain::models::actor::RunActor;
+use crate::domain::models::ids::{RepoFullName, RunId, WorkflowId};
+use crate::domain::models::run::WorkflowRun;
+use crate::domain::models::status::{RunConclusion, RunStatus};
+use chrono::{DateTime, TimeDelta, Utc};
+
+/// Builds the actor `login` with GitHub-style profile URLs.
+#[must_use]
+pub fn run_actor(login: &str) -> RunActor {
+    RunActor {
+        login: login.to_string(),
+        avatar_url: format!("https://avatars.githubusercontent.com/{login}"),
+        html_url: format!("https://github.com/{login}"),
+    }
+}
+
+/// Builds a [`WorkflowRun`], by default run 1 of a `CI` workflow pushed to `main` by
+/// `octocat` that succeeded just now.
+///
+/// ```
+/// # use gha_dashboard_core::testing::WorkflowRunBuilder;
+/// # use gha_dashboard_core::domain::models::status::RunConclusion;
+/// let run = WorkflowRunBuilder::new("owner/repo")
+///     .conclusion(RunConclusion::Failure)
+///     .created_minutes_ago(5)
+///     .build();
+/// assert!(run.is_problem());
+/// ```
+#[derive(Debug, Clone)]
+pub struct WorkflowRunBuilder {
+    run: WorkflowRun,
+}
+
+impl WorkflowRunBuilder {
+    /// Starts a run of `repository`, an `owner/name`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `repository` is not a valid `owner/name`, which is a bug in the test.
+    #[must_use]
+    pub fn new(repository: &str) -> Self {
+        let repository_name: RepoFullName = match repository.parse() {
+            Ok(repository_name) => repository_name,
+            Err(e) => panic!("{e}"),
+        };
+        let now = Utc::now();
+        Self {
+            run: WorkflowRun {
+                html_url: format!("https://github.com/{repository_name}/actions/runs/1"),
+                repository_name,
+                id: RunId(1),
+                run_number: 1,
+                workflow_id: WorkflowId(1),
+                workflow_name: "CI".to_string(),
+                display_title: "Fix bug".to_string(),
+                full_display_title: None,
+                event: "push".to_string(),
+                head_branch: Some("main".to_string()),
+                head_sha: format!("{:040x}", 1),
+                actor: run_actor("octocat"),
+                triggering_actor: Some(run_actor("octocat")),
+                status: RunStatus::Completed,
+                conclusion: Some(RunConclusion::Success),
+                created_at: now,
+                updated_at: now,
+                labels: Vec::new(),
+                repository_active: true,
+                is_required_check: None,
+                failed_jobs: Vec::new(),
+                failed_job_url: None,
+            },
+        }
+    }
+
+    /// Sets the id, and the run number and head SHA derived from it.
+    #[must_use]
+    pub fn id(mut self, id: u64) -> Self {
+        self.run.id = RunId(id);
+        self.run.run_number = id;
+        self.run.head_sha = format!("{id:040x}");
+        self.run.html_url = format!(
+            "https://github.com/{}/actions/runs/{id}",
+            self.run.repository_name
+        );
+        self
+    }
+
+    /// Sets the number of the run within its workflow.
+    #[must_use]
+    pub fn run_number(mut self, run_number: u64) -> Self {
+        self.run.run_number = run_number;
+        self
+    }
+
+    /// Sets the workflow the run belongs to.
+    #[must_use]
+    pub fn workflow(mut self, id: u64, name: &str) -> Self {
+        self.run.workflow_id = WorkflowId(id);
+        self.run.workflow_name = name.to_string();
+        self
+    }
+
+    /// Sets the title of the run.
+    #[must_use]
+    pub fn title(mut self, title: &str) -> Self {
+        self.run.display_title = title.to_string();
+        self
+    }
+
+    /// Sets the event that triggered the run, e.g. `pull_request`.
+    #[must_use]
+    pub fn event(mut self, event: &str) -> Self {
+        self.run.event = event.to_string();
+        self
+    }
+
+    /// Sets the head branch; `None` for events without one.
+    #[must_use]
+    pub fn branch(mut self, branch: Option<&str>) -> Self {
+        self.run.head_branch = branch.map(str::to_string);
+        self
+    }
+
+    /// Sets the account that started the run and its latest attempt.
+    #[must_use]
+    pub fn actor(mut self, login: &str) -> Self {
+        self.run.actor = run_actor(login);
+        self.run.triggering_actor = Some(run_actor(login));
+        self
+    }
+
+    /// Sets the account that started the latest attempt, as for a re-run.
+    #[must_use]
+    pub fn triggering_actor(mut self, login: &str) -> Self {
+        self.run.triggering_actor = Some(run_actor(login));
+        self
+    }
+
+    /// Sets a status; the conclusion is cleared unless the run is completed.
+    #[must_use]
+    pub fn status(mut self, status: RunStatus) -> Self {
+        self.run.status = status;
+        if status != RunStatus::Completed {
+            self.run.conclusion = None;
+        }
+        self
+    }
+
+    /// Completes the run with `conclusion`.
+    #[must_use]
+    pub fn conclusion(mut self, conclusion: RunConclusion) -> Self {
+        self.run.status = RunStatus::Completed;
+        self.run.conclusion = Some(conclusion);
+        self
+    }
+
+    /// Sets when the run was created and last updated.
+    #[must_use]
+    pub fn created_at(mut self, created_at: DateTime<Utc>) -> Self {
+        self.run.created_at = created_at;
+        self.run.updated_at = created_at;
+        self
+    }
+
+    /// Sets the creation time to `minutes` before now.
+    #[must_use]
+    pub fn created_minutes_ago(self, minutes: i64) -> Self {
+        self.created_at(Utc::now() - TimeDelta::minutes(minutes))
+    }
+
+    /// Sets when the run last changed.
+    #[must_use]
+    pub fn updated_at(mut self, updated_at: DateTime<Utc>) -> Self {
+        self.run.updated_at = updated_at;
+        self
+    }
+
+    /// The built run.
+    #[must_use]
+    pub fn build(self) -> WorkflowRun {
+        self.run
+    }
+}
@@ -0,0 +1,687 @@
+use crate::domain::external_apis::github::{GitHubApi, GitHubApiError, RateLimit, Repository};
+use crate::domain::models::commit_status::CommitStatus;
+use crate::domain::models::deployment::{Deployment, DeploymentStatus};
+use crate::domain::models::ids::{DeploymentId, JobId, RepoFullName, RunId, WorkflowId};
+use crate::domain::models::job::Job;
+use crate::domain::models::run::WorkflowRun;
+use crate::domain::models::runner::SelfHostedRunner;
+use crate::domain::models::status::RunStatus;
+use crate::domain::models::timing::RunTiming;
+use crate::domain::models::workflow::Workflow;
+use crate::domain::models::workflow_definition::WorkflowDefinition;
+use async_trait::async_trait;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+/// One response of a [`ScriptedGitHubApi`], given after an optional delay.
+#[derive(Debug, Clone)]
+pub struct Scripted<T> {
+    response: Result<T, GitHubApiError>,
+    delay: Duration,
+}
+
+impl<T> Scripted<T> {
+    /// Answers after `delay`, e.g. to hold a call in flight while the test acts.
+    #[must_use]
+    pub fn after(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+impl<T> From<Result<T, GitHubApiError>> for Scripted<T> {
+    fn from(response: Result<T, GitHubApiError>) -> Self {
+        Self {
+            response,
+            delay: Duration::ZERO,
+        }
+    }
+}
+
+impl<T> From<GitHubApiError> for Scripted<T> {
+    fn from(error: GitHubApiError) -> Self {
+        Err(error).into()
+    }
+}
+
+/// Queues of scripted responses, one per call target.
+#[derive(Default)]
+struct Script {
+    repositories: VecDeque<Scripted<Vec<Repository>>>,
+    repository: HashMap<RepoFullName, VecDeque<Scripted<Repository>>>,
+    workflow_runs: HashMap<RepoFullName, VecDeque<Scripted<Vec<WorkflowRun>>>>,
+    runs_of_workflow: HashMap<(RepoFullName, WorkflowId), VecDeque<Scripted<Vec<WorkflowRun>>>>,
+    runs_by_status: HashMap<(RepoFullName, RunStatus), VecDeque<Scripted<Vec<WorkflowRun>>>>,
+    runs_on_branch: HashMap<(RepoFullName, String), VecDeque<Scripted<Vec<WorkflowRun>>>>,
+    workflow_run: HashMap<RunId, VecDeque<Scripted<WorkflowRun>>>,
+    workflow_jobs: HashMap<RunId, VecDeque<Scripted<Vec<Job>>>>,
+    workflow_job: HashMap<JobId, VecDeque<Scripted<Job>>>,
+    job_logs: HashMap<JobId, VecDeque<Scripted<String>>>,
+    workflows: HashMap<RepoFullName, VecDeque<Scripted<Vec<Workflow>>>>,
+    run_timing: HashMap<RunId, VecDeque<Scripted<RunTiming>>>,
+    runners: HashMap<RepoFullName, VecDeque<Scripted<Vec<SelfHostedRunner>>>>,
+    deployments: HashMap<RepoFullName, VecDeque<Scripted<Vec<Deployment>>>>,
+    deployment_statuses:
+        HashMap<(RepoFullName, DeploymentId), VecDeque<Scripted<Vec<DeploymentStatus>>>>,
+    combined_statuses: HashMap<(RepoFullName, String), VecDeque<Scripted<Vec<CommitStatus>>>>,
+    required_checks: HashMap<(RepoFullName, String), VecDeque<Scripted<Vec<String>>>>,
+    workflow_definitions: HashMap<(RepoFullName, String), VecDeque<Scripted<WorkflowDefinition>>>,
+    rate_limit: VecDeque<Scripted<RateLimit>>,
+}
+
+/// `GitHubApi` answering each call with the next response queued for it.
+///
+/// Responses are queued per repository or run, and each answers one call, except the last
+/// one, which keeps answering until more are queued. Calls with nothing queued fail, so a
+/// test notices calls it did not expect.
+#[derive(Default)]
+pub struct ScriptedGitHubApi {
+    script: Mutex<Script>,
+    calls: Mutex<Vec<String>>,
+}
+
+/// Takes the next response of `queue`, keeping the last one for later calls.
+fn next<T: Clone>(queue: Option<&mut VecDeque<Scripted<T>>>, call: &str) -> Scripted<T> {
+    let scripted = match queue {
+        Some(queue) if queue.len() > 1 => queue.pop_front(),
+        Some(queue) => queue.front().cloned(),
+        None => None,
+    };
+    scripted.unwrap_or_else(|| {
+        GitHubApiError::Other {
+            resource: call.to_string(),
+            message: "No scripted response".to_string(),
+        }
+        .into()
+    })
+}
+
+impl ScriptedGitHubApi {
+    /// A client with nothing queued yet, failing every call.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn script(&self) -> std::sync::MutexGuard<'_, Script> {
+        self.script.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Queues the response to the next call listing repositories.
+    pub fn repositories(&self, response: impl Into<Scripted<Vec<Repository>>>) -> &Self {
+        self.script().repositories.push_back(response.into());
+        self
+    }
+
+    /// Queues the response to the next call fetching `repo`.
+    pub fn repository(
+        &self,
+        repo: &RepoFullName,
+        response: impl Into<Scripted<Repository>>,
+    ) -> &Self {
+        self.script()
+            .repository
+            .entry(repo.clone())
+            .or_default()
+            .push_back(response.into());
+        self
+    }
+
+    /// Queues the response to the next call listing the runs of `repo`.
+    pub fn workflow_runs(
+        &self,
+        repo: &RepoFullName,
+        response: impl Into<Scripted<Vec<WorkflowRun>>>,
+    ) -> &Self {
+        self.script()
+            .workflow_runs
+            .entry(repo.clone())
+            .or_default()
+            .push_back(response.into());
+        self
+    }
+
+    /// Queues the response to the next call listing the runs of one workflow of `repo`.
+    pub fn workflow_runs_of_workflow(
+        &self,
+        repo: &RepoFullName,
+        workflow_id: WorkflowId,
+        response: impl Into<Scripted<Vec<WorkflowRun>>>,
+    ) -> &Self {
+        self.script()
+            .runs_of_workflow
+            .entry((repo.clone(), workflow_id))
+            .or_default()
+            .push_back(response.into());
+        self
+    }
+
+    /// Queues the response to the next call listing the runs of `repo` in `status`.
+    pub fn workflow_runs_by_status(
+        &self,
+        repo: &RepoFullName,
+        status: RunStatus,
+        response: impl Into<Scripted<Vec<WorkflowRun>>>,
+    ) -> &Self {
+        self.script()
+            .runs_by_status
+            .entry((repo.clone(), status))
+            .or_default()
+            .push_back(response.into());
+        self
+    }
+
+    /// Queues the response to the next call listing the runs of `repo` on `branch`.
+    pub fn workflow_runs_on_branch(
+        &self,
+        repo: &RepoFullName,
+        branch: &str,
+        response: impl Into<Scripted<Vec<WorkflowRun>>>,
+    ) -> &Self {
+        self.script()
+            .runs_on_branch
+            .entry((repo.clone(), branch.to_string()))
+            .or_default()
+            .push_back(response.into());
+        self
+    }
+
+    /// Queues the response to the next call looking up run `run_id`.
+    pub fn workflow_run(&self, run_id: RunId, response: impl Into<Scripted<WorkflowRun>>) -> &Self {
+        self.script()
+            .workflow_run
+            .entry(run_id)
+            .or_default()
+            .push_back(response.into());
+        self
+    }
+
+    /// Queues the response to the next call listing the jobs of `run_id`.
+    pub fn workflow_jobs(&self, run_id: RunId, response: impl Into<Scripted<Vec<Job>>>) -> &Self {
+        self.script()
+            .workflow_jobs
+            .entry(run_id)
+            .or_default()
+            .push_back(response.into());
+        self
+    }
+
+    /// Queues the response to the next call looking up job `job_id`.
+    pub fn workflow_job(&self, job_id: JobId, response: impl Into<Scripted<Job>>) -> &Self {
+        self.script()
+            .workflow_job
+            .entry(job_id)
+            .or_default()
+            .push_back(response.into());
+        self
+    }
+
+    /// Queues the response to the next call downloading the log of job `job_id`.
+    pub fn job_logs(&self, job_id: JobId, response: impl Into<Scripted<String>>) -> &Self {
+        self.script()
+            .job_logs
+            .entry(job_id)
+            .or_default()
+            .push_back(response.into());
+        self
+    }
+
+    /// Queues the response to the next call listing the workflows of `repo`.
+    pub fn workflows(
+        &self,
+        repo: &RepoFullName,
+        response: impl Into<Scripted<Vec<Workflow>>>,
+    ) -> &Self {
+        self.script()
+            .workflows
+            .entry(repo.clone())
+            .or_default()
+            .push_back(response.into());
+        self
+    }
+
+    /// Queues the response to the next call asking for the timing of `run_id`.
+    pub fn run_timing(&self, run_id: RunId, response: impl Into<Scripted<RunTiming>>) -> &Self {
+        self.script()
+            .run_timing
+            .entry(run_id)
+            .or_default()
+            .push_back(response.into());
+        self
+    }
+
+    /// Queues the response to the next call listing the self-hosted runners of `repo`.
+    pub fn runners(
+        &self,
+        repo: &RepoFullName,
+        response: impl Into<Scripted<Vec<SelfHostedRunner>>>,
+    ) -> &Self {
+        self.script()
+            .runners
+            .entry(repo.clone())
+            .or_default()
+            .push_back(response.into());
+        self
+    }
+
+    /// Queues the response to the next call listing the deployments of `repo`.
+    pub fn deployments(
+        &self,
+        repo: &RepoFullName,
+        response: impl Into<Scripted<Vec<Deployment>>>,
+    ) -> &Self {
+        self.script()
+            .deployments
+            .entry(repo.clone())
+            .or_default()
+            .push_back(response.into());
+        self
+    }
+
+    /// Queues the response to the next call listing the statuses of deployment `deployment_id`
+    /// of `repo`.
+    pub fn deployment_statuses(
+        &self,
+        repo: &RepoFullName,
+        deployment_id: DeploymentId,
+        response: impl Into<Scripted<Vec<DeploymentStatus>>>,
+    ) -> &Self {
+        self.script()
+            .deployment_statuses
+            .entry((repo.clone(), deployment_id))
+            .or_default()
+            .push_back(response.into());
+        self
+    }
+
+    /// Queues the response to the next call listing the commit statuses of `sha` of `repo`.
+    pub fn combined_status(
+        &self,
+        repo: &RepoFullName,
+        sha: &str,
+        response: impl Into<Scripted<Vec<CommitStatus>>>,
+    ) -> &Self {
+        self.script()
+            .combined_statuses
+            .entry((repo.clone(), sha.to_string()))
+            .or_default()
+            .push_back(response.into());
+        self
+    }
+
+    /// Queues the response to the next call listing the required checks of `branch` of `repo`.
+    pub fn required_checks(
+        &self,
+        repo: &RepoFullName,
+        branch: &str,
+        response: impl Into<Scripted<Vec<String>>>,
+    ) -> &Self {
+        self.script()
+            .required_checks
+            .entry((repo.clone(), branch.to_string()))
+            .or_default()
+            .push_back(response.into());
+        self
+    }
+
+    /// Queues the response to the next call reading the workflow file at `path` of `repo`.
+    pub fn workflow_definition(
+        &self,
+        repo: &RepoFullName,
+        path: &str,
+        response: impl Into<Scripted<WorkflowDefinition>>,
+    ) -> &Self {
+        self.script()
+            .workflow_definitions
+            .entry((repo.clone(), path.to_string()))
+            .or_default()
+            .push_back(response.into());
+        self
+    }
+
+    /// Queues the response to the next rate limit check.
+    pub fn rate_limit(&self, response: impl Into<Scripted<RateLimit>>) -> &Self {
+        self.script().rate_limit.push_back(response.into());
+        self
+    }
+
+    /// Calls received so far, e.g. `fetch_workflow_runs owner/repo 2`.
+    #[must_use]
+    pub fn calls(&self) -> Vec<String> {
+        self.calls
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    async fn answer<T>(
+        &self,
+        call: String,
+        take: impl FnOnce(&mut Script) -> Scripted<T>,
+    ) -> Result<T, GitHubApiError> {
+        let scripted = take(&mut self.script());
+        self.calls
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(call);
+        if !scripted.delay.is_zero() {
+            tokio::time::sleep(scripted.delay).await;
+        }
+        scripted.response
+    }
+}
+
+#[async_trait]
+impl GitHubApi for ScriptedGitHubApi {
+    async fn fetch_repositories(&self, count: u8) -> Result<Vec<Repository>, GitHubApiError> {
+        let call = format!("fetch_repositories {count}");
+        self.answer(call.clone(), |script| {
+            next(Some(&mut script.repositories), &call)
+        })
+        .await
+    }
+
+    async fn fetch_repository(&self, repo: &RepoFullName) -> Result<Repository, GitHubApiError> {
+        let call = format!("fetch_repository {repo}");
+        self.answer(call.clone(), |script| {
+            next(script.repository.get_mut(repo), &call)
+        })
+        .await
+    }
+
+    async fn fetch_workflow_runs(
+        &self,
+        repo: &RepoFullName,
+        count: u8,
+    ) -> Result<Vec<WorkflowRun>, GitHubApiError> {
+        let call = format!("fetch_workflow_runs {repo} {count}");
+        self.answer(call.clone(), |script| {
+            next(script.workflow_runs.get_mut(repo), &call)
+        })
+        .await
+    }
+
+    async fn fetch_workflow_runs_of_workflow(
+        &self,
+        repo: &RepoFullName,
+        workflow_id: WorkflowId,
+        count: u8,
+    ) -> Result<Vec<WorkflowRun>, GitHubApiError> {
+        let call = format!("fetch_workflow_runs_of_workflow {repo} {workflow_id} {count}");
+        self.answer(call.clone(), |script| {
+            next(
+                script
+                    .runs_of_workflow
+                    .get_mut(&(repo.clone(), workflow_id)),
+                &call,
+            )
+        })
+        .await
+    }
+
+    async fn fetch_workflow_runs_by_status(
+        &self,
+        repo: &RepoFullName,
+        status: RunStatus,
+        count: u8,
+    ) -> Result<Vec<WorkflowRun>, GitHubApiError> {
+        let call = format!(
+            "fetch_workflow_runs_by_status {repo} {} {count}",
+            status.as_str()
+        );
+        self.answer(call.clone(), |script| {
+            next(
+                script.runs_by_status.get_mut(&(repo.clone(), status)),
+                &call,
+            )
+        })
+        .await
+    }
+
+    async fn fetch_workflow_runs_on_branch(
+        &self,
+        repo: &RepoFullName,
+        branch: &str,
+        count: u8,
+    ) -> Result<Vec<WorkflowRun>, GitHubApiError> {
+        let call = format!("fetch_workflow_runs_on_branch {repo} {branch} {count}");
+        self.answer(call.clone(), |script| {
+            next(
+                script
+                    .runs_on_branch
+                    .get_mut(&(repo.clone(), branch.to_string())),
+                &call,
+            )
+        })
+        .await
+    }
+
+    async fn fetch_workflow_run(
+        &self,
+        repo: &RepoFullName,
+        run_id: RunId,
+    ) -> Result<WorkflowRun, GitHubApiError> {
+        let call = format!("fetch_workflow_run {repo} {run_id}");
+        self.answer(call.clone(), |script| {
+            next(script.workflow_run.get_mut(&run_id), &call)
+        })
+        .await
+    }
+
+    async fn fetch_workflow_jobs(
+        &self,
+        repo: &RepoFullName,
+        run_id: RunId,
+    ) -> Result<Vec<Job>, GitHubApiError> {
+        let call = format!("fetch_workflow_jobs {repo} {run_id}");
+        self.answer(call.clone(), |script| {
+            next(script.workflow_jobs.get_mut(&run_id), &call)
+        })
+        .await
+    }
+
+    async fn fetch_workflow_job(
+        &self,
+        repo: &RepoFullName,
+        job_id: JobId,
+    ) -> Result<Job, GitHubApiError> {
+        let call = format!("fetch_workflow_job {repo} {job_id}");
+        self.answer(call.clone(), |script| {
+            next(script.workflow_job.get_mut(&job_id), &call)
+        })
+        .await
+    }
+
+    async fn fetch_job_logs(
+        &self,
+        repo: &RepoFullName,
+        job_id: JobId,
+    ) -> Result<String, GitHubApiError> {
+        let call = format!("fetch_job_logs {repo} {job_id}");
+        self.answer(call.clone(), |script| {
+            next(script.job_logs.get_mut(&job_id), &call)
+        })
+        .await
+    }
+
+    async fn fetch_workflows(&self, repo: &RepoFullName) -> Result<Vec<Workflow>, GitHubApiError> {
+        let call = format!("fetch_workflows {repo}");
+        self.answer(call.clone(), |script| {
+            next(script.workflows.get_mut(repo), &call)
+        })
+        .await
+    }
+
+    async fn fetch_run_timing(
+        &self,
+        repo: &RepoFullName,
+        run_id: RunId,
+    ) -> Result<RunTiming, GitHubApiError> {
+        let call = format!("fetch_run_timing {repo} {run_id}");
+        self.answer(call.clone(), |script| {
+            next(script.run_timing.get_mut(&run_id), &call)
+        })
+        .await
+    }
+
+    async fn fetch_runners(
+        &self,
+        repo: &RepoFullName,
+    ) -> Result<Vec<SelfHostedRunner>, GitHubApiError> {
+        let call = format!("fetch_runners {repo}");
+        self.answer(call.clone(), |script| {
+            next(script.runners.get_mut(repo), &call)
+        })
+        .await
+    }
+
+    async fn fetch_deployments(
+        &self,
+        repo: &RepoFullName,
+        environment: Option<&str>,
+    ) -> Result<Vec<Deployment>, GitHubApiError> {
+        let call = match environment {
+            Some(environment) => format!("fetch_deployments {repo} {environment}"),
+            None => format!("fetch_deployments {repo}"),
+        };
+        self.answer(call.clone(), |script| {
+            next(script.deployments.get_mut(repo), &call)
+        })
+        .await
+    }
+
+    async fn fetch_deployment_statuses(
+        &self,
+        repo: &RepoFullName,
+        deployment_id: DeploymentId,
+    ) -> Result<Vec<DeploymentStatus>, GitHubApiError> {
+        let call = format!("fetch_deployment_statuses {repo} {deployment_id}");
+        self.answer(call.clone(), |script| {
+            next(
+                script
+                    .deployment_statuses
+                    .get_mut(&(repo.clone(), deployment_id)),
+                &call,
+            )
+        })
+        .await
+    }
+
+    async fn fetch_combined_status(
+        &self,
+        repo: &RepoFullName,
+        sha: &str,
+    ) -> Result<Vec<CommitStatus>, GitHubApiError> {
+        let call = format!("fetch_combined_status {repo} {sha}");
+        self.answer(call.clone(), |script| {
+            next(
+                script
+                    .combined_statuses
+                    .get_mut(&(repo.clone(), sha.to_string())),
+                &call,
+            )
+        })
+        .await
+    }
+
+    async fn fetch_branch_protection_required_checks(
+        &self,
+        repo: &RepoFullName,
+        branch: &str,
+    ) -> Result<Vec<String>, GitHubApiError> {
+        let call = format!("fetch_branch_protection_required_checks {repo} {branch}");
+        self.answer(call.clone(), |script| {
+            next(
+                script
+                    .required_checks
+                    .get_mut(&(repo.clone(), branch.to_string())),
+                &call,
+            )
+        })
+        .await
+    }
+
+    async fn fetch_workflow_definition(
+        &self,
+        repo: &RepoFullName,
+        path: &str,
+        _git_ref: Option<&str>,
+    ) -> Result<WorkflowDefinition, GitHubApiError> {
+        let call = format!("fetch_workflow_definition {repo} {path}");
+        self.answer(call.clone(), |script| {
+            next(
+                script
+                    .workflow_definitions
+                    .get_mut(&(repo.clone(), path.to_string())),
+                &call,
+            )
+        })
+        .await
+    }
+
+    async fn fetch_rate_limit(&self) -> Result<RateLimit, GitHubApiError> {
+        let call = "fetch_rate_limit".to_string();
+        self.answer(call.clone(), |script| {
+            next(Some(&mut script.rate_limit), &call)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::repo_full_name;
+    use crate::testing::WorkflowRunBuilder;
+
+    #[tokio::test]
+    async fn test_responses_are_served_in_order_and_the_last_one_repeats() -> anyhow::Result<()> {
+        let repo = repo_full_name("owner/repo");
+        let run = WorkflowRunBuilder::new("owner/repo").build();
+        let github_api = ScriptedGitHubApi::new();
+        github_api
+            .workflow_runs(&repo, Ok(vec![run.clone()]))
+            .workflow_runs(
+                &repo,
+                GitHubApiError::Network {
+                    resource: "runs".to_string(),
+                    message: "reset".to_string(),
+                },
+            );
+
+        assert_eq!(github_api.fetch_workflow_runs(&repo, 1).await?, vec![run]);
+        assert!(github_api.fetch_workflow_runs(&repo, 1).await.is_err());
+        assert!(github_api.fetch_workflow_runs(&repo, 1).await.is_err());
+        assert_eq!(github_api.calls().len(), 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unscripted_calls_fail() {
+        let github_api = ScriptedGitHubApi::new();
+        github_api.workflow_runs(&repo_full_name("owner/repo"), Ok(Vec::new()));
+
+        let result = github_api
+            .fetch_workflow_runs(&repo_full_name("owner/other"), 1)
+            .await;
+
+        assert!(matches!(result, Err(GitHubApiError::Other { .. })));
+        assert_eq!(
+            github_api.calls(),
+            vec!["fetch_workflow_runs owner/other 1"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delayed_responses_wait() -> anyhow::Result<()> {
+        let github_api = ScriptedGitHubApi::new();
+        github_api.repositories(Scripted::from(Ok(Vec::new())).after(Duration::from_millis(50)));
+        let started = std::time::Instant::now();
+
+        github_api.fetch_repositories(5).await?;
+
+        assert!(started.elapsed() >= Duration::from_millis(50));
+        Ok(())
+    }
+}
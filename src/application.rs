@@ -1 +0,0 @@
-pub mod use_cases;
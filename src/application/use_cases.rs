@@ -1,6 +0,0 @@
-pub mod stream_github_actions_runs;
-
-pub use stream_github_actions_runs::{
-    StreamGitHubActionsRunsInteractor, StreamGitHubActionsRunsUseCase,
-    StreamGitHubActionsRunsUseCaseInput, StreamGitHubActionsRunsUseCaseOutput,
-};
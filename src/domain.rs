@@ -1,2 +0,0 @@
-pub mod external_apis;
-pub mod models;
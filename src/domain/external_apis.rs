@@ -1,3 +0,0 @@
-pub mod github;
-
-pub use github::{GitHubApi, Repository};
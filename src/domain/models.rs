@@ -1,3 +0,0 @@
-pub mod run;
-
-pub use run::WorkflowRun;
@@ -1 +1,6 @@
 pub mod adapters;
+pub mod app;
+pub mod config;
+pub mod metrics;
+pub mod replication;
+pub mod telemetry;
@@ -1 +1,6 @@
+pub mod cli;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+#[cfg(feature = "tui")]
+pub mod tui;
 pub mod web;
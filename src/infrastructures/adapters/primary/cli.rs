@@ -0,0 +1,1071 @@
+pub mod exporter;
+pub mod preflight;
+pub mod reload;
+pub mod self_test;
+
+#[cfg(feature = "grpc")]
+use super::grpc::{self, RunsService};
+#[cfg(feature = "tui")]
+use super::tui;
+use super::web::presenter::anonymizer::Anonymizer;
+use super::web::presenter::snapshot_json;
+use super::web::server;
+use super::web::webhooks::GitHubWebhooks;
+use crate::application::services::{Notice, RepositoryDiscovery, RunNotifier, SharedPoller};
+use crate::application::use_cases::stream_github_actions_runs::config::StreamConfigPatch;
+use crate::application::use_cases::stream_github_actions_runs::{
+    StreamGitHubActionsRunsInteractor, StreamGitHubActionsRunsUseCase,
+    StreamGitHubActionsRunsUseCaseInput,
+};
+use crate::domain::external_apis::github::GitHubApi;
+use crate::domain::external_apis::notifications::NotificationPort;
+use crate::domain::models::ids::RepoFullName;
+use crate::domain::models::run::WorkflowRun;
+#[cfg(feature = "fixtures")]
+use crate::infrastructures::adapters::secondary::external_apis::fixtures::{
+    RecordingGitHubApi, ReplayGitHubApi,
+};
+use crate::infrastructures::adapters::secondary::external_apis::github::request_log::GitHubRequestLog;
+use crate::infrastructures::adapters::secondary::external_apis::github::retry_events::GitHubRetryEvents;
+use crate::infrastructures::adapters::secondary::external_apis::github::token::{
+    FileToken, TOKEN_FILE_RELOAD_SECONDS, TokenProvider,
+};
+use crate::infrastructures::adapters::secondary::external_apis::{GitHubApiAdapter, MockGitHubApi};
+use crate::infrastructures::adapters::secondary::notifications::{
+    DiscordWebhook, GenericWebhook, SlackWebhook, VapidKeys, WebPushNotifier,
+};
+use crate::infrastructures::adapters::secondary::persistence::SqliteRunRepository;
+use crate::infrastructures::app::{App, AppBuilder, tenant_router};
+use crate::infrastructures::config::{
+    AuthMode, Config, GitHubApiMode, NotificationKind, ServerMode,
+};
+use crate::infrastructures::metrics::Metrics;
+use crate::infrastructures::telemetry::TracePropagator;
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use futures_util::StreamExt;
+use reload::ConfigReloader;
+use std::io::Write;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{oneshot, watch};
+use tokio::task::JoinHandle;
+#[cfg(feature = "grpc")]
+use tonic::transport::server::TcpIncoming;
+use tracing::info;
+
+/// Base URL of the GitHub REST API unless configured otherwise
+const GITHUB_API_URL: &str = "https://api.github.com";
+
+/// Seconds clients are told to wait before reconnecting during a deploy
+const RECONNECT_AFTER_SECONDS: u64 = 10;
+
+/// Live dashboard of GitHub Actions runs.
+///
+/// Settings are read from the config file, then environment variables, then the flags below.
+#[derive(Parser, Debug)]
+#[command(name = "gha-dashboard", version)]
+pub struct Cli {
+    #[command(flatten)]
+    pub settings: SettingsArgs,
+    /// Poll the first repository once, report each step and exit non-zero if one failed,
+    /// instead of running the command; also `GHA_SELFTEST=1`
+    #[arg(long)]
+    pub check: bool,
+    /// Start `serve` without checking the configured repositories and workflow filters
+    /// against GitHub; also `PREFLIGHT=off`
+    #[arg(long)]
+    pub skip_preflight: bool,
+    /// Defaults to `serve`
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Poll GitHub and serve the dashboard
+    Serve,
+    /// Fetch the latest runs once and print them
+    Fetch(FetchArgs),
+    /// Check the configuration and the GitHub token, exiting non-zero on problems
+    ValidateConfig,
+    /// Print the GitHub API rate limit left for the token
+    CheckQuota,
+    /// Print a new key pair for Web Push, to set as `VAPID_PRIVATE_KEY`
+    GenerateVapidKeys,
+    /// Follow a running dashboard in the terminal, or print its run changes when piped
+    #[cfg(feature = "tui")]
+    Tui(TuiArgs),
+}
+
+#[derive(Args, Debug, Clone, PartialEq, Eq)]
+pub struct FetchArgs {
+    /// Repository to fetch, repeatable; defaults to the configured allowlist
+    #[arg(long = "repo", value_name = "OWNER/NAME")]
+    pub repos: Vec<RepoFullName>,
+    /// Runs per repository; defaults to `MAX_RUNS_PER_REPO`
+    #[arg(long)]
+    pub count: Option<u8>,
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+    pub format: OutputFormat,
+}
+
+#[cfg(feature = "tui")]
+#[derive(Args, Debug, Clone, PartialEq, Eq)]
+pub struct TuiArgs {
+    /// Websocket endpoint of the dashboard
+    #[arg(long, value_name = "URL", default_value = "ws://localhost:3000/ws")]
+    pub url: String,
+    /// Seconds without a frame before the table is marked stale
+    #[arg(long, default_value_t = 90)]
+    pub stale_after_seconds: u32,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Aligned columns for reading
+    Table,
+    /// The snapshot as served by `/runs`
+    Json,
+}
+
+/// Declares [`SettingsArgs`] with one flag per environment variable.
+macro_rules! settings_args {
+    ($($(#[$doc:meta])* $field:ident => $env:literal,)*) => {
+        /// Flags overriding the environment variable of the same name, e.g. `--github-token`
+        /// for `GITHUB_TOKEN`.
+        #[derive(Args, Debug, Clone, Default, PartialEq, Eq)]
+        #[command(next_help_heading = "Settings")]
+        pub struct SettingsArgs {
+            /// Config file; defaults to `GHA_CONFIG`, then `gha-dashboard.toml` if it exists
+            #[arg(long, global = true, value_name = "PATH")]
+            pub config: Option<PathBuf>,
+            $(
+                $(#[$doc])*
+                #[arg(long, global = true, value_name = $env)]
+                pub $field: Option<String>,
+            )*
+        }
+
+        impl SettingsArgs {
+            /// The flag mirroring the environment variable `name`, if given.
+            #[must_use]
+            pub fn get(&self, name: &str) -> Option<String> {
+                match name {
+                    $($env => self.$field.clone(),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+settings_args! {
+    /// Address to listen on, `host:port` or `unix:/path`
+    bind_addr => "BIND_ADDR",
+    /// Permissions of the Unix socket, in octal
+    bind_socket_mode => "BIND_SOCKET_MODE",
+    /// `bearer` or `forward_headers`
+    auth_mode => "AUTH_MODE",
+    /// Bearer token protecting mutating endpoints
+    auth_token => "AUTH_TOKEN",
+    /// Comma-separated groups let in with `AUTH_MODE=forward_headers`
+    allowed_groups => "ALLOWED_GROUPS",
+    /// Trust `Forwarded` and `X-Forwarded-For` from a reverse proxy
+    trust_proxy => "TRUST_PROXY",
+    /// Comma-separated CIDRs of the only clients allowed to connect
+    allowed_cidrs => "ALLOWED_CIDRS",
+    /// `rfc3339` or `millis`
+    timestamp_format => "TIMESTAMP_FORMAT",
+    /// Seconds snapshot routes may take before a 504
+    request_timeout_seconds => "REQUEST_TIMEOUT_SECONDS",
+    /// Seconds `/history` and routes calling GitHub may take before a 504
+    slow_request_timeout_seconds => "SLOW_REQUEST_TIMEOUT_SECONDS",
+    /// Requests handled at once before further ones get 503
+    max_concurrent_requests => "MAX_CONCURRENT_REQUESTS",
+    /// Largest message a websocket client may send, in bytes
+    websocket_max_message_bytes => "WEBSOCKET_MAX_MESSAGE_BYTES",
+    /// Largest frame a websocket client may send, in bytes
+    websocket_max_frame_bytes => "WEBSOCKET_MAX_FRAME_BYTES",
+    /// Seconds between two heartbeats sent to a websocket client
+    websocket_heartbeat_seconds => "WEBSOCKET_HEARTBEAT_SECONDS",
+    /// Runs one websocket client may pin at once
+    websocket_max_pinned_runs => "WEBSOCKET_MAX_PINNED_RUNS",
+    /// Largest chunk of a websocket log tail, in bytes
+    log_tail_chunk_bytes => "LOG_TAIL_CHUNK_BYTES",
+    /// Bytes of log a websocket log tail sends before it ends
+    log_tail_max_bytes => "LOG_TAIL_MAX_BYTES",
+    /// Repositories one `POST /runs/latest` request may name
+    latest_runs_max_repos => "LATEST_RUNS_MAX_REPOS",
+    /// `host:port` of the gRPC server (`grpc` feature)
+    grpc_bind_addr => "GRPC_BIND_ADDR",
+    /// `strict`, `warn` or `off`: what a startup preflight finding problems does
+    preflight => "PREFLIGHT",
+    /// Serve runs with pseudonyms for a public demo, requiring the token everywhere else
+    anonymize => "ANONYMIZE",
+    /// `live`, or `mock` to serve generated runs without a token
+    github_api_mode => "GITHUB_API_MODE",
+    /// Seed of the runs generated in `mock` mode
+    github_api_mock_seed => "GITHUB_API_MOCK_SEED",
+    /// Record every GitHub API call into this directory (`fixtures` feature)
+    github_api_record_dir => "GITHUB_API_RECORD_DIR",
+    /// Serve the GitHub API calls recorded in this directory (`fixtures` feature)
+    github_api_replay_dir => "GITHUB_API_REPLAY_DIR",
+    /// Root of the GitHub REST API, e.g. of a GitHub Enterprise Server
+    github_api_url => "GITHUB_API_URL",
+    /// Token used to call the GitHub API
+    github_token => "GITHUB_TOKEN",
+    /// File holding the GitHub token, read again when it is rotated
+    github_token_file => "GITHUB_TOKEN_FILE",
+    /// Secret of the webhook posting to `/webhooks/github`
+    github_webhook_secret => "GITHUB_WEBHOOK_SECRET",
+    /// Run titles longer than this many characters are truncated
+    max_title_length => "MAX_TITLE_LENGTH",
+    /// Requests sent to GitHub at a time
+    github_max_concurrent_requests => "GITHUB_MAX_CONCURRENT_REQUESTS",
+    /// Wait between two snapshots
+    poll_interval_seconds => "POLL_INTERVAL_SECONDS",
+    /// Recently updated repositories polled without an allowlist
+    max_repositories => "MAX_REPOSITORIES",
+    /// Runs shown per repository
+    max_runs_per_repo => "MAX_RUNS_PER_REPO",
+    /// Age at which runs of repositories no longer polled are dropped
+    inactive_repo_run_max_age_hours => "INACTIVE_REPO_RUN_MAX_AGE_HOURS",
+    /// Most runs of repositories no longer polled kept
+    max_inactive_repo_runs => "MAX_INACTIVE_REPO_RUNS",
+    /// List the next run of each scheduled workflow in snapshots
+    upcoming_scheduled_runs => "UPCOMING_SCHEDULED_RUNS",
+    /// Comma-separated `owner/name` list of repositories whose commit statuses are shown
+    commit_status_repos => "COMMIT_STATUS_REPOS",
+    /// Poll repositories seen for the first time, dropping the least recently pushed
+    auto_monitor_new_repos => "AUTO_MONITOR_NEW_REPOS",
+    /// Comma-separated `owner/name` list
+    repo_allowlist => "REPO_ALLOWLIST",
+    /// Only show runs that failed or need attention
+    problem_only => "PROBLEM_ONLY",
+    /// Collapse the pull request runs of bots into one group per repository
+    group_bot_runs => "GROUP_BOT_RUNS",
+    /// Comma-separated workflow names or `*`/`?` patterns whose runs are hidden
+    ignore_workflows => "IGNORE_WORKFLOWS",
+    /// Comma-separated workflow names or `*`/`?` patterns whose runs are the only ones shown
+    only_workflows => "ONLY_WORKFLOWS",
+    /// Run history database, e.g. `sqlite:///var/lib/gha-dashboard/runs.db`
+    database_url => "DATABASE_URL",
+    /// Stored runs older than this are deleted
+    history_retention_days => "HISTORY_RETENTION_DAYS",
+    /// Most stored runs kept
+    history_max_rows => "HISTORY_MAX_ROWS",
+    /// Share one poller across replicas through Redis
+    redis_url => "REDIS_URL",
+    /// Slack incoming webhook told about run transitions
+    slack_webhook_url => "SLACK_WEBHOOK_URL",
+    /// Key signing Web Push requests, from `generate-vapid-keys`
+    vapid_private_key => "VAPID_PRIVATE_KEY",
+    /// Contact push services may reach the operator at, e.g. `mailto:ops@example.com`
+    vapid_subject => "VAPID_SUBJECT",
+    /// Discord webhook told about run transitions
+    discord_webhook_url => "DISCORD_WEBHOOK_URL",
+    /// Only notify of runs that failed or need attention
+    notify_failures_only => "NOTIFY_FAILURES_ONLY",
+    /// Comma-separated `owner/name` list notified about; all when empty
+    notify_repos => "NOTIFY_REPOS",
+    /// Comma-separated branches notified about; all when empty
+    notify_branches => "NOTIFY_BRANCHES",
+    /// Minimum wait between two notifications about one workflow
+    notify_cooldown_seconds => "NOTIFY_COOLDOWN_SECONDS",
+    /// Log filter, e.g. `gha_dashboard=debug`
+    log_filter => "RUST_LOG",
+    /// `full`, `pretty`, `compact` or `json`
+    log_format => "LOG_FORMAT",
+    /// `strict`, `standard` or `off`
+    log_redaction => "LOG_REDACTION",
+    /// How strict redaction masks repositories: `full`, `owner` or `hash`
+    log_redaction_repositories => "LOG_REDACTION_REPOSITORIES",
+    /// Collector traces are exported to; none when empty
+    otlp_endpoint => "OTEL_EXPORTER_OTLP_ENDPOINT",
+    /// `grpc` or `http/protobuf`
+    otlp_protocol => "OTEL_EXPORTER_OTLP_PROTOCOL",
+    /// Comma-separated `name=value` headers sent to the collector
+    otlp_headers => "OTEL_EXPORTER_OTLP_HEADERS",
+    /// Sampler named as in the OpenTelemetry spec, e.g. `parentbased_traceidratio`
+    traces_sampler => "OTEL_TRACES_SAMPLER",
+    /// Ratio of traces sampled by the `traceidratio` samplers
+    traces_sampler_arg => "OTEL_TRACES_SAMPLER_ARG",
+    /// `tracecontext`, or `none` to keep the trace context off GitHub API requests
+    propagators => "OTEL_PROPAGATORS",
+}
+
+/// The configured GitHub API: the REST API authenticated with the token, the mock, or
+/// recorded fixtures.
+///
+/// `metrics` and `request_log` record the calls made to the REST API, and `retry_events` is
+/// told about its retries. A token file is read
+/// again in the background, so this must be called within a tokio runtime.
+///
+/// # Errors
+///
+/// Returns an error if the replay fixtures or the token file cannot be read.
+pub fn github_api(
+    config: &Config,
+    metrics: Option<Arc<Metrics>>,
+    request_log: Option<Arc<GitHubRequestLog>>,
+    retry_events: Option<Arc<GitHubRetryEvents>>,
+) -> anyhow::Result<Arc<dyn GitHubApi + Send + Sync>> {
+    #[cfg(feature = "fixtures")]
+    if let Some(dir) = &config.github.replay_dir {
+        return Ok(Arc::new(ReplayGitHubApi::open(dir)?));
+    }
+    Ok(match config.github.api_mode {
+        GitHubApiMode::Mock => recorded(config, MockGitHubApi::new(config.github.mock_seed)),
+        GitHubApiMode::Live => {
+            let github_token: Arc<dyn TokenProvider> = match &config.github.token_file {
+                Some(path) => {
+                    let token = Arc::new(FileToken::open(path).with_context(|| {
+                        format!("Failed to read the GitHub token from {}", path.display())
+                    })?);
+                    // Detached: rotations are picked up for as long as the runtime lives
+                    token.spawn(Duration::from_secs(TOKEN_FILE_RELOAD_SECONDS));
+                    token
+                }
+                None => Arc::new(
+                    config
+                        .github
+                        .token
+                        .as_ref()
+                        .map(|token| token.expose().to_string())
+                        .unwrap_or_default(),
+                ),
+            };
+            let api_url = config.github.api_url.as_deref().unwrap_or(GITHUB_API_URL);
+            let mut adapter =
+                GitHubApiAdapter::with_token_provider(api_url.to_string(), github_token)
+                    .with_max_title_length(config.github.max_title_length)
+                    .with_trace_propagation(config.telemetry.propagator != TracePropagator::None)
+                    .with_redactor(config.telemetry.redactor())
+                    .with_retry_deadline(config.github_retry_deadline());
+            if let Some(max_concurrent_requests) = config.github.max_concurrent_requests {
+                adapter = adapter.with_max_concurrent_requests(max_concurrent_requests);
+            }
+            if let Some(metrics) = metrics {
+                adapter = adapter.with_metrics(metrics);
+            }
+            if let Some(request_log) = request_log {
+                adapter = adapter.with_request_log(request_log);
+            }
+            if let Some(retry_events) = retry_events {
+                adapter = adapter.with_retry_events(retry_events);
+            }
+            recorded(config, adapter)
+        }
+    })
+}
+
+/// Wraps `github_api` to record its calls when `GITHUB_API_RECORD_DIR` is set.
+#[cfg(feature = "fixtures")]
+fn recorded<G>(config: &Config, github_api: G) -> Arc<dyn GitHubApi + Send + Sync>
+where
+    G: GitHubApi + Send + Sync + 'static,
+{
+    match &config.github.record_dir {
+        Some(dir) => Arc::new(RecordingGitHubApi::new(github_api, dir)),
+        None => Arc::new(github_api),
+    }
+}
+
+#[cfg(not(feature = "fixtures"))]
+fn recorded<G>(_config: &Config, github_api: G) -> Arc<dyn GitHubApi + Send + Sync>
+where
+    G: GitHubApi + Send + Sync + 'static,
+{
+    Arc::new(github_api)
+}
+
+/// Waits for Ctrl+C or SIGTERM, then tells the clients of every poller why they are being
+/// disconnected.
+async fn shutdown_signal(pollers: Vec<Arc<SharedPoller>>) {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for Ctrl+C: {:?}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {:?}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        () = ctrl_c => {}
+        () = terminate => {}
+    }
+
+    info!("Shutdown signal received, draining clients");
+    drain_clients(pollers);
+}
+
+/// Tells the clients of `pollers` to reconnect shortly, and disconnects them so the server
+/// can stop.
+fn drain_clients(pollers: Vec<Arc<SharedPoller>>) {
+    for poller in pollers {
+        poller.notify_clients(Notice::warning(format!(
+            "Server draining for deploy, reconnect in {RECONNECT_AFTER_SECONDS}s"
+        )));
+        poller.disconnect_clients();
+    }
+}
+
+/// Every configured notification target, each with its own filter.
+///
+/// # Errors
+///
+/// Returns an error if a webhook target is invalid, which `Config::load` already rules out.
+pub fn notifier(config: &Config) -> anyhow::Result<RunNotifier> {
+    let mut notifier = RunNotifier::default();
+    for target in &config.notifications {
+        let url = target.url.expose();
+        let port: Arc<dyn NotificationPort + Send + Sync> = match target.kind {
+            NotificationKind::Slack => Arc::new(SlackWebhook::new(url)),
+            NotificationKind::Discord => Arc::new(DiscordWebhook::new(url)),
+            NotificationKind::Webhook => {
+                let (headers, template) = target.webhook()?;
+                Arc::new(GenericWebhook::new(url, template).with_headers(headers))
+            }
+        };
+        notifier = notifier.with_target(port, target.filter());
+    }
+    Ok(notifier)
+}
+
+/// The dashboard `config` describes, with the GitHub API it polls.
+async fn app(
+    config: &Config,
+    metrics: Arc<Metrics>,
+) -> anyhow::Result<(App, Arc<dyn GitHubApi + Send + Sync>)> {
+    let github_requests = Arc::new(
+        config
+            .github
+            .request_log_size
+            .map_or_else(GitHubRequestLog::default, GitHubRequestLog::new),
+    );
+    let github_retries = Arc::new(GitHubRetryEvents::default());
+    let github_api = github_api(
+        config,
+        Some(Arc::clone(&metrics)),
+        Some(Arc::clone(&github_requests)),
+        Some(Arc::clone(&github_retries)),
+    )?;
+    if config.github.api_mode == GitHubApiMode::Mock {
+        tracing::warn!("GITHUB_API_MODE is mock, serving generated runs instead of GitHub's");
+    }
+
+    let mut builder = AppBuilder::new(Arc::clone(&github_api))
+        .with_metrics(metrics)
+        .with_stream_config(config.stream_config()?)
+        .with_trust_proxy(config.server.trust_proxy)
+        .with_allowed_cidrs(config.server.allowed_cidrs.clone())
+        .with_request_limits(config.server.request_limits())
+        .with_timestamp_format(config.server.timestamp_format)
+        .with_redactor(config.telemetry.redactor())
+        .with_price_table(config.costs)
+        .with_slos(config.slos.clone())
+        .with_github_requests(github_requests)
+        .with_github_retries(github_retries);
+    // Optional bearer token protecting mutating endpoints such as /refresh, or the identity
+    // forwarded by an authenticating proxy on every route
+    if let Some(token) = &config.server.auth_token {
+        builder = builder.with_auth_token(token.expose());
+    }
+    if config.server.auth_mode == AuthMode::ForwardHeaders {
+        builder = builder.with_forwarded_identity(config.server.allowed_groups.clone());
+    }
+    // Optional run history, e.g. `sqlite:///var/lib/gha-dashboard/runs.db`
+    let run_store = match &config.history.database_url {
+        Some(url) => Some(Arc::new(SqliteRunRepository::connect(url)?)),
+        None => None,
+    };
+    if let Some(run_store) = &run_store {
+        builder = builder.with_run_repository(
+            Arc::clone(run_store) as _,
+            config.history.retention_policy(),
+        );
+    }
+    // Repositories seen before are remembered across restarts with a run history
+    let mut discovery = RepositoryDiscovery::new(config.polling.auto_monitor_new_repos);
+    if let Some(run_store) = &run_store {
+        discovery = discovery.with_store(Arc::clone(run_store) as _);
+    }
+    builder = builder.with_repository_discovery(discovery);
+    // Pseudonyms of an anonymized demo stay the same across restarts with a run history
+    if config.server.anonymize {
+        let anonymizer = match &run_store {
+            Some(run_store) => Anonymizer::load(Arc::clone(run_store) as _).await?,
+            None => Anonymizer::default(),
+        };
+        builder = builder.with_anonymizer(anonymizer);
+    }
+    // Optional Web Push, keeping the subscriptions in the run history
+    if let Some(private_key) = &config.push.vapid_private_key {
+        let run_store = run_store
+            .context("VAPID_PRIVATE_KEY requires DATABASE_URL to store the subscriptions")?;
+        let keys = VapidKeys::from_private_key(private_key.expose())?;
+        builder = builder.with_web_push(
+            WebPushNotifier::new(run_store, keys).with_subject(config.push.subject.clone()),
+        );
+    }
+    // With a Redis URL, replicas elect one poller and share its snapshots through Redis
+    if let Some(url) = &config.replication.redis_url {
+        builder = builder.with_redis_replication(url.expose());
+    }
+    // Optional secret of a GitHub webhook pushing workflow_run events to /webhooks/github
+    if let Some(secret) = &config.github.webhook_secret {
+        builder = builder.with_github_webhooks(
+            GitHubWebhooks::new(secret.expose().to_string())
+                .with_max_title_length(config.github.max_title_length),
+        );
+    }
+    let notifier = notifier(config)?;
+    if !notifier.is_empty() {
+        builder = builder.with_notifier(notifier);
+    }
+    // An exporter serves its own admin routes instead of the dashboard
+    if config.server.mode == ServerMode::Exporter {
+        builder = builder.without_routes();
+    }
+    Ok((builder.build()?, github_api))
+}
+
+/// `serve`: polls GitHub and serves the dashboard until Ctrl+C or SIGTERM.
+///
+/// Each request to `reloader` applies the config file again, except with `[[tenants]]`.
+/// With `MODE=exporter`, runs [`exporter::export`] to stdout instead.
+///
+/// # Errors
+///
+/// Returns an error if a backing service cannot be set up or the server fails to start.
+pub async fn serve(config: Config, reloader: Arc<ConfigReloader>) -> anyhow::Result<()> {
+    if config.server.mode == ServerMode::Exporter {
+        return exporter::export(config, reloader, std::io::stdout()).await;
+    }
+    if !config.tenants.is_empty() {
+        tokio::spawn(async move {
+            reloader
+                .refuse("tenants are only set up at startup, restart to apply changes")
+                .await;
+        });
+        return serve_tenants(config).await;
+    }
+    let (app, github_api) = app(&config, Arc::new(Metrics::new()?)).await?;
+    preflight::run(github_api.as_ref(), &config).await?;
+    let poller = Arc::clone(&app.poller);
+    if let Some(web_push) = &app.web_push {
+        reloader.keep_target(Arc::clone(web_push) as _);
+    }
+    tokio::spawn(async move { reloader.run(&poller).await });
+
+    // Optional gRPC server on its own port, stopped along with the HTTP server
+    #[cfg(feature = "grpc")]
+    let grpc_server = match config.server.grpc_bind_addr {
+        Some(addr) => {
+            let incoming = TcpIncoming::bind(addr)
+                .with_context(|| format!("Failed to bind GRPC_BIND_ADDR {addr}"))?;
+            info!("gRPC listening on {}", addr);
+            Some(tokio::spawn(grpc::serve(
+                incoming,
+                RunsService::new(Arc::clone(&app.poller), github_api),
+            )))
+        }
+        None => None,
+    };
+
+    // Start server, on TCP by default or on a Unix socket with `unix:/path`
+    server::serve(
+        &config.server.bind_addr,
+        config.server.socket_mode,
+        app.router,
+        shutdown_signal(vec![app.poller]),
+    )
+    .await?;
+
+    #[cfg(feature = "grpc")]
+    if let Some(grpc_server) = grpc_server {
+        grpc_server.await??;
+    }
+    Ok(())
+}
+
+/// `serve` with `[[tenants]]`: one dashboard per tenant under `/t/{name}`, each polling with
+/// its own token and counting into its own metrics.
+async fn serve_tenants(config: Config) -> anyhow::Result<()> {
+    let mut routers = Vec::with_capacity(config.tenants.len());
+    let mut pollers = Vec::with_capacity(config.tenants.len());
+    for tenant in &config.tenants {
+        let tenant_config = config.for_tenant(tenant);
+        let (app, github_api) = app(&tenant_config, Arc::new(Metrics::for_tenant(&tenant.name)?))
+            .await
+            .with_context(|| format!("Failed to set up tenant {}", tenant.name))?;
+        preflight::run(github_api.as_ref(), &tenant_config)
+            .await
+            .with_context(|| format!("Failed to set up tenant {}", tenant.name))?;
+        info!("Serving tenant {} under /t/{}", tenant.name, tenant.name);
+        routers.push((tenant.name.clone(), app.router));
+        pollers.push(app.poller);
+    }
+
+    server::serve(
+        &config.server.bind_addr,
+        config.server.socket_mode,
+        tenant_router(routers),
+        shutdown_signal(pollers),
+    )
+    .await?;
+    Ok(())
+}
+
+/// A server started by [`run_server`], stopped with [`ServerHandle::shutdown`].
+pub struct ServerHandle {
+    /// Where the server listens, with the port picked when `BIND_ADDR` asked for port 0
+    pub addr: SocketAddr,
+    stop: oneshot::Sender<()>,
+    server: JoinHandle<anyhow::Result<()>>,
+    /// The polling loop and the background tasks, stopped along with the server
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl ServerHandle {
+    /// Drains the clients as a shutdown signal would, then waits for the server to stop.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the server stopped with an I/O error or panicked.
+    pub async fn shutdown(self) -> anyhow::Result<()> {
+        // The server may have stopped on its own already, which the join below reports
+        let _ = self.stop.send(());
+        let served = self.server.await;
+        for task in self.tasks {
+            task.abort();
+        }
+        served?
+    }
+}
+
+/// `serve` without signal handling, config reloading, tenants or gRPC: starts the dashboard
+/// of `config` on its TCP `BIND_ADDR`, such as `127.0.0.1:0` for a free port, and returns
+/// once it listens. Meant for end-to-end tests of the wiring `serve` uses.
+///
+/// # Errors
+///
+/// Returns an error if the app cannot be built, the preflight fails, `BIND_ADDR` is a Unix
+/// socket or binding fails.
+pub async fn run_server(config: Config) -> anyhow::Result<ServerHandle> {
+    let server::BindAddr::Tcp(addr) = config.server.bind_addr else {
+        anyhow::bail!("run_server only listens on TCP");
+    };
+    let (app, github_api) = app(&config, Arc::new(Metrics::new()?)).await?;
+    preflight::run(github_api.as_ref(), &config).await?;
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind BIND_ADDR {addr}"))?;
+    let addr = listener.local_addr()?;
+    let (stop, stopped) = oneshot::channel();
+    let pollers = vec![Arc::clone(&app.poller)];
+    let server = tokio::spawn(server::serve_tcp(listener, app.router, async move {
+        let _ = stopped.await;
+        drain_clients(pollers);
+    }));
+    let mut tasks = app.background_tasks;
+    tasks.push(app.poller_task);
+    Ok(ServerHandle {
+        addr,
+        stop,
+        server,
+        tasks,
+    })
+}
+
+/// `fetch`: takes one snapshot of the configured repositories, or of `args.repos`, and
+/// writes it to `out`.
+///
+/// # Errors
+///
+/// Returns an error if the arguments are invalid, GitHub fails or `out` cannot be written.
+pub async fn fetch<G>(
+    github_api: Arc<G>,
+    config: &Config,
+    args: &FetchArgs,
+    out: &mut impl Write,
+) -> anyhow::Result<()>
+where
+    G: GitHubApi + Send + Sync + ?Sized + 'static,
+{
+    let stream_config = config.stream_config()?.apply(&StreamConfigPatch {
+        max_runs_per_repo: args.count.map(u64::from),
+        repo_allowlist: (!args.repos.is_empty())
+            .then(|| args.repos.iter().map(ToString::to_string).collect()),
+        ..StreamConfigPatch::default()
+    })?;
+    let input = StreamGitHubActionsRunsUseCaseInput {
+        config: watch::channel(stream_config).1,
+        ..StreamGitHubActionsRunsUseCaseInput::default()
+    };
+    let interactor = StreamGitHubActionsRunsInteractor::new(github_api);
+    let stream = interactor.execute(input);
+    tokio::pin!(stream);
+    let output = stream.next().await.context("No snapshot was taken")??;
+
+    match args.format {
+        OutputFormat::Table => write_table(&output.runs, out)?,
+        OutputFormat::Json => writeln!(
+            out,
+            "{}",
+            snapshot_json(&output, config.server.timestamp_format)?
+        )?,
+    }
+    Ok(())
+}
+
+/// Writes `runs` as left-aligned columns under a header.
+fn write_table(runs: &[WorkflowRun], out: &mut impl Write) -> std::io::Result<()> {
+    let header = [
+        "REPOSITORY",
+        "RUN",
+        "WORKFLOW",
+        "STATUS",
+        "BRANCH",
+        "CREATED",
+        "TITLE",
+    ]
+    .map(str::to_string);
+    let rows: Vec<[String; 7]> = runs
+        .iter()
+        .map(|run| {
+            [
+                run.repository_name.to_string(),
+                format!("#{}", run.run_number),
+                run.workflow_name.clone(),
+                run.display_status().to_string(),
+                run.head_branch.clone().unwrap_or_default(),
+                run.created_at.format("%Y-%m-%d %H:%M").to_string(),
+                run.display_title.clone(),
+            ]
+        })
+        .collect();
+
+    let mut widths = header.clone().map(|cell| cell.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    for row in std::iter::once(&header).chain(&rows) {
+        let line = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join("  ");
+        writeln!(out, "{}", line.trim_end())?;
+    }
+    Ok(())
+}
+
+/// `validate-config`: checks that GitHub accepts the token of an already loaded config.
+///
+/// # Errors
+///
+/// Returns an error if GitHub rejects the token or cannot be reached.
+pub async fn validate_config<G: GitHubApi + ?Sized>(
+    github_api: &G,
+    out: &mut impl Write,
+) -> anyhow::Result<()> {
+    let rate_limit = github_api
+        .fetch_rate_limit()
+        .await
+        .context("GitHub rejected the token")?;
+    writeln!(out, "Configuration is valid")?;
+    writeln!(
+        out,
+        "GitHub token accepted, {} of {} requests left",
+        rate_limit.remaining, rate_limit.limit
+    )?;
+    if let Some(expires_at) = github_api.api_status().token_expires_at {
+        writeln!(out, "Token expires at {}", expires_at.to_rfc3339())?;
+    }
+    Ok(())
+}
+
+/// `check-quota`: writes how much of the core rate limit is left and when it resets.
+///
+/// # Errors
+///
+/// Returns an error if GitHub fails or `out` cannot be written.
+pub async fn check_quota<G: GitHubApi + ?Sized>(
+    github_api: &G,
+    now: DateTime<Utc>,
+    out: &mut impl Write,
+) -> anyhow::Result<()> {
+    let rate_limit = github_api.fetch_rate_limit().await?;
+    writeln!(
+        out,
+        "Remaining: {}/{}",
+        rate_limit.remaining, rate_limit.limit
+    )?;
+    writeln!(out, "Used: {}", rate_limit.used)?;
+    writeln!(
+        out,
+        "Resets at {} (in {} minutes)",
+        rate_limit.reset.to_rfc3339(),
+        (rate_limit.reset - now).num_minutes().max(0)
+    )?;
+    Ok(())
+}
+
+/// `generate-vapid-keys`: writes a new private key for `VAPID_PRIVATE_KEY` and the public key
+/// browsers will subscribe with.
+///
+/// # Errors
+///
+/// Returns an error if `out` cannot be written.
+pub fn generate_vapid_keys(out: &mut impl Write) -> anyhow::Result<()> {
+    let private_key = VapidKeys::generate_private_key();
+    let keys = VapidKeys::from_private_key(&private_key)?;
+    writeln!(out, "VAPID_PRIVATE_KEY={private_key}")?;
+    writeln!(out, "VAPID_PUBLIC_KEY={}", keys.public_key())?;
+    Ok(())
+}
+
+/// `tui`: follows the dashboard at `args.url` as a live table when `out` is a terminal,
+/// and as lines of run changes otherwise.
+///
+/// # Errors
+///
+/// Returns an error if the terminal or `out` cannot be written.
+#[cfg(feature = "tui")]
+pub async fn tui(
+    args: &TuiArgs,
+    out: &mut (impl Write + std::io::IsTerminal),
+) -> anyhow::Result<()> {
+    // Boxed, as connecting holds a large future across awaits
+    let events = Box::pin(tui::feed::subscribe(args.url.clone()));
+    if out.is_terminal() {
+        tui::interactive(
+            events,
+            chrono::TimeDelta::seconds(args.stale_after_seconds.into()),
+        )
+        .await
+    } else {
+        tui::print_lines(events, out).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::external_apis::github::{GitHubApiError, RateLimit};
+    use crate::domain::models::ids::WorkflowId;
+    use crate::domain::models::transition::RunTransition;
+    use crate::infrastructures::config::NotificationTargetConfig;
+    use crate::test_support::{StubGitHubApi, base_time, workflow_run};
+    use chrono::TimeDelta;
+    use clap::CommandFactory;
+
+    fn config() -> anyhow::Result<Config> {
+        let env = |name: &str| (name == "GITHUB_TOKEN").then(|| "token".to_string());
+        Ok(Config::load(None, &env)?.0)
+    }
+
+    #[tokio::test]
+    async fn test_notifications_fan_out_to_every_matching_target() -> anyhow::Result<()> {
+        let server = wiremock::MockServer::start().await;
+        for (route, expected) in [("/all", 2), ("/failures", 1)] {
+            wiremock::Mock::given(wiremock::matchers::path(route))
+                .respond_with(wiremock::ResponseTemplate::new(200))
+                .expect(expected)
+                .mount(&server)
+                .await;
+        }
+        let mut config = config()?;
+        let mut all = NotificationTargetConfig::new(
+            NotificationKind::Webhook,
+            format!("{}/all", server.uri()).into(),
+        );
+        all.failures_only = false;
+        config.notifications = vec![
+            all,
+            NotificationTargetConfig::new(
+                NotificationKind::Webhook,
+                format!("{}/failures", server.uri()).into(),
+            ),
+        ];
+        let transition = |id, conclusion| RunTransition {
+            run: workflow_run("owner/repo", id, conclusion),
+            previous_status: None,
+            previous_conclusion: None,
+        };
+        let mut succeeded = transition(1, "success");
+        // Another workflow, so the cooldown does not hold back the failure
+        succeeded.run.workflow_id = WorkflowId(2);
+        let failed = transition(2, "failure");
+
+        notifier(&config)?.dispatch(&[succeeded, failed]).await?;
+
+        server.verify().await;
+        Ok(())
+    }
+
+    #[test]
+    fn test_cli_is_well_formed() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_flags_mirror_environment_variables() -> anyhow::Result<()> {
+        let cli = Cli::try_parse_from([
+            "gha-dashboard",
+            "fetch",
+            "--repo",
+            "owner/repo",
+            "--count",
+            "5",
+            "--format",
+            "json",
+            "--github-token",
+            "secret",
+            "--log-filter",
+            "debug",
+        ])?;
+
+        assert_eq!(
+            cli.command,
+            Some(Command::Fetch(FetchArgs {
+                repos: vec!["owner/repo".parse()?],
+                count: Some(5),
+                format: OutputFormat::Json,
+            }))
+        );
+        assert_eq!(cli.settings.get("GITHUB_TOKEN").as_deref(), Some("secret"));
+        assert_eq!(cli.settings.get("RUST_LOG").as_deref(), Some("debug"));
+        assert_eq!(cli.settings.get("REDIS_URL"), None);
+        assert!(Cli::try_parse_from(["gha-dashboard", "fetch", "--repo", "owner"]).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fetch_prints_a_table_newest_first() -> anyhow::Result<()> {
+        let github_api = StubGitHubApi {
+            runs: vec![
+                workflow_run("owner/repo", 1, "success"),
+                workflow_run("owner/repo", 2, "in_progress"),
+            ],
+            ..StubGitHubApi::with_repository("owner", "repo")
+        };
+        let args = FetchArgs {
+            repos: Vec::new(),
+            count: Some(5),
+            format: OutputFormat::Table,
+        };
+        let mut out = Vec::new();
+
+        fetch(Arc::new(github_api), &config()?, &args, &mut out).await?;
+
+        assert_eq!(
+            String::from_utf8(out)?,
+            "\
+REPOSITORY  RUN  WORKFLOW  STATUS       BRANCH  CREATED           TITLE
+owner/repo  #2   CI        in_progress  main    2024-08-01 10:02  Fix bug
+owner/repo  #1   CI        success      main    2024-08-01 10:01  Fix bug
+"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fetch_prints_the_snapshot_as_json() -> anyhow::Result<()> {
+        let github_api = StubGitHubApi {
+            runs: vec![
+                workflow_run("owner/repo", 1, "success"),
+                workflow_run("other/repo", 2, "failure"),
+            ],
+            ..StubGitHubApi::default()
+        };
+        let args = FetchArgs {
+            repos: vec!["owner/repo".parse()?],
+            count: None,
+            format: OutputFormat::Json,
+        };
+        let mut out = Vec::new();
+
+        fetch(Arc::new(github_api), &config()?, &args, &mut out).await?;
+
+        let snapshot: serde_json::Value = serde_json::from_slice(&out)?;
+        assert_eq!(snapshot["runs"].as_array().map(Vec::len), Some(1));
+        assert_eq!(snapshot["runs"][0]["repositoryName"], "owner/repo");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_check_quota_prints_remaining_calls() -> anyhow::Result<()> {
+        let github_api = StubGitHubApi {
+            rate_limit: RateLimit {
+                limit: 5_000,
+                remaining: 4_990,
+                used: 10,
+                reset: base_time() + TimeDelta::minutes(42),
+            },
+            ..StubGitHubApi::default()
+        };
+        let mut out = Vec::new();
+
+        check_quota(&github_api, base_time(), &mut out).await?;
+
+        assert_eq!(
+            String::from_utf8(out)?,
+            "Remaining: 4990/5000\nUsed: 10\nResets at 2024-08-01T10:42:00+00:00 (in 42 minutes)\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_generated_vapid_keys_load_back() -> anyhow::Result<()> {
+        let mut out = Vec::new();
+
+        generate_vapid_keys(&mut out)?;
+
+        let out = String::from_utf8(out)?;
+        let value = |name: &str| {
+            out.lines()
+                .find_map(|line| line.strip_prefix(name)?.strip_prefix('='))
+                .ok_or_else(|| anyhow::anyhow!("{name} missing from {out}"))
+        };
+        let keys = VapidKeys::from_private_key(value("VAPID_PRIVATE_KEY")?)?;
+        assert_eq!(keys.public_key(), value("VAPID_PUBLIC_KEY")?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_validate_config_fails_when_the_token_is_rejected() {
+        let github_api = StubGitHubApi {
+            error: Some(GitHubApiError::Unauthorized {
+                resource: "rate_limit".to_string(),
+            }),
+            ..StubGitHubApi::default()
+        };
+
+        let result = validate_config(&github_api, &mut Vec::new()).await;
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,167 @@
+use super::super::web::presenter::{TimestampFormat, snapshot_json};
+use super::super::web::server;
+use super::reload::ConfigReloader;
+use super::{ServerHandle, app, drain_clients, preflight, shutdown_signal};
+use crate::application::services::SharedPoller;
+use crate::infrastructures::config::{Config, ExporterOutput};
+use crate::infrastructures::metrics::Metrics;
+use anyhow::Context;
+use axum::Router;
+use axum::http::{StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use std::io::{self, Write};
+use std::sync::Arc;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tracing::info;
+
+/// `serve` with `MODE=exporter`: polls GitHub as `serve` does, writing every snapshot to
+/// `out` as one JSON line unless `EXPORTER_OUTPUT=redis` leaves it to the Redis channel, and
+/// serves only `/livez` and `/metrics` on `BIND_ADDR` until Ctrl+C or SIGTERM.
+///
+/// Each request to `reloader` applies the config file again.
+///
+/// # Errors
+///
+/// Returns an error if a backing service cannot be set up or the admin server fails to
+/// start.
+pub async fn export(
+    config: Config,
+    reloader: Arc<ConfigReloader>,
+    out: impl Write + Send + 'static,
+) -> anyhow::Result<()> {
+    let (router, poller, _tasks) = exporter(&config, out).await?;
+    let reload_target = Arc::clone(&poller);
+    tokio::spawn(async move { reloader.run(&reload_target).await });
+
+    server::serve(
+        &config.server.bind_addr,
+        config.server.socket_mode,
+        router,
+        shutdown_signal(vec![poller]),
+    )
+    .await
+}
+
+/// [`export`] without signal handling or config reloading, on its TCP `BIND_ADDR`, such as
+/// `127.0.0.1:0` for a free port; returns once the admin routes listen. Meant for
+/// end-to-end tests, like [`super::run_server`].
+///
+/// # Errors
+///
+/// Returns an error if the exporter cannot be set up, `BIND_ADDR` is a Unix socket or
+/// binding fails.
+pub async fn start(
+    config: Config,
+    out: impl Write + Send + 'static,
+) -> anyhow::Result<ServerHandle> {
+    let server::BindAddr::Tcp(addr) = config.server.bind_addr else {
+        anyhow::bail!("The exporter only starts on TCP");
+    };
+    let (router, poller, tasks) = exporter(&config, out).await?;
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind BIND_ADDR {addr}"))?;
+    let addr = listener.local_addr()?;
+    let (stop, stopped) = oneshot::channel();
+    let server = tokio::spawn(server::serve_tcp(listener, router, async move {
+        let _ = stopped.await;
+        drain_clients(vec![poller]);
+    }));
+    Ok(ServerHandle {
+        addr,
+        stop,
+        server,
+        tasks,
+    })
+}
+
+/// Starts the poller of `config` and the task writing its snapshots to `out`; returns the
+/// admin routes, the poller and every task.
+async fn exporter(
+    config: &Config,
+    out: impl Write + Send + 'static,
+) -> anyhow::Result<(Router, Arc<SharedPoller>, Vec<JoinHandle<()>>)> {
+    let metrics = Arc::new(Metrics::new()?);
+    let (app, github_api) = app(config, Arc::clone(&metrics)).await?;
+    preflight::run(github_api.as_ref(), config).await?;
+    let mut tasks = app.background_tasks;
+    tasks.push(app.poller_task);
+    match config.server.exporter_output {
+        ExporterOutput::Stdout => {
+            info!("Exporting snapshots to stdout");
+            tasks.push(tokio::spawn(write_snapshots(
+                Arc::clone(&app.poller),
+                config.server.timestamp_format,
+                out,
+            )));
+        }
+        ExporterOutput::Redis => info!("Exporting snapshots to the Redis channel"),
+    }
+    Ok((
+        admin_router(Arc::clone(&app.poller), metrics),
+        app.poller,
+        tasks,
+    ))
+}
+
+/// Writes every snapshot `poller` publishes to `out` as one JSON line, as `/runs` serves it
+/// in `format`, flushing each right away.
+///
+/// A writer falling behind skips to the latest snapshot; one that fails stops exporting.
+async fn write_snapshots(poller: Arc<SharedPoller>, format: TimestampFormat, mut out: impl Write) {
+    let mut latest = poller.snapshots().subscribe();
+    let mut written = None;
+    loop {
+        let snapshot = latest.borrow_and_update().clone();
+        if let Some(snapshot) = snapshot
+            && written != Some(snapshot.seq)
+        {
+            written = Some(snapshot.seq);
+            if let Err(e) = snapshot_json(&snapshot.output, format)
+                .map_err(io::Error::from)
+                .and_then(|line| writeln!(out, "{line}"))
+                .and_then(|()| out.flush())
+            {
+                tracing::error!("Failed to write snapshot, exporting stops: {}", e);
+                return;
+            }
+        }
+        if latest.changed().await.is_err() {
+            return;
+        }
+    }
+}
+
+/// `/livez`, answering as long as the process runs, and `/metrics`.
+fn admin_router(poller: Arc<SharedPoller>, metrics: Arc<Metrics>) -> Router {
+    Router::new()
+        .route("/livez", get(|| async { (StatusCode::OK, "OK") }))
+        .route(
+            "/metrics",
+            get(move || {
+                let (poller, metrics) = (Arc::clone(&poller), Arc::clone(&metrics));
+                async move { render_metrics(&poller, &metrics) }
+            }),
+        )
+}
+
+/// The metrics of the exporter, with the queue of its latest snapshot.
+fn render_metrics(poller: &SharedPoller, metrics: &Metrics) -> Response {
+    if let Some(latest) = poller.snapshots().latest() {
+        metrics.record_queue(&latest.queue);
+    }
+    match metrics.render() {
+        Ok(body) => (
+            StatusCode::OK,
+            [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+            body,
+        )
+            .into_response(),
+        Err(e) => {
+            tracing::error!("Failed to render metrics: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
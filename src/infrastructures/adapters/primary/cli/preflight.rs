@@ -0,0 +1,338 @@
+use crate::domain::external_apis::github::{GitHubApi, GitHubApiError};
+use crate::domain::models::glob::GlobPattern;
+use crate::domain::models::ids::RepoFullName;
+use crate::infrastructures::config::{Config, GitHubApiMode, PreflightMode};
+use futures_util::future::join_all;
+use std::collections::{BTreeMap, BTreeSet};
+use tracing::info;
+
+/// A way the configuration does not match what GitHub reports.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum PreflightProblem {
+    /// The repository does not exist, or the token cannot see it
+    #[error("{repo} does not exist or the token cannot see it")]
+    MissingRepository { repo: RepoFullName },
+    /// The repository was renamed or transferred; GitHub still redirects the old name
+    #[error("{repo} was renamed to {new_name}")]
+    RenamedRepository {
+        repo: RepoFullName,
+        new_name: RepoFullName,
+    },
+    /// GitHub failed otherwise, so the repository could not be checked
+    #[error("{repo} could not be checked: {error}")]
+    UncheckedRepository {
+        repo: RepoFullName,
+        error: GitHubApiError,
+    },
+    /// A workflow filter matches none of the workflows of the repositories it applies to
+    #[error("{key} pattern {pattern:?} matches no workflow")]
+    UnmatchedWorkflowFilter { key: String, pattern: String },
+    /// Webhooks are enabled with a blank secret, which anyone can sign deliveries with
+    #[error("github.webhook_secret is blank")]
+    BlankWebhookSecret,
+}
+
+/// Runs [`check`] unless `config.server.preflight` is `off`, logging every problem found.
+///
+/// Generated and replayed data has nothing to reconcile with, so nothing is checked in
+/// `mock` mode or when replaying fixtures.
+///
+/// # Errors
+///
+/// Returns an error listing the problems in `strict` mode.
+pub async fn run<G: GitHubApi + ?Sized>(github_api: &G, config: &Config) -> anyhow::Result<()> {
+    let mode = config.server.preflight;
+    if mode == PreflightMode::Off
+        || config.github.api_mode == GitHubApiMode::Mock
+        || config.github.replay_dir.is_some()
+    {
+        return Ok(());
+    }
+    let problems = check(github_api, config).await;
+    if problems.is_empty() {
+        info!("Preflight found the configuration consistent with GitHub");
+        return Ok(());
+    }
+    for problem in &problems {
+        tracing::warn!("Preflight: {}", problem);
+    }
+    if mode == PreflightMode::Strict {
+        let problems: Vec<String> = problems.iter().map(ToString::to_string).collect();
+        anyhow::bail!(
+            "Preflight found {} problems, set PREFLIGHT=warn to start anyway: {}",
+            problems.len(),
+            problems.join("; ")
+        );
+    }
+    Ok(())
+}
+
+/// Checks `config` against GitHub, returning every problem found rather than the first.
+///
+/// Each repository configured by name is fetched once, along with its workflows when a
+/// workflow filter applies to it. The filters of `[filters]` apply to every repository, so
+/// each of their patterns must match a workflow of one of them; they are not checked without
+/// repositories configured by name, as the ones polled are only known once polling starts.
+pub async fn check<G: GitHubApi + ?Sized>(
+    github_api: &G,
+    config: &Config,
+) -> Vec<PreflightProblem> {
+    let filters = &config.filters;
+    let mut problems = Vec::new();
+    if config
+        .github
+        .webhook_secret
+        .as_ref()
+        .is_some_and(|secret| secret.expose().trim().is_empty())
+    {
+        problems.push(PreflightProblem::BlankWebhookSecret);
+    }
+
+    let global_filters = !filters.ignore_workflows.is_empty() || !filters.only_workflows.is_empty();
+    let repos: BTreeSet<&RepoFullName> = filters
+        .repo_allowlist
+        .iter()
+        .chain(&config.polling.commit_status_repos)
+        .chain(filters.repos.keys())
+        .collect();
+    let checked = join_all(repos.into_iter().map(|repo| async move {
+        let needs_workflows = global_filters || filters.repos.contains_key(repo);
+        (
+            repo,
+            check_repository(github_api, repo, needs_workflows).await,
+        )
+    }))
+    .await;
+    let mut workflows = BTreeMap::new();
+    for (repo, (repo_problems, names)) in checked {
+        problems.extend(repo_problems);
+        if let Some(names) = names {
+            workflows.insert(repo, names);
+        }
+    }
+
+    for (repo, repo_filters) in &filters.repos {
+        if let Some(names) = workflows.get(repo) {
+            for (field, patterns) in [
+                ("ignore_workflows", &repo_filters.ignore_workflows),
+                ("only_workflows", &repo_filters.only_workflows),
+            ] {
+                let key = format!("filters.repos.\"{repo}\".{field}");
+                problems.extend(unmatched(&key, patterns, names));
+            }
+        }
+    }
+    if !workflows.is_empty() {
+        let names: Vec<String> = workflows.into_values().flatten().collect();
+        for (field, patterns) in [
+            ("ignore_workflows", &filters.ignore_workflows),
+            ("only_workflows", &filters.only_workflows),
+        ] {
+            problems.extend(unmatched(&format!("filters.{field}"), patterns, &names));
+        }
+    }
+    problems
+}
+
+/// Fetches `repo`, and its workflow names when `needs_workflows`, unless it is missing.
+async fn check_repository<G: GitHubApi + ?Sized>(
+    github_api: &G,
+    repo: &RepoFullName,
+    needs_workflows: bool,
+) -> (Vec<PreflightProblem>, Option<Vec<String>>) {
+    let mut problems = Vec::new();
+    match github_api.fetch_repository(repo).await {
+        Ok(repository) if repository.full_name() != repo => {
+            problems.push(PreflightProblem::RenamedRepository {
+                repo: repo.clone(),
+                new_name: repository.full_name().clone(),
+            });
+        }
+        Ok(_) => {}
+        Err(GitHubApiError::NotFound { .. }) => {
+            problems.push(PreflightProblem::MissingRepository { repo: repo.clone() });
+            return (problems, None);
+        }
+        Err(error) => {
+            problems.push(PreflightProblem::UncheckedRepository {
+                repo: repo.clone(),
+                error,
+            });
+            return (problems, None);
+        }
+    }
+    if !needs_workflows {
+        return (problems, None);
+    }
+    // Renamed repositories are still found under their old name, as GitHub redirects it
+    match github_api.fetch_workflows(repo).await {
+        Ok(workflows) => {
+            let names = workflows
+                .into_iter()
+                .map(|workflow| workflow.name)
+                .collect();
+            (problems, Some(names))
+        }
+        Err(error) => {
+            problems.push(PreflightProblem::UncheckedRepository {
+                repo: repo.clone(),
+                error,
+            });
+            (problems, None)
+        }
+    }
+}
+
+/// The patterns of `key` matching none of `names`.
+fn unmatched(key: &str, patterns: &[GlobPattern], names: &[String]) -> Vec<PreflightProblem> {
+    patterns
+        .iter()
+        .filter(|pattern| !names.iter().any(|name| pattern.matches(name)))
+        .map(|pattern| PreflightProblem::UnmatchedWorkflowFilter {
+            key: key.to_string(),
+            pattern: pattern.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructures::adapters::secondary::external_apis::GitHubApiAdapter;
+    use crate::infrastructures::config::RepoFiltersConfig;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn config(repo_allowlist: &str) -> anyhow::Result<Config> {
+        let env = |name: &str| match name {
+            "GITHUB_TOKEN" => Some("token".to_string()),
+            "REPO_ALLOWLIST" => Some(repo_allowlist.to_string()),
+            _ => None,
+        };
+        Ok(Config::load(None, &env)?.0)
+    }
+
+    fn repository_json(owner: &str, name: &str) -> serde_json::Value {
+        serde_json::json!({
+            "id": 1_296_269,
+            "name": name,
+            "full_name": format!("{owner}/{name}"),
+            "owner": { "login": owner, "avatar_url": null },
+            "html_url": format!("https://github.com/{owner}/{name}"),
+            "default_branch": "main"
+        })
+    }
+
+    async fn mount_repository(server: &MockServer, owner: &str, name: &str) {
+        Mock::given(method("GET"))
+            .and(path(format!("/repos/{owner}/{name}")))
+            .respond_with(ResponseTemplate::new(200).set_body_json(repository_json(owner, name)))
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_missing_repositories_are_reported_together() -> anyhow::Result<()> {
+        let server = MockServer::start().await;
+        mount_repository(&server, "owner", "repo").await;
+        // Anything else is a 404, as for repositories the token cannot see
+        let adapter = GitHubApiAdapter::new(server.uri(), "token".to_string());
+        let mut config = config("owner/gone,owner/repo")?;
+        config.polling.commit_status_repos = vec!["owner/private".parse()?];
+
+        let problems = check(&adapter, &config).await;
+
+        assert_eq!(
+            problems,
+            vec![
+                PreflightProblem::MissingRepository {
+                    repo: "owner/gone".parse()?
+                },
+                PreflightProblem::MissingRepository {
+                    repo: "owner/private".parse()?
+                },
+            ]
+        );
+        let error = run(&adapter, &config).await.err().map(|e| e.to_string());
+        assert!(error.is_some_and(|error| error.contains("owner/gone does not exist")));
+        config.server.preflight = PreflightMode::Warn;
+        run(&adapter, &config).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_renamed_repositories_are_reported_under_their_new_name() -> anyhow::Result<()> {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/old-name"))
+            .respond_with(
+                ResponseTemplate::new(301)
+                    .insert_header("Location", format!("{}/repositories/1296269", server.uri())),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repositories/1296269"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(repository_json("new-owner", "new-name")),
+            )
+            .mount(&server)
+            .await;
+        let adapter = GitHubApiAdapter::new(server.uri(), "token".to_string());
+
+        let problems = check(&adapter, &config("owner/old-name")?).await;
+
+        assert_eq!(
+            problems,
+            vec![PreflightProblem::RenamedRepository {
+                repo: "owner/old-name".parse()?,
+                new_name: "new-owner/new-name".parse()?,
+            }]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_workflow_filters_must_match_a_workflow() -> anyhow::Result<()> {
+        let server = MockServer::start().await;
+        mount_repository(&server, "owner", "repo").await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/actions/workflows"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "total_count": 1,
+                "workflows": [{
+                    "id": 161_335,
+                    "name": "CI",
+                    "path": ".github/workflows/ci.yml",
+                    "state": "active",
+                    "html_url": "https://github.com/owner/repo/blob/main/.github/workflows/ci.yml",
+                    "badge_url": "https://github.com/owner/repo/workflows/CI/badge.svg"
+                }]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let adapter = GitHubApiAdapter::new(server.uri(), "token".to_string());
+        let mut config = config("owner/repo")?;
+        config.filters.ignore_workflows = vec!["C?".parse()?];
+        config.filters.repos.insert(
+            "owner/repo".parse()?,
+            RepoFiltersConfig {
+                ignore_workflows: Vec::new(),
+                only_workflows: vec!["CI".parse()?, "Deploy *".parse()?],
+            },
+        );
+
+        let problems = check(&adapter, &config).await;
+
+        assert_eq!(
+            problems,
+            vec![PreflightProblem::UnmatchedWorkflowFilter {
+                key: "filters.repos.\"owner/repo\".only_workflows".to_string(),
+                pattern: "Deploy *".to_string(),
+            }]
+        );
+        server.verify().await;
+        Ok(())
+    }
+}
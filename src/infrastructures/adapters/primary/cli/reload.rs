@@ -0,0 +1,274 @@
+use super::notifier;
+use crate::application::services::{NotificationFilter, SharedPoller};
+use crate::domain::external_apis::notifications::NotificationPort;
+use crate::infrastructures::config::{Config, PreflightMode, ServerConfig};
+use crate::infrastructures::telemetry::{LogFilterHandle, TelemetryConfig, parse_filter};
+use anyhow::Context;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, PoisonError};
+use tokio::sync::Notify;
+use tracing::info;
+
+/// Environment variables, and the flags mirroring them, read on each reload.
+pub type OwnedEnv = Box<dyn Fn(&str) -> Option<String> + Send + Sync>;
+
+/// Reads the config file again when asked, e.g. on SIGHUP, and applies what a running server
+/// can change: the polling settings, filters, labels, notification targets and log filter.
+pub struct ConfigReloader {
+    /// `None` when every setting came from the environment, which cannot change
+    path: Option<PathBuf>,
+    env: OwnedEnv,
+    /// The configuration in effect
+    current: Mutex<Config>,
+    log_filter: Option<LogFilterHandle>,
+    /// Targets not set up from the config file, such as Web Push, added to each new notifier
+    kept_targets: Mutex<Vec<Arc<dyn NotificationPort + Send + Sync>>>,
+    requested: Notify,
+}
+
+/// What a successful reload left for a restart.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Reloaded {
+    /// Sections that changed but are only read at startup, e.g. `server` for the bind address
+    pub restart_required: Vec<&'static str>,
+}
+
+/// The sections of `reloaded` that differ from `current` and are only read at startup.
+fn restart_required(current: &Config, reloaded: &Config) -> Vec<&'static str> {
+    // The log filter is the only telemetry setting applied while running
+    let telemetry = |config: &Config| TelemetryConfig {
+        filter: None,
+        ..config.telemetry.clone()
+    };
+    // Preflight only runs at startup, and `--skip-preflight` only overrides the loaded value
+    let server = |config: &Config| ServerConfig {
+        preflight: PreflightMode::default(),
+        ..config.server.clone()
+    };
+    [
+        ("server", server(current) != server(reloaded)),
+        ("github", current.github != reloaded.github),
+        ("history", current.history != reloaded.history),
+        ("replication", current.replication != reloaded.replication),
+        ("push", current.push != reloaded.push),
+        ("costs", current.costs != reloaded.costs),
+        ("slos", current.slos != reloaded.slos),
+        ("tenants", current.tenants != reloaded.tenants),
+        ("telemetry", telemetry(current) != telemetry(reloaded)),
+    ]
+    .into_iter()
+    .filter_map(|(section, changed)| changed.then_some(section))
+    .collect()
+}
+
+impl ConfigReloader {
+    /// Reloads the file at `path` with the variables of `env`, `current` being the
+    /// configuration the server started with.
+    #[must_use]
+    pub fn new(path: Option<PathBuf>, env: OwnedEnv, current: Config) -> Self {
+        Self {
+            path,
+            env,
+            current: Mutex::new(current),
+            log_filter: None,
+            kept_targets: Mutex::default(),
+            requested: Notify::new(),
+        }
+    }
+
+    /// Swaps the log filter through `log_filter` on each reload.
+    #[must_use]
+    pub fn with_log_filter(mut self, log_filter: Option<LogFilterHandle>) -> Self {
+        self.log_filter = log_filter;
+        self
+    }
+
+    /// Keeps notifying `target` of every transition after each reload.
+    pub fn keep_target(&self, target: Arc<dyn NotificationPort + Send + Sync>) {
+        self.kept_targets
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(target);
+    }
+
+    /// Asks [`Self::run`] to reload, e.g. from a signal handler.
+    pub fn request(&self) {
+        self.requested.notify_one();
+    }
+
+    /// Reloads into `poller` each time [`Self::request`] is called, logging the outcome.
+    pub async fn run(&self, poller: &SharedPoller) {
+        loop {
+            self.requested.notified().await;
+            match self.reload(poller) {
+                Ok(reloaded) if reloaded.restart_required.is_empty() => {
+                    info!("Reloaded the configuration");
+                }
+                Ok(reloaded) => tracing::warn!(
+                    "Reloaded the configuration; changes to {} only apply after a restart",
+                    reloaded.restart_required.join(", ")
+                ),
+                Err(e) => tracing::error!(
+                    "Failed to reload the configuration, keeping the previous one: {:#}",
+                    e
+                ),
+            }
+        }
+    }
+
+    /// Logs each request as refused for `reason`, for servers that cannot apply a reload.
+    pub async fn refuse(&self, reason: &str) {
+        loop {
+            self.requested.notified().await;
+            tracing::warn!("Not reloading the configuration: {}", reason);
+        }
+    }
+
+    /// Reads and checks the config file like at startup, then applies it to `poller`.
+    ///
+    /// # Errors
+    ///
+    /// Returns why the file cannot be read or is invalid, including a poll budget over
+    /// GitHub's rate limit with the runs pinned now; nothing is applied then.
+    pub fn reload(&self, poller: &SharedPoller) -> anyhow::Result<Reloaded> {
+        let path = self
+            .path
+            .as_deref()
+            .context("No config file to reload, settings only come from the environment")?;
+        let (config, unknown_keys) = Config::load(Some(path), &*self.env)?;
+        for key in &unknown_keys {
+            tracing::warn!("Ignoring unknown key {} in {}", key, path.display());
+        }
+        let stream_config = config.stream_config()?;
+        let notifier = self
+            .kept_targets
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .fold(notifier(&config)?, |notifier, target| {
+                notifier.with_target(Arc::clone(target), NotificationFilter::default())
+            });
+        let log_filter = parse_filter(&config.telemetry)?;
+
+        let mut current = self.current.lock().unwrap_or_else(PoisonError::into_inner);
+        // Checked against the runs pinned now, which startup validation cannot know of
+        poller.replace_config(stream_config)?;
+        if let Some(handle) = &self.log_filter {
+            handle.set(log_filter)?;
+        }
+        poller.replace_notifier((!notifier.is_empty()).then(|| Arc::new(notifier)));
+        // The other sections stay as the server started, so later reloads still report them
+        let restart_required = restart_required(&current, &config);
+        current.polling = config.polling;
+        current.filters = config.filters;
+        current.labels = config.labels;
+        current.notifications = config.notifications;
+        current.telemetry.filter = config.telemetry.filter;
+        Ok(Reloaded { restart_required })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::services::PinnedRun;
+    use crate::domain::models::ids::RunId;
+    use std::sync::Arc;
+
+    fn reloader(path: PathBuf) -> anyhow::Result<(ConfigReloader, SharedPoller)> {
+        let env: OwnedEnv = Box::new(|name: &str| (name == "GITHUB_TOKEN").then(|| "t".into()));
+        let (config, _) = Config::load(Some(&path), &*env)?;
+        let poller = SharedPoller::new(Arc::new(Notify::new()));
+        poller.replace_config(config.stream_config()?)?;
+        Ok((ConfigReloader::new(Some(path), env, config), poller))
+    }
+
+    #[test]
+    fn test_reload_applies_the_new_poll_interval() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("gha-dashboard.toml");
+        std::fs::write(&path, "[polling]\ninterval_seconds = 30\n")?;
+        let (reloader, poller) = reloader(path.clone())?;
+
+        std::fs::write(
+            &path,
+            "[polling]\ninterval_seconds = 120\n\n[filters]\nrepo_allowlist = [\"owner/repo\"]\n",
+        )?;
+        let outcome = reloader.reload(&poller)?;
+
+        assert_eq!(outcome, Reloaded::default());
+        let config = poller.config();
+        assert_eq!(config.poll_interval_seconds, 120);
+        assert_eq!(config.repo_allowlist, ["owner/repo".parse()?]);
+
+        std::fs::write(
+            &path,
+            "[server]\nbind_addr = \"127.0.0.1:4000\"\n\n[polling]\ninterval_seconds = 60\n",
+        )?;
+        let outcome = reloader.reload(&poller)?;
+
+        assert_eq!(outcome.restart_required, ["server"]);
+        assert_eq!(poller.config().poll_interval_seconds, 60);
+        // The server still listens on the address it started with
+        assert_eq!(reloader.reload(&poller)?.restart_required, ["server"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rejected_reload_keeps_the_previous_config() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("gha-dashboard.toml");
+        std::fs::write(&path, "[polling]\ninterval_seconds = 30\n")?;
+        let (reloader, poller) = reloader(path.clone())?;
+        let before = poller.config();
+
+        std::fs::write(
+            &path,
+            "[polling]\ninterval_seconds = 1\n\n[filters]\nrepo_allowlist = [\"owner/repo\"]\n",
+        )?;
+        let error = reloader.reload(&poller);
+
+        assert!(
+            error
+                .as_ref()
+                .is_err_and(|e| format!("{e:#}").contains("pollIntervalSeconds")),
+            "{error:?}"
+        );
+        assert_eq!(poller.config(), before);
+
+        // A later valid reload still compares against the config in effect
+        std::fs::write(&path, "[polling]\ninterval_seconds = 45\n")?;
+        assert_eq!(reloader.reload(&poller)?, Reloaded::default());
+        assert_eq!(poller.config().poll_interval_seconds, 45);
+        Ok(())
+    }
+
+    #[test]
+    fn test_reload_over_budget_with_the_pinned_runs_is_rejected() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("gha-dashboard.toml");
+        std::fs::write(&path, "[polling]\ninterval_seconds = 30\n")?;
+        let (reloader, poller) = reloader(path.clone())?;
+        for run_id in 0..20 {
+            poller
+                .pin_run(PinnedRun {
+                    repo: "owner/repo".parse()?,
+                    run_id: RunId(run_id),
+                })
+                .map_err(|calls| anyhow::anyhow!("{calls} calls per hour"))?;
+        }
+
+        // Within budget on its own, but not with 20 pinned runs fetched twice as often
+        std::fs::write(&path, "[polling]\ninterval_seconds = 15\n")?;
+        let error = reloader.reload(&poller);
+
+        assert!(
+            error
+                .as_ref()
+                .is_err_and(|e| format!("{e:#}").contains("GitHub API calls per hour")),
+            "{error:?}"
+        );
+        assert_eq!(poller.config().poll_interval_seconds, 30);
+        Ok(())
+    }
+}
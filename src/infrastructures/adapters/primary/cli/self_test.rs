@@ -0,0 +1,295 @@
+use super::super::web::presenter::snapshot_json;
+use crate::application::use_cases::stream_github_actions_runs::config::StreamConfigPatch;
+use crate::application::use_cases::stream_github_actions_runs::{
+    StreamGitHubActionsRunsInteractor, StreamGitHubActionsRunsUseCase,
+    StreamGitHubActionsRunsUseCaseInput, StreamGitHubActionsRunsUseCaseOutput,
+};
+use crate::domain::external_apis::github::GitHubApi;
+use crate::domain::external_apis::github::calls::ApiCallRecorder;
+use crate::domain::models::ids::RepoFullName;
+use crate::infrastructures::config::Config;
+use anyhow::Context;
+use futures_util::StreamExt;
+use std::io::Write;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+/// How long the whole self-test may take before it fails
+pub const SELF_TEST_TIMEOUT: Duration = Duration::from_mins(1);
+
+/// Runs fetched for the one repository polled, keeping the iteration to a few requests
+const SELF_TEST_MAX_RUNS: u64 = 5;
+
+/// Steps in the order they run, each relying on the ones before it
+const STEPS: [&str; 5] = ["config", "credentials", "repositories", "runs", "serialize"];
+
+/// `--check`: one bounded iteration of the poller, writing a line to `out` per step with
+/// its latency and result, then a summary.
+///
+/// Only the first repository is polled, for [`SELF_TEST_MAX_RUNS`] runs and without the
+/// upcoming scheduled runs and commit statuses, so the iteration costs a handful of
+/// requests whatever the config. Steps after a failing one are skipped.
+///
+/// # Errors
+///
+/// Returns an error if a step fails, the whole run exceeds [`SELF_TEST_TIMEOUT`], or `out`
+/// cannot be written.
+pub async fn run<G>(github_api: Arc<G>, config: &Config, out: &mut impl Write) -> anyhow::Result<()>
+where
+    G: GitHubApi + Send + Sync + ?Sized + 'static,
+{
+    let started = Instant::now();
+    let calls = ApiCallRecorder::default();
+    let mut report = Report { out, done: 0 };
+    let steps = steps(github_api, config, &calls, &mut report);
+    let passed = if let Ok(passed) = tokio::time::timeout(SELF_TEST_TIMEOUT, steps).await {
+        passed?
+    } else {
+        // The step cut short wrote nothing yet
+        let name = STEPS
+            .get(report.done.saturating_sub(1))
+            .copied()
+            .unwrap_or_default();
+        writeln!(
+            report.out,
+            "FAIL  {name:<12}  {:>5} ms  Timed out after {}s",
+            started.elapsed().as_millis(),
+            SELF_TEST_TIMEOUT.as_secs()
+        )?;
+        false
+    };
+    for name in STEPS.iter().skip(report.done) {
+        writeln!(report.out, "skip  {name}")?;
+    }
+    let elapsed = started.elapsed().as_millis();
+    let total = calls.counts().total;
+    if !passed {
+        anyhow::bail!("Self-test failed after {elapsed} ms and {total} GitHub API calls");
+    }
+    writeln!(
+        report.out,
+        "Self-test passed in {elapsed} ms with {total} GitHub API calls"
+    )?;
+    Ok(())
+}
+
+/// Runs every step, returning whether all of them passed.
+async fn steps<G>(
+    github_api: Arc<G>,
+    config: &Config,
+    calls: &ApiCallRecorder,
+    report: &mut Report<'_, impl Write>,
+) -> anyhow::Result<bool>
+where
+    G: GitHubApi + Send + Sync + ?Sized + 'static,
+{
+    let Some(stream_config) = report
+        .step(async {
+            let stream_config = config.stream_config()?;
+            let detail = format!("polling every {}s", stream_config.poll_interval_seconds);
+            Ok((stream_config, detail))
+        })
+        .await?
+    else {
+        return Ok(false);
+    };
+
+    let credentials = report.step(calls.scope(async {
+        let rate_limit = github_api
+            .fetch_rate_limit()
+            .await
+            .context("GitHub rejected the token")?;
+        let detail = format!(
+            "token accepted, {} of {} requests left",
+            rate_limit.remaining, rate_limit.limit
+        );
+        Ok(((), detail))
+    }));
+    if credentials.await?.is_none() {
+        return Ok(false);
+    }
+
+    let repository = report.step(calls.scope(async {
+        let repository = match stream_config.repo_allowlist.first() {
+            Some(repository) => repository.clone(),
+            None => github_api
+                .fetch_repositories(1)
+                .await?
+                .first()
+                .map(|repository| repository.full_name().clone())
+                .context("GitHub returned no repositories")?,
+        };
+        let detail = format!("polling {repository}");
+        Ok((repository, detail))
+    }));
+    let Some(repository) = repository.await? else {
+        return Ok(false);
+    };
+
+    let Some(output) = report
+        .step(async {
+            let output = first_snapshot(github_api, config, &repository).await?;
+            if let Some(counts) = &output.github_calls {
+                calls.add_counts(counts);
+            }
+            let detail = format!("{} runs of {repository}", output.runs.len());
+            Ok((output, detail))
+        })
+        .await?
+    else {
+        return Ok(false);
+    };
+
+    let serialized = report
+        .step(async {
+            let json = snapshot_json(&output, config.server.timestamp_format)?;
+            Ok(((), format!("{} bytes of JSON", json.len())))
+        })
+        .await?;
+    Ok(serialized.is_some())
+}
+
+/// The first snapshot the interactor takes of `repository` alone.
+async fn first_snapshot<G>(
+    github_api: Arc<G>,
+    config: &Config,
+    repository: &RepoFullName,
+) -> anyhow::Result<StreamGitHubActionsRunsUseCaseOutput>
+where
+    G: GitHubApi + Send + Sync + ?Sized + 'static,
+{
+    let stream_config = config.stream_config()?.apply(&StreamConfigPatch {
+        max_runs_per_repo: Some(SELF_TEST_MAX_RUNS),
+        repo_allowlist: Some(vec![repository.to_string()]),
+        upcoming_scheduled_runs: Some(false),
+        commit_status_repos: Some(Vec::new()),
+        ..StreamConfigPatch::default()
+    })?;
+    let input = StreamGitHubActionsRunsUseCaseInput {
+        config: watch::channel(stream_config).1,
+        ..StreamGitHubActionsRunsUseCaseInput::default()
+    };
+    let interactor = StreamGitHubActionsRunsInteractor::new(github_api);
+    let stream = interactor.execute(input);
+    tokio::pin!(stream);
+    Ok(stream.next().await.context("No snapshot was taken")??)
+}
+
+/// Lines written so far, one per step.
+struct Report<'a, W> {
+    out: &'a mut W,
+    /// Steps already run, indexing [`STEPS`]
+    done: usize,
+}
+
+impl<W: Write> Report<'_, W> {
+    /// Runs the next step, writing its latency and the detail it returns or its error.
+    ///
+    /// Returns `None` if the step failed.
+    async fn step<T>(
+        &mut self,
+        check: impl Future<Output = anyhow::Result<(T, String)>>,
+    ) -> anyhow::Result<Option<T>> {
+        let name = STEPS.get(self.done).copied().unwrap_or_default();
+        self.done += 1;
+        let started = Instant::now();
+        let result = check.await;
+        let elapsed = started.elapsed().as_millis();
+        match result {
+            Ok((value, detail)) => {
+                writeln!(self.out, "ok    {name:<12}  {elapsed:>5} ms  {detail}")?;
+                Ok(Some(value))
+            }
+            Err(e) => {
+                writeln!(self.out, "FAIL  {name:<12}  {elapsed:>5} ms  {e:#}")?;
+                Ok(None)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::external_apis::github::{GitHubApiError, RateLimit};
+    use crate::test_support::{StubGitHubApi, base_time, workflow_run};
+
+    fn config() -> anyhow::Result<Config> {
+        let env = |name: &str| (name == "GITHUB_TOKEN").then(|| "token".to_string());
+        Ok(Config::load(None, &env)?.0)
+    }
+
+    /// The lines of `out` without the latencies, which vary between runs.
+    fn lines(out: Vec<u8>) -> anyhow::Result<Vec<String>> {
+        Ok(String::from_utf8(out)?
+            .lines()
+            .map(|line| match (line.get(..18), line.split_once(" ms  ")) {
+                (Some(step), Some((_, detail))) => format!("{step}  {detail}"),
+                _ => line.to_string(),
+            })
+            .collect())
+    }
+
+    #[tokio::test]
+    async fn test_every_step_passes() -> anyhow::Result<()> {
+        let github_api = StubGitHubApi {
+            runs: vec![
+                workflow_run("owner/repo", 1, "success"),
+                workflow_run("owner/repo", 2, "failure"),
+            ],
+            rate_limit: RateLimit {
+                limit: 5_000,
+                remaining: 4_990,
+                used: 10,
+                reset: base_time(),
+            },
+            ..StubGitHubApi::with_repository("owner", "repo")
+        };
+        let mut out = Vec::new();
+
+        run(Arc::new(github_api), &config()?, &mut out).await?;
+
+        let lines = lines(out)?;
+        assert_eq!(
+            lines[..4],
+            [
+                "ok    config        polling every 30s",
+                "ok    credentials   token accepted, 4990 of 5000 requests left",
+                "ok    repositories  polling owner/repo",
+                "ok    runs          2 runs of owner/repo",
+            ]
+        );
+        assert!(lines[4].starts_with("ok    serialize     "), "{}", lines[4]);
+        assert!(lines[5].starts_with("Self-test passed in "), "{}", lines[5]);
+        assert_eq!(lines.len(), 6);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_steps_after_a_failing_one_are_skipped() -> anyhow::Result<()> {
+        let github_api = StubGitHubApi {
+            error: Some(GitHubApiError::Unauthorized {
+                resource: "rate_limit".to_string(),
+            }),
+            ..StubGitHubApi::with_repository("owner", "repo")
+        };
+        let mut out = Vec::new();
+
+        let result = run(Arc::new(github_api), &config()?, &mut out).await;
+
+        assert!(result.is_err());
+        let lines = lines(out)?;
+        assert_eq!(lines[0], "ok    config        polling every 30s");
+        assert!(
+            lines[1].starts_with("FAIL  credentials   GitHub rejected the token: "),
+            "{}",
+            lines[1]
+        );
+        assert_eq!(
+            lines[2..],
+            ["skip  repositories", "skip  runs", "skip  serialize"]
+        );
+        Ok(())
+    }
+}
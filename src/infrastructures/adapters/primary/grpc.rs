@@ -0,0 +1,435 @@
+pub mod mapping;
+pub mod proto;
+
+use crate::application::services::{CatchUp, PollerEvent, SequencedEvent, SharedPoller};
+use crate::domain::external_apis::github::{GitHubApi, GitHubApiError};
+use crate::domain::models::ids::{RepoFullName, RunId};
+use crate::domain::models::matrix::MatrixGroup;
+use futures_util::Stream;
+use mapping::runs_snapshot;
+use std::convert::Infallible;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::sync::broadcast;
+use tonic::codegen::{Body, BoxFuture, Service, StdError, http};
+use tonic::server::{Grpc, NamedService, ServerStreamingService, UnaryService};
+use tonic::transport::server::TcpIncoming;
+use tonic::{Request, Response, Status};
+use tonic_prost::ProstCodec;
+
+/// Package and name of the service in `proto/gha_dashboard/v1/runs.proto`
+pub const SERVICE_NAME: &str = "gha_dashboard.v1.Runs";
+
+type SnapshotStream = Pin<Box<dyn Stream<Item = Result<proto::RunsSnapshot, Status>> + Send>>;
+
+/// The `Runs` gRPC service, answered from the same poller and GitHub API as the HTTP routes.
+#[derive(Clone)]
+pub struct RunsService {
+    poller: Arc<SharedPoller>,
+    github_api: Arc<dyn GitHubApi + Send + Sync>,
+}
+
+impl RunsService {
+    #[must_use]
+    pub fn new(poller: Arc<SharedPoller>, github_api: Arc<dyn GitHubApi + Send + Sync>) -> Self {
+        Self { poller, github_api }
+    }
+
+    /// `WatchRuns`: what the client missed, or the latest snapshot, then every new snapshot
+    /// until the server shuts down, numbered like `/ws` frames.
+    #[must_use]
+    pub fn watch_runs(&self, request: &proto::WatchRequest) -> SnapshotStream {
+        let (mut events, catch_up) = self.poller.snapshots().attach(request.since_seq);
+        let (missed, resync) = match catch_up {
+            CatchUp::Replay(missed) => (missed, false),
+            CatchUp::Snapshot { latest, resync } => (latest.into_iter().collect(), resync),
+        };
+        Box::pin(async_stream::stream! {
+            for event in &missed {
+                if let Some(snapshot) = snapshot_message(event, resync) {
+                    yield Ok(snapshot);
+                }
+            }
+            loop {
+                match events.recv().await {
+                    Ok(SequencedEvent { event: PollerEvent::Shutdown, .. }) => {
+                        yield Err(Status::unavailable("Server is shutting down, reconnect"));
+                        break;
+                    }
+                    Ok(event) => {
+                        if let Some(snapshot) = snapshot_message(&event, false) {
+                            yield Ok(snapshot);
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!("gRPC client lagged behind, skipped {} updates", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    /// `ListRuns`: the latest snapshot.
+    ///
+    /// # Errors
+    ///
+    /// Returns `UNAVAILABLE` before the first snapshot has been fetched.
+    pub fn list_runs(&self) -> Result<proto::RunsSnapshot, Status> {
+        self.poller
+            .snapshots()
+            .latest()
+            .map(|output| runs_snapshot(&output, 0, false))
+            .ok_or_else(|| Status::unavailable("No snapshot has been fetched yet"))
+    }
+
+    /// `GetRunDetail`: the run as of the latest snapshot, with its jobs fetched from GitHub.
+    ///
+    /// # Errors
+    ///
+    /// Returns `INVALID_ARGUMENT` for a malformed repository name, otherwise the status
+    /// matching the GitHub API error.
+    pub async fn get_run_detail(
+        &self,
+        request: proto::GetRunDetailRequest,
+    ) -> Result<proto::RunDetail, Status> {
+        let repo: RepoFullName = request
+            .repository_name
+            .parse()
+            .map_err(|e| Status::invalid_argument(format!("{e}")))?;
+        let run_id = RunId(request.run_id);
+        let jobs = self
+            .github_api
+            .fetch_workflow_jobs(&repo, run_id)
+            .await
+            .map_err(|e| {
+                github_status(&e, &format!("Workflow run {repo}#{run_id} was not found"))
+            })?;
+        let run = self.poller.snapshots().latest().and_then(|output| {
+            output
+                .runs
+                .iter()
+                .find(|run| run.id == run_id && run.repository_name.eq_ignore_ascii_case(&repo))
+                .map(Into::into)
+        });
+        Ok(proto::RunDetail {
+            run,
+            jobs: jobs.iter().map(Into::into).collect(),
+            matrix_groups: MatrixGroup::from_jobs(&jobs)
+                .iter()
+                .map(Into::into)
+                .collect(),
+        })
+    }
+}
+
+/// A snapshot event as a message; `None` for events not forwarded to gRPC clients.
+fn snapshot_message(event: &SequencedEvent, resync: bool) -> Option<proto::RunsSnapshot> {
+    match &event.event {
+        PollerEvent::Snapshot(output) => Some(runs_snapshot(output, event.seq, resync)),
+        PollerEvent::Error(_) | PollerEvent::Notice(_) | PollerEvent::Shutdown => None,
+    }
+}
+
+/// The gRPC counterpart of `github_error_response`.
+fn github_status(error: &GitHubApiError, not_found: &str) -> Status {
+    match error {
+        GitHubApiError::NotFound { .. } => Status::not_found(not_found),
+        GitHubApiError::RateLimited { .. } => Status::unavailable("GitHub API rate limit exceeded"),
+        GitHubApiError::Forbidden { .. } => Status::permission_denied(error.to_string()),
+        GitHubApiError::InvalidWorkflow { .. } => Status::failed_precondition(error.to_string()),
+        GitHubApiError::Network { .. } | GitHubApiError::Timeout { .. } => {
+            tracing::error!("{}", error);
+            Status::deadline_exceeded(format!("Error: {error}"))
+        }
+        GitHubApiError::Unauthorized { .. }
+        | GitHubApiError::Decode { .. }
+        | GitHubApiError::Other { .. } => {
+            tracing::error!("{}", error);
+            Status::internal(format!("Error: {error}"))
+        }
+    }
+}
+
+struct WatchRuns(RunsService);
+
+impl ServerStreamingService<proto::WatchRequest> for WatchRuns {
+    type Response = proto::RunsSnapshot;
+    type ResponseStream = SnapshotStream;
+    type Future = BoxFuture<Response<SnapshotStream>, Status>;
+
+    fn call(&mut self, request: Request<proto::WatchRequest>) -> Self::Future {
+        let stream = self.0.watch_runs(request.get_ref());
+        Box::pin(async move { Ok(Response::new(stream)) })
+    }
+}
+
+struct ListRuns(RunsService);
+
+impl UnaryService<proto::ListRunsRequest> for ListRuns {
+    type Response = proto::RunsSnapshot;
+    type Future = BoxFuture<Response<proto::RunsSnapshot>, Status>;
+
+    fn call(&mut self, _request: Request<proto::ListRunsRequest>) -> Self::Future {
+        let snapshot = self.0.list_runs().map(Response::new);
+        Box::pin(async move { snapshot })
+    }
+}
+
+struct GetRunDetail(RunsService);
+
+impl UnaryService<proto::GetRunDetailRequest> for GetRunDetail {
+    type Response = proto::RunDetail;
+    type Future = BoxFuture<Response<proto::RunDetail>, Status>;
+
+    fn call(&mut self, request: Request<proto::GetRunDetailRequest>) -> Self::Future {
+        let service = self.0.clone();
+        Box::pin(async move {
+            service
+                .get_run_detail(request.into_inner())
+                .await
+                .map(Response::new)
+        })
+    }
+}
+
+/// Routes `/gha_dashboard.v1.Runs/<method>` to the methods, as `tonic-build` would generate.
+impl<B> Service<http::Request<B>> for RunsService
+where
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+{
+    type Response = http::Response<tonic::body::Body>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        let service = self.clone();
+        match request.uri().path() {
+            "/gha_dashboard.v1.Runs/WatchRuns" => Box::pin(async move {
+                let mut grpc = Grpc::new(ProstCodec::default());
+                Ok(grpc.server_streaming(WatchRuns(service), request).await)
+            }),
+            "/gha_dashboard.v1.Runs/ListRuns" => Box::pin(async move {
+                let mut grpc = Grpc::new(ProstCodec::default());
+                Ok(grpc.unary(ListRuns(service), request).await)
+            }),
+            "/gha_dashboard.v1.Runs/GetRunDetail" => Box::pin(async move {
+                let mut grpc = Grpc::new(ProstCodec::default());
+                Ok(grpc.unary(GetRunDetail(service), request).await)
+            }),
+            _ => Box::pin(async move { Ok(Status::unimplemented("").into_http()) }),
+        }
+    }
+}
+
+impl NamedService for RunsService {
+    const NAME: &'static str = SERVICE_NAME;
+}
+
+/// Serves `service` on `incoming` until the poller disconnects its clients for shutdown.
+///
+/// # Errors
+///
+/// Returns an error if the server stops with an I/O error.
+pub async fn serve(incoming: TcpIncoming, service: RunsService) -> anyhow::Result<()> {
+    let mut events = service.poller.subscribe();
+    let shutdown = async move {
+        while let Ok(event) = events.recv().await {
+            if matches!(event, PollerEvent::Shutdown) {
+                break;
+            }
+        }
+    };
+    tonic::transport::Server::builder()
+        .add_service(service)
+        .serve_with_incoming_shutdown(incoming, shutdown)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::use_cases::StreamGitHubActionsRunsUseCaseOutput;
+    use crate::test_support::{StubGitHubApi, app_state, base_time, workflow_run};
+    use futures_util::StreamExt;
+    use tonic::transport::Channel;
+
+    /// A client of the `Runs` service on `channel`, as `tonic-build` would generate it.
+    struct RunsClient(tonic::client::Grpc<Channel>);
+
+    impl RunsClient {
+        async fn ready(&mut self) -> anyhow::Result<()> {
+            self.0.ready().await.map_err(|e| anyhow::anyhow!("{e}"))
+        }
+
+        async fn watch_runs(
+            &mut self,
+            since_seq: Option<u64>,
+        ) -> anyhow::Result<tonic::Streaming<proto::RunsSnapshot>> {
+            self.ready().await?;
+            let path = http::uri::PathAndQuery::from_static("/gha_dashboard.v1.Runs/WatchRuns");
+            Ok(self
+                .0
+                .server_streaming(
+                    Request::new(proto::WatchRequest { since_seq }),
+                    path,
+                    ProstCodec::default(),
+                )
+                .await?
+                .into_inner())
+        }
+
+        async fn get_run_detail(
+            &mut self,
+            repository_name: &str,
+            run_id: u64,
+        ) -> Result<proto::RunDetail, Status> {
+            self.ready()
+                .await
+                .map_err(|e| Status::unknown(e.to_string()))?;
+            let path = http::uri::PathAndQuery::from_static("/gha_dashboard.v1.Runs/GetRunDetail");
+            let request = proto::GetRunDetailRequest {
+                repository_name: repository_name.to_string(),
+                run_id,
+            };
+            Ok(self
+                .0
+                .unary(Request::new(request), path, ProstCodec::default())
+                .await?
+                .into_inner())
+        }
+    }
+
+    async fn serve_runs(service: RunsService) -> anyhow::Result<RunsClient> {
+        let incoming = TcpIncoming::bind("127.0.0.1:0".parse()?)?;
+        let url = format!("http://{}", incoming.local_addr()?);
+        tokio::spawn(serve(incoming, service));
+        let channel = Channel::from_shared(url)?.connect().await?;
+        Ok(RunsClient(tonic::client::Grpc::new(channel)))
+    }
+
+    fn snapshot(
+        runs: Vec<crate::domain::models::run::WorkflowRun>,
+    ) -> StreamGitHubActionsRunsUseCaseOutput {
+        StreamGitHubActionsRunsUseCaseOutput::new(runs, base_time())
+    }
+
+    #[tokio::test]
+    async fn test_watch_runs_resumes_like_the_websocket_and_follows_new_snapshots()
+    -> anyhow::Result<()> {
+        let state = app_state(StubGitHubApi::default(), None)?;
+        state.poller.publish(snapshot(Vec::new()));
+        state
+            .poller
+            .publish(snapshot(vec![workflow_run("owner/repo", 1, "queued")]));
+        let mut client = serve_runs(RunsService::new(
+            Arc::clone(&state.poller),
+            Arc::clone(&state.github_api),
+        ))
+        .await?;
+
+        let mut fresh = client.watch_runs(None).await?;
+        let latest = fresh
+            .message()
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("no snapshot"))?;
+        assert_eq!(
+            (latest.seq, latest.resync, latest.runs.len()),
+            (2, false, 1)
+        );
+        let mut resumed = client.watch_runs(Some(1)).await?;
+        assert_eq!(
+            resumed.message().await?.map(|snapshot| snapshot.seq),
+            Some(2)
+        );
+        let mut unknown = client.watch_runs(Some(1000)).await?;
+        let resync = unknown
+            .message()
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("no snapshot"))?;
+        assert_eq!((resync.seq, resync.resync), (2, true));
+
+        state
+            .poller
+            .publish(snapshot(vec![workflow_run("owner/repo", 1, "success")]));
+        for stream in [&mut fresh, &mut resumed] {
+            let live = stream
+                .message()
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("no snapshot"))?;
+            assert_eq!(
+                (live.seq, live.runs[0].display_status.as_str()),
+                (3, "success")
+            );
+        }
+
+        state.poller.disconnect_clients();
+        let ended = fresh.next().await;
+        assert!(
+            matches!(&ended, Some(Err(status)) if status.code() == tonic::Code::Unavailable),
+            "{ended:?}"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_run_details_come_with_the_run_and_its_jobs() -> anyhow::Result<()> {
+        let state = app_state(
+            StubGitHubApi {
+                jobs: Some(Vec::new()),
+                ..StubGitHubApi::default()
+            },
+            None,
+        )?;
+        state
+            .poller
+            .publish(snapshot(vec![workflow_run("owner/repo", 42, "success")]));
+        let mut client = serve_runs(RunsService::new(
+            Arc::clone(&state.poller),
+            Arc::clone(&state.github_api),
+        ))
+        .await?;
+
+        let detail = client.get_run_detail("Owner/Repo", 42).await?;
+        assert_eq!(detail.run.map(|run| run.id), Some(42));
+        let unlisted = client.get_run_detail("owner/repo", 7).await?;
+        assert_eq!(unlisted.run, None);
+
+        let invalid = client.get_run_detail("not a repository", 42).await;
+        assert!(
+            matches!(&invalid, Err(status) if status.code() == tonic::Code::InvalidArgument),
+            "{invalid:?}"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unknown_runs_are_not_found() -> anyhow::Result<()> {
+        let state = app_state(StubGitHubApi::default(), None)?;
+        let service = RunsService::new(Arc::clone(&state.poller), Arc::clone(&state.github_api));
+
+        let missing = service
+            .get_run_detail(proto::GetRunDetailRequest {
+                repository_name: "owner/repo".to_string(),
+                run_id: 42,
+            })
+            .await;
+
+        assert!(
+            matches!(&missing, Err(status) if status.code() == tonic::Code::NotFound),
+            "{missing:?}"
+        );
+        assert_eq!(
+            service.list_runs().map_err(|status| status.code()),
+            Err(tonic::Code::Unavailable)
+        );
+        Ok(())
+    }
+}
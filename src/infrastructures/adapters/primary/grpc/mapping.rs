@@ -0,0 +1,334 @@
+use super::proto;
+use crate::application::use_cases::StreamGitHubActionsRunsUseCaseOutput;
+use crate::domain::models::actor::RunActor;
+use crate::domain::models::ids::{RunId, WorkflowId};
+use crate::domain::models::job::{Job, Step};
+use crate::domain::models::matrix::{MatrixCell, MatrixGroup};
+use crate::domain::models::run::WorkflowRun;
+use crate::domain::models::status::{RunConclusion, RunStatus};
+use chrono::{DateTime, Utc};
+
+/// A message that does not describe a valid domain value.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("invalid {field}: {message}")]
+pub struct InvalidMessage {
+    pub field: &'static str,
+    pub message: String,
+}
+
+impl InvalidMessage {
+    fn new(field: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            field,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<DateTime<Utc>> for proto::Timestamp {
+    fn from(time: DateTime<Utc>) -> Self {
+        Self {
+            seconds: time.timestamp(),
+            // Below 2e9 even during a leap second, so it always fits
+            nanos: i32::try_from(time.timestamp_subsec_nanos()).unwrap_or(i32::MAX),
+        }
+    }
+}
+
+impl TryFrom<proto::Timestamp> for DateTime<Utc> {
+    type Error = InvalidMessage;
+
+    fn try_from(timestamp: proto::Timestamp) -> Result<Self, Self::Error> {
+        u32::try_from(timestamp.nanos)
+            .ok()
+            .and_then(|nanos| DateTime::from_timestamp(timestamp.seconds, nanos))
+            .ok_or_else(|| InvalidMessage::new("timestamp", format!("{timestamp:?}")))
+    }
+}
+
+impl From<&RunActor> for proto::Actor {
+    fn from(actor: &RunActor) -> Self {
+        Self {
+            login: actor.login.clone(),
+            avatar_url: actor.avatar_url.clone(),
+            html_url: actor.html_url.clone(),
+        }
+    }
+}
+
+impl From<proto::Actor> for RunActor {
+    fn from(actor: proto::Actor) -> Self {
+        Self {
+            login: actor.login,
+            avatar_url: actor.avatar_url,
+            html_url: actor.html_url,
+        }
+    }
+}
+
+/// Carries the computed `displayStatus` and `durationSeconds` of the JSON along.
+impl From<&WorkflowRun> for proto::WorkflowRun {
+    fn from(run: &WorkflowRun) -> Self {
+        Self {
+            repository_name: run.repository_name.to_string(),
+            id: run.id.0,
+            run_number: run.run_number,
+            workflow_id: run.workflow_id.0,
+            workflow_name: run.workflow_name.clone(),
+            display_title: run.display_title.clone(),
+            full_display_title: run.full_display_title.clone(),
+            event: run.event.clone(),
+            head_branch: run.head_branch.clone(),
+            head_sha: run.head_sha.clone(),
+            actor: Some((&run.actor).into()),
+            triggering_actor: run.triggering_actor.as_ref().map(Into::into),
+            status: run.status.as_str().to_string(),
+            conclusion: run
+                .conclusion
+                .map(|conclusion| conclusion.as_str().to_string()),
+            display_status: run.display_status().to_string(),
+            duration_seconds: run.duration_seconds(),
+            created_at: Some(run.created_at.into()),
+            updated_at: Some(run.updated_at.into()),
+            html_url: run.html_url.clone(),
+            labels: run.labels.clone(),
+            repository_active: Some(run.repository_active),
+            is_required_check: run.is_required_check,
+            failed_jobs: run.failed_jobs.clone(),
+            failed_job_url: run.failed_job_url.clone(),
+        }
+    }
+}
+
+/// For clients decoding runs; the computed fields are derived again, not read.
+impl TryFrom<proto::WorkflowRun> for WorkflowRun {
+    type Error = InvalidMessage;
+
+    fn try_from(run: proto::WorkflowRun) -> Result<Self, Self::Error> {
+        let time = |field, timestamp: Option<proto::Timestamp>| {
+            timestamp
+                .ok_or_else(|| InvalidMessage::new(field, "missing"))?
+                .try_into()
+                .map_err(|e: InvalidMessage| InvalidMessage::new(field, e.message))
+        };
+        Ok(Self {
+            repository_name: run
+                .repository_name
+                .parse()
+                .map_err(|e| InvalidMessage::new("repository_name", format!("{e}")))?,
+            id: RunId(run.id),
+            run_number: run.run_number,
+            workflow_id: WorkflowId(run.workflow_id),
+            workflow_name: run.workflow_name,
+            display_title: run.display_title,
+            full_display_title: run.full_display_title,
+            event: run.event,
+            head_branch: run.head_branch,
+            head_sha: run.head_sha,
+            actor: run
+                .actor
+                .ok_or_else(|| InvalidMessage::new("actor", "missing"))?
+                .into(),
+            triggering_actor: run.triggering_actor.map(Into::into),
+            // Unrecognized spellings parse to `Unknown` like in serde
+            status: run.status.parse().unwrap_or(RunStatus::Unknown),
+            conclusion: run
+                .conclusion
+                .map(|conclusion| conclusion.parse().unwrap_or(RunConclusion::Unknown)),
+            created_at: time("created_at", run.created_at)?,
+            updated_at: time("updated_at", run.updated_at)?,
+            html_url: run.html_url,
+            labels: run.labels,
+            repository_active: run.repository_active.unwrap_or(true),
+            is_required_check: run.is_required_check,
+            failed_jobs: run.failed_jobs,
+            failed_job_url: run.failed_job_url,
+        })
+    }
+}
+
+impl From<&Step> for proto::Step {
+    fn from(step: &Step) -> Self {
+        Self {
+            number: step.number,
+            name: step.name.clone(),
+            status: step.status.as_str().to_string(),
+            conclusion: step
+                .conclusion
+                .map(|conclusion| conclusion.as_str().to_string()),
+            started_at: step.started_at.map(Into::into),
+            completed_at: step.completed_at.map(Into::into),
+        }
+    }
+}
+
+impl From<&Job> for proto::Job {
+    fn from(job: &Job) -> Self {
+        Self {
+            id: job.id.0,
+            run_id: job.run_id.0,
+            name: job.name.clone(),
+            status: job.status.as_str().to_string(),
+            conclusion: job
+                .conclusion
+                .map(|conclusion| conclusion.as_str().to_string()),
+            started_at: job.started_at.map(Into::into),
+            completed_at: job.completed_at.map(Into::into),
+            runner_name: job.runner_name.clone(),
+            labels: job.labels.clone(),
+            html_url: job.html_url.clone(),
+            steps: job.steps.iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<&MatrixCell> for proto::MatrixCell {
+    fn from(cell: &MatrixCell) -> Self {
+        Self {
+            job_id: cell.job_id.0,
+            values: cell.values.clone(),
+            status: cell.status.as_str().to_string(),
+            conclusion: cell
+                .conclusion
+                .map(|conclusion| conclusion.as_str().to_string()),
+            duration_seconds: cell.duration.map(|duration| duration.as_secs()),
+        }
+    }
+}
+
+impl From<&MatrixGroup> for proto::MatrixGroup {
+    fn from(group: &MatrixGroup) -> Self {
+        let rollup = group.rollup();
+        Self {
+            name: group.name.clone(),
+            cells: group.cells.iter().map(Into::into).collect(),
+            all_passed: rollup.all_passed,
+            failed: u32::try_from(rollup.failed).unwrap_or(u32::MAX),
+            slowest_job_id: rollup.slowest.map(|job_id| job_id.0),
+            total_duration_seconds: rollup.total_duration.as_secs(),
+        }
+    }
+}
+
+/// `output` as a `RunsSnapshot`; `seq` is 0 outside of `WatchRuns`.
+#[must_use]
+pub fn runs_snapshot(
+    output: &StreamGitHubActionsRunsUseCaseOutput,
+    seq: u64,
+    resync: bool,
+) -> proto::RunsSnapshot {
+    proto::RunsSnapshot {
+        seq,
+        resync,
+        schema_version: output.schema_version,
+        generated_at: Some(output.generated_at.into()),
+        runs: output.runs.iter().map(Into::into).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::ids::JobId;
+    use crate::test_support::{base_time, workflow_run};
+    use chrono::TimeDelta;
+    use prost::Message;
+
+    #[test]
+    fn test_runs_survive_a_round_trip_through_the_wire_format() -> anyhow::Result<()> {
+        let mut completed = workflow_run("owner/repo", 1, "failure");
+        completed.updated_at += TimeDelta::milliseconds(90_500);
+        completed.full_display_title = Some("Fix bug in the parser".to_string());
+        completed.labels = vec!["release".to_string()];
+        let mut branchless = workflow_run("owner/repo", 2, "in_progress");
+        branchless.head_branch = None;
+        branchless.triggering_actor = None;
+
+        for run in [completed, branchless] {
+            let message = proto::WorkflowRun::from(&run);
+            let decoded = proto::WorkflowRun::decode(message.encode_to_vec().as_slice())?;
+
+            assert_eq!(decoded, message);
+            assert_eq!(WorkflowRun::try_from(decoded)?, run);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_runs_carry_the_same_values_as_their_json() -> anyhow::Result<()> {
+        let mut run = workflow_run("owner/repo", 7, "timed_out");
+        run.updated_at += TimeDelta::seconds(42);
+        let json = serde_json::to_value(&run)?;
+
+        let message = proto::WorkflowRun::from(&run);
+
+        assert_eq!(message.repository_name, json["repositoryName"]);
+        assert_eq!(message.status, json["status"]);
+        assert_eq!(message.conclusion.as_deref(), json["conclusion"].as_str());
+        assert_eq!(message.display_status, json["displayStatus"]);
+        assert_eq!(message.duration_seconds, json["durationSeconds"].as_u64());
+        assert_eq!(message.head_branch.as_deref(), json["headBranch"].as_str());
+        assert_eq!(
+            message.created_at,
+            Some(proto::Timestamp {
+                seconds: run.created_at.timestamp(),
+                nanos: 0
+            })
+        );
+        assert_eq!(
+            message.triggering_actor.map(|actor| actor.login),
+            json["triggeringActor"]["login"].as_str().map(String::from)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_timestamps_keep_sub_second_precision_and_reject_invalid_nanos() -> anyhow::Result<()> {
+        let time = base_time() + TimeDelta::nanoseconds(123_456_789);
+
+        let timestamp = proto::Timestamp::from(time);
+
+        assert_eq!(timestamp.nanos, 123_456_789);
+        assert_eq!(DateTime::<Utc>::try_from(timestamp)?, time);
+        for nanos in [-1, 2_000_000_000] {
+            assert!(DateTime::<Utc>::try_from(proto::Timestamp { seconds: 0, nanos }).is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_jobs_map_with_their_steps() {
+        let job = Job {
+            id: JobId(101),
+            run_id: RunId(42),
+            name: "build".to_string(),
+            status: RunStatus::InProgress,
+            conclusion: None,
+            started_at: Some(base_time()),
+            completed_at: None,
+            runner_name: None,
+            labels: vec!["ubuntu-latest".to_string()],
+            html_url: "https://github.com/owner/repo/actions/runs/42/job/101".to_string(),
+            steps: vec![Step {
+                number: 1,
+                name: "Checkout".to_string(),
+                status: RunStatus::Completed,
+                conclusion: Some(RunConclusion::Success),
+                started_at: Some(base_time()),
+                completed_at: Some(base_time() + TimeDelta::seconds(4)),
+            }],
+        };
+
+        let message = proto::Job::from(&job);
+
+        assert_eq!((message.id, message.run_id), (101, 42));
+        assert_eq!(message.status, "in_progress");
+        assert_eq!(message.conclusion, None);
+        assert_eq!(message.completed_at, None);
+        assert_eq!(message.steps[0].conclusion.as_deref(), Some("success"));
+        assert_eq!(
+            message.steps[0].completed_at,
+            Some((base_time() + TimeDelta::seconds(4)).into())
+        );
+    }
+}
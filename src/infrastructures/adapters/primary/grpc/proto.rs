@@ -0,0 +1,186 @@
+// Messages of `proto/gha_dashboard/v1/runs.proto`, written out by hand so building needs
+// no `protoc`; keep the two in sync.
+
+/// Wire-compatible with `google.protobuf.Timestamp`.
+#[derive(Clone, Copy, PartialEq, Eq, prost::Message)]
+pub struct Timestamp {
+    #[prost(int64, tag = "1")]
+    pub seconds: i64,
+    #[prost(int32, tag = "2")]
+    pub nanos: i32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, prost::Message)]
+pub struct WatchRequest {
+    #[prost(uint64, optional, tag = "1")]
+    pub since_seq: Option<u64>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, prost::Message)]
+pub struct ListRunsRequest {}
+
+#[derive(Clone, PartialEq, Eq, prost::Message)]
+pub struct RunsSnapshot {
+    #[prost(uint64, tag = "1")]
+    pub seq: u64,
+    #[prost(bool, tag = "2")]
+    pub resync: bool,
+    #[prost(uint32, tag = "3")]
+    pub schema_version: u32,
+    #[prost(message, optional, tag = "4")]
+    pub generated_at: Option<Timestamp>,
+    #[prost(message, repeated, tag = "5")]
+    pub runs: Vec<WorkflowRun>,
+}
+
+#[derive(Clone, PartialEq, Eq, prost::Message)]
+pub struct Actor {
+    #[prost(string, tag = "1")]
+    pub login: String,
+    #[prost(string, tag = "2")]
+    pub avatar_url: String,
+    #[prost(string, tag = "3")]
+    pub html_url: String,
+}
+
+#[derive(Clone, PartialEq, Eq, prost::Message)]
+pub struct WorkflowRun {
+    #[prost(string, tag = "1")]
+    pub repository_name: String,
+    #[prost(uint64, tag = "2")]
+    pub id: u64,
+    #[prost(uint64, tag = "3")]
+    pub run_number: u64,
+    #[prost(uint64, tag = "4")]
+    pub workflow_id: u64,
+    #[prost(string, tag = "5")]
+    pub workflow_name: String,
+    #[prost(string, tag = "6")]
+    pub display_title: String,
+    #[prost(string, optional, tag = "7")]
+    pub full_display_title: Option<String>,
+    #[prost(string, tag = "8")]
+    pub event: String,
+    #[prost(string, optional, tag = "9")]
+    pub head_branch: Option<String>,
+    #[prost(string, tag = "10")]
+    pub head_sha: String,
+    #[prost(message, optional, tag = "11")]
+    pub actor: Option<Actor>,
+    #[prost(message, optional, tag = "12")]
+    pub triggering_actor: Option<Actor>,
+    #[prost(string, tag = "13")]
+    pub status: String,
+    #[prost(string, optional, tag = "14")]
+    pub conclusion: Option<String>,
+    #[prost(string, tag = "15")]
+    pub display_status: String,
+    #[prost(uint64, optional, tag = "16")]
+    pub duration_seconds: Option<u64>,
+    #[prost(message, optional, tag = "17")]
+    pub created_at: Option<Timestamp>,
+    #[prost(message, optional, tag = "18")]
+    pub updated_at: Option<Timestamp>,
+    #[prost(string, tag = "19")]
+    pub html_url: String,
+    #[prost(string, repeated, tag = "20")]
+    pub labels: Vec<String>,
+    #[prost(bool, optional, tag = "21")]
+    pub repository_active: Option<bool>,
+    #[prost(bool, optional, tag = "22")]
+    pub is_required_check: Option<bool>,
+    #[prost(string, repeated, tag = "23")]
+    pub failed_jobs: Vec<String>,
+    #[prost(string, optional, tag = "24")]
+    pub failed_job_url: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Eq, prost::Message)]
+pub struct GetRunDetailRequest {
+    #[prost(string, tag = "1")]
+    pub repository_name: String,
+    #[prost(uint64, tag = "2")]
+    pub run_id: u64,
+}
+
+#[derive(Clone, PartialEq, Eq, prost::Message)]
+pub struct RunDetail {
+    #[prost(message, optional, tag = "1")]
+    pub run: Option<WorkflowRun>,
+    #[prost(message, repeated, tag = "2")]
+    pub jobs: Vec<Job>,
+    #[prost(message, repeated, tag = "3")]
+    pub matrix_groups: Vec<MatrixGroup>,
+}
+
+#[derive(Clone, PartialEq, Eq, prost::Message)]
+pub struct Job {
+    #[prost(uint64, tag = "1")]
+    pub id: u64,
+    #[prost(uint64, tag = "2")]
+    pub run_id: u64,
+    #[prost(string, tag = "3")]
+    pub name: String,
+    #[prost(string, tag = "4")]
+    pub status: String,
+    #[prost(string, optional, tag = "5")]
+    pub conclusion: Option<String>,
+    #[prost(message, optional, tag = "6")]
+    pub started_at: Option<Timestamp>,
+    #[prost(message, optional, tag = "7")]
+    pub completed_at: Option<Timestamp>,
+    #[prost(string, optional, tag = "8")]
+    pub runner_name: Option<String>,
+    #[prost(string, repeated, tag = "9")]
+    pub labels: Vec<String>,
+    #[prost(string, tag = "10")]
+    pub html_url: String,
+    #[prost(message, repeated, tag = "11")]
+    pub steps: Vec<Step>,
+}
+
+#[derive(Clone, PartialEq, Eq, prost::Message)]
+pub struct MatrixGroup {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(message, repeated, tag = "2")]
+    pub cells: Vec<MatrixCell>,
+    #[prost(bool, tag = "3")]
+    pub all_passed: bool,
+    #[prost(uint32, tag = "4")]
+    pub failed: u32,
+    #[prost(uint64, optional, tag = "5")]
+    pub slowest_job_id: Option<u64>,
+    #[prost(uint64, tag = "6")]
+    pub total_duration_seconds: u64,
+}
+
+#[derive(Clone, PartialEq, Eq, prost::Message)]
+pub struct MatrixCell {
+    #[prost(uint64, tag = "1")]
+    pub job_id: u64,
+    #[prost(string, repeated, tag = "2")]
+    pub values: Vec<String>,
+    #[prost(string, tag = "3")]
+    pub status: String,
+    #[prost(string, optional, tag = "4")]
+    pub conclusion: Option<String>,
+    #[prost(uint64, optional, tag = "5")]
+    pub duration_seconds: Option<u64>,
+}
+
+#[derive(Clone, PartialEq, Eq, prost::Message)]
+pub struct Step {
+    #[prost(uint32, tag = "1")]
+    pub number: u32,
+    #[prost(string, tag = "2")]
+    pub name: String,
+    #[prost(string, tag = "3")]
+    pub status: String,
+    #[prost(string, optional, tag = "4")]
+    pub conclusion: Option<String>,
+    #[prost(message, optional, tag = "5")]
+    pub started_at: Option<Timestamp>,
+    #[prost(message, optional, tag = "6")]
+    pub completed_at: Option<Timestamp>,
+}
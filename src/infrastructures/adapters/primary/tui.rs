@@ -0,0 +1,219 @@
+pub mod dashboard;
+pub mod feed;
+pub mod view;
+
+use crate::domain::models::ids::RunId;
+use chrono::{TimeDelta, Utc};
+use crossterm::event::{Event, EventStream, KeyEventKind};
+use dashboard::{Action, Dashboard};
+use feed::{FeedEvent, Frame};
+use futures_util::{Stream, StreamExt};
+use std::collections::HashMap;
+use std::io::{ErrorKind, Write};
+use std::time::Duration;
+
+/// How often the table is redrawn without news, so ages and the stale indicator move on
+const REDRAW_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Shows the runs of `events` as a table on the terminal until the user quits.
+///
+/// # Errors
+///
+/// Returns an error if the terminal cannot be drawn on or read from.
+pub async fn interactive(
+    events: impl Stream<Item = FeedEvent>,
+    stale_after: TimeDelta,
+) -> anyhow::Result<()> {
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, events, stale_after).await;
+    ratatui::restore();
+    result
+}
+
+async fn event_loop(
+    terminal: &mut ratatui::DefaultTerminal,
+    events: impl Stream<Item = FeedEvent>,
+    stale_after: TimeDelta,
+) -> anyhow::Result<()> {
+    tokio::pin!(events);
+    let mut dashboard = Dashboard::new(stale_after);
+    let mut keys = EventStream::new();
+    let mut redraw = tokio::time::interval(REDRAW_INTERVAL);
+    loop {
+        terminal.draw(|frame| view::render(frame, &dashboard, Utc::now()))?;
+        tokio::select! {
+            Some(event) = events.next() => dashboard.apply(event, Utc::now()),
+            Some(event) = keys.next() => {
+                let Event::Key(key) = event? else {
+                    continue;
+                };
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                match dashboard.handle_key(key) {
+                    Action::Quit => return Ok(()),
+                    Action::Open(url) => {
+                        if let Err(e) = open_url(&url) {
+                            dashboard.set_message(format!("Failed to open {url}: {e}"));
+                        }
+                    }
+                    Action::None => {}
+                }
+            }
+            _ = redraw.tick() => {}
+        }
+    }
+}
+
+/// Opens `url` in the desktop's browser without waiting for it.
+fn open_url(url: &str) -> std::io::Result<()> {
+    let opener = if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    std::process::Command::new(opener)
+        .arg(url)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .map(drop)
+}
+
+/// Writes a tab-separated line to `out` for every run of `events` that is new or changed
+/// its status: update time, repository, workflow, run number, status, actor and URL.
+///
+/// Returns once `out` is closed, e.g. when piped to `head`.
+///
+/// # Errors
+///
+/// Returns an error if `out` cannot be written for another reason.
+pub async fn print_lines(
+    events: impl Stream<Item = FeedEvent>,
+    out: &mut impl Write,
+) -> anyhow::Result<()> {
+    tokio::pin!(events);
+    let mut statuses: HashMap<RunId, &'static str> = HashMap::new();
+    while let Some(event) = events.next().await {
+        let written = match event {
+            FeedEvent::Connected => {
+                tracing::info!("Connected");
+                Ok(())
+            }
+            FeedEvent::Frame(Frame::Snapshot(snapshot)) => snapshot
+                .runs
+                .iter()
+                .filter(|run| {
+                    statuses.insert(run.id, run.display_status()) != Some(run.display_status())
+                })
+                .try_for_each(|run| {
+                    writeln!(
+                        out,
+                        "{}\t{}\t{}\t#{}\t{}\t{}\t{}",
+                        run.updated_at.to_rfc3339(),
+                        run.repository_name,
+                        run.workflow_name,
+                        run.run_number,
+                        run.display_status(),
+                        run.actor.login,
+                        run.html_url
+                    )
+                })
+                .and_then(|()| out.flush()),
+            FeedEvent::Frame(Frame::Notice(notice)) => {
+                tracing::warn!("{}", notice.message);
+                Ok(())
+            }
+            FeedEvent::Frame(Frame::Error(error)) => {
+                tracing::error!("{}", error);
+                Ok(())
+            }
+            FeedEvent::Disconnected { error, retry_in } => {
+                tracing::warn!("Disconnected: {}, reconnecting in {:?}", error, retry_in);
+                Ok(())
+            }
+        };
+        match written {
+            Err(e) if e.kind() == ErrorKind::BrokenPipe => return Ok(()),
+            written => written?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{base_time, workflow_run};
+    use feed::Snapshot;
+
+    fn snapshot(seq: u64, runs: Vec<crate::domain::models::run::WorkflowRun>) -> FeedEvent {
+        FeedEvent::Frame(Frame::Snapshot(Snapshot {
+            seq,
+            runs,
+            generated_at: base_time(),
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_lines_are_printed_for_new_and_changed_runs() -> anyhow::Result<()> {
+        let events = futures_util::stream::iter([
+            FeedEvent::Connected,
+            snapshot(
+                1,
+                vec![
+                    workflow_run("owner/repo", 2, "in_progress"),
+                    workflow_run("owner/repo", 1, "success"),
+                ],
+            ),
+            FeedEvent::Disconnected {
+                error: "Connection closed by the server".to_string(),
+                retry_in: Duration::from_secs(1),
+            },
+            snapshot(
+                2,
+                vec![
+                    workflow_run("owner/repo", 2, "failure"),
+                    workflow_run("owner/repo", 1, "success"),
+                ],
+            ),
+        ]);
+        let mut out = Vec::new();
+
+        print_lines(events, &mut out).await?;
+
+        assert_eq!(
+            String::from_utf8(out)?,
+            "\
+2024-08-01T10:02:00+00:00\towner/repo\tCI\t#2\tin_progress\toctocat\thttps://github.com/owner/repo/actions/runs/2
+2024-08-01T10:01:00+00:00\towner/repo\tCI\t#1\tsuccess\toctocat\thttps://github.com/owner/repo/actions/runs/1
+2024-08-01T10:02:00+00:00\towner/repo\tCI\t#2\tfailure\toctocat\thttps://github.com/owner/repo/actions/runs/2
+"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_a_closed_pipe_ends_the_output() -> anyhow::Result<()> {
+        struct ClosedPipe;
+
+        impl Write for ClosedPipe {
+            fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+                Err(ErrorKind::BrokenPipe.into())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let events = futures_util::stream::iter([snapshot(
+            1,
+            vec![workflow_run("owner/repo", 1, "success")],
+        )])
+        .chain(futures_util::stream::pending());
+
+        print_lines(events, &mut ClosedPipe).await
+    }
+}
@@ -0,0 +1,348 @@
+use super::feed::{FeedEvent, Frame};
+use crate::domain::models::run::WorkflowRun;
+use crate::domain::models::status::RunStatus;
+use chrono::{DateTime, TimeDelta, Utc};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Which runs the table lists, cycled through with `f`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StatusFilter {
+    #[default]
+    All,
+    /// Runs that ended in a state needing attention
+    Problems,
+    /// Runs that have not completed yet
+    Running,
+    Succeeded,
+}
+
+impl StatusFilter {
+    #[must_use]
+    pub fn next(self) -> Self {
+        match self {
+            Self::All => Self::Problems,
+            Self::Problems => Self::Running,
+            Self::Running => Self::Succeeded,
+            Self::Succeeded => Self::All,
+        }
+    }
+
+    #[must_use]
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::All => "all",
+            Self::Problems => "problems",
+            Self::Running => "running",
+            Self::Succeeded => "succeeded",
+        }
+    }
+
+    #[must_use]
+    pub fn matches(self, run: &WorkflowRun) -> bool {
+        match self {
+            Self::All => true,
+            Self::Problems => run.is_problem(),
+            Self::Running => run.status != RunStatus::Completed,
+            Self::Succeeded => run.display_status() == "success",
+        }
+    }
+}
+
+/// How current the table is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Freshness {
+    /// No frame has arrived yet
+    Connecting,
+    Live,
+    /// Connected, but no frame arrived for longer than expected since `last_frame_at`
+    Stale {
+        last_frame_at: DateTime<Utc>,
+    },
+    /// The connection dropped; it is tried again at `retry_at`
+    Disconnected {
+        error: String,
+        retry_at: DateTime<Utc>,
+    },
+}
+
+/// What the caller should do after a key press.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    None,
+    Quit,
+    /// Open the URL in a browser
+    Open(String),
+}
+
+/// The state of the terminal dashboard, fed with what happens on `/ws` and with key presses.
+#[derive(Debug)]
+pub struct Dashboard {
+    runs: Vec<WorkflowRun>,
+    filter: StatusFilter,
+    /// Index into the runs the filter lets through
+    selected: usize,
+    last_frame_at: Option<DateTime<Utc>>,
+    disconnected: Option<(String, DateTime<Utc>)>,
+    /// Wait for a frame before the table is marked stale
+    stale_after: TimeDelta,
+    /// Last notice or error sent by the server, or of opening a run
+    message: Option<String>,
+}
+
+impl Dashboard {
+    #[must_use]
+    pub fn new(stale_after: TimeDelta) -> Self {
+        Self {
+            runs: Vec::new(),
+            filter: StatusFilter::default(),
+            selected: 0,
+            last_frame_at: None,
+            disconnected: None,
+            stale_after,
+            message: None,
+        }
+    }
+
+    /// Applies what happened on `/ws` at `now`.
+    pub fn apply(&mut self, event: FeedEvent, now: DateTime<Utc>) {
+        match event {
+            FeedEvent::Connected => self.disconnected = None,
+            FeedEvent::Frame(frame) => {
+                self.last_frame_at = Some(now);
+                match frame {
+                    Frame::Snapshot(snapshot) => {
+                        // Keep the selection on the same run as the rows move
+                        let selected = self.selected_run().map(|run| run.id);
+                        self.runs = snapshot.runs;
+                        self.selected = selected
+                            .and_then(|id| self.visible_runs().iter().position(|run| run.id == id))
+                            .unwrap_or(self.selected);
+                        self.clamp_selection();
+                    }
+                    Frame::Notice(notice) => self.message = Some(notice.message),
+                    Frame::Error(error) => self.message = Some(error),
+                }
+            }
+            FeedEvent::Disconnected { error, retry_in } => {
+                let retry_in = TimeDelta::from_std(retry_in).unwrap_or(TimeDelta::MAX);
+                self.disconnected = Some((error, now + retry_in));
+            }
+        }
+    }
+
+    /// Handles a key press: arrows or `j`/`k` move, `f` cycles the filter, `o` or Enter
+    /// opens the selected run and `q`, Esc or Ctrl+C quit.
+    pub fn handle_key(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Action::Quit,
+            KeyCode::Char('q') | KeyCode::Esc => Action::Quit,
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = self.selected.saturating_add(1);
+                self.clamp_selection();
+                Action::None
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.saturating_sub(1);
+                Action::None
+            }
+            KeyCode::Char('f') => {
+                self.filter = self.filter.next();
+                self.selected = 0;
+                Action::None
+            }
+            KeyCode::Char('o') | KeyCode::Enter => self
+                .selected_run()
+                .map_or(Action::None, |run| Action::Open(run.html_url.clone())),
+            _ => Action::None,
+        }
+    }
+
+    /// Shows `message` below the table until the server sends another.
+    pub fn set_message(&mut self, message: String) {
+        self.message = Some(message);
+    }
+
+    /// Runs the filter lets through, newest first as the server sends them.
+    #[must_use]
+    pub fn visible_runs(&self) -> Vec<&WorkflowRun> {
+        self.runs
+            .iter()
+            .filter(|run| self.filter.matches(run))
+            .collect()
+    }
+
+    #[must_use]
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    #[must_use]
+    pub fn selected_run(&self) -> Option<&WorkflowRun> {
+        self.visible_runs().get(self.selected).copied()
+    }
+
+    #[must_use]
+    pub fn filter(&self) -> StatusFilter {
+        self.filter
+    }
+
+    #[must_use]
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    #[must_use]
+    pub fn freshness(&self, now: DateTime<Utc>) -> Freshness {
+        if let Some((error, retry_at)) = &self.disconnected {
+            return Freshness::Disconnected {
+                error: error.clone(),
+                retry_at: *retry_at,
+            };
+        }
+        match self.last_frame_at {
+            None => Freshness::Connecting,
+            Some(last_frame_at) if now - last_frame_at > self.stale_after => {
+                Freshness::Stale { last_frame_at }
+            }
+            Some(_) => Freshness::Live,
+        }
+    }
+
+    fn clamp_selection(&mut self) {
+        self.selected = self
+            .selected
+            .min(self.visible_runs().len().saturating_sub(1));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructures::adapters::primary::tui::feed::Snapshot;
+    use crate::test_support::{base_time, workflow_run};
+    use std::time::Duration;
+
+    fn snapshot(runs: Vec<WorkflowRun>) -> FeedEvent {
+        FeedEvent::Frame(Frame::Snapshot(Snapshot {
+            seq: 1,
+            runs,
+            generated_at: base_time(),
+        }))
+    }
+
+    fn press(dashboard: &mut Dashboard, code: KeyCode) -> Action {
+        dashboard.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn test_filter_cycles_through_statuses() {
+        let mut dashboard = Dashboard::new(TimeDelta::seconds(90));
+        dashboard.apply(
+            snapshot(vec![
+                workflow_run("owner/repo", 4, "in_progress"),
+                workflow_run("owner/repo", 3, "failure"),
+                workflow_run("owner/repo", 2, "success"),
+                workflow_run("owner/repo", 1, "timed_out"),
+            ]),
+            base_time(),
+        );
+        let ids = |dashboard: &Dashboard| -> Vec<u64> {
+            dashboard
+                .visible_runs()
+                .iter()
+                .map(|run| run.id.0)
+                .collect()
+        };
+
+        assert_eq!(ids(&dashboard), [4, 3, 2, 1]);
+        press(&mut dashboard, KeyCode::Char('f'));
+        assert_eq!(dashboard.filter(), StatusFilter::Problems);
+        assert_eq!(ids(&dashboard), [3, 1]);
+        press(&mut dashboard, KeyCode::Char('f'));
+        assert_eq!(ids(&dashboard), [4]);
+        press(&mut dashboard, KeyCode::Char('f'));
+        assert_eq!(ids(&dashboard), [2]);
+        press(&mut dashboard, KeyCode::Char('f'));
+        assert_eq!(dashboard.filter(), StatusFilter::All);
+    }
+
+    #[test]
+    fn test_selection_follows_its_run_and_opens_it() {
+        let mut dashboard = Dashboard::new(TimeDelta::seconds(90));
+        dashboard.apply(
+            snapshot(vec![
+                workflow_run("owner/repo", 2, "in_progress"),
+                workflow_run("owner/repo", 1, "success"),
+            ]),
+            base_time(),
+        );
+        press(&mut dashboard, KeyCode::Down);
+        press(&mut dashboard, KeyCode::Down);
+        assert_eq!(dashboard.selected(), 1);
+
+        // A new run pushes the selected one down a row
+        dashboard.apply(
+            snapshot(vec![
+                workflow_run("owner/repo", 3, "queued"),
+                workflow_run("owner/repo", 2, "in_progress"),
+                workflow_run("owner/repo", 1, "success"),
+            ]),
+            base_time(),
+        );
+        assert_eq!(dashboard.selected(), 2);
+        assert_eq!(
+            press(&mut dashboard, KeyCode::Enter),
+            Action::Open("https://github.com/owner/repo/actions/runs/1".to_string())
+        );
+
+        // Its run is gone, so the selection stays within the rows left
+        dashboard.apply(
+            snapshot(vec![workflow_run("owner/repo", 3, "queued")]),
+            base_time(),
+        );
+        assert_eq!(dashboard.selected(), 0);
+        assert_eq!(press(&mut dashboard, KeyCode::Char('q')), Action::Quit);
+    }
+
+    #[test]
+    fn test_freshness_tracks_frames_and_disconnects() {
+        let mut dashboard = Dashboard::new(TimeDelta::seconds(90));
+        assert_eq!(dashboard.freshness(base_time()), Freshness::Connecting);
+
+        dashboard.apply(FeedEvent::Connected, base_time());
+        dashboard.apply(snapshot(Vec::new()), base_time());
+        assert_eq!(
+            dashboard.freshness(base_time() + TimeDelta::seconds(90)),
+            Freshness::Live
+        );
+        assert_eq!(
+            dashboard.freshness(base_time() + TimeDelta::seconds(91)),
+            Freshness::Stale {
+                last_frame_at: base_time()
+            }
+        );
+
+        let later = base_time() + TimeDelta::minutes(5);
+        dashboard.apply(
+            FeedEvent::Disconnected {
+                error: "Connection closed by the server".to_string(),
+                retry_in: Duration::from_secs(4),
+            },
+            later,
+        );
+        assert_eq!(
+            dashboard.freshness(later),
+            Freshness::Disconnected {
+                error: "Connection closed by the server".to_string(),
+                retry_at: later + TimeDelta::seconds(4),
+            }
+        );
+        dashboard.apply(FeedEvent::Connected, later);
+        assert_eq!(
+            dashboard.freshness(later),
+            Freshness::Stale {
+                last_frame_at: base_time()
+            }
+        );
+    }
+}
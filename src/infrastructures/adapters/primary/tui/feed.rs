@@ -0,0 +1,255 @@
+use crate::application::services::Notice;
+use crate::domain::models::run::WorkflowRun;
+use chrono::{DateTime, Utc};
+use futures_util::{Stream, StreamExt};
+use serde::Deserialize;
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Wait before reconnecting after the first failure (seconds), doubled for each further one
+/// until a frame arrives again
+const RECONNECT_INITIAL_SECONDS: u64 = 1;
+
+/// Longest wait before reconnecting (seconds)
+const RECONNECT_MAX_SECONDS: u64 = 60;
+
+/// A snapshot frame of `/ws`, read into the same models the server serializes.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub seq: u64,
+    pub runs: Vec<WorkflowRun>,
+    #[serde(rename = "generatedAt")]
+    pub generated_at: DateTime<Utc>,
+}
+
+/// A notice frame of `/ws`.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+struct SequencedNotice {
+    seq: u64,
+    #[serde(flatten)]
+    notice: Notice,
+}
+
+/// A frame received from `/ws`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Frame {
+    Snapshot(Snapshot),
+    Notice(Notice),
+    /// A plain-text `Error: ...` frame, an error about a message the client sent, or a frame
+    /// that could not be read
+    Error(String),
+}
+
+impl Frame {
+    /// Reads a text frame; notices and errors are told apart from snapshots by their `type`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a JSON frame does not match the models, e.g. after the wire
+    /// format changed.
+    pub fn parse(text: &str) -> serde_json::Result<(Option<u64>, Self)> {
+        if !text.starts_with('{') {
+            return Ok((None, Self::Error(text.to_string())));
+        }
+        let frame: serde_json::Value = serde_json::from_str(text)?;
+        if frame["type"] == "error" {
+            let message = frame["message"].as_str().unwrap_or_default();
+            Ok((None, Self::Error(message.to_string())))
+        } else if frame.get("type").is_some() {
+            let SequencedNotice { seq, notice } = serde_json::from_value(frame)?;
+            Ok((Some(seq), Self::Notice(notice)))
+        } else {
+            let snapshot: Snapshot = serde_json::from_value(frame)?;
+            Ok((Some(snapshot.seq), Self::Snapshot(snapshot)))
+        }
+    }
+}
+
+/// What happened to the connection to `/ws`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FeedEvent {
+    Connected,
+    Frame(Frame),
+    /// The connection failed or dropped; it is tried again after `retry_in`
+    Disconnected {
+        error: String,
+        retry_in: Duration,
+    },
+}
+
+/// `url` asking for RFC 3339 timestamps, which the models read, and for the frames after
+/// `since_seq`.
+fn frames_url(url: &str, since_seq: Option<u64>) -> String {
+    let separator = if url.contains('?') { '&' } else { '?' };
+    match since_seq {
+        Some(seq) => format!("{url}{separator}ts=rfc3339&since_seq={seq}"),
+        None => format!("{url}{separator}ts=rfc3339"),
+    }
+}
+
+/// Follows the `/ws` endpoint at `url` forever, reconnecting with backoff whenever the
+/// connection drops and resuming after the last frame received.
+pub fn subscribe(url: String) -> impl Stream<Item = FeedEvent> {
+    async_stream::stream! {
+        let mut last_seq = None;
+        let mut retry_in = Duration::from_secs(RECONNECT_INITIAL_SECONDS);
+        loop {
+            let error = match tokio_tungstenite::connect_async(frames_url(&url, last_seq)).await {
+                Ok((mut socket, _)) => {
+                    yield FeedEvent::Connected;
+                    loop {
+                        match socket.next().await {
+                            Some(Ok(Message::Text(text))) => {
+                                retry_in = Duration::from_secs(RECONNECT_INITIAL_SECONDS);
+                                match Frame::parse(&text) {
+                                    Ok((seq, frame)) => {
+                                        last_seq = seq.or(last_seq);
+                                        yield FeedEvent::Frame(frame);
+                                    }
+                                    Err(e) => {
+                                        yield FeedEvent::Frame(Frame::Error(format!(
+                                            "Unreadable frame: {e}"
+                                        )));
+                                    }
+                                }
+                            }
+                            Some(Ok(Message::Close(_))) | None => {
+                                break "Connection closed by the server".to_string();
+                            }
+                            // Ping and pong are answered by tungstenite itself
+                            Some(Ok(_)) => {}
+                            Some(Err(e)) => break e.to_string(),
+                        }
+                    }
+                }
+                Err(e) => e.to_string(),
+            };
+            yield FeedEvent::Disconnected { error, retry_in };
+            tokio::time::sleep(retry_in).await;
+            retry_in = (retry_in * 2).min(Duration::from_secs(RECONNECT_MAX_SECONDS));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::services::NoticeLevel;
+    use crate::application::use_cases::StreamGitHubActionsRunsUseCaseOutput;
+    use crate::infrastructures::adapters::primary::web::create_router;
+    use crate::infrastructures::adapters::primary::web::presenter::projection::RunProjection;
+    use crate::infrastructures::adapters::primary::web::presenter::{
+        TimestampFormat, sequenced_json, sequenced_snapshot_json,
+    };
+    use crate::test_support::{StubGitHubApi, app_state, base_time, workflow_run};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_frames_read_what_the_server_writes() -> anyhow::Result<()> {
+        let mut run = workflow_run("owner/repo", 1, "failure");
+        run.full_display_title = Some("Fix bug in the parser".to_string());
+        let output = StreamGitHubActionsRunsUseCaseOutput::new(vec![run.clone()], base_time());
+        let notice = Notice::warning("Server draining for deploy");
+
+        let snapshot = sequenced_snapshot_json(
+            &output,
+            TimestampFormat::Rfc3339,
+            &RunProjection::default(),
+            7,
+            true,
+        )?;
+        assert_eq!(
+            Frame::parse(&snapshot)?,
+            (
+                Some(7),
+                Frame::Snapshot(Snapshot {
+                    seq: 7,
+                    runs: vec![run],
+                    generated_at: base_time(),
+                })
+            )
+        );
+        assert_eq!(
+            Frame::parse(&sequenced_json(&notice, 8)?)?,
+            (
+                Some(8),
+                Frame::Notice(Notice {
+                    level: NoticeLevel::Warning,
+                    message: "Server draining for deploy".to_string(),
+                })
+            )
+        );
+        assert_eq!(
+            Frame::parse("Error: GitHub API rate limit exceeded")?,
+            (
+                None,
+                Frame::Error("Error: GitHub API rate limit exceeded".to_string())
+            )
+        );
+        assert_eq!(
+            Frame::parse(r#"{"type":"error","message":"Binary messages are not supported"}"#)?,
+            (
+                None,
+                Frame::Error("Binary messages are not supported".to_string())
+            )
+        );
+        assert!(Frame::parse(r#"{"seq":1,"runs":[{"id":1}]}"#).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_urls_ask_for_rfc3339_and_resume() {
+        assert_eq!(
+            frames_url("ws://host:3000/ws", None),
+            "ws://host:3000/ws?ts=rfc3339"
+        );
+        assert_eq!(
+            frames_url("wss://host/ws?encoding=json", Some(12)),
+            "wss://host/ws?encoding=json&ts=rfc3339&since_seq=12"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_feed_reconnects_after_the_server_is_unreachable() -> anyhow::Result<()> {
+        // Reserve a port, then leave it closed until the server starts
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        drop(listener);
+        let feed = subscribe(format!("ws://{addr}/ws"));
+        tokio::pin!(feed);
+        let next = async |feed: &mut std::pin::Pin<&mut _>| {
+            tokio::time::timeout(Duration::from_secs(5), StreamExt::next(feed))
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("feed ended"))
+        };
+
+        let Some(FeedEvent::Disconnected { retry_in, .. }) = next(&mut feed).await.ok() else {
+            anyhow::bail!("the first attempt should fail");
+        };
+        assert_eq!(retry_in, Duration::from_secs(RECONNECT_INITIAL_SECONDS));
+
+        let state = app_state(StubGitHubApi::default(), None)?;
+        state
+            .poller
+            .publish(StreamGitHubActionsRunsUseCaseOutput::new(
+                vec![workflow_run("owner/repo", 1, "success")],
+                base_time(),
+            ));
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        tokio::spawn(async move {
+            axum::serve(
+                listener,
+                create_router(Arc::clone(&state))
+                    .into_make_service_with_connect_info::<std::net::SocketAddr>(),
+            )
+            .await
+        });
+
+        assert_eq!(next(&mut feed).await?, FeedEvent::Connected);
+        let Some(FeedEvent::Frame(Frame::Snapshot(snapshot))) = next(&mut feed).await.ok() else {
+            anyhow::bail!("the latest snapshot should follow the connection");
+        };
+        assert_eq!(snapshot.runs[0].display_status(), "success");
+        Ok(())
+    }
+}
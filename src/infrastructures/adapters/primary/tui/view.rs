@@ -0,0 +1,151 @@
+use super::dashboard::{Dashboard, Freshness};
+use crate::domain::models::run::WorkflowRun;
+use crate::domain::models::status::{RunConclusion, RunStatus};
+use chrono::{DateTime, Utc};
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Row, Table, TableState};
+
+/// Keys listed in the footer
+const KEYS: &str = "↑/↓ select  f filter  o open  q quit";
+
+/// Draws the dashboard as of `now`: a status line, the runs and a footer.
+pub fn render(frame: &mut Frame, dashboard: &Dashboard, now: DateTime<Utc>) {
+    let [header, table, footer] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Min(0),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    let runs = dashboard.visible_runs();
+    frame.render_widget(
+        Line::from(vec![
+            "gha-dashboard ".bold(),
+            freshness(&dashboard.freshness(now), now),
+            Span::raw(format!(
+                "  filter: {}  {} runs",
+                dashboard.filter().label(),
+                runs.len()
+            )),
+        ]),
+        header,
+    );
+
+    let rows = runs.iter().map(|run| {
+        Row::new(vec![
+            Span::raw(run.repository_name.to_string()),
+            Span::raw(run.workflow_name.clone()),
+            Span::styled(run.display_status(), Style::new().fg(status_color(run))),
+            Span::raw(age(run.created_at, now)),
+            Span::raw(run.actor.login.clone()),
+        ])
+    });
+    let widths = [
+        Constraint::Fill(2),
+        Constraint::Fill(2),
+        Constraint::Length(15),
+        Constraint::Length(5),
+        Constraint::Fill(1),
+    ];
+    let mut state = TableState::default().with_selected(Some(dashboard.selected()));
+    frame.render_stateful_widget(
+        Table::new(rows, widths)
+            .header(
+                Row::new(["REPOSITORY", "WORKFLOW", "STATUS", "AGE", "ACTOR"])
+                    .style(Style::new().add_modifier(Modifier::BOLD)),
+            )
+            .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED)),
+        table,
+        &mut state,
+    );
+
+    frame.render_widget(
+        Line::from(match dashboard.message() {
+            Some(message) => format!("{KEYS}  | {message}"),
+            None => KEYS.to_string(),
+        })
+        .dim(),
+        footer,
+    );
+}
+
+/// The connection state, green while frames keep arriving.
+fn freshness(freshness: &Freshness, now: DateTime<Utc>) -> Span<'static> {
+    match freshness {
+        Freshness::Connecting => "○ connecting".yellow(),
+        Freshness::Live => "● live".green(),
+        Freshness::Stale { last_frame_at } => {
+            format!("● stale, last update {} ago", age(*last_frame_at, now)).yellow()
+        }
+        Freshness::Disconnected { error, retry_at } => format!(
+            "○ disconnected ({error}), retrying in {}",
+            age(now, *retry_at)
+        )
+        .red(),
+    }
+}
+
+fn status_color(run: &WorkflowRun) -> Color {
+    match (run.status, run.conclusion) {
+        (RunStatus::Completed, Some(RunConclusion::Success)) => Color::Green,
+        (RunStatus::Completed, Some(conclusion)) if conclusion.is_problem() => Color::Red,
+        (RunStatus::Completed, _) => Color::DarkGray,
+        _ => Color::Yellow,
+    }
+}
+
+/// Time from `since` to `now` in its largest whole unit, e.g. `42s` or `3h`.
+fn age(since: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let seconds = (now - since).num_seconds().max(0);
+    match seconds {
+        0..60 => format!("{seconds}s"),
+        60..3_600 => format!("{}m", seconds / 60),
+        3_600..86_400 => format!("{}h", seconds / 3_600),
+        _ => format!("{}d", seconds / 86_400),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructures::adapters::primary::tui::feed::{FeedEvent, Frame, Snapshot};
+    use crate::test_support::{base_time, workflow_run};
+    use chrono::TimeDelta;
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+
+    #[test]
+    fn test_ages_use_their_largest_unit() {
+        let age = |seconds| age(base_time(), base_time() + TimeDelta::seconds(seconds));
+
+        assert_eq!(age(-5), "0s");
+        assert_eq!(age(59), "59s");
+        assert_eq!(age(60), "1m");
+        assert_eq!(age(7_199), "1h");
+        assert_eq!(age(3 * 86_400), "3d");
+    }
+
+    #[test]
+    fn test_dashboard_renders_runs_under_a_stale_indicator() -> anyhow::Result<()> {
+        let mut dashboard = Dashboard::new(TimeDelta::seconds(90));
+        let mut dependabot = workflow_run("owner/other", 2, "failure");
+        dependabot.actor.login = "dependabot[bot]".to_string();
+        dashboard.apply(
+            FeedEvent::Frame(Frame::Snapshot(Snapshot {
+                seq: 3,
+                runs: vec![workflow_run("owner/repo", 3, "in_progress"), dependabot],
+                generated_at: base_time(),
+            })),
+            base_time(),
+        );
+        let mut terminal = Terminal::new(TestBackend::new(72, 5))?;
+
+        terminal.draw(|frame| render(frame, &dashboard, base_time() + TimeDelta::minutes(3)))?;
+
+        insta::assert_snapshot!(terminal.backend());
+        Ok(())
+    }
+}
@@ -0,0 +1,407 @@
+use super::auth::Authenticated;
+use super::{AppState, json_error};
+use crate::application::use_cases::stream_github_actions_runs::config::{
+    StreamConfig, StreamConfigPatch,
+};
+use crate::domain::external_apis::github::calls::GitHubOperation;
+use crate::infrastructures::adapters::secondary::external_apis::github::request_log::{
+    GitHubRequest, RequestLogSummary,
+};
+use axum::{
+    Json,
+    extract::{
+        Query, State,
+        rejection::{JsonRejection, QueryRejection},
+    },
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Placeholder shown instead of configured secrets
+const REDACTED: &str = "[REDACTED]";
+
+/// Effective configuration as returned by `/admin/config`
+#[derive(Serialize, Debug)]
+struct EffectiveConfig {
+    #[serde(flatten)]
+    stream: StreamConfig,
+    /// `null` when authentication is disabled
+    #[serde(rename = "authToken")]
+    auth_token: Option<&'static str>,
+}
+
+impl EffectiveConfig {
+    fn new(state: &AppState, stream: StreamConfig) -> Self {
+        Self {
+            stream,
+            auth_token: state.auth_token.as_ref().map(|_| REDACTED),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+struct ConfigRejected {
+    error: &'static str,
+    violations: Vec<String>,
+}
+
+#[tracing::instrument(name = "get_config_handler", skip_all)]
+pub async fn get_config_handler(
+    _auth: Authenticated,
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    Json(EffectiveConfig::new(&state, state.poller.config())).into_response()
+}
+
+#[tracing::instrument(name = "update_config_handler", skip_all)]
+pub async fn update_config_handler(
+    auth: Authenticated,
+    State(state): State<Arc<AppState>>,
+    patch: Result<Json<StreamConfigPatch>, JsonRejection>,
+) -> Response {
+    auth.audit("update_config");
+    let Json(patch) = match patch {
+        Ok(patch) => patch,
+        Err(rejection) => return json_error(rejection.status(), &rejection.body_text()),
+    };
+
+    match state.poller.update_config(&patch) {
+        Ok(config) => Json(EffectiveConfig::new(&state, config)).into_response(),
+        Err(e) => {
+            tracing::warn!("Rejected config patch: {}", e);
+            (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(ConfigRejected {
+                    error: "Invalid configuration",
+                    violations: e.violations,
+                }),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[tracing::instrument(name = "compact_handler", skip_all)]
+pub async fn compact_handler(auth: Authenticated, State(state): State<Arc<AppState>>) -> Response {
+    auth.audit("compact");
+    let Some(compactor) = &state.history_compactor else {
+        return json_error(
+            StatusCode::NOT_FOUND,
+            "Run history is disabled, set DATABASE_URL to enable it",
+        );
+    };
+    match compactor.compact().await {
+        Ok(report) => {
+            state.metrics.record_compaction(&report);
+            Json(report).into_response()
+        }
+        Err(e) => {
+            tracing::error!("Failed to compact run history: {}", e);
+            json_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to compact run history",
+            )
+        }
+    }
+}
+
+/// The last comparison of the runs pushed by webhooks with a poll.
+#[tracing::instrument(name = "reconciliation_handler", skip_all)]
+pub async fn reconciliation_handler(
+    _auth: Authenticated,
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    match state.poller.last_reconciliation() {
+        Some(report) => Json(report.as_ref()).into_response(),
+        None => json_error(
+            StatusCode::NOT_FOUND,
+            "No reconciliation has run yet, it starts once webhooks push runs",
+        ),
+    }
+}
+
+/// Parameters of `GET /admin/github-requests`.
+#[derive(Deserialize, Debug)]
+pub struct GitHubRequestsQuery {
+    /// Only requests of this operation, e.g. `workflow_runs`
+    operation: Option<String>,
+}
+
+/// The request log as returned by `/admin/github-requests`
+#[derive(Serialize, Debug)]
+struct GitHubRequests {
+    capacity: usize,
+    /// Every request in the log, whatever `?operation=` picks
+    summary: RequestLogSummary,
+    requests: Vec<GitHubRequest>,
+}
+
+/// The latest requests sent to GitHub, newest first, to tell what used up the rate limit.
+#[tracing::instrument(name = "github_requests_handler", skip_all)]
+pub async fn github_requests_handler(
+    _auth: Authenticated,
+    query: Result<Query<GitHubRequestsQuery>, QueryRejection>,
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    let Query(query) = match query {
+        Ok(query) => query,
+        Err(rejection) => return json_error(rejection.status(), &rejection.body_text()),
+    };
+    let operation = match query.operation.as_deref() {
+        Some(name) => match GitHubOperation::ALL
+            .into_iter()
+            .find(|operation| operation.as_str() == name)
+        {
+            Some(operation) => Some(operation),
+            None => {
+                return json_error(
+                    StatusCode::BAD_REQUEST,
+                    &format!("Unknown operation {name:?}, expected e.g. workflow_runs"),
+                );
+            }
+        },
+        None => None,
+    };
+    let log = &state.github_requests;
+    Json(GitHubRequests {
+        capacity: log.capacity(),
+        summary: log.summary(),
+        requests: log.requests(operation),
+    })
+    .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::services::HistoryCompactor;
+    use crate::domain::repositories::{RetentionPolicy, RunRepository};
+    use crate::infrastructures::adapters::primary::web::create_router;
+    use crate::infrastructures::adapters::secondary::persistence::SqliteRunRepository;
+    use crate::test_support::{StubGitHubApi, app_state, base_time, workflow_run};
+    use axum::body::{Body, to_bytes};
+    use axum::http::{Request, header};
+    use chrono::TimeDelta;
+    use tower::ServiceExt;
+
+    fn put_config(body: &str) -> anyhow::Result<Request<Body>> {
+        Ok(Request::put("/admin/config")
+            .header(header::AUTHORIZATION, "Bearer secret")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))?)
+    }
+
+    async fn json_body(response: Response) -> anyhow::Result<serde_json::Value> {
+        Ok(serde_json::from_slice(
+            &to_bytes(response.into_body(), usize::MAX).await?,
+        )?)
+    }
+
+    #[tokio::test]
+    async fn test_get_config_redacts_auth_token() -> anyhow::Result<()> {
+        let router = create_router(app_state(StubGitHubApi::default(), Some("secret"))?);
+
+        let response = router
+            .oneshot(
+                Request::get("/admin/config")
+                    .header(header::AUTHORIZATION, "Bearer secret")
+                    .body(Body::empty())?,
+            )
+            .await?;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = json_body(response).await?;
+        assert_eq!(body["pollIntervalSeconds"], 30);
+        assert_eq!(body["authToken"], REDACTED);
+        assert!(!body.to_string().contains("secret"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_valid_patch_is_applied_to_poller() -> anyhow::Result<()> {
+        let state = app_state(StubGitHubApi::default(), Some("secret"))?;
+        let router = create_router(Arc::clone(&state));
+
+        let response = router
+            .oneshot(put_config(
+                r#"{"pollIntervalSeconds": 120, "problemOnly": true}"#,
+            )?)
+            .await?;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let config = state.poller.config();
+        assert_eq!(config.poll_interval_seconds, 120);
+        assert!(config.problem_only);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_over_budget_patch_is_rejected_with_violations() -> anyhow::Result<()> {
+        let state = app_state(StubGitHubApi::default(), Some("secret"))?;
+        let router = create_router(Arc::clone(&state));
+        let allowlist = (0..20)
+            .map(|i| format!("\"owner/repo{i}\""))
+            .collect::<Vec<_>>()
+            .join(",");
+
+        let response = router
+            .oneshot(put_config(&format!(
+                r#"{{"pollIntervalSeconds": 10, "repoAllowlist": [{allowlist}]}}"#
+            ))?)
+            .await?;
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = json_body(response).await?;
+        let violations = body["violations"]
+            .as_array()
+            .ok_or_else(|| anyhow::anyhow!("missing violations"))?;
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].as_str().is_some_and(|v| v.contains("budget")));
+        // The running config is untouched
+        assert_eq!(state.poller.config(), StreamConfig::default());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_compact_deletes_old_runs_and_records_metrics() -> anyhow::Result<()> {
+        let run_repository = Arc::new(SqliteRunRepository::connect("sqlite::memory:")?);
+        let mut old = workflow_run("owner/repo", 1, "success");
+        old.created_at -= TimeDelta::days(365);
+        old.updated_at = old.created_at;
+        run_repository
+            .save_runs(
+                &[old, workflow_run("owner/repo", 2, "success")],
+                base_time(),
+            )
+            .await?;
+        let policy = RetentionPolicy {
+            max_age: Some(TimeDelta::days(90)),
+            max_rows: None,
+        };
+        let state = Arc::into_inner(app_state(StubGitHubApi::default(), Some("secret"))?)
+            .ok_or_else(|| anyhow::anyhow!("state is shared"))?
+            .with_history_compactor(Arc::new(HistoryCompactor::new(run_repository, policy)));
+        let state = Arc::new(state);
+
+        let response = create_router(Arc::clone(&state))
+            .oneshot(
+                Request::post("/admin/compact")
+                    .header(header::AUTHORIZATION, "Bearer secret")
+                    .body(Body::empty())?,
+            )
+            .await?;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = json_body(response).await?;
+        assert_eq!(body, serde_json::json!({"deleted": 1, "remaining": 1}));
+        assert_eq!(state.metrics.history_runs_deleted.get(), 1);
+        assert_eq!(state.metrics.history_runs.get(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_compact_is_not_found_without_a_store() -> anyhow::Result<()> {
+        let router = create_router(app_state(StubGitHubApi::default(), Some("secret"))?);
+
+        let response = router
+            .oneshot(
+                Request::post("/admin/compact")
+                    .header(header::AUTHORIZATION, "Bearer secret")
+                    .body(Body::empty())?,
+            )
+            .await?;
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reconciliation_is_not_found_before_webhooks_push_runs() -> anyhow::Result<()> {
+        let router = create_router(app_state(StubGitHubApi::default(), Some("secret"))?);
+
+        let unauthenticated = router
+            .clone()
+            .oneshot(Request::get("/admin/reconciliation").body(Body::empty())?)
+            .await?;
+        let response = router
+            .oneshot(
+                Request::get("/admin/reconciliation")
+                    .header(header::AUTHORIZATION, "Bearer secret")
+                    .body(Body::empty())?,
+            )
+            .await?;
+
+        assert_eq!(unauthenticated.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert!(json_body(response).await?["error"].is_string());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_github_requests_are_filtered_by_operation() -> anyhow::Result<()> {
+        let state = app_state(StubGitHubApi::default(), Some("secret"))?;
+        for (second, operation) in [
+            (0, GitHubOperation::Repositories),
+            (1, GitHubOperation::WorkflowRuns),
+            (2, GitHubOperation::WorkflowRuns),
+        ] {
+            state.github_requests.record(GitHubRequest {
+                at: base_time() + TimeDelta::seconds(second),
+                operation,
+                route: "/user/repos".to_string(),
+                status: Some(200),
+                latency_ms: 50,
+                rate_limit_remaining: Some(4000),
+                error: None,
+            });
+        }
+        let router = create_router(state);
+        let get = |uri: &str| {
+            Request::get(uri)
+                .header(header::AUTHORIZATION, "Bearer secret")
+                .body(Body::empty())
+        };
+
+        let unauthenticated = router
+            .clone()
+            .oneshot(Request::get("/admin/github-requests").body(Body::empty())?)
+            .await?;
+        let response = router
+            .clone()
+            .oneshot(get("/admin/github-requests?operation=repositories")?)
+            .await?;
+        let unknown = router
+            .oneshot(get("/admin/github-requests?operation=everything")?)
+            .await?;
+
+        assert_eq!(unauthenticated.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(unknown.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = json_body(response).await?;
+        assert_eq!(body["capacity"], 1000);
+        assert_eq!(body["requests"].as_array().map(Vec::len), Some(1));
+        assert_eq!(body["requests"][0]["operation"], "repositories");
+        assert_eq!(body["requests"][0]["rateLimitRemaining"], 4000);
+        assert_eq!(body["summary"]["requests"], 3);
+        assert_eq!(
+            body["summary"]["operations"][0]["operation"],
+            "workflow_runs"
+        );
+        assert_eq!(body["summary"]["operations"][0]["requests"], 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_config_requires_authentication() -> anyhow::Result<()> {
+        let router = create_router(app_state(StubGitHubApi::default(), Some("secret"))?);
+
+        let response = router
+            .oneshot(Request::get("/admin/config").body(Body::empty())?)
+            .await?;
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        Ok(())
+    }
+}
@@ -0,0 +1,396 @@
+use super::rate_limit::route_template;
+use super::{AppState, json_error};
+use axum::{
+    body::Body,
+    extract::{FromRequestParts, State},
+    http::{HeaderMap, Request, StatusCode, header, request::Parts},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::sync::Arc;
+use tracing::Instrument;
+
+/// Header naming the user an authenticating proxy signed in
+pub const FORWARDED_USER_HEADER: &str = "x-forwarded-user";
+
+/// Header listing the groups of that user, comma-separated
+pub const FORWARDED_GROUPS_HEADER: &str = "x-forwarded-groups";
+
+/// Target of the log entries naming who triggered a mutating endpoint
+pub const AUDIT_TARGET: &str = "audit";
+
+/// Routes open to requests without forwarded identity headers: probes, which reach the
+/// server without going through the proxy, and webhooks, which check a signature instead
+const IDENTITY_EXEMPT_ROUTES: [&str; 3] = ["/health", "/ready", "/webhooks/github"];
+
+/// Routes open to anyone in anonymized deployments: those serving runs anonymized, those
+/// naming no repository, and those checking credentials of their own
+const ANONYMIZED_PUBLIC_ROUTES: [&str; 10] = [
+    "/ws",
+    "/sse",
+    "/runs",
+    "/runs/bot-group",
+    "/history",
+    "/health",
+    "/ready",
+    "/schema",
+    "/push/vapid-public-key",
+    "/webhooks/github",
+];
+
+/// Who made a request, as forwarded by an authenticating proxy such as oauth2-proxy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestIdentity {
+    pub user: String,
+    pub groups: Vec<String>,
+}
+
+impl RequestIdentity {
+    /// The identity `X-Forwarded-User` and `X-Forwarded-Groups` name; `None` without a user.
+    #[must_use]
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let value = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::trim)
+        };
+        let user = value(FORWARDED_USER_HEADER).filter(|user| !user.is_empty())?;
+        let groups = value(FORWARDED_GROUPS_HEADER)
+            .into_iter()
+            .flat_map(|groups| groups.split(','))
+            .map(str::trim)
+            .filter(|group| !group.is_empty())
+            .map(str::to_string)
+            .collect();
+        Some(Self {
+            user: user.to_string(),
+            groups,
+        })
+    }
+}
+
+/// `AUTH_MODE=forward_headers`: a proxy in front of the dashboard authenticates every
+/// request and forwards who made it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ForwardedIdentityAuth {
+    /// Groups whose members are let in; empty lets in every forwarded user
+    pub allowed_groups: Vec<String>,
+}
+
+impl ForwardedIdentityAuth {
+    #[must_use]
+    pub fn allows(&self, identity: &RequestIdentity) -> bool {
+        self.allowed_groups.is_empty()
+            || identity
+                .groups
+                .iter()
+                .any(|group| self.allowed_groups.contains(group))
+    }
+}
+
+/// Middleware of `AUTH_MODE=forward_headers`, a no-op otherwise: rejects requests without
+/// a forwarded user with 401 and users outside the allowed groups with 403, and hands the
+/// [`RequestIdentity`] of the others to the handler as an extension, in a span naming it.
+pub async fn forwarded_identity_middleware(
+    State(state): State<Arc<AppState>>,
+    mut request: Request<Body>,
+    next: Next,
+) -> Response {
+    let Some(auth) = &state.forwarded_identity else {
+        return next.run(request).await;
+    };
+    if IDENTITY_EXEMPT_ROUTES.contains(&route_template(&request).as_str()) {
+        return next.run(request).await;
+    }
+    let Some(identity) = RequestIdentity::from_headers(request.headers()) else {
+        tracing::warn!(
+            "Rejected {} {} without {}",
+            request.method(),
+            request.uri().path(),
+            FORWARDED_USER_HEADER
+        );
+        return json_error(StatusCode::UNAUTHORIZED, "Missing forwarded identity");
+    };
+    if !auth.allows(&identity) {
+        tracing::warn!(
+            "Rejected {} {} from {}, who is in none of the allowed groups",
+            request.method(),
+            request.uri().path(),
+            identity.user
+        );
+        return json_error(StatusCode::FORBIDDEN, "User not in an allowed group");
+    }
+    let span = tracing::info_span!(
+        "identity",
+        user = %identity.user,
+        groups = %identity.groups.join(",")
+    );
+    request.extensions_mut().insert(identity);
+    next.run(request).instrument(span).await
+}
+
+/// Extractor guarding routes that require the `AUTH_TOKEN` bearer token, or a forwarded
+/// identity with `AUTH_MODE=forward_headers`.
+///
+/// When no token is configured, every request is accepted.
+pub struct Authenticated {
+    /// The forwarded user, else how the caller authenticated
+    caller: String,
+}
+
+impl Authenticated {
+    /// Logs that the caller triggered `action`, such as `refresh`, under [`AUDIT_TARGET`].
+    pub fn audit(&self, action: &str) {
+        tracing::info!(
+            target: AUDIT_TARGET,
+            user = %self.caller,
+            action,
+            "{} triggered by {}",
+            action,
+            self.caller
+        );
+    }
+}
+
+impl FromRequestParts<Arc<AppState>> for Authenticated {
+    type Rejection = Response;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        if state.forwarded_identity.is_some() {
+            // Set by `forwarded_identity_middleware` on every route but the exempt ones
+            return match parts.extensions.get::<RequestIdentity>() {
+                Some(identity) => Ok(Self {
+                    caller: identity.user.clone(),
+                }),
+                None => Err(json_error(
+                    StatusCode::UNAUTHORIZED,
+                    "Missing forwarded identity",
+                )),
+            };
+        }
+        let Some(expected) = state.auth_token.as_deref() else {
+            return Ok(Self {
+                caller: "anonymous".to_string(),
+            });
+        };
+
+        let provided = parts
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+
+        match provided {
+            Some(token) if constant_time_eq(token.as_bytes(), expected.as_bytes()) => Ok(Self {
+                caller: "bearer token".to_string(),
+            }),
+            _ => {
+                tracing::warn!("Rejected unauthenticated request to {}", parts.uri.path());
+                Err((
+                    StatusCode::UNAUTHORIZED,
+                    [(header::WWW_AUTHENTICATE, "Bearer")],
+                    "Unauthorized",
+                )
+                    .into_response())
+            }
+        }
+    }
+}
+
+/// Middleware requiring the bearer token on every route that would show real names in an
+/// anonymized deployment, such as `/status` and `/admin`; a no-op otherwise.
+pub async fn anonymized_routes_middleware(
+    State(state): State<Arc<AppState>>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    if state.anonymizer.is_none()
+        || ANONYMIZED_PUBLIC_ROUTES.contains(&route_template(&request).as_str())
+    {
+        return next.run(request).await;
+    }
+    let (mut parts, body) = request.into_parts();
+    if let Err(rejection) = Authenticated::from_request_parts(&mut parts, &state).await {
+        return rejection;
+    }
+    next.run(Request::from_parts(parts, body)).await
+}
+
+/// Compares two byte strings without short-circuiting on the first mismatch.
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructures::adapters::primary::web::create_router;
+    use crate::test_support::{StubGitHubApi, app_state};
+    use std::io;
+    use std::sync::{Mutex, PoisonError};
+    use tower::ServiceExt;
+    use tracing_subscriber::fmt::MakeWriter;
+
+    /// Collects the lines logged while it is the default subscriber's writer.
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for Captured {
+        type Writer = Self;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    fn forwarded_identity_state(allowed_groups: &[&str]) -> anyhow::Result<Arc<AppState>> {
+        let state = Arc::into_inner(app_state(StubGitHubApi::default(), None)?)
+            .ok_or_else(|| anyhow::anyhow!("state is shared"))?
+            .with_trust_proxy(true)
+            .with_forwarded_identity(ForwardedIdentityAuth {
+                allowed_groups: allowed_groups.iter().map(ToString::to_string).collect(),
+            });
+        Ok(Arc::new(state))
+    }
+
+    async fn status_of(
+        state: &Arc<AppState>,
+        request: axum::http::request::Builder,
+    ) -> anyhow::Result<StatusCode> {
+        Ok(create_router(Arc::clone(state))
+            .oneshot(request.body(Body::empty())?)
+            .await?
+            .status())
+    }
+
+    #[tokio::test]
+    async fn test_requests_without_a_forwarded_user_are_rejected() -> anyhow::Result<()> {
+        let state = forwarded_identity_state(&[])?;
+
+        assert_eq!(
+            status_of(&state, Request::get("/admin/config")).await?,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            status_of(
+                &state,
+                Request::get("/status").header(FORWARDED_USER_HEADER, " ")
+            )
+            .await?,
+            StatusCode::UNAUTHORIZED
+        );
+        // A bearer token is no substitute for the proxy
+        assert_eq!(
+            status_of(
+                &state,
+                Request::get("/status").header(header::AUTHORIZATION, "Bearer secret")
+            )
+            .await?,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            status_of(&state, Request::get("/health")).await?,
+            StatusCode::OK
+        );
+        assert_eq!(
+            status_of(
+                &state,
+                Request::get("/admin/config").header(FORWARDED_USER_HEADER, "alice")
+            )
+            .await?,
+            StatusCode::OK
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_only_members_of_the_allowed_groups_are_let_in() -> anyhow::Result<()> {
+        let state = forwarded_identity_state(&["ci-admins", "sre"])?;
+        let request = |groups: Option<&str>| {
+            let request = Request::get("/admin/config").header(FORWARDED_USER_HEADER, "alice");
+            match groups {
+                Some(groups) => request.header(FORWARDED_GROUPS_HEADER, groups),
+                None => request,
+            }
+        };
+
+        assert_eq!(
+            status_of(&state, request(Some("developers, sre"))).await?,
+            StatusCode::OK
+        );
+        assert_eq!(
+            status_of(&state, request(Some("developers,ci-admins-readonly"))).await?,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            status_of(&state, request(None)).await?,
+            StatusCode::FORBIDDEN
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_mutating_endpoints_log_who_triggered_them() -> anyhow::Result<()> {
+        let state = forwarded_identity_state(&[])?;
+        let captured = Captured::default();
+        let _subscriber = tracing::subscriber::set_default(
+            tracing_subscriber::fmt()
+                .with_writer(captured.clone())
+                .with_ansi(false)
+                .finish(),
+        );
+
+        let status = status_of(
+            &state,
+            Request::post("/refresh")
+                .header(FORWARDED_USER_HEADER, "alice")
+                .header(FORWARDED_GROUPS_HEADER, "sre"),
+        )
+        .await?;
+
+        assert_eq!(status, StatusCode::ACCEPTED);
+        let logs = String::from_utf8(
+            captured
+                .0
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clone(),
+        )?;
+        let audit = logs
+            .lines()
+            .find(|line| line.contains(AUDIT_TARGET) && line.contains("refresh triggered by"))
+            .ok_or_else(|| anyhow::anyhow!("no audit entry in {logs}"))?;
+        assert!(audit.contains("refresh triggered by alice"), "{audit}");
+        assert!(audit.contains("user=alice"), "{audit}");
+        Ok(())
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret2"));
+        assert!(constant_time_eq(b"", b""));
+    }
+}
@@ -0,0 +1,120 @@
+use crate::infrastructures::adapters::secondary::external_apis::github::retry_events::{
+    GitHubApiHealth, GitHubRetryEvents, RetryEvent,
+};
+use chrono::Utc;
+use serde::Serialize;
+use std::sync::Arc;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::time::{Duration, Instant, sleep_until};
+
+/// Shortest time between two `backend_status` frames sent to a websocket client
+pub const BACKEND_STATUS_INTERVAL: Duration = Duration::from_secs(3);
+
+/// A `backend_status` frame: a retry of a GitHub request and the health it leaves the API in.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename = "backend_status")]
+pub struct BackendStatusFrame {
+    pub health: GitHubApiHealth,
+    #[serde(flatten)]
+    pub retry: RetryEvent,
+}
+
+/// Retries of GitHub requests for a websocket client that asked for them, throttled: of the
+/// retries within an interval, only the latest is sent, at its end.
+pub struct BackendStatusFeed {
+    retry_events: Arc<GitHubRetryEvents>,
+    /// `None` once the events stopped
+    events: Option<broadcast::Receiver<RetryEvent>>,
+    /// The latest retry not sent yet
+    pending: Option<RetryEvent>,
+    /// When the next frame may be sent
+    next_at: Instant,
+    interval: Duration,
+}
+
+impl BackendStatusFeed {
+    /// Follows the retries published to `retry_events` from now on, sending at most one frame
+    /// per `interval`.
+    #[must_use]
+    pub fn new(retry_events: Arc<GitHubRetryEvents>, interval: Duration) -> Self {
+        Self {
+            events: Some(retry_events.subscribe()),
+            retry_events,
+            pending: None,
+            next_at: Instant::now(),
+            interval,
+        }
+    }
+
+    /// The next frame to send; `None` once the events stopped.
+    ///
+    /// Cancel safe: a retry received meanwhile is kept for the next call.
+    pub async fn next(&mut self) -> Option<BackendStatusFrame> {
+        loop {
+            let events = self.events.as_mut()?;
+            tokio::select! {
+                received = events.recv() => match received {
+                    Ok(retry) if Instant::now() >= self.next_at => return Some(self.frame(retry)),
+                    Ok(retry) => self.pending = Some(retry),
+                    // The latest retry is what counts, the ones skipped are not missed
+                    Err(RecvError::Lagged(_)) => {}
+                    Err(RecvError::Closed) => self.events = None,
+                },
+                () = sleep_until(self.next_at), if self.pending.is_some() => {
+                    if let Some(retry) = self.pending.take() {
+                        return Some(self.frame(retry));
+                    }
+                }
+            }
+        }
+    }
+
+    fn frame(&mut self, retry: RetryEvent) -> BackendStatusFrame {
+        self.next_at = Instant::now() + self.interval;
+        BackendStatusFrame {
+            health: self.retry_events.health(Utc::now()).state,
+            retry,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::external_apis::github::calls::GitHubOperation;
+
+    fn retry(attempt: u32) -> RetryEvent {
+        RetryEvent {
+            at: Utc::now(),
+            operation: GitHubOperation::WorkflowRuns,
+            attempt,
+            category: "network",
+            next_wait_ms: Some(1000),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retries_within_an_interval_are_sent_as_the_latest() -> anyhow::Result<()> {
+        let retry_events = Arc::new(GitHubRetryEvents::default());
+        let mut feed =
+            BackendStatusFeed::new(Arc::clone(&retry_events), Duration::from_millis(200));
+
+        for attempt in 1..=3 {
+            retry_events.publish(retry(attempt));
+        }
+        let first = tokio::time::timeout(Duration::from_secs(1), feed.next()).await?;
+        let started = Instant::now();
+        let second = tokio::time::timeout(Duration::from_secs(1), feed.next()).await?;
+
+        assert_eq!(first.map(|frame| frame.retry.attempt), Some(1));
+        let second = second.ok_or_else(|| anyhow::anyhow!("no second frame"))?;
+        assert_eq!(second.retry.attempt, 3);
+        assert_eq!(second.health, GitHubApiHealth::DegradedWithRetries);
+        assert!(started.elapsed() >= Duration::from_millis(150));
+        assert_eq!(
+            serde_json::to_value(&second)?["type"],
+            serde_json::json!("backend_status")
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,279 @@
+use super::AppState;
+use crate::domain::external_apis::github::GitHubApiError;
+use crate::domain::models::ids::RepoFullName;
+use crate::domain::models::run::WorkflowRun;
+use crate::domain::models::status::{RunConclusion, RunStatus};
+use axum::{
+    extract::{Path, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio::time::{Duration, Instant};
+
+/// How long an on-demand lookup for a repository outside the snapshot is reused (seconds)
+const BADGE_CACHE_TTL_SECONDS: u64 = 30;
+
+/// Maximum number of repositories kept in the on-demand lookup cache
+const BADGE_CACHE_MAX_ENTRIES: usize = 256;
+
+/// Approximate width of one character of 11px Verdana, in pixels
+const CHAR_WIDTH: usize = 7;
+
+/// Horizontal padding of each badge half, in pixels
+const PADDING: usize = 10;
+
+const COLOR_SUCCESS: &str = "#4c1";
+const COLOR_FAILURE: &str = "#e05d44";
+const COLOR_IN_PROGRESS: &str = "#dfb317";
+const COLOR_UNKNOWN: &str = "#9f9f9f";
+
+/// Short-lived cache of the latest run of repositories not covered by the poller snapshot.
+#[derive(Default)]
+pub struct BadgeCache {
+    entries: Mutex<HashMap<String, (Instant, Option<WorkflowRun>)>>,
+}
+
+impl BadgeCache {
+    async fn get(&self, key: &str) -> Option<Option<WorkflowRun>> {
+        let entries = self.entries.lock().await;
+        entries
+            .get(key)
+            .filter(|(fetched_at, _)| {
+                fetched_at.elapsed() < Duration::from_secs(BADGE_CACHE_TTL_SECONDS)
+            })
+            .map(|(_, run)| run.clone())
+    }
+
+    async fn insert(&self, key: String, run: Option<WorkflowRun>) {
+        let mut entries = self.entries.lock().await;
+        if entries.len() >= BADGE_CACHE_MAX_ENTRIES {
+            entries.retain(|_, (fetched_at, _)| {
+                fetched_at.elapsed() < Duration::from_secs(BADGE_CACHE_TTL_SECONDS)
+            });
+            if entries.len() >= BADGE_CACHE_MAX_ENTRIES {
+                entries.clear();
+            }
+        }
+        entries.insert(key, (Instant::now(), run));
+    }
+}
+
+/// Picks the badge color for a workflow run.
+fn status_color(run: &WorkflowRun) -> &'static str {
+    match (run.status, run.conclusion) {
+        (RunStatus::Completed, Some(RunConclusion::Success)) => COLOR_SUCCESS,
+        (
+            RunStatus::Completed,
+            Some(RunConclusion::Failure | RunConclusion::TimedOut | RunConclusion::StartupFailure),
+        ) => COLOR_FAILURE,
+        (
+            RunStatus::InProgress
+            | RunStatus::Queued
+            | RunStatus::Waiting
+            | RunStatus::Pending
+            | RunStatus::Requested,
+            _,
+        ) => COLOR_IN_PROGRESS,
+        _ => COLOR_UNKNOWN,
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '&' => "&amp;".to_string(),
+            '<' => "&lt;".to_string(),
+            '>' => "&gt;".to_string(),
+            '"' => "&quot;".to_string(),
+            '\'' => "&apos;".to_string(),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+/// Renders a flat shield-style badge with `label` on the left and `message` on the right.
+#[must_use]
+pub fn render_badge(label: &str, message: &str, color: &str) -> String {
+    let label_width = label.chars().count() * CHAR_WIDTH + PADDING;
+    let message_width = message.chars().count() * CHAR_WIDTH + PADDING;
+    let width = label_width + message_width;
+    let label_x = label_width / 2;
+    let message_x = label_width + message_width / 2;
+    let label = escape_xml(label);
+    let message = escape_xml(message);
+
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="20" role="img" aria-label="{label}: {message}"><title>{label}: {message}</title><linearGradient id="s" x2="0" y2="100%"><stop offset="0" stop-color="#bbb" stop-opacity=".1"/><stop offset="1" stop-opacity=".1"/></linearGradient><clipPath id="r"><rect width="{width}" height="20" rx="3" fill="#fff"/></clipPath><g clip-path="url(#r)"><rect width="{label_width}" height="20" fill="#555"/><rect x="{label_width}" width="{message_width}" height="20" fill="{color}"/><rect width="{width}" height="20" fill="url(#s)"/></g><g fill="#fff" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="11"><text x="{label_x}" y="15" fill="#010101" fill-opacity=".3">{label}</text><text x="{label_x}" y="14">{label}</text><text x="{message_x}" y="15" fill="#010101" fill-opacity=".3">{message}</text><text x="{message_x}" y="14">{message}</text></g></svg>"##
+    )
+}
+
+/// Renders the badge for the latest run of a repository, or a "not monitored" badge.
+fn render_run_badge(run: Option<&WorkflowRun>) -> String {
+    match run {
+        Some(run) => render_badge(
+            &run.workflow_name,
+            &run.display_status().replace('_', " "),
+            status_color(run),
+        ),
+        None => render_badge("workflow", "not monitored", COLOR_UNKNOWN),
+    }
+}
+
+/// Finds the latest run of a repository, preferring the poller snapshot over GitHub.
+async fn latest_run(state: &AppState, full_name: &RepoFullName) -> Option<WorkflowRun> {
+    if let Some(snapshot) = state.snapshots.latest()
+        && let Some(run) = snapshot
+            .runs
+            .iter()
+            .filter(|run| run.repository_name.eq_ignore_ascii_case(full_name))
+            .max_by_key(|run| run.created_at)
+    {
+        return Some(run.clone());
+    }
+
+    let cache_key = full_name.to_string().to_ascii_lowercase();
+    if let Some(run) = state.badge_cache.get(&cache_key).await {
+        return run;
+    }
+
+    match state.github_api.fetch_workflow_runs(full_name, 1).await {
+        Ok(runs) => {
+            let run = runs.into_iter().max_by_key(|run| run.created_at);
+            state.badge_cache.insert(cache_key, run.clone()).await;
+            run
+        }
+        Err(GitHubApiError::NotFound { .. }) => {
+            state.badge_cache.insert(cache_key, None).await;
+            None
+        }
+        Err(e) => {
+            // Not cached, so the next request retries
+            tracing::warn!("Failed to fetch runs for badge of {}: {:?}", full_name, e);
+            None
+        }
+    }
+}
+
+#[tracing::instrument(name = "badge_handler", skip(state))]
+pub async fn badge_handler(
+    Path((owner, file)): Path<(String, String)>,
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    let Some(repo) = file.strip_suffix(".svg") else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    // Keep `<img>` tags working even for names we refuse to look up
+    let svg = match RepoFullName::new(owner, repo) {
+        Ok(full_name) => render_run_badge(latest_run(&state, &full_name).await.as_ref()),
+        Err(_) => render_run_badge(None),
+    };
+
+    (
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, "image/svg+xml"),
+            (header::CACHE_CONTROL, "max-age=30"),
+        ],
+        svg,
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructures::adapters::primary::web::create_router;
+    use crate::test_support::{StubGitHubApi, app_state, workflow_run};
+    use axum::body::{Body, to_bytes};
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    fn run_with_status(status: &str) -> WorkflowRun {
+        workflow_run("owner/repo", 1, status)
+    }
+
+    #[test]
+    fn test_success_badge_snapshot() {
+        insta::assert_snapshot!(render_run_badge(Some(&run_with_status("success"))));
+    }
+
+    #[test]
+    fn test_failure_badge_snapshot() {
+        insta::assert_snapshot!(render_run_badge(Some(&run_with_status("failure"))));
+    }
+
+    #[test]
+    fn test_in_progress_badge_snapshot() {
+        insta::assert_snapshot!(render_run_badge(Some(&run_with_status("in_progress"))));
+    }
+
+    #[test]
+    fn test_unknown_status_badge_snapshot() {
+        insta::assert_snapshot!(render_run_badge(Some(&run_with_status("neutral"))));
+    }
+
+    #[test]
+    fn test_not_monitored_badge_snapshot() {
+        insta::assert_snapshot!(render_run_badge(None));
+    }
+
+    #[test]
+    fn test_label_is_xml_escaped() {
+        let svg = render_badge("<script>&", "ok", COLOR_SUCCESS);
+        assert!(svg.contains("&lt;script&gt;&amp;"));
+        assert!(!svg.contains("<script>"));
+    }
+
+    #[tokio::test]
+    async fn test_badge_endpoint_fetches_unknown_repo_on_demand() -> anyhow::Result<()> {
+        let github_api = StubGitHubApi {
+            runs: vec![run_with_status("failure")],
+            ..StubGitHubApi::default()
+        };
+
+        let response = create_router(app_state(github_api, None)?)
+            .oneshot(Request::get("/badge/owner/repo.svg").body(Body::empty())?)
+            .await?;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(header::CONTENT_TYPE),
+            Some(&"image/svg+xml".parse()?)
+        );
+        assert_eq!(
+            response.headers().get(header::CACHE_CONTROL),
+            Some(&"max-age=30".parse()?)
+        );
+        let body = to_bytes(response.into_body(), usize::MAX).await?;
+        assert!(std::str::from_utf8(&body)?.contains(COLOR_FAILURE));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_badge_endpoint_returns_grey_badge_for_unknown_repo() -> anyhow::Result<()> {
+        let response = create_router(app_state(StubGitHubApi::default(), None)?)
+            .oneshot(Request::get("/badge/owner/missing.svg").body(Body::empty())?)
+            .await?;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await?;
+        let svg = std::str::from_utf8(&body)?;
+        assert!(svg.contains("not monitored"));
+        assert!(svg.contains(COLOR_UNKNOWN));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_badge_path_without_svg_extension_is_not_found() -> anyhow::Result<()> {
+        let response = create_router(app_state(StubGitHubApi::default(), None)?)
+            .oneshot(Request::get("/badge/owner/repo").body(Body::empty())?)
+            .await?;
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        Ok(())
+    }
+}
@@ -0,0 +1,127 @@
+use axum::extract::ConnectInfo;
+use axum::http::{HeaderMap, Request, header};
+use std::net::{IpAddr, SocketAddr};
+
+/// Determines the client IP of a request.
+///
+/// `Forwarded`, or else `X-Forwarded-For`, is only honored when `trust_proxy` is set, since
+/// any client can send them; otherwise the socket peer address is used. Only the right-most
+/// entry is read, the one the trusted proxy appended, as a client can prepend anything.
+pub fn client_ip<B>(request: &Request<B>, trust_proxy: bool) -> Option<IpAddr> {
+    if trust_proxy {
+        let headers = request.headers();
+        let ip = if headers.contains_key(header::FORWARDED) {
+            // Never fall back to X-Forwarded-For, which the proxy may pass through untouched
+            forwarded(headers)
+        } else {
+            forwarded_for(headers)
+        };
+        if let Some(ip) = ip {
+            return Some(ip);
+        }
+    }
+    request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip())
+}
+
+/// Returns the `for` address of the right-most element of the RFC 7239 `Forwarded` header, e.g. `for=192.0.2.60` or `for="[2001:db8::1]:4711"`.
+///
+/// Obfuscated identifiers such as `for=unknown` yield `None`.
+fn forwarded(headers: &HeaderMap) -> Option<IpAddr> {
+    let element = headers
+        .get(header::FORWARDED)?
+        .to_str()
+        .ok()?
+        .rsplit(',')
+        .next()?;
+    let node = element.split(';').find_map(|pair| {
+        let (name, value) = pair.trim().split_once('=')?;
+        name.eq_ignore_ascii_case("for").then_some(value.trim())
+    })?;
+    let node = node.trim_matches('"');
+    if let Some(bracketed) = node.strip_prefix('[') {
+        // IPv6, optionally followed by a port
+        return bracketed.split_once(']')?.0.parse().ok();
+    }
+    // IPv4, optionally followed by a port
+    node.split(':').next()?.parse().ok()
+}
+
+/// Returns the right-most address of `X-Forwarded-For`.
+fn forwarded_for(headers: &HeaderMap) -> Option<IpAddr> {
+    headers
+        .get("x-forwarded-for")?
+        .to_str()
+        .ok()?
+        .rsplit(',')
+        .next()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+
+    fn request(forwarded_for: Option<&str>, peer: Option<&str>) -> anyhow::Result<Request<Body>> {
+        let mut builder = Request::get("/");
+        if let Some(forwarded_for) = forwarded_for {
+            builder = builder.header("x-forwarded-for", forwarded_for);
+        }
+        let mut request = builder.body(Body::empty())?;
+        if let Some(peer) = peer {
+            request
+                .extensions_mut()
+                .insert(ConnectInfo(peer.parse::<SocketAddr>()?));
+        }
+        Ok(request)
+    }
+
+    #[test]
+    fn test_peer_address_is_used_without_trust_proxy() -> anyhow::Result<()> {
+        let request = request(Some("203.0.113.7"), Some("10.0.0.1:5000"))?;
+        assert_eq!(client_ip(&request, false), Some("10.0.0.1".parse()?));
+        Ok(())
+    }
+
+    #[test]
+    fn test_forwarded_for_is_used_with_trust_proxy() -> anyhow::Result<()> {
+        let request = request(Some("10.0.0.2, 203.0.113.7"), Some("10.0.0.1:5000"))?;
+        assert_eq!(client_ip(&request, true), Some("203.0.113.7".parse()?));
+        Ok(())
+    }
+
+    #[test]
+    fn test_forwarded_is_used_with_trust_proxy() -> anyhow::Result<()> {
+        let mut request = request(Some("203.0.113.7"), Some("10.0.0.1:5000"))?;
+        let mut forwarded = |value: &'static str| {
+            request
+                .headers_mut()
+                .insert(header::FORWARDED, header::HeaderValue::from_static(value));
+            client_ip(&request, true)
+        };
+
+        assert_eq!(
+            forwarded(r#"for=10.0.0.2, for="[2001:db8::1]:4711";proto=https"#),
+            Some("2001:db8::1".parse()?)
+        );
+        assert_eq!(
+            forwarded("proto=https;For=192.0.2.60:8080"),
+            Some("192.0.2.60".parse()?)
+        );
+        // An obfuscated node falls back to the peer, never to X-Forwarded-For
+        assert_eq!(forwarded("for=unknown"), Some("10.0.0.1".parse()?));
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_forwarded_for_falls_back_to_peer() -> anyhow::Result<()> {
+        let request = request(Some("not-an-ip"), Some("[::1]:5000"))?;
+        assert_eq!(client_ip(&request, true), Some("::1".parse()?));
+        Ok(())
+    }
+}
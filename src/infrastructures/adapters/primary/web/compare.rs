@@ -0,0 +1,283 @@
+use super::{AppState, github_error_response, json_error};
+use crate::domain::external_apis::github::GitHubApiError;
+use crate::domain::models::ids::{RepoFullName, RunId};
+use crate::domain::models::run::WorkflowRun;
+use crate::domain::models::run_comparison::{DEFAULT_REGRESSION_THRESHOLD_PERCENT, RunComparison};
+use crate::domain::models::timing::RunTiming;
+use crate::domain::repositories::{RunQuery, RunRepositoryError};
+use axum::{
+    Json,
+    extract::{Query, State, rejection::QueryRejection},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Parameters of `GET /compare`.
+#[derive(Deserialize, Debug)]
+pub struct CompareQuery {
+    /// Repository of both runs, `owner/name`
+    repo: RepoFullName,
+    /// The run compared against, e.g. the last green one
+    base: RunId,
+    /// The run compared, e.g. the latest one
+    head: RunId,
+    /// Slowdown in percent of the base flagged as a regression
+    threshold: Option<f64>,
+}
+
+#[derive(Serialize, Debug)]
+struct CompareResponse {
+    repo: RepoFullName,
+    #[serde(rename = "workflowName")]
+    workflow_name: String,
+    base: RunId,
+    head: RunId,
+    #[serde(rename = "thresholdPercent")]
+    threshold_percent: f64,
+    #[serde(flatten)]
+    comparison: RunComparison,
+}
+
+/// The runs of `repo` with one of `ids`: from the latest snapshot, then from the history
+/// for any it does not hold.
+async fn find_runs(
+    state: &AppState,
+    repo: &RepoFullName,
+    ids: &[RunId],
+) -> Result<Vec<WorkflowRun>, RunRepositoryError> {
+    let query = RunQuery {
+        repository: Some(repo.clone()),
+        ids: ids.to_vec(),
+        ..RunQuery::default()
+    };
+    let mut runs: Vec<WorkflowRun> = state
+        .snapshots
+        .latest()
+        .map(|output| {
+            output
+                .runs
+                .iter()
+                .filter(|run| query.matches(run))
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default();
+    if runs.len() < ids.len()
+        && let Some(run_repository) = &state.run_repository
+    {
+        runs.extend(
+            run_repository
+                .query_runs(&query)
+                .await?
+                .into_iter()
+                .filter(|stored| runs.iter().all(|run| run.id != stored.id))
+                .collect::<Vec<_>>(),
+        );
+    }
+    Ok(runs)
+}
+
+/// Wall-clock time of a run, if GitHub reported its timing; without it the total comes from
+/// the jobs.
+fn run_duration(timing: Result<RunTiming, GitHubApiError>) -> Option<Duration> {
+    timing
+        .ok()
+        .and_then(|timing| timing.run_duration_ms)
+        .map(Duration::from_millis)
+}
+
+#[tracing::instrument(name = "compare_handler", skip(state))]
+pub async fn compare_handler(
+    query: Result<Query<CompareQuery>, QueryRejection>,
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    let Query(query) = match query {
+        Ok(query) => query,
+        Err(rejection) => return json_error(rejection.status(), &rejection.body_text()),
+    };
+    let threshold_percent = query
+        .threshold
+        .unwrap_or(DEFAULT_REGRESSION_THRESHOLD_PERCENT);
+    if !threshold_percent.is_finite() || threshold_percent < 0.0 {
+        return json_error(
+            StatusCode::BAD_REQUEST,
+            "threshold must be a percentage of at least 0",
+        );
+    }
+
+    let runs = match find_runs(&state, &query.repo, &[query.base, query.head]).await {
+        Ok(runs) => runs,
+        Err(e) => {
+            tracing::error!("Failed to read runs to compare: {}", e);
+            return json_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to read runs");
+        }
+    };
+    let find = |id: RunId| runs.iter().find(|run| run.id == id);
+    let (Some(base), Some(head)) = (find(query.base), find(query.head)) else {
+        let missing = if find(query.base).is_none() {
+            query.base
+        } else {
+            query.head
+        };
+        return json_error(
+            StatusCode::NOT_FOUND,
+            &format!("Run {missing} of {} not found", query.repo),
+        );
+    };
+    if base.workflow_id != head.workflow_id {
+        return json_error(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            &format!(
+                "Run {} is of {:?}, run {} of {:?}; only runs of the same workflow compare",
+                base.id, base.workflow_name, head.id, head.workflow_name
+            ),
+        );
+    }
+
+    // Issued together; the adapter's concurrency limit still applies to each
+    let github_api = &state.github_api;
+    let (base_jobs, head_jobs, base_timing, head_timing) = tokio::join!(
+        github_api.fetch_workflow_jobs(&query.repo, base.id),
+        github_api.fetch_workflow_jobs(&query.repo, head.id),
+        github_api.fetch_run_timing(&query.repo, base.id),
+        github_api.fetch_run_timing(&query.repo, head.id),
+    );
+    let (base_jobs, head_jobs) = match (base_jobs, head_jobs) {
+        (Ok(base_jobs), Ok(head_jobs)) => (base_jobs, head_jobs),
+        (Err(e), _) | (_, Err(e)) => return github_error_response(&e, "Jobs not found"),
+    };
+    Json(CompareResponse {
+        repo: query.repo,
+        workflow_name: head.workflow_name.clone(),
+        base: base.id,
+        head: head.id,
+        threshold_percent,
+        comparison: RunComparison::new(
+            &base_jobs,
+            &head_jobs,
+            (run_duration(base_timing), run_duration(head_timing)),
+            threshold_percent,
+        ),
+    })
+    .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::use_cases::stream_github_actions_runs::StreamGitHubActionsRunsUseCaseOutput;
+    use crate::domain::models::ids::{JobId, WorkflowId};
+    use crate::domain::models::job::Job;
+    use crate::domain::models::status::{RunConclusion, RunStatus};
+    use crate::infrastructures::adapters::primary::web::create_router;
+    use crate::test_support::{StubGitHubApi, app_state, base_time, workflow_run};
+    use axum::body::{Body, to_bytes};
+    use axum::http::Request;
+    use chrono::TimeDelta;
+    use tower::ServiceExt;
+
+    fn job(name: &str, seconds: i64) -> Job {
+        Job {
+            id: JobId(1),
+            run_id: RunId(1),
+            name: name.to_string(),
+            status: RunStatus::Completed,
+            conclusion: Some(RunConclusion::Success),
+            started_at: Some(base_time()),
+            completed_at: Some(base_time() + TimeDelta::seconds(seconds)),
+            runner_name: None,
+            labels: Vec::new(),
+            html_url: String::new(),
+            steps: Vec::new(),
+        }
+    }
+
+    async fn get_json(
+        stub: StubGitHubApi,
+        uri: &str,
+    ) -> anyhow::Result<(StatusCode, serde_json::Value)> {
+        let mut other = workflow_run("owner/repo", 3, "success");
+        other.workflow_id = WorkflowId(99);
+        other.workflow_name = "Release".to_string();
+        let runs = vec![
+            workflow_run("owner/repo", 1, "success"),
+            workflow_run("owner/repo", 2, "failure"),
+            other,
+        ];
+        let state = app_state(stub, None)?;
+        state
+            .poller
+            .publish(StreamGitHubActionsRunsUseCaseOutput::new(runs, base_time()));
+        let response = create_router(state)
+            .oneshot(Request::builder().uri(uri).body(Body::empty())?)
+            .await?;
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await?;
+        Ok((status, serde_json::from_slice(&body)?))
+    }
+
+    #[tokio::test]
+    async fn test_compares_the_jobs_of_two_runs() -> anyhow::Result<()> {
+        let stub = StubGitHubApi {
+            jobs: Some(vec![job("build", 100), job("test", 50)]),
+            timing: Some(RunTiming {
+                run_duration_ms: Some(150_000),
+                ..RunTiming::default()
+            }),
+            ..StubGitHubApi::default()
+        };
+
+        let (status, body) = get_json(stub, "/compare?repo=owner/repo&base=1&head=2").await?;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["workflowName"], "CI");
+        assert_eq!(body["thresholdPercent"], 20.0);
+        assert_eq!(body["total"]["baseSeconds"], 150);
+        assert_eq!(body["total"]["deltaSeconds"], 0);
+        assert_eq!(body["jobs"][1]["name"], "test");
+        assert_eq!(body["regression"], false);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_runs_of_different_workflows_are_rejected() -> anyhow::Result<()> {
+        let stub = StubGitHubApi {
+            jobs: Some(Vec::new()),
+            ..StubGitHubApi::default()
+        };
+
+        let (status, body) = get_json(stub, "/compare?repo=owner/repo&base=1&head=3").await?;
+
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(
+            body["error"]
+                .as_str()
+                .is_some_and(|e| e.contains("Release"))
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unknown_runs_and_bad_parameters_are_rejected() -> anyhow::Result<()> {
+        let (status, body) = get_json(
+            StubGitHubApi::default(),
+            "/compare?repo=owner/repo&base=1&head=404",
+        )
+        .await?;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"], "Run 404 of owner/repo not found");
+
+        let (status, _) = get_json(
+            StubGitHubApi::default(),
+            "/compare?repo=owner/repo&base=1&head=2&threshold=-5",
+        )
+        .await?;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = get_json(StubGitHubApi::default(), "/compare?repo=owner&base=1").await?;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        Ok(())
+    }
+}
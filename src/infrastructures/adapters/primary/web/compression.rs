@@ -0,0 +1,158 @@
+use super::rate_limit::route_template;
+use crate::infrastructures::metrics::Metrics;
+use axum::{
+    body::{Body, Bytes},
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use http_body::{Frame, SizeHint};
+use prometheus::IntCounter;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, ready};
+use tower_http::compression::{CompressionLayer, DefaultPredicate};
+
+/// Counted by [`response_size_middleware`] inside the compression layer
+pub const UNCOMPRESSED: &str = "uncompressed";
+
+/// Counted by [`response_size_middleware`] outside the compression layer
+pub const SENT: &str = "sent";
+
+/// Compresses responses with gzip or brotli, whichever the client prefers.
+///
+/// Server-sent events are left alone: the encoder holds bytes back until its buffer fills, so
+/// events would wait for the ones after them. So are images, gRPC and bodies under 32 bytes.
+#[must_use]
+pub fn compression_layer() -> CompressionLayer {
+    CompressionLayer::new().compress_when(DefaultPredicate::new())
+}
+
+/// A response body adding the length of every data frame to a counter as it is polled, so
+/// streamed responses are counted as they go.
+struct CountedBody {
+    inner: Body,
+    bytes: IntCounter,
+}
+
+impl http_body::Body for CountedBody {
+    type Data = Bytes;
+    type Error = axum::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, axum::Error>>> {
+        let frame = ready!(Pin::new(&mut self.inner).poll_frame(cx));
+        if let Some(Ok(frame)) = &frame
+            && let Some(data) = frame.data_ref()
+        {
+            self.bytes.inc_by(data.len() as u64);
+        }
+        Poll::Ready(frame)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+/// Middleware counting the response body bytes of each route in
+/// `http_response_body_bytes_total`, labeled with `stage`: [`UNCOMPRESSED`] when layered
+/// inside [`compression_layer`] and [`SENT`] when layered outside it.
+pub async fn response_size_middleware(
+    State((metrics, stage)): State<(Arc<Metrics>, &'static str)>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let route = route_template(&request);
+    let bytes = metrics
+        .http_response_body_bytes
+        .with_label_values(&[route.as_str(), stage]);
+    next.run(request)
+        .await
+        .map(|inner| Body::new(CountedBody { inner, bytes }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::services::Notice;
+    use crate::application::use_cases::StreamGitHubActionsRunsUseCaseOutput;
+    use crate::infrastructures::adapters::primary::web::create_router;
+    use crate::test_support::{StubGitHubApi, app_state, base_time, workflow_run};
+    use axum::body::to_bytes;
+    use axum::http::header;
+    use futures_util::StreamExt;
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    fn snapshot() -> StreamGitHubActionsRunsUseCaseOutput {
+        let runs = (1..=20)
+            .map(|id| workflow_run("owner/repo", id, "success"))
+            .collect();
+        StreamGitHubActionsRunsUseCaseOutput::new(runs, base_time())
+    }
+
+    #[tokio::test]
+    async fn test_runs_are_gzipped_and_counted_before_and_after() -> anyhow::Result<()> {
+        let state = app_state(StubGitHubApi::default(), None)?;
+        state.poller.publish(snapshot());
+
+        let response = create_router(Arc::clone(&state))
+            .oneshot(
+                Request::get("/runs")
+                    .header(header::ACCEPT_ENCODING, "gzip")
+                    .body(Body::empty())?,
+            )
+            .await?;
+        assert_eq!(
+            response.headers().get(header::CONTENT_ENCODING),
+            Some(&"gzip".parse()?)
+        );
+        let body = to_bytes(response.into_body(), usize::MAX).await?;
+
+        let bytes = |stage| {
+            state
+                .metrics
+                .http_response_body_bytes
+                .with_label_values(&["/runs", stage])
+                .get()
+        };
+        assert_eq!(bytes(SENT), body.len() as u64);
+        assert!(bytes(UNCOMPRESSED) > bytes(SENT));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sse_events_arrive_uncompressed_as_they_are_sent() -> anyhow::Result<()> {
+        let state = app_state(StubGitHubApi::default(), None)?;
+        state.poller.publish(snapshot());
+
+        let response = create_router(Arc::clone(&state))
+            .oneshot(
+                Request::get("/sse")
+                    .header(header::ACCEPT_ENCODING, "gzip, br")
+                    .body(Body::empty())?,
+            )
+            .await?;
+        assert_eq!(response.headers().get(header::CONTENT_ENCODING), None);
+        let mut body = response.into_body().into_data_stream();
+
+        // Each event arrives while the stream is still open, not when it ends
+        let first = tokio::time::timeout(Duration::from_secs(5), body.next())
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("stream ended"))??;
+        assert!(std::str::from_utf8(&first)?.contains("generatedAt"));
+        state.poller.notify_clients(Notice::info("still here"));
+        let notice = tokio::time::timeout(Duration::from_secs(5), body.next())
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("stream ended"))??;
+        assert!(std::str::from_utf8(&notice)?.contains("data: still here"));
+        Ok(())
+    }
+}
@@ -0,0 +1,209 @@
+use super::{AppState, json_error};
+use crate::application::use_cases::cost_estimation::{
+    CostEstimationInput, CostEstimationOutput, CostEstimationUseCase,
+};
+use crate::domain::models::run::WorkflowRun;
+use crate::domain::models::status::RunStatus;
+use crate::domain::repositories::{RunQuery, RunRepositoryError};
+use axum::{
+    Json,
+    extract::{Query, State, rejection::QueryRejection},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Days covered by `/costs` when the request does not say
+const DEFAULT_COST_DAYS: i64 = 30;
+
+/// Most days one `/costs` request may cover
+const MAX_COST_DAYS: i64 = 365;
+
+/// Most stored runs priced per request, since each run not priced before costs two GitHub
+/// API calls
+const MAX_COSTED_RUNS: usize = 1000;
+
+/// Parameters of `GET /costs`.
+#[derive(Deserialize, Debug)]
+pub struct CostQuery {
+    /// Days to cover, ending now
+    days: Option<i64>,
+}
+
+#[derive(Serialize, Debug)]
+struct Costs {
+    since: DateTime<Utc>,
+    #[serde(flatten)]
+    estimate: CostEstimationOutput,
+}
+
+/// Completed runs created since `since`: from the history when there is one, otherwise
+/// from the latest snapshot.
+async fn completed_runs(
+    state: &AppState,
+    since: DateTime<Utc>,
+) -> Result<Vec<WorkflowRun>, RunRepositoryError> {
+    let Some(run_repository) = &state.run_repository else {
+        return Ok(state
+            .snapshots
+            .latest()
+            .map(|output| {
+                output
+                    .runs
+                    .iter()
+                    .filter(|run| run.status == RunStatus::Completed && run.created_at >= since)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default());
+    };
+    run_repository
+        .query_runs(&RunQuery {
+            since: Some(since),
+            status: Some(RunStatus::Completed),
+            limit: MAX_COSTED_RUNS,
+            ..RunQuery::default()
+        })
+        .await
+}
+
+#[tracing::instrument(name = "costs_handler", skip(state))]
+pub async fn costs_handler(
+    query: Result<Query<CostQuery>, QueryRejection>,
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    let Query(query) = match query {
+        Ok(query) => query,
+        Err(rejection) => return json_error(rejection.status(), &rejection.body_text()),
+    };
+    let days = query.days.unwrap_or(DEFAULT_COST_DAYS);
+    if !(1..=MAX_COST_DAYS).contains(&days) {
+        return json_error(
+            StatusCode::BAD_REQUEST,
+            &format!("days must be between 1 and {MAX_COST_DAYS}"),
+        );
+    }
+
+    let since = Utc::now() - TimeDelta::days(days);
+    let runs = match completed_runs(&state, since).await {
+        Ok(runs) => runs,
+        Err(e) => {
+            tracing::error!("Failed to read runs to estimate: {}", e);
+            return json_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to read runs");
+        }
+    };
+    let estimate = state
+        .cost_estimation
+        .execute(CostEstimationInput { runs })
+        .await;
+    Json(Costs { since, estimate }).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::use_cases::stream_github_actions_runs::StreamGitHubActionsRunsUseCaseOutput;
+    use crate::domain::models::timing::{BillableTime, RunTiming, RunnerOs};
+    use crate::domain::repositories::RunRepository;
+    use crate::infrastructures::adapters::primary::web::create_router;
+    use crate::infrastructures::adapters::secondary::persistence::SqliteRunRepository;
+    use crate::test_support::{StubGitHubApi, app_state, workflow_run};
+    use axum::body::{Body, to_bytes};
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    async fn get_json(
+        state: Arc<AppState>,
+        uri: &str,
+    ) -> anyhow::Result<(StatusCode, serde_json::Value)> {
+        let response = create_router(state)
+            .oneshot(Request::get(uri).body(Body::empty())?)
+            .await?;
+        let status = response.status();
+        let body = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await?)?;
+        Ok((status, body))
+    }
+
+    /// Every run takes 90 seconds on Ubuntu, billed as 2 minutes.
+    fn github_api() -> StubGitHubApi {
+        StubGitHubApi {
+            timing: Some(RunTiming {
+                billable: [(
+                    RunnerOs::Ubuntu,
+                    BillableTime {
+                        total_ms: 90_000,
+                        job_runs: Vec::new(),
+                    },
+                )]
+                .into(),
+                run_duration_ms: Some(90_000),
+            }),
+            jobs: Some(Vec::new()),
+            ..StubGitHubApi::default()
+        }
+    }
+
+    fn recent_run(id: u64, display_status: &str, age: TimeDelta) -> WorkflowRun {
+        let mut run = workflow_run("owner/repo", id, display_status);
+        run.created_at = Utc::now() - age;
+        run
+    }
+
+    #[tokio::test]
+    async fn test_costs_of_the_snapshot_cover_the_requested_days() -> anyhow::Result<()> {
+        let state = app_state(github_api(), None)?;
+        state
+            .poller
+            .publish(StreamGitHubActionsRunsUseCaseOutput::new(
+                vec![
+                    recent_run(1, "success", TimeDelta::hours(1)),
+                    recent_run(2, "in_progress", TimeDelta::hours(1)),
+                    recent_run(3, "failure", TimeDelta::days(3)),
+                ],
+                Utc::now(),
+            ));
+
+        let (status, body) = get_json(Arc::clone(&state), "/costs").await?;
+        let (_, recent) = get_json(state, "/costs?days=2").await?;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["repositories"][0]["repository"], "owner/repo");
+        assert_eq!(body["repositories"][0]["billableMinutes"]["ubuntu"], 4);
+        assert_eq!(body["repositories"][0]["workflows"][0]["runs"], 2);
+        assert_eq!(body["estimatedCost"], 0.032);
+        assert_eq!(recent["repositories"][0]["workflows"][0]["runs"], 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_costs_are_read_from_the_history_when_enabled() -> anyhow::Result<()> {
+        let run_repository = Arc::new(SqliteRunRepository::connect("sqlite::memory:")?);
+        run_repository
+            .save_runs(&[recent_run(1, "success", TimeDelta::days(10))], Utc::now())
+            .await?;
+        let state = Arc::into_inner(app_state(github_api(), None)?)
+            .ok_or_else(|| anyhow::anyhow!("state is shared"))?
+            .with_run_repository(run_repository);
+
+        let (status, body) = get_json(Arc::new(state), "/costs?days=30").await?;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["repositories"][0]["billableMinutes"]["ubuntu"], 2);
+        assert_eq!(body["unestimatedRuns"], 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_costs_reject_bad_days() -> anyhow::Result<()> {
+        let state = app_state(github_api(), None)?;
+
+        for uri in ["/costs?days=0", "/costs?days=366", "/costs?days=week"] {
+            let (status, body) = get_json(Arc::clone(&state), uri).await?;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{uri}");
+            assert!(body["error"].is_string());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,115 @@
+use super::presenter::{TimestampQuery, deployments_json};
+use super::{AppState, json_error};
+use axum::{
+    extract::{Query, State, rejection::QueryRejection},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use std::sync::Arc;
+
+/// Latest deployment to each environment of the polled repositories as of the last refresh.
+#[tracing::instrument(name = "deployments_handler", skip(state))]
+pub async fn deployments_handler(
+    timestamps: Result<Query<TimestampQuery>, QueryRejection>,
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    let timestamps = match timestamps {
+        Ok(Query(timestamps)) => timestamps.or(state.timestamp_format),
+        Err(rejection) => return json_error(rejection.status(), &rejection.body_text()),
+    };
+    let Some(board) = state.deployment_inventory.board() else {
+        return json_error(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "No deployments have been listed yet",
+        );
+    };
+
+    match deployments_json(&board, timestamps) {
+        Ok(body) => ([(header::CONTENT_TYPE, "application/json")], body).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to serialize deployments: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::deployment::{Deployment, DeploymentStatus, DeploymentStatusState};
+    use crate::domain::models::ids::DeploymentId;
+    use crate::infrastructures::adapters::primary::web::create_router;
+    use crate::test_support::{StubGitHubApi, app_state, base_time, repo_full_name, run_actor};
+    use axum::body::{Body, to_bytes};
+    use axum::http::Request;
+    use chrono::TimeDelta;
+    use tower::ServiceExt;
+
+    async fn get(state: Arc<AppState>, uri: &str) -> anyhow::Result<(StatusCode, String)> {
+        let response = create_router(state)
+            .oneshot(Request::get(uri).body(Body::empty())?)
+            .await?;
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await?;
+        Ok((status, String::from_utf8(body.to_vec())?))
+    }
+
+    #[tokio::test]
+    async fn test_deployments_are_listed_per_environment() -> anyhow::Result<()> {
+        let state = app_state(
+            StubGitHubApi {
+                deployments: vec![Deployment {
+                    id: DeploymentId(7),
+                    environment: "production".to_string(),
+                    git_ref: "v1.2.0".to_string(),
+                    sha: "abc123".to_string(),
+                    creator: Some(run_actor("octocat")),
+                    created_at: base_time(),
+                    updated_at: base_time(),
+                }],
+                deployment_statuses: vec![DeploymentStatus {
+                    state: DeploymentStatusState::Success,
+                    target_url: Some("https://example.com".to_string()),
+                    created_at: base_time() + TimeDelta::minutes(2),
+                }],
+                ..StubGitHubApi::default()
+            },
+            None,
+        )?;
+        state
+            .deployment_inventory
+            .refresh(&[repo_full_name("owner/repo")], base_time())
+            .await;
+
+        let (status, body) = get(Arc::clone(&state), "/deployments?ts=rfc3339").await?;
+        let (_, millis) = get(state, "/deployments?ts=millis").await?;
+
+        assert_eq!(status, StatusCode::OK);
+        let body: serde_json::Value = serde_json::from_str(&body)?;
+        let environment = &body["environments"][0];
+        assert_eq!(environment["repository"], "owner/repo");
+        assert_eq!(environment["environment"], "production");
+        assert_eq!(environment["ref"], "v1.2.0");
+        assert_eq!(environment["creator"]["login"], "octocat");
+        assert_eq!(environment["state"], "success");
+        assert_eq!(environment["updatedAt"], "2024-08-01T10:02:00Z");
+        assert_eq!(environment["targetUrl"], "https://example.com");
+        assert_eq!(body["updatedAt"], "2024-08-01T10:00:00Z");
+        let millis: serde_json::Value = serde_json::from_str(&millis)?;
+        assert_eq!(
+            millis["environments"][0]["updatedAt"],
+            (base_time() + TimeDelta::minutes(2)).timestamp_millis()
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_deployments_are_unavailable_before_the_first_refresh() -> anyhow::Result<()> {
+        let state = app_state(StubGitHubApi::default(), None)?;
+
+        let (status, _) = get(state, "/deployments").await?;
+
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        Ok(())
+    }
+}
@@ -0,0 +1,334 @@
+use super::{AppState, json_error};
+use crate::application::use_cases::generate_digest::{
+    GenerateDigestInput, GenerateDigestInteractor, GenerateDigestUseCase,
+};
+use crate::domain::models::digest::{Digest, IsoWeek, RegressionKind};
+use axum::{
+    Json,
+    extract::{Query, State, rejection::QueryRejection},
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use chrono::{Days, Utc};
+use serde::Deserialize;
+use std::fmt::Write;
+use std::sync::Arc;
+
+const CONTENT_TYPE_MARKDOWN: &str = "text/markdown; charset=utf-8";
+
+/// Parameters of `GET /digest`.
+#[derive(Deserialize, Debug)]
+pub struct DigestQuery {
+    /// ISO week such as `2024-W32`; the last complete week when absent
+    week: Option<String>,
+}
+
+/// Whether the client asked for Markdown rather than JSON.
+fn wants_markdown(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|media_range| media_range.split(';').next())
+        .find_map(|media_type| match media_type.trim() {
+            "text/markdown" => Some(true),
+            "application/json" => Some(false),
+            _ => None,
+        })
+        .unwrap_or(false)
+}
+
+/// `share`, from 0 to 1, as a percentage.
+fn percent(share: f64) -> String {
+    format!("{:.1}%", share * 100.0)
+}
+
+/// `seconds` rounded to the second, e.g. `4m 05s`.
+fn duration(seconds: f64) -> String {
+    let seconds =
+        std::time::Duration::try_from_secs_f64(seconds.round()).map_or(0, |d| d.as_secs());
+    match (seconds / 3600, seconds / 60 % 60, seconds % 60) {
+        (0, 0, s) => format!("{s}s"),
+        (0, m, s) => format!("{m}m {s:02}s"),
+        (h, m, s) => format!("{h}h {m:02}m {s:02}s"),
+    }
+}
+
+/// Escapes `text` for a Markdown table cell.
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+/// Renders `digest` as a Markdown report, e.g. to paste in a chat or an issue.
+#[must_use]
+pub fn render_markdown(digest: &Digest) -> String {
+    let mut markdown = format!(
+        "# CI digest {}\n\n{} to {} (UTC)\n\n",
+        digest.week,
+        digest.since.date_naive(),
+        (digest.until.date_naive() - Days::new(1)),
+    );
+    let Some(failure_rate) = digest.failure_rate else {
+        markdown.push_str("No runs completed during the week.\n");
+        return markdown;
+    };
+    // Writing to a String cannot fail
+    let _ = writeln!(
+        markdown,
+        "**{} runs**, {} failed ({})",
+        digest.runs,
+        digest.failed_runs,
+        percent(failure_rate)
+    );
+    for repository in &digest.repositories {
+        let _ = write!(
+            markdown,
+            "\n## {}\n\n{} runs, {} failed ({})\n\n### Slowest workflows\n\n\
+             | Workflow | Runs | Failure rate | Mean duration |\n\
+             | --- | ---: | ---: | ---: |\n",
+            repository.repository,
+            repository.runs,
+            repository.failed_runs,
+            percent(repository.failure_rate)
+        );
+        for workflow in &repository.slowest_workflows {
+            let _ = writeln!(
+                markdown,
+                "| {} | {} | {} | {} |",
+                cell(&workflow.workflow_name),
+                workflow.runs,
+                percent(workflow.failure_rate),
+                duration(workflow.mean_duration_seconds)
+            );
+        }
+        if repository.regressions.is_empty() {
+            continue;
+        }
+        markdown.push_str("\n### Regressions\n\n");
+        for regression in &repository.regressions {
+            let (what, previous, current) = match regression.kind {
+                RegressionKind::FailureRate => (
+                    "failure rate",
+                    percent(regression.previous),
+                    percent(regression.current),
+                ),
+                RegressionKind::Duration => (
+                    "mean duration",
+                    duration(regression.previous),
+                    duration(regression.current),
+                ),
+            };
+            let _ = writeln!(
+                markdown,
+                "- **{}**: {what} {previous} → {current}",
+                regression.workflow_name
+            );
+        }
+    }
+    markdown
+}
+
+#[tracing::instrument(name = "digest_handler", skip(state, headers))]
+pub async fn digest_handler(
+    query: Result<Query<DigestQuery>, QueryRejection>,
+    headers: HeaderMap,
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    let Some(run_repository) = &state.run_repository else {
+        return json_error(
+            StatusCode::NOT_FOUND,
+            "Run history is disabled, set DATABASE_URL to enable it",
+        );
+    };
+    let Query(query) = match query {
+        Ok(query) => query,
+        Err(rejection) => return json_error(rejection.status(), &rejection.body_text()),
+    };
+    let week = match query.week.as_deref().map(str::parse::<IsoWeek>) {
+        Some(Ok(week)) => week,
+        Some(Err(e)) => return json_error(StatusCode::BAD_REQUEST, &e.to_string()),
+        None => IsoWeek::of(Utc::now().date_naive()).previous(),
+    };
+
+    let digest = match GenerateDigestInteractor::new(Arc::clone(run_repository))
+        .execute(GenerateDigestInput { week })
+        .await
+    {
+        Ok(digest) => digest,
+        Err(e) => {
+            tracing::error!("Failed to generate the digest: {}", e);
+            return json_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to generate the digest",
+            );
+        }
+    };
+    let vary = [(header::VARY, header::ACCEPT.to_string())];
+    if wants_markdown(&headers) {
+        (
+            vary,
+            [(header::CONTENT_TYPE, CONTENT_TYPE_MARKDOWN)],
+            render_markdown(&digest),
+        )
+            .into_response()
+    } else {
+        (vary, Json(digest)).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::use_cases::generate_digest::digest;
+    use crate::domain::models::ids::WorkflowId;
+    use crate::domain::models::status::RunConclusion;
+    use crate::domain::repositories::{DailyStats, RunRepository};
+    use crate::infrastructures::adapters::primary::web::create_router;
+    use crate::infrastructures::adapters::secondary::persistence::SqliteRunRepository;
+    use crate::test_support::{StubGitHubApi, app_state, base_time, workflow_run};
+    use axum::body::{Body, to_bytes};
+    use axum::http::Request;
+    use chrono::TimeDelta;
+    use std::collections::HashMap;
+    use tower::ServiceExt;
+
+    async fn get(
+        state: Arc<AppState>,
+        uri: &str,
+        accept: &str,
+    ) -> anyhow::Result<(StatusCode, HeaderMap, String)> {
+        let response = create_router(state)
+            .oneshot(
+                Request::get(uri)
+                    .header(header::ACCEPT, accept)
+                    .body(Body::empty())?,
+            )
+            .await?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = to_bytes(response.into_body(), usize::MAX).await?;
+        Ok((status, headers, String::from_utf8(body.to_vec())?))
+    }
+
+    fn stats(day: u32, workflow: u64, runs: u64, failed: u64, seconds: f64) -> DailyStats {
+        DailyStats {
+            day: chrono::NaiveDate::from_ymd_opt(2024, 8, day).unwrap_or_default(),
+            workflow_id: WorkflowId(workflow),
+            workflow_name: ["CI", "Deploy | prod", "Lint"]
+                [usize::try_from(workflow % 3).unwrap_or_default()]
+            .to_string(),
+            runs,
+            conclusions: HashMap::from([
+                (RunConclusion::Success, runs - failed),
+                (RunConclusion::Failure, failed),
+            ]),
+            success_rate: 0.0,
+            mean_duration_seconds: seconds,
+        }
+    }
+
+    /// Week 32 of 2024 for two repositories, one of them slower and flakier than in week 31.
+    fn digest_of_week_32() -> anyhow::Result<Digest> {
+        Ok(digest(
+            "2024-W32".parse()?,
+            vec![
+                (
+                    "owner/app".parse()?,
+                    vec![
+                        stats(1, 0, 10, 0, 300.0),
+                        stats(1, 1, 5, 0, 60.0),
+                        stats(6, 0, 12, 3, 420.0),
+                        stats(7, 1, 5, 0, 61.0),
+                        stats(8, 2, 2, 0, 4.0),
+                    ],
+                ),
+                ("owner/site".parse()?, vec![stats(9, 0, 4, 0, 3_725.0)]),
+            ],
+        ))
+    }
+
+    #[test]
+    fn test_markdown_snapshot() -> anyhow::Result<()> {
+        insta::assert_snapshot!(render_markdown(&digest_of_week_32()?));
+        Ok(())
+    }
+
+    #[test]
+    fn test_empty_markdown_snapshot() -> anyhow::Result<()> {
+        insta::assert_snapshot!(render_markdown(&digest("2024-W01".parse()?, Vec::new())));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_digest_is_served_as_json_or_markdown() -> anyhow::Result<()> {
+        let mut failure = workflow_run("owner/repo", 2, "failure");
+        failure.updated_at = failure.created_at + TimeDelta::minutes(4);
+        let runs = [workflow_run("owner/repo", 1, "success"), failure];
+        let run_repository = Arc::new(SqliteRunRepository::connect("sqlite::memory:")?);
+        run_repository.save_runs(&runs, Utc::now()).await?;
+        run_repository
+            .aggregate_daily_stats(base_time().date_naive() - TimeDelta::days(1))
+            .await?;
+        let state = Arc::into_inner(app_state(StubGitHubApi::default(), None)?)
+            .ok_or_else(|| anyhow::anyhow!("state is shared"))?
+            .with_run_repository(run_repository);
+        let state = Arc::new(state);
+
+        let (status, headers, body) = get(
+            Arc::clone(&state),
+            "/digest?week=2024-W31",
+            "application/json",
+        )
+        .await?;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            headers
+                .get(header::VARY)
+                .map(axum::http::HeaderValue::as_bytes),
+            Some(&b"accept"[..])
+        );
+        let json: serde_json::Value = serde_json::from_str(&body)?;
+        assert_eq!(json["week"], "2024-W31");
+        assert_eq!(json["since"], "2024-07-29T00:00:00Z");
+        assert_eq!(
+            (json["runs"].as_u64(), json["failedRuns"].as_u64()),
+            (Some(2), Some(1))
+        );
+        assert_eq!(
+            json["repositories"][0]["slowestWorkflows"][0]["workflowName"],
+            "CI"
+        );
+
+        let (_, headers, body) =
+            get(Arc::clone(&state), "/digest?week=2024-W31", "text/markdown").await?;
+        assert_eq!(
+            headers
+                .get(header::CONTENT_TYPE)
+                .map(axum::http::HeaderValue::as_bytes),
+            Some(CONTENT_TYPE_MARKDOWN.as_bytes())
+        );
+        assert!(body.starts_with("# CI digest 2024-W31\n"));
+
+        // Weeks without runs are empty rather than missing
+        let (status, _, body) = get(Arc::clone(&state), "/digest?week=2024-W40", "*/*").await?;
+        assert_eq!(status, StatusCode::OK);
+        let json: serde_json::Value = serde_json::from_str(&body)?;
+        assert_eq!(json["repositories"], serde_json::json!([]));
+        assert_eq!(json["failureRate"], serde_json::Value::Null);
+
+        let (status, _, _) = get(state, "/digest?week=2024-W99", "*/*").await?;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_digest_is_not_found_without_a_store() -> anyhow::Result<()> {
+        let state = app_state(StubGitHubApi::default(), None)?;
+
+        let (status, _, _) = get(state, "/digest", "*/*").await?;
+
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        Ok(())
+    }
+}
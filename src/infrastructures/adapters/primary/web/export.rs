@@ -0,0 +1,256 @@
+use super::AppState;
+#[cfg(feature = "import")]
+use super::auth::Authenticated;
+#[cfg(feature = "import")]
+use super::json_error;
+use super::status::RateLimitStatus;
+use crate::application::services::RepositoryStatus;
+use crate::application::use_cases::SCHEMA_VERSION;
+use crate::application::use_cases::stream_github_actions_runs::document::SnapshotDocument;
+use crate::domain::models::transition::RunTransition;
+use axum::{
+    Json,
+    extract::State,
+    response::{IntoResponse, Response},
+};
+#[cfg(feature = "import")]
+use axum::{extract::rejection::JsonRejection, http::StatusCode};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+#[cfg(feature = "import")]
+use std::sync::PoisonError;
+
+/// Body of `/export`, and of `POST /import`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ExportDocument {
+    /// [`SCHEMA_VERSION`] of the exporting server; imports of any other version are rejected
+    #[serde(rename = "schemaVersion")]
+    pub schema_version: u32,
+    /// `null` before the first snapshot
+    pub snapshot: Option<SnapshotDocument>,
+    /// Runs that changed between recent snapshots, oldest first
+    pub transitions: Vec<RunTransition>,
+    /// Outcome of the last fetches of each polled repository
+    pub repositories: Vec<RepositoryStatus>,
+    #[serde(rename = "rateLimit")]
+    pub rate_limit: RateLimitStatus,
+}
+
+impl ExportDocument {
+    fn new(state: &AppState) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            snapshot: state
+                .snapshots
+                .latest()
+                .map(|latest| SnapshotDocument::from(latest.as_ref())),
+            transitions: state.poller.recent_transitions(),
+            repositories: state.poller.status().repositories,
+            rate_limit: RateLimitStatus::current(state),
+        }
+    }
+}
+
+/// The in-memory state of the dashboard as one document, for analysing it offline or loading
+/// it into another server with `POST /import`.
+#[tracing::instrument(name = "export_handler", skip_all)]
+pub async fn export_handler(State(state): State<Arc<AppState>>) -> Response {
+    Json(ExportDocument::new(&state)).into_response()
+}
+
+/// Loads a document from `/export` into the in-memory state, so the UI and the stats
+/// endpoints render it until the next snapshot is polled.
+///
+/// The schema version is checked before the rest of the document, so one exported by an
+/// incompatible server is rejected with 422 and both versions instead of a parse error.
+///
+/// Unlike other mutating routes, imports are refused with 403 while neither `AUTH_TOKEN` nor
+/// forwarded identities are configured, as anyone could replace what the dashboard shows.
+#[cfg(feature = "import")]
+#[tracing::instrument(name = "import_handler", skip_all)]
+pub async fn import_handler(
+    auth: Authenticated,
+    State(state): State<Arc<AppState>>,
+    body: Result<Json<serde_json::Value>, JsonRejection>,
+) -> Response {
+    if state.auth_token.is_none() && state.forwarded_identity.is_none() {
+        return json_error(
+            StatusCode::FORBIDDEN,
+            "Import requires AUTH_TOKEN or AUTH_MODE=forward_headers",
+        );
+    }
+    auth.audit("import");
+    let Json(body) = match body {
+        Ok(body) => body,
+        Err(rejection) => return json_error(rejection.status(), &rejection.body_text()),
+    };
+    match body
+        .get("schemaVersion")
+        .and_then(serde_json::Value::as_u64)
+    {
+        Some(version) if version == u64::from(SCHEMA_VERSION) => {}
+        Some(version) => {
+            return json_error(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                &format!(
+                    "Unsupported schema version {version}, this server imports version {SCHEMA_VERSION}"
+                ),
+            );
+        }
+        None => {
+            return json_error(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "Missing schemaVersion, expected a document from /export",
+            );
+        }
+    }
+    let document: ExportDocument = match serde_json::from_value(body) {
+        Ok(document) => document,
+        Err(e) => {
+            return json_error(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                &format!("Invalid export document: {e}"),
+            );
+        }
+    };
+
+    tracing::warn!("Importing a dashboard state exported elsewhere");
+    *state
+        .imported_rate_limit
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner) = Some(document.rate_limit);
+    state.poller.import(
+        document.snapshot.map(Into::into),
+        document.repositories,
+        document.transitions,
+    );
+    StatusCode::NO_CONTENT.into_response()
+}
+
+#[cfg(all(test, feature = "import"))]
+mod tests {
+    use super::*;
+    use crate::application::services::PollerError;
+    use crate::application::use_cases::StreamGitHubActionsRunsUseCaseOutput;
+    use crate::domain::external_apis::github::Repository;
+    use crate::domain::models::status::RunStatus;
+    use crate::infrastructures::adapters::primary::web::create_router;
+    use crate::test_support::{StubGitHubApi, app_state, base_time, repo_full_name, workflow_run};
+    use axum::body::{Body, to_bytes};
+    use axum::http::{Request, header};
+    use std::borrow::Cow;
+    use tower::ServiceExt;
+
+    async fn export(state: Arc<AppState>) -> anyhow::Result<ExportDocument> {
+        let response = create_router(state)
+            .oneshot(Request::get("/export").body(Body::empty())?)
+            .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await?;
+        Ok(serde_json::from_slice(&body)?)
+    }
+
+    async fn import(
+        state: Arc<AppState>,
+        body: &serde_json::Value,
+    ) -> anyhow::Result<(StatusCode, String)> {
+        let response = create_router(state)
+            .oneshot(
+                Request::post("/import")
+                    .header(header::AUTHORIZATION, "Bearer secret-token")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(serde_json::to_vec(body)?))?,
+            )
+            .await?;
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await?;
+        Ok((status, String::from_utf8(body.to_vec())?))
+    }
+
+    /// A state showing a failed run of `owner/repo` and a rate-limited `owner/app`.
+    fn busy_state() -> anyhow::Result<Arc<AppState>> {
+        let state = app_state(StubGitHubApi::default(), Some("secret-token"))?;
+        let failed = workflow_run("owner/repo", 1, "failure");
+        let output = StreamGitHubActionsRunsUseCaseOutput::new(vec![failed.clone()], base_time())
+            .with_repositories(vec![Repository::new("owner", "repo")?])
+            .with_queued_runs(vec![workflow_run("owner/repo", 2, "queued")]);
+        let repositories = vec![RepositoryStatus {
+            repository: repo_full_name("owner/app"),
+            last_success_at: None,
+            last_error: Some(PollerError {
+                kind: Cow::Borrowed("rate_limited"),
+                message: "GitHub API rate limit exceeded".to_string(),
+                occurred_at: base_time(),
+            }),
+        }];
+        let transitions = vec![RunTransition {
+            run: failed,
+            previous_status: Some(RunStatus::InProgress),
+            previous_conclusion: None,
+        }];
+        state.poller.import(Some(output), repositories, transitions);
+        *state
+            .imported_rate_limit
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Some(RateLimitStatus {
+            remaining: Some(12),
+            limit: Some(5000),
+            token_expires_at: None,
+        });
+        Ok(state)
+    }
+
+    #[tokio::test]
+    async fn test_export_round_trips_through_import() -> anyhow::Result<()> {
+        let exported = export(busy_state()?).await?;
+        assert_eq!(exported.schema_version, SCHEMA_VERSION);
+        assert_eq!(exported.transitions.len(), 1);
+
+        let wiped = app_state(StubGitHubApi::default(), Some("secret-token"))?;
+        let (status, body) = import(Arc::clone(&wiped), &serde_json::to_value(&exported)?).await?;
+        assert_eq!(status, StatusCode::NO_CONTENT, "{body}");
+
+        assert_eq!(export(Arc::clone(&wiped)).await?, exported);
+        let queue = wiped
+            .snapshots
+            .latest()
+            .ok_or_else(|| anyhow::anyhow!("import published no snapshot"))?
+            .queue
+            .clone();
+        assert_eq!(queue.len(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_import_rejects_another_schema_version() -> anyhow::Result<()> {
+        let mut document = serde_json::to_value(export(busy_state()?).await?)?;
+        document["schemaVersion"] = serde_json::json!(SCHEMA_VERSION + 1);
+        let state = app_state(StubGitHubApi::default(), Some("secret-token"))?;
+
+        let (status, body) = import(Arc::clone(&state), &document).await?;
+
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(
+            body.contains(&format!(
+                "Unsupported schema version {}",
+                SCHEMA_VERSION + 1
+            )),
+            "{body}"
+        );
+        assert!(state.snapshots.latest().is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_import_is_refused_without_authentication() -> anyhow::Result<()> {
+        let document = serde_json::to_value(export(busy_state()?).await?)?;
+        let state = app_state(StubGitHubApi::default(), None)?;
+
+        let (status, body) = import(Arc::clone(&state), &document).await?;
+
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert!(body.contains("Import requires AUTH_TOKEN"), "{body}");
+        assert!(state.snapshots.latest().is_none());
+        Ok(())
+    }
+}
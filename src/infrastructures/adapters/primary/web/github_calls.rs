@@ -0,0 +1,120 @@
+use super::AppState;
+use super::limits::ON_DEMAND_ROUTES;
+use super::rate_limit::route_template;
+use crate::domain::external_apis::github::calls::ApiCallRecorder;
+use axum::{
+    body::Body,
+    extract::State,
+    http::{HeaderValue, Request},
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+
+/// Response header of on-demand routes counting the GitHub API requests made to answer them
+pub const GITHUB_CALLS_HEADER: &str = "x-github-calls-used";
+
+/// Middleware counting the GitHub API requests of each on-demand route, such as `/costs`
+/// and job details, in the [`GITHUB_CALLS_HEADER`] and in the totals of `/status`.
+pub async fn github_calls_middleware(
+    State(state): State<Arc<AppState>>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    if !ON_DEMAND_ROUTES.contains(&route_template(&request).as_str()) {
+        return next.run(request).await;
+    }
+    let calls = ApiCallRecorder::default();
+    let mut response = calls.scope(next.run(request)).await;
+    let counts = calls.counts();
+    state.on_demand_github_calls.add_counts(&counts);
+    response
+        .headers_mut()
+        .insert(GITHUB_CALLS_HEADER, HeaderValue::from(counts.total));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::use_cases::StreamGitHubActionsRunsUseCaseOutput;
+    use crate::infrastructures::adapters::primary::web::create_router;
+    use crate::infrastructures::adapters::secondary::external_apis::github::GitHubApiAdapter;
+    use crate::test_support::workflow_run;
+    use axum::body::to_bytes;
+    use chrono::{TimeDelta, Utc};
+    use tower::ServiceExt;
+    use wiremock::matchers::{method, path_regex};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn get(
+        state: Arc<AppState>,
+        uri: &str,
+    ) -> anyhow::Result<(Option<String>, serde_json::Value)> {
+        let response = create_router(state)
+            .oneshot(Request::get(uri).body(Body::empty())?)
+            .await?;
+        let calls = response
+            .headers()
+            .get(GITHUB_CALLS_HEADER)
+            .map(|calls| calls.to_str().map(str::to_string))
+            .transpose()?;
+        let body = to_bytes(response.into_body(), usize::MAX).await?;
+        Ok((calls, serde_json::from_slice(&body)?))
+    }
+
+    #[tokio::test]
+    async fn test_calls_of_a_composed_request_are_counted() -> anyhow::Result<()> {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path_regex(r"^/repos/owner/repo/actions/runs/\d+/timing$"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "billable": {"UBUNTU": {"total_ms": 60_000, "jobs": 1}},
+                "run_duration_ms": 60_000
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path_regex(r"^/repos/owner/repo/actions/runs/\d+/jobs$"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({"total_count": 0, "jobs": []})),
+            )
+            .mount(&server)
+            .await;
+        let state = crate::testing::app_state(
+            GitHubApiAdapter::new(server.uri(), "token".to_string()),
+            None,
+        )?;
+        let runs = [(2, "success"), (1, "failure")].map(|(id, display_status)| {
+            let mut run = workflow_run("owner/repo", id, display_status);
+            run.created_at = Utc::now() - TimeDelta::hours(1);
+            run
+        });
+        state
+            .poller
+            .publish(StreamGitHubActionsRunsUseCaseOutput::new(
+                runs.to_vec(),
+                Utc::now(),
+            ));
+
+        // Each run is priced with its timing and its jobs
+        let (first, _) = get(Arc::clone(&state), "/costs").await?;
+        // Then from memory
+        let (second, _) = get(Arc::clone(&state), "/costs").await?;
+        let (snapshot, _) = get(Arc::clone(&state), "/runs").await?;
+        let (_, status) = get(state, "/status").await?;
+
+        assert_eq!(first.as_deref(), Some("4"));
+        assert_eq!(second.as_deref(), Some("0"));
+        assert_eq!(snapshot, None);
+        assert_eq!(
+            status["onDemandGitHubCalls"],
+            serde_json::json!({
+                "total": 4,
+                "byOperation": {"run_timing": 2, "workflow_jobs": 2}
+            })
+        );
+        Ok(())
+    }
+}
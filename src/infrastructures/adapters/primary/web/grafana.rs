@@ -0,0 +1,515 @@
+pub mod models;
+
+use super::{AppState, json_error};
+use crate::domain::models::ids::RepoFullName;
+use crate::domain::models::run::WorkflowRun;
+use crate::domain::models::status::{RunConclusion, RunStatus};
+use crate::domain::repositories::{RunQuery, RunRepositoryError};
+use axum::{
+    Json,
+    extract::{State, rejection::JsonRejection},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use chrono::{NaiveDate, NaiveTime};
+use models::{
+    Column, ColumnType, QueryRequest, QueryResult, SearchRequest, Table, TimeRange, TimeSeries,
+};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+
+/// Rows of a `recent_runs` table when the panel sets no `maxDataPoints`
+const DEFAULT_TABLE_ROWS: usize = 100;
+
+/// Most rows of one `recent_runs` table
+const MAX_TABLE_ROWS: usize = 1000;
+
+/// What a series measures; series are named `<metric>:<owner/repo>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Metric {
+    /// Share of the completed runs of each day that succeeded
+    SuccessRate,
+    /// Completed runs per day
+    Runs,
+    /// Mean duration of the completed runs of each day
+    MeanDuration,
+    /// A table of the runs in the range, newest first
+    RecentRuns,
+}
+
+impl Metric {
+    const ALL: [Self; 4] = [
+        Self::SuccessRate,
+        Self::Runs,
+        Self::MeanDuration,
+        Self::RecentRuns,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::SuccessRate => "success_rate",
+            Self::Runs => "runs",
+            Self::MeanDuration => "mean_duration_seconds",
+            Self::RecentRuns => "recent_runs",
+        }
+    }
+}
+
+/// Splits a series name into its metric and repository.
+fn parse_series(name: &str) -> Result<(Metric, RepoFullName), String> {
+    let (metric, repo) = name
+        .split_once(':')
+        .ok_or_else(|| format!("Unknown series {name:?}, expected metric:owner/repo"))?;
+    let metric = Metric::ALL
+        .into_iter()
+        .find(|candidate| candidate.as_str() == metric)
+        .ok_or_else(|| format!("Unknown metric {metric:?} in series {name:?}"))?;
+    Ok((metric, repo.parse().map_err(|e| format!("{e}"))?))
+}
+
+/// Counts are far below `u32::MAX`, the largest integer `f64::from` converts.
+fn as_f64(count: u64) -> f64 {
+    f64::from(u32::try_from(count).unwrap_or(u32::MAX))
+}
+
+/// The completed runs of one UTC day.
+#[derive(Debug, Default)]
+struct DayTotals {
+    runs: u64,
+    successes: u64,
+    duration_seconds: f64,
+}
+
+impl DayTotals {
+    fn add_run(&mut self, run: &WorkflowRun) {
+        self.runs += 1;
+        self.successes += u64::from(run.conclusion == Some(RunConclusion::Success));
+        self.duration_seconds += as_f64(run.duration_seconds().unwrap_or_default());
+    }
+
+    fn value(&self, metric: Metric) -> f64 {
+        match metric {
+            Metric::SuccessRate => as_f64(self.successes) / as_f64(self.runs),
+            Metric::MeanDuration => self.duration_seconds / as_f64(self.runs),
+            Metric::Runs | Metric::RecentRuns => as_f64(self.runs),
+        }
+    }
+}
+
+/// Midnight of `day` in milliseconds, the timestamp of its datapoint.
+fn day_timestamp(day: NaiveDate) -> i64 {
+    day.and_time(NaiveTime::MIN).and_utc().timestamp_millis()
+}
+
+/// Whether `range` covers the creation of `run`.
+fn in_range(run: &WorkflowRun, range: &TimeRange) -> bool {
+    (range.from..range.to).contains(&run.created_at)
+}
+
+/// The runs of `repo` in the live snapshot, newest first.
+fn snapshot_runs(state: &AppState, repo: &RepoFullName) -> Vec<WorkflowRun> {
+    let mut runs: Vec<_> = state
+        .snapshots
+        .latest()
+        .map(|output| {
+            output
+                .runs
+                .iter()
+                .filter(|run| &run.repository_name == repo)
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default();
+    runs.sort_by_key(|run| Reverse(run.created_at));
+    runs
+}
+
+/// Per-day totals of `repo` for the days whose datapoint falls within `range`, from the daily
+/// stats of the history or, without one, from the live snapshot.
+async fn daily_totals(
+    state: &AppState,
+    repo: &RepoFullName,
+    range: &TimeRange,
+) -> Result<BTreeMap<NaiveDate, DayTotals>, RunRepositoryError> {
+    let mut totals = BTreeMap::<_, DayTotals>::new();
+    if let Some(run_repository) = &state.run_repository {
+        for stats in run_repository
+            .daily_stats(repo, range.from.date_naive())
+            .await?
+        {
+            let day = totals.entry(stats.day).or_default();
+            day.runs += stats.runs;
+            day.successes += stats
+                .conclusions
+                .get(&RunConclusion::Success)
+                .copied()
+                .unwrap_or_default();
+            day.duration_seconds += stats.mean_duration_seconds * as_f64(stats.runs);
+        }
+    } else {
+        for run in snapshot_runs(state, repo) {
+            if run.status == RunStatus::Completed {
+                totals
+                    .entry(run.created_at.date_naive())
+                    .or_default()
+                    .add_run(&run);
+            }
+        }
+    }
+    let (from, to) = (range.from.timestamp_millis(), range.to.timestamp_millis());
+    totals.retain(|day, _| (from..=to).contains(&day_timestamp(*day)));
+    Ok(totals)
+}
+
+/// Up to `limit` runs of `repo` created within `range`, newest first.
+async fn recent_runs(
+    state: &AppState,
+    repo: &RepoFullName,
+    range: &TimeRange,
+    limit: usize,
+) -> Result<Vec<WorkflowRun>, RunRepositoryError> {
+    let Some(run_repository) = &state.run_repository else {
+        let mut runs = snapshot_runs(state, repo);
+        runs.retain(|run| in_range(run, range));
+        runs.truncate(limit);
+        return Ok(runs);
+    };
+    run_repository
+        .query_runs(&RunQuery {
+            repository: Some(repo.clone()),
+            since: Some(range.from),
+            until: Some(range.to),
+            limit,
+            ..RunQuery::default()
+        })
+        .await
+}
+
+fn runs_table(runs: &[WorkflowRun]) -> Table {
+    let column = |text, kind| Column { text, kind };
+    Table {
+        columns: vec![
+            column("Time", ColumnType::Time),
+            column("Workflow", ColumnType::String),
+            column("Run", ColumnType::Number),
+            column("Branch", ColumnType::String),
+            column("Status", ColumnType::String),
+            column("Duration", ColumnType::Number),
+            column("URL", ColumnType::String),
+        ],
+        rows: runs
+            .iter()
+            .map(|run| {
+                vec![
+                    run.created_at.timestamp_millis().into(),
+                    run.workflow_name.clone().into(),
+                    run.run_number.into(),
+                    run.head_branch.clone().into(),
+                    run.display_status().into(),
+                    run.duration_seconds().into(),
+                    run.html_url.clone().into(),
+                ]
+            })
+            .collect(),
+    }
+}
+
+/// Why a series could not be answered.
+enum SeriesError {
+    /// The name is not one `/grafana/search` lists
+    Unknown(String),
+    Store(RunRepositoryError),
+}
+
+impl From<RunRepositoryError> for SeriesError {
+    fn from(error: RunRepositoryError) -> Self {
+        Self::Store(error)
+    }
+}
+
+/// Answers one series of a query.
+async fn query_series(
+    state: &AppState,
+    name: &str,
+    range: &TimeRange,
+    max_rows: usize,
+) -> Result<QueryResult, SeriesError> {
+    let (metric, repo) = parse_series(name).map_err(SeriesError::Unknown)?;
+    if metric == Metric::RecentRuns {
+        let runs = recent_runs(state, &repo, range, max_rows).await?;
+        return Ok(QueryResult::Table(runs_table(&runs)));
+    }
+    let datapoints = daily_totals(state, &repo, range)
+        .await?
+        .iter()
+        .map(|(day, totals)| (totals.value(metric), day_timestamp(*day)))
+        .collect();
+    Ok(QueryResult::TimeSeries(TimeSeries {
+        target: name.to_string(),
+        datapoints,
+    }))
+}
+
+/// `GET /grafana/`, which Grafana calls to test the data source.
+pub async fn grafana_health_handler() -> impl IntoResponse {
+    (StatusCode::OK, "OK")
+}
+
+/// `POST /grafana/search`: the series of every repository in the live snapshot.
+#[tracing::instrument(name = "grafana_search_handler", skip_all)]
+pub async fn grafana_search_handler(
+    State(state): State<Arc<AppState>>,
+    request: Result<Json<SearchRequest>, JsonRejection>,
+) -> Response {
+    let Json(request) = match request {
+        Ok(request) => request,
+        Err(rejection) => return json_error(rejection.status(), &rejection.body_text()),
+    };
+    let repositories: BTreeSet<RepoFullName> = state
+        .snapshots
+        .latest()
+        .map(|output| {
+            output
+                .repositories
+                .iter()
+                .map(|repository| repository.full_name().clone())
+                .chain(output.runs.iter().map(|run| run.repository_name.clone()))
+                .collect()
+        })
+        .unwrap_or_default();
+    let series: Vec<String> = repositories
+        .iter()
+        .flat_map(|repo| {
+            Metric::ALL
+                .iter()
+                .map(move |metric| format!("{}:{repo}", metric.as_str()))
+        })
+        .filter(|name| name.contains(&request.target))
+        .collect();
+    Json(series).into_response()
+}
+
+/// `POST /grafana/query`: a time series or table per target; ranges without data give empty
+/// ones.
+#[tracing::instrument(name = "grafana_query_handler", skip_all)]
+pub async fn grafana_query_handler(
+    State(state): State<Arc<AppState>>,
+    request: Result<Json<QueryRequest>, JsonRejection>,
+) -> Response {
+    let Json(request) = match request {
+        Ok(request) => request,
+        Err(rejection) => return json_error(rejection.status(), &rejection.body_text()),
+    };
+    let max_rows = request
+        .max_data_points
+        .unwrap_or(DEFAULT_TABLE_ROWS)
+        .clamp(1, MAX_TABLE_ROWS);
+
+    let mut results = Vec::with_capacity(request.targets.len());
+    for target in &request.targets {
+        match query_series(&state, &target.target, &request.range, max_rows).await {
+            Ok(result) => results.push(result),
+            Err(SeriesError::Unknown(message)) => {
+                return json_error(StatusCode::BAD_REQUEST, &message);
+            }
+            Err(SeriesError::Store(e)) => {
+                tracing::error!("Failed to query run history for Grafana: {}", e);
+                return json_error(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to read run history",
+                );
+            }
+        }
+    }
+    Json(results).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::use_cases::stream_github_actions_runs::StreamGitHubActionsRunsUseCaseOutput;
+    use crate::domain::repositories::RunRepository;
+    use crate::infrastructures::adapters::primary::web::create_router;
+    use crate::infrastructures::adapters::secondary::persistence::SqliteRunRepository;
+    use crate::test_support::{StubGitHubApi, app_state, base_time, workflow_run};
+    use axum::body::{Body, to_bytes};
+    use axum::http::{Request, header};
+    use chrono::TimeDelta;
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    async fn post_json(
+        state: Arc<AppState>,
+        uri: &str,
+        body: &serde_json::Value,
+    ) -> anyhow::Result<(StatusCode, serde_json::Value)> {
+        let response = create_router(state)
+            .oneshot(
+                Request::post(uri)
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(body.to_string()))?,
+            )
+            .await?;
+        let status = response.status();
+        let body = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await?)?;
+        Ok((status, body))
+    }
+
+    /// A success the day before `base_time()`, then a success and a failure on its day.
+    fn runs() -> Vec<WorkflowRun> {
+        let mut yesterday = workflow_run("owner/repo", 1, "success");
+        yesterday.created_at -= TimeDelta::days(1);
+        let mut failed = workflow_run("owner/repo", 3, "failure");
+        failed.updated_at += TimeDelta::minutes(4);
+        vec![failed, workflow_run("owner/repo", 2, "success"), yesterday]
+    }
+
+    fn query(from: &str, to: &str, target: &str) -> serde_json::Value {
+        json!({
+            "range": {"from": from, "to": to},
+            "intervalMs": 60000,
+            "targets": [{"target": target, "refId": "A", "type": "timeserie"}]
+        })
+    }
+
+    fn live_state() -> anyhow::Result<Arc<AppState>> {
+        let state = app_state(StubGitHubApi::default(), None)?;
+        state
+            .poller
+            .publish(StreamGitHubActionsRunsUseCaseOutput::new(
+                runs(),
+                base_time(),
+            ));
+        Ok(state)
+    }
+
+    async fn history_state() -> anyhow::Result<Arc<AppState>> {
+        let run_repository = Arc::new(SqliteRunRepository::connect("sqlite::memory:")?);
+        run_repository.save_runs(&runs(), base_time()).await?;
+        run_repository
+            .aggregate_daily_stats(base_time().date_naive() - TimeDelta::days(1))
+            .await?;
+        let state = app_state(StubGitHubApi::default(), None)?;
+        let state = Arc::into_inner(state)
+            .ok_or_else(|| anyhow::anyhow!("state is shared"))?
+            .with_run_repository(run_repository);
+        Ok(Arc::new(state))
+    }
+
+    #[tokio::test]
+    async fn test_series_cover_only_the_days_with_data() -> anyhow::Result<()> {
+        let midnight = json!(1_722_470_400_000_i64); // 2024-08-01T00:00:00Z
+        for state in [live_state()?, history_state().await?] {
+            // The range starts after the first day's midnight, leaving its datapoint out, and
+            // runs past the data
+            let (status, body) = post_json(
+                Arc::clone(&state),
+                "/grafana/query",
+                &query(
+                    "2024-07-31T12:00:00Z",
+                    "2024-08-10T00:00:00Z",
+                    "success_rate:owner/repo",
+                ),
+            )
+            .await?;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(body[0]["type"], "timeserie");
+            assert_eq!(body[0]["target"], "success_rate:owner/repo");
+            assert_eq!(body[0]["datapoints"], json!([[0.5, midnight]]));
+
+            let (_, body) = post_json(
+                Arc::clone(&state),
+                "/grafana/query",
+                &query(
+                    "2024-07-30T00:00:00Z",
+                    "2024-08-10T00:00:00Z",
+                    "mean_duration_seconds:owner/repo",
+                ),
+            )
+            .await?;
+            assert_eq!(body[0]["datapoints"].as_array().map(Vec::len), Some(2));
+            assert_eq!(body[0]["datapoints"][1], json!([120.0, midnight]));
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ranges_without_data_give_empty_results() -> anyhow::Result<()> {
+        for state in [live_state()?, history_state().await?] {
+            for target in [
+                "runs:owner/repo",
+                "recent_runs:owner/repo",
+                "runs:owner/other",
+            ] {
+                let (status, body) = post_json(
+                    Arc::clone(&state),
+                    "/grafana/query",
+                    &query("2020-01-01T00:00:00Z", "2020-02-01T00:00:00Z", target),
+                )
+                .await?;
+                assert_eq!(status, StatusCode::OK, "{target}");
+                let empty = body[0]["datapoints"]
+                    .as_array()
+                    .or(body[0]["rows"].as_array())
+                    .is_some_and(Vec::is_empty);
+                assert!(empty, "{target}: {body}");
+            }
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_recent_runs_are_a_table() -> anyhow::Result<()> {
+        let state = history_state().await?;
+        let mut request = query(
+            "2024-08-01T00:00:00Z",
+            "2024-08-02T00:00:00Z",
+            "recent_runs:owner/repo",
+        );
+        request["maxDataPoints"] = json!(1);
+
+        let (status, body) = post_json(state, "/grafana/query", &request).await?;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body[0]["type"], "table");
+        assert_eq!(
+            body[0]["columns"][0],
+            json!({"text": "Time", "type": "time"})
+        );
+        assert_eq!(body[0]["rows"].as_array().map(Vec::len), Some(1));
+        assert_eq!(body[0]["rows"][0][4], "failure");
+        assert_eq!(body[0]["rows"][0][5], 240);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_search_lists_the_series_of_snapshot_repositories() -> anyhow::Result<()> {
+        let state = live_state()?;
+
+        let (_, all) = post_json(Arc::clone(&state), "/grafana/search", &json!({})).await?;
+        let (_, matching) =
+            post_json(state, "/grafana/search", &json!({"target": "success"})).await?;
+
+        assert_eq!(all.as_array().map(Vec::len), Some(Metric::ALL.len()));
+        assert_eq!(matching, json!(["success_rate:owner/repo"]));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unknown_series_are_rejected() -> anyhow::Result<()> {
+        let state = live_state()?;
+
+        for target in ["owner/repo", "p99:owner/repo", "runs:owner"] {
+            let (status, body) = post_json(
+                Arc::clone(&state),
+                "/grafana/query",
+                &query("2024-08-01T00:00:00Z", "2024-08-02T00:00:00Z", target),
+            )
+            .await?;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{target}");
+            assert!(body["error"].is_string());
+        }
+        Ok(())
+    }
+}
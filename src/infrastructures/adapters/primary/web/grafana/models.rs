@@ -0,0 +1,73 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Body of `POST /grafana/search`.
+#[derive(Deserialize, Debug, Default)]
+pub struct SearchRequest {
+    /// Text typed into the metric picker; only series containing it are listed
+    #[serde(default)]
+    pub target: String,
+}
+
+/// Dashboard time range of a query.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeRange {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+}
+
+/// One series a panel asks for.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct QueryTarget {
+    /// A series name listed by `/grafana/search`
+    pub target: String,
+}
+
+/// Body of `POST /grafana/query`.
+#[derive(Deserialize, Debug)]
+pub struct QueryRequest {
+    pub range: TimeRange,
+    #[serde(default)]
+    pub targets: Vec<QueryTarget>,
+    /// Upper bound on the rows of a table
+    #[serde(rename = "maxDataPoints")]
+    pub max_data_points: Option<usize>,
+}
+
+/// Answer to one [`QueryTarget`], tagged with the panel type it fits.
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum QueryResult {
+    #[serde(rename = "timeserie")]
+    TimeSeries(TimeSeries),
+    Table(Table),
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct TimeSeries {
+    pub target: String,
+    /// `[value, milliseconds since the Unix epoch]` pairs, oldest first
+    pub datapoints: Vec<(f64, i64)>,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ColumnType {
+    Time,
+    String,
+    Number,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct Column {
+    pub text: &'static str,
+    #[serde(rename = "type")]
+    pub kind: ColumnType,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Table {
+    pub columns: Vec<Column>,
+    /// One value per column, in column order
+    pub rows: Vec<Vec<serde_json::Value>>,
+}
@@ -0,0 +1,639 @@
+pub mod types;
+
+use super::AppState;
+use super::trends::{DEFAULT_TREND_DAYS, MAX_TREND_DAYS};
+use crate::application::services::{CatchUp, PollerEvent, SequencedEvent};
+use crate::application::use_cases::StreamGitHubActionsRunsUseCaseOutput;
+use crate::domain::external_apis::github::GitHubApiError;
+use crate::domain::models::ids::{RepoFullName, RunId};
+use crate::domain::models::job::Job;
+use crate::domain::models::matrix::MatrixGroup;
+use async_graphql::http::ALL_WEBSOCKET_PROTOCOLS;
+use async_graphql::{
+    Context, EmptyMutation, Error, Object, Result, Schema, SimpleObject, Subscription,
+};
+use async_graphql_axum::{GraphQLProtocol, GraphQLRequest, GraphQLResponse, GraphQLWebSocket};
+use axum::Extension;
+use axum::extract::ws::WebSocketUpgrade;
+use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, TimeDelta, Utc};
+use futures_util::Stream;
+use std::sync::Arc;
+use tokio::sync::{OnceCell, broadcast};
+use types::{DailyStat, PolledRepository, Run, RunFilter, RunJob, RunMatrixGroup};
+
+/// Deepest selection a query may nest
+pub const MAX_QUERY_DEPTH: usize = 8;
+
+/// Most complexity a query may add up to; every field counts 1 unless it says otherwise
+pub const MAX_QUERY_COMPLEXITY: usize = 500;
+
+/// Complexity of `runDetail`, which calls GitHub, so a query can only request a few
+const RUN_DETAIL_COMPLEXITY: usize = 100;
+
+/// The schema served on `POST /graphql` and over `/ws` with the graphql-ws protocols.
+pub type DashboardSchema = Schema<QueryRoot, EmptyMutation, SubscriptionRoot>;
+
+/// Builds the schema, resolving fields from `state`.
+#[must_use]
+pub fn schema(state: Arc<AppState>) -> DashboardSchema {
+    Schema::build(QueryRoot, EmptyMutation, SubscriptionRoot)
+        .data(state)
+        .limit_depth(MAX_QUERY_DEPTH)
+        .limit_complexity(MAX_QUERY_COMPLEXITY)
+        .finish()
+}
+
+fn app_state<'a>(ctx: &Context<'a>) -> Result<&'a Arc<AppState>> {
+    ctx.data::<Arc<AppState>>()
+}
+
+fn latest_snapshot(ctx: &Context<'_>) -> Result<Arc<StreamGitHubActionsRunsUseCaseOutput>> {
+    app_state(ctx)?
+        .snapshots
+        .latest()
+        .ok_or_else(|| Error::new("No snapshot has been fetched yet"))
+}
+
+fn parse_repository(repository: &str) -> Result<RepoFullName> {
+    repository.parse().map_err(|e| Error::new(format!("{e}")))
+}
+
+pub struct QueryRoot;
+
+#[Object]
+#[expect(clippy::unused_async, reason = "async-graphql resolvers must be async")]
+impl QueryRoot {
+    /// Runs of the latest snapshot, newest first
+    async fn runs(&self, ctx: &Context<'_>, filter: Option<RunFilter>) -> Result<Vec<Run>> {
+        let filter = filter.unwrap_or_default();
+        Ok(latest_snapshot(ctx)?
+            .runs
+            .iter()
+            .filter(|run| filter.matches(run))
+            .cloned()
+            .map(Run)
+            .collect())
+    }
+
+    /// Repositories of the latest snapshot
+    async fn repositories(&self, ctx: &Context<'_>) -> Result<Vec<PolledRepository>> {
+        Ok(latest_snapshot(ctx)?
+            .repositories
+            .iter()
+            .map(PolledRepository::from)
+            .collect())
+    }
+
+    /// Daily stats of a repository's workflows from the run history, oldest first, like
+    /// `/trends`
+    async fn stats(
+        &self,
+        ctx: &Context<'_>,
+        repository: String,
+        #[graphql(default_with = "DEFAULT_TREND_DAYS")] days: i64,
+    ) -> Result<Vec<DailyStat>> {
+        let Some(run_repository) = &app_state(ctx)?.run_repository else {
+            return Err(Error::new(
+                "Run history is disabled, set DATABASE_URL to enable it",
+            ));
+        };
+        if !(1..=MAX_TREND_DAYS).contains(&days) {
+            return Err(Error::new(format!(
+                "days must be between 1 and {MAX_TREND_DAYS}"
+            )));
+        }
+        let since = Utc::now().date_naive() - TimeDelta::days(days - 1);
+        let stats = run_repository
+            .daily_stats(&parse_repository(&repository)?, since)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to read daily run stats: {}", e);
+                Error::new("Failed to read stats")
+            })?;
+        Ok(stats.into_iter().map(DailyStat::from).collect())
+    }
+
+    /// A run with its jobs; the only field that calls GitHub, so it weighs heavily on the
+    /// query complexity
+    #[graphql(complexity = "RUN_DETAIL_COMPLEXITY + child_complexity")]
+    async fn run_detail(&self, repository: String, id: u64) -> Result<RunDetail> {
+        Ok(RunDetail {
+            repository: parse_repository(&repository)?,
+            run_id: RunId(id),
+            jobs: OnceCell::new(),
+        })
+    }
+}
+
+/// A run looked up by repository and id.
+pub struct RunDetail {
+    repository: RepoFullName,
+    run_id: RunId,
+    /// Fetched once for `jobs` and `matrixGroups`
+    jobs: OnceCell<Vec<Job>>,
+}
+
+impl RunDetail {
+    async fn fetched_jobs(&self, ctx: &Context<'_>) -> Result<&[Job]> {
+        let github_api = &app_state(ctx)?.github_api;
+        let jobs = self
+            .jobs
+            .get_or_try_init(|| github_api.fetch_workflow_jobs(&self.repository, self.run_id))
+            .await
+            .map_err(|e| github_error(&e, self))?;
+        Ok(jobs)
+    }
+}
+
+#[Object]
+#[expect(clippy::unused_async, reason = "async-graphql resolvers must be async")]
+impl RunDetail {
+    /// The run as of the latest snapshot; unset when it is not in it
+    async fn run(&self, ctx: &Context<'_>) -> Result<Option<Run>> {
+        Ok(latest_snapshot(ctx)?
+            .runs
+            .iter()
+            .find(|run| {
+                run.id == self.run_id && run.repository_name.eq_ignore_ascii_case(&self.repository)
+            })
+            .cloned()
+            .map(Run))
+    }
+
+    /// Jobs of the run, fetched from GitHub
+    async fn jobs(&self, ctx: &Context<'_>) -> Result<Vec<RunJob>> {
+        Ok(self
+            .fetched_jobs(ctx)
+            .await?
+            .iter()
+            .cloned()
+            .map(RunJob)
+            .collect())
+    }
+
+    /// The jobs that are cells of a matrix, grouped by job; `jobs` still lists every job
+    async fn matrix_groups(&self, ctx: &Context<'_>) -> Result<Vec<RunMatrixGroup>> {
+        Ok(MatrixGroup::from_jobs(self.fetched_jobs(ctx).await?)
+            .into_iter()
+            .map(RunMatrixGroup)
+            .collect())
+    }
+}
+
+/// The GraphQL counterpart of `github_error_response`.
+fn github_error(error: &GitHubApiError, detail: &RunDetail) -> Error {
+    match error {
+        GitHubApiError::NotFound { .. } => Error::new(format!(
+            "Workflow run {}#{} was not found",
+            detail.repository, detail.run_id
+        )),
+        GitHubApiError::RateLimited { .. } => Error::new("GitHub API rate limit exceeded"),
+        GitHubApiError::Forbidden { .. } | GitHubApiError::InvalidWorkflow { .. } => {
+            Error::new(error.to_string())
+        }
+        GitHubApiError::Unauthorized { .. }
+        | GitHubApiError::Network { .. }
+        | GitHubApiError::Timeout { .. }
+        | GitHubApiError::Decode { .. }
+        | GitHubApiError::Other { .. } => {
+            tracing::error!("{}", error);
+            Error::new(format!("Error: {error}"))
+        }
+    }
+}
+
+/// A snapshot published by the poller.
+#[derive(SimpleObject)]
+pub struct RunUpdate {
+    /// Sequence number shared with `/ws` frames
+    seq: u64,
+    generated_at: DateTime<Utc>,
+    runs: Vec<Run>,
+}
+
+impl RunUpdate {
+    fn new(event: &SequencedEvent, filter: &RunFilter) -> Option<Self> {
+        let PollerEvent::Snapshot(output) = &event.event else {
+            return None;
+        };
+        Some(Self {
+            seq: event.seq,
+            generated_at: output.generated_at,
+            runs: output
+                .runs
+                .iter()
+                .filter(|run| filter.matches(run))
+                .cloned()
+                .map(Run)
+                .collect(),
+        })
+    }
+}
+
+pub struct SubscriptionRoot;
+
+#[Subscription]
+#[expect(clippy::unused_async, reason = "async-graphql resolvers must be async")]
+impl SubscriptionRoot {
+    /// The latest snapshot, then every new one until the server shuts down
+    async fn run_updates(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<RunFilter>,
+    ) -> Result<impl Stream<Item = RunUpdate> + use<>> {
+        let filter = filter.unwrap_or_default();
+        let (mut events, catch_up) = app_state(ctx)?.snapshots.attach(None);
+        let latest = match catch_up {
+            CatchUp::Snapshot { latest, .. } => latest,
+            CatchUp::Replay(_) => None,
+        };
+        Ok(async_stream::stream! {
+            if let Some(update) = latest.and_then(|latest| RunUpdate::new(&latest, &filter)) {
+                yield update;
+            }
+            loop {
+                match events.recv().await {
+                    Ok(SequencedEvent { event: PollerEvent::Shutdown, .. })
+                    | Err(broadcast::error::RecvError::Closed) => break,
+                    Ok(event) => {
+                        if let Some(update) = RunUpdate::new(&event, &filter) {
+                            yield update;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!("GraphQL subscriber lagged behind, skipped {} updates", skipped);
+                    }
+                }
+            }
+        })
+    }
+}
+
+#[tracing::instrument(name = "graphql_handler", skip_all)]
+pub async fn graphql_handler(
+    Extension(schema): Extension<DashboardSchema>,
+    request: GraphQLRequest,
+) -> GraphQLResponse {
+    schema.execute(request.into_inner()).await.into()
+}
+
+/// Serves GraphQL subscriptions on a `/ws` upgrade that asked for a graphql-ws protocol.
+pub fn graphql_websocket(
+    ws: WebSocketUpgrade,
+    protocol: GraphQLProtocol,
+    schema: DashboardSchema,
+    state: Arc<AppState>,
+) -> Response {
+    ws.protocols(ALL_WEBSOCKET_PROTOCOLS)
+        .on_upgrade(move |socket| async move {
+            state.metrics.websocket_connected();
+            GraphQLWebSocket::new(socket, schema, protocol)
+                .serve()
+                .await;
+            state.metrics.websocket_disconnected();
+        })
+        .into_response()
+}
+
+/// `GraphiQL` for trying queries out, only in debug builds.
+///
+/// Endpoints are derived from the page's own path, so it works wherever the router is nested.
+#[cfg(debug_assertions)]
+pub async fn graphql_playground_handler(
+    axum::extract::OriginalUri(uri): axum::extract::OriginalUri,
+) -> axum::response::Html<String> {
+    let base = uri
+        .path()
+        .strip_suffix("/graphql/playground")
+        .unwrap_or_default();
+    let endpoint = format!("{base}/graphql");
+    let subscription_endpoint = format!("{base}/ws");
+    axum::response::Html(
+        async_graphql::http::GraphiQLSource::build()
+            .endpoint(&endpoint)
+            .subscription_endpoint(&subscription_endpoint)
+            .title("gha-dashboard")
+            .finish(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::ids::JobId;
+    use crate::domain::models::job::Job;
+    use crate::domain::models::status::RunStatus;
+    use crate::domain::repositories::RunRepository;
+    use crate::infrastructures::adapters::primary::web::create_router;
+    use crate::infrastructures::adapters::secondary::persistence::SqliteRunRepository;
+    use crate::test_support::{StubGitHubApi, app_state, base_time, workflow_run};
+    use axum::body::{Body, to_bytes};
+    use axum::http::{Request, StatusCode};
+    use futures_util::{SinkExt, StreamExt};
+    use std::time::Duration;
+    use tokio_tungstenite::tungstenite::Message;
+    use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+    use tower::ServiceExt;
+
+    /// A state whose latest snapshot holds a failure and a success of `owner/repo` and an
+    /// in-progress run of `owner/other`.
+    fn state_with_snapshot(github_api: StubGitHubApi) -> anyhow::Result<Arc<AppState>> {
+        let state = app_state(github_api, None)?;
+        state.poller.publish(
+            StreamGitHubActionsRunsUseCaseOutput::new(
+                vec![
+                    workflow_run("owner/repo", 3, "failure"),
+                    workflow_run("owner/other", 2, "in_progress"),
+                    workflow_run("owner/repo", 1, "success"),
+                ],
+                base_time(),
+            )
+            .with_repositories(StubGitHubApi::with_repository("owner", "repo").repositories),
+        );
+        Ok(state)
+    }
+
+    async fn execute(
+        state: Arc<AppState>,
+        query: &str,
+    ) -> anyhow::Result<(serde_json::Value, Vec<String>)> {
+        let response = schema(state).execute(query).await;
+        let errors = response.errors.iter().map(|e| e.message.clone()).collect();
+        Ok((response.data.into_json()?, errors))
+    }
+
+    #[test]
+    fn test_schema_is_stable() -> anyhow::Result<()> {
+        insta::assert_snapshot!(schema(app_state(StubGitHubApi::default(), None)?).sdl());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_runs_are_filtered() -> anyhow::Result<()> {
+        let state = state_with_snapshot(StubGitHubApi::default())?;
+
+        let (data, errors) = execute(
+            Arc::clone(&state),
+            "{ runs { id displayStatus actor { login isBot } } }",
+        )
+        .await?;
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(data["runs"].as_array().map(Vec::len), Some(3));
+        assert_eq!(data["runs"][0]["displayStatus"], "failure");
+        assert_eq!(data["runs"][0]["actor"]["login"], "octocat");
+
+        let (data, _) = execute(
+            Arc::clone(&state),
+            r#"{ runs(filter: { repository: "OWNER/REPO", displayStatus: "success" }) { id } }"#,
+        )
+        .await?;
+        assert_eq!(data["runs"], serde_json::json!([{ "id": 1 }]));
+
+        let (data, _) = execute(state, "{ runs(filter: { problemOnly: true }) { id } }").await?;
+        assert_eq!(data["runs"], serde_json::json!([{ "id": 3 }]));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_repositories_come_from_the_snapshot() -> anyhow::Result<()> {
+        let state = state_with_snapshot(StubGitHubApi::default())?;
+
+        let (data, errors) = execute(state, "{ repositories { fullName ownerAvatarUrl } }").await?;
+
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(
+            data["repositories"],
+            serde_json::json!([{
+                "fullName": "owner/repo",
+                "ownerAvatarUrl": "https://avatars.githubusercontent.com/owner"
+            }])
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_queries_fail_before_the_first_snapshot() -> anyhow::Result<()> {
+        let state = app_state(StubGitHubApi::default(), None)?;
+
+        let (_, errors) = execute(state, "{ runs { id } }").await?;
+
+        assert_eq!(errors, ["No snapshot has been fetched yet"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stats_are_read_from_the_run_history() -> anyhow::Result<()> {
+        let mut run = workflow_run("owner/repo", 1, "success");
+        run.created_at = Utc::now() - TimeDelta::minutes(10);
+        run.updated_at = run.created_at + TimeDelta::minutes(2);
+        let run_repository = Arc::new(SqliteRunRepository::connect("sqlite::memory:")?);
+        run_repository.save_runs(&[run], Utc::now()).await?;
+        run_repository
+            .aggregate_daily_stats(Utc::now().date_naive() - TimeDelta::days(1))
+            .await?;
+        let state = Arc::into_inner(app_state(StubGitHubApi::default(), None)?)
+            .ok_or_else(|| anyhow::anyhow!("state is shared"))?
+            .with_run_repository(run_repository);
+
+        let (data, errors) = execute(
+            Arc::new(state),
+            r#"{ stats(repository: "owner/repo", days: 2) {
+                workflowName runs successRate meanDurationSeconds conclusions { conclusion runs }
+            } }"#,
+        )
+        .await?;
+
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(
+            data["stats"],
+            serde_json::json!([{
+                "workflowName": "CI",
+                "runs": 1,
+                "successRate": 1.0,
+                "meanDurationSeconds": 120.0,
+                "conclusions": [{ "conclusion": "success", "runs": 1 }]
+            }])
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stats_need_the_run_history() -> anyhow::Result<()> {
+        let state = app_state(StubGitHubApi::default(), None)?;
+
+        let (_, errors) = execute(state, r#"{ stats(repository: "owner/repo") { runs } }"#).await?;
+
+        assert_eq!(
+            errors,
+            ["Run history is disabled, set DATABASE_URL to enable it"]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_run_detail_fetches_jobs_from_github() -> anyhow::Result<()> {
+        let state = state_with_snapshot(StubGitHubApi {
+            jobs: Some(vec![Job {
+                id: JobId(101),
+                run_id: RunId(3),
+                name: "build".to_string(),
+                status: RunStatus::InProgress,
+                conclusion: None,
+                started_at: Some(base_time()),
+                completed_at: None,
+                runner_name: None,
+                labels: vec!["ubuntu-latest".to_string()],
+                html_url: "https://github.com/owner/repo/actions/runs/3/job/101".to_string(),
+                steps: Vec::new(),
+            }]),
+            ..StubGitHubApi::default()
+        })?;
+
+        let (data, errors) = execute(
+            Arc::clone(&state),
+            r#"{ runDetail(repository: "owner/repo", id: 3) {
+                run { displayStatus } jobs { id status labels } matrixGroups { name }
+            } }"#,
+        )
+        .await?;
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(data["runDetail"]["run"]["displayStatus"], "failure");
+        assert_eq!(
+            data["runDetail"]["jobs"],
+            serde_json::json!([{ "id": 101, "status": "in_progress", "labels": ["ubuntu-latest"] }])
+        );
+        assert_eq!(data["runDetail"]["matrixGroups"], serde_json::json!([]));
+
+        // A run missing from the snapshot still has its jobs looked up
+        let (data, _) = execute(
+            state,
+            r#"{ runDetail(repository: "owner/repo", id: 99) { run { id } } }"#,
+        )
+        .await?;
+        assert_eq!(data["runDetail"]["run"], serde_json::Value::Null);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_run_detail_reports_missing_runs() -> anyhow::Result<()> {
+        let state = state_with_snapshot(StubGitHubApi::default())?;
+
+        let (_, errors) = execute(
+            state,
+            r#"{ runDetail(repository: "owner/repo", id: 99) { jobs { id } } }"#,
+        )
+        .await?;
+
+        assert_eq!(errors, ["Workflow run owner/repo#99 was not found"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_queries_calling_github_too_often_are_rejected() -> anyhow::Result<()> {
+        let detail = |alias: u64| {
+            format!(
+                r#"d{alias}: runDetail(repository: "owner/repo", id: {alias}) {{ jobs {{ id }} }}"#
+            )
+        };
+        let query = format!(
+            "{{ {} }}",
+            (1..=5).map(detail).collect::<Vec<_>>().join(" ")
+        );
+        let state = state_with_snapshot(StubGitHubApi::default())?;
+
+        let (_, errors) = execute(Arc::clone(&state), &query).await?;
+        assert_eq!(errors, ["Query is too complex."]);
+
+        let (_, errors) = execute(
+            state,
+            "{ runs { actor { login } triggeringActor { login } } }",
+        )
+        .await?;
+        assert!(errors.is_empty(), "{errors:?}");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_queries_are_served_over_http() -> anyhow::Result<()> {
+        let state = state_with_snapshot(StubGitHubApi::default())?;
+
+        let response = create_router(state)
+            .oneshot(
+                Request::post("/graphql")
+                    .header("content-type", "application/json")
+                    .body(Body::from(r#"{"query":"{ runs { id } }"}"#))?,
+            )
+            .await?;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: serde_json::Value =
+            serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await?)?;
+        assert_eq!(
+            body["data"]["runs"],
+            serde_json::json!([{ "id": 3 }, { "id": 2 }, { "id": 1 }])
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_run_updates_are_streamed_over_the_websocket() -> anyhow::Result<()> {
+        let state = state_with_snapshot(StubGitHubApi::default())?;
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let router = create_router(Arc::clone(&state));
+        tokio::spawn(async move {
+            axum::serve(
+                listener,
+                router.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+            )
+            .await
+        });
+        let mut request = format!("ws://{addr}/ws").into_client_request()?;
+        request
+            .headers_mut()
+            .insert("sec-websocket-protocol", "graphql-transport-ws".parse()?);
+        let (socket, response) = tokio_tungstenite::connect_async(request).await?;
+        assert_eq!(
+            response.headers()["sec-websocket-protocol"],
+            "graphql-transport-ws"
+        );
+        let (mut sink, mut stream) = socket.split();
+        let mut next_message = async || -> anyhow::Result<serde_json::Value> {
+            let message = tokio::time::timeout(Duration::from_secs(5), stream.next())
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("websocket closed"))??;
+            Ok(serde_json::from_str(message.to_text()?)?)
+        };
+
+        sink.send(Message::text(r#"{"type":"connection_init"}"#))
+            .await?;
+        assert_eq!(next_message().await?["type"], "connection_ack");
+        sink.send(Message::text(
+                r#"{"id":"1","type":"subscribe","payload":{"query":
+                "subscription { runUpdates(filter: { repository: \"owner/other\" }) { seq runs { id } } }"}}"#,
+            ))
+            .await?;
+        let first = next_message().await?;
+        assert_eq!(first["type"], "next");
+        assert_eq!(
+            first["payload"]["data"]["runUpdates"]["runs"],
+            serde_json::json!([{ "id": 2 }])
+        );
+
+        state
+            .poller
+            .publish(StreamGitHubActionsRunsUseCaseOutput::new(
+                vec![workflow_run("owner/other", 2, "success")],
+                base_time(),
+            ));
+        let second = next_message().await?;
+        assert_eq!(
+            second["payload"]["data"]["runUpdates"]["seq"].as_u64(),
+            first["payload"]["data"]["runUpdates"]["seq"]
+                .as_u64()
+                .map(|seq| seq + 1)
+        );
+        Ok(())
+    }
+}
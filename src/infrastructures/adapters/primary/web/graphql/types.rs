@@ -0,0 +1,388 @@
+use crate::domain::external_apis::github::Repository;
+use crate::domain::models::actor::RunActor;
+use crate::domain::models::job::{Job, Step};
+use crate::domain::models::matrix::{MatrixCell, MatrixGroup};
+use crate::domain::models::run::WorkflowRun;
+use crate::domain::models::status::RunConclusion;
+use crate::domain::repositories::DailyStats;
+use async_graphql::{InputObject, Object, SimpleObject};
+use chrono::{DateTime, NaiveDate, Utc};
+
+/// Conditions a run must all meet to be listed; unset ones match every run.
+#[derive(InputObject, Debug, Clone, Default)]
+pub struct RunFilter {
+    /// `owner/name`, matched ignoring ASCII case
+    pub repository: Option<String>,
+    /// The conclusion of a completed run, otherwise its status, e.g. `failure` or `queued`
+    pub display_status: Option<String>,
+    pub branch: Option<String>,
+    /// Workflow name
+    pub workflow: Option<String>,
+    /// Triggering event, e.g. `push`
+    pub event: Option<String>,
+    /// Login of the account that started the run
+    pub actor: Option<String>,
+    /// Name of a configured label rule the run matches
+    pub label: Option<String>,
+    /// Only runs that ended in a state needing attention
+    #[graphql(default)]
+    pub problem_only: bool,
+}
+
+impl RunFilter {
+    /// Whether `run` meets every condition that is set.
+    #[must_use]
+    pub fn matches(&self, run: &WorkflowRun) -> bool {
+        let holds = |expected: &Option<String>, value: Option<&str>| {
+            expected
+                .as_deref()
+                .is_none_or(|expected| value == Some(expected))
+        };
+        self.repository.as_deref().is_none_or(|repository| {
+            run.repository_name
+                .to_string()
+                .eq_ignore_ascii_case(repository)
+        }) && holds(&self.display_status, Some(run.display_status()))
+            && holds(&self.branch, run.head_branch.as_deref())
+            && holds(&self.workflow, Some(&run.workflow_name))
+            && holds(&self.event, Some(&run.event))
+            && holds(&self.actor, Some(&run.actor.login))
+            && self
+                .label
+                .as_ref()
+                .is_none_or(|label| run.labels.contains(label))
+            && (!self.problem_only || run.is_problem())
+    }
+}
+
+/// A workflow run, with the same fields as in the JSON of `/runs`.
+pub struct Run(pub WorkflowRun);
+
+#[Object]
+impl Run {
+    /// `owner/name`
+    async fn repository_name(&self) -> String {
+        self.0.repository_name.to_string()
+    }
+
+    async fn id(&self) -> u64 {
+        self.0.id.0
+    }
+
+    /// Sequential number of the run within its workflow, shown as `#1234`
+    async fn run_number(&self) -> u64 {
+        self.0.run_number
+    }
+
+    async fn workflow_id(&self) -> u64 {
+        self.0.workflow_id.0
+    }
+
+    async fn workflow_name(&self) -> &str {
+        &self.0.workflow_name
+    }
+
+    async fn display_title(&self) -> &str {
+        &self.0.display_title
+    }
+
+    /// The original title when `displayTitle` was truncated
+    async fn full_display_title(&self) -> Option<&str> {
+        self.0.full_display_title.as_deref()
+    }
+
+    async fn event(&self) -> &str {
+        &self.0.event
+    }
+
+    /// Unset for events without a branch, e.g. some tag pushes
+    async fn head_branch(&self) -> Option<&str> {
+        self.0.head_branch.as_deref()
+    }
+
+    async fn head_sha(&self) -> &str {
+        &self.0.head_sha
+    }
+
+    /// Account that started the first attempt of the run
+    async fn actor(&self) -> Actor<'_> {
+        Actor(&self.0.actor)
+    }
+
+    /// Account that started the latest attempt; differs from `actor` for re-runs
+    async fn triggering_actor(&self) -> Option<Actor<'_>> {
+        self.0.triggering_actor.as_ref().map(Actor)
+    }
+
+    /// GitHub's spelling, e.g. `in_progress` or `completed`
+    async fn status(&self) -> &str {
+        self.0.status.as_str()
+    }
+
+    /// GitHub's spelling, e.g. `success`; set once the run has completed
+    async fn conclusion(&self) -> Option<&str> {
+        self.0.conclusion.map(RunConclusion::as_str)
+    }
+
+    /// The conclusion of a completed run, otherwise its status
+    async fn display_status(&self) -> &str {
+        self.0.display_status()
+    }
+
+    /// Unset while the run is not completed
+    async fn duration_seconds(&self) -> Option<u64> {
+        self.0.duration_seconds()
+    }
+
+    async fn created_at(&self) -> DateTime<Utc> {
+        self.0.created_at
+    }
+
+    async fn updated_at(&self) -> DateTime<Utc> {
+        self.0.updated_at
+    }
+
+    async fn html_url(&self) -> &str {
+        &self.0.html_url
+    }
+
+    /// Names of the configured label rules the run matches, sorted
+    async fn labels(&self) -> &[String] {
+        &self.0.labels
+    }
+
+    /// False for runs kept after their repository dropped out of the polled ones
+    async fn repository_active(&self) -> bool {
+        self.0.repository_active
+    }
+}
+
+/// A GitHub account.
+pub struct Actor<'a>(&'a RunActor);
+
+#[Object]
+impl Actor<'_> {
+    async fn login(&self) -> &str {
+        &self.0.login
+    }
+
+    async fn avatar_url(&self) -> &str {
+        &self.0.avatar_url
+    }
+
+    async fn html_url(&self) -> &str {
+        &self.0.html_url
+    }
+
+    /// Whether the account is a bot, e.g. `dependabot[bot]`
+    async fn is_bot(&self) -> bool {
+        self.0.is_bot()
+    }
+}
+
+/// A polled repository.
+#[derive(SimpleObject)]
+pub struct PolledRepository {
+    /// `owner/name`
+    pub full_name: String,
+    pub html_url: String,
+    /// Avatar of the owning user or organization
+    pub owner_avatar_url: Option<String>,
+}
+
+impl From<&Repository> for PolledRepository {
+    fn from(repository: &Repository) -> Self {
+        Self {
+            full_name: repository.full_name().to_string(),
+            html_url: repository.html_url.clone(),
+            owner_avatar_url: repository.owner_avatar_url.clone(),
+        }
+    }
+}
+
+/// The completed runs of one workflow on one UTC day.
+#[derive(SimpleObject)]
+pub struct DailyStat {
+    pub date: NaiveDate,
+    pub workflow_id: u64,
+    pub workflow_name: String,
+    pub runs: u64,
+    /// Runs per conclusion, most frequent first
+    pub conclusions: Vec<ConclusionCount>,
+    /// Share of the runs that succeeded, from 0 to 1
+    pub success_rate: f64,
+    pub mean_duration_seconds: f64,
+}
+
+/// How many runs ended with a conclusion.
+#[derive(SimpleObject)]
+pub struct ConclusionCount {
+    pub conclusion: &'static str,
+    pub runs: u64,
+}
+
+impl From<DailyStats> for DailyStat {
+    fn from(stats: DailyStats) -> Self {
+        let mut conclusions: Vec<ConclusionCount> = stats
+            .conclusions
+            .into_iter()
+            .map(|(conclusion, runs)| ConclusionCount {
+                conclusion: conclusion.as_str(),
+                runs,
+            })
+            .collect();
+        conclusions.sort_by(|a, b| b.runs.cmp(&a.runs).then(a.conclusion.cmp(b.conclusion)));
+        Self {
+            date: stats.day,
+            workflow_id: stats.workflow_id.0,
+            workflow_name: stats.workflow_name,
+            runs: stats.runs,
+            conclusions,
+            success_rate: stats.success_rate,
+            mean_duration_seconds: stats.mean_duration_seconds,
+        }
+    }
+}
+
+/// A job of a workflow run, as `/runs/{owner}/{repo}/{id}/jobs` returns it.
+pub struct RunJob(pub Job);
+
+#[Object]
+impl RunJob {
+    async fn id(&self) -> u64 {
+        self.0.id.0
+    }
+
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    async fn status(&self) -> &str {
+        self.0.status.as_str()
+    }
+
+    /// Set once the job has completed
+    async fn conclusion(&self) -> Option<&str> {
+        self.0.conclusion.map(RunConclusion::as_str)
+    }
+
+    async fn started_at(&self) -> Option<DateTime<Utc>> {
+        self.0.started_at
+    }
+
+    async fn completed_at(&self) -> Option<DateTime<Utc>> {
+        self.0.completed_at
+    }
+
+    /// Unset until a runner has picked up the job
+    async fn runner_name(&self) -> Option<&str> {
+        self.0.runner_name.as_deref()
+    }
+
+    /// Labels the job requested from `runs-on`, e.g. `ubuntu-latest`
+    async fn labels(&self) -> &[String] {
+        &self.0.labels
+    }
+
+    async fn html_url(&self) -> &str {
+        &self.0.html_url
+    }
+
+    async fn steps(&self) -> Vec<RunStep<'_>> {
+        self.0.steps.iter().map(RunStep).collect()
+    }
+}
+
+/// A step of a job.
+pub struct RunStep<'a>(&'a Step);
+
+#[Object]
+impl RunStep<'_> {
+    /// 1-based position of the step within its job
+    async fn number(&self) -> u32 {
+        self.0.number
+    }
+
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    async fn status(&self) -> &str {
+        self.0.status.as_str()
+    }
+
+    async fn conclusion(&self) -> Option<&str> {
+        self.0.conclusion.map(RunConclusion::as_str)
+    }
+
+    async fn started_at(&self) -> Option<DateTime<Utc>> {
+        self.0.started_at
+    }
+
+    async fn completed_at(&self) -> Option<DateTime<Utc>> {
+        self.0.completed_at
+    }
+}
+
+/// The cells of a matrix job, with a rollup of their outcomes.
+pub struct RunMatrixGroup(pub MatrixGroup);
+
+#[Object]
+impl RunMatrixGroup {
+    /// Name of the job without the values of its cells
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    async fn cells(&self) -> Vec<RunMatrixCell<'_>> {
+        self.0.cells.iter().map(RunMatrixCell).collect()
+    }
+
+    /// Every cell completed without needing attention
+    async fn all_passed(&self) -> bool {
+        self.0.rollup().all_passed
+    }
+
+    /// Cells that ended in a state needing attention
+    async fn failed(&self) -> usize {
+        self.0.rollup().failed
+    }
+
+    /// Job id of the completed cell that took longest
+    async fn slowest_job_id(&self) -> Option<u64> {
+        self.0.rollup().slowest.map(|job_id| job_id.0)
+    }
+
+    /// Sum of the durations of the completed cells
+    async fn total_duration_seconds(&self) -> u64 {
+        self.0.rollup().total_duration.as_secs()
+    }
+}
+
+/// A job of a matrix.
+pub struct RunMatrixCell<'a>(&'a MatrixCell);
+
+#[Object]
+impl RunMatrixCell<'_> {
+    async fn job_id(&self) -> u64 {
+        self.0.job_id.0
+    }
+
+    /// Values of the cell, in the order of the matrix keys
+    async fn values(&self) -> &[String] {
+        &self.0.values
+    }
+
+    async fn status(&self) -> &str {
+        self.0.status.as_str()
+    }
+
+    async fn conclusion(&self) -> Option<&str> {
+        self.0.conclusion.map(RunConclusion::as_str)
+    }
+
+    /// Unset until the job has both started and completed
+    async fn duration_seconds(&self) -> Option<u64> {
+        self.0.duration.map(|duration| duration.as_secs())
+    }
+}
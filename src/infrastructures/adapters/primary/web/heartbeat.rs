@@ -0,0 +1,219 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use tokio::time::{Duration, Instant, Interval, MissedTickBehavior, interval_at};
+
+/// Time between two heartbeats sent to a websocket client unless configured otherwise
+pub const DEFAULT_WEBSOCKET_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Heartbeats in a row a websocket client may leave unacknowledged before it is considered gone
+pub const MAX_MISSED_HEARTBEAT_ACKS: u32 = 3;
+
+/// A `heartbeat` frame, which the client is expected to echo as `heartbeat_ack` with its `seq`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeartbeatFrame {
+    /// Counts the heartbeats of one connection from 1
+    pub seq: u64,
+    pub sent_at: DateTime<Utc>,
+}
+
+/// Heartbeat health of an open websocket connection, as listed by `/status`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ConnectionHealth {
+    pub id: u64,
+    /// User the authenticating proxy forwarded with `AUTH_MODE=forward_headers`
+    pub user: Option<String>,
+    #[serde(rename = "connectedAt")]
+    pub connected_at: DateTime<Utc>,
+    /// Round trip of the latest acknowledged heartbeat; `None` before the first ack
+    #[serde(rename = "rttMs")]
+    pub rtt_ms: Option<f64>,
+    /// Heartbeats left unacknowledged until the next one was due, since connecting
+    #[serde(rename = "missedAcks")]
+    pub missed_acks: u64,
+    /// Heartbeats missed since the latest ack
+    #[serde(rename = "consecutiveMissedAcks")]
+    pub consecutive_missed_acks: u32,
+}
+
+/// The open websocket connections, served by `/status`.
+#[derive(Debug, Default)]
+pub struct WebsocketConnections {
+    next_id: AtomicU64,
+    connections: Mutex<BTreeMap<u64, ConnectionHealth>>,
+}
+
+impl WebsocketConnections {
+    /// Lists a new connection of `user` until the returned handle is dropped.
+    #[must_use]
+    pub fn register(self: &Arc<Self>, user: Option<String>) -> RegisteredConnection {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        self.lock().insert(
+            id,
+            ConnectionHealth {
+                id,
+                user,
+                connected_at: Utc::now(),
+                rtt_ms: None,
+                missed_acks: 0,
+                consecutive_missed_acks: 0,
+            },
+        );
+        RegisteredConnection {
+            connections: Arc::clone(self),
+            id,
+        }
+    }
+
+    /// The open connections, oldest first.
+    #[must_use]
+    pub fn list(&self) -> Vec<ConnectionHealth> {
+        self.lock().values().cloned().collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<u64, ConnectionHealth>> {
+        self.connections
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// A connection listed in [`WebsocketConnections`], removed from it on drop.
+#[derive(Debug)]
+pub struct RegisteredConnection {
+    connections: Arc<WebsocketConnections>,
+    id: u64,
+}
+
+impl RegisteredConnection {
+    fn update(&self, update: impl FnOnce(&mut ConnectionHealth)) {
+        if let Some(health) = self.connections.lock().get_mut(&self.id) {
+            update(health);
+        }
+    }
+}
+
+impl Drop for RegisteredConnection {
+    fn drop(&mut self) {
+        self.connections.lock().remove(&self.id);
+    }
+}
+
+/// What to do when a heartbeat is due.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeartbeatTick {
+    Send(HeartbeatFrame),
+    /// The client missed [`MAX_MISSED_HEARTBEAT_ACKS`] heartbeats in a row
+    Dead,
+}
+
+/// Sends heartbeats to a websocket client and matches its acknowledgments, keeping the
+/// health of its connection up to date.
+#[derive(Debug)]
+pub struct HeartbeatMonitor {
+    ticks: Interval,
+    /// `seq` of the latest heartbeat, 0 before the first
+    last_seq: u64,
+    /// When the heartbeats not acknowledged yet were sent, by `seq`
+    pending: BTreeMap<u64, Instant>,
+    consecutive_missed: u32,
+    connection: RegisteredConnection,
+}
+
+impl HeartbeatMonitor {
+    /// Sends the first heartbeat one `interval` from now, and one per `interval` afterwards.
+    #[must_use]
+    pub fn new(interval: Duration, connection: RegisteredConnection) -> Self {
+        let mut ticks = interval_at(Instant::now() + interval, interval);
+        ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        Self {
+            ticks,
+            last_seq: 0,
+            pending: BTreeMap::new(),
+            consecutive_missed: 0,
+            connection,
+        }
+    }
+
+    /// Waits until the next heartbeat is due, counting the previous one as missed if it was
+    /// not acknowledged meanwhile.
+    ///
+    /// Cancel safe: nothing happens until the heartbeat is due.
+    pub async fn tick(&mut self) -> HeartbeatTick {
+        self.ticks.tick().await;
+        if self.pending.contains_key(&self.last_seq) {
+            self.consecutive_missed += 1;
+            let consecutive_missed = self.consecutive_missed;
+            self.connection.update(|health| {
+                health.missed_acks += 1;
+                health.consecutive_missed_acks = consecutive_missed;
+            });
+            if consecutive_missed >= MAX_MISSED_HEARTBEAT_ACKS {
+                return HeartbeatTick::Dead;
+            }
+        }
+        self.last_seq += 1;
+        self.pending.insert(self.last_seq, Instant::now());
+        HeartbeatTick::Send(HeartbeatFrame {
+            seq: self.last_seq,
+            sent_at: Utc::now(),
+        })
+    }
+
+    /// Records the client's ack of heartbeat `seq`, returning its round trip.
+    ///
+    /// A late ack of a heartbeat already counted as missed still shows the client is alive.
+    /// Acks of heartbeats never sent, or already acknowledged, are ignored.
+    pub fn ack(&mut self, seq: u64) -> Option<Duration> {
+        let Some(sent_at) = self.pending.remove(&seq) else {
+            tracing::debug!("Ignoring heartbeat_ack of unknown heartbeat {}", seq);
+            return None;
+        };
+        let rtt = sent_at.elapsed();
+        // Heartbeats sent before the one acknowledged can no longer count as missed
+        self.pending.retain(|&pending, _| pending > seq);
+        self.consecutive_missed = 0;
+        self.connection.update(|health| {
+            health.rtt_ms = Some(rtt.as_secs_f64() * 1000.0);
+            health.consecutive_missed_acks = 0;
+        });
+        Some(rtt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_unacknowledged_heartbeats_end_the_connection() -> anyhow::Result<()> {
+        let connections = Arc::new(WebsocketConnections::default());
+        let mut monitor =
+            HeartbeatMonitor::new(Duration::from_millis(20), connections.register(None));
+
+        let HeartbeatTick::Send(first) = monitor.tick().await else {
+            anyhow::bail!("the first heartbeat was not sent");
+        };
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        let rtt = monitor.ack(first.seq);
+        assert!(rtt.is_some_and(|rtt| rtt >= Duration::from_millis(5)));
+        assert_eq!(monitor.ack(first.seq), None);
+        assert_eq!(monitor.ack(42), None);
+        let mut sent = Vec::new();
+        while let HeartbeatTick::Send(frame) = monitor.tick().await {
+            sent.push(frame.seq);
+        }
+
+        assert_eq!(sent, vec![2, 3, 4]);
+        let listed = connections.list();
+        assert_eq!(listed.len(), 1);
+        assert!(listed[0].rtt_ms.is_some_and(|rtt_ms| rtt_ms >= 5.0));
+        assert_eq!(listed[0].missed_acks, 3);
+        assert_eq!(listed[0].consecutive_missed_acks, 3);
+        drop(monitor);
+        assert!(connections.list().is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,188 @@
+use super::presenter::{TimestampQuery, history_json};
+use super::{AppState, json_error};
+use crate::domain::models::ids::RepoFullName;
+use crate::domain::models::status::{RunConclusion, RunStatus};
+use crate::domain::repositories::RunQuery;
+use axum::{
+    extract::{Query, State, rejection::QueryRejection},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::sync::Arc;
+
+/// Most runs one `/history` request may return
+const MAX_HISTORY_LIMIT: usize = 1000;
+
+/// Filters of `GET /history`.
+#[derive(Deserialize, Debug, Default)]
+pub struct HistoryQuery {
+    repo: Option<RepoFullName>,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    status: Option<RunStatus>,
+    conclusion: Option<RunConclusion>,
+    limit: Option<usize>,
+}
+
+impl HistoryQuery {
+    fn into_run_query(self) -> Result<RunQuery, String> {
+        let limit = self.limit.unwrap_or(RunQuery::default().limit);
+        if !(1..=MAX_HISTORY_LIMIT).contains(&limit) {
+            return Err(format!("limit must be between 1 and {MAX_HISTORY_LIMIT}"));
+        }
+        Ok(RunQuery {
+            repository: self.repo,
+            since: self.since,
+            until: self.until,
+            status: self.status,
+            conclusion: self.conclusion,
+            limit,
+            ..RunQuery::default()
+        })
+    }
+}
+
+#[tracing::instrument(name = "history_handler", skip(state))]
+pub async fn history_handler(
+    query: Result<Query<HistoryQuery>, QueryRejection>,
+    timestamps: Result<Query<TimestampQuery>, QueryRejection>,
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    let Some(run_repository) = &state.run_repository else {
+        return json_error(
+            StatusCode::NOT_FOUND,
+            "Run history is disabled, set DATABASE_URL to enable it",
+        );
+    };
+    let mut query = match query {
+        Ok(Query(query)) => match query.into_run_query() {
+            Ok(query) => query,
+            Err(message) => return json_error(StatusCode::BAD_REQUEST, &message),
+        },
+        Err(rejection) => return json_error(rejection.status(), &rejection.body_text()),
+    };
+    let timestamps = match timestamps {
+        Ok(Query(timestamps)) => timestamps.or(state.timestamp_format),
+        Err(rejection) => return json_error(rejection.status(), &rejection.body_text()),
+    };
+
+    // Anonymized clients filter by pseudonym, and are only shown anonymized runs
+    if let (Some(anonymizer), Some(repository)) = (&state.anonymizer, &mut query.repository)
+        && let Some(original) = anonymizer.original(repository)
+    {
+        *repository = original;
+    }
+    let runs = match run_repository.query_runs(&query).await {
+        Ok(runs) => runs,
+        Err(e) => {
+            tracing::error!("Failed to query run history: {}", e);
+            return json_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to read run history",
+            );
+        }
+    };
+    let runs = match &state.anonymizer {
+        Some(anonymizer) => anonymizer.runs(&runs),
+        None => runs,
+    };
+    match history_json(&runs, timestamps) {
+        Ok(body) => ([(header::CONTENT_TYPE, "application/json")], body).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to serialize run history: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::repositories::RunRepository;
+    use crate::infrastructures::adapters::primary::web::create_router;
+    use crate::infrastructures::adapters::secondary::persistence::SqliteRunRepository;
+    use crate::test_support::{StubGitHubApi, app_state, base_time, workflow_run};
+    use axum::body::{Body, to_bytes};
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    async fn get_json(
+        state: Arc<AppState>,
+        uri: &str,
+    ) -> anyhow::Result<(StatusCode, serde_json::Value)> {
+        let response = create_router(state)
+            .oneshot(Request::get(uri).body(Body::empty())?)
+            .await?;
+        let status = response.status();
+        let body = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await?)?;
+        Ok((status, body))
+    }
+
+    async fn state_with_history() -> anyhow::Result<Arc<AppState>> {
+        let run_repository = Arc::new(SqliteRunRepository::connect("sqlite::memory:")?);
+        run_repository
+            .save_runs(
+                &[
+                    workflow_run("owner/repo", 1, "success"),
+                    workflow_run("owner/repo", 2, "failure"),
+                    workflow_run("owner/other", 3, "in_progress"),
+                ],
+                base_time(),
+            )
+            .await?;
+        let state = app_state(StubGitHubApi::default(), None)?;
+        let state = Arc::into_inner(state)
+            .ok_or_else(|| anyhow::anyhow!("state is shared"))?
+            .with_run_repository(run_repository);
+        Ok(Arc::new(state))
+    }
+
+    #[tokio::test]
+    async fn test_history_is_filtered_by_query() -> anyhow::Result<()> {
+        let state = state_with_history().await?;
+
+        let (status, body) = get_json(
+            Arc::clone(&state),
+            "/history?repo=owner/repo&conclusion=failure&ts=millis",
+        )
+        .await?;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["runs"].as_array().map(Vec::len), Some(1));
+        assert_eq!(body["runs"][0]["id"], 2);
+        assert!(body["runs"][0]["createdAt"].is_i64());
+
+        let (_, body) = get_json(state, "/history?status=in_progress").await?;
+        assert_eq!(body["runs"][0]["repositoryName"], "owner/other");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_history_rejects_bad_queries() -> anyhow::Result<()> {
+        let state = state_with_history().await?;
+
+        for uri in [
+            "/history?limit=0",
+            "/history?limit=1001",
+            "/history?since=yesterday",
+            "/history?repo=owner",
+        ] {
+            let (status, body) = get_json(Arc::clone(&state), uri).await?;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{uri}");
+            assert!(body["error"].is_string());
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_history_is_not_found_without_a_store() -> anyhow::Result<()> {
+        let state = app_state(StubGitHubApi::default(), None)?;
+
+        let (status, _) = get_json(state, "/history").await?;
+
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        Ok(())
+    }
+}
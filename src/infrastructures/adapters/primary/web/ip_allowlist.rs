@@ -0,0 +1,296 @@
+use super::client_ip::client_ip;
+use super::{AppState, json_error};
+use axum::{
+    body::Body,
+    extract::State,
+    http::{Request, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display};
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// A block of IPv4 or IPv6 addresses such as `10.0.0.0/8` or `fd00::/8`.
+///
+/// A bare address is a block of one; bits past the prefix are ignored, so `10.1.2.3/8`
+/// is `10.0.0.0/8`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
+pub struct IpCidr {
+    network: IpAddr,
+    prefix_len: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "invalid CIDR {0:?}, expected an address with an optional prefix length such as 10.0.0.0/8 or fd00::/8"
+)]
+pub struct InvalidCidr(String);
+
+impl IpCidr {
+    /// Whether `ip` is in the block; IPv4-mapped IPv6 addresses match IPv4 blocks.
+    #[must_use]
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.network, ip.to_canonical()) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                mask(u128::from(ip.to_bits()), 32, self.prefix_len) == u128::from(network.to_bits())
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                mask(ip.to_bits(), 128, self.prefix_len) == network.to_bits()
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Clears the bits of `bits`, an address `width` bits wide, past the first `prefix_len`.
+fn mask(bits: u128, width: u8, prefix_len: u8) -> u128 {
+    let host_bits = width - prefix_len;
+    if host_bits >= 128 {
+        0
+    } else {
+        bits >> host_bits << host_bits
+    }
+}
+
+impl FromStr for IpCidr {
+    type Err = InvalidCidr;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidCidr(value.to_string());
+        let (address, prefix_len) = match value.trim().split_once('/') {
+            Some((address, prefix_len)) => (address, Some(prefix_len)),
+            None => (value.trim(), None),
+        };
+        let address: IpAddr = address.parse().map_err(|_| invalid())?;
+        let width = if address.is_ipv4() { 32 } else { 128 };
+        let prefix_len = match prefix_len {
+            Some(prefix_len) => prefix_len.parse::<u8>().map_err(|_| invalid())?,
+            None => width,
+        };
+        if prefix_len > width {
+            return Err(invalid());
+        }
+        let network = match address {
+            IpAddr::V4(address) => IpAddr::V4(
+                u32::try_from(mask(u128::from(address.to_bits()), width, prefix_len))
+                    .map_err(|_| invalid())?
+                    .into(),
+            ),
+            IpAddr::V6(address) => IpAddr::V6(mask(address.to_bits(), width, prefix_len).into()),
+        };
+        Ok(Self {
+            network,
+            prefix_len,
+        })
+    }
+}
+
+impl TryFrom<String> for IpCidr {
+    type Error = InvalidCidr;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl Display for IpCidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.network, self.prefix_len)
+    }
+}
+
+impl From<IpCidr> for String {
+    fn from(cidr: IpCidr) -> Self {
+        cidr.to_string()
+    }
+}
+
+/// Rejects requests from clients outside `AppState::allowed_cidrs` with 403, before routing.
+///
+/// Clients whose address is unknown, e.g. on a Unix socket without a trusted proxy header,
+/// are rejected too. An empty list lets everyone through.
+pub async fn ip_allowlist_middleware(
+    State(state): State<Arc<AppState>>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    if state.allowed_cidrs.is_empty() {
+        return next.run(request).await;
+    }
+    let ip = client_ip(&request, state.trust_proxy);
+    if ip.is_some_and(|ip| state.allowed_cidrs.iter().any(|cidr| cidr.contains(ip))) {
+        return next.run(request).await;
+    }
+    tracing::warn!(
+        "Rejected {} {} from {}",
+        request.method(),
+        request.uri().path(),
+        ip.map_or_else(|| "an unknown address".to_string(), |ip| ip.to_string())
+    );
+    json_error(StatusCode::FORBIDDEN, "Client address not allowed")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructures::adapters::primary::web::create_router;
+    use crate::test_support::{StubGitHubApi, app_state};
+    use axum::extract::ConnectInfo;
+    use axum::http::header;
+    use std::net::SocketAddr;
+    use tower::ServiceExt;
+
+    fn cidrs(values: &[&str]) -> anyhow::Result<Vec<IpCidr>> {
+        Ok(values
+            .iter()
+            .map(|value| value.parse())
+            .collect::<Result<_, _>>()?)
+    }
+
+    #[test]
+    fn test_ipv4_and_ipv6_blocks_parse_and_match() -> anyhow::Result<()> {
+        let [v4, v6, single] =
+            <[IpCidr; 3]>::try_from(cidrs(&["10.1.2.3/8", "fd00:1234::/32", "192.0.2.7"])?)
+                .map_err(|_| anyhow::anyhow!("expected three blocks"))?;
+
+        assert_eq!(v4.to_string(), "10.0.0.0/8");
+        assert!(v4.contains("10.200.0.1".parse()?));
+        assert!(v4.contains("::ffff:10.0.0.1".parse()?));
+        assert!(!v4.contains("11.0.0.1".parse()?));
+        assert!(v6.contains("fd00:1234:ab::1".parse()?));
+        assert!(!v6.contains("fd00:1235::1".parse()?));
+        assert!(single.contains("192.0.2.7".parse()?));
+        assert!(!single.contains("192.0.2.8".parse()?));
+        assert!(cidrs(&["0.0.0.0/0"])?[0].contains("203.0.113.1".parse()?));
+        assert!(cidrs(&["::/0"])?[0].contains("2001:db8::1".parse()?));
+        for invalid in [
+            "10.0.0.0/33",
+            "fd00::/129",
+            "10.0.0/8",
+            "vpn",
+            "10.0.0.0/-1",
+        ] {
+            assert!(invalid.parse::<IpCidr>().is_err(), "{invalid}");
+        }
+        Ok(())
+    }
+
+    async fn status(
+        trust_proxy: bool,
+        peer: &str,
+        headers: &[(&str, &str)],
+    ) -> anyhow::Result<StatusCode> {
+        let state = Arc::into_inner(app_state(StubGitHubApi::default(), None)?)
+            .ok_or_else(|| anyhow::anyhow!("state is shared"))?
+            .with_trust_proxy(trust_proxy)
+            .with_allowed_cidrs(cidrs(&["10.0.0.0/8", "fd00::/8"])?);
+        let mut request = Request::get("/health");
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        let mut request = request.body(Body::empty())?;
+        request
+            .extensions_mut()
+            .insert(ConnectInfo(peer.parse::<SocketAddr>()?));
+        Ok(create_router(Arc::new(state))
+            .oneshot(request)
+            .await?
+            .status())
+    }
+
+    #[tokio::test]
+    async fn test_direct_clients_are_checked_by_peer_address() -> anyhow::Result<()> {
+        assert_eq!(status(false, "10.0.0.5:5000", &[]).await?, StatusCode::OK);
+        assert_eq!(status(false, "[fd00::5]:5000", &[]).await?, StatusCode::OK);
+        assert_eq!(
+            status(false, "192.168.50.5:5000", &[]).await?,
+            StatusCode::FORBIDDEN
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_trusted_proxies_forward_the_client_address() -> anyhow::Result<()> {
+        let proxy = "192.168.1.1:5000";
+
+        assert_eq!(
+            status(
+                true,
+                proxy,
+                &[("x-forwarded-for", "192.168.50.5, 10.0.0.5")]
+            )
+            .await?,
+            StatusCode::OK
+        );
+        assert_eq!(
+            status(
+                true,
+                proxy,
+                &[(header::FORWARDED.as_str(), r#"for="[fd00::5]:4711""#)]
+            )
+            .await?,
+            StatusCode::OK
+        );
+        assert_eq!(
+            status(true, proxy, &[("x-forwarded-for", "192.168.50.5")]).await?,
+            StatusCode::FORBIDDEN
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_spoofed_left_most_forwarded_addresses_are_rejected() -> anyhow::Result<()> {
+        let proxy = "192.168.1.1:5000";
+
+        assert_eq!(
+            status(
+                true,
+                proxy,
+                &[("x-forwarded-for", "10.0.0.5, 192.168.50.5")]
+            )
+            .await?,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            status(
+                true,
+                proxy,
+                &[(header::FORWARDED.as_str(), "for=10.0.0.5, for=192.168.50.5")]
+            )
+            .await?,
+            StatusCode::FORBIDDEN
+        );
+        // An obfuscated Forwarded node does not let a spoofed X-Forwarded-For through
+        assert_eq!(
+            status(
+                true,
+                proxy,
+                &[
+                    (header::FORWARDED.as_str(), "for=unknown"),
+                    ("x-forwarded-for", "10.0.0.5")
+                ]
+            )
+            .await?,
+            StatusCode::FORBIDDEN
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_forwarding_headers_are_ignored_without_trust_proxy() -> anyhow::Result<()> {
+        let spoofed = [
+            ("x-forwarded-for", "10.0.0.5"),
+            (header::FORWARDED.as_str(), "for=10.0.0.5"),
+        ];
+
+        assert_eq!(
+            status(false, "192.168.50.5:5000", &spoofed).await?,
+            StatusCode::FORBIDDEN
+        );
+        Ok(())
+    }
+}
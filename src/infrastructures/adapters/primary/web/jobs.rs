@@ -0,0 +1,324 @@
+use super::presenter::{TimestampFormat, TimestampQuery};
+use super::{AppState, github_error_response, json_error};
+use crate::domain::models::ids::{InvalidRepoFullName, RepoFullName, RunId};
+use crate::domain::models::job::{Job, Step};
+use crate::domain::models::status::{RunConclusion, RunStatus};
+use axum::{
+    Json,
+    extract::{
+        Path, Query, State,
+        rejection::{PathRejection, QueryRejection},
+    },
+    response::{IntoResponse, Response},
+};
+use chrono::serde::ts_milliseconds_option;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Job as returned by `GET /runs/{owner}/{repo}/{id}/jobs`
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct JobDto {
+    pub name: String,
+    pub status: RunStatus,
+    pub conclusion: Option<RunConclusion>,
+    #[serde(rename = "startedAt")]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(rename = "completedAt")]
+    pub completed_at: Option<DateTime<Utc>>,
+    /// Null until the job has both started and completed
+    #[serde(rename = "durationSeconds")]
+    pub duration_seconds: Option<u64>,
+    #[serde(rename = "htmlUrl")]
+    pub html_url: String,
+    pub steps: Vec<JobStepDto>,
+}
+
+/// [`JobDto`] with epoch millisecond timestamps, served for `?ts=millis`
+#[derive(Serialize, Debug)]
+pub struct JobMillisDto<'a> {
+    name: &'a str,
+    status: RunStatus,
+    conclusion: Option<RunConclusion>,
+    #[serde(rename = "startedAt", with = "ts_milliseconds_option")]
+    started_at: Option<DateTime<Utc>>,
+    #[serde(rename = "completedAt", with = "ts_milliseconds_option")]
+    completed_at: Option<DateTime<Utc>>,
+    #[serde(rename = "durationSeconds")]
+    duration_seconds: Option<u64>,
+    #[serde(rename = "htmlUrl")]
+    html_url: &'a str,
+    steps: &'a [JobStepDto],
+}
+
+impl<'a> From<&'a JobDto> for JobMillisDto<'a> {
+    fn from(job: &'a JobDto) -> Self {
+        Self {
+            name: &job.name,
+            status: job.status,
+            conclusion: job.conclusion,
+            started_at: job.started_at,
+            completed_at: job.completed_at,
+            duration_seconds: job.duration_seconds,
+            html_url: &job.html_url,
+            steps: &job.steps,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct JobStepDto {
+    pub name: String,
+    pub conclusion: Option<RunConclusion>,
+}
+
+impl From<Job> for JobDto {
+    fn from(job: Job) -> Self {
+        let duration_seconds = job.duration().map(|duration| duration.as_secs());
+        Self {
+            name: job.name,
+            status: job.status,
+            conclusion: job.conclusion,
+            started_at: job.started_at,
+            completed_at: job.completed_at,
+            duration_seconds,
+            html_url: job.html_url,
+            steps: job.steps.into_iter().map(JobStepDto::from).collect(),
+        }
+    }
+}
+
+impl From<Step> for JobStepDto {
+    fn from(step: Step) -> Self {
+        Self {
+            name: step.name,
+            conclusion: step.conclusion,
+        }
+    }
+}
+
+/// Path parameters of `GET /runs/{owner}/{repo}/{id}/jobs`, rejected by the extractor when malformed
+#[derive(Deserialize, Debug)]
+#[serde(try_from = "RunPathSegments")]
+pub struct RunPath {
+    pub repo: RepoFullName,
+    pub run_id: RunId,
+}
+
+#[derive(Deserialize)]
+struct RunPathSegments {
+    owner: String,
+    repo: String,
+    id: RunId,
+}
+
+impl TryFrom<RunPathSegments> for RunPath {
+    type Error = InvalidRepoFullName;
+
+    fn try_from(segments: RunPathSegments) -> Result<Self, Self::Error> {
+        Ok(Self {
+            repo: RepoFullName::new(segments.owner, segments.repo)?,
+            run_id: segments.id,
+        })
+    }
+}
+
+#[tracing::instrument(name = "workflow_jobs_handler", skip(state))]
+pub async fn workflow_jobs_handler(
+    path: Result<Path<RunPath>, PathRejection>,
+    timestamps: Result<Query<TimestampQuery>, QueryRejection>,
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    let Path(RunPath { repo, run_id }) = match path {
+        Ok(path) => path,
+        Err(rejection) => return json_error(rejection.status(), &rejection.body_text()),
+    };
+    let timestamps = match timestamps {
+        Ok(Query(timestamps)) => timestamps.or(state.timestamp_format),
+        Err(rejection) => return json_error(rejection.status(), &rejection.body_text()),
+    };
+
+    match state.github_api.fetch_workflow_jobs(&repo, run_id).await {
+        Ok(jobs) => {
+            // Webhooks may have reported jobs GitHub's listing does not show yet
+            let jobs = state.poller.jobs().overlay(run_id, jobs);
+            let jobs: Vec<JobDto> = jobs.into_iter().map(JobDto::from).collect();
+            match timestamps {
+                TimestampFormat::Rfc3339 => Json(jobs).into_response(),
+                TimestampFormat::Millis => {
+                    Json(jobs.iter().map(JobMillisDto::from).collect::<Vec<_>>()).into_response()
+                }
+            }
+        }
+        Err(e) => github_error_response(&e, &format!("Workflow run {repo}#{run_id} was not found")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::external_apis::github::GitHubApiError;
+    use crate::domain::models::ids::JobId;
+    use crate::infrastructures::adapters::primary::web::create_router;
+    use crate::test_support::{StubGitHubApi, app_state};
+    use axum::body::{Body, to_bytes};
+    use axum::http::Request;
+    use axum::http::StatusCode;
+    use tower::ServiceExt;
+
+    fn job_fixture() -> anyhow::Result<Job> {
+        Ok(Job {
+            id: JobId(101),
+            run_id: RunId(42),
+            name: "build".to_string(),
+            status: RunStatus::Completed,
+            conclusion: Some(RunConclusion::Success),
+            started_at: Some(DateTime::parse_from_rfc3339("2024-08-01T10:00:00Z")?.into()),
+            completed_at: Some(DateTime::parse_from_rfc3339("2024-08-01T10:02:30Z")?.into()),
+            runner_name: Some("GitHub Actions 2".to_string()),
+            labels: vec!["ubuntu-latest".to_string()],
+            html_url: "https://github.com/owner/repo/actions/runs/42/job/101".to_string(),
+            steps: vec![Step {
+                number: 1,
+                name: "Checkout".to_string(),
+                status: RunStatus::Completed,
+                conclusion: Some(RunConclusion::Success),
+                started_at: Some(DateTime::parse_from_rfc3339("2024-08-01T10:00:01Z")?.into()),
+                completed_at: Some(DateTime::parse_from_rfc3339("2024-08-01T10:00:05Z")?.into()),
+            }],
+        })
+    }
+
+    fn router(github_api: StubGitHubApi) -> anyhow::Result<axum::Router> {
+        Ok(create_router(app_state(github_api, None)?))
+    }
+
+    #[tokio::test]
+    async fn test_jobs_are_returned_as_dtos() -> anyhow::Result<()> {
+        let github_api = StubGitHubApi {
+            jobs: Some(vec![job_fixture()?]),
+            ..StubGitHubApi::default()
+        };
+
+        let response = router(github_api)?
+            .oneshot(Request::get("/runs/owner/repo/42/jobs").body(Body::empty())?)
+            .await?;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: serde_json::Value =
+            serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await?)?;
+        assert_eq!(
+            body,
+            serde_json::json!([{
+                "name": "build",
+                "status": "completed",
+                "conclusion": "success",
+                "startedAt": "2024-08-01T10:00:00Z",
+                "completedAt": "2024-08-01T10:02:30Z",
+                "durationSeconds": 150,
+                "htmlUrl": "https://github.com/owner/repo/actions/runs/42/job/101",
+                "steps": [{"name": "Checkout", "conclusion": "success"}]
+            }])
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_millis_timestamps_are_integers() -> anyhow::Result<()> {
+        let mut queued = job_fixture()?;
+        queued.started_at = None;
+        queued.completed_at = None;
+        let github_api = StubGitHubApi {
+            jobs: Some(vec![job_fixture()?, queued]),
+            ..StubGitHubApi::default()
+        };
+
+        let response = router(github_api)?
+            .oneshot(Request::get("/runs/owner/repo/42/jobs?ts=millis").body(Body::empty())?)
+            .await?;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: serde_json::Value =
+            serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await?)?;
+        insta::assert_json_snapshot!(body);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unknown_timestamp_format_is_rejected() -> anyhow::Result<()> {
+        let response = router(StubGitHubApi::default())?
+            .oneshot(Request::get("/runs/owner/repo/42/jobs?ts=iso").body(Body::empty())?)
+            .await?;
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_github_is_service_unavailable() -> anyhow::Result<()> {
+        let github_api = StubGitHubApi {
+            error: Some(GitHubApiError::RateLimited {
+                resource: "jobs for owner/repo run 42".to_string(),
+                reset: None,
+            }),
+            ..StubGitHubApi::default()
+        };
+
+        let response = router(github_api)?
+            .oneshot(Request::get("/runs/owner/repo/42/jobs").body(Body::empty())?)
+            .await?;
+
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(
+            response
+                .headers()
+                .contains_key(axum::http::header::RETRY_AFTER)
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unknown_run_is_not_found() -> anyhow::Result<()> {
+        let response = router(StubGitHubApi::default())?
+            .oneshot(Request::get("/runs/owner/repo/999/jobs").body(Body::empty())?)
+            .await?;
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unsafe_path_segment_is_rejected() -> anyhow::Result<()> {
+        let response = router(StubGitHubApi::default())?
+            .oneshot(Request::get("/runs/owner/..%2Fadmin/42/jobs").body(Body::empty())?)
+            .await?;
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_non_numeric_run_id_is_rejected() -> anyhow::Result<()> {
+        let response = router(StubGitHubApi::default())?
+            .oneshot(Request::get("/runs/owner/repo/latest/jobs").body(Body::empty())?)
+            .await?;
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value =
+            serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await?)?;
+        assert!(body["error"].is_string());
+        Ok(())
+    }
+
+    #[test]
+    fn test_duration_is_none_while_running_and_clamped_at_zero() -> anyhow::Result<()> {
+        let mut running = job_fixture()?;
+        running.completed_at = None;
+        assert_eq!(JobDto::from(running).duration_seconds, None);
+
+        let mut skewed = job_fixture()?;
+        skewed.completed_at = Some(DateTime::parse_from_rfc3339("2024-08-01T09:59:00Z")?.into());
+        assert_eq!(JobDto::from(skewed).duration_seconds, Some(0));
+        Ok(())
+    }
+}
@@ -0,0 +1,232 @@
+use super::presenter::{TimestampQuery, latest_runs_json};
+use super::{AppState, json_error};
+use crate::application::use_cases::StreamGitHubActionsRunsUseCaseOutput;
+use crate::domain::external_apis::github::{GitHubApi, GitHubApiError};
+use crate::domain::models::ids::RepoFullName;
+use crate::domain::models::run::WorkflowRun;
+use axum::{
+    Json,
+    extract::{
+        Query, State,
+        rejection::{JsonRejection, QueryRejection},
+    },
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use futures_util::future::join_all;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// The default branch of `repo` if it is monitored and the snapshot knows it, in which case
+/// the snapshot holds its latest runs.
+fn monitored_default_branch<'a>(
+    output: &'a StreamGitHubActionsRunsUseCaseOutput,
+    repo: &RepoFullName,
+) -> Option<&'a str> {
+    output
+        .repositories
+        .iter()
+        .find(|repository| repository.full_name() == repo)?
+        .default_branch
+        .as_deref()
+}
+
+/// The latest run of `repo` on `branch` in the snapshot.
+fn cached_latest_run(
+    output: &StreamGitHubActionsRunsUseCaseOutput,
+    repo: &RepoFullName,
+    branch: &str,
+) -> Option<WorkflowRun> {
+    output
+        .runs
+        .iter()
+        .chain(&output.queued_runs)
+        .chain(&output.bot_runs)
+        .filter(|run| run.repository_name == *repo && run.head_branch.as_deref() == Some(branch))
+        .max_by_key(|run| run.created_at)
+        .cloned()
+}
+
+/// The latest run of `repo` on its default branch, asked of GitHub.
+async fn fetch_latest_run(
+    github_api: &(dyn GitHubApi + Send + Sync),
+    repo: &RepoFullName,
+) -> Result<Option<WorkflowRun>, GitHubApiError> {
+    let repository = github_api.fetch_repository(repo).await?;
+    let Some(default_branch) = repository.default_branch else {
+        return Ok(None);
+    };
+    Ok(github_api
+        .fetch_workflow_runs_on_branch(repo, &default_branch, 1)
+        .await?
+        .into_iter()
+        .max_by_key(|run| run.created_at))
+}
+
+/// Answers `POST /runs/latest`: the latest default-branch run of each repository of a JSON
+/// array of `owner/name`, from the snapshot for monitored ones and from GitHub for the rest.
+#[tracing::instrument(name = "latest_runs_handler", skip(state, repos))]
+pub async fn latest_runs_handler(
+    timestamps: Result<Query<TimestampQuery>, QueryRejection>,
+    State(state): State<Arc<AppState>>,
+    repos: Result<Json<Vec<String>>, JsonRejection>,
+) -> Response {
+    let timestamps = match timestamps {
+        Ok(Query(timestamps)) => timestamps.or(state.timestamp_format),
+        Err(rejection) => return json_error(rejection.status(), &rejection.body_text()),
+    };
+    let Json(repos) = match repos {
+        Ok(repos) => repos,
+        Err(rejection) => return json_error(rejection.status(), &rejection.body_text()),
+    };
+    let max_repos = state.request_limiter.limits().latest_runs_max_repos;
+    if repos.len() > max_repos {
+        return json_error(
+            StatusCode::BAD_REQUEST,
+            &format!("At most {max_repos} repositories may be requested at once"),
+        );
+    }
+
+    let mut runs = BTreeMap::new();
+    let mut errors = BTreeMap::new();
+    let mut uncached = Vec::new();
+    let snapshot = state.snapshots.latest();
+    for requested in repos {
+        if runs.contains_key(&requested) || errors.contains_key(&requested) {
+            continue;
+        }
+        match requested.parse::<RepoFullName>() {
+            Ok(repo) => match snapshot.as_deref().and_then(|output| {
+                monitored_default_branch(output, &repo).map(|branch| (output, branch))
+            }) {
+                Some((output, branch)) => {
+                    let run = cached_latest_run(output, &repo, branch);
+                    runs.insert(requested, run);
+                }
+                None => uncached.push((requested, repo)),
+            },
+            Err(e) => {
+                errors.insert(requested, e.to_string());
+            }
+        }
+    }
+
+    // Issued together; the adapter's concurrency limit still applies to each
+    let fetched = join_all(
+        uncached
+            .iter()
+            .map(|(_, repo)| fetch_latest_run(state.github_api.as_ref(), repo)),
+    )
+    .await;
+    for ((requested, _), result) in uncached.into_iter().zip(fetched) {
+        match result {
+            Ok(run) => {
+                runs.insert(requested, run);
+            }
+            Err(e) => {
+                errors.insert(requested, e.to_string());
+            }
+        }
+    }
+
+    match latest_runs_json(&runs, &errors, timestamps) {
+        Ok(body) => ([(header::CONTENT_TYPE, "application/json")], body).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to serialize latest runs: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::external_apis::github::Repository;
+    use crate::infrastructures::adapters::primary::web::create_router;
+    use crate::infrastructures::adapters::primary::web::limits::RequestLimits;
+    use crate::test_support::{StubGitHubApi, app_state, base_time, repo_full_name, workflow_run};
+    use axum::body::{Body, to_bytes};
+    use axum::http::Request;
+    use chrono::TimeDelta;
+    use tower::ServiceExt;
+
+    fn repository(full_name: &str) -> Repository {
+        Repository::from_full_name(&repo_full_name(full_name))
+            .with_default_branch(Some("main".to_string()))
+    }
+
+    async fn post_json(
+        state: Arc<AppState>,
+        body: &str,
+    ) -> anyhow::Result<(StatusCode, serde_json::Value)> {
+        let response = create_router(state)
+            .oneshot(
+                Request::post("/runs/latest")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(body.to_string()))?,
+            )
+            .await?;
+        let status = response.status();
+        let body = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await?)?;
+        Ok((status, body))
+    }
+
+    #[tokio::test]
+    async fn test_latest_runs_come_from_the_snapshot_then_github() -> anyhow::Result<()> {
+        let mut older = workflow_run("owner/cached", 1, "success");
+        older.created_at = base_time() - TimeDelta::hours(1);
+        let mut feature = workflow_run("owner/cached", 3, "failure");
+        feature.head_branch = Some("feature".to_string());
+        let stub = StubGitHubApi {
+            repositories: vec![repository("owner/fetched")],
+            // Ignored for the monitored repository, which the snapshot answers for
+            runs: vec![
+                workflow_run("owner/fetched", 10, "failure"),
+                workflow_run("owner/cached", 11, "failure"),
+            ],
+            ..StubGitHubApi::default()
+        };
+        let state = app_state(stub, None)?;
+        state.poller.publish(
+            StreamGitHubActionsRunsUseCaseOutput::new(
+                vec![older, workflow_run("owner/cached", 2, "success"), feature],
+                base_time(),
+            )
+            .with_repositories(vec![repository("owner/cached"), repository("owner/idle")]),
+        );
+
+        let (status, body) = post_json(
+            state,
+            r#"["owner/cached", "owner/idle", "owner/fetched", "owner/missing", "nope"]"#,
+        )
+        .await?;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["runs"]["owner/cached"]["id"], 2);
+        assert!(body["runs"]["owner/idle"].is_null());
+        assert_eq!(body["runs"]["owner/fetched"]["id"], 10);
+        assert_eq!(body["runs"].as_object().map(serde_json::Map::len), Some(3));
+        assert!(body["errors"]["owner/missing"].is_string());
+        assert!(body["errors"]["nope"].is_string());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_too_many_repositories_are_rejected() -> anyhow::Result<()> {
+        let state = Arc::into_inner(app_state(StubGitHubApi::default(), None)?)
+            .ok_or_else(|| anyhow::anyhow!("state is shared"))?
+            .with_request_limits(RequestLimits {
+                latest_runs_max_repos: 1,
+                ..RequestLimits::default()
+            });
+
+        let (status, body) = post_json(Arc::new(state), r#"["owner/a", "owner/b"]"#).await?;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body["error"],
+            "At most 1 repositories may be requested at once"
+        );
+        Ok(())
+    }
+}
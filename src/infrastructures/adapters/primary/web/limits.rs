@@ -0,0 +1,254 @@
+use super::heartbeat::DEFAULT_WEBSOCKET_HEARTBEAT_INTERVAL;
+use super::pins::DEFAULT_WEBSOCKET_MAX_PINNED_RUNS;
+use super::rate_limit::{retry_after, route_template};
+use super::{AppState, json_error};
+use crate::application::services::log_tail::{
+    DEFAULT_LOG_TAIL_CHUNK_BYTES, DEFAULT_LOG_TAIL_MAX_BYTES,
+};
+use axum::{
+    body::Body,
+    extract::State,
+    http::{Request, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+/// How long handlers of in-memory routes such as `/runs` may take unless configured otherwise
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long handlers calling GitHub or the history may take unless configured otherwise
+pub const DEFAULT_SLOW_REQUEST_TIMEOUT: Duration = Duration::from_mins(1);
+
+/// Requests handled at once unless configured otherwise
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 256;
+
+/// Largest message a websocket client may send unless configured otherwise (bytes)
+pub const DEFAULT_WEBSOCKET_MAX_MESSAGE_BYTES: usize = 64 * 1024;
+
+/// Largest frame a websocket client may send unless configured otherwise (bytes)
+pub const DEFAULT_WEBSOCKET_MAX_FRAME_BYTES: usize = 16 * 1024;
+
+/// Repositories one `POST /runs/latest` request may name unless configured otherwise
+pub const DEFAULT_LATEST_RUNS_MAX_REPOS: usize = 50;
+
+/// Templates of the routes that call GitHub or read the history rather than answer from memory
+pub const ON_DEMAND_ROUTES: [&str; 17] = [
+    "/history",
+    "/snapshot",
+    "/snapshots",
+    "/search/runs",
+    "/compare",
+    "/trends",
+    "/costs",
+    "/slos",
+    "/digest",
+    "/runs/latest",
+    "/grafana/query",
+    "/graphql",
+    "/admin/compact",
+    "/runs/{owner}/{repo}/{id}/jobs",
+    "/repos/{owner}/{repo}/workflows",
+    "/repos/{owner}/{repo}/workflows/{file}/inputs",
+    "/badge/{owner}/{file}",
+];
+
+/// How long a shed client is asked to wait before retrying
+const SHED_RETRY_AFTER: Duration = Duration::from_secs(1);
+
+/// Bounds on how long handlers run and how many run at once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestLimits {
+    /// For routes answered from memory, such as `/runs` and `/status`
+    pub timeout: Duration,
+    /// For routes that call GitHub or read the history, such as `/history` and job details
+    pub slow_timeout: Duration,
+    /// Requests beyond this many in flight get 503 instead of waiting
+    pub max_concurrent: usize,
+    /// Websocket clients sending a larger message are disconnected
+    pub websocket_max_message_bytes: usize,
+    /// Websocket clients sending a larger frame are disconnected
+    pub websocket_max_frame_bytes: usize,
+    /// Time between two heartbeats sent to a websocket client
+    pub websocket_heartbeat_interval: Duration,
+    /// Runs one websocket client may pin at once
+    pub websocket_max_pinned_runs: usize,
+    /// Largest `log_chunk` frame of a websocket log tail, in bytes of log
+    pub log_tail_chunk_bytes: usize,
+    /// Bytes of log a websocket log tail sends before it ends
+    pub log_tail_max_bytes: usize,
+    /// Repositories one `POST /runs/latest` request may name
+    pub latest_runs_max_repos: usize,
+}
+
+impl Default for RequestLimits {
+    fn default() -> Self {
+        Self {
+            timeout: DEFAULT_REQUEST_TIMEOUT,
+            slow_timeout: DEFAULT_SLOW_REQUEST_TIMEOUT,
+            max_concurrent: DEFAULT_MAX_CONCURRENT_REQUESTS,
+            websocket_max_message_bytes: DEFAULT_WEBSOCKET_MAX_MESSAGE_BYTES,
+            websocket_max_frame_bytes: DEFAULT_WEBSOCKET_MAX_FRAME_BYTES,
+            websocket_heartbeat_interval: DEFAULT_WEBSOCKET_HEARTBEAT_INTERVAL,
+            websocket_max_pinned_runs: DEFAULT_WEBSOCKET_MAX_PINNED_RUNS,
+            log_tail_chunk_bytes: DEFAULT_LOG_TAIL_CHUNK_BYTES,
+            log_tail_max_bytes: DEFAULT_LOG_TAIL_MAX_BYTES,
+            latest_runs_max_repos: DEFAULT_LATEST_RUNS_MAX_REPOS,
+        }
+    }
+}
+
+/// Enforces [`RequestLimits`], counting the requests in flight.
+#[derive(Debug)]
+pub struct RequestLimiter {
+    limits: RequestLimits,
+    in_flight: Semaphore,
+}
+
+impl Default for RequestLimiter {
+    fn default() -> Self {
+        Self::new(RequestLimits::default())
+    }
+}
+
+impl RequestLimiter {
+    #[must_use]
+    pub fn new(limits: RequestLimits) -> Self {
+        Self {
+            limits,
+            in_flight: Semaphore::new(limits.max_concurrent),
+        }
+    }
+
+    #[must_use]
+    pub fn limits(&self) -> RequestLimits {
+        self.limits
+    }
+
+    /// The time the handler of a route template may take, or `None` for routes never cut off.
+    fn timeout(&self, route: &str) -> Option<Duration> {
+        match route {
+            // Their handlers return at once and hand the connection over to a stream
+            "/ws" | "/sse" => None,
+            route if ON_DEMAND_ROUTES.contains(&route) => Some(self.limits.slow_timeout),
+            _ => Some(self.limits.timeout),
+        }
+    }
+}
+
+/// Middleware answering 504 when a handler outlives the timeout of its route.
+pub async fn timeout_middleware(
+    State(state): State<Arc<AppState>>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let route = route_template(&request);
+    let Some(timeout) = state.request_limiter.timeout(&route) else {
+        return next.run(request).await;
+    };
+    let Ok(response) = tokio::time::timeout(timeout, next.run(request)).await else {
+        tracing::warn!("Request to {} timed out after {:?}", route, timeout);
+        return json_error(StatusCode::GATEWAY_TIMEOUT, "Request timed out");
+    };
+    response
+}
+
+/// Middleware answering 503 instead of queueing once too many requests are in flight.
+///
+/// Health probes are never shed, so a busy instance is not mistaken for a dead one.
+pub async fn load_shed_middleware(
+    State(state): State<Arc<AppState>>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let route = route_template(&request);
+    if matches!(route.as_str(), "/health" | "/ready") {
+        return next.run(request).await;
+    }
+    let Ok(_permit) = state.request_limiter.in_flight.try_acquire() else {
+        tracing::warn!("Shed request to {}, too many requests in flight", route);
+        return retry_after(
+            StatusCode::SERVICE_UNAVAILABLE,
+            SHED_RETRY_AFTER,
+            "Server is busy",
+        );
+    };
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructures::adapters::primary::web::create_router;
+    use crate::test_support::{StubGitHubApi, app_state};
+    use axum::body::to_bytes;
+    use tower::ServiceExt;
+
+    fn router(limits: RequestLimits) -> anyhow::Result<axum::Router> {
+        let state = Arc::into_inner(app_state(
+            StubGitHubApi {
+                delay: Duration::from_hours(1),
+                ..StubGitHubApi::default()
+            },
+            None,
+        )?)
+        .ok_or_else(|| anyhow::anyhow!("state is shared"))?
+        .with_request_limits(limits);
+        Ok(create_router(Arc::new(state)))
+    }
+
+    fn workflows() -> anyhow::Result<Request<Body>> {
+        Ok(Request::get("/repos/owner/repo/workflows").body(Body::empty())?)
+    }
+
+    #[tokio::test]
+    async fn test_slow_handlers_time_out_with_504() -> anyhow::Result<()> {
+        let router = router(RequestLimits {
+            timeout: Duration::from_millis(10),
+            slow_timeout: Duration::from_millis(200),
+            ..RequestLimits::default()
+        })?;
+
+        let started = tokio::time::Instant::now();
+        let response = router.clone().oneshot(workflows()?).await?;
+
+        assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+        // Detail routes get the longer timeout
+        assert!(started.elapsed() >= Duration::from_millis(200));
+        let body: serde_json::Value =
+            serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await?)?;
+        assert_eq!(body["error"], "Request timed out");
+        let snapshot = router
+            .oneshot(Request::get("/runs").body(Body::empty())?)
+            .await?;
+        assert_ne!(snapshot.status(), StatusCode::GATEWAY_TIMEOUT);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_requests_beyond_the_concurrency_limit_are_shed() -> anyhow::Result<()> {
+        let router = router(RequestLimits {
+            max_concurrent: 1,
+            ..RequestLimits::default()
+        })?;
+        let pending = tokio::spawn(router.clone().oneshot(workflows()?));
+        // Let the first request take the only permit
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let shed = router.clone().oneshot(workflows()?).await?;
+        let health = router
+            .oneshot(Request::get("/health").body(Body::empty())?)
+            .await?;
+        pending.abort();
+
+        assert_eq!(shed.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(
+            shed.headers().get(axum::http::header::RETRY_AFTER),
+            Some(&axum::http::HeaderValue::from_static("1"))
+        );
+        assert_eq!(health.status(), StatusCode::OK);
+        Ok(())
+    }
+}
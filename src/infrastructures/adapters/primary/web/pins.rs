@@ -0,0 +1,239 @@
+use super::presenter::json_patch::SnapshotPatcher;
+use super::presenter::projection::RunProjection;
+use crate::application::services::{PinnedRun, PollerEvent, SequencedEvent, SharedPoller};
+use crate::application::use_cases::StreamGitHubActionsRunsUseCaseOutput;
+use crate::application::use_cases::stream_github_actions_runs::config::API_CALL_BUDGET_PER_HOUR;
+use crate::domain::models::run::WorkflowRun;
+use crate::domain::models::status::RunStatus;
+use std::sync::Arc;
+
+/// Runs a websocket client may pin at once unless configured otherwise
+pub const DEFAULT_WEBSOCKET_MAX_PINNED_RUNS: usize = 10;
+
+/// A run one client pinned.
+#[derive(Debug)]
+struct Pin {
+    run: PinnedRun,
+    /// Whether the client was told the run completed; cleared when it runs again
+    completion_sent: bool,
+}
+
+/// The runs one websocket client pinned: sent first in its snapshots, marked `pinned`,
+/// and announced once they complete.
+///
+/// Pins are registered with the poller, which fetches the runs too old to be listed, and
+/// taken back when the connection ends.
+pub struct ConnectionPins {
+    poller: Arc<SharedPoller>,
+    max: usize,
+    /// In the order they were pinned
+    pins: Vec<Pin>,
+    /// Set when pins changed since the projection last marked them
+    changed: bool,
+}
+
+impl ConnectionPins {
+    #[must_use]
+    pub fn new(poller: Arc<SharedPoller>, max: usize) -> Self {
+        Self {
+            poller,
+            max,
+            pins: Vec::new(),
+            changed: false,
+        }
+    }
+
+    /// Pins `run`; pinning it again changes nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns the message for the client when it already pinned as many runs as it may, or
+    /// when fetching the run would take the poller over its GitHub API call budget.
+    pub fn pin(&mut self, run: PinnedRun) -> Result<(), String> {
+        if self.pins.iter().any(|pin| pin.run == run) {
+            return Ok(());
+        }
+        if self.pins.len() >= self.max {
+            return Err(format!(
+                "At most {} runs can be pinned, unpin one first",
+                self.max
+            ));
+        }
+        self.poller.pin_run(run.clone()).map_err(|api_calls_per_hour| {
+            format!(
+                "Pinning run {} would make {api_calls_per_hour} GitHub API calls per hour, exceeding the budget of {API_CALL_BUDGET_PER_HOUR}",
+                run.run_id
+            )
+        })?;
+        tracing::info!("Client pins run {} of {}", run.run_id, run.repo);
+        self.pins.push(Pin {
+            run,
+            completion_sent: false,
+        });
+        self.changed = true;
+        Ok(())
+    }
+
+    /// Unpins `run`; no-op for runs that are not pinned.
+    pub fn unpin(&mut self, run: &PinnedRun) {
+        let before = self.pins.len();
+        self.pins.retain(|pin| pin.run != *run);
+        if self.pins.len() < before {
+            self.poller.pinned_runs().unpin(run);
+            self.changed = true;
+        }
+    }
+
+    /// Marks the pinned runs in `projection`, and in that of `patcher`, when pins changed.
+    pub fn mark(&mut self, projection: &mut RunProjection, patcher: Option<&mut SnapshotPatcher>) {
+        if !std::mem::take(&mut self.changed) {
+            return;
+        }
+        projection.set_pinned(self.pins.iter().map(|pin| pin.run.clone()).collect());
+        if let Some(patcher) = patcher {
+            patcher.set_projection(projection.clone());
+        }
+    }
+
+    /// `event` with the pinned runs first among the runs of a snapshot, in the order they
+    /// were pinned, and the pinned runs that completed since the previous snapshot.
+    ///
+    /// A pinned run shown is taken from the runs, which webhooks update, and from those the
+    /// poller fetched on their own otherwise.
+    pub fn apply(&mut self, event: SequencedEvent) -> (SequencedEvent, Vec<WorkflowRun>) {
+        let PollerEvent::Snapshot(output) = &event.event else {
+            return (event, Vec::new());
+        };
+        if self.pins.is_empty() {
+            return (event, Vec::new());
+        }
+        let latest = |pin: &PinnedRun| {
+            output
+                .runs
+                .iter()
+                .chain(&output.pinned_runs)
+                .find(|run| run.repository_name == pin.repo && run.id == pin.run_id)
+        };
+        let mut pinned = Vec::new();
+        let mut completed = Vec::new();
+        for pin in &mut self.pins {
+            let Some(run) = latest(&pin.run) else {
+                continue;
+            };
+            let is_completed = run.status == RunStatus::Completed;
+            if is_completed && !pin.completion_sent {
+                completed.push(run.clone());
+            }
+            pin.completion_sent = is_completed;
+            pinned.push(run.clone());
+        }
+        let mut runs = pinned;
+        runs.extend(
+            output
+                .runs
+                .iter()
+                .filter(|run| {
+                    !self
+                        .pins
+                        .iter()
+                        .any(|pin| run.repository_name == pin.run.repo && run.id == pin.run.run_id)
+                })
+                .cloned(),
+        );
+        let output = StreamGitHubActionsRunsUseCaseOutput {
+            runs,
+            ..StreamGitHubActionsRunsUseCaseOutput::clone(output)
+        };
+        let event = SequencedEvent {
+            seq: event.seq,
+            event: PollerEvent::Snapshot(Arc::new(output)),
+        };
+        (event, completed)
+    }
+}
+
+impl Drop for ConnectionPins {
+    fn drop(&mut self) {
+        for pin in &self.pins {
+            self.poller.pinned_runs().unpin(&pin.run);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::ids::RunId;
+    use crate::test_support::{base_time, repo_full_name, workflow_run};
+    use tokio::sync::Notify;
+
+    fn poller() -> Arc<SharedPoller> {
+        Arc::new(SharedPoller::new(Arc::new(Notify::new())))
+    }
+
+    fn pinned(run_id: u64) -> PinnedRun {
+        PinnedRun {
+            repo: repo_full_name("owner/repo"),
+            run_id: RunId(run_id),
+        }
+    }
+
+    fn snapshot(runs: Vec<WorkflowRun>, pinned_runs: Vec<WorkflowRun>) -> SequencedEvent {
+        SequencedEvent {
+            seq: 1,
+            event: PollerEvent::Snapshot(Arc::new(
+                StreamGitHubActionsRunsUseCaseOutput::new(runs, base_time())
+                    .with_pinned_runs(pinned_runs),
+            )),
+        }
+    }
+
+    fn run_ids(event: &SequencedEvent) -> Vec<RunId> {
+        match &event.event {
+            PollerEvent::Snapshot(output) => output.runs.iter().map(|run| run.id).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_pins_are_capped_and_taken_back_with_the_connection() {
+        let poller = poller();
+        let mut pins = ConnectionPins::new(Arc::clone(&poller), 2);
+
+        assert_eq!(pins.pin(pinned(1)), Ok(()));
+        assert_eq!(pins.pin(pinned(1)), Ok(()));
+        assert_eq!(pins.pin(pinned(2)), Ok(()));
+        assert!(pins.pin(pinned(3)).is_err());
+        pins.unpin(&pinned(2));
+        assert_eq!(pins.pin(pinned(3)), Ok(()));
+        assert_eq!(poller.pinned_runs().runs(), [pinned(1), pinned(3)]);
+
+        drop(pins);
+        assert!(poller.pinned_runs().runs().is_empty());
+    }
+
+    #[test]
+    fn test_pinned_runs_come_first_and_complete_once() {
+        let mut pins = ConnectionPins::new(poller(), 10);
+        pins.pin(pinned(1)).ok();
+        let old_run = |status| workflow_run("owner/repo", 1, status);
+
+        let (event, completed) = pins.apply(snapshot(
+            vec![workflow_run("owner/repo", 2, "success")],
+            vec![old_run("in_progress")],
+        ));
+        assert_eq!(run_ids(&event), [RunId(1), RunId(2)]);
+        assert!(completed.is_empty());
+
+        let done = || {
+            snapshot(
+                vec![workflow_run("owner/repo", 2, "success")],
+                vec![old_run("failure")],
+            )
+        };
+        let (_, completed) = pins.apply(done());
+        assert_eq!(completed, [old_run("failure")]);
+        let (_, completed) = pins.apply(done());
+        assert!(completed.is_empty());
+    }
+}
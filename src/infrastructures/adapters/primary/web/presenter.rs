@@ -0,0 +1,1098 @@
+pub mod anonymizer;
+pub mod json_patch;
+pub mod locale;
+pub mod projection;
+
+use crate::application::services::{DeploymentBoard, RunnerFleet};
+use crate::application::use_cases::StreamGitHubActionsRunsUseCaseOutput;
+use crate::application::use_cases::stream_github_actions_runs::summary::{
+    RunSummary, StatusCounts, SummaryStatus, summarize,
+};
+use crate::domain::external_apis::github::Repository;
+use crate::domain::models::actor::RunActor;
+use crate::domain::models::bot_group::BotRunGroup;
+use crate::domain::models::commit_status::{CheckSource, CommitStatusState, ExternalCheck};
+use crate::domain::models::deployment::{DeploymentState, DeploymentStatusState};
+use crate::domain::models::ids::{RepoFullName, RunId, WorkflowId};
+use crate::domain::models::queue::RepositoryQueue;
+use crate::domain::models::run::WorkflowRun;
+use crate::domain::models::runner::RepositoryRunners;
+use crate::domain::models::schedule::UpcomingRun;
+use crate::domain::models::status::{RunConclusion, RunStatus};
+use crate::domain::repositories::{HistoricalSnapshot, SnapshotRange, SnapshotStatusCounts};
+use chrono::serde::{ts_milliseconds, ts_milliseconds_option};
+use chrono::{DateTime, TimeDelta, Utc};
+use projection::RunProjection;
+use schemars::{JsonSchema, Schema};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// How timestamps are written in JSON responses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimestampFormat {
+    /// RFC 3339 strings such as `2024-08-01T10:00:00Z`
+    #[default]
+    Rfc3339,
+    /// Integer milliseconds since the Unix epoch
+    Millis,
+}
+
+impl FromStr for TimestampFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "rfc3339" => Ok(Self::Rfc3339),
+            "millis" => Ok(Self::Millis),
+            _ => anyhow::bail!("Invalid timestamp format {value:?}, expected rfc3339 or millis"),
+        }
+    }
+}
+
+/// `?ts=` override of the deployment's [`TimestampFormat`].
+#[derive(Deserialize, Debug, Default)]
+pub struct TimestampQuery {
+    ts: Option<TimestampFormat>,
+}
+
+impl TimestampQuery {
+    #[must_use]
+    pub fn or(&self, default: TimestampFormat) -> TimestampFormat {
+        self.ts.unwrap_or(default)
+    }
+}
+
+/// [`WorkflowRun`] with epoch millisecond timestamps; keep in sync with its serialized form.
+#[derive(Serialize, JsonSchema)]
+#[schemars(rename = "WorkflowRun")]
+pub struct WorkflowRunMillis<'a> {
+    #[serde(rename = "repositoryName")]
+    repository_name: &'a RepoFullName,
+    id: RunId,
+    #[serde(rename = "runNumber")]
+    run_number: u64,
+    #[serde(rename = "workflowId")]
+    workflow_id: WorkflowId,
+    #[serde(rename = "workflowName")]
+    workflow_name: &'a str,
+    #[serde(rename = "displayTitle")]
+    display_title: &'a str,
+    #[serde(rename = "fullDisplayTitle")]
+    full_display_title: Option<&'a str>,
+    event: &'a str,
+    #[serde(rename = "headBranch")]
+    head_branch: Option<&'a str>,
+    #[serde(rename = "headSha")]
+    head_sha: &'a str,
+    actor: &'a RunActor,
+    #[serde(rename = "triggeringActor")]
+    triggering_actor: Option<&'a RunActor>,
+    status: RunStatus,
+    conclusion: Option<RunConclusion>,
+    #[serde(rename = "displayStatus")]
+    display_status: &'a str,
+    #[serde(rename = "durationSeconds")]
+    duration_seconds: Option<u64>,
+    #[serde(rename = "createdAt", with = "ts_milliseconds")]
+    #[schemars(with = "i64")]
+    created_at: DateTime<Utc>,
+    #[serde(rename = "updatedAt", with = "ts_milliseconds")]
+    #[schemars(with = "i64")]
+    updated_at: DateTime<Utc>,
+    #[serde(rename = "htmlUrl")]
+    html_url: &'a str,
+    labels: &'a [String],
+    #[serde(rename = "repositoryActive")]
+    repository_active: bool,
+    #[serde(rename = "isRequiredCheck", skip_serializing_if = "Option::is_none")]
+    is_required_check: Option<bool>,
+}
+
+impl<'a> From<&'a WorkflowRun> for WorkflowRunMillis<'a> {
+    fn from(run: &'a WorkflowRun) -> Self {
+        Self {
+            repository_name: &run.repository_name,
+            id: run.id,
+            run_number: run.run_number,
+            workflow_id: run.workflow_id,
+            workflow_name: &run.workflow_name,
+            display_title: &run.display_title,
+            full_display_title: run.full_display_title.as_deref(),
+            event: &run.event,
+            head_branch: run.head_branch.as_deref(),
+            head_sha: &run.head_sha,
+            actor: &run.actor,
+            triggering_actor: run.triggering_actor.as_ref(),
+            status: run.status,
+            conclusion: run.conclusion,
+            display_status: run.display_status(),
+            duration_seconds: run.duration_seconds(),
+            created_at: run.created_at,
+            updated_at: run.updated_at,
+            html_url: &run.html_url,
+            labels: &run.labels,
+            repository_active: run.repository_active,
+            is_required_check: run.is_required_check,
+        }
+    }
+}
+
+/// [`UpcomingRun`] with epoch millisecond timestamps.
+#[derive(Serialize, JsonSchema)]
+#[schemars(rename = "UpcomingRun")]
+pub struct UpcomingRunMillis<'a> {
+    #[serde(rename = "repositoryName")]
+    repository_name: &'a RepoFullName,
+    #[serde(rename = "workflowId")]
+    workflow_id: WorkflowId,
+    #[serde(rename = "workflowName")]
+    workflow_name: &'a str,
+    #[serde(rename = "scheduledAt", with = "ts_milliseconds")]
+    #[schemars(with = "i64")]
+    scheduled_at: DateTime<Utc>,
+}
+
+impl<'a> From<&'a UpcomingRun> for UpcomingRunMillis<'a> {
+    fn from(run: &'a UpcomingRun) -> Self {
+        Self {
+            repository_name: &run.repository_name,
+            workflow_id: run.workflow_id,
+            workflow_name: &run.workflow_name,
+            scheduled_at: run.scheduled_at,
+        }
+    }
+}
+
+/// [`ExternalCheck`] with epoch millisecond timestamps.
+#[derive(Serialize, JsonSchema)]
+#[schemars(rename = "ExternalCheck")]
+pub struct ExternalCheckMillis<'a> {
+    #[serde(rename = "repositoryName")]
+    repository_name: &'a RepoFullName,
+    #[serde(rename = "headSha")]
+    head_sha: &'a str,
+    source: CheckSource,
+    context: &'a str,
+    state: CommitStatusState,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<&'a str>,
+    #[serde(rename = "targetUrl")]
+    target_url: Option<&'a str>,
+    #[serde(rename = "createdAt", with = "ts_milliseconds")]
+    #[schemars(with = "i64")]
+    created_at: DateTime<Utc>,
+    #[serde(rename = "updatedAt", with = "ts_milliseconds")]
+    #[schemars(with = "i64")]
+    updated_at: DateTime<Utc>,
+}
+
+impl<'a> From<&'a ExternalCheck> for ExternalCheckMillis<'a> {
+    fn from(check: &'a ExternalCheck) -> Self {
+        Self {
+            repository_name: &check.repository_name,
+            head_sha: &check.head_sha,
+            source: check.source,
+            context: &check.context,
+            state: check.state,
+            description: check.description.as_deref(),
+            target_url: check.target_url.as_deref(),
+            created_at: check.created_at,
+            updated_at: check.updated_at,
+        }
+    }
+}
+
+/// [`BotRunGroup`] with epoch millisecond timestamps.
+#[derive(Serialize, JsonSchema)]
+#[schemars(rename = "BotRunGroup")]
+pub struct BotRunGroupMillis<'a> {
+    #[serde(rename = "repositoryName")]
+    repository_name: &'a RepoFullName,
+    count: u64,
+    failing: u64,
+    #[serde(rename = "newestAt", with = "ts_milliseconds")]
+    #[schemars(with = "i64")]
+    newest_at: DateTime<Utc>,
+    #[serde(rename = "runIds")]
+    run_ids: &'a [RunId],
+}
+
+impl<'a> From<&'a BotRunGroup> for BotRunGroupMillis<'a> {
+    fn from(group: &'a BotRunGroup) -> Self {
+        Self {
+            repository_name: &group.repository_name,
+            count: group.count,
+            failing: group.failing,
+            newest_at: group.newest_at,
+            run_ids: &group.run_ids,
+        }
+    }
+}
+
+/// [`RunSummary`] with epoch millisecond timestamps.
+#[derive(Serialize, JsonSchema)]
+#[schemars(rename = "RunSummary")]
+pub struct RunSummaryMillis {
+    counts: StatusCounts,
+    overall: Option<SummaryStatus>,
+    #[serde(rename = "longestFailing")]
+    longest_failing: Option<FailingRepositoryMillis>,
+    #[serde(rename = "lastChangedAt", with = "ts_milliseconds_option")]
+    #[schemars(with = "Option<i64>")]
+    last_changed_at: Option<DateTime<Utc>>,
+}
+
+/// [`FailingRepository`] with epoch millisecond timestamps.
+///
+/// [`FailingRepository`]: crate::application::use_cases::stream_github_actions_runs::summary::FailingRepository
+#[derive(Serialize, JsonSchema)]
+#[schemars(rename = "FailingRepository")]
+pub struct FailingRepositoryMillis {
+    #[serde(rename = "repositoryName")]
+    repository_name: RepoFullName,
+    #[serde(rename = "failingSince", with = "ts_milliseconds")]
+    #[schemars(with = "i64")]
+    failing_since: DateTime<Utc>,
+}
+
+impl From<RunSummary> for RunSummaryMillis {
+    fn from(summary: RunSummary) -> Self {
+        Self {
+            counts: summary.counts,
+            overall: summary.overall,
+            longest_failing: summary
+                .longest_failing
+                .map(|failing| FailingRepositoryMillis {
+                    repository_name: failing.repository_name,
+                    failing_since: failing.failing_since,
+                }),
+            last_changed_at: summary.last_changed_at,
+        }
+    }
+}
+
+/// A [`RunSummary`] serialized with either timestamp format.
+#[derive(Serialize)]
+#[serde(untagged)]
+enum SummaryView {
+    Rfc3339(RunSummary),
+    Millis(RunSummaryMillis),
+}
+
+impl SummaryView {
+    fn new(summary: RunSummary, format: TimestampFormat) -> Self {
+        match format {
+            TimestampFormat::Rfc3339 => Self::Rfc3339(summary),
+            TimestampFormat::Millis => Self::Millis(RunSummaryMillis::from(summary)),
+        }
+    }
+}
+
+/// [`StreamGitHubActionsRunsUseCaseOutput`] with the [`RunSummary`] of the runs sent.
+#[derive(Serialize, JsonSchema)]
+#[schemars(rename = "StreamGitHubActionsRunsUseCaseOutput")]
+pub struct SummarizedSnapshot<'a> {
+    #[serde(flatten)]
+    output: &'a StreamGitHubActionsRunsUseCaseOutput,
+    summary: RunSummary,
+}
+
+/// [`StreamGitHubActionsRunsUseCaseOutput`] with epoch millisecond timestamps.
+#[derive(Serialize, JsonSchema)]
+#[schemars(rename = "StreamGitHubActionsRunsUseCaseOutput")]
+pub struct SnapshotMillis<'a> {
+    #[serde(rename = "schemaVersion")]
+    schema_version: u32,
+    runs: Vec<WorkflowRunMillis<'a>>,
+    #[serde(rename = "generatedAt", with = "ts_milliseconds")]
+    #[schemars(with = "i64")]
+    generated_at: DateTime<Utc>,
+    queue: &'a [RepositoryQueue],
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    upcoming: Vec<UpcomingRunMillis<'a>>,
+    #[serde(
+        rename = "externalChecks",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    external_checks: Vec<ExternalCheckMillis<'a>>,
+    #[serde(rename = "botGroups", default, skip_serializing_if = "Vec::is_empty")]
+    bot_groups: Vec<BotRunGroupMillis<'a>>,
+    summary: RunSummaryMillis,
+}
+
+impl<'a> SnapshotMillis<'a> {
+    fn new(output: &'a StreamGitHubActionsRunsUseCaseOutput, summary: RunSummary) -> Self {
+        Self {
+            schema_version: output.schema_version,
+            runs: output.runs.iter().map(WorkflowRunMillis::from).collect(),
+            generated_at: output.generated_at,
+            queue: &output.queue,
+            upcoming: output
+                .upcoming
+                .iter()
+                .map(UpcomingRunMillis::from)
+                .collect(),
+            external_checks: output
+                .external_checks
+                .iter()
+                .map(ExternalCheckMillis::from)
+                .collect(),
+            bot_groups: output
+                .bot_groups
+                .iter()
+                .map(BotRunGroupMillis::from)
+                .collect(),
+            summary: RunSummaryMillis::from(summary),
+        }
+    }
+}
+
+/// Serializes a snapshot with timestamps in `format`, summarizing all of its runs.
+///
+/// # Errors
+///
+/// Returns an error if serialization fails.
+pub fn snapshot_json(
+    output: &StreamGitHubActionsRunsUseCaseOutput,
+    format: TimestampFormat,
+) -> serde_json::Result<String> {
+    serde_json::to_string(&SnapshotView::new(
+        output,
+        format,
+        &RunProjection::default(),
+    ))
+}
+
+/// Serializes a snapshot with timestamps in `format`, sending and summarizing the runs of
+/// `projection`.
+///
+/// # Errors
+///
+/// Returns an error if serialization fails.
+pub fn projected_snapshot_json(
+    output: &StreamGitHubActionsRunsUseCaseOutput,
+    format: TimestampFormat,
+    projection: &RunProjection,
+) -> serde_json::Result<String> {
+    serde_json::to_string(&SnapshotView::projected(output, format, projection)?)
+}
+
+/// A snapshot serialized with either timestamp format.
+#[derive(Serialize)]
+#[serde(untagged)]
+enum SnapshotView<'a> {
+    Rfc3339(SummarizedSnapshot<'a>),
+    Millis(Box<SnapshotMillis<'a>>),
+    /// Serialized already, with only some runs or fields
+    Projected(serde_json::Value),
+}
+
+impl<'a> SnapshotView<'a> {
+    /// Every run of `output`, with the summary of those `projection` sends.
+    fn new(
+        output: &'a StreamGitHubActionsRunsUseCaseOutput,
+        format: TimestampFormat,
+        projection: &RunProjection,
+    ) -> Self {
+        let summary = summarize(projection.runs(&output.runs));
+        match format {
+            TimestampFormat::Rfc3339 => Self::Rfc3339(SummarizedSnapshot { output, summary }),
+            TimestampFormat::Millis => Self::Millis(Box::new(SnapshotMillis::new(output, summary))),
+        }
+    }
+
+    fn projected(
+        output: &'a StreamGitHubActionsRunsUseCaseOutput,
+        format: TimestampFormat,
+        projection: &RunProjection,
+    ) -> serde_json::Result<Self> {
+        let view = Self::new(output, format, projection);
+        if projection.is_full() {
+            return Ok(view);
+        }
+        let mut snapshot = serde_json::to_value(view)?;
+        projection.project_snapshot(&mut snapshot);
+        Ok(Self::Projected(snapshot))
+    }
+}
+
+/// A websocket frame with the sequence number of the event it carries.
+#[derive(Serialize)]
+struct Sequenced<T> {
+    seq: u64,
+    /// Set on a snapshot replacing events the client missed but that are no longer buffered
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    resync: bool,
+    #[serde(flatten)]
+    frame: T,
+}
+
+/// Serializes a snapshot with timestamps in `format` and the runs of `projection`, tagged
+/// with its sequence number.
+///
+/// # Errors
+///
+/// Returns an error if serialization fails.
+pub fn sequenced_snapshot_json(
+    output: &StreamGitHubActionsRunsUseCaseOutput,
+    format: TimestampFormat,
+    projection: &RunProjection,
+    seq: u64,
+    resync: bool,
+) -> serde_json::Result<String> {
+    serde_json::to_string(&Sequenced {
+        seq,
+        resync,
+        frame: SnapshotView::projected(output, format, projection)?,
+    })
+}
+
+/// Serializes `frame`, tagged with its sequence number.
+///
+/// # Errors
+///
+/// Returns an error if serialization fails.
+pub fn sequenced_json(frame: &impl Serialize, seq: u64) -> serde_json::Result<String> {
+    serde_json::to_string(&Sequenced {
+        seq,
+        resync: false,
+        frame,
+    })
+}
+
+/// A heartbeat sent to websocket clients, to be echoed as `heartbeat_ack`.
+#[derive(Serialize)]
+#[serde(tag = "type", rename = "heartbeat")]
+struct HeartbeatView {
+    seq: u64,
+    timestamp: TimestampView,
+}
+
+/// Serializes a heartbeat as `{"type": "heartbeat", "seq", "timestamp"}`.
+///
+/// # Errors
+///
+/// Returns an error if serialization fails.
+pub fn heartbeat_json(
+    seq: u64,
+    sent_at: DateTime<Utc>,
+    format: TimestampFormat,
+) -> serde_json::Result<String> {
+    serde_json::to_string(&HeartbeatView {
+        seq,
+        timestamp: TimestampView::new(sent_at, format),
+    })
+}
+
+/// Serializes a single run with timestamps in `format`.
+///
+/// # Errors
+///
+/// Returns an error if serialization fails.
+pub fn run_json(run: &WorkflowRun, format: TimestampFormat) -> serde_json::Result<String> {
+    match format {
+        TimestampFormat::Rfc3339 => serde_json::to_string(run),
+        TimestampFormat::Millis => serde_json::to_string(&WorkflowRunMillis::from(run)),
+    }
+}
+
+/// Serializes a run with timestamps in `format` and the fields of `projection`.
+///
+/// # Errors
+///
+/// Returns an error if serialization fails.
+pub fn projected_run_json(
+    run: &WorkflowRun,
+    format: TimestampFormat,
+    projection: &RunProjection,
+) -> serde_json::Result<String> {
+    serde_json::to_string(&RunView::projected(run, format, projection)?)
+}
+
+/// A pinned run that completed, sent to the websocket client that pinned it.
+#[derive(Serialize)]
+#[serde(tag = "type", rename = "pinned_run_completed")]
+struct PinnedRunCompletedView<'a> {
+    run: RunView<'a>,
+}
+
+/// Serializes a completed pinned run as `{"type": "pinned_run_completed", "run"}`, the run
+/// as in snapshots.
+///
+/// # Errors
+///
+/// Returns an error if serialization fails.
+pub fn pinned_run_completed_json(
+    run: &WorkflowRun,
+    format: TimestampFormat,
+    projection: &RunProjection,
+) -> serde_json::Result<String> {
+    serde_json::to_string(&PinnedRunCompletedView {
+        run: RunView::projected(run, format, projection)?,
+    })
+}
+
+/// A run serialized with either timestamp format.
+#[derive(Serialize)]
+#[serde(untagged)]
+enum RunView<'a> {
+    Rfc3339(&'a WorkflowRun),
+    Millis(Box<WorkflowRunMillis<'a>>),
+    /// Serialized already, with only some fields
+    Projected(serde_json::Value),
+}
+
+impl<'a> RunView<'a> {
+    fn new(run: &'a WorkflowRun, format: TimestampFormat) -> Self {
+        match format {
+            TimestampFormat::Rfc3339 => Self::Rfc3339(run),
+            TimestampFormat::Millis => Self::Millis(Box::new(WorkflowRunMillis::from(run))),
+        }
+    }
+
+    fn projected(
+        run: &'a WorkflowRun,
+        format: TimestampFormat,
+        projection: &RunProjection,
+    ) -> serde_json::Result<Self> {
+        let view = Self::new(run, format);
+        if !projection.rewrites_runs() {
+            return Ok(view);
+        }
+        let mut run = serde_json::to_value(view)?;
+        projection.project_run(&mut run);
+        Ok(Self::Projected(run))
+    }
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum TimestampView {
+    Rfc3339(DateTime<Utc>),
+    Millis(i64),
+}
+
+impl TimestampView {
+    fn new(timestamp: DateTime<Utc>, format: TimestampFormat) -> Self {
+        match format {
+            TimestampFormat::Rfc3339 => Self::Rfc3339(timestamp),
+            TimestampFormat::Millis => Self::Millis(timestamp.timestamp_millis()),
+        }
+    }
+}
+
+/// Stored runs returned by `GET /history`.
+#[derive(Serialize)]
+struct History<'a> {
+    runs: Vec<RunView<'a>>,
+}
+
+/// Serializes stored runs as `{"runs": [...]}`.
+///
+/// # Errors
+///
+/// Returns an error if serialization fails.
+pub fn history_json(runs: &[WorkflowRun], format: TimestampFormat) -> serde_json::Result<String> {
+    serde_json::to_string(&History {
+        runs: runs.iter().map(|run| RunView::new(run, format)).collect(),
+    })
+}
+
+/// Latest default-branch runs returned by `POST /runs/latest`.
+#[derive(Serialize)]
+struct LatestRuns<'a> {
+    runs: BTreeMap<&'a str, Option<RunView<'a>>>,
+    errors: &'a BTreeMap<String, String>,
+}
+
+/// Serializes the latest run of each requested repository, or `null` if it has none, as
+/// `{"runs": {...}, "errors": {...}}`, both keyed by the names as requested.
+///
+/// # Errors
+///
+/// Returns an error if serialization fails.
+pub fn latest_runs_json(
+    runs: &BTreeMap<String, Option<WorkflowRun>>,
+    errors: &BTreeMap<String, String>,
+    format: TimestampFormat,
+) -> serde_json::Result<String> {
+    serde_json::to_string(&LatestRuns {
+        runs: runs
+            .iter()
+            .map(|(repo, run)| {
+                (
+                    repo.as_str(),
+                    run.as_ref().map(|run| RunView::new(run, format)),
+                )
+            })
+            .collect(),
+        errors,
+    })
+}
+
+/// A stored snapshot returned by `GET /snapshot`.
+#[derive(Serialize)]
+struct HistoricalSnapshotView<'a> {
+    sequence: u64,
+    #[serde(rename = "generatedAt")]
+    generated_at: TimestampView,
+    runs: Vec<RunView<'a>>,
+}
+
+/// Serializes a stored snapshot as `{"sequence", "generatedAt", "runs": [...]}`.
+///
+/// # Errors
+///
+/// Returns an error if serialization fails.
+pub fn historical_snapshot_json(
+    snapshot: &HistoricalSnapshot,
+    format: TimestampFormat,
+) -> serde_json::Result<String> {
+    serde_json::to_string(&HistoricalSnapshotView {
+        sequence: snapshot.sequence,
+        generated_at: TimestampView::new(snapshot.generated_at, format),
+        runs: snapshot
+            .runs
+            .iter()
+            .map(|run| RunView::new(run, format))
+            .collect(),
+    })
+}
+
+/// One point of the series returned by `GET /snapshots`.
+#[derive(Serialize)]
+struct SnapshotPoint<'a> {
+    sequence: u64,
+    #[serde(rename = "generatedAt")]
+    generated_at: TimestampView,
+    counts: &'a BTreeMap<String, u64>,
+}
+
+/// Status counts of stored snapshots returned by `GET /snapshots`.
+#[derive(Serialize)]
+struct SnapshotSeries<'a> {
+    from: TimestampView,
+    to: TimestampView,
+    #[serde(rename = "stepSeconds")]
+    step_seconds: i64,
+    points: Vec<SnapshotPoint<'a>>,
+}
+
+/// Serializes the status counts of one snapshot per `step` as
+/// `{"from", "to", "stepSeconds", "points": [{"sequence", "generatedAt", "counts"}]}`.
+///
+/// # Errors
+///
+/// Returns an error if serialization fails.
+pub fn snapshot_series_json(
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    step: TimeDelta,
+    points: &[SnapshotStatusCounts],
+    format: TimestampFormat,
+) -> serde_json::Result<String> {
+    serde_json::to_string(&SnapshotSeries {
+        from: TimestampView::new(from, format),
+        to: TimestampView::new(to, format),
+        step_seconds: step.num_seconds(),
+        points: points
+            .iter()
+            .map(|point| SnapshotPoint {
+                sequence: point.sequence,
+                generated_at: TimestampView::new(point.generated_at, format),
+                counts: &point.counts,
+            })
+            .collect(),
+    })
+}
+
+/// The range of stored snapshots, returned along with the error of requests outside it.
+#[derive(Serialize)]
+struct SnapshotRangeView {
+    oldest: TimestampView,
+    newest: TimestampView,
+}
+
+/// The error of a request for snapshots outside those stored.
+#[derive(Serialize)]
+struct SnapshotsUnavailable<'a> {
+    error: &'a str,
+    #[serde(rename = "availableRange")]
+    available_range: Option<SnapshotRangeView>,
+}
+
+/// Serializes `error` with the stored range as `{"error", "availableRange": {"oldest",
+/// "newest"}}`, the range being `null` when no snapshot is stored.
+///
+/// # Errors
+///
+/// Returns an error if serialization fails.
+pub fn snapshots_unavailable_json(
+    error: &str,
+    range: Option<SnapshotRange>,
+    format: TimestampFormat,
+) -> serde_json::Result<String> {
+    serde_json::to_string(&SnapshotsUnavailable {
+        error,
+        available_range: range.map(|range| SnapshotRangeView {
+            oldest: TimestampView::new(range.oldest, format),
+            newest: TimestampView::new(range.newest, format),
+        }),
+    })
+}
+
+/// A page of runs returned by `GET /search/runs`.
+#[derive(Serialize)]
+struct SearchResults<'a> {
+    runs: Vec<RunView<'a>>,
+    #[serde(rename = "nextCursor")]
+    next_cursor: Option<String>,
+}
+
+/// Serializes a page of search results as `{"runs": [...], "nextCursor": ...}`.
+///
+/// # Errors
+///
+/// Returns an error if serialization fails.
+pub fn search_json(
+    runs: &[WorkflowRun],
+    next_cursor: Option<String>,
+    format: TimestampFormat,
+) -> serde_json::Result<String> {
+    serde_json::to_string(&SearchResults {
+        runs: runs.iter().map(|run| RunView::new(run, format)).collect(),
+        next_cursor,
+    })
+}
+
+/// Collapsed runs of bots returned by `GET /runs/bot-group`.
+#[derive(Serialize)]
+struct BotGroupRuns<'a> {
+    #[serde(rename = "repositoryName")]
+    repository_name: &'a RepoFullName,
+    runs: Vec<RunView<'a>>,
+}
+
+/// Serializes the collapsed runs of bots of `repository` as
+/// `{"repositoryName": ..., "runs": [...]}`.
+///
+/// # Errors
+///
+/// Returns an error if serialization fails.
+pub fn bot_group_json(
+    repository: &RepoFullName,
+    runs: &[&WorkflowRun],
+    format: TimestampFormat,
+) -> serde_json::Result<String> {
+    serde_json::to_string(&BotGroupRuns {
+        repository_name: repository,
+        runs: runs.iter().map(|run| RunView::new(run, format)).collect(),
+    })
+}
+
+/// Queue depths returned by `GET /queue`.
+#[derive(Serialize)]
+struct Queue<'a> {
+    repositories: &'a [RepositoryQueue],
+    #[serde(rename = "generatedAt")]
+    generated_at: TimestampView,
+}
+
+/// Serializes the queues of a snapshot as `{"repositories": [...], "generatedAt": ...}`.
+///
+/// # Errors
+///
+/// Returns an error if serialization fails.
+pub fn queue_json(
+    output: &StreamGitHubActionsRunsUseCaseOutput,
+    format: TimestampFormat,
+) -> serde_json::Result<String> {
+    serde_json::to_string(&Queue {
+        repositories: &output.queue,
+        generated_at: TimestampView::new(output.generated_at, format),
+    })
+}
+
+/// Self-hosted runners returned by `GET /runners`.
+#[derive(Serialize)]
+struct Runners<'a> {
+    repositories: &'a [RepositoryRunners],
+    #[serde(rename = "updatedAt")]
+    updated_at: TimestampView,
+}
+
+/// Serializes a runner inventory as `{"repositories": [...], "updatedAt": ...}`.
+///
+/// # Errors
+///
+/// Returns an error if serialization fails.
+pub fn runners_json(fleet: &RunnerFleet, format: TimestampFormat) -> serde_json::Result<String> {
+    serde_json::to_string(&Runners {
+        repositories: &fleet.repositories,
+        updated_at: TimestampView::new(fleet.updated_at, format),
+    })
+}
+
+/// A deployment state serialized with either timestamp format.
+#[derive(Serialize)]
+#[serde(untagged)]
+enum DeploymentView<'a> {
+    Rfc3339(&'a DeploymentState),
+    Millis(DeploymentStateMillis<'a>),
+}
+
+/// [`DeploymentState`] with an epoch millisecond timestamp.
+#[derive(Serialize)]
+struct DeploymentStateMillis<'a> {
+    repository: &'a RepoFullName,
+    environment: &'a str,
+    #[serde(rename = "ref")]
+    git_ref: &'a str,
+    sha: &'a str,
+    creator: Option<&'a RunActor>,
+    state: DeploymentStatusState,
+    #[serde(rename = "updatedAt", with = "ts_milliseconds")]
+    updated_at: DateTime<Utc>,
+    #[serde(rename = "targetUrl")]
+    target_url: Option<&'a str>,
+}
+
+impl<'a> DeploymentView<'a> {
+    fn new(state: &'a DeploymentState, format: TimestampFormat) -> Self {
+        match format {
+            TimestampFormat::Rfc3339 => Self::Rfc3339(state),
+            TimestampFormat::Millis => Self::Millis(DeploymentStateMillis {
+                repository: &state.repository,
+                environment: &state.environment,
+                git_ref: &state.git_ref,
+                sha: &state.sha,
+                creator: state.creator.as_ref(),
+                state: state.state,
+                updated_at: state.updated_at,
+                target_url: state.target_url.as_deref(),
+            }),
+        }
+    }
+}
+
+/// Deployments returned by `GET /deployments`.
+#[derive(Serialize)]
+struct Deployments<'a> {
+    environments: Vec<DeploymentView<'a>>,
+    #[serde(rename = "updatedAt")]
+    updated_at: TimestampView,
+}
+
+/// Serializes the latest deployments as `{"environments": [...], "updatedAt": ...}`.
+///
+/// # Errors
+///
+/// Returns an error if serialization fails.
+pub fn deployments_json(
+    board: &DeploymentBoard,
+    format: TimestampFormat,
+) -> serde_json::Result<String> {
+    serde_json::to_string(&Deployments {
+        environments: board
+            .environments
+            .iter()
+            .map(|state| DeploymentView::new(state, format))
+            .collect(),
+        updated_at: TimestampView::new(board.updated_at, format),
+    })
+}
+
+#[derive(Serialize)]
+struct RepositoryGroup<'a> {
+    repository: Cow<'a, Repository>,
+    runs: Vec<RunView<'a>>,
+}
+
+/// Snapshot with runs nested under the repository they belong to.
+#[derive(Serialize)]
+struct GroupedSnapshot<'a> {
+    #[serde(rename = "schemaVersion")]
+    schema_version: u32,
+    repositories: Vec<RepositoryGroup<'a>>,
+    #[serde(rename = "generatedAt")]
+    generated_at: TimestampView,
+    queue: &'a [RepositoryQueue],
+    summary: SummaryView,
+}
+
+/// Serializes a snapshot as `{"repositories": [{"repository": {...}, "runs": [...]}]}`.
+///
+/// Groups follow the snapshot's repository order and include repositories without runs.
+/// Runs of a repository missing from the snapshot's records get a group built from their name.
+/// The limit of `projection` applies to the runs before they are grouped and summarized.
+///
+/// # Errors
+///
+/// Returns an error if serialization fails.
+pub fn grouped_snapshot_json(
+    output: &StreamGitHubActionsRunsUseCaseOutput,
+    format: TimestampFormat,
+    projection: &RunProjection,
+) -> serde_json::Result<String> {
+    let runs = projection.runs(&output.runs);
+    let mut groups: Vec<(Cow<Repository>, Vec<RunView>)> = output
+        .repositories
+        .iter()
+        .map(|repository| (Cow::Borrowed(repository), Vec::new()))
+        .collect();
+    for run in runs {
+        let position = groups
+            .iter()
+            .position(|(repository, _)| *repository.full_name() == run.repository_name);
+        let index = position.unwrap_or_else(|| {
+            groups.push((
+                Cow::Owned(Repository::from_full_name(&run.repository_name)),
+                Vec::new(),
+            ));
+            groups.len() - 1
+        });
+        groups[index]
+            .1
+            .push(RunView::projected(run, format, projection)?);
+    }
+
+    serde_json::to_string(&GroupedSnapshot {
+        schema_version: output.schema_version,
+        repositories: groups
+            .into_iter()
+            .map(|(repository, runs)| RepositoryGroup { repository, runs })
+            .collect(),
+        generated_at: TimestampView::new(output.generated_at, format),
+        queue: &output.queue,
+        summary: SummaryView::new(summarize(runs), format),
+    })
+}
+
+/// JSON Schema of snapshots serialized with timestamps in `format`.
+#[must_use]
+pub fn snapshot_schema(format: TimestampFormat) -> Schema {
+    match format {
+        TimestampFormat::Rfc3339 => schemars::schema_for!(SummarizedSnapshot<'static>),
+        TimestampFormat::Millis => schemars::schema_for!(SnapshotMillis<'static>),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{base_time, workflow_run};
+    use chrono::TimeDelta;
+
+    fn output() -> StreamGitHubActionsRunsUseCaseOutput {
+        let mut completed = workflow_run("owner/repo", 1, "success");
+        completed.updated_at = completed.created_at + TimeDelta::seconds(95);
+        StreamGitHubActionsRunsUseCaseOutput::new(
+            vec![completed, workflow_run("owner/repo", 2, "in_progress")],
+            base_time(),
+        )
+    }
+
+    #[test]
+    fn test_rfc3339_snapshot() -> anyhow::Result<()> {
+        let json: serde_json::Value =
+            serde_json::from_str(&snapshot_json(&output(), TimestampFormat::Rfc3339)?)?;
+        insta::assert_json_snapshot!(json);
+        Ok(())
+    }
+
+    #[test]
+    fn test_millis_snapshot() -> anyhow::Result<()> {
+        let json: serde_json::Value =
+            serde_json::from_str(&snapshot_json(&output(), TimestampFormat::Millis)?)?;
+        insta::assert_json_snapshot!(json);
+        Ok(())
+    }
+
+    #[test]
+    fn test_millis_run_has_the_same_fields() -> anyhow::Result<()> {
+        let run = workflow_run("owner/repo", 1, "failure");
+        let rfc3339: serde_json::Map<String, serde_json::Value> =
+            serde_json::from_str(&run_json(&run, TimestampFormat::Rfc3339)?)?;
+        let millis: serde_json::Map<String, serde_json::Value> =
+            serde_json::from_str(&run_json(&run, TimestampFormat::Millis)?)?;
+
+        assert!(rfc3339.keys().eq(millis.keys()));
+        assert_eq!(millis["createdAt"], run.created_at.timestamp_millis());
+        Ok(())
+    }
+
+    #[test]
+    fn test_grouped_snapshot_embeds_repositories() -> anyhow::Result<()> {
+        let repository = Repository::new("owner", "repo")?.with_owner_avatar_url(Some(
+            "https://avatars.githubusercontent.com/u/1?v=4".to_string(),
+        ));
+        let idle = Repository::from_full_name(&"owner/idle".parse()?);
+        let output = StreamGitHubActionsRunsUseCaseOutput::new(
+            vec![
+                workflow_run("owner/repo", 2, "failure"),
+                workflow_run("owner/unlisted", 3, "success"),
+                workflow_run("owner/repo", 1, "success"),
+            ],
+            base_time(),
+        )
+        .with_repositories(vec![repository, idle]);
+
+        let json: serde_json::Value = serde_json::from_str(&grouped_snapshot_json(
+            &output,
+            TimestampFormat::Millis,
+            &RunProjection::default(),
+        )?)?;
+
+        let groups = &json["repositories"];
+        assert_eq!(
+            groups[0]["repository"],
+            serde_json::json!({
+                "fullName": "owner/repo",
+                "name": "repo",
+                "owner": "owner",
+                "ownerAvatarUrl": "https://avatars.githubusercontent.com/u/1?v=4",
+                "htmlUrl": "https://github.com/owner/repo"
+            })
+        );
+        let run_ids = |group: &serde_json::Value| -> Vec<serde_json::Value> {
+            group["runs"]
+                .as_array()
+                .map(|runs| runs.iter().map(|run| run["id"].clone()).collect())
+                .unwrap_or_default()
+        };
+        assert_eq!(run_ids(&groups[0]), [2, 1]);
+        assert_eq!(
+            groups[0]["runs"][0]["repositoryName"],
+            groups[0]["repository"]["fullName"]
+        );
+        // Repositories without runs are still listed
+        assert_eq!(groups[1]["repository"]["fullName"], "owner/idle");
+        assert_eq!(
+            groups[1]["repository"]["ownerAvatarUrl"],
+            serde_json::Value::Null
+        );
+        assert_eq!(run_ids(&groups[1]), Vec::<serde_json::Value>::new());
+        assert_eq!(groups[2]["repository"]["fullName"], "owner/unlisted");
+        assert_eq!(run_ids(&groups[2]), [3]);
+        assert_eq!(json["generatedAt"], base_time().timestamp_millis());
+        Ok(())
+    }
+
+    #[test]
+    fn test_timestamp_format_parsing() -> anyhow::Result<()> {
+        assert_eq!(
+            "millis".parse::<TimestampFormat>()?,
+            TimestampFormat::Millis
+        );
+        assert_eq!(
+            "rfc3339".parse::<TimestampFormat>()?,
+            TimestampFormat::Rfc3339
+        );
+        assert!("iso".parse::<TimestampFormat>().is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,341 @@
+use crate::application::services::{Notice, PollerEvent, SequencedEvent};
+use crate::application::use_cases::StreamGitHubActionsRunsUseCaseOutput;
+use crate::domain::external_apis::github::Repository;
+use crate::domain::models::actor::RunActor;
+use crate::domain::models::bot_group::BotRunGroup;
+use crate::domain::models::commit_status::ExternalCheck;
+use crate::domain::models::ids::RepoFullName;
+use crate::domain::models::queue::RepositoryQueue;
+use crate::domain::models::run::WorkflowRun;
+use crate::domain::models::schedule::UpcomingRun;
+use crate::domain::repositories::{PseudonymRepository, RunRepositoryError};
+use crate::infrastructures::telemetry::{LogRedaction, Redactor, RepositoryMasking};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Owner of every pseudonymous repository
+const PSEUDONYM_OWNER: &str = "demo";
+
+/// What links to GitHub are replaced with
+const STRIPPED_URL: &str = "#";
+
+/// Titles standing in for run titles, each for titles up to its number of characters
+const GENERIC_TITLES: [(usize, &str); 3] = [
+    (24, "Update files"),
+    (72, "Update several files across the project"),
+    (
+        usize::MAX,
+        "Update several files across the project and adjust the configuration they rely on",
+    ),
+];
+
+/// Rewrites what the public routes serve so a demo deployment shows no repository, account
+/// or commit of the repositories it polls.
+///
+/// Repositories become `demo/repo-N`, numbered in the order they are first shown and kept
+/// in the store when there is one. Logins and commit SHAs are hashed, titles replaced by
+/// generic text of a similar length and links to GitHub by `#`.
+#[derive(Default)]
+pub struct Anonymizer {
+    /// Number of each repository by its lowercase `owner/name`
+    numbers: Mutex<HashMap<String, u64>>,
+    store: Option<Arc<dyn PseudonymRepository + Send + Sync>>,
+}
+
+impl fmt::Debug for Anonymizer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Anonymizer")
+            .field("persistent", &self.store.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Anonymizer {
+    /// An anonymizer numbering repositories after those already in `store`, and recording
+    /// the new ones there.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stored pseudonyms cannot be read.
+    pub async fn load(
+        store: Arc<dyn PseudonymRepository + Send + Sync>,
+    ) -> Result<Self, RunRepositoryError> {
+        let numbers = store
+            .pseudonyms()
+            .await?
+            .into_iter()
+            .map(|(repository, number)| (repository.to_string().to_ascii_lowercase(), number))
+            .collect();
+        Ok(Self {
+            numbers: Mutex::new(numbers),
+            store: Some(store),
+        })
+    }
+
+    /// The pseudonym of `repository`, numbering it if it has none yet.
+    #[must_use]
+    pub fn repository(&self, repository: &RepoFullName) -> RepoFullName {
+        let key = repository.to_string().to_ascii_lowercase();
+        let mut numbers = self.numbers.lock().unwrap_or_else(PoisonError::into_inner);
+        let number = if let Some(number) = numbers.get(&key) {
+            *number
+        } else {
+            let number = numbers.values().max().map_or(1, |last| last + 1);
+            numbers.insert(key, number);
+            if let Some(store) = &self.store {
+                let (store, repository) = (Arc::clone(store), repository.clone());
+                tokio::spawn(async move {
+                    if let Err(e) = store.save_pseudonym(&repository, number).await {
+                        tracing::error!("Failed to store the pseudonym of a repository: {}", e);
+                    }
+                });
+            }
+            number
+        };
+        pseudonym(number)
+    }
+
+    /// The repository going by `pseudonym`, for filters clients give in pseudonyms.
+    #[must_use]
+    pub fn original(&self, pseudonym: &RepoFullName) -> Option<RepoFullName> {
+        let number = pseudonym
+            .name()
+            .strip_prefix("repo-")
+            .filter(|_| pseudonym.owner() == PSEUDONYM_OWNER)?
+            .parse::<u64>()
+            .ok()?;
+        let numbers = self.numbers.lock().unwrap_or_else(PoisonError::into_inner);
+        numbers
+            .iter()
+            .find(|(_, assigned)| **assigned == number)
+            .and_then(|(repository, _)| repository.parse().ok())
+    }
+
+    /// `run` as the public routes show it.
+    #[must_use]
+    pub fn run(&self, run: &WorkflowRun) -> WorkflowRun {
+        WorkflowRun {
+            repository_name: self.repository(&run.repository_name),
+            display_title: generic_title(&run.display_title).to_string(),
+            full_display_title: run
+                .full_display_title
+                .as_deref()
+                .map(|title| generic_title(title).to_string()),
+            head_sha: hashed_sha(&run.head_sha),
+            actor: anonymous_actor(&run.actor),
+            triggering_actor: run.triggering_actor.as_ref().map(anonymous_actor),
+            html_url: STRIPPED_URL.to_string(),
+            failed_job_url: run
+                .failed_job_url
+                .as_ref()
+                .map(|_| STRIPPED_URL.to_string()),
+            ..run.clone()
+        }
+    }
+
+    #[must_use]
+    pub fn runs(&self, runs: &[WorkflowRun]) -> Vec<WorkflowRun> {
+        runs.iter().map(|run| self.run(run)).collect()
+    }
+
+    /// `output` as the public routes show it.
+    #[must_use]
+    pub fn snapshot(
+        &self,
+        output: &StreamGitHubActionsRunsUseCaseOutput,
+    ) -> StreamGitHubActionsRunsUseCaseOutput {
+        StreamGitHubActionsRunsUseCaseOutput {
+            runs: self.runs(&output.runs),
+            repositories: output
+                .repositories
+                .iter()
+                .map(|repository| {
+                    Repository::from_full_name(&self.repository(repository.full_name()))
+                        .with_html_url(STRIPPED_URL.to_string())
+                        .with_default_branch(repository.default_branch.clone())
+                })
+                .collect(),
+            queued_runs: self.runs(&output.queued_runs),
+            queue: output
+                .queue
+                .iter()
+                .map(|queue| RepositoryQueue {
+                    repository: self.repository(&queue.repository),
+                    ..queue.clone()
+                })
+                .collect(),
+            upcoming: output
+                .upcoming
+                .iter()
+                .map(|upcoming| UpcomingRun {
+                    repository_name: self.repository(&upcoming.repository_name),
+                    ..upcoming.clone()
+                })
+                .collect(),
+            external_checks: output
+                .external_checks
+                .iter()
+                .map(|check| ExternalCheck {
+                    repository_name: self.repository(&check.repository_name),
+                    head_sha: hashed_sha(&check.head_sha),
+                    description: check.description.as_deref().map(anonymous_text),
+                    target_url: check.target_url.as_ref().map(|_| STRIPPED_URL.to_string()),
+                    ..check.clone()
+                })
+                .collect(),
+            bot_groups: output
+                .bot_groups
+                .iter()
+                .map(|group| BotRunGroup {
+                    repository_name: self.repository(&group.repository_name),
+                    ..group.clone()
+                })
+                .collect(),
+            bot_runs: self.runs(&output.bot_runs),
+            ..output.clone()
+        }
+    }
+
+    /// `event` as the public streams send it; errors and notices have names masked as
+    /// strict log redaction does, since they are free text.
+    #[must_use]
+    pub fn event(&self, event: PollerEvent) -> PollerEvent {
+        match event {
+            PollerEvent::Snapshot(output) => {
+                PollerEvent::Snapshot(Arc::new(self.snapshot(&output)))
+            }
+            PollerEvent::Error(message) => PollerEvent::Error(anonymous_text(&message).into()),
+            PollerEvent::Notice(notice) => PollerEvent::Notice(Arc::new(Notice {
+                message: anonymous_text(&notice.message),
+                ..notice.as_ref().clone()
+            })),
+            PollerEvent::Shutdown => PollerEvent::Shutdown,
+        }
+    }
+
+    /// Like [`Anonymizer::event`], keeping the sequence number.
+    #[must_use]
+    pub fn sequenced(&self, event: SequencedEvent) -> SequencedEvent {
+        SequencedEvent {
+            seq: event.seq,
+            event: self.event(event.event),
+        }
+    }
+}
+
+fn pseudonym(number: u64) -> RepoFullName {
+    let name = format!("repo-{number}");
+    match RepoFullName::new(PSEUDONYM_OWNER, name) {
+        Ok(pseudonym) => pseudonym,
+        // Both parts are made of letters, digits and `-` only
+        Err(e) => unreachable!("{e}"),
+    }
+}
+
+/// First 8 hex digits of the SHA-256 of `value`.
+fn short_hash(value: &str) -> String {
+    let hex = format!("{:x}", Sha256::digest(value));
+    hex[..8].to_string()
+}
+
+/// A commit SHA of the same shape, so runs of one commit still share it.
+fn hashed_sha(sha: &str) -> String {
+    let hex = format!("{:x}", Sha256::digest(sha));
+    hex[..sha.len().min(hex.len())].to_string()
+}
+
+/// `actor` with a hashed login, keeping bots recognizable as such.
+fn anonymous_actor(actor: &RunActor) -> RunActor {
+    let login = if actor.is_bot() {
+        format!("bot-{}[bot]", short_hash(&actor.login))
+    } else {
+        format!("user-{}", short_hash(&actor.login))
+    };
+    RunActor {
+        login,
+        avatar_url: STRIPPED_URL.to_string(),
+        html_url: STRIPPED_URL.to_string(),
+    }
+}
+
+fn generic_title(title: &str) -> &'static str {
+    let length = title.chars().count();
+    GENERIC_TITLES
+        .iter()
+        .find(|(longest, _)| length <= *longest)
+        .map_or(GENERIC_TITLES[2].1, |(_, title)| title)
+}
+
+fn anonymous_text(text: &str) -> String {
+    Redactor::new(LogRedaction::Strict, RepositoryMasking::Full)
+        .redact(text)
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructures::adapters::secondary::persistence::SqliteRunRepository;
+    use crate::test_support::{repo_full_name, workflow_run};
+
+    #[tokio::test]
+    async fn test_repositories_keep_their_pseudonym_across_restarts() -> anyhow::Result<()> {
+        let store = Arc::new(SqliteRunRepository::connect("sqlite::memory:")?);
+        let anonymizer = Anonymizer::load(Arc::clone(&store) as _).await?;
+        assert_eq!(
+            anonymizer.repository(&repo_full_name("owner/repo")),
+            repo_full_name("demo/repo-1")
+        );
+        assert_eq!(
+            anonymizer.repository(&repo_full_name("owner/other")),
+            repo_full_name("demo/repo-2")
+        );
+        assert_eq!(
+            anonymizer.repository(&repo_full_name("Owner/Repo")),
+            repo_full_name("demo/repo-1")
+        );
+        // The pseudonyms are stored in the background
+        while store.pseudonyms().await?.len() < 2 {
+            tokio::task::yield_now().await;
+        }
+
+        let restarted = Anonymizer::load(store).await?;
+        assert_eq!(
+            restarted.repository(&repo_full_name("owner/other")),
+            repo_full_name("demo/repo-2")
+        );
+        assert_eq!(
+            restarted.repository(&repo_full_name("owner/third")),
+            repo_full_name("demo/repo-3")
+        );
+        assert_eq!(
+            restarted.original(&repo_full_name("demo/repo-1")),
+            Some(repo_full_name("owner/repo"))
+        );
+        assert_eq!(restarted.original(&repo_full_name("owner/repo-1")), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_runs_keep_their_shape_without_identifiers() {
+        let anonymizer = Anonymizer::default();
+        let mut run = workflow_run("owner/repo", 1, "failure");
+        run.display_title = "Fix the login of octocat".to_string();
+        run.actor.login = "dependabot[bot]".to_string();
+        run.failed_job_url = Some("https://github.com/owner/repo/actions/runs/1/job/2".to_string());
+
+        let anonymous = anonymizer.run(&run);
+        assert_eq!(anonymous.repository_name, repo_full_name("demo/repo-1"));
+        assert_eq!(anonymous.display_title, "Update files");
+        assert!(anonymous.actor.is_bot());
+        assert!(!anonymous.actor.login.contains("dependabot"));
+        assert_eq!(anonymous.head_sha.len(), run.head_sha.len());
+        assert_ne!(anonymous.head_sha, run.head_sha);
+        assert_eq!(anonymous.html_url, "#");
+        assert_eq!(anonymous.failed_job_url.as_deref(), Some("#"));
+        assert_eq!(anonymous.id, run.id);
+        assert_eq!(generic_title(&"x".repeat(73)), GENERIC_TITLES[2].1);
+    }
+}
@@ -0,0 +1,371 @@
+use super::projection::RunProjection;
+use super::{Sequenced, SnapshotView, TimestampFormat};
+use crate::application::use_cases::StreamGitHubActionsRunsUseCaseOutput;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Snapshot frames sent between two full ones in [`WebsocketEncoding::JsonPatch`] mode
+pub const PATCH_RESYNC_INTERVAL: u32 = 50;
+
+/// How a websocket client is sent snapshots, chosen with `?encoding=`.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum WebsocketEncoding {
+    /// Every snapshot in full
+    #[default]
+    Json,
+    /// A full snapshot, then RFC 6902 patches against the previous one
+    JsonPatch,
+}
+
+/// One RFC 6902 operation; only the ones a diff of two documents needs.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+}
+
+/// A snapshot as patched documents hold it: `runs` is an object keyed by run id, so a
+/// changed run is patched where it is instead of shifting every run after it.
+///
+/// Runs are keyed before `projection` drops their fields, so they keep their key without
+/// their `id`.
+///
+/// # Errors
+///
+/// Returns an error if serialization fails.
+pub fn keyed_snapshot(
+    output: &StreamGitHubActionsRunsUseCaseOutput,
+    format: TimestampFormat,
+    projection: &RunProjection,
+) -> serde_json::Result<Value> {
+    let mut snapshot = serde_json::to_value(SnapshotView::new(output, format, projection))?;
+    if let Some(runs) = snapshot.get_mut("runs")
+        && let Value::Array(list) = runs.take()
+    {
+        *runs = Value::Object(
+            list.into_iter()
+                .take(projection.max_runs())
+                .map(|mut run| {
+                    let key = run["id"].to_string();
+                    projection.project_run(&mut run);
+                    (key, run)
+                })
+                .collect(),
+        );
+    }
+    Ok(snapshot)
+}
+
+/// Operations turning `from` into `to`: objects are compared key by key, anything else is
+/// replaced whole when it differs.
+#[must_use]
+pub fn diff(from: &Value, to: &Value) -> Vec<PatchOperation> {
+    let mut operations = Vec::new();
+    diff_at(from, to, &mut String::new(), &mut operations);
+    operations
+}
+
+fn diff_at(from: &Value, to: &Value, path: &mut String, operations: &mut Vec<PatchOperation>) {
+    if from == to {
+        return;
+    }
+    let (Value::Object(from), Value::Object(to)) = (from, to) else {
+        operations.push(PatchOperation::Replace {
+            path: path.clone(),
+            value: to.clone(),
+        });
+        return;
+    };
+    let prefix_len = path.len();
+    for key in from.keys().filter(|key| !to.contains_key(*key)) {
+        push_token(path, key);
+        operations.push(PatchOperation::Remove { path: path.clone() });
+        path.truncate(prefix_len);
+    }
+    for (key, value) in to {
+        push_token(path, key);
+        match from.get(key) {
+            Some(previous) => diff_at(previous, value, path, operations),
+            None => operations.push(PatchOperation::Add {
+                path: path.clone(),
+                value: value.clone(),
+            }),
+        }
+        path.truncate(prefix_len);
+    }
+}
+
+/// Appends `key` to a JSON Pointer, escaping `~` and `/` as RFC 6901 requires.
+fn push_token(path: &mut String, key: &str) {
+    path.push('/');
+    path.push_str(&key.replace('~', "~0").replace('/', "~1"));
+}
+
+/// A full snapshot in patch mode.
+#[derive(Serialize)]
+struct FullFrame<'a> {
+    snapshot: &'a Value,
+}
+
+/// Changes since the previous snapshot frame.
+#[derive(Serialize)]
+struct PatchFrame {
+    patch: Vec<PatchOperation>,
+}
+
+/// Encodes the snapshots sent to one websocket client as patches against the previous one,
+/// with a full snapshot first and after every `resync_interval` patches.
+pub struct SnapshotPatcher {
+    format: TimestampFormat,
+    projection: RunProjection,
+    resync_interval: u32,
+    /// What the client holds after the last frame
+    previous: Option<Value>,
+    patches_since_full: u32,
+}
+
+impl SnapshotPatcher {
+    #[must_use]
+    pub fn new(format: TimestampFormat, projection: RunProjection, resync_interval: u32) -> Self {
+        Self {
+            format,
+            projection,
+            resync_interval,
+            previous: None,
+            patches_since_full: 0,
+        }
+    }
+
+    /// Projects the following snapshots with `projection`; the next patch changes the runs
+    /// it projects differently.
+    pub fn set_projection(&mut self, projection: RunProjection) {
+        self.projection = projection;
+    }
+
+    /// The frame bringing the client to `output`: `{"seq", "snapshot"}` with the keyed
+    /// snapshot, or `{"seq", "patch"}` with the operations to apply to the previous one.
+    ///
+    /// `resync` forces a full snapshot and tags it as such.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization fails.
+    pub fn frame(
+        &mut self,
+        output: &StreamGitHubActionsRunsUseCaseOutput,
+        seq: u64,
+        resync: bool,
+    ) -> serde_json::Result<String> {
+        let snapshot = keyed_snapshot(output, self.format, &self.projection)?;
+        let json = match self.previous.as_ref() {
+            Some(previous) if !resync && self.patches_since_full < self.resync_interval => {
+                self.patches_since_full += 1;
+                serde_json::to_string(&Sequenced {
+                    seq,
+                    resync,
+                    frame: PatchFrame {
+                        patch: diff(previous, &snapshot),
+                    },
+                })?
+            }
+            _ => {
+                self.patches_since_full = 0;
+                serde_json::to_string(&Sequenced {
+                    seq,
+                    resync,
+                    frame: FullFrame {
+                        snapshot: &snapshot,
+                    },
+                })?
+            }
+        };
+        self.previous = Some(snapshot);
+        Ok(json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{base_time, workflow_run};
+    use chrono::TimeDelta;
+    use serde_json::Map;
+
+    /// Applies `operations` to `document` the way a client would.
+    fn apply(document: &mut Value, operations: &[PatchOperation]) -> anyhow::Result<()> {
+        for operation in operations {
+            let (path, value) = match operation {
+                PatchOperation::Add { path, value } | PatchOperation::Replace { path, value } => {
+                    (path, Some(value.clone()))
+                }
+                PatchOperation::Remove { path } => (path, None),
+            };
+            if path.is_empty() {
+                *document = value.ok_or_else(|| anyhow::anyhow!("cannot remove the root"))?;
+                continue;
+            }
+            let (parent, key) = path
+                .rsplit_once('/')
+                .ok_or_else(|| anyhow::anyhow!("invalid path {path}"))?;
+            let key = key.replace("~1", "/").replace("~0", "~");
+            let parent = document
+                .pointer_mut(parent)
+                .and_then(Value::as_object_mut)
+                .ok_or_else(|| anyhow::anyhow!("no object at {parent}"))?;
+            match value {
+                Some(value) => parent.insert(key, value),
+                None => parent.remove(&key),
+            };
+        }
+        Ok(())
+    }
+
+    fn frame_body(text: &str) -> anyhow::Result<Map<String, Value>> {
+        match serde_json::from_str(text)? {
+            Value::Object(frame) => Ok(frame),
+            other => anyhow::bail!("frame is not an object: {other}"),
+        }
+    }
+
+    #[test]
+    fn test_applying_the_patches_reconstructs_every_snapshot() -> anyhow::Result<()> {
+        let snapshot = |minutes, runs| {
+            StreamGitHubActionsRunsUseCaseOutput::new(
+                runs,
+                base_time() + TimeDelta::minutes(minutes),
+            )
+        };
+        let mut renamed = workflow_run("owner/repo", 2, "success");
+        renamed.display_title = "Retitled".to_string();
+        let snapshots = [
+            snapshot(0, vec![workflow_run("owner/repo", 1, "in_progress")]),
+            // A new run, and the first one finished
+            snapshot(
+                1,
+                vec![
+                    workflow_run("owner/repo", 2, "queued"),
+                    workflow_run("owner/repo", 1, "failure"),
+                ],
+            ),
+            // The first run dropped out of the window
+            snapshot(2, vec![renamed]),
+            snapshot(3, Vec::new()),
+        ];
+
+        for format in [TimestampFormat::Rfc3339, TimestampFormat::Millis] {
+            let mut patcher =
+                SnapshotPatcher::new(format, RunProjection::default(), PATCH_RESYNC_INTERVAL);
+            let mut client = Value::Null;
+            for (seq, output) in (1..).zip(&snapshots) {
+                let frame = frame_body(&patcher.frame(output, seq, false)?)?;
+                assert_eq!(frame["seq"], seq);
+                match (frame.get("snapshot"), frame.get("patch")) {
+                    (Some(snapshot), None) => client = snapshot.clone(),
+                    (None, Some(patch)) => {
+                        let patch: Vec<PatchOperation> = serde_json::from_value(patch.clone())?;
+                        assert!(
+                            !patch.iter().any(|operation| matches!(
+                                operation,
+                                PatchOperation::Replace { path, .. } if path == "/runs"
+                            )),
+                            "{patch:?}"
+                        );
+                        apply(&mut client, &patch)?;
+                    }
+                    _ => anyhow::bail!("unexpected frame {frame:?}"),
+                }
+                assert_eq!(
+                    client,
+                    keyed_snapshot(output, format, &RunProjection::default())?
+                );
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_patches_only_carry_the_projected_runs_and_fields() -> anyhow::Result<()> {
+        let axum::extract::Query(query) =
+            axum::extract::Query::try_from_uri(&"/ws?limit=1&fields=status".parse()?)?;
+        let mut patcher = SnapshotPatcher::new(
+            TimestampFormat::Rfc3339,
+            RunProjection::from_query(&query, None)?,
+            PATCH_RESYNC_INTERVAL,
+        );
+        let mut finished = workflow_run("owner/repo", 1, "success");
+        finished.display_title = "Retitled".to_string();
+        let snapshots = [
+            StreamGitHubActionsRunsUseCaseOutput::new(
+                vec![
+                    workflow_run("owner/repo", 2, "queued"),
+                    workflow_run("owner/repo", 1, "in_progress"),
+                ],
+                base_time(),
+            ),
+            StreamGitHubActionsRunsUseCaseOutput::new(
+                vec![workflow_run("owner/repo", 2, "in_progress"), finished],
+                base_time() + TimeDelta::minutes(1),
+            ),
+        ];
+
+        let full = frame_body(&patcher.frame(&snapshots[0], 1, false)?)?;
+        let patch = frame_body(&patcher.frame(&snapshots[1], 2, false)?)?;
+
+        assert_eq!(
+            full["snapshot"]["runs"],
+            serde_json::json!({"2": {"status": "queued"}})
+        );
+        let patch: Vec<PatchOperation> = serde_json::from_value(patch["patch"].clone())?;
+        let paths: Vec<&str> = patch
+            .iter()
+            .map(|operation| match operation {
+                PatchOperation::Add { path, .. }
+                | PatchOperation::Remove { path }
+                | PatchOperation::Replace { path, .. } => path.as_str(),
+            })
+            .collect();
+        // The other run changed too, but it is past the limit, so it is not summarized either
+        assert_eq!(
+            paths,
+            [
+                "/generatedAt",
+                "/runs/2/status",
+                "/summary/counts/inProgress",
+                "/summary/counts/queued",
+                "/summary/overall"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_full_snapshots_are_resent_periodically_and_on_resync() -> anyhow::Result<()> {
+        let output = StreamGitHubActionsRunsUseCaseOutput::new(
+            vec![workflow_run("owner/repo", 1, "success")],
+            base_time(),
+        );
+        let mut patcher =
+            SnapshotPatcher::new(TimestampFormat::Rfc3339, RunProjection::default(), 2);
+
+        let kinds = (1..=7)
+            .map(|seq| {
+                let frame = frame_body(&patcher.frame(&output, seq, seq == 7)?)?;
+                Ok(if frame.contains_key("snapshot") {
+                    "full"
+                } else {
+                    "patch"
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        assert_eq!(
+            kinds,
+            ["full", "patch", "patch", "full", "patch", "patch", "full"]
+        );
+        assert_eq!(patcher.frame(&output, 8, false)?, r#"{"seq":8,"patch":[]}"#);
+        Ok(())
+    }
+}
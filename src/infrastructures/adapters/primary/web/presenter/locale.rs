@@ -0,0 +1,234 @@
+use crate::domain::models::status::{RunConclusion, RunStatus};
+
+/// Languages of the `statusLabel` added to runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Locale {
+    #[default]
+    En,
+    Ja,
+}
+
+impl Locale {
+    /// The locale of a language tag such as `ja` or `ja-JP`; `None` for a language without
+    /// labels.
+    #[must_use]
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let language = tag.trim().split(['-', '_']).next().unwrap_or_default();
+        if language.eq_ignore_ascii_case("en") {
+            Some(Self::En)
+        } else if language.eq_ignore_ascii_case("ja") {
+            Some(Self::Ja)
+        } else {
+            None
+        }
+    }
+
+    /// The locale `?lang=` asks for, else the preferred one of an `Accept-Language` header,
+    /// falling back to English for languages without labels; `None` when neither is given.
+    #[must_use]
+    pub fn negotiate(lang: Option<&str>, accept_language: Option<&str>) -> Option<Self> {
+        if let Some(lang) = lang {
+            return Some(Self::from_tag(lang).unwrap_or_default());
+        }
+        let mut ranges: Vec<(&str, f32)> = accept_language?
+            .split(',')
+            .filter_map(|range| {
+                let mut parts = range.split(';');
+                let tag = parts.next()?.trim();
+                let quality = parts
+                    .find_map(|parameter| parameter.trim().strip_prefix("q="))
+                    .map_or(Some(1.0), |quality| quality.trim().parse().ok())?;
+                (quality > 0.0).then_some((tag, quality))
+            })
+            .collect();
+        // Stable, so ranges of equal quality keep the order they were sent in
+        ranges.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        Some(
+            ranges
+                .into_iter()
+                .find_map(|(tag, _)| Self::from_tag(tag))
+                .unwrap_or_default(),
+        )
+    }
+
+    /// The language tag, e.g. `ja`.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::En => "en",
+            Self::Ja => "ja",
+        }
+    }
+}
+
+/// A label in every locale, so a locale cannot be added without labeling every value.
+struct Labels {
+    en: &'static str,
+    ja: &'static str,
+}
+
+impl Labels {
+    fn get(&self, locale: Locale) -> &'static str {
+        match locale {
+            Locale::En => self.en,
+            Locale::Ja => self.ja,
+        }
+    }
+}
+
+fn status_labels(status: RunStatus) -> Labels {
+    match status {
+        RunStatus::Queued => Labels {
+            en: "Queued",
+            ja: "待機中",
+        },
+        RunStatus::InProgress => Labels {
+            en: "In progress",
+            ja: "実行中",
+        },
+        RunStatus::Completed => Labels {
+            en: "Completed",
+            ja: "完了",
+        },
+        RunStatus::Waiting => Labels {
+            en: "Waiting for approval",
+            ja: "承認待ち",
+        },
+        RunStatus::Requested => Labels {
+            en: "Requested",
+            ja: "リクエスト済み",
+        },
+        RunStatus::Pending => Labels {
+            en: "Pending",
+            ja: "保留中",
+        },
+        RunStatus::Unknown => Labels {
+            en: "Unknown",
+            ja: "不明",
+        },
+    }
+}
+
+fn conclusion_labels(conclusion: RunConclusion) -> Labels {
+    match conclusion {
+        RunConclusion::Success => Labels {
+            en: "Success",
+            ja: "成功",
+        },
+        RunConclusion::Failure => Labels {
+            en: "Failure",
+            ja: "失敗",
+        },
+        RunConclusion::Cancelled => Labels {
+            en: "Cancelled",
+            ja: "キャンセル",
+        },
+        RunConclusion::Skipped => Labels {
+            en: "Skipped",
+            ja: "スキップ",
+        },
+        RunConclusion::TimedOut => Labels {
+            en: "Timed out",
+            ja: "タイムアウト",
+        },
+        RunConclusion::ActionRequired => Labels {
+            en: "Action required",
+            ja: "要対応",
+        },
+        RunConclusion::Neutral => Labels {
+            en: "Neutral",
+            ja: "ニュートラル",
+        },
+        RunConclusion::Stale => Labels {
+            en: "Stale",
+            ja: "期限切れ",
+        },
+        RunConclusion::StartupFailure => Labels {
+            en: "Startup failure",
+            ja: "起動失敗",
+        },
+        RunConclusion::Unknown => Labels {
+            en: "Unknown",
+            ja: "不明",
+        },
+    }
+}
+
+/// The label of a run's state: its conclusion once it has one, else its status.
+#[must_use]
+pub fn status_label(
+    status: RunStatus,
+    conclusion: Option<RunConclusion>,
+    locale: Locale,
+) -> &'static str {
+    match conclusion {
+        Some(conclusion) => conclusion_labels(conclusion).get(locale),
+        None => status_labels(status).get(locale),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_runs_are_labeled_in_japanese() {
+        assert_eq!(
+            status_label(
+                RunStatus::Completed,
+                Some(RunConclusion::Success),
+                Locale::Ja
+            ),
+            "成功"
+        );
+        assert_eq!(
+            status_label(
+                RunStatus::Completed,
+                Some(RunConclusion::Failure),
+                Locale::Ja
+            ),
+            "失敗"
+        );
+        assert_eq!(
+            status_label(RunStatus::InProgress, None, Locale::Ja),
+            "実行中"
+        );
+    }
+
+    #[test]
+    fn test_every_status_and_conclusion_has_an_english_label() {
+        for status in RunStatus::ALL {
+            assert!(!status_label(status, None, Locale::En).is_empty());
+        }
+        for conclusion in RunConclusion::ALL {
+            assert!(!status_label(RunStatus::Completed, Some(conclusion), Locale::En).is_empty());
+        }
+        assert_eq!(
+            status_label(
+                RunStatus::Completed,
+                Some(RunConclusion::TimedOut),
+                Locale::En
+            ),
+            "Timed out"
+        );
+    }
+
+    #[test]
+    fn test_unknown_languages_fall_back_to_english() {
+        assert_eq!(Locale::negotiate(Some("ja"), Some("en")), Some(Locale::Ja));
+        assert_eq!(Locale::negotiate(Some("fr"), Some("ja")), Some(Locale::En));
+        assert_eq!(
+            Locale::negotiate(None, Some("fr-FR, ja-JP;q=0.8, en;q=0.9")),
+            Some(Locale::En)
+        );
+        assert_eq!(
+            Locale::negotiate(None, Some("fr-FR, ja-JP;q=0.9, en;q=0.8")),
+            Some(Locale::Ja)
+        );
+        assert_eq!(
+            Locale::negotiate(None, Some("de, ja;q=0")),
+            Some(Locale::En)
+        );
+        assert_eq!(Locale::negotiate(None, None), None);
+    }
+}
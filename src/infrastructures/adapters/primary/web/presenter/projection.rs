@@ -0,0 +1,250 @@
+use super::locale::{Locale, status_label};
+use crate::application::services::PinnedRun;
+use crate::domain::models::ids::RunId;
+use crate::domain::models::run::WorkflowRun;
+use crate::domain::models::status::RunStatus;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+/// `?limit=`, `?fields=` and `?lang=` of the endpoints streaming snapshots.
+#[derive(Deserialize, Debug, Default)]
+pub struct ProjectionQuery {
+    /// Most runs to send, the first ones after sorting and filtering
+    limit: Option<usize>,
+    /// Comma-separated run fields to send, e.g. `repositoryName,status,htmlUrl`
+    fields: Option<String>,
+    /// Language of the `statusLabel` added to each run, e.g. `ja`; takes precedence over
+    /// `Accept-Language`
+    lang: Option<String>,
+}
+
+/// Why a [`ProjectionQuery`] was rejected.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum InvalidProjection {
+    /// `limit=0` would send no run at all
+    #[error("limit must be at least 1")]
+    ZeroLimit,
+    /// `fields` names fields runs do not have
+    #[error("Unknown run fields: {}; valid fields are: {}", .unknown.join(", "), .valid.join(", "))]
+    UnknownFields {
+        unknown: Vec<String>,
+        valid: Vec<String>,
+    },
+}
+
+/// The fields of serialized runs, from their JSON Schema so they cannot drift apart.
+#[must_use]
+pub fn run_fields() -> Vec<String> {
+    let schema = schemars::schema_for!(WorkflowRun);
+    schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .map(|properties| properties.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Which runs of a snapshot a client is sent, which of their fields, in which language
+/// their `statusLabel` is, and which of them it pinned.
+///
+/// The default sends every run in full, without a label; serializers skip the work then.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RunProjection {
+    limit: Option<usize>,
+    fields: Option<BTreeSet<String>>,
+    locale: Option<Locale>,
+    /// Runs marked `pinned: true`, those a websocket client pinned
+    pinned: BTreeSet<PinnedRun>,
+}
+
+impl RunProjection {
+    /// Validates `query` against the fields of [`run_fields`], labeling runs in the language
+    /// of `?lang=` or else `accept_language`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for `limit=0` or fields runs do not have, listing the valid ones.
+    pub fn from_query(
+        query: &ProjectionQuery,
+        accept_language: Option<&str>,
+    ) -> Result<Self, InvalidProjection> {
+        if query.limit == Some(0) {
+            return Err(InvalidProjection::ZeroLimit);
+        }
+        let fields = match query.fields.as_deref() {
+            None => None,
+            Some(fields) => {
+                let valid = run_fields();
+                let fields: BTreeSet<String> = fields
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|field| !field.is_empty())
+                    .map(str::to_string)
+                    .collect();
+                let unknown: Vec<String> = fields
+                    .iter()
+                    .filter(|field| !valid.contains(field))
+                    .cloned()
+                    .collect();
+                if !unknown.is_empty() {
+                    return Err(InvalidProjection::UnknownFields { unknown, valid });
+                }
+                Some(fields)
+            }
+        };
+        Ok(Self {
+            limit: query.limit,
+            fields,
+            locale: Locale::negotiate(query.lang.as_deref(), accept_language),
+            pinned: BTreeSet::new(),
+        })
+    }
+
+    /// Marks `pinned` with `pinned: true` instead of the runs marked so far.
+    pub fn set_pinned(&mut self, pinned: BTreeSet<PinnedRun>) {
+        self.pinned = pinned;
+    }
+
+    /// Whether every run is sent in full, as serialized.
+    #[must_use]
+    pub fn is_full(&self) -> bool {
+        self.limit.is_none() && !self.rewrites_runs()
+    }
+
+    /// Whether serialized runs are changed before they are sent, by selecting fields or
+    /// adding a label or a pin.
+    #[must_use]
+    pub fn rewrites_runs(&self) -> bool {
+        self.fields.is_some() || self.locale.is_some() || !self.pinned.is_empty()
+    }
+
+    /// Whether only some fields of each run are sent.
+    #[must_use]
+    pub fn selects_fields(&self) -> bool {
+        self.fields.is_some()
+    }
+
+    /// Most runs sent.
+    #[must_use]
+    pub fn max_runs(&self) -> usize {
+        self.limit.unwrap_or(usize::MAX)
+    }
+
+    /// The runs sent out of `runs`.
+    #[must_use]
+    pub fn runs<'a>(&self, runs: &'a [WorkflowRun]) -> &'a [WorkflowRun] {
+        &runs[..runs.len().min(self.max_runs())]
+    }
+
+    /// Whether a serialized run is one of the pinned ones.
+    fn is_pinned(&self, run: &serde_json::Map<String, Value>) -> bool {
+        if self.pinned.is_empty() {
+            return false;
+        }
+        let repo = run
+            .get("repositoryName")
+            .and_then(Value::as_str)
+            .and_then(|repo| repo.parse().ok());
+        let run_id = run.get("id").and_then(Value::as_u64).map(RunId);
+        repo.zip(run_id)
+            .is_some_and(|(repo, run_id)| self.pinned.contains(&PinnedRun { repo, run_id }))
+    }
+
+    /// Keeps the requested fields of a serialized run, labels its status, and marks it when
+    /// it is pinned.
+    pub fn project_run(&self, run: &mut Value) {
+        let Value::Object(run) = run else {
+            return;
+        };
+        let pinned = self.is_pinned(run);
+        let label = self.locale.map(|locale| {
+            let status = run
+                .get("status")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            let conclusion = run.get("conclusion").and_then(Value::as_str);
+            status_label(
+                status.parse().unwrap_or(RunStatus::Unknown),
+                conclusion.and_then(|conclusion| conclusion.parse().ok()),
+                locale,
+            )
+        });
+        if let Some(fields) = &self.fields {
+            run.retain(|key, _| fields.contains(key));
+        }
+        if let Some(label) = label {
+            run.insert("statusLabel".to_string(), Value::from(label));
+        }
+        if pinned {
+            run.insert("pinned".to_string(), Value::Bool(true));
+        }
+    }
+
+    /// Trims the `runs` array of a serialized snapshot and projects each run.
+    pub fn project_snapshot(&self, snapshot: &mut Value) {
+        if let Some(Value::Array(runs)) = snapshot.get_mut("runs") {
+            runs.truncate(self.max_runs());
+            for run in runs {
+                self.project_run(run);
+            }
+        }
+    }
+
+    /// Key identifying the projection in entity tags; empty for the default.
+    #[must_use]
+    pub fn etag_suffix(&self) -> String {
+        let mut suffix = String::new();
+        if let Some(limit) = self.limit {
+            let _ = write!(suffix, "-limit{limit}");
+        }
+        if let Some(fields) = &self.fields {
+            let fields: Vec<&str> = fields.iter().map(String::as_str).collect();
+            let _ = write!(suffix, "-fields:{}", fields.join(","));
+        }
+        if let Some(locale) = self.locale {
+            let _ = write!(suffix, "-lang:{}", locale.as_str());
+        }
+        suffix
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(limit: Option<usize>, fields: Option<&str>) -> ProjectionQuery {
+        ProjectionQuery {
+            limit,
+            fields: fields.map(str::to_string),
+            lang: None,
+        }
+    }
+
+    #[test]
+    fn test_unknown_fields_are_rejected_with_the_valid_ones() -> anyhow::Result<()> {
+        let error = RunProjection::from_query(&query(None, Some("status,htmlURL,colour")), None);
+
+        let Err(InvalidProjection::UnknownFields { unknown, valid }) = error else {
+            anyhow::bail!("accepted unknown fields: {error:?}");
+        };
+        assert_eq!(unknown, ["colour", "htmlURL"]);
+        for field in [
+            "repositoryName",
+            "status",
+            "htmlUrl",
+            "updatedAt",
+            "failedJobs",
+        ] {
+            assert!(valid.iter().any(|valid| valid == field), "{field} missing");
+        }
+        assert_eq!(
+            RunProjection::from_query(&query(Some(0), None), None),
+            Err(InvalidProjection::ZeroLimit)
+        );
+        assert!(
+            RunProjection::from_query(&ProjectionQuery::default(), None).is_ok_and(|p| p.is_full())
+        );
+        Ok(())
+    }
+}
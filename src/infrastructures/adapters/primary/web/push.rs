@@ -0,0 +1,149 @@
+use super::auth::Authenticated;
+use super::{AppState, json_error};
+use crate::domain::models::push_subscription::PushSubscription;
+use crate::infrastructures::adapters::secondary::notifications::SubscribeError;
+use axum::{
+    Json,
+    extract::{State, rejection::JsonRejection},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use std::sync::Arc;
+
+/// Body of `GET /push/vapid-public-key`
+#[derive(Serialize, Debug)]
+struct VapidPublicKey<'a> {
+    /// The `applicationServerKey` of `PushManager.subscribe()`, base64url encoded
+    #[serde(rename = "publicKey")]
+    public_key: &'a str,
+}
+
+/// Error of the `/push` routes when no VAPID key is configured
+const WEB_PUSH_DISABLED: &str = "Web Push is disabled, set VAPID_PRIVATE_KEY to enable it";
+
+/// The key browsers subscribe with, for the frontend's service worker.
+#[tracing::instrument(name = "vapid_public_key_handler", skip_all)]
+pub async fn vapid_public_key_handler(State(state): State<Arc<AppState>>) -> Response {
+    match &state.web_push {
+        Some(web_push) => Json(VapidPublicKey {
+            public_key: web_push.public_key(),
+        })
+        .into_response(),
+        None => json_error(StatusCode::NOT_FOUND, WEB_PUSH_DISABLED),
+    }
+}
+
+/// Stores the browser subscription in the body, replacing an earlier one of its endpoint.
+#[tracing::instrument(name = "push_subscribe_handler", skip_all)]
+pub async fn push_subscribe_handler(
+    auth: Authenticated,
+    State(state): State<Arc<AppState>>,
+    subscription: Result<Json<PushSubscription>, JsonRejection>,
+) -> Response {
+    auth.audit("push_subscribe");
+    let Some(web_push) = &state.web_push else {
+        return json_error(StatusCode::NOT_FOUND, WEB_PUSH_DISABLED);
+    };
+    let Json(subscription) = match subscription {
+        Ok(subscription) => subscription,
+        Err(rejection) => return json_error(rejection.status(), &rejection.body_text()),
+    };
+    match web_push.subscribe(&subscription).await {
+        Ok(()) => StatusCode::CREATED.into_response(),
+        Err(e @ SubscribeError::Invalid(_)) => {
+            json_error(StatusCode::UNPROCESSABLE_ENTITY, &e.to_string())
+        }
+        Err(SubscribeError::Store(e)) => {
+            tracing::error!("Failed to store a push subscription: {}", e);
+            json_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to store the subscription",
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::repositories::PushSubscriptionRepository;
+    use crate::infrastructures::adapters::primary::web::create_router;
+    use crate::infrastructures::adapters::secondary::notifications::{VapidKeys, WebPushNotifier};
+    use crate::infrastructures::adapters::secondary::persistence::SqliteRunRepository;
+    use crate::test_support::{StubGitHubApi, app_state};
+    use axum::body::{Body, to_bytes};
+    use axum::http::{Request, header};
+    use base64::Engine;
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    use jwt_simple::algorithms::{ECDSAP256PublicKeyLike, ES256KeyPair};
+    use tower::ServiceExt;
+
+    fn subscribe(body: &serde_json::Value) -> anyhow::Result<Request<Body>> {
+        Ok(Request::post("/push/subscribe")
+            .header(header::AUTHORIZATION, "Bearer secret")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))?)
+    }
+
+    #[tokio::test]
+    async fn test_subscriptions_are_stored_with_their_filter() -> anyhow::Result<()> {
+        let store = Arc::new(SqliteRunRepository::connect("sqlite::memory:")?);
+        let keys = VapidKeys::from_private_key(&VapidKeys::generate_private_key())?;
+        let public_key = keys.public_key().to_string();
+        let state = Arc::into_inner(app_state(StubGitHubApi::default(), Some("secret"))?)
+            .ok_or_else(|| anyhow::anyhow!("state is shared"))?
+            .with_web_push(Arc::new(WebPushNotifier::new(
+                Arc::clone(&store) as _,
+                keys,
+            )));
+        let router = create_router(Arc::new(state));
+        let browser = ES256KeyPair::generate().public_key();
+        let body = serde_json::json!({
+            "endpoint": "https://push.example.com/send/abc",
+            "expirationTime": null,
+            "keys": {
+                "p256dh": URL_SAFE_NO_PAD.encode(browser.public_key().to_bytes_uncompressed()),
+                "auth": URL_SAFE_NO_PAD.encode([1_u8; 16]),
+            },
+            "filter": {"failuresOnly": false, "repos": ["owner/repo"]},
+        });
+
+        let response = router
+            .clone()
+            .oneshot(Request::get("/push/vapid-public-key").body(Body::empty())?)
+            .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        let key: serde_json::Value =
+            serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await?)?;
+        assert_eq!(key["publicKey"], public_key);
+
+        let response = router.clone().oneshot(subscribe(&body)?).await?;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let stored = store.subscriptions().await?;
+        assert_eq!(stored.len(), 1);
+        assert!(!stored[0].filter.failures_only);
+        assert_eq!(stored[0].filter.repos, ["owner/repo".parse()?]);
+
+        let mut invalid = body.clone();
+        invalid["keys"]["p256dh"] = "short".into();
+        let response = router.oneshot(subscribe(&invalid)?).await?;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(store.subscriptions().await?.len(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_push_routes_are_missing_without_vapid_keys() -> anyhow::Result<()> {
+        let router = create_router(app_state(StubGitHubApi::default(), None)?);
+
+        let response = router
+            .clone()
+            .oneshot(Request::get("/push/vapid-public-key").body(Body::empty())?)
+            .await?;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let response = router.oneshot(subscribe(&serde_json::json!({}))?).await?;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        Ok(())
+    }
+}
@@ -0,0 +1,136 @@
+use super::presenter::{TimestampQuery, queue_json};
+use super::{AppState, json_error};
+use axum::{
+    extract::{Query, State, rejection::QueryRejection},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use std::sync::Arc;
+
+/// Queued runs of every polled repository in the latest snapshot.
+#[tracing::instrument(name = "queue_handler", skip(state))]
+pub async fn queue_handler(
+    timestamps: Result<Query<TimestampQuery>, QueryRejection>,
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    let timestamps = match timestamps {
+        Ok(Query(timestamps)) => timestamps.or(state.timestamp_format),
+        Err(rejection) => return json_error(rejection.status(), &rejection.body_text()),
+    };
+    let Some(latest) = state.snapshots.latest() else {
+        return json_error(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "No snapshot has been fetched yet",
+        );
+    };
+
+    match queue_json(&latest, timestamps) {
+        Ok(body) => ([(header::CONTENT_TYPE, "application/json")], body).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to serialize queue: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::use_cases::stream_github_actions_runs::StreamGitHubActionsRunsUseCaseOutput;
+    use crate::domain::external_apis::github::Repository;
+    use crate::domain::models::run::WorkflowRun;
+    use crate::infrastructures::adapters::primary::web::create_router;
+    use crate::test_support::{StubGitHubApi, app_state, base_time, repo_full_name, workflow_run};
+    use axum::body::{Body, to_bytes};
+    use axum::http::Request;
+    use chrono::TimeDelta;
+    use tower::ServiceExt;
+
+    async fn get(state: Arc<AppState>, uri: &str) -> anyhow::Result<(StatusCode, String)> {
+        let response = create_router(state)
+            .oneshot(Request::get(uri).body(Body::empty())?)
+            .await?;
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await?;
+        Ok((status, String::from_utf8(body.to_vec())?))
+    }
+
+    /// A state whose snapshot polled `owner/repo` three hours after `base_time()`.
+    fn state_with_queued(queued_runs: Vec<WorkflowRun>) -> anyhow::Result<Arc<AppState>> {
+        let state = app_state(StubGitHubApi::default(), None)?;
+        state.poller.publish(
+            StreamGitHubActionsRunsUseCaseOutput::new(
+                Vec::new(),
+                base_time() + TimeDelta::hours(3),
+            )
+            .with_repositories(vec![Repository::from_full_name(&repo_full_name(
+                "owner/repo",
+            ))])
+            .with_queued_runs(queued_runs),
+        );
+        Ok(state)
+    }
+
+    async fn queue(state: Arc<AppState>) -> anyhow::Result<serde_json::Value> {
+        let (status, body) = get(state, "/queue").await?;
+        assert_eq!(status, StatusCode::OK);
+        Ok(serde_json::from_str::<serde_json::Value>(&body)?["repositories"][0].clone())
+    }
+
+    #[tokio::test]
+    async fn test_an_empty_queue_reads_as_zero() -> anyhow::Result<()> {
+        let state = state_with_queued(Vec::new())?;
+
+        let repository = queue(Arc::clone(&state)).await?;
+        let (_, metrics) = get(state, "/metrics").await?;
+
+        assert_eq!(repository["repository"], "owner/repo");
+        assert_eq!(repository["queued"], 0);
+        assert_eq!(repository["oldestAgeSeconds"], serde_json::Value::Null);
+        assert_eq!(repository["runIds"], serde_json::json!([]));
+        assert!(metrics.contains(r#"workflow_runs_queued{repository="owner/repo"} 0"#));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_a_single_queued_run_is_aged_from_its_creation() -> anyhow::Result<()> {
+        let state = state_with_queued(vec![workflow_run("owner/repo", 30, "queued")])?;
+
+        let repository = queue(state).await?;
+
+        assert_eq!(repository["queued"], 1);
+        assert_eq!(repository["oldestAgeSeconds"], 150 * 60);
+        assert_eq!(repository["runIds"], serde_json::json!([30]));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_many_queued_runs_are_listed_longest_waiting_first() -> anyhow::Result<()> {
+        let state = state_with_queued(
+            (1..=150)
+                .rev()
+                .map(|id| workflow_run("owner/repo", id, "queued"))
+                .collect(),
+        )?;
+
+        let repository = queue(Arc::clone(&state)).await?;
+        let (_, metrics) = get(state, "/metrics").await?;
+
+        assert_eq!(repository["queued"], 150);
+        assert_eq!(repository["oldestAgeSeconds"], 179 * 60);
+        assert_eq!(repository["runIds"][0], 1);
+        assert_eq!(repository["runIds"][149], 150);
+        assert!(metrics.contains(r#"workflow_runs_queued{repository="owner/repo"} 150"#));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_queue_is_unavailable_before_the_first_snapshot() -> anyhow::Result<()> {
+        let state = app_state(StubGitHubApi::default(), None)?;
+
+        let (status, _) = get(state, "/queue").await?;
+
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        Ok(())
+    }
+}
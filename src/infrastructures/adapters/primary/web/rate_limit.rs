@@ -0,0 +1,382 @@
+use super::AppState;
+use super::auth::constant_time_eq;
+use super::client_ip::client_ip;
+use axum::{
+    Json,
+    body::Body,
+    extract::{MatchedPath, NestedPath, State},
+    http::{Request, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio::time::{Duration, Instant};
+
+/// Number of tracked buckets above which full, idle buckets are discarded
+const MAX_TRACKED_BUCKETS: usize = 10_000;
+
+/// Token bucket parameters for a group of routes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RouteBudget {
+    /// Maximum burst size
+    pub capacity: f64,
+    /// Tokens regained per second
+    pub refill_per_second: f64,
+}
+
+/// Generous budget for snapshot reads and stream connections
+const DEFAULT_BUDGET: RouteBudget = RouteBudget {
+    capacity: 60.0,
+    refill_per_second: 1.0,
+};
+
+/// Tight budget for routes that trigger GitHub API calls
+const EXPENSIVE_BUDGET: RouteBudget = RouteBudget {
+    capacity: 3.0,
+    refill_per_second: 0.1,
+};
+
+/// Returns the budget of a route template, or `None` for routes that are never limited.
+fn route_budget(route: &str) -> Option<RouteBudget> {
+    match route {
+        // Webhook deliveries are signed and may arrive in bursts from GitHub's few addresses
+        "/health" | "/ready" | "/metrics" | "/webhooks/github" => None,
+        "/refresh" => Some(EXPENSIVE_BUDGET),
+        _ => Some(DEFAULT_BUDGET),
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TokenBucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl TokenBucket {
+    fn full(budget: RouteBudget, now: Instant) -> Self {
+        Self {
+            tokens: budget.capacity,
+            updated_at: now,
+        }
+    }
+
+    fn refill(&mut self, budget: RouteBudget, now: Instant) {
+        let elapsed = now.duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * budget.refill_per_second).min(budget.capacity);
+        self.updated_at = now;
+    }
+
+    /// Takes a token, or returns how long until one is available.
+    fn try_take(&mut self, budget: RouteBudget, now: Instant) -> Result<(), Duration> {
+        self.refill(budget, now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - self.tokens) / budget.refill_per_second,
+            ))
+        }
+    }
+}
+
+/// Per-client token buckets keyed by route and client identity.
+#[derive(Default)]
+pub struct HttpRateLimiter {
+    buckets: Mutex<HashMap<(String, String), TokenBucket>>,
+}
+
+impl HttpRateLimiter {
+    async fn check(
+        &self,
+        route: &str,
+        client: &str,
+        budget: RouteBudget,
+        now: Instant,
+    ) -> Result<(), Duration> {
+        let mut buckets = self.buckets.lock().await;
+        if buckets.len() >= MAX_TRACKED_BUCKETS {
+            // A refilled bucket behaves exactly like a new one, so dropping it is safe
+            buckets.retain(|(route, _), bucket| {
+                let budget = route_budget(route).unwrap_or(DEFAULT_BUDGET);
+                bucket.refill(budget, now);
+                bucket.tokens < budget.capacity
+            });
+        }
+        buckets
+            .entry((route.to_string(), client.to_string()))
+            .or_insert_with(|| TokenBucket::full(budget, now))
+            .try_take(budget, now)
+    }
+}
+
+/// Identifies the client by its bearer token when it is the configured `AUTH_TOKEN`,
+/// otherwise by IP, so made-up tokens cannot each claim a fresh bucket.
+fn client_key(request: &Request<Body>, trust_proxy: bool, auth_token: Option<&str>) -> String {
+    let token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    if let (Some(token), Some(expected)) = (token, auth_token)
+        && constant_time_eq(token.as_bytes(), expected.as_bytes())
+    {
+        // Keep only a hash so raw tokens never sit in memory longer than the request
+        let digest = Sha256::digest(token.as_bytes());
+        return format!("token:{digest:x}");
+    }
+    client_ip(request, trust_proxy).map_or_else(|| "unknown".to_string(), |ip| format!("ip:{ip}"))
+}
+
+#[derive(Serialize, Debug)]
+struct RetryAfterBody<'a> {
+    error: &'a str,
+    #[serde(rename = "retryAfterSeconds")]
+    retry_after_seconds: u64,
+}
+
+/// Builds a 429 response with `Retry-After` rounded up to whole seconds.
+pub(crate) fn too_many_requests(wait: Duration, message: &str) -> Response {
+    retry_after(StatusCode::TOO_MANY_REQUESTS, wait, message)
+}
+
+/// Builds a `{"error", "retryAfterSeconds"}` response with a matching `Retry-After` header.
+pub(crate) fn retry_after(status: StatusCode, wait: Duration, message: &str) -> Response {
+    // Round up so clients never retry before the window has passed
+    let retry_after_seconds = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+    (
+        status,
+        [(header::RETRY_AFTER, retry_after_seconds.to_string())],
+        Json(RetryAfterBody {
+            error: message,
+            retry_after_seconds,
+        }),
+    )
+        .into_response()
+}
+
+/// The matched route template without the prefix the router is nested under, so budgets and
+/// metric labels do not depend on where a host mounts the dashboard.
+pub(super) fn route_template(request: &Request<Body>) -> String {
+    let extensions = request.extensions();
+    let Some(matched) = extensions.get::<MatchedPath>() else {
+        return request.uri().path().to_string();
+    };
+    extensions
+        .get::<NestedPath>()
+        .and_then(|nested| matched.as_str().strip_prefix(nested.as_str()))
+        .unwrap_or(matched.as_str())
+        .to_string()
+}
+
+/// Middleware enforcing the per-route, per-client request budgets.
+pub async fn rate_limit_middleware(
+    State(state): State<Arc<AppState>>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let route = route_template(&request);
+    let Some(budget) = route_budget(&route) else {
+        return next.run(request).await;
+    };
+
+    let client = client_key(&request, state.trust_proxy, state.auth_token.as_deref());
+    match state
+        .rate_limiter
+        .check(&route, &client, budget, Instant::now())
+        .await
+    {
+        Ok(()) => next.run(request).await,
+        Err(wait) => {
+            tracing::warn!("Throttled request to {} from {}", route, client);
+            state
+                .metrics
+                .http_requests_throttled
+                .with_label_values(&[route.as_str()])
+                .inc();
+            too_many_requests(wait, "Too many requests")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructures::adapters::primary::web::create_router;
+    use crate::test_support::{StubGitHubApi, app_state};
+    use axum::Router;
+    use axum::extract::ConnectInfo;
+    use std::net::SocketAddr;
+    use tower::ServiceExt;
+
+    const BUDGET: RouteBudget = RouteBudget {
+        capacity: 2.0,
+        refill_per_second: 1.0,
+    };
+
+    #[tokio::test]
+    async fn test_bucket_allows_burst_then_throttles() {
+        let limiter = HttpRateLimiter::default();
+        let now = Instant::now();
+
+        assert!(limiter.check("/runs", "a", BUDGET, now).await.is_ok());
+        assert!(limiter.check("/runs", "a", BUDGET, now).await.is_ok());
+        let wait = limiter.check("/runs", "a", BUDGET, now).await;
+
+        assert_eq!(wait, Err(Duration::from_secs(1)));
+    }
+
+    #[tokio::test]
+    async fn test_bucket_refills_over_time() {
+        let limiter = HttpRateLimiter::default();
+        let now = Instant::now();
+        for _ in 0..2 {
+            assert!(limiter.check("/runs", "a", BUDGET, now).await.is_ok());
+        }
+
+        // Half a second refills half a token: still throttled
+        let half = now + Duration::from_millis(500);
+        assert!(limiter.check("/runs", "a", BUDGET, half).await.is_err());
+        // After a full second one token is available again, but only one
+        let later = now + Duration::from_secs(1);
+        assert!(limiter.check("/runs", "a", BUDGET, later).await.is_ok());
+        assert!(limiter.check("/runs", "a", BUDGET, later).await.is_err());
+        // Refill never exceeds capacity
+        let much_later = now + Duration::from_mins(1);
+        assert!(
+            limiter
+                .check("/runs", "a", BUDGET, much_later)
+                .await
+                .is_ok()
+        );
+        assert!(
+            limiter
+                .check("/runs", "a", BUDGET, much_later)
+                .await
+                .is_ok()
+        );
+        assert!(
+            limiter
+                .check("/runs", "a", BUDGET, much_later)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_buckets_are_independent_per_client_and_route() {
+        let limiter = HttpRateLimiter::default();
+        let now = Instant::now();
+        for _ in 0..2 {
+            assert!(limiter.check("/runs", "a", BUDGET, now).await.is_ok());
+        }
+
+        assert!(limiter.check("/runs", "b", BUDGET, now).await.is_ok());
+        assert!(limiter.check("/badge", "a", BUDGET, now).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_throttled_route_returns_429_and_counts_metric() -> anyhow::Result<()> {
+        let state = app_state(StubGitHubApi::default(), None)?;
+        let router = create_router(Arc::clone(&state));
+        let request = || -> anyhow::Result<Request<Body>> {
+            let mut request = Request::post("/refresh").body(Body::empty())?;
+            request
+                .extensions_mut()
+                .insert(ConnectInfo("192.0.2.1:4000".parse::<SocketAddr>()?));
+            Ok(request)
+        };
+
+        let mut last = None;
+        // One more request than the burst allowed by `EXPENSIVE_BUDGET`
+        for _ in 0..4 {
+            last = Some(router.clone().oneshot(request()?).await?);
+        }
+        let response = last.ok_or_else(|| anyhow::anyhow!("no response"))?;
+
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(response.headers().contains_key(header::RETRY_AFTER));
+        let body: serde_json::Value =
+            serde_json::from_slice(&axum::body::to_bytes(response.into_body(), usize::MAX).await?)?;
+        assert_eq!(body["error"], "Too many requests");
+        assert_eq!(
+            state
+                .metrics
+                .http_requests_throttled
+                .with_label_values(&["/refresh"])
+                .get(),
+            1
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_nested_routes_keep_their_budgets() -> anyhow::Result<()> {
+        let state = app_state(StubGitHubApi::default(), None)?;
+        let router = Router::new().nest("/gha", create_router(Arc::clone(&state)));
+        let request = |path: &str| -> anyhow::Result<Request<Body>> {
+            let mut request = Request::post(path).body(Body::empty())?;
+            request
+                .extensions_mut()
+                .insert(ConnectInfo("192.0.2.1:4000".parse::<SocketAddr>()?));
+            Ok(request)
+        };
+
+        let mut statuses = Vec::new();
+        for _ in 0..4 {
+            statuses.push(
+                router
+                    .clone()
+                    .oneshot(request("/gha/refresh")?)
+                    .await?
+                    .status(),
+            );
+        }
+
+        assert_eq!(statuses.last(), Some(&StatusCode::TOO_MANY_REQUESTS));
+        assert_eq!(
+            state
+                .metrics
+                .http_requests_throttled
+                .with_label_values(&["/refresh"])
+                .get(),
+            1
+        );
+        Ok(())
+    }
+
+    fn request_with_token(token: &str) -> anyhow::Result<Request<Body>> {
+        let mut request = Request::get("/runs")
+            .header(header::AUTHORIZATION, format!("Bearer {token}"))
+            .body(Body::empty())?;
+        request
+            .extensions_mut()
+            .insert(ConnectInfo("192.0.2.1:4000".parse::<SocketAddr>()?));
+        Ok(request)
+    }
+
+    #[test]
+    fn test_client_key_ignores_unverified_tokens() -> anyhow::Result<()> {
+        let first = client_key(&request_with_token("made-up-1")?, false, Some("secret"));
+        let second = client_key(&request_with_token("made-up-2")?, false, Some("secret"));
+        let without_auth = client_key(&request_with_token("made-up-3")?, false, None);
+
+        assert_eq!(first, "ip:192.0.2.1");
+        assert_eq!(second, first);
+        assert_eq!(without_auth, first);
+        Ok(())
+    }
+
+    #[test]
+    fn test_client_key_gives_the_valid_token_its_own_bucket() -> anyhow::Result<()> {
+        let key = client_key(&request_with_token("secret")?, false, Some("secret"));
+
+        assert!(key.starts_with("token:"));
+        assert!(!key.contains("secret"));
+        Ok(())
+    }
+}
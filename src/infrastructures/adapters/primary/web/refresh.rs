@@ -0,0 +1,159 @@
+use super::AppState;
+use super::auth::Authenticated;
+use super::rate_limit::too_many_requests;
+use axum::{
+    Json,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio::time::{Duration, Instant};
+
+/// Minimum interval between two accepted refresh requests (seconds)
+pub const REFRESH_MIN_INTERVAL_SECONDS: u64 = 10;
+
+/// Global limiter allowing at most one refresh per interval across all clients.
+pub struct RefreshRateLimiter {
+    min_interval: Duration,
+    last_accepted: Mutex<Option<Instant>>,
+}
+
+impl RefreshRateLimiter {
+    #[must_use]
+    pub fn new(min_interval: Duration) -> Self {
+        Self {
+            min_interval,
+            last_accepted: Mutex::new(None),
+        }
+    }
+
+    /// Records an accepted refresh, or returns how long the caller must wait.
+    async fn try_acquire(&self) -> Result<(), Duration> {
+        let mut last_accepted = self.last_accepted.lock().await;
+        let now = Instant::now();
+        if let Some(last) = *last_accepted
+            && let Some(wait) = self.min_interval.checked_sub(now.duration_since(last))
+            && !wait.is_zero()
+        {
+            return Err(wait);
+        }
+        *last_accepted = Some(now);
+        Ok(())
+    }
+}
+
+impl Default for RefreshRateLimiter {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(REFRESH_MIN_INTERVAL_SECONDS))
+    }
+}
+
+#[derive(Serialize, Debug)]
+struct RefreshAccepted {
+    /// Lower bound of the `generatedAt` of the snapshot produced by this refresh
+    #[serde(rename = "generatedAt")]
+    generated_at: DateTime<Utc>,
+}
+
+#[tracing::instrument(name = "refresh_handler", skip_all)]
+pub async fn refresh_handler(auth: Authenticated, State(state): State<Arc<AppState>>) -> Response {
+    auth.audit("refresh");
+    match state.refresh_limiter.try_acquire().await {
+        Ok(()) => {
+            tracing::info!("Refresh requested");
+            state.refresh.notify_one();
+            (
+                StatusCode::ACCEPTED,
+                Json(RefreshAccepted {
+                    generated_at: Utc::now(),
+                }),
+            )
+                .into_response()
+        }
+        Err(wait) => {
+            tracing::debug!("Refresh throttled for {:?}", wait);
+            too_many_requests(wait, "Refresh was requested too recently")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructures::adapters::primary::web::create_router;
+    use crate::test_support::{self, StubGitHubApi};
+    use axum::body::Body;
+    use axum::http::{Request, header};
+    use tower::ServiceExt;
+
+    fn app_state(auth_token: Option<&str>) -> anyhow::Result<Arc<AppState>> {
+        test_support::app_state(StubGitHubApi::default(), auth_token)
+    }
+
+    fn refresh_request(token: Option<&str>) -> anyhow::Result<Request<Body>> {
+        let mut builder = Request::post("/refresh");
+        if let Some(token) = token {
+            builder = builder.header(header::AUTHORIZATION, format!("Bearer {token}"));
+        }
+        Ok(builder.body(Body::empty())?)
+    }
+
+    #[tokio::test]
+    async fn test_refresh_is_accepted_and_notifies_poller() -> anyhow::Result<()> {
+        let state = app_state(Some("secret"))?;
+        let router = create_router(Arc::clone(&state));
+
+        let response = router.oneshot(refresh_request(Some("secret"))?).await?;
+
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        // The stored permit makes `notified()` resolve immediately
+        tokio::time::timeout(Duration::from_secs(1), state.refresh.notified()).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_second_refresh_within_interval_is_rejected_with_retry_after() -> anyhow::Result<()>
+    {
+        let router = create_router(app_state(None)?);
+
+        let first = router.clone().oneshot(refresh_request(None)?).await?;
+        let second = router.oneshot(refresh_request(None)?).await?;
+
+        assert_eq!(first.status(), StatusCode::ACCEPTED);
+        assert_eq!(second.status(), StatusCode::TOO_MANY_REQUESTS);
+        let retry_after: u64 = second
+            .headers()
+            .get(header::RETRY_AFTER)
+            .ok_or_else(|| anyhow::anyhow!("missing Retry-After"))?
+            .to_str()?
+            .parse()?;
+        assert!((1..=REFRESH_MIN_INTERVAL_SECONDS).contains(&retry_after));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_refresh_without_token_is_unauthorized() -> anyhow::Result<()> {
+        let router = create_router(app_state(Some("secret"))?);
+
+        let missing = router.clone().oneshot(refresh_request(None)?).await?;
+        let wrong = router.oneshot(refresh_request(Some("wrong"))?).await?;
+
+        assert_eq!(missing.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(wrong.status(), StatusCode::UNAUTHORIZED);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter_allows_again_after_interval() {
+        let limiter = RefreshRateLimiter::new(Duration::from_millis(20));
+
+        assert!(limiter.try_acquire().await.is_ok());
+        assert!(limiter.try_acquire().await.is_err());
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(limiter.try_acquire().await.is_ok());
+    }
+}
@@ -0,0 +1,118 @@
+use super::presenter::{TimestampQuery, runners_json};
+use super::{AppState, json_error};
+use axum::{
+    extract::{Query, State, rejection::QueryRejection},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use std::sync::Arc;
+
+/// Self-hosted runners of the polled repositories as of the last inventory.
+#[tracing::instrument(name = "runners_handler", skip(state))]
+pub async fn runners_handler(
+    timestamps: Result<Query<TimestampQuery>, QueryRejection>,
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    let timestamps = match timestamps {
+        Ok(Query(timestamps)) => timestamps.or(state.timestamp_format),
+        Err(rejection) => return json_error(rejection.status(), &rejection.body_text()),
+    };
+    let Some(fleet) = state.runner_inventory.fleet() else {
+        return json_error(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "No runner inventory has been taken yet",
+        );
+    };
+
+    match runners_json(&fleet, timestamps) {
+        Ok(body) => ([(header::CONTENT_TYPE, "application/json")], body).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to serialize runners: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::ids::RunnerId;
+    use crate::domain::models::runner::{RunnerStatus, SelfHostedRunner};
+    use crate::infrastructures::adapters::primary::web::create_router;
+    use crate::test_support::{StubGitHubApi, app_state, base_time, repo_full_name};
+    use axum::body::{Body, to_bytes};
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    async fn get(state: Arc<AppState>, uri: &str) -> anyhow::Result<(StatusCode, String)> {
+        let response = create_router(state)
+            .oneshot(Request::get(uri).body(Body::empty())?)
+            .await?;
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await?;
+        Ok((status, String::from_utf8(body.to_vec())?))
+    }
+
+    fn runner(id: u64, status: RunnerStatus, busy: bool) -> SelfHostedRunner {
+        SelfHostedRunner {
+            id: RunnerId(id),
+            name: format!("build-{id}"),
+            os: "Linux".to_string(),
+            status,
+            busy,
+            labels: vec!["self-hosted".to_string(), "linux".to_string()],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_runners_are_counted_per_repository() -> anyhow::Result<()> {
+        let state = app_state(
+            StubGitHubApi {
+                runners: vec![
+                    runner(2, RunnerStatus::Offline, false),
+                    runner(1, RunnerStatus::Online, true),
+                ],
+                ..StubGitHubApi::default()
+            },
+            None,
+        )?;
+        state
+            .runner_inventory
+            .refresh(&[repo_full_name("owner/repo")], base_time())
+            .await;
+
+        let (status, body) = get(Arc::clone(&state), "/runners?ts=rfc3339").await?;
+        let (_, metrics) = get(state, "/metrics").await?;
+
+        assert_eq!(status, StatusCode::OK);
+        let body: serde_json::Value = serde_json::from_str(&body)?;
+        let repository = &body["repositories"][0];
+        assert_eq!(repository["repository"], "owner/repo");
+        assert_eq!(
+            (
+                &repository["online"],
+                &repository["offline"],
+                &repository["busy"]
+            ),
+            (&1.into(), &1.into(), &1.into())
+        );
+        assert_eq!(repository["runners"][0]["name"], "build-1");
+        assert_eq!(repository["runners"][1]["status"], "offline");
+        assert_eq!(body["updatedAt"], "2024-08-01T10:00:00Z");
+        assert!(
+            metrics.contains(r#"selfhosted_runners{repository="owner/repo",status="offline"} 1"#)
+        );
+        assert!(metrics.contains(r#"selfhosted_runners{repository="owner/repo",status="busy"} 1"#));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_runners_are_unavailable_before_the_first_inventory() -> anyhow::Result<()> {
+        let state = app_state(StubGitHubApi::default(), None)?;
+
+        let (status, _) = get(state, "/runners").await?;
+
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        Ok(())
+    }
+}
@@ -0,0 +1,817 @@
+use super::presenter::projection::RunProjection;
+use super::presenter::{
+    TimestampFormat, TimestampQuery, bot_group_json, grouped_snapshot_json, projected_run_json,
+    projected_snapshot_json,
+};
+use super::{AppState, RequestedProjection, json_error};
+use crate::domain::models::ids::RepoFullName;
+use crate::domain::models::run::WorkflowRun;
+use axum::{
+    extract::{Query, State, rejection::QueryRejection},
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use chrono::SecondsFormat;
+use serde::Deserialize;
+use std::sync::Arc;
+
+const CONTENT_TYPE_CSV: &str = "text/csv";
+const CONTENT_TYPE_NDJSON: &str = "application/x-ndjson";
+
+/// Column order of the CSV representation. Changing it breaks spreadsheets
+/// built on top of the export, so only ever append new columns.
+const CSV_COLUMNS: [&str; 12] = [
+    "repositoryName",
+    "id",
+    "workflowName",
+    "displayTitle",
+    "event",
+    // Holds the display status, the combined status and conclusion
+    "status",
+    "createdAt",
+    "updatedAt",
+    "htmlUrl",
+    "runNumber",
+    "headBranch",
+    "headSha",
+];
+
+/// Representation of the snapshot returned by `GET /runs`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RunsFormat {
+    Json,
+    /// JSON with runs nested under their repository, selected with `?group=repository`
+    GroupedJson,
+    Csv,
+    Ndjson,
+}
+
+impl RunsFormat {
+    /// Suffix distinguishing the entity tags of the different representations of one snapshot.
+    fn etag_suffix(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::GroupedJson => "grouped-json",
+            Self::Csv => "csv",
+            Self::Ndjson => "ndjson",
+        }
+    }
+
+    fn from_query(format: &str) -> Option<Self> {
+        match format {
+            "json" => Some(Self::Json),
+            "csv" => Some(Self::Csv),
+            "ndjson" => Some(Self::Ndjson),
+            _ => None,
+        }
+    }
+
+    /// Picks the first supported media type listed in the `Accept` header.
+    fn from_accept(headers: &HeaderMap) -> Self {
+        headers
+            .get_all(header::ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .filter_map(|media_range| media_range.split(';').next())
+            .find_map(|media_type| match media_type.trim() {
+                CONTENT_TYPE_CSV => Some(Self::Csv),
+                CONTENT_TYPE_NDJSON => Some(Self::Ndjson),
+                "application/json" => Some(Self::Json),
+                _ => None,
+            })
+            .unwrap_or(Self::Json)
+    }
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct RunsQuery {
+    /// Overrides the `Accept` header: `json`, `csv` or `ndjson`
+    format: Option<String>,
+    /// `repository` nests runs under the repository they belong to (JSON only)
+    group: Option<String>,
+}
+
+/// Quotes a CSV field per RFC 4180 when it contains a delimiter, quote or line break.
+fn escape_csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn runs_to_csv(runs: &[WorkflowRun]) -> String {
+    let mut csv = CSV_COLUMNS.join(",");
+    csv.push_str("\r\n");
+    for run in runs {
+        let fields = [
+            run.repository_name.to_string(),
+            run.id.to_string(),
+            run.workflow_name.clone(),
+            run.display_title.clone(),
+            run.event.clone(),
+            run.display_status().to_string(),
+            run.created_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+            run.updated_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+            run.html_url.clone(),
+            run.run_number.to_string(),
+            run.head_branch.clone().unwrap_or_default(),
+            run.head_sha.clone(),
+        ];
+        let row: Vec<String> = fields.iter().map(|field| escape_csv_field(field)).collect();
+        csv.push_str(&row.join(","));
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+/// Returns true when an `If-None-Match` header matches `etag` (weak comparison, per RFC 9110).
+fn if_none_match(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == etag)
+}
+
+fn runs_to_ndjson(
+    runs: &[WorkflowRun],
+    timestamps: TimestampFormat,
+    projection: &RunProjection,
+) -> Result<String, serde_json::Error> {
+    let mut ndjson = String::new();
+    for run in projection.runs(runs) {
+        ndjson.push_str(&projected_run_json(run, timestamps, projection)?);
+        ndjson.push('\n');
+    }
+    Ok(ndjson)
+}
+
+/// Entity tag of one representation of the snapshot with `digest`.
+fn etag(
+    digest: &str,
+    format: RunsFormat,
+    timestamps: TimestampFormat,
+    projection: &RunProjection,
+) -> String {
+    // CSV always writes RFC 3339, so only JSON representations differ by timestamp format
+    let millis = match (format, timestamps) {
+        (RunsFormat::Csv, _) | (_, TimestampFormat::Rfc3339) => "",
+        (_, TimestampFormat::Millis) => "-millis",
+    };
+    format!(
+        "\"{digest}-{}{millis}{}\"",
+        format.etag_suffix(),
+        projection.etag_suffix()
+    )
+}
+
+#[tracing::instrument(name = "runs_handler", skip(state, headers))]
+pub async fn runs_handler(
+    Query(query): Query<RunsQuery>,
+    timestamps: Result<Query<TimestampQuery>, QueryRejection>,
+    RequestedProjection(projection): RequestedProjection,
+    headers: HeaderMap,
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    let timestamps = match timestamps {
+        Ok(Query(timestamps)) => timestamps.or(state.timestamp_format),
+        Err(rejection) => return json_error(rejection.status(), &rejection.body_text()),
+    };
+    let format = match query.format.as_deref() {
+        Some(format) => match RunsFormat::from_query(format) {
+            Some(format) => format,
+            None => {
+                return json_error(
+                    StatusCode::BAD_REQUEST,
+                    "Unsupported format, expected one of: json, csv, ndjson",
+                );
+            }
+        },
+        None => RunsFormat::from_accept(&headers),
+    };
+    let format = match (query.group.as_deref(), format) {
+        (None, format) => format,
+        (Some("repository"), RunsFormat::Json) => RunsFormat::GroupedJson,
+        (Some("repository"), _) => {
+            return json_error(
+                StatusCode::BAD_REQUEST,
+                "Grouping by repository is only supported for json",
+            );
+        }
+        (Some(_), _) => {
+            return json_error(
+                StatusCode::BAD_REQUEST,
+                "Unsupported group, expected: repository",
+            );
+        }
+    };
+
+    // CSV columns are fixed, so spreadsheets built on the export keep working
+    if format == RunsFormat::Csv && projection.selects_fields() {
+        return json_error(
+            StatusCode::BAD_REQUEST,
+            "Selecting fields is only supported for json and ndjson",
+        );
+    }
+
+    let Some(latest) = state.snapshots.latest_with_digest() else {
+        return json_error(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "No snapshot has been fetched yet",
+        );
+    };
+
+    let etag = etag(&latest.digest, format, timestamps, &projection);
+    let cache_headers = [
+        (header::ETAG, etag.clone()),
+        (
+            header::VARY,
+            format!("{}, {}", header::ACCEPT, header::ACCEPT_LANGUAGE),
+        ),
+    ];
+    if if_none_match(&headers, &etag) {
+        return (StatusCode::NOT_MODIFIED, cache_headers).into_response();
+    }
+
+    let snapshot = match &state.anonymizer {
+        Some(anonymizer) => Arc::new(anonymizer.snapshot(&latest.output)),
+        None => latest.output,
+    };
+    let response = match format {
+        RunsFormat::Json | RunsFormat::GroupedJson => {
+            let body = if format == RunsFormat::Json {
+                projected_snapshot_json(&snapshot, timestamps, &projection)
+            } else {
+                grouped_snapshot_json(&snapshot, timestamps, &projection)
+            };
+            match body {
+                Ok(body) => ([(header::CONTENT_TYPE, "application/json")], body).into_response(),
+                Err(e) => {
+                    tracing::error!("Failed to serialize output: {:?}", e);
+                    return json_error(StatusCode::INTERNAL_SERVER_ERROR, "Serialization error");
+                }
+            }
+        }
+        RunsFormat::Csv => (
+            [(header::CONTENT_TYPE, "text/csv; charset=utf-8")],
+            runs_to_csv(projection.runs(&snapshot.runs)),
+        )
+            .into_response(),
+        RunsFormat::Ndjson => match runs_to_ndjson(&snapshot.runs, timestamps, &projection) {
+            Ok(body) => ([(header::CONTENT_TYPE, CONTENT_TYPE_NDJSON)], body).into_response(),
+            Err(e) => {
+                tracing::error!("Failed to serialize output: {:?}", e);
+                return json_error(StatusCode::INTERNAL_SERVER_ERROR, "Serialization error");
+            }
+        },
+    };
+    (cache_headers, response).into_response()
+}
+
+/// Parameters of `GET /runs/bot-group`.
+#[derive(Deserialize, Debug)]
+pub struct BotGroupQuery {
+    repo: RepoFullName,
+}
+
+/// Runs of the bot group of a repository in the latest snapshot, newest first.
+#[tracing::instrument(name = "bot_group_handler", skip(state))]
+pub async fn bot_group_handler(
+    query: Result<Query<BotGroupQuery>, QueryRejection>,
+    timestamps: Result<Query<TimestampQuery>, QueryRejection>,
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    let (Query(query), timestamps) = match (query, timestamps) {
+        (Ok(query), Ok(Query(timestamps))) => (query, timestamps.or(state.timestamp_format)),
+        (Err(rejection), _) | (_, Err(rejection)) => {
+            return json_error(rejection.status(), &rejection.body_text());
+        }
+    };
+    let Some(latest) = state.snapshots.latest() else {
+        return json_error(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "No snapshot has been fetched yet",
+        );
+    };
+    // Anonymized clients know repositories by their pseudonym only
+    let bot_runs = match &state.anonymizer {
+        Some(anonymizer) => anonymizer.runs(&latest.bot_runs),
+        None => latest.bot_runs.clone(),
+    };
+    let runs: Vec<&WorkflowRun> = bot_runs
+        .iter()
+        .filter(|run| run.repository_name == query.repo)
+        .collect();
+    if runs.is_empty() {
+        return json_error(StatusCode::NOT_FOUND, "No bot group for this repository");
+    }
+
+    match bot_group_json(&query.repo, &runs, timestamps) {
+        Ok(body) => ([(header::CONTENT_TYPE, "application/json")], body).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to serialize bot group: {:?}", e);
+            json_error(StatusCode::INTERNAL_SERVER_ERROR, "Serialization error")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::use_cases::StreamGitHubActionsRunsUseCaseOutput;
+    use crate::infrastructures::adapters::primary::web::create_router;
+    use crate::test_support::{StubGitHubApi, app_state, base_time, run_actor, workflow_run};
+    use axum::body::{Body, to_bytes};
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    fn snapshot_runs() -> Vec<WorkflowRun> {
+        let mut tricky = workflow_run("owner/repo", 2, "failure");
+        tricky.display_title = "Merge \"feature\", part 1\nand more".to_string();
+        vec![tricky, workflow_run("owner/other", 1, "success")]
+    }
+
+    fn router_with_snapshot() -> anyhow::Result<axum::Router> {
+        let state = app_state(StubGitHubApi::default(), None)?;
+        state
+            .poller
+            .publish(StreamGitHubActionsRunsUseCaseOutput::new(
+                snapshot_runs(),
+                base_time(),
+            ));
+        Ok(create_router(state))
+    }
+
+    async fn get(
+        router: axum::Router,
+        uri: &str,
+        accept: Option<&str>,
+    ) -> anyhow::Result<(StatusCode, Option<String>, String)> {
+        let mut request = Request::get(uri);
+        if let Some(accept) = accept {
+            request = request.header(header::ACCEPT, accept);
+        }
+        let response = router.oneshot(request.body(Body::empty())?).await?;
+        response_parts(response).await
+    }
+
+    async fn response_parts(
+        response: Response,
+    ) -> anyhow::Result<(StatusCode, Option<String>, String)> {
+        let status = response.status();
+        let content_type = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let body = to_bytes(response.into_body(), usize::MAX).await?;
+        Ok((status, content_type, String::from_utf8(body.to_vec())?))
+    }
+
+    #[tokio::test]
+    async fn test_default_format_is_single_json_document() -> anyhow::Result<()> {
+        let (status, content_type, body) = get(router_with_snapshot()?, "/runs", None).await?;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type.as_deref(), Some("application/json"));
+        let json: serde_json::Value = serde_json::from_str(&body)?;
+        assert_eq!(json["runs"].as_array().map(Vec::len), Some(2));
+        assert_eq!(json["generatedAt"], "2024-08-01T10:00:00Z");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_csv_escapes_quotes_commas_and_newlines() -> anyhow::Result<()> {
+        let (status, content_type, body) =
+            get(router_with_snapshot()?, "/runs", Some("text/csv")).await?;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type.as_deref(), Some("text/csv; charset=utf-8"));
+        assert_eq!(
+            body,
+            "repositoryName,id,workflowName,displayTitle,event,status,createdAt,updatedAt,htmlUrl,runNumber,headBranch,headSha\r\n\
+             owner/repo,2,CI,\"Merge \"\"feature\"\", part 1\nand more\",push,failure,2024-08-01T10:02:00Z,2024-08-01T10:02:00Z,https://github.com/owner/repo/actions/runs/2,2,main,0000000000000000000000000000000000000002\r\n\
+             owner/other,1,CI,Fix bug,push,success,2024-08-01T10:01:00Z,2024-08-01T10:01:00Z,https://github.com/owner/other/actions/runs/1,1,main,0000000000000000000000000000000000000001\r\n"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ndjson_emits_one_object_per_line() -> anyhow::Result<()> {
+        let (status, content_type, body) = get(
+            router_with_snapshot()?,
+            "/runs",
+            Some("application/x-ndjson"),
+        )
+        .await?;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type.as_deref(), Some(CONTENT_TYPE_NDJSON));
+        let lines: Vec<&str> = body.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let first: WorkflowRun = serde_json::from_str(lines[0])?;
+        assert_eq!(first.display_title, "Merge \"feature\", part 1\nand more");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_limit_and_fields_project_the_runs() -> anyhow::Result<()> {
+        let uri = "/runs?limit=1&fields=repositoryName,status,htmlUrl,updatedAt&ts=millis";
+
+        let (status, _, body) = get(router_with_snapshot()?, uri, None).await?;
+
+        assert_eq!(status, StatusCode::OK);
+        let json: serde_json::Value = serde_json::from_str(&body)?;
+        assert_eq!(
+            json["runs"],
+            serde_json::json!([{
+                "repositoryName": "owner/repo",
+                "status": "completed",
+                "htmlUrl": "https://github.com/owner/repo/actions/runs/2",
+                "updatedAt": 1_722_506_520_000_i64,
+            }])
+        );
+        assert_eq!(json["generatedAt"], 1_722_506_400_000_i64);
+
+        let (_, _, body) = get(
+            router_with_snapshot()?,
+            &format!("{uri}&format=ndjson"),
+            None,
+        )
+        .await?;
+        assert_eq!(body.lines().count(), 1);
+        let (_, _, body) = get(router_with_snapshot()?, "/runs?limit=1&format=csv", None).await?;
+        assert_eq!(body.matches("\r\n").count(), 2);
+        // A limit above the runs available sends them all
+        let (_, _, body) = get(router_with_snapshot()?, "/runs?limit=10", None).await?;
+        let json: serde_json::Value = serde_json::from_str(&body)?;
+        assert_eq!(json["runs"].as_array().map(Vec::len), Some(2));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_summary_covers_only_the_runs_sent() -> anyhow::Result<()> {
+        let summary = |uri: &'static str| async move {
+            let (_, _, body) = get(router_with_snapshot()?, uri, None).await?;
+            let json: serde_json::Value = serde_json::from_str(&body)?;
+            anyhow::Ok(json["summary"].clone())
+        };
+
+        let all = summary("/runs").await?;
+        let limited = summary("/runs?limit=1&fields=id").await?;
+        let grouped = summary("/runs?group=repository&limit=1").await?;
+
+        assert_eq!(all["counts"]["success"], 1);
+        assert_eq!(all["counts"]["failure"], 1);
+        assert_eq!(all["overall"], "failure");
+        assert_eq!(all["longestFailing"]["repositoryName"], "owner/repo");
+        assert_eq!(limited["counts"]["success"], 0);
+        assert_eq!(limited["counts"]["failure"], 1);
+        assert_eq!(grouped, limited);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_runs_are_labeled_in_the_language_asked_for() -> anyhow::Result<()> {
+        let labels = |body: &str| -> anyhow::Result<Vec<serde_json::Value>> {
+            let json: serde_json::Value = serde_json::from_str(body)?;
+            Ok(json["runs"]
+                .as_array()
+                .map(|runs| runs.iter().map(|run| run["statusLabel"].clone()).collect())
+                .unwrap_or_default())
+        };
+
+        let (_, _, japanese) = get(router_with_snapshot()?, "/runs?lang=ja", None).await?;
+        let response = router_with_snapshot()?
+            .oneshot(
+                Request::get("/runs?fields=status")
+                    .header(header::ACCEPT_LANGUAGE, "fr-FR, en;q=0.5")
+                    .body(Body::empty())?,
+            )
+            .await?;
+        let (_, _, english) = response_parts(response).await?;
+        let (_, _, unknown) = get(router_with_snapshot()?, "/runs?lang=xx", None).await?;
+        let (_, _, unlabeled) = get(router_with_snapshot()?, "/runs", None).await?;
+
+        assert_eq!(labels(&japanese)?, ["失敗", "成功"]);
+        let json: serde_json::Value = serde_json::from_str(&japanese)?;
+        assert_eq!(json["runs"][0]["conclusion"], "failure");
+        assert_eq!(labels(&english)?, ["Failure", "Success"]);
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&english)?["runs"][0],
+            serde_json::json!({"status": "completed", "statusLabel": "Failure"})
+        );
+        assert_eq!(labels(&unknown)?, ["Failure", "Success"]);
+        assert_eq!(
+            labels(&unlabeled)?,
+            [serde_json::Value::Null, serde_json::Value::Null]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_grouped_runs_are_limited_before_grouping() -> anyhow::Result<()> {
+        let (status, _, body) = get(
+            router_with_snapshot()?,
+            "/runs?group=repository&limit=1&fields=id",
+            None,
+        )
+        .await?;
+
+        assert_eq!(status, StatusCode::OK);
+        let json: serde_json::Value = serde_json::from_str(&body)?;
+        let runs: Vec<&serde_json::Value> = json["repositories"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .flat_map(|group| group["runs"].as_array().into_iter().flatten())
+            .collect();
+        assert_eq!(runs, [&serde_json::json!({"id": 2})]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unknown_fields_are_rejected() -> anyhow::Result<()> {
+        for uri in [
+            "/runs?fields=status,colour",
+            "/runs?limit=0",
+            "/runs?limit=many",
+            "/runs?fields=status&format=csv",
+        ] {
+            let (status, _, body) = get(router_with_snapshot()?, uri, None).await?;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{uri}");
+            let json: serde_json::Value = serde_json::from_str(&body)?;
+            assert!(json["error"].is_string(), "{uri}");
+        }
+        let (_, _, body) = get(router_with_snapshot()?, "/runs?fields=colour", None).await?;
+        let json: serde_json::Value = serde_json::from_str(&body)?;
+        assert!(
+            json["error"]
+                .as_str()
+                .is_some_and(|error| error.contains("colour") && error.contains("htmlUrl")),
+            "{json}"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_format_query_overrides_accept_header() -> anyhow::Result<()> {
+        let (status, content_type, _) = get(
+            router_with_snapshot()?,
+            "/runs?format=ndjson",
+            Some("text/csv"),
+        )
+        .await?;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type.as_deref(), Some(CONTENT_TYPE_NDJSON));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ts_query_switches_json_timestamps_to_millis() -> anyhow::Result<()> {
+        let router = router_with_snapshot()?;
+        let (_, _, body) = get(router.clone(), "/runs?ts=millis", None).await?;
+        let snapshot: serde_json::Value = serde_json::from_str(&body)?;
+        assert_eq!(snapshot["generatedAt"], base_time().timestamp_millis());
+        assert_eq!(
+            snapshot["runs"][0]["createdAt"],
+            snapshot_runs()[0].created_at.timestamp_millis()
+        );
+
+        let (_, _, body) = get(router.clone(), "/runs?format=ndjson&ts=millis", None).await?;
+        let first: serde_json::Value =
+            serde_json::from_str(body.lines().next().unwrap_or_default())?;
+        assert!(first["updatedAt"].is_i64());
+
+        // Each timestamp format is a separate representation for caching
+        let rfc3339 = router
+            .clone()
+            .oneshot(Request::get("/runs").body(Body::empty())?)
+            .await?;
+        let millis = router
+            .oneshot(Request::get("/runs?ts=millis").body(Body::empty())?)
+            .await?;
+        assert_ne!(etag_of(&rfc3339)?, etag_of(&millis)?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_grouped_json_joins_runs_to_repositories() -> anyhow::Result<()> {
+        let state = app_state(StubGitHubApi::default(), None)?;
+        let repositories = StubGitHubApi::with_repository("owner", "repo").repositories;
+        state.poller.publish(
+            StreamGitHubActionsRunsUseCaseOutput::new(
+                vec![workflow_run("owner/repo", 1, "success")],
+                base_time(),
+            )
+            .with_repositories(repositories),
+        );
+        let router = create_router(state);
+
+        let (status, _, body) = get(router.clone(), "/runs?group=repository", None).await?;
+        assert_eq!(status, StatusCode::OK);
+        let grouped: serde_json::Value = serde_json::from_str(&body)?;
+        let group = &grouped["repositories"][0];
+        assert_eq!(group["repository"]["fullName"], "owner/repo");
+        assert_eq!(
+            group["repository"]["ownerAvatarUrl"],
+            "https://avatars.githubusercontent.com/owner"
+        );
+        assert_eq!(group["runs"][0]["repositoryName"], "owner/repo");
+
+        let (status, _, _) = get(router.clone(), "/runs?group=repository&format=csv", None).await?;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _, _) = get(router, "/runs?group=workflow", None).await?;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_bot_group_lists_the_collapsed_runs_of_a_repository() -> anyhow::Result<()> {
+        let bot_run = |repo, id| {
+            let mut run = workflow_run(repo, id, "success");
+            run.event = "pull_request".to_string();
+            run.actor = run_actor("renovate[bot]");
+            run.triggering_actor = None;
+            run
+        };
+        let state = app_state(StubGitHubApi::default(), None)?;
+        state.poller.publish(
+            StreamGitHubActionsRunsUseCaseOutput::new(snapshot_runs(), base_time()).with_bot_runs(
+                vec![
+                    bot_run("owner/repo", 4),
+                    bot_run("owner/other", 3),
+                    bot_run("owner/repo", 1),
+                ],
+            ),
+        );
+        let router = create_router(state);
+
+        let (status, _, body) = get(router.clone(), "/runs", None).await?;
+        assert_eq!(status, StatusCode::OK);
+        let snapshot: serde_json::Value = serde_json::from_str(&body)?;
+        assert_eq!(snapshot["runs"].as_array().map(Vec::len), Some(2));
+        assert_eq!(snapshot["botGroups"][1]["repositoryName"], "owner/repo");
+        assert_eq!(
+            snapshot["botGroups"][1]["runIds"],
+            serde_json::json!([4, 1])
+        );
+
+        let (status, _, body) = get(
+            router.clone(),
+            "/runs/bot-group?repo=owner/repo&ts=millis",
+            None,
+        )
+        .await?;
+        assert_eq!(status, StatusCode::OK);
+        let group: serde_json::Value = serde_json::from_str(&body)?;
+        assert_eq!(group["repositoryName"], "owner/repo");
+        let ids: Vec<&serde_json::Value> = group["runs"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|run| &run["id"])
+            .collect();
+        assert_eq!(ids, [4, 1]);
+        assert!(group["runs"][0]["createdAt"].is_i64());
+
+        let (status, _, _) = get(router.clone(), "/runs/bot-group?repo=owner/none", None).await?;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _, _) = get(router, "/runs/bot-group?repo=owner", None).await?;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unknown_format_is_rejected() -> anyhow::Result<()> {
+        let (status, _, _) = get(router_with_snapshot()?, "/runs?format=xml", None).await?;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_missing_snapshot_is_service_unavailable() -> anyhow::Result<()> {
+        let router = create_router(app_state(StubGitHubApi::default(), None)?);
+
+        let (status, _, _) = get(router, "/runs", None).await?;
+
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        Ok(())
+    }
+
+    fn etag_of(response: &Response) -> anyhow::Result<String> {
+        Ok(response
+            .headers()
+            .get(header::ETAG)
+            .ok_or_else(|| anyhow::anyhow!("missing ETag"))?
+            .to_str()?
+            .to_string())
+    }
+
+    async fn get_if_none_match(router: &axum::Router, etag: &str) -> anyhow::Result<Response> {
+        Ok(router
+            .clone()
+            .oneshot(
+                Request::get("/runs")
+                    .header(header::IF_NONE_MATCH, etag)
+                    .body(Body::empty())?,
+            )
+            .await?)
+    }
+
+    #[tokio::test]
+    async fn test_matching_if_none_match_is_not_modified() -> anyhow::Result<()> {
+        let router = router_with_snapshot()?;
+        let first = router
+            .clone()
+            .oneshot(Request::get("/runs").body(Body::empty())?)
+            .await?;
+        let etag = etag_of(&first)?;
+        assert_eq!(
+            first.headers().get(header::VARY),
+            Some(&"accept, accept-language".parse()?)
+        );
+
+        let second = get_if_none_match(&router, &etag).await?;
+
+        assert_eq!(second.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(etag_of(&second)?, etag);
+        let (_, _, body) = response_parts(second).await?;
+        assert!(body.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_new_snapshot_changes_etag() -> anyhow::Result<()> {
+        let state = app_state(StubGitHubApi::default(), None)?;
+        state
+            .poller
+            .publish(StreamGitHubActionsRunsUseCaseOutput::new(
+                snapshot_runs(),
+                base_time(),
+            ));
+        let router = create_router(Arc::clone(&state));
+        let first = router
+            .clone()
+            .oneshot(Request::get("/runs").body(Body::empty())?)
+            .await?;
+        let old_etag = etag_of(&first)?;
+
+        let mut runs = snapshot_runs();
+        runs.reverse();
+        state
+            .poller
+            .publish(StreamGitHubActionsRunsUseCaseOutput::new(runs, base_time()));
+        let second = get_if_none_match(&router, &old_etag).await?;
+
+        assert_eq!(second.status(), StatusCode::OK);
+        assert_ne!(etag_of(&second)?, old_etag);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_etag_differs_per_representation() -> anyhow::Result<()> {
+        let router = router_with_snapshot()?;
+
+        let json = router
+            .clone()
+            .oneshot(Request::get("/runs").body(Body::empty())?)
+            .await?;
+        let csv = router
+            .oneshot(Request::get("/runs?format=csv").body(Body::empty())?)
+            .await?;
+
+        assert_ne!(etag_of(&json)?, etag_of(&csv)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_if_none_match_parsing() -> anyhow::Result<()> {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_NONE_MATCH, "\"other\", W/\"abc-json\"".parse()?);
+        assert!(if_none_match(&headers, "\"abc-json\""));
+        assert!(!if_none_match(&headers, "\"abc-csv\""));
+        headers.insert(header::IF_NONE_MATCH, "*".parse()?);
+        assert!(if_none_match(&headers, "\"abc-json\""));
+        Ok(())
+    }
+
+    #[test]
+    fn test_accept_header_with_quality_values() -> anyhow::Result<()> {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::ACCEPT,
+            "text/html;q=0.9, application/x-ndjson;q=0.8".parse()?,
+        );
+        assert_eq!(RunsFormat::from_accept(&headers), RunsFormat::Ndjson);
+        headers.insert(header::ACCEPT, "*/*".parse()?);
+        assert_eq!(RunsFormat::from_accept(&headers), RunsFormat::Json);
+        Ok(())
+    }
+}
@@ -0,0 +1,429 @@
+use super::presenter::{TimestampQuery, search_json};
+use super::{AppState, json_error};
+use crate::domain::models::ids::RepoFullName;
+use crate::domain::models::run::WorkflowRun;
+use crate::domain::models::status::{RunConclusion, RunStatus};
+use crate::domain::repositories::{RunCursor, RunQuery, RunRepositoryError};
+use axum::{
+    Json,
+    extract::{Query, State, rejection::QueryRejection},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::sync::Arc;
+
+/// Most runs one `/search/runs` page may hold
+const MAX_SEARCH_LIMIT: usize = 1000;
+
+/// Set to [`SNAPSHOT_COVERAGE`] when the search only covered the latest snapshot
+pub const SEARCH_COVERAGE_HEADER: &str = "x-search-coverage";
+
+/// Value of [`SEARCH_COVERAGE_HEADER`] when there is no history to search
+pub const SNAPSHOT_COVERAGE: &str = "latest-snapshot";
+
+/// Parameters of `GET /search/runs`, validated together so that every bad one is reported.
+#[derive(Deserialize, Debug, Default)]
+pub struct SearchQuery {
+    q: Option<String>,
+    repo: Option<String>,
+    workflow: Option<String>,
+    status: Option<String>,
+    actor: Option<String>,
+    branch: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    limit: Option<String>,
+    cursor: Option<String>,
+}
+
+/// A parameter of `GET /search/runs` that could not be used.
+#[derive(Serialize, Debug, PartialEq, Eq)]
+struct FieldError {
+    field: &'static str,
+    message: String,
+}
+
+#[derive(Serialize, Debug)]
+struct InvalidSearch {
+    error: &'static str,
+    fields: Vec<FieldError>,
+}
+
+/// `value`, unless it was left empty.
+fn present(value: Option<String>) -> Option<String> {
+    value.filter(|value| !value.trim().is_empty())
+}
+
+/// Parses `value` of `field`, recording the error in `errors` if it is bad.
+fn parse<T>(
+    field: &'static str,
+    value: Option<&str>,
+    errors: &mut Vec<FieldError>,
+    parse: impl FnOnce(&str) -> Result<T, String>,
+) -> Option<T> {
+    match parse(value?) {
+        Ok(parsed) => Some(parsed),
+        Err(message) => {
+            errors.push(FieldError { field, message });
+            None
+        }
+    }
+}
+
+/// Like GitHub's `status` filter, a run state or a conclusion.
+fn status_or_conclusion(value: &str) -> Result<(Option<RunStatus>, Option<RunConclusion>), String> {
+    let status = value.parse().unwrap_or(RunStatus::Unknown);
+    let conclusion = value.parse().unwrap_or(RunConclusion::Unknown);
+    match (status, conclusion) {
+        (RunStatus::Unknown, RunConclusion::Unknown) => Err(format!(
+            "Unknown status {value:?}, expected a run state such as in_progress or a \
+             conclusion such as failure"
+        )),
+        (RunStatus::Unknown, conclusion) => Ok((None, Some(conclusion))),
+        (status, _) => Ok((Some(status), None)),
+    }
+}
+
+fn timestamp(value: &str) -> Result<DateTime<Utc>, String> {
+    value
+        .parse()
+        .map_err(|e| format!("Invalid RFC 3339 timestamp {value:?}: {e}"))
+}
+
+impl SearchQuery {
+    /// The run query these parameters describe, asking for one run more than the page holds
+    /// to tell whether another page follows.
+    fn into_run_query(self) -> Result<RunQuery, Vec<FieldError>> {
+        let mut errors = Vec::new();
+        let repository = parse(
+            "repo",
+            present(self.repo).as_deref(),
+            &mut errors,
+            |value| value.parse::<RepoFullName>().map_err(|e| e.to_string()),
+        );
+        let (status, conclusion) = parse(
+            "status",
+            present(self.status).as_deref(),
+            &mut errors,
+            status_or_conclusion,
+        )
+        .unwrap_or_default();
+        let since = parse(
+            "from",
+            present(self.from).as_deref(),
+            &mut errors,
+            timestamp,
+        );
+        let until = parse("to", present(self.to).as_deref(), &mut errors, timestamp);
+        if let (Some(since), Some(until)) = (since, until)
+            && since >= until
+        {
+            errors.push(FieldError {
+                field: "to",
+                message: "to must be later than from".to_string(),
+            });
+        }
+        let limit = parse(
+            "limit",
+            present(self.limit).as_deref(),
+            &mut errors,
+            |value| {
+                value
+                    .parse()
+                    .ok()
+                    .filter(|limit| (1..=MAX_SEARCH_LIMIT).contains(limit))
+                    .ok_or_else(|| format!("limit must be between 1 and {MAX_SEARCH_LIMIT}"))
+            },
+        );
+        let before = parse(
+            "cursor",
+            present(self.cursor).as_deref(),
+            &mut errors,
+            str::parse,
+        );
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        Ok(RunQuery {
+            repository,
+            since,
+            until,
+            status,
+            conclusion,
+            workflow: present(self.workflow),
+            actor: present(self.actor),
+            branch: present(self.branch),
+            text: present(self.q),
+            before,
+            limit: limit.unwrap_or(RunQuery::default().limit) + 1,
+            ..RunQuery::default()
+        })
+    }
+}
+
+/// The runs matching `query`: from the history when there is one, otherwise from the latest
+/// snapshot, newest first either way.
+///
+/// Returns whether the history was searched alongside the runs.
+async fn matching_runs(
+    state: &AppState,
+    query: &RunQuery,
+) -> Result<(Vec<WorkflowRun>, bool), RunRepositoryError> {
+    if let Some(run_repository) = &state.run_repository {
+        return Ok((run_repository.query_runs(query).await?, true));
+    }
+    let mut runs: Vec<_> = state
+        .snapshots
+        .latest()
+        .map(|output| {
+            output
+                .runs
+                .iter()
+                .filter(|run| query.matches(run))
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default();
+    runs.sort_by_key(|run| Reverse(RunCursor::of(run)));
+    runs.truncate(query.limit);
+    Ok((runs, false))
+}
+
+#[tracing::instrument(name = "search_runs_handler", skip(state))]
+pub async fn search_runs_handler(
+    query: Result<Query<SearchQuery>, QueryRejection>,
+    timestamps: Result<Query<TimestampQuery>, QueryRejection>,
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    let query = match query {
+        Ok(Query(query)) => match query.into_run_query() {
+            Ok(query) => query,
+            Err(fields) => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(InvalidSearch {
+                        error: "Invalid search parameters",
+                        fields,
+                    }),
+                )
+                    .into_response();
+            }
+        },
+        Err(rejection) => return json_error(rejection.status(), &rejection.body_text()),
+    };
+    let timestamps = match timestamps {
+        Ok(Query(timestamps)) => timestamps.or(state.timestamp_format),
+        Err(rejection) => return json_error(rejection.status(), &rejection.body_text()),
+    };
+
+    let (mut runs, searched_history) = match matching_runs(&state, &query).await {
+        Ok(found) => found,
+        Err(e) => {
+            tracing::error!("Failed to search run history: {}", e);
+            return json_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to search run history",
+            );
+        }
+    };
+    // The extra run asked for only tells that another page follows
+    let next_cursor = (runs.len() == query.limit).then(|| {
+        runs.pop();
+        runs.last().map(|run| RunCursor::of(run).to_string())
+    });
+    let body = match search_json(&runs, next_cursor.flatten(), timestamps) {
+        Ok(body) => body,
+        Err(e) => {
+            tracing::error!("Failed to serialize search results: {:?}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    let mut response = ([(header::CONTENT_TYPE, "application/json")], body).into_response();
+    if !searched_history {
+        response.headers_mut().insert(
+            SEARCH_COVERAGE_HEADER,
+            header::HeaderValue::from_static(SNAPSHOT_COVERAGE),
+        );
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::use_cases::stream_github_actions_runs::StreamGitHubActionsRunsUseCaseOutput;
+    use crate::domain::models::ids::RunId;
+    use crate::domain::repositories::RunRepository;
+    use crate::infrastructures::adapters::primary::web::create_router;
+    use crate::infrastructures::adapters::secondary::persistence::SqliteRunRepository;
+    use crate::test_support::{StubGitHubApi, app_state, base_time, workflow_run};
+    use axum::body::{Body, to_bytes};
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    async fn get(
+        state: Arc<AppState>,
+        uri: &str,
+    ) -> anyhow::Result<(StatusCode, Option<String>, serde_json::Value)> {
+        let response = create_router(state)
+            .oneshot(Request::get(uri).body(Body::empty())?)
+            .await?;
+        let status = response.status();
+        let coverage = response
+            .headers()
+            .get(SEARCH_COVERAGE_HEADER)
+            .map(|value| value.to_str().map(str::to_string))
+            .transpose()?;
+        let body = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await?)?;
+        Ok((status, coverage, body))
+    }
+
+    fn runs() -> Vec<WorkflowRun> {
+        let mut runs: Vec<_> = (1..=5)
+            .map(|id| workflow_run("owner/repo", id, "failure"))
+            .collect();
+        runs[0].display_title = "Bump serde".to_string();
+        runs[3].display_title = "bump tokio".to_string();
+        runs[4].conclusion = Some(RunConclusion::Success);
+        runs
+    }
+
+    async fn state_with_history() -> anyhow::Result<Arc<AppState>> {
+        let run_repository = Arc::new(SqliteRunRepository::connect("sqlite::memory:")?);
+        run_repository.save_runs(&runs(), base_time()).await?;
+        let state = Arc::into_inner(app_state(StubGitHubApi::default(), None)?)
+            .ok_or_else(|| anyhow::anyhow!("state is shared"))?
+            .with_run_repository(run_repository);
+        Ok(Arc::new(state))
+    }
+
+    fn ids(body: &serde_json::Value) -> Vec<u64> {
+        body["runs"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|run| run["id"].as_u64())
+            .collect()
+    }
+
+    #[test]
+    fn test_parameters_become_a_run_query() -> anyhow::Result<()> {
+        let query = SearchQuery {
+            q: Some("bump".to_string()),
+            repo: Some("owner/repo".to_string()),
+            status: Some("failure".to_string()),
+            actor: Some("octocat".to_string()),
+            branch: Some(String::new()),
+            from: Some("2024-01-01T00:00:00Z".to_string()),
+            limit: Some("10".to_string()),
+            cursor: Some("1704067200000-7".to_string()),
+            ..SearchQuery::default()
+        }
+        .into_run_query()
+        .map_err(|errors| anyhow::anyhow!("{errors:?}"))?;
+
+        assert_eq!(query.text.as_deref(), Some("bump"));
+        assert_eq!(query.repository, Some("owner/repo".parse()?));
+        assert_eq!(
+            (query.status, query.conclusion),
+            (None, Some(RunConclusion::Failure))
+        );
+        assert_eq!(query.branch, None);
+        assert_eq!(query.since, Some("2024-01-01T00:00:00Z".parse()?));
+        assert_eq!(query.limit, 11);
+        assert_eq!(query.before.map(|cursor| cursor.id.0), Some(7));
+
+        let query = SearchQuery {
+            status: Some("in_progress".to_string()),
+            ..SearchQuery::default()
+        }
+        .into_run_query()
+        .map_err(|errors| anyhow::anyhow!("{errors:?}"))?;
+        assert_eq!(query.status, Some(RunStatus::InProgress));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_each_bad_parameter_is_listed() -> anyhow::Result<()> {
+        let state = state_with_history().await?;
+
+        let (status, _, body) = get(
+            state,
+            "/search/runs?repo=owner&status=broken&from=yesterday&limit=0&cursor=abc",
+        )
+        .await?;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let fields: Vec<_> = body["fields"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|field| field["field"].as_str())
+            .collect();
+        assert_eq!(fields, ["repo", "status", "from", "limit", "cursor"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_history_is_paged_newest_first() -> anyhow::Result<()> {
+        let state = state_with_history().await?;
+
+        let (status, coverage, body) = get(
+            Arc::clone(&state),
+            "/search/runs?repo=owner/repo&status=failure&limit=2",
+        )
+        .await?;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(coverage, None);
+        assert_eq!(ids(&body), [4, 3]);
+
+        let cursor = body["nextCursor"].as_str().unwrap_or_default().to_string();
+        let uri = format!("/search/runs?repo=owner/repo&status=failure&limit=2&cursor={cursor}");
+        let (_, _, body) = get(Arc::clone(&state), &uri).await?;
+        assert_eq!(ids(&body), [2, 1]);
+        assert_eq!(body["nextCursor"], serde_json::Value::Null);
+
+        let (_, _, body) = get(state, "/search/runs?q=BUMP").await?;
+        assert_eq!(ids(&body), [4, 1]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cursor_past_the_end_returns_an_empty_page() -> anyhow::Result<()> {
+        let state = state_with_history().await?;
+        let past_the_end = RunCursor {
+            created_at: base_time(),
+            id: RunId(1),
+        };
+
+        let uri = format!("/search/runs?cursor={past_the_end}");
+        let (status, _, body) = get(state, &uri).await?;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(ids(&body), Vec::<u64>::new());
+        assert_eq!(body["nextCursor"], serde_json::Value::Null);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_is_searched_without_history() -> anyhow::Result<()> {
+        let state = app_state(StubGitHubApi::default(), None)?;
+        state
+            .poller
+            .publish(StreamGitHubActionsRunsUseCaseOutput::new(
+                runs(),
+                base_time(),
+            ));
+
+        let (status, coverage, body) = get(state, "/search/runs?q=bump&limit=1").await?;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(coverage.as_deref(), Some(SNAPSHOT_COVERAGE));
+        assert_eq!(ids(&body), [4]);
+        assert!(body["nextCursor"].is_string());
+        Ok(())
+    }
+}
@@ -0,0 +1,270 @@
+use anyhow::{Context, bail};
+use axum::Router;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Prefix of `BIND_ADDR` values naming a Unix domain socket
+const UNIX_PREFIX: &str = "unix:";
+
+/// Permissions of the socket file unless configured otherwise: owner and group may connect
+pub const DEFAULT_SOCKET_MODE: u32 = 0o660;
+
+/// Address the HTTP server listens on, parsed from `BIND_ADDR`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BindAddr {
+    Tcp(SocketAddr),
+    /// `unix:/run/gha-dashboard.sock`
+    Unix(PathBuf),
+}
+
+impl Default for BindAddr {
+    fn default() -> Self {
+        Self::Tcp(SocketAddr::from(([0, 0, 0, 0], 3000)))
+    }
+}
+
+impl FromStr for BindAddr {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if let Some(path) = value.strip_prefix(UNIX_PREFIX) {
+            if path.is_empty() {
+                bail!("BIND_ADDR {value:?} is missing the socket path");
+            }
+            return Ok(Self::Unix(PathBuf::from(path)));
+        }
+        value
+            .parse()
+            .map(Self::Tcp)
+            .with_context(|| format!("Invalid BIND_ADDR {value:?}"))
+    }
+}
+
+/// Parses an octal file mode such as `660` or `0o600`.
+///
+/// # Errors
+///
+/// Returns an error if `value` is not an octal number up to `777`.
+pub fn parse_socket_mode(value: &str) -> anyhow::Result<u32> {
+    let digits = value.strip_prefix("0o").unwrap_or(value);
+    match u32::from_str_radix(digits, 8) {
+        Ok(mode) if mode <= 0o777 => Ok(mode),
+        _ => bail!("Invalid socket mode {value:?}, expected octal such as 660"),
+    }
+}
+
+/// Serves `router` on `bind_addr` until `shutdown` resolves.
+///
+/// A Unix socket file left behind by a previous run is replaced, and the file is
+/// removed again once the server stops.
+///
+/// # Errors
+///
+/// Returns an error if binding fails or the server stops with an I/O error.
+pub async fn serve<F>(
+    bind_addr: &BindAddr,
+    socket_mode: u32,
+    router: Router,
+    shutdown: F,
+) -> anyhow::Result<()>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    match bind_addr {
+        BindAddr::Tcp(addr) => {
+            let listener = tokio::net::TcpListener::bind(addr).await?;
+            serve_tcp(listener, router, shutdown).await
+        }
+        #[cfg(unix)]
+        BindAddr::Unix(path) => serve_unix(path, socket_mode, router, shutdown).await,
+        #[cfg(not(unix))]
+        BindAddr::Unix(_) => {
+            let _ = (socket_mode, router, shutdown);
+            bail!("Unix domain sockets are not supported on this platform")
+        }
+    }
+}
+
+/// Serves `router` on a bound TCP `listener` until `shutdown` resolves.
+///
+/// # Errors
+///
+/// Returns an error if the server stops with an I/O error.
+pub async fn serve_tcp<F>(
+    listener: tokio::net::TcpListener,
+    router: Router,
+    shutdown: F,
+) -> anyhow::Result<()>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    tracing::info!("Listening on {}", listener.local_addr()?);
+    axum::serve(
+        listener,
+        router.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown)
+    .await?;
+    Ok(())
+}
+
+#[cfg(unix)]
+async fn serve_unix<F>(
+    path: &std::path::Path,
+    socket_mode: u32,
+    router: Router,
+    shutdown: F,
+) -> anyhow::Result<()>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => {
+            tracing::info!("Removing stale socket {}", path.display());
+            std::fs::remove_file(path)
+                .with_context(|| format!("Failed to remove stale socket {}", path.display()))?;
+        }
+        // Never delete something that is not a socket, it may be a misconfigured path
+        Ok(_) => bail!("{} exists and is not a socket", path.display()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to inspect {}", path.display()));
+        }
+    }
+
+    let listener = tokio::net::UnixListener::bind(path)
+        .with_context(|| format!("Failed to bind {}", path.display()))?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(socket_mode))
+        .with_context(|| format!("Failed to set permissions of {}", path.display()))?;
+    tracing::info!(
+        "Listening on unix:{} (mode {:o})",
+        path.display(),
+        socket_mode
+    );
+
+    // Client IPs are unknown over a Unix socket; the reverse proxy supplies them
+    let result = axum::serve(listener, router.into_make_service())
+        .with_graceful_shutdown(shutdown)
+        .await;
+
+    if let Err(e) = std::fs::remove_file(path) {
+        tracing::warn!("Failed to remove socket {}: {:?}", path.display(), e);
+    }
+    Ok(result?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bind_addr_parsing() -> anyhow::Result<()> {
+        assert_eq!(
+            "127.0.0.1:8080".parse::<BindAddr>()?,
+            BindAddr::Tcp(SocketAddr::from(([127, 0, 0, 1], 8080)))
+        );
+        assert_eq!(
+            "unix:/run/gha-dashboard.sock".parse::<BindAddr>()?,
+            BindAddr::Unix(PathBuf::from("/run/gha-dashboard.sock"))
+        );
+        assert!("unix:".parse::<BindAddr>().is_err());
+        assert!("localhost".parse::<BindAddr>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_socket_mode_parsing() -> anyhow::Result<()> {
+        assert_eq!(parse_socket_mode("660")?, 0o660);
+        assert_eq!(parse_socket_mode("0o600")?, 0o600);
+        assert!(parse_socket_mode("999").is_err());
+        assert!(parse_socket_mode("7777").is_err());
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_unix_socket_serves_health_and_websocket() -> anyhow::Result<()> {
+        use crate::application::use_cases::StreamGitHubActionsRunsUseCaseOutput;
+        use crate::infrastructures::adapters::primary::web::create_router;
+        use crate::test_support::{StubGitHubApi, app_state, base_time};
+        use futures_util::StreamExt;
+        use std::os::unix::fs::PermissionsExt;
+        use std::time::Duration;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::UnixStream;
+
+        let path = std::env::temp_dir().join(format!(
+            "gha-dashboard-test-{}-{}.sock",
+            std::process::id(),
+            unique_suffix()
+        ));
+        // A socket file left behind by a crashed run must not prevent startup
+        drop(std::os::unix::net::UnixListener::bind(&path)?);
+
+        let state = app_state(StubGitHubApi::default(), None)?;
+        state
+            .poller
+            .publish(StreamGitHubActionsRunsUseCaseOutput::new(
+                Vec::new(),
+                base_time(),
+            ));
+        let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn({
+            let bind_addr = BindAddr::Unix(path.clone());
+            let router = create_router(state);
+            async move {
+                serve(&bind_addr, 0o600, router, async {
+                    let _ = stopped.await;
+                })
+                .await
+            }
+        });
+
+        let mut stream = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                match UnixStream::connect(&path).await {
+                    Ok(stream) => return stream,
+                    Err(_) => tokio::time::sleep(Duration::from_millis(10)).await,
+                }
+            }
+        })
+        .await?;
+        assert_eq!(
+            std::fs::metadata(&path)?.permissions().mode() & 0o777,
+            0o600
+        );
+
+        stream
+            .write_all(b"GET /health HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await?;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with("OK"));
+
+        let (mut socket, _) =
+            tokio_tungstenite::client_async("ws://localhost/ws", UnixStream::connect(&path).await?)
+                .await?;
+        let message = tokio::time::timeout(Duration::from_secs(5), socket.next())
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("websocket closed"))??;
+        assert!(message.into_text()?.contains("generatedAt"));
+        socket.close(None).await?;
+
+        let _ = stop.send(());
+        tokio::time::timeout(Duration::from_secs(5), server).await???;
+        assert!(!path.exists());
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    fn unique_suffix() -> u32 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.subsec_nanos())
+    }
+}
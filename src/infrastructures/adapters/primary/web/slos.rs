@@ -0,0 +1,149 @@
+use super::{AppState, json_error};
+use crate::application::use_cases::slo_evaluation::{
+    SloEvaluationInput, SloEvaluationInteractor, SloEvaluationOutput, SloEvaluationUseCase,
+};
+use crate::domain::repositories::{RunRepository, RunRepositoryError};
+use axum::{
+    Json,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use chrono::Utc;
+use std::sync::Arc;
+
+/// Evaluates the configured objectives against `run_repository` now, and updates their
+/// gauges.
+pub(super) async fn evaluate_slos(
+    state: &AppState,
+    run_repository: &Arc<dyn RunRepository + Send + Sync>,
+) -> Result<SloEvaluationOutput, RunRepositoryError> {
+    let output = SloEvaluationInteractor::new(Arc::clone(run_repository))
+        .execute(SloEvaluationInput {
+            slos: state.slos.clone(),
+            now: Utc::now(),
+        })
+        .await?;
+    state.metrics.record_slos(&output.slos);
+    Ok(output)
+}
+
+#[tracing::instrument(name = "slos_handler", skip_all)]
+pub async fn slos_handler(State(state): State<Arc<AppState>>) -> Response {
+    let Some(run_repository) = &state.run_repository else {
+        return json_error(
+            StatusCode::NOT_FOUND,
+            "Run history is disabled, set DATABASE_URL to enable it",
+        );
+    };
+    match evaluate_slos(&state, run_repository).await {
+        Ok(output) => Json(output).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to evaluate SLOs: {}", e);
+            json_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to evaluate SLOs")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::slo::Slo;
+    use crate::domain::models::status::RunConclusion;
+    use crate::infrastructures::adapters::primary::web::create_router;
+    use crate::infrastructures::adapters::secondary::persistence::SqliteRunRepository;
+    use crate::test_support::{StubGitHubApi, app_state};
+    use crate::testing::WorkflowRunBuilder;
+    use axum::body::{Body, to_bytes};
+    use axum::http::Request;
+    use chrono::TimeDelta;
+    use tower::ServiceExt;
+
+    async fn get_json(
+        state: Arc<AppState>,
+        uri: &str,
+    ) -> anyhow::Result<(StatusCode, serde_json::Value)> {
+        let response = create_router(state)
+            .oneshot(Request::get(uri).body(Body::empty())?)
+            .await?;
+        let status = response.status();
+        let body = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await?)?;
+        Ok((status, body))
+    }
+
+    fn slo(workflow: &str) -> anyhow::Result<Slo> {
+        Ok(Slo {
+            repo: "owner/repo".parse()?,
+            workflow: Some(workflow.parse()?),
+            branch: Some("main".parse()?),
+            target_duration_minutes: 30,
+            target_success_rate: 0.95,
+            window_days: 7,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_slos_are_evaluated_over_the_history() -> anyhow::Result<()> {
+        let created_at = Utc::now() - TimeDelta::hours(1);
+        let runs: Vec<_> = [
+            (1, 10, RunConclusion::Success),
+            (2, 45, RunConclusion::Success),
+        ]
+        .into_iter()
+        .map(|(id, minutes, conclusion)| {
+            WorkflowRunBuilder::new("owner/repo")
+                .id(id)
+                .conclusion(conclusion)
+                .created_at(created_at)
+                .updated_at(created_at + TimeDelta::minutes(minutes))
+                .build()
+        })
+        .collect();
+        let run_repository = Arc::new(SqliteRunRepository::connect("sqlite::memory:")?);
+        run_repository.save_runs(&runs, Utc::now()).await?;
+        let state = Arc::into_inner(app_state(StubGitHubApi::default(), None)?)
+            .ok_or_else(|| anyhow::anyhow!("state is shared"))?
+            .with_run_repository(run_repository)
+            .with_slos(vec![slo("CI")?, slo("Deploy")?]);
+        let state = Arc::new(state);
+
+        let (status, body) = get_json(Arc::clone(&state), "/slos").await?;
+
+        assert_eq!(status, StatusCode::OK);
+        let ci = &body["slos"][0];
+        assert_eq!(ci["workflow"], "CI");
+        assert_eq!(ci["status"], "violated");
+        assert_eq!(
+            (ci["runs"].as_u64(), ci["goodRuns"].as_u64()),
+            (Some(2), Some(1))
+        );
+        assert_eq!(ci["recentViolations"][0]["reason"], "slow");
+        assert_eq!(
+            ci["recentViolations"][0]["htmlUrl"],
+            "https://github.com/owner/repo/actions/runs/2"
+        );
+        let deploy = &body["slos"][1];
+        assert_eq!(deploy["status"], "noData");
+        assert_eq!(deploy["compliance"], serde_json::Value::Null);
+
+        let metrics = state.metrics.render()?;
+        assert!(
+            metrics.contains(
+                r#"slo_compliance_ratio{branch="main",repository="owner/repo",workflow="CI"} 0.5"#
+            ),
+            "{metrics}"
+        );
+        assert!(!metrics.contains(r#"workflow="Deploy""#), "{metrics}");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_slos_need_the_history() -> anyhow::Result<()> {
+        let state = app_state(StubGitHubApi::default(), None)?;
+
+        let (status, _) = get_json(state, "/slos").await?;
+
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        Ok(())
+    }
+}
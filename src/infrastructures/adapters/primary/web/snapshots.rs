@@ -0,0 +1,287 @@
+use super::presenter::{
+    TimestampFormat, TimestampQuery, historical_snapshot_json, snapshot_series_json,
+    snapshots_unavailable_json,
+};
+use super::{AppState, json_error};
+use crate::domain::repositories::{RunRepositoryError, SnapshotRange};
+use axum::{
+    extract::{Query, State, rejection::QueryRejection},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::Deserialize;
+use std::sync::Arc;
+
+/// Interval between the points of `/snapshots` unless `?step=` says otherwise
+const DEFAULT_STEP_SECONDS: u32 = 300;
+
+/// Most points one `/snapshots` request may span
+const MAX_SNAPSHOT_POINTS: i64 = 1000;
+
+/// Time of `GET /snapshot`.
+#[derive(Deserialize, Debug)]
+pub struct SnapshotQuery {
+    at: DateTime<Utc>,
+}
+
+/// Range and resolution of `GET /snapshots`.
+#[derive(Deserialize, Debug)]
+pub struct SnapshotsQuery {
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    /// Seconds
+    step: Option<u32>,
+}
+
+/// Sends `body` as JSON with `status`, or 500 if it could not be serialized.
+fn json_response(body: serde_json::Result<String>, status: StatusCode) -> Response {
+    match body {
+        Ok(body) => (status, [(header::CONTENT_TYPE, "application/json")], body).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to serialize snapshots: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// 404 naming the stored range, for requests outside it.
+fn unavailable(message: &str, range: Option<SnapshotRange>, format: TimestampFormat) -> Response {
+    json_response(
+        snapshots_unavailable_json(message, range, format),
+        StatusCode::NOT_FOUND,
+    )
+}
+
+fn storage_failure(e: &RunRepositoryError) -> Response {
+    tracing::error!("Failed to read stored snapshots: {}", e);
+    json_error(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Failed to read stored snapshots",
+    )
+}
+
+/// Answers `GET /snapshot?at=`: the latest stored snapshot taken at or before `at`, as the
+/// dashboard showed it then.
+#[tracing::instrument(name = "snapshot_handler", skip(state))]
+pub async fn snapshot_handler(
+    query: Result<Query<SnapshotQuery>, QueryRejection>,
+    timestamps: Result<Query<TimestampQuery>, QueryRejection>,
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    let Some(run_repository) = &state.run_repository else {
+        return json_error(
+            StatusCode::NOT_FOUND,
+            "Run history is disabled, set DATABASE_URL to enable it",
+        );
+    };
+    let (Query(query), Query(timestamps)) = match (query, timestamps) {
+        (Ok(query), Ok(timestamps)) => (query, timestamps),
+        (Err(rejection), _) | (_, Err(rejection)) => {
+            return json_error(rejection.status(), &rejection.body_text());
+        }
+    };
+    let format = timestamps.or(state.timestamp_format);
+
+    match run_repository.snapshot_at(query.at).await {
+        Ok(Some(snapshot)) => {
+            json_response(historical_snapshot_json(&snapshot, format), StatusCode::OK)
+        }
+        Ok(None) => match run_repository.snapshot_range().await {
+            Ok(range) => unavailable(
+                &format!("No snapshot was stored at or before {}", query.at),
+                range,
+                format,
+            ),
+            Err(e) => storage_failure(&e),
+        },
+        Err(e) => storage_failure(&e),
+    }
+}
+
+/// Answers `GET /snapshots?from=&to=&step=`: the status counts of the latest stored snapshot
+/// of each `step` seconds from `from` until `to`, for charting.
+#[tracing::instrument(name = "snapshots_handler", skip(state))]
+pub async fn snapshots_handler(
+    query: Result<Query<SnapshotsQuery>, QueryRejection>,
+    timestamps: Result<Query<TimestampQuery>, QueryRejection>,
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    let Some(run_repository) = &state.run_repository else {
+        return json_error(
+            StatusCode::NOT_FOUND,
+            "Run history is disabled, set DATABASE_URL to enable it",
+        );
+    };
+    let (Query(query), Query(timestamps)) = match (query, timestamps) {
+        (Ok(query), Ok(timestamps)) => (query, timestamps),
+        (Err(rejection), _) | (_, Err(rejection)) => {
+            return json_error(rejection.status(), &rejection.body_text());
+        }
+    };
+    let format = timestamps.or(state.timestamp_format);
+    let step = TimeDelta::seconds(i64::from(query.step.unwrap_or(DEFAULT_STEP_SECONDS)));
+    if query.from >= query.to {
+        return json_error(StatusCode::BAD_REQUEST, "from must be before to");
+    }
+    if step <= TimeDelta::zero() {
+        return json_error(StatusCode::BAD_REQUEST, "step must be at least 1 second");
+    }
+    let points = (query.to - query.from).num_milliseconds() / step.num_milliseconds();
+    if points >= MAX_SNAPSHOT_POINTS {
+        return json_error(
+            StatusCode::BAD_REQUEST,
+            &format!("At most {MAX_SNAPSHOT_POINTS} steps may be requested at once"),
+        );
+    }
+
+    let range = match run_repository.snapshot_range().await {
+        Ok(range) => range,
+        Err(e) => return storage_failure(&e),
+    };
+    if range.is_none_or(|range| query.to <= range.oldest || query.from > range.newest) {
+        return unavailable(
+            &format!(
+                "No snapshot was stored from {} until {}",
+                query.from, query.to
+            ),
+            range,
+            format,
+        );
+    }
+    match run_repository
+        .snapshot_series(query.from, query.to, step)
+        .await
+    {
+        Ok(series) => json_response(
+            snapshot_series_json(query.from, query.to, step, &series, format),
+            StatusCode::OK,
+        ),
+        Err(e) => storage_failure(&e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::repositories::RunRepository;
+    use crate::infrastructures::adapters::primary::web::create_router;
+    use crate::infrastructures::adapters::secondary::persistence::SqliteRunRepository;
+    use crate::test_support::{StubGitHubApi, app_state, base_time, workflow_run};
+    use axum::body::{Body, to_bytes};
+    use axum::http::Request;
+    use chrono::SecondsFormat;
+    use tower::ServiceExt;
+
+    /// `base_time()` moved by `minutes`, as a query parameter.
+    fn minutes_after(minutes: i64) -> String {
+        (base_time() + TimeDelta::minutes(minutes)).to_rfc3339_opts(SecondsFormat::Secs, true)
+    }
+
+    async fn get_json(
+        state: Arc<AppState>,
+        uri: &str,
+    ) -> anyhow::Result<(StatusCode, serde_json::Value)> {
+        let response = create_router(state)
+            .oneshot(Request::get(uri).body(Body::empty())?)
+            .await?;
+        let status = response.status();
+        let body = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await?)?;
+        Ok((status, body))
+    }
+
+    /// Snapshots taken at `base_time()` with a run in progress, and 10 minutes later with
+    /// it failed.
+    async fn state_with_snapshots() -> anyhow::Result<Arc<AppState>> {
+        let run_repository = Arc::new(SqliteRunRepository::connect("sqlite::memory:")?);
+        run_repository
+            .save_runs(&[workflow_run("owner/repo", 1, "in_progress")], base_time())
+            .await?;
+        let mut failed = workflow_run("owner/repo", 1, "failure");
+        failed.updated_at += TimeDelta::minutes(10);
+        run_repository
+            .save_runs(&[failed], base_time() + TimeDelta::minutes(10))
+            .await?;
+        let state = Arc::into_inner(app_state(StubGitHubApi::default(), None)?)
+            .ok_or_else(|| anyhow::anyhow!("state is shared"))?
+            .with_run_repository(run_repository);
+        Ok(Arc::new(state))
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_is_the_latest_taken_by_the_requested_time() -> anyhow::Result<()> {
+        let state = state_with_snapshots().await?;
+
+        let (status, during) = get_json(
+            Arc::clone(&state),
+            &format!("/snapshot?at={}", minutes_after(9)),
+        )
+        .await?;
+        let (_, after) = get_json(
+            Arc::clone(&state),
+            &format!("/snapshot?at={}&ts=millis", minutes_after(10)),
+        )
+        .await?;
+        let (before_status, before) =
+            get_json(state, &format!("/snapshot?at={}", minutes_after(-1))).await?;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(during["sequence"], 1);
+        assert_eq!(during["runs"][0]["status"], "in_progress");
+        assert_eq!(after["sequence"], 2);
+        assert_eq!(after["runs"][0]["conclusion"], "failure");
+        assert!(after["generatedAt"].is_i64());
+        assert_eq!(before_status, StatusCode::NOT_FOUND);
+        assert_eq!(
+            before["availableRange"],
+            serde_json::json!({"oldest": minutes_after(0), "newest": minutes_after(10)})
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_snapshots_are_downsampled_within_the_stored_range() -> anyhow::Result<()> {
+        let state = state_with_snapshots().await?;
+
+        let (status, series) = get_json(
+            Arc::clone(&state),
+            &format!(
+                "/snapshots?from={}&to={}&step=600",
+                minutes_after(-10),
+                minutes_after(20)
+            ),
+        )
+        .await?;
+        let (outside, _) = get_json(
+            Arc::clone(&state),
+            &format!(
+                "/snapshots?from={}&to={}",
+                minutes_after(11),
+                minutes_after(20)
+            ),
+        )
+        .await?;
+        let (too_fine, _) = get_json(
+            state,
+            &format!(
+                "/snapshots?from={}&to={}&step=1",
+                minutes_after(0),
+                minutes_after(20)
+            ),
+        )
+        .await?;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(series["stepSeconds"], 600);
+        assert_eq!(
+            series["points"],
+            serde_json::json!([
+                {"sequence": 1, "generatedAt": minutes_after(0), "counts": {"in_progress": 1}},
+                {"sequence": 2, "generatedAt": minutes_after(10), "counts": {"failure": 1}},
+            ])
+        );
+        assert_eq!(outside, StatusCode::NOT_FOUND);
+        assert_eq!(too_fine, StatusCode::BAD_REQUEST);
+        Ok(())
+    }
+}
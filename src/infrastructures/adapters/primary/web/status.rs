@@ -0,0 +1,195 @@
+use super::AppState;
+use super::heartbeat::ConnectionHealth;
+use crate::application::services::PollerStatus;
+use crate::domain::external_apis::github::GitHubApi;
+use crate::domain::external_apis::github::calls::ApiCallCounts;
+use crate::infrastructures::adapters::secondary::external_apis::github::retry_events::GitHubApiHealthStatus;
+use axum::{
+    Json,
+    extract::State,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Quota of the GitHub token as of the last response.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RateLimitStatus {
+    pub remaining: Option<u64>,
+    pub limit: Option<u64>,
+    #[serde(rename = "tokenExpiresAt")]
+    pub token_expires_at: Option<DateTime<Utc>>,
+}
+
+impl RateLimitStatus {
+    /// The quota imported with `POST /import` if any, else the one GitHub last reported.
+    pub(super) fn current(state: &AppState) -> Self {
+        Self::read(&state.imported_rate_limit, &*state.github_api)
+    }
+
+    /// [`Self::current`] from the parts of the state it reads, for the metrics to hold on to.
+    pub(super) fn read(
+        imported: &Mutex<Option<Self>>,
+        github_api: &(dyn GitHubApi + Send + Sync),
+    ) -> Self {
+        if let Some(imported) = imported
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+        {
+            return imported;
+        }
+        let api_status = github_api.api_status();
+        Self {
+            remaining: api_status.rate_limit_remaining,
+            limit: api_status.rate_limit_limit,
+            token_expires_at: api_status.token_expires_at,
+        }
+    }
+}
+
+/// Body of `/status`
+#[derive(Serialize, Debug)]
+struct StatusResponse {
+    #[serde(flatten)]
+    poller: PollerStatus,
+    #[serde(rename = "rateLimit")]
+    rate_limit: RateLimitStatus,
+    /// Connected websocket and SSE clients
+    clients: usize,
+    /// Open websocket connections with their heartbeat round trip and missed acks
+    connections: Vec<ConnectionHealth>,
+    /// GitHub API requests made by on-demand routes such as `/costs` since startup
+    #[serde(rename = "onDemandGitHubCalls")]
+    on_demand_github_calls: ApiCallCounts,
+    /// Whether GitHub requests were retried, or given up on, lately
+    #[serde(rename = "githubApiHealth")]
+    github_api_health: GitHubApiHealthStatus,
+}
+
+/// Whether the poller is alive and what it last saw from GitHub.
+#[tracing::instrument(name = "status_handler", skip_all)]
+pub async fn status_handler(State(state): State<Arc<AppState>>) -> Response {
+    Json(StatusResponse {
+        poller: state.poller.status(),
+        rate_limit: RateLimitStatus::current(&state),
+        clients: state.poller.client_count(),
+        connections: state.websocket_connections.list(),
+        on_demand_github_calls: state.on_demand_github_calls.counts(),
+        github_api_health: state.github_retries.health(Utc::now()),
+    })
+    .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::services::{PollerEvent, PollerState};
+    use crate::application::use_cases::stream_github_actions_runs::StreamGitHubActionsRunsInteractor;
+    use crate::domain::external_apis::github::calls::GitHubOperation;
+    use crate::domain::external_apis::github::{GitHubApiError, Repository};
+    use crate::infrastructures::adapters::primary::web::create_router;
+    use crate::infrastructures::adapters::secondary::external_apis::github::retry_events::RetryEvent;
+    use crate::test_support::{StubGitHubApi, app_state, repo_full_name};
+    use crate::testing::{ScriptedGitHubApi, WorkflowRunBuilder};
+    use axum::body::{Body, to_bytes};
+    use axum::http::{Request, StatusCode};
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    async fn status(state: Arc<AppState>) -> anyhow::Result<serde_json::Value> {
+        let response = create_router(state)
+            .oneshot(Request::get("/status").body(Body::empty())?)
+            .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await?;
+        Ok(serde_json::from_slice(&body)?)
+    }
+
+    #[tokio::test]
+    async fn test_status_reflects_a_successful_and_a_failing_iteration() -> anyhow::Result<()> {
+        let repo = repo_full_name("owner/repo");
+        let github_api = ScriptedGitHubApi::new();
+        github_api
+            .repositories(Ok(vec![Repository::from_full_name(&repo)]))
+            .workflows(&repo, Ok(Vec::new()))
+            .workflow_runs(
+                &repo,
+                Ok(vec![WorkflowRunBuilder::new("owner/repo").build()]),
+            )
+            .workflow_runs(
+                &repo,
+                GitHubApiError::Unauthorized {
+                    resource: "workflow runs for owner/repo".to_string(),
+                },
+            );
+        let state = app_state(StubGitHubApi::default(), Some("secret-token"))?;
+        let mut events = state.poller.subscribe();
+        let poller_task = state
+            .poller
+            .spawn(Arc::new(StreamGitHubActionsRunsInteractor::new(Arc::new(
+                github_api,
+            ))));
+
+        let event = tokio::time::timeout(Duration::from_secs(5), events.recv()).await??;
+        assert!(matches!(event, PollerEvent::Snapshot(_)));
+        let succeeded = status(Arc::clone(&state)).await?;
+
+        state.refresh.notify_one();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while state.poller.status().state != PollerState::BackingOff {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await?;
+        let failed = status(Arc::clone(&state)).await?;
+        poller_task.abort();
+
+        assert_eq!(succeeded["state"], "running");
+        assert_eq!(succeeded["lastError"], serde_json::Value::Null);
+        assert!(succeeded["lastDurationMs"].is_u64());
+        assert_eq!(succeeded["repositories"][0]["repository"], "owner/repo");
+        assert_eq!(
+            succeeded["repositories"][0]["lastError"],
+            serde_json::Value::Null
+        );
+        assert_eq!(succeeded["clients"], 1);
+        assert_eq!(succeeded["rateLimit"]["remaining"], serde_json::Value::Null);
+        assert_eq!(succeeded["githubApiHealth"]["state"], "healthy");
+
+        assert_eq!(failed["state"], "backing-off");
+        assert_eq!(failed["lastSuccessAt"], succeeded["lastSuccessAt"]);
+        assert_eq!(failed["lastError"]["kind"], "unauthorized");
+        assert_eq!(
+            failed["lastError"]["message"],
+            "GitHub rejected the token for workflow runs for owner/repo"
+        );
+        let repository = &failed["repositories"][0];
+        assert_eq!(repository["lastSuccessAt"], succeeded["lastSuccessAt"]);
+        assert_eq!(repository["lastError"]["kind"], "unauthorized");
+        assert!(!failed.to_string().contains("secret-token"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_status_reports_retries_of_github_requests() -> anyhow::Result<()> {
+        let state = app_state(StubGitHubApi::default(), None)?;
+        state.github_retries.publish(RetryEvent {
+            at: Utc::now(),
+            operation: GitHubOperation::WorkflowRuns,
+            attempt: 3,
+            category: "network",
+            next_wait_ms: None,
+        });
+
+        let body = status(state).await?;
+
+        let health = &body["githubApiHealth"];
+        assert_eq!(health["state"], "failing");
+        assert_eq!(health["retries"], 1);
+        assert_eq!(health["lastRetry"]["operation"], "workflow_runs");
+        assert_eq!(health["lastRetry"]["nextWaitMs"], serde_json::Value::Null);
+        Ok(())
+    }
+}
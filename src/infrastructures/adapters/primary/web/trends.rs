@@ -0,0 +1,205 @@
+use super::{AppState, json_error};
+use crate::domain::models::ids::{RepoFullName, WorkflowId};
+use crate::domain::repositories::DailyStats;
+use axum::{
+    Json,
+    extract::{Query, State, rejection::QueryRejection},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use chrono::{NaiveDate, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Days covered by `/trends` when the request does not say
+pub(super) const DEFAULT_TREND_DAYS: i64 = 30;
+
+/// Most days one `/trends` request may cover
+pub(super) const MAX_TREND_DAYS: i64 = 365;
+
+/// Parameters of `GET /trends`.
+#[derive(Deserialize, Debug)]
+pub struct TrendQuery {
+    repo: RepoFullName,
+    /// Days to cover, counting today
+    days: Option<i64>,
+}
+
+/// One workflow on one day of a trend.
+#[derive(Serialize, Debug)]
+struct TrendPoint {
+    date: NaiveDate,
+    #[serde(rename = "workflowId")]
+    workflow_id: WorkflowId,
+    #[serde(rename = "workflowName")]
+    workflow_name: String,
+    runs: u64,
+    conclusions: BTreeMap<&'static str, u64>,
+    #[serde(rename = "successRate")]
+    success_rate: f64,
+    #[serde(rename = "meanDurationSeconds")]
+    mean_duration_seconds: f64,
+}
+
+impl From<DailyStats> for TrendPoint {
+    fn from(stats: DailyStats) -> Self {
+        Self {
+            date: stats.day,
+            workflow_id: stats.workflow_id,
+            workflow_name: stats.workflow_name,
+            runs: stats.runs,
+            conclusions: stats
+                .conclusions
+                .into_iter()
+                .map(|(conclusion, runs)| (conclusion.as_str(), runs))
+                .collect(),
+            success_rate: stats.success_rate,
+            mean_duration_seconds: stats.mean_duration_seconds,
+        }
+    }
+}
+
+/// Daily stats of a repository's workflows, oldest first; days without completed runs are
+/// left out.
+#[derive(Serialize, Debug)]
+struct Trend {
+    repository: RepoFullName,
+    since: NaiveDate,
+    series: Vec<TrendPoint>,
+}
+
+#[tracing::instrument(name = "trends_handler", skip(state))]
+pub async fn trends_handler(
+    query: Result<Query<TrendQuery>, QueryRejection>,
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    let Some(run_repository) = &state.run_repository else {
+        return json_error(
+            StatusCode::NOT_FOUND,
+            "Run history is disabled, set DATABASE_URL to enable it",
+        );
+    };
+    let Query(query) = match query {
+        Ok(query) => query,
+        Err(rejection) => return json_error(rejection.status(), &rejection.body_text()),
+    };
+    let days = query.days.unwrap_or(DEFAULT_TREND_DAYS);
+    if !(1..=MAX_TREND_DAYS).contains(&days) {
+        return json_error(
+            StatusCode::BAD_REQUEST,
+            &format!("days must be between 1 and {MAX_TREND_DAYS}"),
+        );
+    }
+
+    let since = Utc::now().date_naive() - TimeDelta::days(days - 1);
+    match run_repository.daily_stats(&query.repo, since).await {
+        Ok(daily_stats) => Json(Trend {
+            repository: query.repo,
+            since,
+            series: daily_stats.into_iter().map(TrendPoint::from).collect(),
+        })
+        .into_response(),
+        Err(e) => {
+            tracing::error!("Failed to read daily run stats: {}", e);
+            json_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to read trends")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::repositories::RunRepository;
+    use crate::infrastructures::adapters::primary::web::create_router;
+    use crate::infrastructures::adapters::secondary::persistence::SqliteRunRepository;
+    use crate::test_support::{StubGitHubApi, app_state, workflow_run};
+    use axum::body::{Body, to_bytes};
+    use axum::http::Request;
+    use chrono::NaiveTime;
+    use tower::ServiceExt;
+
+    async fn get_json(
+        state: Arc<AppState>,
+        uri: &str,
+    ) -> anyhow::Result<(StatusCode, serde_json::Value)> {
+        let response = create_router(state)
+            .oneshot(Request::get(uri).body(Body::empty())?)
+            .await?;
+        let status = response.status();
+        let body = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await?)?;
+        Ok((status, body))
+    }
+
+    /// A state whose history holds a success just before today's UTC midnight and a failure
+    /// just after, already aggregated.
+    async fn state_with_stats() -> anyhow::Result<Arc<AppState>> {
+        let midnight = Utc::now().date_naive().and_time(NaiveTime::MIN).and_utc();
+        let mut before = workflow_run("owner/repo", 1, "success");
+        before.created_at = midnight - TimeDelta::minutes(10);
+        before.updated_at = midnight + TimeDelta::minutes(5);
+        let mut after = workflow_run("owner/repo", 2, "failure");
+        after.created_at = midnight + TimeDelta::minutes(10);
+        after.updated_at = after.created_at + TimeDelta::minutes(1);
+
+        let run_repository = Arc::new(SqliteRunRepository::connect("sqlite::memory:")?);
+        run_repository
+            .save_runs(&[before, after], Utc::now())
+            .await?;
+        run_repository
+            .aggregate_daily_stats(midnight.date_naive() - TimeDelta::days(1))
+            .await?;
+        let state = app_state(StubGitHubApi::default(), None)?;
+        let state = Arc::into_inner(state)
+            .ok_or_else(|| anyhow::anyhow!("state is shared"))?
+            .with_run_repository(run_repository);
+        Ok(Arc::new(state))
+    }
+
+    #[tokio::test]
+    async fn test_trend_has_a_point_per_day() -> anyhow::Result<()> {
+        let state = state_with_stats().await?;
+
+        let (status, body) = get_json(Arc::clone(&state), "/trends?repo=owner/repo&days=2").await?;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["repository"], "owner/repo");
+        assert_eq!(body["series"].as_array().map(Vec::len), Some(2));
+        assert_eq!(body["series"][0]["conclusions"]["success"], 1);
+        assert_eq!(body["series"][0]["meanDurationSeconds"], 900.0);
+        assert_eq!(body["series"][1]["successRate"], 0.0);
+        assert_eq!(body["series"][1]["workflowName"], "CI");
+
+        // Yesterday falls outside a one-day trend
+        let (_, body) = get_json(state, "/trends?repo=owner/repo&days=1").await?;
+        assert_eq!(body["series"].as_array().map(Vec::len), Some(1));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_trends_reject_bad_queries() -> anyhow::Result<()> {
+        let state = state_with_stats().await?;
+
+        for uri in [
+            "/trends",
+            "/trends?repo=owner",
+            "/trends?repo=owner/repo&days=0",
+            "/trends?repo=owner/repo&days=366",
+        ] {
+            let (status, body) = get_json(Arc::clone(&state), uri).await?;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{uri}");
+            assert!(body["error"].is_string());
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_trends_are_not_found_without_a_store() -> anyhow::Result<()> {
+        let state = app_state(StubGitHubApi::default(), None)?;
+
+        let (status, _) = get_json(state, "/trends?repo=owner/repo").await?;
+
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        Ok(())
+    }
+}
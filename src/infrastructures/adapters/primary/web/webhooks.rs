@@ -0,0 +1,470 @@
+use super::auth::constant_time_eq;
+use super::{AppState, json_error};
+use crate::infrastructures::adapters::secondary::external_apis::github::{
+    workflow_job_from_event, workflow_run_from_event,
+};
+use axum::{
+    Json,
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use chrono::{TimeDelta, Utc};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+use std::collections::{HashSet, VecDeque};
+use std::fmt::Write;
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Deliveries whose run was last updated longer ago than this are rejected (seconds)
+const MAX_DELIVERY_AGE_SECONDS: i64 = 3_600;
+
+/// Number of recent delivery ids remembered to drop replays
+const REMEMBERED_DELIVERIES: usize = 1_024;
+
+/// Receives GitHub webhook deliveries signed with a shared secret.
+pub struct GitHubWebhooks {
+    secret: String,
+    max_title_length: Option<NonZeroUsize>,
+    max_delivery_age: TimeDelta,
+    deliveries: Mutex<RecentDeliveries>,
+}
+
+/// Delivery ids in arrival order, bounded to [`REMEMBERED_DELIVERIES`].
+#[derive(Default)]
+struct RecentDeliveries {
+    ids: HashSet<String>,
+    order: VecDeque<String>,
+}
+
+impl GitHubWebhooks {
+    /// Accepts deliveries signed with `secret`, the webhook's `GITHUB_WEBHOOK_SECRET`.
+    #[must_use]
+    pub fn new(secret: String) -> Self {
+        Self {
+            secret,
+            max_title_length: None,
+            max_delivery_age: TimeDelta::seconds(MAX_DELIVERY_AGE_SECONDS),
+            deliveries: Mutex::default(),
+        }
+    }
+
+    /// Truncates run titles as the GitHub API adapter does for polled runs.
+    #[must_use]
+    pub fn with_max_title_length(mut self, max_title_length: Option<NonZeroUsize>) -> Self {
+        self.max_title_length = max_title_length;
+        self
+    }
+
+    #[must_use]
+    pub fn with_max_delivery_age(mut self, max_delivery_age: TimeDelta) -> Self {
+        self.max_delivery_age = max_delivery_age;
+        self
+    }
+
+    /// Checks an `X-Hub-Signature-256` header value against the HMAC of `payload`.
+    fn verify_signature(&self, signature: &str, payload: &[u8]) -> bool {
+        match (
+            signature.strip_prefix("sha256="),
+            hmac_hex(&self.secret, payload),
+        ) {
+            (Some(signature), Some(expected)) => constant_time_eq(
+                signature.to_ascii_lowercase().as_bytes(),
+                expected.as_bytes(),
+            ),
+            _ => false,
+        }
+    }
+
+    /// Remembers `delivery`, returning false if it was already received.
+    fn first_delivery(&self, delivery: &str) -> bool {
+        let mut deliveries = self
+            .deliveries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if !deliveries.ids.insert(delivery.to_string()) {
+            return false;
+        }
+        deliveries.order.push_back(delivery.to_string());
+        if deliveries.order.len() > REMEMBERED_DELIVERIES
+            && let Some(oldest) = deliveries.order.pop_front()
+        {
+            deliveries.ids.remove(&oldest);
+        }
+        true
+    }
+}
+
+/// Hex-encoded HMAC-SHA256 of `payload` keyed with `secret`.
+fn hmac_hex(secret: &str, payload: &[u8]) -> Option<String> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).ok()?;
+    mac.update(payload);
+    Some(
+        mac.finalize()
+            .into_bytes()
+            .iter()
+            .fold(String::with_capacity(64), |mut hex, byte| {
+                let _ = write!(hex, "{byte:02x}");
+                hex
+            }),
+    )
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+/// Payload of a `ping` delivery, sent when a webhook is created.
+#[derive(Deserialize, Debug)]
+struct PingEvent {
+    zen: Option<String>,
+}
+
+/// `POST /webhooks/github`: dispatches signed deliveries on their `X-GitHub-Event`.
+///
+/// - `ping` is answered with its zen echoed
+/// - `workflow_run` updates the run in the latest snapshot
+/// - `workflow_job` updates the job views and the failed jobs of its run
+///
+/// Other events are accepted with 202 and counted, so GitHub does not report them as failed.
+#[tracing::instrument(name = "github_webhook_handler", skip_all)]
+pub async fn github_webhook_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    payload: Bytes,
+) -> Response {
+    let Some(webhooks) = &state.github_webhooks else {
+        return json_error(
+            StatusCode::NOT_FOUND,
+            "Webhooks are disabled, set GITHUB_WEBHOOK_SECRET to enable them",
+        );
+    };
+    match header(&headers, "x-hub-signature-256") {
+        Some(signature) if webhooks.verify_signature(signature, &payload) => {}
+        _ => {
+            tracing::warn!("Rejected webhook delivery with a missing or invalid signature");
+            return json_error(StatusCode::UNAUTHORIZED, "Invalid webhook signature");
+        }
+    }
+    let (Some(event), Some(delivery)) = (
+        header(&headers, "x-github-event"),
+        header(&headers, "x-github-delivery"),
+    ) else {
+        return json_error(
+            StatusCode::BAD_REQUEST,
+            "X-GitHub-Event and X-GitHub-Delivery are required",
+        );
+    };
+    match event {
+        "ping" => ping(&payload),
+        "workflow_run" => workflow_run(&state, webhooks, delivery, &payload).await,
+        "workflow_job" => workflow_job(&state, webhooks, delivery, &payload),
+        _ => {
+            tracing::debug!("Accepted unhandled {} webhook delivery {}", event, delivery);
+            state
+                .metrics
+                .webhook_events_unhandled
+                .with_label_values(&[event])
+                .inc();
+            StatusCode::ACCEPTED.into_response()
+        }
+    }
+}
+
+fn ping(payload: &[u8]) -> Response {
+    match serde_json::from_slice::<PingEvent>(payload) {
+        Ok(ping) => Json(serde_json::json!({ "zen": ping.zen })).into_response(),
+        Err(e) => json_error(StatusCode::BAD_REQUEST, &e.to_string()),
+    }
+}
+
+async fn workflow_run(
+    state: &AppState,
+    webhooks: &GitHubWebhooks,
+    delivery: &str,
+    payload: &[u8],
+) -> Response {
+    let run = match workflow_run_from_event(payload, webhooks.max_title_length) {
+        Ok(run) => run,
+        Err(e) => return json_error(StatusCode::BAD_REQUEST, &e.to_string()),
+    };
+    if Utc::now() - run.updated_at > webhooks.max_delivery_age {
+        tracing::warn!(
+            "Rejected stale webhook delivery {} of run {}",
+            delivery,
+            run.id
+        );
+        return json_error(StatusCode::BAD_REQUEST, "Stale webhook delivery");
+    }
+    if !webhooks.first_delivery(delivery) {
+        tracing::debug!("Ignoring replayed webhook delivery {}", delivery);
+        return StatusCode::NO_CONTENT.into_response();
+    }
+
+    tracing::info!("Webhook delivery {} updated run {}", delivery, run.id);
+    state.poller.push_run(run).await;
+    StatusCode::NO_CONTENT.into_response()
+}
+
+fn workflow_job(
+    state: &AppState,
+    webhooks: &GitHubWebhooks,
+    delivery: &str,
+    payload: &[u8],
+) -> Response {
+    let job = match workflow_job_from_event(payload) {
+        Ok(job) => job,
+        Err(e) => return json_error(StatusCode::BAD_REQUEST, &e.to_string()),
+    };
+    if !webhooks.first_delivery(delivery) {
+        tracing::debug!("Ignoring replayed webhook delivery {}", delivery);
+        return StatusCode::NO_CONTENT.into_response();
+    }
+
+    tracing::info!(
+        "Webhook delivery {} updated job {} of run {}",
+        delivery,
+        job.id,
+        job.run_id
+    );
+    state.poller.push_job(job);
+    StatusCode::NO_CONTENT.into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::use_cases::StreamGitHubActionsRunsUseCaseOutput;
+    use crate::domain::external_apis::github::Repository;
+    use crate::domain::models::ids::{JobId, RunId};
+    use crate::infrastructures::adapters::primary::web::create_router;
+    use crate::test_support::{
+        StubGitHubApi, WORKFLOW_JOB_EVENT, WORKFLOW_RUN_EVENT, app_state, base_time,
+    };
+    use axum::body::{Body, to_bytes};
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    const SECRET: &str = "It's a Secret to Everybody";
+
+    fn webhooks() -> GitHubWebhooks {
+        GitHubWebhooks::new(SECRET.to_string()).with_max_delivery_age(TimeDelta::days(365 * 100))
+    }
+
+    fn signature(payload: &str) -> anyhow::Result<String> {
+        let hex = hmac_hex(SECRET, payload.as_bytes()).ok_or_else(|| anyhow::anyhow!("no HMAC"))?;
+        Ok(format!("sha256={hex}"))
+    }
+
+    /// `AppState` whose latest snapshot covers the fixture's repository.
+    fn webhook_state(webhooks: GitHubWebhooks) -> anyhow::Result<Arc<AppState>> {
+        let state = Arc::into_inner(app_state(StubGitHubApi::default(), None)?)
+            .ok_or_else(|| anyhow::anyhow!("state is shared"))?
+            .with_github_webhooks(Arc::new(webhooks));
+        state.poller.publish(
+            StreamGitHubActionsRunsUseCaseOutput::new(Vec::new(), base_time())
+                .with_repositories(vec![Repository::new("octo-org", "octo-repo")?]),
+        );
+        Ok(Arc::new(state))
+    }
+
+    async fn deliver(
+        state: &Arc<AppState>,
+        event: &str,
+        delivery: &str,
+        signature: Option<&str>,
+        payload: &str,
+    ) -> anyhow::Result<StatusCode> {
+        Ok(send(state, event, delivery, signature, payload)
+            .await?
+            .status())
+    }
+
+    async fn send(
+        state: &Arc<AppState>,
+        event: &str,
+        delivery: &str,
+        signature: Option<&str>,
+        payload: &str,
+    ) -> anyhow::Result<Response> {
+        let mut request = Request::post("/webhooks/github")
+            .header("X-GitHub-Event", event)
+            .header("X-GitHub-Delivery", delivery);
+        if let Some(signature) = signature {
+            request = request.header("X-Hub-Signature-256", signature);
+        }
+        Ok(create_router(Arc::clone(state))
+            .oneshot(request.body(Body::from(payload.to_string()))?)
+            .await?
+            .into_response())
+    }
+
+    #[test]
+    fn test_signature_matches_github_example() {
+        // Example from GitHub's "Validating webhook deliveries" documentation
+        let webhooks = webhooks();
+
+        assert!(webhooks.verify_signature(
+            "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17",
+            b"Hello, World!"
+        ));
+        assert!(!webhooks.verify_signature(
+            "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17",
+            b"Hello, World?"
+        ));
+        assert!(!webhooks.verify_signature(
+            "sha1=01dc10d0c83e72ed246219cdd91669667fe2ca59",
+            b"Hello, World!"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_signed_workflow_run_updates_the_snapshot_once() -> anyhow::Result<()> {
+        let state = webhook_state(webhooks())?;
+        let signature = signature(WORKFLOW_RUN_EVENT)?;
+
+        let status = deliver(
+            &state,
+            "workflow_run",
+            "1",
+            Some(&signature),
+            WORKFLOW_RUN_EVENT,
+        )
+        .await?;
+
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let latest = state
+            .snapshots
+            .latest()
+            .ok_or_else(|| anyhow::anyhow!("no snapshot"))?;
+        assert_eq!(
+            latest.runs.iter().map(|run| run.id).collect::<Vec<_>>(),
+            vec![RunId(30_433_642)]
+        );
+
+        // GitHub redelivering the same delivery does not publish again
+        let mut events = state.poller.subscribe();
+        let status = deliver(
+            &state,
+            "workflow_run",
+            "1",
+            Some(&signature),
+            WORKFLOW_RUN_EVENT,
+        )
+        .await?;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        assert!(events.try_recv().is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unsigned_and_stale_deliveries_are_rejected() -> anyhow::Result<()> {
+        let state = webhook_state(webhooks())?;
+
+        let unsigned = deliver(&state, "workflow_run", "1", None, WORKFLOW_RUN_EVENT).await?;
+        let forged = deliver(
+            &state,
+            "workflow_run",
+            "2",
+            Some(&signature("{}")?),
+            WORKFLOW_RUN_EVENT,
+        )
+        .await?;
+        assert_eq!(unsigned, StatusCode::UNAUTHORIZED);
+        assert_eq!(forged, StatusCode::UNAUTHORIZED);
+
+        // The fixture's run was last updated in 2024
+        let strict = webhook_state(GitHubWebhooks::new(SECRET.to_string()))?;
+        let outdated = deliver(
+            &strict,
+            "workflow_run",
+            "3",
+            Some(&signature(WORKFLOW_RUN_EVENT)?),
+            WORKFLOW_RUN_EVENT,
+        )
+        .await?;
+        assert_eq!(outdated, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            state.snapshots.latest().map(|latest| latest.runs.len()),
+            Some(0)
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ping_echoes_the_zen() -> anyhow::Result<()> {
+        let state = webhook_state(webhooks())?;
+        let payload = r#"{"zen": "Keep it logically awesome.", "hook_id": 1}"#;
+
+        let response = send(&state, "ping", "1", Some(&signature(payload)?), payload).await?;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: serde_json::Value =
+            serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await?)?;
+        assert_eq!(body["zen"], "Keep it logically awesome.");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_workflow_job_marks_its_run_failed() -> anyhow::Result<()> {
+        let state = webhook_state(webhooks())?;
+        deliver(
+            &state,
+            "workflow_run",
+            "1",
+            Some(&signature(WORKFLOW_RUN_EVENT)?),
+            WORKFLOW_RUN_EVENT,
+        )
+        .await?;
+
+        let status = deliver(
+            &state,
+            "workflow_job",
+            "2",
+            Some(&signature(WORKFLOW_JOB_EVENT)?),
+            WORKFLOW_JOB_EVENT,
+        )
+        .await?;
+
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let latest = state
+            .snapshots
+            .latest()
+            .ok_or_else(|| anyhow::anyhow!("no snapshot"))?;
+        assert_eq!(latest.runs[0].failed_jobs, ["test (ubuntu-latest)"]);
+        assert!(state.poller.jobs().job(JobId(21_400_001)).is_some());
+
+        let malformed = deliver(&state, "workflow_job", "3", Some(&signature("{}")?), "{}").await?;
+        assert_eq!(malformed, StatusCode::BAD_REQUEST);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_other_events_are_accepted_and_counted() -> anyhow::Result<()> {
+        let state = webhook_state(webhooks())?;
+        let payload = r#"{"action": "opened"}"#;
+
+        let status = deliver(
+            &state,
+            "pull_request",
+            "1",
+            Some(&signature(payload)?),
+            payload,
+        )
+        .await?;
+
+        assert_eq!(status, StatusCode::ACCEPTED);
+        assert_eq!(
+            state
+                .metrics
+                .webhook_events_unhandled
+                .with_label_values(&["pull_request"])
+                .get(),
+            1
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,361 @@
+use super::{AppState, github_error_response, json_error};
+use crate::domain::external_apis::github::GitHubApiError;
+use crate::domain::models::ids::{InvalidRepoFullName, RepoFullName};
+use axum::{
+    Json,
+    extract::{
+        Path, Query, State,
+        rejection::{PathRejection, QueryRejection},
+    },
+    response::{IntoResponse, Response},
+};
+use chrono::{TimeDelta, Utc};
+use serde::Deserialize;
+use std::sync::Arc;
+
+/// How long workflows of a repository the poller does not refresh are reused (seconds)
+const WORKFLOW_INVENTORY_TTL_SECONDS: i64 = 300;
+
+/// Path parameters of `GET /repos/{owner}/{repo}/workflows`, rejected by the extractor when malformed
+#[derive(Deserialize, Debug)]
+#[serde(try_from = "RepoPathSegments")]
+pub struct RepoPath {
+    pub repo: RepoFullName,
+}
+
+#[derive(Deserialize)]
+struct RepoPathSegments {
+    owner: String,
+    repo: String,
+}
+
+impl TryFrom<RepoPathSegments> for RepoPath {
+    type Error = InvalidRepoFullName;
+
+    fn try_from(segments: RepoPathSegments) -> Result<Self, Self::Error> {
+        Ok(Self {
+            repo: RepoFullName::new(segments.owner, segments.repo)?,
+        })
+    }
+}
+
+/// Path parameters of `GET /repos/{owner}/{repo}/workflows/{file}/inputs`
+#[derive(Deserialize, Debug)]
+#[serde(try_from = "WorkflowFileSegments")]
+pub struct WorkflowFilePath {
+    pub repo: RepoFullName,
+    /// Name of a file in `.github/workflows`, e.g. `deploy.yml`
+    pub file: String,
+}
+
+#[derive(Deserialize)]
+struct WorkflowFileSegments {
+    owner: String,
+    repo: String,
+    file: String,
+}
+
+impl TryFrom<WorkflowFileSegments> for WorkflowFilePath {
+    type Error = String;
+
+    fn try_from(segments: WorkflowFileSegments) -> Result<Self, Self::Error> {
+        let repo = RepoFullName::new(segments.owner, segments.repo).map_err(|e| e.to_string())?;
+        // A file of the workflows directory, not a path out of it
+        if segments.file.is_empty() || segments.file.starts_with('.') || segments.file.contains('/')
+        {
+            return Err(format!("Invalid workflow file name {:?}", segments.file));
+        }
+        Ok(Self {
+            repo,
+            file: segments.file,
+        })
+    }
+}
+
+/// Query of `GET /repos/{owner}/{repo}/workflows/{file}/inputs`
+#[derive(Deserialize, Debug)]
+pub struct WorkflowInputsQuery {
+    /// Branch, tag or commit to read the workflow file at; the default branch when absent
+    #[serde(rename = "ref")]
+    git_ref: Option<String>,
+}
+
+#[tracing::instrument(name = "workflows_handler", skip(state))]
+pub async fn workflows_handler(
+    path: Result<Path<RepoPath>, PathRejection>,
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    let Path(RepoPath { repo }) = match path {
+        Ok(path) => path,
+        Err(rejection) => return json_error(rejection.status(), &rejection.body_text()),
+    };
+
+    let inventory = state.poller.workflows();
+    let cached = inventory.get(&repo).await;
+    let entry = match cached {
+        Some(entry)
+            if Utc::now() - entry.fetched_at
+                < TimeDelta::seconds(WORKFLOW_INVENTORY_TTL_SECONDS) =>
+        {
+            entry
+        }
+        cached => match state.github_api.fetch_workflows(&repo).await {
+            Ok(workflows) => inventory.insert(repo.clone(), workflows, Utc::now()).await,
+            Err(e) => match cached {
+                // An outdated inventory beats none while GitHub is unavailable
+                Some(entry) if !matches!(e, GitHubApiError::NotFound { .. }) => {
+                    tracing::warn!("Serving stale workflows of {}: {}", repo, e);
+                    entry
+                }
+                _ => return github_error_response(&e, &format!("Repository {repo} was not found")),
+            },
+        },
+    };
+    let scheduled = inventory
+        .scheduled(state.github_api.as_ref(), &repo, entry, Utc::now())
+        .await;
+    Json(scheduled).into_response()
+}
+
+/// Inputs a workflow is dispatched with, read from its workflow file on demand.
+#[tracing::instrument(name = "workflow_inputs_handler", skip(state))]
+pub async fn workflow_inputs_handler(
+    path: Result<Path<WorkflowFilePath>, PathRejection>,
+    query: Result<Query<WorkflowInputsQuery>, QueryRejection>,
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    let Path(WorkflowFilePath { repo, file }) = match path {
+        Ok(path) => path,
+        Err(rejection) => return json_error(rejection.status(), &rejection.body_text()),
+    };
+    let Query(query) = match query {
+        Ok(query) => query,
+        Err(rejection) => return json_error(rejection.status(), &rejection.body_text()),
+    };
+
+    let path = format!(".github/workflows/{file}");
+    match state
+        .github_api
+        .fetch_workflow_definition(&repo, &path, query.git_ref.as_deref())
+        .await
+    {
+        Ok(definition) => Json(definition.dispatch).into_response(),
+        Err(e) => {
+            github_error_response(&e, &format!("Workflow file {path} of {repo} was not found"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::workflow::WorkflowState;
+    use crate::domain::models::workflow_definition::WorkflowDefinition;
+    use crate::infrastructures::adapters::primary::web::create_router;
+    use crate::test_support::{
+        DISPATCH_WORKFLOW, StubGitHubApi, app_state, base_time, repo_full_name, workflow,
+    };
+    use axum::body::{Body, to_bytes};
+    use axum::http::Request;
+    use axum::http::StatusCode;
+    use tower::ServiceExt;
+
+    async fn get_json(
+        state: Arc<AppState>,
+        uri: &str,
+    ) -> anyhow::Result<(StatusCode, serde_json::Value)> {
+        let response = create_router(state)
+            .oneshot(Request::get(uri).body(Body::empty())?)
+            .await?;
+        let status = response.status();
+        let body = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await?)?;
+        Ok((status, body))
+    }
+
+    #[tokio::test]
+    async fn test_workflows_are_fetched_on_demand_and_cached() -> anyhow::Result<()> {
+        let github_api = StubGitHubApi {
+            workflows: vec![
+                workflow(1, WorkflowState::Active),
+                workflow(2, WorkflowState::DisabledManually),
+            ],
+            ..StubGitHubApi::default()
+        };
+        let state = app_state(github_api, None)?;
+
+        let (status, body) = get_json(Arc::clone(&state), "/repos/owner/repo/workflows").await?;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["workflows"][0]["state"], "active");
+        assert_eq!(body["workflows"][1]["state"], "disabled_manually");
+        assert_eq!(
+            body["workflows"][1]["path"],
+            ".github/workflows/workflow-2.yml"
+        );
+        assert!(
+            state
+                .poller
+                .workflows()
+                .get(&repo_full_name("owner/repo"))
+                .await
+                .is_some()
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fresh_inventory_is_served_without_fetching() -> anyhow::Result<()> {
+        // The stub has no workflows, so a fetch would return an empty list
+        let state = app_state(StubGitHubApi::default(), None)?;
+        state
+            .poller
+            .workflows()
+            .insert(
+                repo_full_name("owner/repo"),
+                vec![workflow(1, WorkflowState::Active)],
+                Utc::now(),
+            )
+            .await;
+
+        let (_, body) = get_json(Arc::clone(&state), "/repos/owner/repo/workflows").await?;
+        assert_eq!(body["workflows"].as_array().map(Vec::len), Some(1));
+
+        // An expired entry is replaced by a fresh fetch
+        state
+            .poller
+            .workflows()
+            .insert(
+                repo_full_name("owner/repo"),
+                vec![workflow(1, WorkflowState::Active)],
+                base_time(),
+            )
+            .await;
+        let (_, body) = get_json(state, "/repos/owner/repo/workflows").await?;
+        assert_eq!(body["workflows"].as_array().map(Vec::len), Some(0));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_workflows_list_their_next_scheduled_run() -> anyhow::Result<()> {
+        let github_api = StubGitHubApi {
+            workflows: vec![
+                workflow(1, WorkflowState::Active),
+                workflow(2, WorkflowState::DisabledManually),
+            ],
+            workflow_definition: Some(WorkflowDefinition {
+                schedules: vec!["0 */6 * * *".to_string(), "every day".to_string()],
+                ..WorkflowDefinition::default()
+            }),
+            ..StubGitHubApi::default()
+        };
+        let state = app_state(github_api, None)?;
+
+        let (status, body) = get_json(state, "/repos/owner/repo/workflows").await?;
+
+        assert_eq!(status, StatusCode::OK);
+        let next = body["workflows"][0]["nextScheduledAt"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("no next scheduled run"))?
+            .parse::<chrono::DateTime<Utc>>()?;
+        assert!(next > Utc::now() && next <= Utc::now() + TimeDelta::hours(6));
+        assert_eq!(
+            body["workflows"][0]["scheduleError"],
+            r#"invalid cron expression "every day": expected 5 fields, found 2"#
+        );
+        assert_eq!(
+            body["workflows"][1]["nextScheduledAt"],
+            serde_json::Value::Null
+        );
+        assert!(body["workflows"][1].get("scheduleError").is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_workflow_inputs_are_read_from_the_workflow_file() -> anyhow::Result<()> {
+        let github_api = StubGitHubApi {
+            workflow_definition: Some(WorkflowDefinition::from_yaml(DISPATCH_WORKFLOW)?),
+            ..StubGitHubApi::default()
+        };
+        let state = app_state(github_api, None)?;
+
+        let (status, body) = get_json(
+            state,
+            "/repos/owner/repo/workflows/deploy.yml/inputs?ref=main",
+        )
+        .await?;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["dispatchable"], true);
+        assert_eq!(
+            body["inputs"][0],
+            serde_json::json!({
+                "name": "environment",
+                "description": "Where to deploy",
+                "type": "choice",
+                "required": true,
+                "default": "staging",
+                "options": ["staging", "production"]
+            })
+        );
+        assert_eq!(body["inputs"][1]["default"], false);
+        assert_eq!(body["inputs"][2]["default"], 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_workflows_without_dispatch_trigger_have_an_empty_schema() -> anyhow::Result<()> {
+        let github_api = StubGitHubApi {
+            workflow_definition: Some(WorkflowDefinition::from_yaml("on: push\n")?),
+            ..StubGitHubApi::default()
+        };
+        let state = app_state(github_api, None)?;
+
+        let (status, body) = get_json(state, "/repos/owner/repo/workflows/ci.yml/inputs").await?;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            body,
+            serde_json::json!({"dispatchable": false, "inputs": []})
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unreadable_workflow_files_are_unprocessable() -> anyhow::Result<()> {
+        let github_api = StubGitHubApi {
+            error: Some(GitHubApiError::InvalidWorkflow {
+                resource: ".github/workflows/broken.yml of owner/repo".to_string(),
+                message: "line 3: unexpected indentation".to_string(),
+            }),
+            ..StubGitHubApi::default()
+        };
+        let state = app_state(github_api, None)?;
+
+        let (status, body) = get_json(
+            Arc::clone(&state),
+            "/repos/owner/repo/workflows/broken.yml/inputs",
+        )
+        .await?;
+        let (outside, _) = get_json(
+            state,
+            "/repos/owner/repo/workflows/..%2Fdependabot.yml/inputs",
+        )
+        .await?;
+
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["error"], "line 3: unexpected indentation");
+        assert_eq!(outside, StatusCode::BAD_REQUEST);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_malformed_repository_is_rejected() -> anyhow::Result<()> {
+        let state = app_state(StubGitHubApi::default(), None)?;
+
+        let (status, body) = get_json(state, "/repos/owner/re%20po/workflows").await?;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"].is_string());
+        Ok(())
+    }
+}
@@ -1 +1,3 @@
 pub mod external_apis;
+pub mod notifications;
+pub mod persistence;
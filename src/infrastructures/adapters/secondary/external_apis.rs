@@ -1,2 +1,6 @@
+#[cfg(feature = "fixtures")]
+pub mod fixtures;
 pub mod github;
+pub mod mock;
 pub use github::GitHubApiAdapter;
+pub use mock::MockGitHubApi;
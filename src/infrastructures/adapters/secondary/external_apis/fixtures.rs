@@ -0,0 +1,754 @@
+use crate::domain::external_apis::github::{
+    GitHubApi, GitHubApiError, GitHubApiStatus, RateLimit, Repository,
+};
+use crate::domain::models::commit_status::CommitStatus;
+use crate::domain::models::deployment::{Deployment, DeploymentStatus};
+use crate::domain::models::ids::{DeploymentId, JobId, RepoFullName, RunId, WorkflowId};
+use crate::domain::models::job::Job;
+use crate::domain::models::run::WorkflowRun;
+use crate::domain::models::runner::SelfHostedRunner;
+use crate::domain::models::status::RunStatus;
+use crate::domain::models::timing::RunTiming;
+use crate::domain::models::workflow::Workflow;
+use crate::domain::models::workflow_definition::WorkflowDefinition;
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+/// A fixture directory that cannot be loaded.
+#[derive(Debug, thiserror::Error)]
+pub enum FixtureError {
+    #[error("Failed to read fixture {path}: {source}")]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Invalid fixture {path}: {source}")]
+    Parse {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+}
+
+/// One call to the GitHub API and what it returned, stored as one JSON file.
+#[derive(Serialize, Deserialize)]
+struct Interaction {
+    operation: String,
+    arguments: Value,
+    /// `{"Ok": ...}` or `{"Err": ...}`
+    response: Value,
+}
+
+/// Calls are matched on their operation and arguments.
+fn interaction_key(operation: &str, arguments: &Value) -> String {
+    format!("{operation} {arguments}")
+}
+
+/// `GitHubApi` decorator writing every call made to `inner` into a fixture directory.
+///
+/// Files are numbered in the order calls complete, so [`ReplayGitHubApi`] serves repeated
+/// calls back in the same order.
+pub struct RecordingGitHubApi<G> {
+    inner: G,
+    dir: PathBuf,
+    sequence: AtomicU64,
+}
+
+impl<G> RecordingGitHubApi<G> {
+    /// Records into `dir`, created on the first call; files already there are kept.
+    #[must_use]
+    pub fn new(inner: G, dir: impl Into<PathBuf>) -> Self {
+        let dir = dir.into();
+        // Number after the files of an earlier session so replay keeps their order
+        let existing = std::fs::read_dir(&dir).map_or(0, Iterator::count);
+        Self {
+            inner,
+            dir,
+            sequence: AtomicU64::new(existing as u64),
+        }
+    }
+
+    /// Writes one interaction; a failed write is logged rather than failing the call.
+    async fn record<T: Serialize>(
+        &self,
+        operation: &str,
+        arguments: Value,
+        response: &Result<T, GitHubApiError>,
+    ) {
+        let sequence = self.sequence.fetch_add(1, Ordering::Relaxed);
+        let path = self.dir.join(format!("{sequence:06}-{operation}.json"));
+        let interaction = Interaction {
+            operation: operation.to_string(),
+            arguments,
+            response: json!(response),
+        };
+        let written = match serde_json::to_vec_pretty(&interaction) {
+            Ok(bytes) => match tokio::fs::create_dir_all(&self.dir).await {
+                Ok(()) => tokio::fs::write(&path, bytes).await,
+                Err(e) => Err(e),
+            },
+            Err(e) => Err(e.into()),
+        };
+        if let Err(e) = written {
+            tracing::warn!("Failed to record {}: {:?}", path.display(), e);
+        }
+    }
+}
+
+#[async_trait]
+impl<G: GitHubApi + Send + Sync> GitHubApi for RecordingGitHubApi<G> {
+    async fn fetch_repositories(&self, count: u8) -> Result<Vec<Repository>, GitHubApiError> {
+        let response = self.inner.fetch_repositories(count).await;
+        self.record("fetch_repositories", json!({ "count": count }), &response)
+            .await;
+        response
+    }
+
+    async fn fetch_repository(&self, repo: &RepoFullName) -> Result<Repository, GitHubApiError> {
+        let response = self.inner.fetch_repository(repo).await;
+        self.record("fetch_repository", json!({ "repo": repo }), &response)
+            .await;
+        response
+    }
+
+    async fn fetch_workflow_runs(
+        &self,
+        repo: &RepoFullName,
+        count: u8,
+    ) -> Result<Vec<WorkflowRun>, GitHubApiError> {
+        let response = self.inner.fetch_workflow_runs(repo, count).await;
+        self.record(
+            "fetch_workflow_runs",
+            json!({ "repo": repo, "count": count }),
+            &response,
+        )
+        .await;
+        response
+    }
+
+    async fn fetch_workflow_runs_of_workflow(
+        &self,
+        repo: &RepoFullName,
+        workflow_id: WorkflowId,
+        count: u8,
+    ) -> Result<Vec<WorkflowRun>, GitHubApiError> {
+        let response = self
+            .inner
+            .fetch_workflow_runs_of_workflow(repo, workflow_id, count)
+            .await;
+        self.record(
+            "fetch_workflow_runs_of_workflow",
+            json!({ "repo": repo, "workflowId": workflow_id, "count": count }),
+            &response,
+        )
+        .await;
+        response
+    }
+
+    async fn fetch_workflow_runs_by_status(
+        &self,
+        repo: &RepoFullName,
+        status: RunStatus,
+        count: u8,
+    ) -> Result<Vec<WorkflowRun>, GitHubApiError> {
+        let response = self
+            .inner
+            .fetch_workflow_runs_by_status(repo, status, count)
+            .await;
+        self.record(
+            "fetch_workflow_runs_by_status",
+            json!({ "repo": repo, "status": status, "count": count }),
+            &response,
+        )
+        .await;
+        response
+    }
+
+    async fn fetch_workflow_runs_on_branch(
+        &self,
+        repo: &RepoFullName,
+        branch: &str,
+        count: u8,
+    ) -> Result<Vec<WorkflowRun>, GitHubApiError> {
+        let response = self
+            .inner
+            .fetch_workflow_runs_on_branch(repo, branch, count)
+            .await;
+        self.record(
+            "fetch_workflow_runs_on_branch",
+            json!({ "repo": repo, "branch": branch, "count": count }),
+            &response,
+        )
+        .await;
+        response
+    }
+
+    async fn fetch_workflow_run(
+        &self,
+        repo: &RepoFullName,
+        run_id: RunId,
+    ) -> Result<WorkflowRun, GitHubApiError> {
+        let response = self.inner.fetch_workflow_run(repo, run_id).await;
+        self.record(
+            "fetch_workflow_run",
+            json!({ "repo": repo, "runId": run_id }),
+            &response,
+        )
+        .await;
+        response
+    }
+
+    async fn fetch_workflow_jobs(
+        &self,
+        repo: &RepoFullName,
+        run_id: RunId,
+    ) -> Result<Vec<Job>, GitHubApiError> {
+        let response = self.inner.fetch_workflow_jobs(repo, run_id).await;
+        self.record(
+            "fetch_workflow_jobs",
+            json!({ "repo": repo, "runId": run_id }),
+            &response,
+        )
+        .await;
+        response
+    }
+
+    async fn fetch_workflow_job(
+        &self,
+        repo: &RepoFullName,
+        job_id: JobId,
+    ) -> Result<Job, GitHubApiError> {
+        let response = self.inner.fetch_workflow_job(repo, job_id).await;
+        self.record(
+            "fetch_workflow_job",
+            json!({ "repo": repo, "jobId": job_id }),
+            &response,
+        )
+        .await;
+        response
+    }
+
+    async fn fetch_job_logs(
+        &self,
+        repo: &RepoFullName,
+        job_id: JobId,
+    ) -> Result<String, GitHubApiError> {
+        let response = self.inner.fetch_job_logs(repo, job_id).await;
+        self.record(
+            "fetch_job_logs",
+            json!({ "repo": repo, "jobId": job_id }),
+            &response,
+        )
+        .await;
+        response
+    }
+
+    async fn fetch_workflows(&self, repo: &RepoFullName) -> Result<Vec<Workflow>, GitHubApiError> {
+        let response = self.inner.fetch_workflows(repo).await;
+        self.record("fetch_workflows", json!({ "repo": repo }), &response)
+            .await;
+        response
+    }
+
+    async fn fetch_run_timing(
+        &self,
+        repo: &RepoFullName,
+        run_id: RunId,
+    ) -> Result<RunTiming, GitHubApiError> {
+        let response = self.inner.fetch_run_timing(repo, run_id).await;
+        self.record(
+            "fetch_run_timing",
+            json!({ "repo": repo, "runId": run_id }),
+            &response,
+        )
+        .await;
+        response
+    }
+
+    async fn fetch_runners(
+        &self,
+        repo: &RepoFullName,
+    ) -> Result<Vec<SelfHostedRunner>, GitHubApiError> {
+        let response = self.inner.fetch_runners(repo).await;
+        self.record("fetch_runners", json!({ "repo": repo }), &response)
+            .await;
+        response
+    }
+
+    async fn fetch_deployments(
+        &self,
+        repo: &RepoFullName,
+        environment: Option<&str>,
+    ) -> Result<Vec<Deployment>, GitHubApiError> {
+        let response = self.inner.fetch_deployments(repo, environment).await;
+        self.record(
+            "fetch_deployments",
+            json!({ "repo": repo, "environment": environment }),
+            &response,
+        )
+        .await;
+        response
+    }
+
+    async fn fetch_deployment_statuses(
+        &self,
+        repo: &RepoFullName,
+        deployment_id: DeploymentId,
+    ) -> Result<Vec<DeploymentStatus>, GitHubApiError> {
+        let response = self
+            .inner
+            .fetch_deployment_statuses(repo, deployment_id)
+            .await;
+        self.record(
+            "fetch_deployment_statuses",
+            json!({ "repo": repo, "deploymentId": deployment_id }),
+            &response,
+        )
+        .await;
+        response
+    }
+
+    async fn fetch_combined_status(
+        &self,
+        repo: &RepoFullName,
+        sha: &str,
+    ) -> Result<Vec<CommitStatus>, GitHubApiError> {
+        let response = self.inner.fetch_combined_status(repo, sha).await;
+        self.record(
+            "fetch_combined_status",
+            json!({ "repo": repo, "sha": sha }),
+            &response,
+        )
+        .await;
+        response
+    }
+
+    async fn fetch_branch_protection_required_checks(
+        &self,
+        repo: &RepoFullName,
+        branch: &str,
+    ) -> Result<Vec<String>, GitHubApiError> {
+        let response = self
+            .inner
+            .fetch_branch_protection_required_checks(repo, branch)
+            .await;
+        self.record(
+            "fetch_branch_protection_required_checks",
+            json!({ "repo": repo, "branch": branch }),
+            &response,
+        )
+        .await;
+        response
+    }
+
+    async fn fetch_workflow_definition(
+        &self,
+        repo: &RepoFullName,
+        path: &str,
+        git_ref: Option<&str>,
+    ) -> Result<WorkflowDefinition, GitHubApiError> {
+        let response = self
+            .inner
+            .fetch_workflow_definition(repo, path, git_ref)
+            .await;
+        self.record(
+            "fetch_workflow_definition",
+            json!({ "repo": repo, "path": path, "ref": git_ref }),
+            &response,
+        )
+        .await;
+        response
+    }
+
+    async fn fetch_rate_limit(&self) -> Result<RateLimit, GitHubApiError> {
+        let response = self.inner.fetch_rate_limit().await;
+        self.record("fetch_rate_limit", json!({}), &response).await;
+        response
+    }
+
+    fn api_status(&self) -> GitHubApiStatus {
+        self.inner.api_status()
+    }
+}
+
+/// `GitHubApi` serving the responses written by [`RecordingGitHubApi`].
+///
+/// Each call gets the next unused response recorded for the same operation and arguments.
+/// A call with no response left fails instead of returning empty data, so a test that
+/// drifts from its recording is noticed.
+pub struct ReplayGitHubApi {
+    responses: Mutex<HashMap<String, VecDeque<Value>>>,
+}
+
+impl ReplayGitHubApi {
+    /// Loads every `*.json` fixture of `dir`, in file name order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `dir` or one of its fixtures cannot be read or parsed.
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, FixtureError> {
+        let dir = dir.as_ref();
+        let read_error = |path: &Path| {
+            let path = path.to_path_buf();
+            move |source| FixtureError::Read { path, source }
+        };
+        let mut paths = std::fs::read_dir(dir)
+            .map_err(read_error(dir))?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(read_error(dir))?;
+        paths.retain(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        });
+        paths.sort();
+
+        let mut responses: HashMap<String, VecDeque<Value>> = HashMap::new();
+        for path in paths {
+            let text = std::fs::read_to_string(&path).map_err(read_error(&path))?;
+            let interaction: Interaction = serde_json::from_str(&text)
+                .map_err(|source| FixtureError::Parse { path, source })?;
+            responses
+                .entry(interaction_key(
+                    &interaction.operation,
+                    &interaction.arguments,
+                ))
+                .or_default()
+                .push_back(interaction.response);
+        }
+        Ok(Self {
+            responses: Mutex::new(responses),
+        })
+    }
+
+    fn replay<T: DeserializeOwned>(
+        &self,
+        operation: &str,
+        arguments: &Value,
+    ) -> Result<T, GitHubApiError> {
+        let key = interaction_key(operation, arguments);
+        let response = self
+            .responses
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_mut(&key)
+            .and_then(VecDeque::pop_front);
+        let Some(response) = response else {
+            tracing::error!("No recorded response left for {}", key);
+            return Err(GitHubApiError::Other {
+                resource: key,
+                message: "No recorded response left in the replay fixtures".to_string(),
+            });
+        };
+        serde_json::from_value::<Result<T, GitHubApiError>>(response).map_err(|e| {
+            GitHubApiError::Decode {
+                resource: key,
+                message: e.to_string(),
+            }
+        })?
+    }
+}
+
+#[async_trait]
+impl GitHubApi for ReplayGitHubApi {
+    async fn fetch_repositories(&self, count: u8) -> Result<Vec<Repository>, GitHubApiError> {
+        self.replay("fetch_repositories", &json!({ "count": count }))
+    }
+
+    async fn fetch_repository(&self, repo: &RepoFullName) -> Result<Repository, GitHubApiError> {
+        self.replay("fetch_repository", &json!({ "repo": repo }))
+    }
+
+    async fn fetch_workflow_runs(
+        &self,
+        repo: &RepoFullName,
+        count: u8,
+    ) -> Result<Vec<WorkflowRun>, GitHubApiError> {
+        self.replay(
+            "fetch_workflow_runs",
+            &json!({ "repo": repo, "count": count }),
+        )
+    }
+
+    async fn fetch_workflow_runs_of_workflow(
+        &self,
+        repo: &RepoFullName,
+        workflow_id: WorkflowId,
+        count: u8,
+    ) -> Result<Vec<WorkflowRun>, GitHubApiError> {
+        self.replay(
+            "fetch_workflow_runs_of_workflow",
+            &json!({ "repo": repo, "workflowId": workflow_id, "count": count }),
+        )
+    }
+
+    async fn fetch_workflow_runs_by_status(
+        &self,
+        repo: &RepoFullName,
+        status: RunStatus,
+        count: u8,
+    ) -> Result<Vec<WorkflowRun>, GitHubApiError> {
+        self.replay(
+            "fetch_workflow_runs_by_status",
+            &json!({ "repo": repo, "status": status, "count": count }),
+        )
+    }
+
+    async fn fetch_workflow_runs_on_branch(
+        &self,
+        repo: &RepoFullName,
+        branch: &str,
+        count: u8,
+    ) -> Result<Vec<WorkflowRun>, GitHubApiError> {
+        self.replay(
+            "fetch_workflow_runs_on_branch",
+            &json!({ "repo": repo, "branch": branch, "count": count }),
+        )
+    }
+
+    async fn fetch_workflow_run(
+        &self,
+        repo: &RepoFullName,
+        run_id: RunId,
+    ) -> Result<WorkflowRun, GitHubApiError> {
+        self.replay(
+            "fetch_workflow_run",
+            &json!({ "repo": repo, "runId": run_id }),
+        )
+    }
+
+    async fn fetch_workflow_jobs(
+        &self,
+        repo: &RepoFullName,
+        run_id: RunId,
+    ) -> Result<Vec<Job>, GitHubApiError> {
+        self.replay(
+            "fetch_workflow_jobs",
+            &json!({ "repo": repo, "runId": run_id }),
+        )
+    }
+
+    async fn fetch_workflow_job(
+        &self,
+        repo: &RepoFullName,
+        job_id: JobId,
+    ) -> Result<Job, GitHubApiError> {
+        self.replay(
+            "fetch_workflow_job",
+            &json!({ "repo": repo, "jobId": job_id }),
+        )
+    }
+
+    async fn fetch_job_logs(
+        &self,
+        repo: &RepoFullName,
+        job_id: JobId,
+    ) -> Result<String, GitHubApiError> {
+        self.replay("fetch_job_logs", &json!({ "repo": repo, "jobId": job_id }))
+    }
+
+    async fn fetch_workflows(&self, repo: &RepoFullName) -> Result<Vec<Workflow>, GitHubApiError> {
+        self.replay("fetch_workflows", &json!({ "repo": repo }))
+    }
+
+    async fn fetch_run_timing(
+        &self,
+        repo: &RepoFullName,
+        run_id: RunId,
+    ) -> Result<RunTiming, GitHubApiError> {
+        self.replay(
+            "fetch_run_timing",
+            &json!({ "repo": repo, "runId": run_id }),
+        )
+    }
+
+    async fn fetch_runners(
+        &self,
+        repo: &RepoFullName,
+    ) -> Result<Vec<SelfHostedRunner>, GitHubApiError> {
+        self.replay("fetch_runners", &json!({ "repo": repo }))
+    }
+
+    async fn fetch_deployments(
+        &self,
+        repo: &RepoFullName,
+        environment: Option<&str>,
+    ) -> Result<Vec<Deployment>, GitHubApiError> {
+        self.replay(
+            "fetch_deployments",
+            &json!({ "repo": repo, "environment": environment }),
+        )
+    }
+
+    async fn fetch_deployment_statuses(
+        &self,
+        repo: &RepoFullName,
+        deployment_id: DeploymentId,
+    ) -> Result<Vec<DeploymentStatus>, GitHubApiError> {
+        self.replay(
+            "fetch_deployment_statuses",
+            &json!({ "repo": repo, "deploymentId": deployment_id }),
+        )
+    }
+
+    async fn fetch_combined_status(
+        &self,
+        repo: &RepoFullName,
+        sha: &str,
+    ) -> Result<Vec<CommitStatus>, GitHubApiError> {
+        self.replay(
+            "fetch_combined_status",
+            &json!({ "repo": repo, "sha": sha }),
+        )
+    }
+
+    async fn fetch_branch_protection_required_checks(
+        &self,
+        repo: &RepoFullName,
+        branch: &str,
+    ) -> Result<Vec<String>, GitHubApiError> {
+        self.replay(
+            "fetch_branch_protection_required_checks",
+            &json!({ "repo": repo, "branch": branch }),
+        )
+    }
+
+    async fn fetch_workflow_definition(
+        &self,
+        repo: &RepoFullName,
+        path: &str,
+        git_ref: Option<&str>,
+    ) -> Result<WorkflowDefinition, GitHubApiError> {
+        self.replay(
+            "fetch_workflow_definition",
+            &json!({ "repo": repo, "path": path, "ref": git_ref }),
+        )
+    }
+
+    async fn fetch_rate_limit(&self) -> Result<RateLimit, GitHubApiError> {
+        self.replay("fetch_rate_limit", &json!({}))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::use_cases::stream_github_actions_runs::{
+        StreamGitHubActionsRunsInteractor, StreamGitHubActionsRunsUseCase,
+        StreamGitHubActionsRunsUseCaseInput, StreamGitHubActionsRunsUseCaseOutput,
+    };
+    use crate::infrastructures::adapters::secondary::external_apis::GitHubApiAdapter;
+    use futures_util::StreamExt;
+    use std::sync::Arc;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn first_snapshot<G: GitHubApi + Send + Sync + 'static>(
+        github_api: G,
+    ) -> anyhow::Result<StreamGitHubActionsRunsUseCaseOutput> {
+        let interactor = StreamGitHubActionsRunsInteractor::new(Arc::new(github_api));
+        let stream = interactor.execute(StreamGitHubActionsRunsUseCaseInput::default());
+        tokio::pin!(stream);
+        Ok(stream
+            .next()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("stream ended"))??)
+    }
+
+    async fn github_server() -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/user/repos"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([{
+                "name": "repo",
+                "owner": {"login": "owner", "avatar_url": null},
+                "html_url": "https://github.com/owner/repo"
+            }])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/actions/workflows"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/actions/runs"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "total_count": 1,
+                "workflow_runs": [{
+                    "id": 42,
+                    "run_number": 7,
+                    "workflow_id": 1,
+                    "name": "CI",
+                    "display_title": "Fix bug",
+                    "event": "push",
+                    "head_branch": "main",
+                    "head_sha": "0123456789abcdef0123456789abcdef01234567",
+                    "status": "in_progress",
+                    "conclusion": null,
+                    "created_at": "2024-08-01T10:00:00Z",
+                    "updated_at": "2024-08-01T10:05:00Z",
+                    "html_url": "https://github.com/owner/repo/actions/runs/42",
+                    "repository": {"full_name": "owner/repo"},
+                    "actor": {
+                        "login": "octocat",
+                        "avatar_url": "https://avatars.githubusercontent.com/octocat",
+                        "html_url": "https://github.com/octocat"
+                    }
+                }]
+            })))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[tokio::test]
+    async fn test_replay_reproduces_the_recorded_snapshot() -> anyhow::Result<()> {
+        let server = github_server().await;
+        let dir = tempfile::tempdir()?;
+        let adapter = GitHubApiAdapter::new(server.uri(), "token".to_string());
+
+        let recorded = first_snapshot(RecordingGitHubApi::new(adapter, dir.path())).await?;
+        drop(server);
+        let replayed = first_snapshot(ReplayGitHubApi::open(dir.path())?).await?;
+
+        assert_eq!(recorded.runs.len(), 1);
+        assert_eq!(replayed.runs, recorded.runs);
+        assert_eq!(replayed.repositories, recorded.repositories);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_replay_fails_on_unrecorded_calls() -> anyhow::Result<()> {
+        let server = github_server().await;
+        let dir = tempfile::tempdir()?;
+        let repo: RepoFullName = "owner/repo".parse()?;
+        let recorder = RecordingGitHubApi::new(
+            GitHubApiAdapter::new(server.uri(), "token".to_string()),
+            dir.path(),
+        );
+        recorder.fetch_workflow_runs(&repo, 2).await?;
+        let workflows = recorder.fetch_workflows(&repo).await;
+
+        let replay = ReplayGitHubApi::open(dir.path())?;
+
+        // Recorded errors are replayed as errors
+        assert_eq!(replay.fetch_workflows(&repo).await, workflows);
+        assert_eq!(replay.fetch_workflow_runs(&repo, 2).await?.len(), 1);
+        // Each recorded response is served once, and other arguments match nothing
+        assert!(matches!(
+            replay.fetch_workflow_runs(&repo, 2).await,
+            Err(GitHubApiError::Other { .. })
+        ));
+        assert!(matches!(
+            replay.fetch_workflow_runs(&repo, 5).await,
+            Err(GitHubApiError::Other { .. })
+        ));
+        Ok(())
+    }
+}
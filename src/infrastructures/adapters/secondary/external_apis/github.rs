@@ -1,38 +1,110 @@
-use crate::domain::external_apis::github::{GitHubApi, Repository};
+pub mod request_log;
+pub mod retry_events;
+pub mod token;
+
+use crate::domain::external_apis::github::calls::{ApiCallRecorder, GitHubOperation};
+use crate::domain::external_apis::github::{
+    GitHubApi, GitHubApiError, GitHubApiStatus, RateLimit, Repository,
+};
+use crate::domain::models::actor::RunActor;
+use crate::domain::models::commit_status::{CommitStatus, CommitStatusState};
+use crate::domain::models::deployment::{Deployment, DeploymentStatus, DeploymentStatusState};
+use crate::domain::models::ids::{
+    DeploymentId, InvalidRepoFullName, JobId, RepoFullName, RunId, RunnerId, WorkflowId,
+};
+use crate::domain::models::job::{Job, Step};
 use crate::domain::models::run::WorkflowRun;
-use anyhow::{Context, Error};
+use crate::domain::models::runner::{RunnerStatus, SelfHostedRunner};
+use crate::domain::models::status::{RunConclusion, RunStatus};
+use crate::domain::models::timing::{BillableTime, JobTiming, RunTiming, RunnerOs};
+use crate::domain::models::workflow::{Workflow, WorkflowState};
+use crate::domain::models::workflow_definition::WorkflowDefinition;
+use crate::infrastructures::metrics::Metrics;
+use crate::infrastructures::telemetry::Redactor;
+use crate::infrastructures::telemetry::propagation::trace_context_headers;
 use async_trait::async_trait;
-use reqwest::{Client, Response};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chrono::{DateTime, NaiveDateTime, TimeDelta, Utc};
+use request_log::{GitHubRequest, GitHubRequestLog};
+use reqwest::header::HeaderMap;
+use reqwest::{Client, RequestBuilder, Response, StatusCode, Url};
+use retry_events::{GitHubRetryEvents, RetryEvent};
 use serde::Deserialize;
-use std::future::Future;
-use tokio::time::{Duration, sleep};
+use std::collections::{BTreeMap, HashMap};
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use token::TokenProvider;
+use tokio::sync::{Semaphore, watch};
+use tokio::time::{Duration, Instant, sleep, timeout};
 
 #[derive(Deserialize, Debug, Clone)]
 struct GitHubRepositoryResponse {
     name: String,
     owner: GitHubOwnerResponse,
+    html_url: String,
+    #[serde(default)]
+    default_branch: Option<String>,
+    #[serde(default)]
+    pushed_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
 struct GitHubOwnerResponse {
     login: String,
+    avatar_url: Option<String>,
+}
+
+impl TryFrom<GitHubRepositoryResponse> for Repository {
+    type Error = InvalidRepoFullName;
+
+    fn try_from(repo: GitHubRepositoryResponse) -> Result<Self, Self::Error> {
+        Ok(Self::new(repo.owner.login, repo.name)?
+            .with_owner_avatar_url(repo.owner.avatar_url)
+            .with_html_url(repo.html_url)
+            .with_default_branch(repo.default_branch)
+            .with_pushed_at(repo.pushed_at))
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct GitHubActorResponse {
+    login: String,
+    avatar_url: String,
+    html_url: String,
+}
+
+impl From<GitHubActorResponse> for RunActor {
+    fn from(actor: GitHubActorResponse) -> Self {
+        Self {
+            login: actor.login,
+            avatar_url: actor.avatar_url,
+            html_url: actor.html_url,
+        }
+    }
 }
 
 #[derive(Deserialize, Debug, Clone)]
 struct GitHubRepositoryMinimalResponse {
-    full_name: String, // e.g., "owner/repo"
+    full_name: RepoFullName, // e.g., "owner/repo"
 }
 
 #[derive(Deserialize, Debug, Clone)]
 struct GitHubWorkflowRunResponse {
-    id: u64,
+    id: RunId,
+    run_number: u64,
+    workflow_id: WorkflowId,
     name: String, // workflow name
     display_title: String,
     event: String,
-    status: String,
-    conclusion: Option<String>, // Refer to this when status is "completed"
-    created_at: String,         // ISO 8601 format, parse during domain model conversion
-    updated_at: String,         // ISO 8601 format, parse during domain model conversion
+    head_branch: Option<String>, // null for some events
+    head_sha: String,
+    actor: GitHubActorResponse,
+    triggering_actor: Option<GitHubActorResponse>, // differs from actor for re-runs
+    status: RunStatus,
+    conclusion: Option<RunConclusion>, // Set when status is "completed"
+    created_at: String,                // ISO 8601 format, parse during domain model conversion
+    updated_at: String,                // ISO 8601 format, parse during domain model conversion
     html_url: String,
     repository: GitHubRepositoryMinimalResponse, // Type changed as instructed
 }
@@ -45,183 +117,3187 @@ struct GitHubWorkflowRunsApiResponse {
     workflow_runs: Vec<GitHubWorkflowRunResponse>,
 }
 
+/// Payload of a `workflow_run` webhook event; other fields such as `action` are not needed.
+#[derive(Deserialize, Debug)]
+struct GitHubWorkflowRunEvent {
+    workflow_run: GitHubWorkflowRunResponse,
+}
+
+/// Payload of a `workflow_job` webhook event; the job is shaped as in the jobs API.
+#[derive(Deserialize, Debug)]
+struct GitHubWorkflowJobEvent {
+    workflow_job: GitHubJobResponse,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct GitHubJobResponse {
+    id: JobId,
+    run_id: RunId,
+    name: String,
+    status: RunStatus,
+    conclusion: Option<RunConclusion>,
+    started_at: Option<String>,   // ISO 8601 format
+    completed_at: Option<String>, // ISO 8601 format, null while the job is running
+    runner_name: Option<String>,  // null until a runner picks the job up
+    #[serde(default)]
+    labels: Vec<String>,
+    html_url: String,
+    #[serde(default)]
+    steps: Vec<GitHubJobStepResponse>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct GitHubJobStepResponse {
+    number: u32,
+    name: String,
+    status: RunStatus,
+    conclusion: Option<RunConclusion>,
+    started_at: Option<String>,   // ISO 8601 format
+    completed_at: Option<String>, // ISO 8601 format
+}
+
+#[derive(Deserialize, Debug)]
+struct GitHubWorkflowJobsApiResponse {
+    jobs: Vec<GitHubJobResponse>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct GitHubWorkflowResponse {
+    id: WorkflowId,
+    name: String,
+    path: String,
+    state: WorkflowState,
+    html_url: String,
+    badge_url: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitHubRateLimitApiResponse {
+    resources: GitHubRateLimitResources,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitHubRateLimitResources {
+    core: GitHubRateLimitResponse,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitHubRateLimitResponse {
+    limit: u64,
+    remaining: u64,
+    used: u64,
+    reset: i64, // Unix epoch seconds
+}
+
+#[derive(Deserialize, Debug)]
+struct GitHubWorkflowsApiResponse {
+    workflows: Vec<GitHubWorkflowResponse>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitHubRunTimingResponse {
+    /// Keyed by `UBUNTU`, `WINDOWS` or `MACOS`
+    #[serde(default)]
+    billable: HashMap<String, GitHubBillableResponse>,
+    run_duration_ms: Option<u64>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitHubBillableResponse {
+    total_ms: u64,
+    #[serde(default)]
+    job_runs: Vec<GitHubJobRunResponse>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitHubJobRunResponse {
+    job_id: JobId,
+    duration_ms: u64,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitHubRunnersApiResponse {
+    runners: Vec<GitHubRunnerResponse>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitHubRunnerResponse {
+    id: RunnerId,
+    name: String,
+    os: String,
+    status: RunnerStatus,
+    busy: bool,
+    #[serde(default)]
+    labels: Vec<GitHubRunnerLabelResponse>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitHubRunnerLabelResponse {
+    name: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitHubDeploymentResponse {
+    id: DeploymentId,
+    environment: String,
+    #[serde(rename = "ref")]
+    git_ref: String,
+    sha: String,
+    creator: Option<GitHubActorResponse>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitHubDeploymentStatusResponse {
+    state: DeploymentStatusState,
+    #[serde(default)]
+    environment_url: Option<String>,
+    #[serde(default)]
+    target_url: Option<String>,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitHubCombinedStatusResponse {
+    statuses: Vec<GitHubCommitStatusResponse>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitHubCommitStatusResponse {
+    context: String,
+    state: CommitStatusState,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    target_url: Option<String>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitHubRequiredStatusChecksResponse {
+    /// Legacy status contexts, which still list the checks set up before `checks` existed
+    #[serde(default)]
+    contexts: Vec<String>,
+    #[serde(default)]
+    checks: Vec<GitHubRequiredCheckResponse>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitHubRequiredCheckResponse {
+    context: String,
+}
+
+impl GitHubRequiredStatusChecksResponse {
+    /// Every required check once, sorted.
+    fn names(self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .contexts
+            .into_iter()
+            .chain(self.checks.into_iter().map(|check| check.context))
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct GitHubContentResponse {
+    /// The file, in `encoding`; files over 1 MB come without content
+    #[serde(default)]
+    content: String,
+    encoding: String,
+}
+
+impl GitHubContentResponse {
+    /// The file as text.
+    fn text(&self) -> Result<String, String> {
+        if self.encoding != "base64" {
+            return Err(format!("unexpected encoding {}", self.encoding));
+        }
+        let content: String = self
+            .content
+            .chars()
+            .filter(|c| !c.is_ascii_whitespace())
+            .collect();
+        let bytes = BASE64.decode(content).map_err(|e| e.to_string())?;
+        String::from_utf8(bytes).map_err(|e| e.to_string())
+    }
+}
+
+impl From<GitHubRunnerResponse> for SelfHostedRunner {
+    fn from(runner: GitHubRunnerResponse) -> Self {
+        Self {
+            id: runner.id,
+            name: runner.name,
+            os: runner.os,
+            status: runner.status,
+            busy: runner.busy,
+            labels: runner.labels.into_iter().map(|label| label.name).collect(),
+        }
+    }
+}
+
+impl From<GitHubDeploymentResponse> for Deployment {
+    fn from(deployment: GitHubDeploymentResponse) -> Self {
+        Self {
+            id: deployment.id,
+            environment: deployment.environment,
+            git_ref: deployment.git_ref,
+            sha: deployment.sha,
+            creator: deployment.creator.map(RunActor::from),
+            created_at: deployment.created_at,
+            updated_at: deployment.updated_at,
+        }
+    }
+}
+
+impl From<GitHubDeploymentStatusResponse> for DeploymentStatus {
+    fn from(status: GitHubDeploymentStatusResponse) -> Self {
+        Self {
+            state: status.state,
+            // GitHub sends empty strings for URLs that were never set
+            target_url: [status.environment_url, status.target_url]
+                .into_iter()
+                .flatten()
+                .find(|url| !url.is_empty()),
+            created_at: status.created_at,
+        }
+    }
+}
+
+impl From<GitHubCommitStatusResponse> for CommitStatus {
+    fn from(status: GitHubCommitStatusResponse) -> Self {
+        Self {
+            context: status.context,
+            state: status.state,
+            description: status
+                .description
+                .filter(|description| !description.is_empty()),
+            target_url: status.target_url.filter(|url| !url.is_empty()),
+            created_at: status.created_at,
+            updated_at: status.updated_at,
+        }
+    }
+}
+
+impl From<GitHubRunTimingResponse> for RunTiming {
+    fn from(timing: GitHubRunTimingResponse) -> Self {
+        let billable: BTreeMap<_, _> = timing
+            .billable
+            .into_iter()
+            .filter_map(|(key, time)| {
+                let Some(os) = RunnerOs::from_billable_key(&key) else {
+                    tracing::warn!("Ignoring billable time on unknown runner OS {}", key);
+                    return None;
+                };
+                let job_runs = time
+                    .job_runs
+                    .into_iter()
+                    .map(|job_run| JobTiming {
+                        job_id: job_run.job_id,
+                        duration_ms: job_run.duration_ms,
+                    })
+                    .collect();
+                Some((
+                    os,
+                    BillableTime {
+                        total_ms: time.total_ms,
+                        job_runs,
+                    },
+                ))
+            })
+            .collect();
+        Self {
+            billable,
+            run_duration_ms: timing.run_duration_ms,
+        }
+    }
+}
+
+impl From<GitHubWorkflowResponse> for Workflow {
+    fn from(workflow: GitHubWorkflowResponse) -> Self {
+        Self {
+            id: workflow.id,
+            name: workflow.name,
+            path: workflow.path,
+            state: workflow.state,
+            html_url: workflow.html_url,
+            badge_url: workflow.badge_url,
+        }
+    }
+}
+
+/// Timestamps without an offset, as some GitHub Enterprise Server builds send them, in UTC
+const OFFSETLESS_TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.f";
+
+/// Parses an ISO 8601 timestamp of `resource` into UTC, taking one without an offset as UTC.
+fn parse_timestamp(
+    value: &str,
+    field: &str,
+    resource: &str,
+) -> Result<DateTime<Utc>, GitHubApiError> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+        .or_else(|e| {
+            NaiveDateTime::parse_from_str(value, OFFSETLESS_TIMESTAMP_FORMAT)
+                .map(|timestamp| timestamp.and_utc())
+                .map_err(|_| e)
+        })
+        .map_err(|e| GitHubApiError::Decode {
+            resource: resource.to_string(),
+            message: format!("Failed to parse {field}: {e}"),
+        })
+}
+
+/// Parses an optional ISO 8601 timestamp into UTC.
+fn parse_optional_timestamp(
+    value: Option<&str>,
+    field: &str,
+    id: JobId,
+) -> Result<Option<DateTime<Utc>>, GitHubApiError> {
+    value
+        .map(|value| parse_timestamp(value, field, &format!("job {id}")))
+        .transpose()
+}
+
+/// `html_url` of a run if it is an absolute https URL, else the run's page on github.com.
+fn run_html_url(html_url: String, repo: &RepoFullName, id: RunId) -> String {
+    match Url::parse(&html_url) {
+        Ok(url) if url.scheme() == "https" && url.host().is_some() => html_url,
+        _ => {
+            tracing::warn!(
+                "Run {} has an invalid html_url {:?}, linking to github.com instead",
+                id,
+                html_url
+            );
+            format!("https://github.com/{repo}/actions/runs/{id}")
+        }
+    }
+}
+
+/// Maps a run as returned by the REST API or a webhook to the domain model.
+fn workflow_run(
+    run_res: GitHubWorkflowRunResponse,
+    max_title_length: Option<NonZeroUsize>,
+) -> Result<WorkflowRun, GitHubApiError> {
+    // Parse ISO 8601 string to DateTime<Utc>
+    let resource = format!("run {}", run_res.id);
+    let created_at = parse_timestamp(&run_res.created_at, "created_at", &resource)?;
+    let updated_at = parse_timestamp(&run_res.updated_at, "updated_at", &resource)?;
+
+    let html_url = run_html_url(run_res.html_url, &run_res.repository.full_name, run_res.id);
+    let mut run = WorkflowRun {
+        repository_name: run_res.repository.full_name,
+        id: run_res.id,
+        run_number: run_res.run_number,
+        workflow_id: run_res.workflow_id,
+        workflow_name: run_res.name,
+        display_title: run_res.display_title,
+        full_display_title: None,
+        event: run_res.event,
+        head_branch: run_res.head_branch,
+        head_sha: run_res.head_sha,
+        actor: run_res.actor.into(),
+        triggering_actor: run_res.triggering_actor.map(RunActor::from),
+        status: run_res.status,
+        conclusion: run_res.conclusion,
+        created_at,
+        updated_at,
+        html_url,
+        labels: Vec::new(),
+        repository_active: true,
+        is_required_check: None,
+        failed_jobs: Vec::new(),
+        failed_job_url: None,
+    };
+    if let Some(max_title_length) = max_title_length {
+        run.truncate_display_title(max_title_length);
+    }
+    Ok(run)
+}
+
+/// Parses the payload of a `workflow_run` webhook event into the run it reports.
+///
+/// Titles longer than `max_title_length` are truncated as for polled runs.
+///
+/// # Errors
+///
+/// Returns [`GitHubApiError::Decode`] if the payload is not a `workflow_run` event.
+pub fn workflow_run_from_event(
+    payload: &[u8],
+    max_title_length: Option<NonZeroUsize>,
+) -> Result<WorkflowRun, GitHubApiError> {
+    let event: GitHubWorkflowRunEvent =
+        serde_json::from_slice(payload).map_err(|e| GitHubApiError::Decode {
+            resource: "workflow_run event".to_string(),
+            message: e.to_string(),
+        })?;
+    workflow_run(event.workflow_run, max_title_length)
+}
+
+/// Parses the payload of a `workflow_job` webhook event into the job it reports.
+///
+/// # Errors
+///
+/// Returns [`GitHubApiError::Decode`] if the payload is not a `workflow_job` event.
+pub fn workflow_job_from_event(payload: &[u8]) -> Result<Job, GitHubApiError> {
+    let event: GitHubWorkflowJobEvent =
+        serde_json::from_slice(payload).map_err(|e| GitHubApiError::Decode {
+            resource: "workflow_job event".to_string(),
+            message: e.to_string(),
+        })?;
+    job_from_response(event.workflow_job)
+}
+
+/// Returns true when an error response body says GitHub Actions is disabled.
+fn is_actions_disabled_message(body: &str) -> bool {
+    let body = body.to_ascii_lowercase();
+    body.contains("actions") && body.contains("disabled")
+}
+
+/// Parses `github-authentication-token-expiration`, e.g. `2024-09-01 00:00:00 UTC`.
+fn parse_token_expiration(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(timestamp) = DateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S %z") {
+        return Some(timestamp.with_timezone(&Utc));
+    }
+    chrono::NaiveDateTime::parse_from_str(value.strip_suffix(" UTC")?, "%Y-%m-%d %H:%M:%S")
+        .ok()
+        .map(|timestamp| timestamp.and_utc())
+}
+
+fn header_u64(headers: &HeaderMap, name: &str) -> Option<u64> {
+    headers.get(name)?.to_str().ok()?.parse().ok()
+}
+
+/// Requests sent to GitHub at a time unless configured otherwise
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 10;
+
+/// Time all the attempts of one operation get unless configured otherwise
+pub const DEFAULT_RETRY_DEADLINE: Duration = Duration::from_secs(20);
+
 pub struct GitHubApiAdapter {
     client: Client,
     base_url: String,
-    github_token: String,
+    github_token: Arc<dyn TokenProvider>,
+    api_status: watch::Sender<GitHubApiStatus>,
+    /// Display titles longer than this many grapheme clusters are truncated
+    max_title_length: Option<NonZeroUsize>,
+    metrics: Option<Arc<Metrics>>,
+    /// Whether requests carry the `traceparent` of the current span
+    trace_propagation: bool,
+    /// Permits to send a request, shared by everything using the adapter
+    requests: Arc<Semaphore>,
+    /// Masks the repositories recorded on spans in strict mode
+    redactor: Redactor,
+    /// Remembers every attempt when set
+    request_log: Option<Arc<GitHubRequestLog>>,
+    /// Told about every retry when set
+    retry_events: Option<Arc<GitHubRetryEvents>>,
+    /// Time all the attempts of one operation get, backoff included
+    retry_deadline: Duration,
 }
 
 impl GitHubApiAdapter {
     #[must_use]
     pub fn new(base_url: String, github_token: String) -> Self {
+        Self::with_token_provider(base_url, Arc::new(github_token))
+    }
+
+    /// Sends the token `github_token` provides at the time of each request, reloading it
+    /// once when GitHub answers 401.
+    #[must_use]
+    pub fn with_token_provider(base_url: String, github_token: Arc<dyn TokenProvider>) -> Self {
         Self {
             client: Client::new(),
             base_url,
             github_token,
+            api_status: watch::Sender::new(GitHubApiStatus::default()),
+            max_title_length: None,
+            metrics: None,
+            trace_propagation: true,
+            requests: Arc::new(Semaphore::new(DEFAULT_MAX_CONCURRENT_REQUESTS)),
+            redactor: Redactor::default(),
+            request_log: None,
+            retry_events: None,
+            retry_deadline: DEFAULT_RETRY_DEADLINE,
         }
     }
 
-    async fn execute_with_retry<T, F, Fut>(
-        &self,
-        operation_name: &str,
-        request_fn: F,
-    ) -> Result<T, Error>
-    where
-        F: Fn() -> Fut,
-        Fut: Future<Output = Result<Response, reqwest::Error>>,
-        T: serde::de::DeserializeOwned,
-    {
-        const MAX_RETRIES: u32 = 10;
-        const INITIAL_WAIT_SECS: f64 = 1.0;
-        const BACKOFF_MULTIPLIER: f64 = 1.5;
+    /// Records every request attempt and the reported rate limit in `metrics`.
+    #[must_use]
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
 
-        let mut retries = 0;
-        let mut wait_time = INITIAL_WAIT_SECS;
+    /// Truncates run display titles to `max_title_length` grapheme clusters; `None` keeps them whole.
+    #[must_use]
+    pub fn with_max_title_length(mut self, max_title_length: Option<NonZeroUsize>) -> Self {
+        self.max_title_length = max_title_length;
+        self
+    }
 
-        loop {
-            match request_fn().await {
-                Ok(response) => match response.error_for_status() {
-                    Ok(response) => match response.json::<T>().await {
-                        Ok(result) => return Ok(result),
-                        Err(e) => {
-                            if retries >= MAX_RETRIES {
-                                return Err(e).context(format!(
-                                    "Failed to deserialize response for {operation_name} after {MAX_RETRIES} retries"
-                                ));
-                            }
-                            tracing::warn!(
-                                "Failed to deserialize response for {}, retry {} of {}: {}",
-                                operation_name,
-                                retries + 1,
-                                MAX_RETRIES,
-                                e
-                            );
-                        }
-                    },
-                    Err(e) => {
-                        if retries >= MAX_RETRIES {
-                            return Err(e).context(format!(
-                                "API returned an error for {operation_name} after {MAX_RETRIES} retries"
-                            ));
-                        }
-                        tracing::warn!(
-                            "API error for {}, retry {} of {}: {}",
-                            operation_name,
-                            retries + 1,
-                            MAX_RETRIES,
-                            e
-                        );
-                    }
-                },
-                Err(e) => {
-                    if retries >= MAX_RETRIES {
-                        return Err(e).context(format!(
-                            "Failed to send request for {operation_name} after {MAX_RETRIES} retries"
-                        ));
-                    }
-                    tracing::warn!(
-                        "Request failed for {}, retry {} of {}: {}",
-                        operation_name,
-                        retries + 1,
-                        MAX_RETRIES,
-                        e
-                    );
-                }
-            }
+    /// Sends the trace context of the current span with every request, the default; `false`
+    /// keeps it from GitHub and any proxy in between.
+    #[must_use]
+    pub fn with_trace_propagation(mut self, trace_propagation: bool) -> Self {
+        self.trace_propagation = trace_propagation;
+        self
+    }
 
-            retries += 1;
-            sleep(Duration::from_secs_f64(wait_time)).await;
-            wait_time *= BACKOFF_MULTIPLIER;
-        }
+    /// Records the repository of each call on its span as `redactor` names it, so strict
+    /// redaction keeps private names out of exported traces.
+    #[must_use]
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = redactor;
+        self
     }
-}
 
-#[async_trait]
-impl GitHubApi for GitHubApiAdapter {
-    #[tracing::instrument(name = "GitHubApiAdapter::fetch_repositories", skip(self))]
-    async fn fetch_repositories(&self, count: u8) -> Result<Vec<Repository>, Error> {
-        let url = format!(
-            "{}/user/repos?type=owner&sort=pushed&direction=desc&per_page={}",
-            self.base_url, count
-        );
+    /// Records every request attempt in `request_log`, its errors redacted by the redactor.
+    #[must_use]
+    pub fn with_request_log(mut self, request_log: Arc<GitHubRequestLog>) -> Self {
+        self.request_log = Some(request_log);
+        self
+    }
 
-        let response_items: Vec<GitHubRepositoryResponse> = self
-            .execute_with_retry("fetch_repositories", || {
-                self.client
-                    .get(&url)
-                    .header("Authorization", format!("Bearer {}", self.github_token))
-                    .header("Accept", "application/vnd.github.v3+json")
-                    .header("User-Agent", "gha-dashboard-rust-app")
-                    .send()
-            })
-            .await?;
+    /// Publishes every retried attempt, and every request given up on, to `retry_events`.
+    #[must_use]
+    pub fn with_retry_events(mut self, retry_events: Arc<GitHubRetryEvents>) -> Self {
+        self.retry_events = Some(retry_events);
+        self
+    }
+
+    /// Sends at most `max_concurrent_requests` requests to GitHub at a time, whatever they
+    /// are for; the others wait their turn.
+    #[must_use]
+    pub fn with_max_concurrent_requests(mut self, max_concurrent_requests: NonZeroUsize) -> Self {
+        self.requests = Arc::new(Semaphore::new(max_concurrent_requests.get()));
+        self
+    }
+
+    /// Gives up on an operation with [`GitHubApiError::Timeout`] once its attempts took
+    /// `retry_deadline`, whatever retries are left.
+    #[must_use]
+    pub fn with_retry_deadline(mut self, retry_deadline: Duration) -> Self {
+        self.retry_deadline = retry_deadline;
+        self
+    }
+
+    /// Builds `{base_url}/{segments}?{query}`, percent-encoding each segment on its own.
+    ///
+    /// A segment can never add a path level or a query, whatever it contains.
+    fn api_url(
+        &self,
+        resource: &str,
+        segments: &[&str],
+        query: &[(&str, &str)],
+    ) -> Result<Url, GitHubApiError> {
+        let invalid = |message: String| GitHubApiError::Other {
+            resource: resource.to_string(),
+            message,
+        };
+        let mut url = Url::parse(&self.base_url)
+            .map_err(|e| invalid(format!("Invalid base URL {:?}: {e}", self.base_url)))?;
+        url.path_segments_mut()
+            .map_err(|()| invalid(format!("Base URL {:?} cannot have a path", self.base_url)))?
+            .pop_if_empty()
+            .extend(segments);
+        if !query.is_empty() {
+            url.query_pairs_mut().extend_pairs(query);
+        }
+        Ok(url)
+    }
 
-        let repositories = response_items
+    /// Builds the URL of `segments` under `repos/{owner}/{name}`.
+    fn repo_url(
+        &self,
+        resource: &str,
+        repo: &RepoFullName,
+        segments: &[&str],
+        query: &[(&str, &str)],
+    ) -> Result<Url, GitHubApiError> {
+        let path: Vec<&str> = ["repos", repo.owner(), repo.name()]
             .into_iter()
-            .map(|repo_res| Repository {
-                name: repo_res.name,
-                owner: repo_res.owner.login,
-            })
+            .chain(segments.iter().copied())
             .collect();
-
-        Ok(repositories)
+        self.api_url(resource, &path, query)
     }
 
-    #[tracing::instrument(name = "GitHubApiAdapter::fetch_repository", skip(self))]
-    async fn fetch_workflow_runs(
+    /// Lists the runs of `repo`, or of one of its workflows, matching `query`, newest first.
+    async fn list_workflow_runs(
         &self,
-        owner: &str,
-        repo: &str,
-        count: u8,
-    ) -> Result<Vec<WorkflowRun>, Error> {
-        let url = format!(
-            "{}/repos/{}/{}/actions/runs?per_page={}",
-            self.base_url, owner, repo, count
-        );
+        resource: &str,
+        repo: &RepoFullName,
+        workflow_id: Option<WorkflowId>,
+        query: &[(&str, &str)],
+    ) -> Result<Vec<WorkflowRun>, GitHubApiError> {
+        let (url, route) = match workflow_id {
+            Some(workflow_id) => (
+                self.repo_url(
+                    resource,
+                    repo,
+                    &["actions", "workflows", &workflow_id.to_string(), "runs"],
+                    query,
+                )?,
+                "/repos/{owner}/{repo}/actions/workflows/{workflow_id}/runs",
+            ),
+            None => (
+                self.repo_url(resource, repo, &["actions", "runs"], query)?,
+                "/repos/{owner}/{repo}/actions/runs",
+            ),
+        };
 
         let api_response: GitHubWorkflowRunsApiResponse = self
-            .execute_with_retry(&format!("workflow runs for {owner}/{repo}"), || {
+            .execute_with_retry(GitHubOperation::WorkflowRuns, route, resource, || {
                 self.client
-                    .get(&url)
-                    .header("Authorization", format!("Bearer {}", self.github_token))
+                    .get(url.clone())
+                    .header(
+                        "Authorization",
+                        format!("Bearer {}", self.github_token.token()),
+                    )
                     .header("Accept", "application/vnd.github.v3+json")
                     .header("User-Agent", "gha-dashboard-rust-app")
-                    .send()
             })
             .await?;
 
-        let workflow_runs = api_response
+        // One run GitHub sent garbled, e.g. with a malformed timestamp, must not hide the others
+        Ok(api_response
             .workflow_runs
             .into_iter()
-            .map(|run_res| {
-                let status = if run_res.status == "completed" {
-                    run_res.conclusion.unwrap_or_else(|| run_res.status.clone()) // Use status if conclusion is None
-                } else {
-                    run_res.status.clone()
-                };
+            .filter_map(
+                |run_res| match workflow_run(run_res, self.max_title_length) {
+                    Ok(run) => Some(run),
+                    Err(e) => {
+                        tracing::warn!("Skipping run of {}: {}", repo, e);
+                        None
+                    }
+                },
+            )
+            .collect())
+    }
 
-                // Parse ISO 8601 string to DateTime<Utc>
-                let created_at = chrono::DateTime::parse_from_rfc3339(&run_res.created_at)
-                    .context(format!("Failed to parse created_at for run {}", run_res.id))?
-                    .with_timezone(&chrono::Utc);
-                let updated_at = chrono::DateTime::parse_from_rfc3339(&run_res.updated_at)
-                    .context(format!("Failed to parse updated_at for run {}", run_res.id))?
-                    .with_timezone(&chrono::Utc);
-
-                Ok(WorkflowRun {
-                    repository_name: run_res.repository.full_name,
-                    id: run_res.id,
-                    workflow_name: run_res.name,
-                    display_title: run_res.display_title,
-                    event: run_res.event,
-                    status,
-                    created_at,
-                    updated_at,
-                    html_url: run_res.html_url,
-                })
-            })
-            .collect::<Result<Vec<WorkflowRun>, Error>>()?; // Early return if an error occurs
+    /// Records the token expiry and rate limit headers of a response.
+    fn record_api_status(&self, headers: &HeaderMap) {
+        let token_expires_at = headers
+            .get("github-authentication-token-expiration")
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_token_expiration);
+        let rate_limit_remaining = header_u64(headers, "x-ratelimit-remaining");
+        let rate_limit_limit = header_u64(headers, "x-ratelimit-limit");
+
+        self.api_status.send_if_modified(|status| {
+            let before = status.clone();
+            // Keep previous values when a response omits a header
+            status.token_expires_at = token_expires_at.or(status.token_expires_at);
+            status.rate_limit_remaining = rate_limit_remaining.or(status.rate_limit_remaining);
+            status.rate_limit_limit = rate_limit_limit.or(status.rate_limit_limit);
+            *status != before
+        });
+
+        if let Some(metrics) = &self.metrics
+            && let Some(remaining) =
+                rate_limit_remaining.and_then(|value| i64::try_from(value).ok())
+        {
+            metrics.github_rate_limit_remaining.set(remaining);
+        }
+    }
+
+    /// Tells the retry events, if any, that `attempt` failed with `error`; `wait` is `None`
+    /// when the request is given up on.
+    fn publish_retry(
+        &self,
+        operation: GitHubOperation,
+        attempt: u32,
+        error: &GitHubApiError,
+        wait: Option<Duration>,
+    ) {
+        if let Some(retry_events) = &self.retry_events {
+            retry_events.publish(RetryEvent {
+                at: Utc::now(),
+                operation,
+                attempt,
+                category: error.kind(),
+                next_wait_ms: wait.map(|wait| u64::try_from(wait.as_millis()).unwrap_or(u64::MAX)),
+            });
+        }
+    }
+
+    /// Sends the request until it succeeds or fails permanently, decoding the JSON body.
+    async fn execute_with_retry<T, F>(
+        &self,
+        operation: GitHubOperation,
+        route: &str,
+        operation_name: &str,
+        request_fn: F,
+    ) -> Result<T, GitHubApiError>
+    where
+        F: Fn() -> RequestBuilder,
+        T: serde::de::DeserializeOwned,
+    {
+        self.send_with_retry(operation, route, operation_name, request_fn, Response::json)
+            .await
+    }
 
-        Ok(workflow_runs)
+    /// Sends the request until it succeeds, fails permanently or runs out of time, reading the
+    /// body of a successful response with `read`.
+    ///
+    /// A 401 is retried once, right away, if reloading the token gives a new one.
+    ///
+    /// Each attempt waits for one of the permits limiting concurrent requests and holds it
+    /// until the body is read; none is held while backing off.
+    ///
+    /// Every attempt is counted under `operation` in metrics and in the current
+    /// [`ApiCallRecorder`]. `route` is the path template recorded on the span, e.g.
+    /// `/repos/{owner}/{repo}/actions/runs`.
+    ///
+    /// The attempts, waits included, end with [`GitHubApiError::Timeout`] after the retry
+    /// deadline, so one operation cannot outlast a poll.
+    #[tracing::instrument(
+        name = "GitHubApiAdapter::request",
+        skip(self, operation_name, request_fn, read),
+        fields(otel.kind = "client", http.route = route)
+    )]
+    async fn send_with_retry<T, F, R, Fut>(
+        &self,
+        operation: GitHubOperation,
+        route: &str,
+        operation_name: &str,
+        request_fn: F,
+        read: R,
+    ) -> Result<T, GitHubApiError>
+    where
+        F: Fn() -> RequestBuilder,
+        R: Fn(Response) -> Fut,
+        Fut: Future<Output = reqwest::Result<T>>,
+    {
+        let mut retries = 0;
+        let attempts = self.send_until_done(
+            operation,
+            route,
+            operation_name,
+            request_fn,
+            read,
+            &mut retries,
+        );
+        if let Ok(result) = timeout(self.retry_deadline, attempts).await {
+            return result;
+        }
+        let error = GitHubApiError::Timeout {
+            resource: operation_name.to_string(),
+            deadline_ms: u64::try_from(self.retry_deadline.as_millis()).unwrap_or(u64::MAX),
+        };
+        tracing::error!("Giving up: {}", error);
+        self.publish_retry(operation, retries + 1, &error, None);
+        Err(error)
+    }
+
+    /// The attempts of [`Self::send_with_retry`], with no deadline, counting the retries
+    /// made in `retries`.
+    async fn send_until_done<T, F, R, Fut>(
+        &self,
+        operation: GitHubOperation,
+        route: &str,
+        operation_name: &str,
+        request_fn: F,
+        read: R,
+        retries: &mut u32,
+    ) -> Result<T, GitHubApiError>
+    where
+        F: Fn() -> RequestBuilder,
+        R: Fn(Response) -> Fut,
+        Fut: Future<Output = reqwest::Result<T>>,
+    {
+        const MAX_RETRIES: u32 = 10;
+        const INITIAL_WAIT_SECS: f64 = 1.0;
+        const BACKOFF_MULTIPLIER: f64 = 1.5;
+
+        let mut wait_time = INITIAL_WAIT_SECS;
+        let trace_context = if self.trace_propagation {
+            trace_context_headers()
+        } else {
+            HeaderMap::new()
+        };
+        let mut reloaded_token = false;
+
+        loop {
+            let error = {
+                // The semaphore is never closed
+                let _permit = self.requests.acquire().await.ok();
+                let started = Instant::now();
+                ApiCallRecorder::record(operation);
+                let result = request_fn().headers(trace_context.clone()).send().await;
+                let latency = started.elapsed();
+                let at = Utc::now();
+                let status = result
+                    .as_ref()
+                    .ok()
+                    .map(|response| response.status().as_u16());
+                if let Some(metrics) = &self.metrics {
+                    metrics.observe_github_api_request(operation.as_str(), status, latency);
+                }
+                let rate_limit_remaining = result.as_ref().ok().and_then(|response| {
+                    self.record_api_status(response.headers());
+                    header_u64(response.headers(), "x-ratelimit-remaining")
+                });
+                let outcome = match result {
+                    Ok(response) if response.status().is_success() => read(response)
+                        .await
+                        .map_err(|e| request_error(operation_name, &e)),
+                    Ok(response) => Err(error_from_response(operation_name, response).await),
+                    Err(e) => Err(request_error(operation_name, &e)),
+                };
+                if let Some(request_log) = &self.request_log {
+                    request_log.record(GitHubRequest {
+                        at,
+                        operation,
+                        route: route.to_string(),
+                        status,
+                        latency_ms: u64::try_from(latency.as_millis()).unwrap_or(u64::MAX),
+                        rate_limit_remaining,
+                        error: outcome
+                            .as_ref()
+                            .err()
+                            .map(|e| self.redactor.redact(&e.to_string()).into_owned()),
+                    });
+                }
+                match outcome {
+                    Ok(result) => {
+                        if let Some(retry_events) = &self.retry_events {
+                            retry_events.succeeded();
+                        }
+                        return Ok(result);
+                    }
+                    Err(error) => error,
+                }
+            };
+            // A rotated token is picked up right away rather than at the next reload
+            if matches!(error, GitHubApiError::Unauthorized { .. })
+                && !reloaded_token
+                && self.github_token.reload()
+            {
+                reloaded_token = true;
+                tracing::info!("Retrying {} with the reloaded token", operation_name);
+                continue;
+            }
+            if !is_retryable(&error) {
+                return Err(error);
+            }
+            if *retries >= MAX_RETRIES {
+                tracing::error!("Giving up after {} retries: {}", MAX_RETRIES, error);
+                self.publish_retry(operation, *retries + 1, &error, None);
+                return Err(error);
+            }
+            tracing::warn!("{}, retry {} of {}", error, *retries + 1, MAX_RETRIES);
+            let wait = Duration::from_secs_f64(wait_time);
+            self.publish_retry(operation, *retries + 1, &error, Some(wait));
+
+            *retries += 1;
+            sleep(wait).await;
+            wait_time *= BACKOFF_MULTIPLIER;
+        }
+    }
+}
+
+/// Maps a job of the jobs API, failing on malformed timestamps.
+fn job_from_response(job_res: GitHubJobResponse) -> Result<Job, GitHubApiError> {
+    let steps = job_res
+        .steps
+        .into_iter()
+        .map(|step| {
+            Ok(Step {
+                started_at: parse_optional_timestamp(
+                    step.started_at.as_deref(),
+                    &format!("started_at of step {}", step.number),
+                    job_res.id,
+                )?,
+                completed_at: parse_optional_timestamp(
+                    step.completed_at.as_deref(),
+                    &format!("completed_at of step {}", step.number),
+                    job_res.id,
+                )?,
+                number: step.number,
+                name: step.name,
+                status: step.status,
+                conclusion: step.conclusion,
+            })
+        })
+        .collect::<Result<Vec<Step>, GitHubApiError>>()?;
+    Ok(Job {
+        started_at: parse_optional_timestamp(
+            job_res.started_at.as_deref(),
+            "started_at",
+            job_res.id,
+        )?,
+        completed_at: parse_optional_timestamp(
+            job_res.completed_at.as_deref(),
+            "completed_at",
+            job_res.id,
+        )?,
+        id: job_res.id,
+        run_id: job_res.run_id,
+        name: job_res.name,
+        status: job_res.status,
+        conclusion: job_res.conclusion,
+        runner_name: job_res.runner_name,
+        labels: job_res.labels,
+        html_url: job_res.html_url,
+        steps,
+    })
+}
+
+/// Classifies a failed request or an undecodable body.
+fn request_error(resource: &str, error: &reqwest::Error) -> GitHubApiError {
+    let resource = resource.to_string();
+    let message = error.to_string();
+    if error.is_decode() {
+        GitHubApiError::Decode { resource, message }
+    } else {
+        GitHubApiError::Network { resource, message }
+    }
+}
+
+/// Classifies a response with an error status.
+async fn error_from_response(resource: &str, response: Response) -> GitHubApiError {
+    let resource = resource.to_string();
+    let status = response.status();
+    let rate_limited = is_rate_limited(response.headers());
+    let reset = rate_limit_reset(response.headers(), Utc::now());
+    match status {
+        StatusCode::NOT_FOUND => return GitHubApiError::NotFound { resource },
+        StatusCode::UNAUTHORIZED => return GitHubApiError::Unauthorized { resource },
+        _ => {}
+    }
+
+    let body = response.text().await.unwrap_or_default();
+    match status {
+        StatusCode::FORBIDDEN | StatusCode::CONFLICT if is_actions_disabled_message(&body) => {
+            tracing::info!("GitHub Actions is disabled for {}", resource);
+            GitHubApiError::NotFound { resource }
+        }
+        StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS
+            if rate_limited
+                || status == StatusCode::TOO_MANY_REQUESTS
+                || body.to_ascii_lowercase().contains("rate limit") =>
+        {
+            GitHubApiError::RateLimited { resource, reset }
+        }
+        StatusCode::FORBIDDEN => GitHubApiError::Forbidden {
+            resource,
+            message: body,
+        },
+        _ => GitHubApiError::Other {
+            resource,
+            message: format!("{status}: {body}"),
+        },
+    }
+}
+
+/// Whether the headers show an exhausted primary or secondary rate limit.
+fn is_rate_limited(headers: &HeaderMap) -> bool {
+    headers.contains_key("retry-after") || header_u64(headers, "x-ratelimit-remaining") == Some(0)
+}
+
+/// When an exhausted rate limit resets, from `retry-after` or else `x-ratelimit-reset`.
+fn rate_limit_reset(headers: &HeaderMap, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    if let Some(seconds) = header_u64(headers, "retry-after") {
+        return now.checked_add_signed(TimeDelta::seconds(i64::try_from(seconds).ok()?));
+    }
+    header_u64(headers, "x-ratelimit-reset")
+        .and_then(|epoch| i64::try_from(epoch).ok())
+        .and_then(|epoch| DateTime::from_timestamp(epoch, 0))
+}
+
+/// Whether the same request may succeed later without anything else changing.
+fn is_retryable(error: &GitHubApiError) -> bool {
+    matches!(
+        error,
+        GitHubApiError::Network { .. }
+            | GitHubApiError::Decode { .. }
+            | GitHubApiError::Other { .. }
+    )
+}
+
+#[async_trait]
+impl GitHubApi for GitHubApiAdapter {
+    fn api_status(&self) -> GitHubApiStatus {
+        self.api_status.borrow().clone()
+    }
+
+    #[tracing::instrument(name = "GitHubApiAdapter::fetch_repositories", skip(self))]
+    async fn fetch_repositories(&self, count: u8) -> Result<Vec<Repository>, GitHubApiError> {
+        let resource = "repositories";
+        let url = self.api_url(
+            resource,
+            &["user", "repos"],
+            &[
+                ("type", "owner"),
+                ("sort", "pushed"),
+                ("direction", "desc"),
+                ("per_page", &count.to_string()),
+            ],
+        )?;
+
+        let response_items: Vec<GitHubRepositoryResponse> = self
+            .execute_with_retry(
+                GitHubOperation::Repositories,
+                "/user/repos",
+                resource,
+                || {
+                    self.client
+                        .get(url.clone())
+                        .header(
+                            "Authorization",
+                            format!("Bearer {}", self.github_token.token()),
+                        )
+                        .header("Accept", "application/vnd.github.v3+json")
+                        .header("User-Agent", "gha-dashboard-rust-app")
+                },
+            )
+            .await?;
+
+        Ok(response_items
+            .into_iter()
+            .filter_map(|repo| match Repository::try_from(repo) {
+                Ok(repository) => Some(repository),
+                Err(e) => {
+                    tracing::warn!("Skipping repository: {}", e);
+                    None
+                }
+            })
+            .collect())
+    }
+
+    #[tracing::instrument(
+        name = "GitHubApiAdapter::fetch_repository",
+        skip(self, repo),
+        fields(repo = %self.redactor.repository(repo))
+    )]
+    async fn fetch_repository(&self, repo: &RepoFullName) -> Result<Repository, GitHubApiError> {
+        let resource = format!("repository {repo}");
+        let url = self.repo_url(&resource, repo, &[], &[])?;
+
+        // GitHub redirects renamed and transferred repositories to their new name, which
+        // reqwest follows with the token as it stays on the same host
+        let response: GitHubRepositoryResponse = self
+            .execute_with_retry(
+                GitHubOperation::Repository,
+                "/repos/{owner}/{repo}",
+                &resource,
+                || {
+                    self.client
+                        .get(url.clone())
+                        .header(
+                            "Authorization",
+                            format!("Bearer {}", self.github_token.token()),
+                        )
+                        .header("Accept", "application/vnd.github.v3+json")
+                        .header("User-Agent", "gha-dashboard-rust-app")
+                },
+            )
+            .await?;
+
+        Repository::try_from(response).map_err(|e| GitHubApiError::Decode {
+            resource,
+            message: e.to_string(),
+        })
+    }
+
+    #[tracing::instrument(
+        name = "GitHubApiAdapter::fetch_workflow_runs",
+        skip(self, repo),
+        fields(repo = %self.redactor.repository(repo))
+    )]
+    async fn fetch_workflow_runs(
+        &self,
+        repo: &RepoFullName,
+        count: u8,
+    ) -> Result<Vec<WorkflowRun>, GitHubApiError> {
+        let resource = format!("workflow runs for {repo}");
+        self.list_workflow_runs(&resource, repo, None, &[("per_page", &count.to_string())])
+            .await
+    }
+
+    #[tracing::instrument(
+        name = "GitHubApiAdapter::fetch_workflow_runs_of_workflow",
+        skip(self, repo),
+        fields(repo = %self.redactor.repository(repo))
+    )]
+    async fn fetch_workflow_runs_of_workflow(
+        &self,
+        repo: &RepoFullName,
+        workflow_id: WorkflowId,
+        count: u8,
+    ) -> Result<Vec<WorkflowRun>, GitHubApiError> {
+        let resource = format!("workflow {workflow_id} runs for {repo}");
+        self.list_workflow_runs(
+            &resource,
+            repo,
+            Some(workflow_id),
+            &[("per_page", &count.to_string())],
+        )
+        .await
+    }
+
+    #[tracing::instrument(
+        name = "GitHubApiAdapter::fetch_workflow_runs_by_status",
+        skip(self, repo),
+        fields(repo = %self.redactor.repository(repo))
+    )]
+    async fn fetch_workflow_runs_by_status(
+        &self,
+        repo: &RepoFullName,
+        status: RunStatus,
+        count: u8,
+    ) -> Result<Vec<WorkflowRun>, GitHubApiError> {
+        let resource = format!("{} workflow runs for {repo}", status.as_str());
+        self.list_workflow_runs(
+            &resource,
+            repo,
+            None,
+            &[
+                ("status", status.as_str()),
+                ("per_page", &count.to_string()),
+            ],
+        )
+        .await
+    }
+
+    #[tracing::instrument(
+        name = "GitHubApiAdapter::fetch_workflow_runs_on_branch",
+        skip(self, repo),
+        fields(repo = %self.redactor.repository(repo))
+    )]
+    async fn fetch_workflow_runs_on_branch(
+        &self,
+        repo: &RepoFullName,
+        branch: &str,
+        count: u8,
+    ) -> Result<Vec<WorkflowRun>, GitHubApiError> {
+        let resource = format!("workflow runs on {branch} for {repo}");
+        self.list_workflow_runs(
+            &resource,
+            repo,
+            None,
+            &[("branch", branch), ("per_page", &count.to_string())],
+        )
+        .await
+    }
+
+    #[tracing::instrument(
+        name = "GitHubApiAdapter::fetch_workflow_run",
+        skip(self, repo),
+        fields(repo = %self.redactor.repository(repo))
+    )]
+    async fn fetch_workflow_run(
+        &self,
+        repo: &RepoFullName,
+        run_id: RunId,
+    ) -> Result<WorkflowRun, GitHubApiError> {
+        let resource = format!("run {run_id} of {repo}");
+        let url = self.repo_url(
+            &resource,
+            repo,
+            &["actions", "runs", &run_id.to_string()],
+            &[],
+        )?;
+
+        let run_res: GitHubWorkflowRunResponse = self
+            .execute_with_retry(
+                GitHubOperation::WorkflowRuns,
+                "/repos/{owner}/{repo}/actions/runs/{run_id}",
+                &resource,
+                || {
+                    self.client
+                        .get(url.clone())
+                        .header(
+                            "Authorization",
+                            format!("Bearer {}", self.github_token.token()),
+                        )
+                        .header("Accept", "application/vnd.github.v3+json")
+                        .header("User-Agent", "gha-dashboard-rust-app")
+                },
+            )
+            .await?;
+        workflow_run(run_res, self.max_title_length)
+    }
+
+    #[tracing::instrument(
+        name = "GitHubApiAdapter::fetch_workflow_jobs",
+        skip(self, repo),
+        fields(repo = %self.redactor.repository(repo))
+    )]
+    async fn fetch_workflow_jobs(
+        &self,
+        repo: &RepoFullName,
+        run_id: RunId,
+    ) -> Result<Vec<Job>, GitHubApiError> {
+        let resource = format!("jobs for {repo} run {run_id}");
+        let url = self.repo_url(
+            &resource,
+            repo,
+            &["actions", "runs", &run_id.to_string(), "jobs"],
+            &[],
+        )?;
+
+        let api_response: GitHubWorkflowJobsApiResponse = self
+            .execute_with_retry(
+                GitHubOperation::WorkflowJobs,
+                "/repos/{owner}/{repo}/actions/runs/{run_id}/jobs",
+                &resource,
+                || {
+                    self.client
+                        .get(url.clone())
+                        .header(
+                            "Authorization",
+                            format!("Bearer {}", self.github_token.token()),
+                        )
+                        .header("Accept", "application/vnd.github.v3+json")
+                        .header("User-Agent", "gha-dashboard-rust-app")
+                },
+            )
+            .await?;
+
+        api_response
+            .jobs
+            .into_iter()
+            .map(job_from_response)
+            .collect()
+    }
+
+    #[tracing::instrument(
+        name = "GitHubApiAdapter::fetch_workflow_job",
+        skip(self, repo),
+        fields(repo = %self.redactor.repository(repo))
+    )]
+    async fn fetch_workflow_job(
+        &self,
+        repo: &RepoFullName,
+        job_id: JobId,
+    ) -> Result<Job, GitHubApiError> {
+        let resource = format!("job {job_id} of {repo}");
+        let url = self.repo_url(
+            &resource,
+            repo,
+            &["actions", "jobs", &job_id.to_string()],
+            &[],
+        )?;
+
+        let job_res: GitHubJobResponse = self
+            .execute_with_retry(
+                GitHubOperation::WorkflowJobs,
+                "/repos/{owner}/{repo}/actions/jobs/{job_id}",
+                &resource,
+                || {
+                    self.client
+                        .get(url.clone())
+                        .header(
+                            "Authorization",
+                            format!("Bearer {}", self.github_token.token()),
+                        )
+                        .header("Accept", "application/vnd.github.v3+json")
+                        .header("User-Agent", "gha-dashboard-rust-app")
+                },
+            )
+            .await?;
+        job_from_response(job_res)
+    }
+
+    #[tracing::instrument(
+        name = "GitHubApiAdapter::fetch_job_logs",
+        skip(self, repo),
+        fields(repo = %self.redactor.repository(repo))
+    )]
+    async fn fetch_job_logs(
+        &self,
+        repo: &RepoFullName,
+        job_id: JobId,
+    ) -> Result<String, GitHubApiError> {
+        let resource = format!("logs of job {job_id} of {repo}");
+        let url = self.repo_url(
+            &resource,
+            repo,
+            &["actions", "jobs", &job_id.to_string(), "logs"],
+            &[],
+        )?;
+
+        // GitHub redirects to the log file, which reqwest follows without the token
+        self.send_with_retry(
+            GitHubOperation::JobLogs,
+            "/repos/{owner}/{repo}/actions/jobs/{job_id}/logs",
+            &resource,
+            || {
+                self.client
+                    .get(url.clone())
+                    .header(
+                        "Authorization",
+                        format!("Bearer {}", self.github_token.token()),
+                    )
+                    .header("Accept", "application/vnd.github.v3+json")
+                    .header("User-Agent", "gha-dashboard-rust-app")
+            },
+            Response::text,
+        )
+        .await
+    }
+
+    #[tracing::instrument(
+        name = "GitHubApiAdapter::fetch_workflows",
+        skip(self, repo),
+        fields(repo = %self.redactor.repository(repo))
+    )]
+    async fn fetch_workflows(&self, repo: &RepoFullName) -> Result<Vec<Workflow>, GitHubApiError> {
+        let resource = format!("workflows of {repo}");
+        let url = self.repo_url(
+            &resource,
+            repo,
+            &["actions", "workflows"],
+            &[("per_page", "100")],
+        )?;
+
+        let api_response: GitHubWorkflowsApiResponse = self
+            .execute_with_retry(
+                GitHubOperation::Workflows,
+                "/repos/{owner}/{repo}/actions/workflows",
+                &resource,
+                || {
+                    self.client
+                        .get(url.clone())
+                        .header(
+                            "Authorization",
+                            format!("Bearer {}", self.github_token.token()),
+                        )
+                        .header("Accept", "application/vnd.github.v3+json")
+                        .header("User-Agent", "gha-dashboard-rust-app")
+                },
+            )
+            .await?;
+
+        Ok(api_response
+            .workflows
+            .into_iter()
+            .map(Workflow::from)
+            .collect())
+    }
+
+    #[tracing::instrument(
+        name = "GitHubApiAdapter::fetch_run_timing",
+        skip(self, repo),
+        fields(repo = %self.redactor.repository(repo))
+    )]
+    async fn fetch_run_timing(
+        &self,
+        repo: &RepoFullName,
+        run_id: RunId,
+    ) -> Result<RunTiming, GitHubApiError> {
+        let resource = format!("timing of {repo} run {run_id}");
+        let url = self.repo_url(
+            &resource,
+            repo,
+            &["actions", "runs", &run_id.to_string(), "timing"],
+            &[],
+        )?;
+
+        let api_response: GitHubRunTimingResponse = self
+            .execute_with_retry(
+                GitHubOperation::RunTiming,
+                "/repos/{owner}/{repo}/actions/runs/{run_id}/timing",
+                &resource,
+                || {
+                    self.client
+                        .get(url.clone())
+                        .header(
+                            "Authorization",
+                            format!("Bearer {}", self.github_token.token()),
+                        )
+                        .header("Accept", "application/vnd.github.v3+json")
+                        .header("User-Agent", "gha-dashboard-rust-app")
+                },
+            )
+            .await?;
+
+        Ok(RunTiming::from(api_response))
+    }
+
+    #[tracing::instrument(
+        name = "GitHubApiAdapter::fetch_runners",
+        skip(self, repo),
+        fields(repo = %self.redactor.repository(repo))
+    )]
+    async fn fetch_runners(
+        &self,
+        repo: &RepoFullName,
+    ) -> Result<Vec<SelfHostedRunner>, GitHubApiError> {
+        let resource = format!("runners of {repo}");
+        let url = self.repo_url(
+            &resource,
+            repo,
+            &["actions", "runners"],
+            &[("per_page", "100")],
+        )?;
+
+        let api_response: GitHubRunnersApiResponse = self
+            .execute_with_retry(
+                GitHubOperation::Runners,
+                "/repos/{owner}/{repo}/actions/runners",
+                &resource,
+                || {
+                    self.client
+                        .get(url.clone())
+                        .header(
+                            "Authorization",
+                            format!("Bearer {}", self.github_token.token()),
+                        )
+                        .header("Accept", "application/vnd.github.v3+json")
+                        .header("User-Agent", "gha-dashboard-rust-app")
+                },
+            )
+            .await?;
+
+        Ok(api_response
+            .runners
+            .into_iter()
+            .map(SelfHostedRunner::from)
+            .collect())
+    }
+
+    #[tracing::instrument(
+        name = "GitHubApiAdapter::fetch_deployments",
+        skip(self, repo),
+        fields(repo = %self.redactor.repository(repo))
+    )]
+    async fn fetch_deployments(
+        &self,
+        repo: &RepoFullName,
+        environment: Option<&str>,
+    ) -> Result<Vec<Deployment>, GitHubApiError> {
+        let resource = match environment {
+            Some(environment) => format!("deployments of {repo} to {environment}"),
+            None => format!("deployments of {repo}"),
+        };
+        let query: Vec<(&str, &str)> = std::iter::once(("per_page", "100"))
+            .chain(environment.map(|environment| ("environment", environment)))
+            .collect();
+        let url = self.repo_url(&resource, repo, &["deployments"], &query)?;
+
+        let api_response: Vec<GitHubDeploymentResponse> = self
+            .execute_with_retry(
+                GitHubOperation::Deployments,
+                "/repos/{owner}/{repo}/deployments",
+                &resource,
+                || {
+                    self.client
+                        .get(url.clone())
+                        .header(
+                            "Authorization",
+                            format!("Bearer {}", self.github_token.token()),
+                        )
+                        .header("Accept", "application/vnd.github.v3+json")
+                        .header("User-Agent", "gha-dashboard-rust-app")
+                },
+            )
+            .await?;
+
+        Ok(api_response.into_iter().map(Deployment::from).collect())
+    }
+
+    #[tracing::instrument(
+        name = "GitHubApiAdapter::fetch_deployment_statuses",
+        skip(self, repo),
+        fields(repo = %self.redactor.repository(repo))
+    )]
+    async fn fetch_deployment_statuses(
+        &self,
+        repo: &RepoFullName,
+        deployment_id: DeploymentId,
+    ) -> Result<Vec<DeploymentStatus>, GitHubApiError> {
+        let resource = format!("statuses of deployment {deployment_id} of {repo}");
+        let deployment_id = deployment_id.to_string();
+        let url = self.repo_url(
+            &resource,
+            repo,
+            &["deployments", &deployment_id, "statuses"],
+            &[("per_page", "100")],
+        )?;
+
+        let api_response: Vec<GitHubDeploymentStatusResponse> = self
+            .execute_with_retry(
+                GitHubOperation::DeploymentStatuses,
+                "/repos/{owner}/{repo}/deployments/{deployment_id}/statuses",
+                &resource,
+                || {
+                    self.client
+                        .get(url.clone())
+                        .header(
+                            "Authorization",
+                            format!("Bearer {}", self.github_token.token()),
+                        )
+                        .header("Accept", "application/vnd.github.v3+json")
+                        .header("User-Agent", "gha-dashboard-rust-app")
+                },
+            )
+            .await?;
+
+        Ok(api_response
+            .into_iter()
+            .map(DeploymentStatus::from)
+            .collect())
+    }
+
+    #[tracing::instrument(
+        name = "GitHubApiAdapter::fetch_combined_status",
+        skip(self, repo),
+        fields(repo = %self.redactor.repository(repo))
+    )]
+    async fn fetch_combined_status(
+        &self,
+        repo: &RepoFullName,
+        sha: &str,
+    ) -> Result<Vec<CommitStatus>, GitHubApiError> {
+        let resource = format!("status of {repo} commit {sha}");
+        let url = self.repo_url(
+            &resource,
+            repo,
+            &["commits", sha, "status"],
+            &[("per_page", "100")],
+        )?;
+
+        let api_response: GitHubCombinedStatusResponse = self
+            .execute_with_retry(
+                GitHubOperation::CommitStatuses,
+                "/repos/{owner}/{repo}/commits/{sha}/status",
+                &resource,
+                || {
+                    self.client
+                        .get(url.clone())
+                        .header(
+                            "Authorization",
+                            format!("Bearer {}", self.github_token.token()),
+                        )
+                        .header("Accept", "application/vnd.github.v3+json")
+                        .header("User-Agent", "gha-dashboard-rust-app")
+                },
+            )
+            .await?;
+
+        Ok(api_response
+            .statuses
+            .into_iter()
+            .map(CommitStatus::from)
+            .collect())
+    }
+
+    #[tracing::instrument(
+        name = "GitHubApiAdapter::fetch_branch_protection_required_checks",
+        skip(self, repo),
+        fields(repo = %self.redactor.repository(repo))
+    )]
+    async fn fetch_branch_protection_required_checks(
+        &self,
+        repo: &RepoFullName,
+        branch: &str,
+    ) -> Result<Vec<String>, GitHubApiError> {
+        let resource = format!("required checks of {repo} branch {branch}");
+        let url = self.repo_url(
+            &resource,
+            repo,
+            &["branches", branch, "protection", "required_status_checks"],
+            &[],
+        )?;
+
+        let api_response: GitHubRequiredStatusChecksResponse = self
+            .execute_with_retry(
+                GitHubOperation::RequiredChecks,
+                "/repos/{owner}/{repo}/branches/{branch}/protection/required_status_checks",
+                &resource,
+                || {
+                    self.client
+                        .get(url.clone())
+                        .header(
+                            "Authorization",
+                            format!("Bearer {}", self.github_token.token()),
+                        )
+                        .header("Accept", "application/vnd.github.v3+json")
+                        .header("User-Agent", "gha-dashboard-rust-app")
+                },
+            )
+            .await?;
+
+        Ok(api_response.names())
+    }
+
+    #[tracing::instrument(
+        name = "GitHubApiAdapter::fetch_workflow_definition",
+        skip(self, repo),
+        fields(repo = %self.redactor.repository(repo))
+    )]
+    async fn fetch_workflow_definition(
+        &self,
+        repo: &RepoFullName,
+        path: &str,
+        git_ref: Option<&str>,
+    ) -> Result<WorkflowDefinition, GitHubApiError> {
+        let resource = format!("{path} of {repo}");
+        let segments: Vec<&str> = std::iter::once("contents").chain(path.split('/')).collect();
+        let query: Vec<(&str, &str)> = git_ref
+            .map(|git_ref| ("ref", git_ref))
+            .into_iter()
+            .collect();
+        let url = self.repo_url(&resource, repo, &segments, &query)?;
+
+        let api_response: GitHubContentResponse = self
+            .execute_with_retry(
+                GitHubOperation::WorkflowDefinition,
+                "/repos/{owner}/{repo}/contents/{path}",
+                &resource,
+                || {
+                    self.client
+                        .get(url.clone())
+                        .header(
+                            "Authorization",
+                            format!("Bearer {}", self.github_token.token()),
+                        )
+                        .header("Accept", "application/vnd.github.v3+json")
+                        .header("User-Agent", "gha-dashboard-rust-app")
+                },
+            )
+            .await?;
+
+        let source = api_response
+            .text()
+            .map_err(|message| GitHubApiError::Decode {
+                resource: resource.clone(),
+                message,
+            })?;
+        WorkflowDefinition::from_yaml(&source).map_err(|e| GitHubApiError::InvalidWorkflow {
+            resource,
+            message: e.to_string(),
+        })
+    }
+
+    #[tracing::instrument(name = "GitHubApiAdapter::fetch_rate_limit", skip(self))]
+    async fn fetch_rate_limit(&self) -> Result<RateLimit, GitHubApiError> {
+        let resource = "rate limit";
+        let url = self.api_url(resource, &["rate_limit"], &[])?;
+
+        let api_response: GitHubRateLimitApiResponse = self
+            .execute_with_retry(GitHubOperation::RateLimit, "/rate_limit", resource, || {
+                self.client
+                    .get(url.clone())
+                    .header(
+                        "Authorization",
+                        format!("Bearer {}", self.github_token.token()),
+                    )
+                    .header("Accept", "application/vnd.github.v3+json")
+                    .header("User-Agent", "gha-dashboard-rust-app")
+            })
+            .await?;
+
+        let core = api_response.resources.core;
+        Ok(RateLimit {
+            limit: core.limit,
+            remaining: core.remaining,
+            used: core.used,
+            reset: DateTime::from_timestamp(core.reset, 0).ok_or_else(|| {
+                GitHubApiError::Decode {
+                    resource: resource.to_string(),
+                    message: format!("Invalid reset timestamp {}", core.reset),
+                }
+            })?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::services::DeploymentInventory;
+    use crate::domain::models::deployment::DeploymentStatusState;
+    use crate::infrastructures::telemetry::propagation::install_propagator;
+    use crate::test_support::{
+        DISPATCH_WORKFLOW, WORKFLOW_JOB_EVENT, WORKFLOW_RUN_EVENT, exported_value,
+        in_memory_metrics,
+    };
+    use opentelemetry::KeyValue;
+    use opentelemetry::trace::TracerProvider;
+    use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider};
+    use token::FileToken;
+    use tracing_subscriber::prelude::*;
+    use wiremock::matchers::{header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn test_parse_token_expiration() -> anyhow::Result<()> {
+        let expected: DateTime<Utc> = DateTime::parse_from_rfc3339("2024-09-01T00:00:00Z")?.into();
+
+        assert_eq!(
+            parse_token_expiration("2024-09-01 00:00:00 UTC"),
+            Some(expected)
+        );
+        assert_eq!(
+            parse_token_expiration("2024-08-31 17:00:00 -0700"),
+            Some(expected)
+        );
+        assert_eq!(parse_token_expiration("next tuesday"), None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_api_status_is_captured_from_response_headers() -> anyhow::Result<()> {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/user/repos"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header(
+                        "github-authentication-token-expiration",
+                        "2024-09-01 00:00:00 UTC",
+                    )
+                    .insert_header("x-ratelimit-remaining", "4321")
+                    .insert_header("x-ratelimit-limit", "5000")
+                    .set_body_string("[]"),
+            )
+            .mount(&server)
+            .await;
+        let adapter = GitHubApiAdapter::new(server.uri(), "token".to_string());
+        assert_eq!(adapter.api_status(), GitHubApiStatus::default());
+
+        adapter.fetch_repositories(5).await?;
+
+        let status = adapter.api_status();
+        assert_eq!(
+            status.token_expires_at,
+            Some(DateTime::parse_from_rfc3339("2024-09-01T00:00:00Z")?.into())
+        );
+        assert_eq!(status.rate_limit_remaining, Some(4321));
+        assert_eq!(status.rate_limit_limit, Some(5000));
+        Ok(())
+    }
+
+    const JOBS_PAYLOAD: &str = r#"{
+        "total_count": 2,
+        "jobs": [
+            {
+                "id": 101,
+                "run_id": 42,
+                "name": "build",
+                "status": "completed",
+                "conclusion": "success",
+                "started_at": "2024-08-01T10:00:00Z",
+                "completed_at": "2024-08-01T10:02:30Z",
+                "runner_name": "GitHub Actions 2",
+                "labels": ["ubuntu-latest"],
+                "html_url": "https://github.com/owner/repo/actions/runs/42/job/101",
+                "steps": [
+                    {"name": "Checkout", "status": "completed", "conclusion": "success", "number": 1,
+                     "started_at": "2024-08-01T10:00:01Z", "completed_at": "2024-08-01T10:00:05Z"},
+                    {"name": "Build", "status": "completed", "conclusion": "success", "number": 2,
+                     "started_at": "2024-08-01T10:00:05Z", "completed_at": "2024-08-01T10:02:29Z"}
+                ]
+            },
+            {
+                "id": 102,
+                "run_id": 42,
+                "name": "test",
+                "status": "in_progress",
+                "conclusion": null,
+                "started_at": "2024-08-01T10:02:31Z",
+                "completed_at": null,
+                "runner_name": null,
+                "labels": ["ubuntu-latest"],
+                "html_url": "https://github.com/owner/repo/actions/runs/42/job/102",
+                "steps": []
+            }
+        ]
+    }"#;
+
+    fn actor_json(login: &str) -> serde_json::Value {
+        serde_json::json!({
+            "login": login,
+            "id": 1,
+            "avatar_url": format!("https://avatars.githubusercontent.com/{login}"),
+            "html_url": format!("https://github.com/{login}"),
+            "type": "User"
+        })
+    }
+
+    fn run_json(id: u64, actor: &str, triggering_actor: &str) -> serde_json::Value {
+        serde_json::json!({
+            "id": id,
+            "run_number": id + 100,
+            "workflow_id": 7,
+            "name": "CI",
+            "display_title": "Bump dependency",
+            "event": "pull_request",
+            "head_branch": "dependabot/cargo/serde-1.0.200",
+            "head_sha": "0123456789abcdef0123456789abcdef01234567",
+            "status": "completed",
+            "conclusion": "success",
+            "created_at": "2024-08-01T10:00:00Z",
+            "updated_at": "2024-08-01T10:05:00Z",
+            "html_url": format!("https://github.com/owner/repo/actions/runs/{id}"),
+            "repository": {"full_name": "owner/repo"},
+            "actor": actor_json(actor),
+            "triggering_actor": actor_json(triggering_actor)
+        })
+    }
+
+    #[tokio::test]
+    async fn test_fetch_workflow_runs_maps_actors() -> anyhow::Result<()> {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/actions/runs"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "total_count": 2,
+                "workflow_runs": [
+                    run_json(1, "dependabot[bot]", "dependabot[bot]"),
+                    // Re-run of a bot run by a human
+                    run_json(2, "dependabot[bot]", "octocat")
+                ]
+            })))
+            .mount(&server)
+            .await;
+        let adapter = GitHubApiAdapter::new(server.uri(), "token".to_string());
+
+        let runs = adapter
+            .fetch_workflow_runs(&"owner/repo".parse()?, 2)
+            .await?;
+
+        assert_eq!(runs.len(), 2);
+        assert!(runs[0].actor.is_bot());
+        assert_eq!(runs[0].triggering_actor, Some(runs[0].actor.clone()));
+        assert_eq!(runs[1].actor.login, "dependabot[bot]");
+        let triggering_actor = runs[1]
+            .triggering_actor
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("missing triggering actor"))?;
+        assert_eq!(triggering_actor.login, "octocat");
+        assert!(!triggering_actor.is_bot());
+        assert_eq!(
+            triggering_actor.avatar_url,
+            "https://avatars.githubusercontent.com/octocat"
+        );
+        assert_eq!(runs[1].run_number, 102);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fetch_workflow_run_fetches_one_run() -> anyhow::Result<()> {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/actions/runs/1"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(run_json(1, "octocat", "octocat")),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        let adapter = GitHubApiAdapter::new(server.uri(), "token".to_string());
+
+        let run = adapter
+            .fetch_workflow_run(&"owner/repo".parse()?, RunId(1))
+            .await?;
+
+        assert_eq!(run.id, RunId(1));
+        assert_eq!(run.actor.login, "octocat");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fetch_workflow_runs_by_status_filters_on_github() -> anyhow::Result<()> {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/actions/runs"))
+            .and(query_param("status", "queued"))
+            .and(query_param("per_page", "100"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "total_count": 1,
+                "workflow_runs": [run_json(1, "octocat", "octocat")]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let adapter = GitHubApiAdapter::new(server.uri(), "token".to_string());
+
+        let runs = adapter
+            .fetch_workflow_runs_by_status(&"owner/repo".parse()?, RunStatus::Queued, 100)
+            .await?;
+
+        assert_eq!(runs.len(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fetch_workflow_runs_truncates_long_titles() -> anyhow::Result<()> {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/actions/runs"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "total_count": 1,
+                "workflow_runs": [run_json(1, "octocat", "octocat")]
+            })))
+            .mount(&server)
+            .await;
+        let adapter = GitHubApiAdapter::new(server.uri(), "token".to_string())
+            .with_max_title_length(NonZeroUsize::new(5));
+
+        let runs = adapter
+            .fetch_workflow_runs(&"owner/repo".parse()?, 1)
+            .await?;
+
+        assert_eq!(runs[0].display_title, "Bump\u{2026}");
+        assert_eq!(
+            runs[0].full_display_title.as_deref(),
+            Some("Bump dependency")
+        );
+        Ok(())
+    }
+
+    /// Runs listed by a mocked GitHub that sends `run_json(1, ..)` with `created_at` and
+    /// `updated_at`, and `run_json(2, ..)` as is.
+    async fn runs_with_timestamps(
+        created_at: &str,
+        updated_at: &str,
+    ) -> anyhow::Result<Vec<WorkflowRun>> {
+        let server = MockServer::start().await;
+        let mut run = run_json(1, "octocat", "octocat");
+        run["created_at"] = created_at.into();
+        run["updated_at"] = updated_at.into();
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/actions/runs"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "total_count": 2,
+                "workflow_runs": [run, run_json(2, "octocat", "octocat")]
+            })))
+            .mount(&server)
+            .await;
+        let adapter = GitHubApiAdapter::new(server.uri(), "token".to_string());
+        Ok(adapter
+            .fetch_workflow_runs(&"owner/repo".parse()?, 2)
+            .await?)
+    }
+
+    #[tokio::test]
+    async fn test_timestamps_with_an_offset_are_converted_to_utc() -> anyhow::Result<()> {
+        let runs =
+            runs_with_timestamps("2024-08-01T19:00:00+09:00", "2024-08-01T05:05:00-05:00").await?;
+
+        assert_eq!(runs[0].created_at.to_rfc3339(), "2024-08-01T10:00:00+00:00");
+        assert_eq!(runs[0].updated_at.to_rfc3339(), "2024-08-01T10:05:00+00:00");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_timestamps_without_an_offset_are_taken_as_utc() -> anyhow::Result<()> {
+        let runs = runs_with_timestamps("2024-08-01T10:00:00", "2024-08-01T10:05:00.250").await?;
+
+        assert_eq!(runs[0].created_at.to_rfc3339(), "2024-08-01T10:00:00+00:00");
+        assert_eq!(
+            runs[0].updated_at.to_rfc3339(),
+            "2024-08-01T10:05:00.250+00:00"
+        );
+        assert_eq!(runs[0].duration_seconds(), Some(300));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_runs_with_a_malformed_timestamp_are_skipped() -> anyhow::Result<()> {
+        for (created_at, updated_at) in [
+            ("yesterday", "2024-08-01T10:05:00Z"),
+            ("2024-08-01T10:00:00Z", "2024-13-01T10:05:00Z"),
+            ("2024-08-01T10:00:00Z", ""),
+        ] {
+            let runs = runs_with_timestamps(created_at, updated_at).await?;
+
+            let ids: Vec<RunId> = runs.iter().map(|run| run.id).collect();
+            assert_eq!(ids, [RunId(2)], "{created_at} {updated_at}");
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_skewed_timestamps_are_kept_with_durations_clamped() -> anyhow::Result<()> {
+        let runs = runs_with_timestamps("2024-08-01T10:05:00Z", "2024-08-01T10:00:00Z").await?;
+
+        assert_eq!(runs.len(), 2);
+        assert!(runs[0].has_clock_skew(Utc::now()));
+        assert_eq!(runs[0].duration_seconds(), Some(0));
+        assert!(!runs[1].has_clock_skew(Utc::now()));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fetch_repositories_maps_owner_avatar_and_html_url() -> anyhow::Result<()> {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/user/repos"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!([{
+                    "id": 1_296_269,
+                    "name": "Hello-World",
+                    "full_name": "octocat/Hello-World",
+                    "owner": {
+                        "login": "octocat",
+                        "avatar_url": "https://avatars.githubusercontent.com/u/583231?v=4",
+                        "html_url": "https://github.com/octocat"
+                    },
+                    "html_url": "https://github.com/octocat/Hello-World"
+                }])),
+            )
+            .mount(&server)
+            .await;
+        let adapter = GitHubApiAdapter::new(server.uri(), "token".to_string());
+
+        let repositories = adapter.fetch_repositories(5).await?;
+
+        assert_eq!(
+            repositories,
+            vec![
+                // Query string included: the URL is passed through, not rebuilt or proxied
+                Repository::new("octocat", "Hello-World")?.with_owner_avatar_url(Some(
+                    "https://avatars.githubusercontent.com/u/583231?v=4".to_string()
+                ))
+            ]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_requests_are_recorded_in_metrics() -> anyhow::Result<()> {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/user/repos"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("x-ratelimit-remaining", "4321")
+                    .set_body_string("[]"),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/actions/workflows"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        let (metrics, provider, exporter) = in_memory_metrics()?;
+        let adapter = GitHubApiAdapter::new(server.uri(), "token".to_string())
+            .with_metrics(Arc::clone(&metrics));
+
+        adapter.fetch_repositories(5).await?;
+        assert!(
+            adapter
+                .fetch_workflows(&"owner/repo".parse()?)
+                .await
+                .is_err()
+        );
+
+        let rendered = metrics.render()?;
+        for line in [
+            r#"github_api_requests_total{operation="repositories",status="2xx"} 1"#,
+            r#"github_api_requests_total{operation="workflows",status="4xx"} 1"#,
+            "github_rate_limit_remaining 4321",
+        ] {
+            assert!(rendered.contains(line), "{line} missing");
+        }
+        provider.force_flush()?;
+        for (operation, status) in [("repositories", "2xx"), ("workflows", "4xx")] {
+            let attributes = [
+                KeyValue::new("operation", operation),
+                KeyValue::new("status", status),
+            ];
+            assert_eq!(
+                exported_value(&exporter, "github.api.requests", &attributes)?,
+                Some(1),
+                "{operation} {status}"
+            );
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_retries_are_published_as_they_happen() -> anyhow::Result<()> {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/user/repos"))
+            .respond_with(ResponseTemplate::new(502))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/user/repos"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("[]"))
+            .mount(&server)
+            .await;
+        let retry_events = Arc::new(GitHubRetryEvents::default());
+        let mut events = retry_events.subscribe();
+        let adapter = GitHubApiAdapter::new(server.uri(), "token".to_string())
+            .with_retry_events(Arc::clone(&retry_events));
+
+        adapter.fetch_repositories(5).await?;
+
+        let event = events.try_recv()?;
+        assert_eq!(
+            (
+                event.operation,
+                event.attempt,
+                event.category,
+                event.next_wait_ms
+            ),
+            (GitHubOperation::Repositories, 1, "other", Some(1000))
+        );
+        assert!(events.try_recv().is_err());
+        let health = retry_events.health(Utc::now());
+        assert_eq!(
+            health.state,
+            retry_events::GitHubApiHealth::DegradedWithRetries
+        );
+        assert_eq!(health.retries, 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_retries_give_up_at_the_deadline() -> anyhow::Result<()> {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/user/repos"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string("[]")
+                    .set_delay(Duration::from_secs(30)),
+            )
+            .mount(&server)
+            .await;
+        let retry_events = Arc::new(GitHubRetryEvents::default());
+        let mut events = retry_events.subscribe();
+        let adapter = GitHubApiAdapter::new(server.uri(), "token".to_string())
+            .with_retry_events(Arc::clone(&retry_events))
+            .with_retry_deadline(Duration::from_millis(300));
+
+        let started = Instant::now();
+        let result = adapter.fetch_repositories(5).await;
+
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert_eq!(
+            result.err(),
+            Some(GitHubApiError::Timeout {
+                resource: "repositories".to_string(),
+                deadline_ms: 300,
+            })
+        );
+        let event = events.try_recv()?;
+        assert_eq!(
+            (event.attempt, event.category, event.next_wait_ms),
+            (1, "timeout", None)
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_retries_succeeding_before_the_deadline_are_kept() -> anyhow::Result<()> {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/user/repos"))
+            .respond_with(ResponseTemplate::new(502))
+            .up_to_n_times(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/user/repos"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("[]"))
+            .mount(&server)
+            .await;
+        // The two waits take 2.5 seconds
+        let adapter = GitHubApiAdapter::new(server.uri(), "token".to_string())
+            .with_retry_deadline(Duration::from_secs(5));
+
+        assert_eq!(adapter.fetch_repositories(5).await?, Vec::new());
+        assert_eq!(server.received_requests().await.map(|r| r.len()), Some(3));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_requests_are_recorded_in_the_request_log_redacted() -> anyhow::Result<()> {
+        use crate::infrastructures::telemetry::{LogRedaction, RepositoryMasking};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/user/repos"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("x-ratelimit-remaining", "4321")
+                    .set_body_string("[]"),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/actions/workflows"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        let request_log = Arc::new(GitHubRequestLog::default());
+        let adapter = GitHubApiAdapter::new(server.uri(), "token".to_string())
+            .with_redactor(Redactor::new(LogRedaction::Strict, RepositoryMasking::Full))
+            .with_request_log(Arc::clone(&request_log));
+
+        adapter.fetch_repositories(5).await?;
+        assert!(
+            adapter
+                .fetch_workflows(&"owner/repo".parse()?)
+                .await
+                .is_err()
+        );
+
+        let requests = request_log.requests(None);
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].operation, GitHubOperation::Workflows);
+        assert_eq!(requests[0].route, "/repos/{owner}/{repo}/actions/workflows");
+        assert_eq!(requests[0].status, Some(404));
+        let error = requests[0].error.clone().unwrap_or_default();
+        assert!(error.contains("***/***"), "{error}");
+        assert!(!error.contains("owner/repo"), "{error}");
+        assert_eq!(
+            (requests[1].status, requests[1].rate_limit_remaining),
+            (Some(200), Some(4321))
+        );
+        assert_eq!(requests[1].error, None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_snapshots_count_the_requests_made_for_them() -> anyhow::Result<()> {
+        use crate::application::use_cases::stream_github_actions_runs::{
+            StreamGitHubActionsRunsInteractor, StreamGitHubActionsRunsUseCase,
+            StreamGitHubActionsRunsUseCaseInput,
+        };
+        use futures_util::StreamExt;
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/user/repos"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!([{
+                    "name": "repo",
+                    "owner": {"login": "owner", "avatar_url": null},
+                    "html_url": "https://github.com/owner/repo"
+                }])),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/actions/workflows"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({"total_count": 0, "workflows": []})),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/actions/runs"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({"total_count": 0, "workflow_runs": []})),
+            )
+            .mount(&server)
+            .await;
+        let adapter = GitHubApiAdapter::new(server.uri(), "token".to_string());
+        // Outside any recorder, requests are not counted
+        adapter.fetch_repositories(5).await?;
+        let interactor = StreamGitHubActionsRunsInteractor::new(Arc::new(adapter));
+        let stream = interactor.execute(StreamGitHubActionsRunsUseCaseInput::default());
+        tokio::pin!(stream);
+
+        let snapshot = stream
+            .next()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("stream ended"))??;
+
+        let requests = server.received_requests().await.unwrap_or_default();
+        let calls = snapshot
+            .github_calls
+            .ok_or_else(|| anyhow::anyhow!("no calls counted"))?;
+        assert_eq!(calls.total, u64::try_from(requests.len())? - 1);
+        assert_eq!(calls.by_operation["repositories"], 1);
+        assert_eq!(calls.by_operation["workflows"], 1);
+        assert!(calls.by_operation["workflow_runs"] >= 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fetch_repositories_skips_invalid_names() -> anyhow::Result<()> {
+        let server = MockServer::start().await;
+        let repository = |owner: &str, name: &str| {
+            serde_json::json!({
+                "name": name,
+                "owner": {"login": owner, "avatar_url": null},
+                "html_url": format!("https://github.com/{owner}/{name}")
+            })
+        };
+        Mock::given(method("GET"))
+            .and(path("/user/repos"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                repository("me", "api"),
+                repository("me", "api/"),
+                repository("me", "")
+            ])))
+            .mount(&server)
+            .await;
+        let adapter = GitHubApiAdapter::new(server.uri(), "token".to_string());
+
+        let repositories = adapter.fetch_repositories(5).await?;
+
+        assert_eq!(repositories, vec![Repository::new("me", "api")?]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_api_urls_are_built_from_segments() -> anyhow::Result<()> {
+        let repo: RepoFullName = "me/api".parse()?;
+        for base_url in [
+            "https://ghe.example.com/api/v3",
+            "https://ghe.example.com/api/v3/",
+        ] {
+            let adapter = GitHubApiAdapter::new(base_url.to_string(), "token".to_string());
+            let url =
+                adapter.repo_url("runs", &repo, &["actions", "runs"], &[("per_page", "5")])?;
+            assert_eq!(
+                url.as_str(),
+                "https://ghe.example.com/api/v3/repos/me/api/actions/runs?per_page=5"
+            );
+        }
+
+        let adapter =
+            GitHubApiAdapter::new("https://api.github.com".to_string(), "token".to_string());
+        // A segment stays one path level, whatever it contains
+        let url = adapter.api_url("test", &["user", "a/b?c#d"], &[])?;
+        assert_eq!(url.as_str(), "https://api.github.com/user/a%2Fb%3Fc%23d");
+
+        let adapter = GitHubApiAdapter::new("not a url".to_string(), "token".to_string());
+        assert!(matches!(
+            adapter.api_url("test", &["user"], &[]),
+            Err(GitHubApiError::Other { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_html_url_must_be_absolute_https() -> anyhow::Result<()> {
+        let repo: RepoFullName = "owner/repo".parse()?;
+        let fallback = "https://github.com/owner/repo/actions/runs/7";
+
+        assert_eq!(
+            run_html_url(
+                "https://ghe.example.com/owner/repo/actions/runs/7".to_string(),
+                &repo,
+                RunId(7)
+            ),
+            "https://ghe.example.com/owner/repo/actions/runs/7"
+        );
+        for html_url in [
+            "",
+            "/owner/repo/actions/runs/7",
+            "http://github.com/owner/repo/actions/runs/7",
+            "javascript:alert(1)",
+        ] {
+            assert_eq!(
+                run_html_url(html_url.to_string(), &repo, RunId(7)),
+                fallback,
+                "{html_url:?} accepted"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_workflow_run_event_is_mapped_like_polled_runs() -> anyhow::Result<()> {
+        let run = workflow_run_from_event(WORKFLOW_RUN_EVENT.as_bytes(), NonZeroUsize::new(10))?;
+
+        assert_eq!(run.repository_name, "octo-org/octo-repo".parse()?);
+        assert_eq!(run.id, RunId(30_433_642));
+        assert_eq!(run.run_number, 562);
+        assert_eq!(run.workflow_id, WorkflowId(159_038));
+        assert_eq!(run.status, RunStatus::Completed);
+        assert_eq!(run.conclusion, Some(RunConclusion::Failure));
+        assert_eq!(run.actor.login, "octocat");
+        assert_eq!(
+            run.triggering_actor.map(|actor| actor.login).as_deref(),
+            Some("hubot")
+        );
+        assert_eq!(run.display_title, "Update RE…");
+        assert_eq!(run.full_display_title.as_deref(), Some("Update README.md"));
+
+        assert!(matches!(
+            workflow_run_from_event(b"{\"action\": \"completed\"}", None),
+            Err(GitHubApiError::Decode { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_workflow_job_event_is_mapped_like_fetched_jobs() -> anyhow::Result<()> {
+        let job = workflow_job_from_event(WORKFLOW_JOB_EVENT.as_bytes())?;
+
+        assert_eq!(job.id, JobId(21_400_001));
+        assert_eq!(job.run_id, RunId(30_433_642));
+        assert_eq!(job.name, "test (ubuntu-latest)");
+        assert_eq!(job.conclusion, Some(RunConclusion::Failure));
+        assert_eq!(job.duration(), Some(Duration::from_secs(280)));
+        assert_eq!(job.labels, ["ubuntu-latest"]);
+        assert_eq!(
+            job.steps
+                .iter()
+                .map(|step| (step.name.as_str(), step.conclusion))
+                .collect::<Vec<_>>(),
+            [
+                ("Set up job", Some(RunConclusion::Success)),
+                ("Run tests", Some(RunConclusion::Failure)),
+            ]
+        );
+
+        assert!(matches!(
+            workflow_job_from_event(WORKFLOW_RUN_EVENT.as_bytes()),
+            Err(GitHubApiError::Decode { .. })
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fetch_rate_limit_reads_core_resource() -> anyhow::Result<()> {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rate_limit"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "resources": {
+                    "core": {"limit": 5000, "used": 1, "remaining": 4999, "reset": 1_722_510_000},
+                    "search": {"limit": 30, "used": 12, "remaining": 18, "reset": 1_722_506_460}
+                },
+                "rate": {"limit": 5000, "used": 1, "remaining": 4999, "reset": 1_722_510_000}
+            })))
+            .mount(&server)
+            .await;
+        let adapter = GitHubApiAdapter::new(server.uri(), "token".to_string());
+
+        let rate_limit = adapter.fetch_rate_limit().await?;
+
+        assert_eq!(
+            rate_limit,
+            RateLimit {
+                limit: 5000,
+                remaining: 4999,
+                used: 1,
+                reset: DateTime::parse_from_rfc3339("2024-08-01T11:00:00Z")?.into(),
+            }
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_requests_carry_the_trace_context_unless_disabled() -> anyhow::Result<()> {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rate_limit"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "resources": {
+                    "core": {"limit": 5000, "used": 1, "remaining": 4999, "reset": 1_722_510_000}
+                }
+            })))
+            .mount(&server)
+            .await;
+        install_propagator();
+        let exporter = InMemorySpanExporter::default();
+        let tracer_provider = SdkTracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        let _subscriber = tracing::subscriber::set_default(
+            tracing_subscriber::registry()
+                .with(tracing_opentelemetry::layer().with_tracer(tracer_provider.tracer("test"))),
+        );
+
+        GitHubApiAdapter::new(server.uri(), "token".to_string())
+            .fetch_rate_limit()
+            .await?;
+        GitHubApiAdapter::new(server.uri(), "token".to_string())
+            .with_trace_propagation(false)
+            .fetch_rate_limit()
+            .await?;
+
+        let requests = server
+            .received_requests()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("requests are not recorded"))?;
+        let [propagated, skipped] = requests.as_slice() else {
+            anyhow::bail!("expected two requests, got {}", requests.len());
+        };
+        let traceparent = propagated
+            .headers
+            .get("traceparent")
+            .ok_or_else(|| anyhow::anyhow!("missing traceparent"))?
+            .to_str()?;
+        let [version, trace_id, span_id, flags] = traceparent.split('-').collect::<Vec<_>>()[..]
+        else {
+            anyhow::bail!("malformed traceparent {traceparent}");
+        };
+        assert_eq!(version, "00");
+        assert_eq!(flags, "01");
+        assert!(!skipped.headers.contains_key("traceparent"));
+
+        let spans = exporter.get_finished_spans()?;
+        let request_span = spans
+            .iter()
+            .find(|span| span.span_context.span_id().to_string() == span_id)
+            .ok_or_else(|| anyhow::anyhow!("no span {span_id} in {spans:?}"))?;
+        assert_eq!(request_span.name, "GitHubApiAdapter::request");
+        assert_eq!(request_span.span_context.trace_id().to_string(), trace_id);
+        assert!(
+            request_span
+                .attributes
+                .iter()
+                .any(|attribute| attribute.key.as_str() == "http.route"
+                    && attribute.value.as_str() == "/rate_limit")
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rotated_token_file_is_used_by_later_requests() -> anyhow::Result<()> {
+        let server = MockServer::start().await;
+        let rate_limit = serde_json::json!({
+            "resources": {
+                "core": {"limit": 5000, "used": 1, "remaining": 4999, "reset": 1_722_510_000}
+            }
+        });
+        // Each token is revoked once it has been used
+        for token in ["first", "second", "third"] {
+            Mock::given(method("GET"))
+                .and(path("/rate_limit"))
+                .and(header("Authorization", format!("Bearer {token}")))
+                .respond_with(ResponseTemplate::new(200).set_body_json(&rate_limit))
+                .up_to_n_times(1)
+                .mount(&server)
+                .await;
+        }
+        Mock::given(method("GET"))
+            .and(path("/rate_limit"))
+            .respond_with(ResponseTemplate::new(401))
+            .with_priority(10)
+            .mount(&server)
+            .await;
+        let dir = tempfile::tempdir()?;
+        let file = dir.path().join("github-token");
+        std::fs::write(&file, "first\n")?;
+        let token = Arc::new(FileToken::open(&file)?);
+        let adapter = GitHubApiAdapter::with_token_provider(server.uri(), token.clone());
+
+        adapter.fetch_rate_limit().await?;
+        // Rotated but not reloaded yet: the 401 triggers a reload and a retry
+        std::fs::write(&file, "second\n")?;
+        adapter.fetch_rate_limit().await?;
+        // Rotated and picked up by the reloader before the next request
+        std::fs::write(&file, "third\n")?;
+        let reloader = token.spawn(Duration::from_millis(10));
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while token.token() != "third" {
+                sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await?;
+        adapter.fetch_rate_limit().await?;
+        reloader.abort();
+
+        let requests = server
+            .received_requests()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("requests are not recorded"))?;
+        let authorizations: Vec<_> = requests
+            .iter()
+            .filter_map(|request| request.headers.get("Authorization")?.to_str().ok())
+            .collect();
+        assert_eq!(
+            authorizations,
+            [
+                "Bearer first",
+                "Bearer first",
+                "Bearer second",
+                "Bearer third"
+            ]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fetch_workflows_maps_states() -> anyhow::Result<()> {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/actions/workflows"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "total_count": 2,
+                "workflows": [
+                    {
+                        "id": 161_335,
+                        "node_id": "MDg6V29ya2Zsb3cxNjEzMzU=",
+                        "name": "CI",
+                        "path": ".github/workflows/ci.yml",
+                        "state": "active",
+                        "created_at": "2020-01-08T23:48:37.000-08:00",
+                        "updated_at": "2020-01-08T23:50:21.000-08:00",
+                        "url": "https://api.github.com/repos/owner/repo/actions/workflows/161335",
+                        "html_url": "https://github.com/owner/repo/blob/main/.github/workflows/ci.yml",
+                        "badge_url": "https://github.com/owner/repo/workflows/CI/badge.svg"
+                    },
+                    {
+                        "id": 161_336,
+                        "node_id": "MDg6V29ya2Zsb3cxNjEzMzY=",
+                        "name": "Nightly",
+                        "path": ".github/workflows/nightly.yml",
+                        "state": "disabled_inactivity",
+                        "created_at": "2020-01-08T23:48:37.000-08:00",
+                        "updated_at": "2020-01-08T23:50:21.000-08:00",
+                        "url": "https://api.github.com/repos/owner/repo/actions/workflows/161336",
+                        "html_url": "https://github.com/owner/repo/blob/main/.github/workflows/nightly.yml",
+                        "badge_url": "https://github.com/owner/repo/workflows/Nightly/badge.svg"
+                    }
+                ]
+            })))
+            .mount(&server)
+            .await;
+        let adapter = GitHubApiAdapter::new(server.uri(), "token".to_string());
+
+        let workflows = adapter.fetch_workflows(&"owner/repo".parse()?).await?;
+
+        assert_eq!(workflows.len(), 2);
+        assert_eq!(workflows[0].id, WorkflowId(161_335));
+        assert_eq!(workflows[0].path, ".github/workflows/ci.yml");
+        assert!(workflows[0].is_active());
+        assert_eq!(workflows[1].state, WorkflowState::DisabledInactivity);
+        assert!(!workflows[1].is_active());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fetch_workflow_jobs_maps_payload() -> anyhow::Result<()> {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/actions/runs/42/jobs"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(JOBS_PAYLOAD, "application/json"))
+            .mount(&server)
+            .await;
+        let adapter = GitHubApiAdapter::new(server.uri(), "token".to_string());
+
+        let jobs = adapter
+            .fetch_workflow_jobs(&"owner/repo".parse()?, RunId(42))
+            .await?;
+
+        assert_eq!(jobs.len(), 2);
+        assert_eq!(jobs[0].name, "build");
+        assert_eq!(jobs[0].run_id, RunId(42));
+        assert_eq!(jobs[0].conclusion, Some(RunConclusion::Success));
+        assert_eq!(jobs[0].runner_name.as_deref(), Some("GitHub Actions 2"));
+        assert_eq!(jobs[0].labels, vec!["ubuntu-latest".to_string()]);
+        assert_eq!(jobs[0].steps.len(), 2);
+        assert_eq!(jobs[0].steps[1].name, "Build");
+        assert_eq!(jobs[0].steps[1].number, 2);
+        assert_eq!(
+            jobs[0].steps[1].duration(),
+            Some(std::time::Duration::from_secs(144))
+        );
+        assert!(jobs[0].completed_at.is_some());
+        assert_eq!(jobs[1].status, RunStatus::InProgress);
+        assert!(jobs[1].completed_at.is_none());
+        assert!(jobs[1].runner_name.is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fetch_run_timing_maps_billable_time_by_os() -> anyhow::Result<()> {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/actions/runs/42/timing"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "billable": {
+                    "UBUNTU": {
+                        "total_ms": 180_000,
+                        "jobs": 1,
+                        "job_runs": [{"job_id": 1, "duration_ms": 180_000}]
+                    },
+                    "WINDOWS": {"total_ms": 0, "jobs": 0},
+                    "UBUNTU_16_CORE": {"total_ms": 60_000, "jobs": 1}
+                },
+                "run_duration_ms": 500_000
+            })))
+            .mount(&server)
+            .await;
+        let adapter = GitHubApiAdapter::new(server.uri(), "token".to_string());
+
+        let timing = adapter
+            .fetch_run_timing(&"owner/repo".parse()?, RunId(42))
+            .await?;
+
+        assert_eq!(timing.run_duration_ms, Some(500_000));
+        assert_eq!(timing.billable.len(), 2);
+        assert_eq!(
+            timing.billable[&RunnerOs::Ubuntu].job_runs,
+            vec![JobTiming {
+                job_id: JobId(1),
+                duration_ms: 180_000
+            }]
+        );
+        assert_eq!(timing.billable[&RunnerOs::Windows], BillableTime::default());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fetch_runners_maps_status_and_label_names() -> anyhow::Result<()> {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/actions/runners"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "total_count": 1,
+                "runners": [
+                    {
+                        "id": 23,
+                        "name": "build-1",
+                        "os": "Linux",
+                        "status": "offline",
+                        "busy": false,
+                        "labels": [
+                            {"id": 5, "name": "self-hosted", "type": "read-only"},
+                            {"id": 7, "name": "gpu", "type": "custom"}
+                        ]
+                    }
+                ]
+            })))
+            .mount(&server)
+            .await;
+        let adapter = GitHubApiAdapter::new(server.uri(), "token".to_string());
+
+        let runners = adapter.fetch_runners(&"owner/repo".parse()?).await?;
+
+        assert_eq!(
+            runners,
+            vec![SelfHostedRunner {
+                id: RunnerId(23),
+                name: "build-1".to_string(),
+                os: "Linux".to_string(),
+                status: RunnerStatus::Offline,
+                busy: false,
+                labels: vec!["self-hosted".to_string(), "gpu".to_string()],
+            }]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_inactive_deployment_statuses_do_not_mask_success() -> anyhow::Result<()> {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/deployments"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                {
+                    "id": 12,
+                    "environment": "production",
+                    "ref": "v1.3.0",
+                    "sha": "def456",
+                    "creator": {
+                        "login": "octocat",
+                        "avatar_url": "https://avatars.githubusercontent.com/octocat",
+                        "html_url": "https://github.com/octocat"
+                    },
+                    "created_at": "2024-08-01T09:00:00Z",
+                    "updated_at": "2024-08-01T09:05:00Z"
+                },
+                {
+                    "id": 11,
+                    "environment": "production",
+                    "ref": "v1.2.0",
+                    "sha": "abc123",
+                    "creator": null,
+                    "created_at": "2024-07-31T09:00:00Z",
+                    "updated_at": "2024-07-31T09:05:00Z"
+                }
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/deployments/12/statuses"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                {
+                    "state": "inactive",
+                    "environment_url": "",
+                    "target_url": "",
+                    "created_at": "2024-08-01T10:00:00Z"
+                },
+                {
+                    "state": "success",
+                    "environment_url": "https://example.com",
+                    "target_url": "https://github.com/owner/repo/actions/runs/1",
+                    "created_at": "2024-08-01T09:05:00Z"
+                },
+                {
+                    "state": "in_progress",
+                    "target_url": "https://github.com/owner/repo/actions/runs/1",
+                    "created_at": "2024-08-01T09:01:00Z"
+                }
+            ])))
+            .mount(&server)
+            .await;
+        let inventory = DeploymentInventory::new(Arc::new(GitHubApiAdapter::new(
+            server.uri(),
+            "token".to_string(),
+        )));
+        let now = Utc::now();
+
+        inventory.refresh(&["owner/repo".parse()?], now).await;
+
+        let board = inventory
+            .board()
+            .ok_or_else(|| anyhow::anyhow!("no refresh"))?;
+        assert_eq!(board.environments.len(), 1);
+        let production = &board.environments[0];
+        assert_eq!(
+            (production.environment.as_str(), production.sha.as_str()),
+            ("production", "def456")
+        );
+        assert_eq!(production.state, DeploymentStatusState::Success);
+        assert_eq!(
+            production.target_url.as_deref(),
+            Some("https://example.com")
+        );
+        assert_eq!(
+            production
+                .creator
+                .as_ref()
+                .map(|creator| creator.login.as_str()),
+            Some("octocat")
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_job_logs_follow_the_redirect_one_request_at_a_time() -> anyhow::Result<()> {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/actions/jobs/7/logs"))
+            .respond_with(
+                ResponseTemplate::new(302)
+                    .insert_header("Location", format!("{}/blobs/job-7.txt", server.uri())),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/blobs/job-7.txt"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string("line1\nline2\n")
+                    .set_delay(Duration::from_millis(100)),
+            )
+            .mount(&server)
+            .await;
+        let adapter = GitHubApiAdapter::new(server.uri(), "token".to_string())
+            .with_max_concurrent_requests(NonZeroUsize::MIN);
+        let repo = "owner/repo".parse()?;
+
+        let started = Instant::now();
+        let (first, second) = tokio::join!(
+            adapter.fetch_job_logs(&repo, JobId(7)),
+            adapter.fetch_job_logs(&repo, JobId(7))
+        );
+
+        assert_eq!(first?, "line1\nline2\n");
+        assert_eq!(second?, "line1\nline2\n");
+        assert!(started.elapsed() >= Duration::from_millis(200));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_combined_status_maps_each_context() -> anyhow::Result<()> {
+        let server = MockServer::start().await;
+        let status = |context: &str, state: &str, target_url: &str| {
+            serde_json::json!({
+                "context": context,
+                "state": state,
+                "description": "",
+                "target_url": target_url,
+                "created_at": "2024-08-01T09:00:00Z",
+                "updated_at": "2024-08-01T09:30:00Z"
+            })
+        };
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/commits/abc123/status"))
+            .and(query_param("per_page", "100"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "state": "failure",
+                "sha": "abc123",
+                "total_count": 3,
+                "statuses": [
+                    status("ci/jenkins", "pending", "https://jenkins.example.com/job/1"),
+                    status("ci/lint", "success", ""),
+                    status("ci/deploy", "failure", "https://jenkins.example.com/job/2")
+                ]
+            })))
+            .mount(&server)
+            .await;
+        let adapter = GitHubApiAdapter::new(server.uri(), "token".to_string());
+
+        let statuses = adapter
+            .fetch_combined_status(&"owner/repo".parse()?, "abc123")
+            .await?;
+
+        let mapped: Vec<_> = statuses
+            .iter()
+            .map(|status| {
+                (
+                    status.context.as_str(),
+                    status.state,
+                    status.target_url.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            mapped,
+            [
+                (
+                    "ci/jenkins",
+                    CommitStatusState::Pending,
+                    Some("https://jenkins.example.com/job/1")
+                ),
+                ("ci/lint", CommitStatusState::Success, None),
+                (
+                    "ci/deploy",
+                    CommitStatusState::Failure,
+                    Some("https://jenkins.example.com/job/2")
+                ),
+            ]
+        );
+        assert!(statuses.iter().all(|status| status.description.is_none()));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_required_checks_merge_contexts_and_checks() -> anyhow::Result<()> {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path(
+                "/repos/owner/repo/branches/main/protection/required_status_checks",
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "strict": true,
+                "contexts": ["CI / build", "ci/circleci"],
+                "checks": [
+                    {"context": "CI / build", "app_id": 15368},
+                    {"context": "Lint", "app_id": null}
+                ]
+            })))
+            .mount(&server)
+            .await;
+        let adapter = GitHubApiAdapter::new(server.uri(), "token".to_string());
+
+        let checks = adapter
+            .fetch_branch_protection_required_checks(&"owner/repo".parse()?, "main")
+            .await?;
+
+        assert_eq!(checks, ["CI / build", "Lint", "ci/circleci"]);
+        Ok(())
+    }
+
+    /// A contents API response for `file`, base64-encoded in lines of 60 characters like
+    /// GitHub's.
+    fn contents_response(file: &str) -> ResponseTemplate {
+        let encoded = BASE64.encode(file);
+        let content: Vec<&str> = encoded
+            .as_bytes()
+            .chunks(60)
+            .map(|line| std::str::from_utf8(line).unwrap_or_default())
+            .collect();
+        ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "type": "file",
+            "encoding": "base64",
+            "path": ".github/workflows/deploy.yml",
+            "content": content.join("\n"),
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_fetch_workflow_definition_decodes_the_file_at_the_ref() -> anyhow::Result<()> {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path(
+                "/repos/owner/repo/contents/.github/workflows/deploy.yml",
+            ))
+            .and(query_param("ref", "release/1.0"))
+            .respond_with(contents_response(DISPATCH_WORKFLOW))
+            .mount(&server)
+            .await;
+        let adapter = GitHubApiAdapter::new(server.uri(), "token".to_string());
+
+        let definition = adapter
+            .fetch_workflow_definition(
+                &"owner/repo".parse()?,
+                ".github/workflows/deploy.yml",
+                Some("release/1.0"),
+            )
+            .await?;
+
+        assert_eq!(
+            definition,
+            WorkflowDefinition::from_yaml(DISPATCH_WORKFLOW)?
+        );
+        assert_eq!(definition.dispatch.inputs.len(), 4);
+        assert_eq!(definition.schedules.len(), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_invalid_workflow_files_are_not_retried() -> anyhow::Result<()> {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path(
+                "/repos/owner/repo/contents/.github/workflows/broken.yml",
+            ))
+            .respond_with(contents_response("on: [push\n"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let adapter = GitHubApiAdapter::new(server.uri(), "token".to_string());
+
+        let result = adapter
+            .fetch_workflow_definition(&"owner/repo".parse()?, ".github/workflows/broken.yml", None)
+            .await;
+
+        assert_eq!(
+            result,
+            Err(GitHubApiError::InvalidWorkflow {
+                resource: ".github/workflows/broken.yml of owner/repo".to_string(),
+                message: "line 1: unterminated flow sequence".to_string(),
+            })
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fetch_workflow_jobs_returns_not_found_without_retry() -> anyhow::Result<()> {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/actions/runs/999/jobs"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let adapter = GitHubApiAdapter::new(server.uri(), "token".to_string());
+
+        let result = adapter
+            .fetch_workflow_jobs(&"owner/repo".parse()?, RunId(999))
+            .await;
+
+        assert!(
+            result
+                .as_ref()
+                .is_err_and(|e| matches!(e, GitHubApiError::NotFound { .. }))
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_actions_disabled_is_reported_as_not_found() -> anyhow::Result<()> {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/actions/runs/42/jobs"))
+            .respond_with(ResponseTemplate::new(403).set_body_raw(
+                r#"{"message": "Actions is disabled for this repository."}"#,
+                "application/json",
+            ))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let adapter = GitHubApiAdapter::new(server.uri(), "token".to_string());
+
+        let result = adapter
+            .fetch_workflow_jobs(&"owner/repo".parse()?, RunId(42))
+            .await;
+
+        assert!(
+            result
+                .as_ref()
+                .is_err_and(|e| matches!(e, GitHubApiError::NotFound { .. }))
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_error_statuses_are_classified_without_retry() -> anyhow::Result<()> {
+        let reset: DateTime<Utc> = DateTime::parse_from_rfc3339("2024-08-01T11:00:00Z")?.into();
+        let cases = [
+            (
+                ResponseTemplate::new(401).set_body_string(r#"{"message": "Bad credentials"}"#),
+                GitHubApiError::Unauthorized {
+                    resource: "workflows of owner/repo".to_string(),
+                },
+            ),
+            (
+                ResponseTemplate::new(403)
+                    .insert_header("x-ratelimit-remaining", "0")
+                    .insert_header("x-ratelimit-reset", reset.timestamp().to_string())
+                    .set_body_string(r#"{"message": "API rate limit exceeded"}"#),
+                GitHubApiError::RateLimited {
+                    resource: "workflows of owner/repo".to_string(),
+                    reset: Some(reset),
+                },
+            ),
+            (
+                ResponseTemplate::new(403)
+                    .set_body_string(r#"{"message": "You have exceeded a secondary rate limit."}"#),
+                GitHubApiError::RateLimited {
+                    resource: "workflows of owner/repo".to_string(),
+                    reset: None,
+                },
+            ),
+            (
+                ResponseTemplate::new(403)
+                    .set_body_string(r#"{"message": "Resource not accessible by integration"}"#),
+                GitHubApiError::Forbidden {
+                    resource: "workflows of owner/repo".to_string(),
+                    message: r#"{"message": "Resource not accessible by integration"}"#.to_string(),
+                },
+            ),
+        ];
+
+        for (response, expected) in cases {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/repos/owner/repo/actions/workflows"))
+                .respond_with(response)
+                .expect(1)
+                .mount(&server)
+                .await;
+            let adapter = GitHubApiAdapter::new(server.uri(), "token".to_string());
+
+            let result = adapter.fetch_workflows(&"owner/repo".parse()?).await;
+
+            assert_eq!(result, Err(expected));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_rate_limit_reset_prefers_retry_after() -> anyhow::Result<()> {
+        let now: DateTime<Utc> = DateTime::parse_from_rfc3339("2024-08-01T10:00:00Z")?.into();
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-remaining", "1".parse()?);
+        assert!(!is_rate_limited(&headers));
+
+        headers.insert("x-ratelimit-remaining", "0".parse()?);
+        headers.insert("x-ratelimit-reset", "1722510000".parse()?);
+        assert!(is_rate_limited(&headers));
+        assert_eq!(
+            rate_limit_reset(&headers, now),
+            Some(now + TimeDelta::hours(1))
+        );
+
+        headers.insert("retry-after", "30".parse()?);
+        assert_eq!(
+            rate_limit_reset(&headers, now),
+            Some(now + TimeDelta::seconds(30))
+        );
+        Ok(())
     }
 }
@@ -0,0 +1,237 @@
+use crate::domain::external_apis::github::calls::GitHubOperation;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::num::NonZeroUsize;
+use std::sync::{Mutex, PoisonError};
+
+/// Requests remembered unless configured otherwise
+pub const DEFAULT_REQUEST_LOG_SIZE: usize = 1000;
+
+/// Longest error message kept per request (characters)
+const MAX_ERROR_CHARS: usize = 300;
+
+/// One attempt of a request to GitHub; each retry is an attempt of its own.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct GitHubRequest {
+    /// When the response headers, or the failure, came back
+    pub at: DateTime<Utc>,
+    pub operation: GitHubOperation,
+    /// Path template, e.g. `/repos/{owner}/{repo}/actions/runs`
+    pub route: String,
+    /// `None` when no response came back
+    pub status: Option<u16>,
+    #[serde(rename = "latencyMs")]
+    pub latency_ms: u64,
+    /// `x-ratelimit-remaining` of the response
+    #[serde(rename = "rateLimitRemaining")]
+    pub rate_limit_remaining: Option<u64>,
+    /// Why the attempt failed, redacted like the logs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The requests of one operation in the log.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct OperationSummary {
+    pub operation: GitHubOperation,
+    pub requests: u64,
+    /// Attempts without a successful response
+    pub failures: u64,
+    #[serde(rename = "meanLatencyMs")]
+    pub mean_latency_ms: u64,
+}
+
+/// The requests in the log grouped by operation, busiest first.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct RequestLogSummary {
+    /// When the oldest request in the log was sent; `None` while the log is empty
+    pub since: Option<DateTime<Utc>>,
+    pub requests: u64,
+    pub operations: Vec<OperationSummary>,
+}
+
+/// The latest requests sent to GitHub, kept to tell afterwards what used up the rate limit.
+///
+/// Holds at most `capacity` requests, dropping the oldest to make room.
+#[derive(Debug)]
+pub struct GitHubRequestLog {
+    capacity: NonZeroUsize,
+    requests: Mutex<VecDeque<GitHubRequest>>,
+}
+
+impl Default for GitHubRequestLog {
+    fn default() -> Self {
+        Self::new(NonZeroUsize::new(DEFAULT_REQUEST_LOG_SIZE).unwrap_or(NonZeroUsize::MIN))
+    }
+}
+
+impl GitHubRequestLog {
+    #[must_use]
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self {
+            capacity,
+            requests: Mutex::new(VecDeque::with_capacity(capacity.get())),
+        }
+    }
+
+    /// Most requests the log holds.
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.capacity.get()
+    }
+
+    /// Adds `request`, dropping the oldest one when full; long errors are truncated.
+    pub fn record(&self, mut request: GitHubRequest) {
+        if let Some(error) = &mut request.error
+            && let Some((index, _)) = error.char_indices().nth(MAX_ERROR_CHARS)
+        {
+            error.truncate(index);
+            error.push('…');
+        }
+        let mut requests = self.requests.lock().unwrap_or_else(PoisonError::into_inner);
+        if requests.len() == self.capacity.get() {
+            requests.pop_front();
+        }
+        requests.push_back(request);
+    }
+
+    /// The requests in the log, newest first, only those of `operation` if given.
+    #[must_use]
+    pub fn requests(&self, operation: Option<GitHubOperation>) -> Vec<GitHubRequest> {
+        self.requests
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .rev()
+            .filter(|request| operation.is_none_or(|operation| request.operation == operation))
+            .cloned()
+            .collect()
+    }
+
+    /// Every request in the log grouped by operation.
+    #[must_use]
+    pub fn summary(&self) -> RequestLogSummary {
+        let requests = self.requests.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut operations: Vec<OperationSummary> = GitHubOperation::ALL
+            .into_iter()
+            .filter_map(|operation| {
+                let (count, failures, latency_ms) = requests
+                    .iter()
+                    .filter(|request| request.operation == operation)
+                    .fold((0, 0, 0), |(count, failures, latency_ms), request| {
+                        let failed = request.error.is_some();
+                        (
+                            count + 1,
+                            failures + u64::from(failed),
+                            latency_ms + request.latency_ms,
+                        )
+                    });
+                (count > 0).then(|| OperationSummary {
+                    operation,
+                    requests: count,
+                    failures,
+                    mean_latency_ms: latency_ms / count,
+                })
+            })
+            .collect();
+        // Stable, so ties keep the order of `GitHubOperation::ALL`
+        operations.sort_by_key(|summary| std::cmp::Reverse(summary.requests));
+        RequestLogSummary {
+            since: requests.front().map(|request| request.at),
+            requests: requests.len() as u64,
+            operations,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::base_time;
+    use chrono::TimeDelta;
+
+    fn request(second: i64, operation: GitHubOperation, status: u16) -> GitHubRequest {
+        GitHubRequest {
+            at: base_time() + TimeDelta::seconds(second),
+            operation,
+            route: "/repos/{owner}/{repo}/actions/runs".to_string(),
+            status: Some(status),
+            latency_ms: 100,
+            rate_limit_remaining: Some(5000 - u64::try_from(second).unwrap_or_default()),
+            error: (status >= 400).then(|| format!("HTTP {status}")),
+        }
+    }
+
+    #[test]
+    fn test_oldest_requests_are_evicted_beyond_capacity() -> anyhow::Result<()> {
+        let log = GitHubRequestLog::new(NonZeroUsize::new(3).ok_or_else(|| anyhow::anyhow!("0"))?);
+
+        for second in 0..5 {
+            log.record(request(second, GitHubOperation::WorkflowRuns, 200));
+        }
+
+        let at: Vec<_> = log
+            .requests(None)
+            .iter()
+            .map(|request| request.at)
+            .collect();
+        assert_eq!(
+            at,
+            [4, 3, 2].map(|second| base_time() + TimeDelta::seconds(second))
+        );
+        assert_eq!(
+            log.summary().since,
+            Some(base_time() + TimeDelta::seconds(2))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_summary_groups_the_window_by_operation() -> anyhow::Result<()> {
+        let log = GitHubRequestLog::new(NonZeroUsize::new(4).ok_or_else(|| anyhow::anyhow!("0"))?);
+        // Evicted before the summary is taken
+        log.record(request(0, GitHubOperation::Runners, 200));
+        log.record(request(1, GitHubOperation::RateLimit, 200));
+        log.record(request(2, GitHubOperation::WorkflowRuns, 200));
+        log.record(request(3, GitHubOperation::WorkflowRuns, 403));
+        log.record(request(4, GitHubOperation::WorkflowRuns, 200));
+
+        let summary = log.summary();
+
+        assert_eq!(summary.requests, 4);
+        assert_eq!(
+            summary.operations,
+            [
+                OperationSummary {
+                    operation: GitHubOperation::WorkflowRuns,
+                    requests: 3,
+                    failures: 1,
+                    mean_latency_ms: 100,
+                },
+                OperationSummary {
+                    operation: GitHubOperation::RateLimit,
+                    requests: 1,
+                    failures: 0,
+                    mean_latency_ms: 100,
+                },
+            ]
+        );
+        assert_eq!(log.requests(Some(GitHubOperation::RateLimit)).len(), 1);
+        assert!(log.requests(Some(GitHubOperation::Runners)).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_long_errors_are_truncated() {
+        let log = GitHubRequestLog::default();
+
+        log.record(GitHubRequest {
+            error: Some("x".repeat(1000)),
+            ..request(0, GitHubOperation::WorkflowRuns, 500)
+        });
+
+        let error = log.requests(None)[0].error.clone().unwrap_or_default();
+        assert_eq!(error.chars().count(), MAX_ERROR_CHARS + 1);
+    }
+}
@@ -0,0 +1,206 @@
+use crate::domain::external_apis::github::calls::GitHubOperation;
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::num::NonZeroUsize;
+use std::sync::{Mutex, PoisonError};
+use tokio::sync::broadcast;
+
+/// Events buffered for each subscriber unless configured otherwise; a subscriber that falls
+/// further behind loses the oldest
+pub const DEFAULT_RETRY_EVENT_CAPACITY: usize = 64;
+
+/// How long a retry keeps the API degraded (seconds)
+const DEGRADED_WINDOW_SECONDS: i64 = 300;
+
+/// A failed attempt of a request to GitHub that is retried, or given up on.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct RetryEvent {
+    /// When the attempt failed
+    pub at: DateTime<Utc>,
+    pub operation: GitHubOperation,
+    /// The attempt that failed, from 1
+    pub attempt: u32,
+    /// `kind` of the error, e.g. `network`
+    pub category: &'static str,
+    /// Wait before the next attempt; `None` when the request is given up on
+    #[serde(rename = "nextWaitMs")]
+    pub next_wait_ms: Option<u64>,
+}
+
+/// How GitHub has been answering lately.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum GitHubApiHealth {
+    /// No request was retried lately
+    Healthy,
+    /// Requests were retried lately, but the last one given up on, if any, was followed by a
+    /// success
+    DegradedWithRetries,
+    /// A request ran out of retries and none succeeded since
+    Failing,
+}
+
+/// The rolling health of the GitHub API, as served by `/status`.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct GitHubApiHealthStatus {
+    pub state: GitHubApiHealth,
+    /// Retried or given up attempts in the last five minutes
+    pub retries: usize,
+    #[serde(rename = "lastRetry")]
+    pub last_retry: Option<RetryEvent>,
+}
+
+#[derive(Debug, Default)]
+struct HealthState {
+    /// When recent attempts failed, oldest first
+    retried_at: VecDeque<DateTime<Utc>>,
+    last_retry: Option<RetryEvent>,
+    /// Whether a request was given up on since the last success
+    failing: bool,
+}
+
+/// Retries of the GitHub API adapter as they happen, for clients watching a degraded GitHub.
+///
+/// Publishing never waits: subscribers that fall behind lose the oldest events, so telemetry
+/// cannot slow the adapter down.
+#[derive(Debug)]
+pub struct GitHubRetryEvents {
+    sender: broadcast::Sender<RetryEvent>,
+    health: Mutex<HealthState>,
+}
+
+impl Default for GitHubRetryEvents {
+    fn default() -> Self {
+        Self::new(NonZeroUsize::new(DEFAULT_RETRY_EVENT_CAPACITY).unwrap_or(NonZeroUsize::MIN))
+    }
+}
+
+impl GitHubRetryEvents {
+    #[must_use]
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self {
+            sender: broadcast::channel(capacity.get()).0,
+            health: Mutex::default(),
+        }
+    }
+
+    /// Events published from now on.
+    #[must_use]
+    pub fn subscribe(&self) -> broadcast::Receiver<RetryEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Sends `event` to the subscribers, if any, and counts it towards the health.
+    pub fn publish(&self, event: RetryEvent) {
+        {
+            let mut health = self.health.lock().unwrap_or_else(PoisonError::into_inner);
+            health.retried_at.push_back(event.at);
+            Self::expire(&mut health, event.at);
+            health.failing = event.next_wait_ms.is_none();
+            health.last_retry = Some(event.clone());
+        }
+        // Fails only without subscribers
+        let _ = self.sender.send(event);
+    }
+
+    /// Records a request that succeeded, ending a failing state.
+    pub fn succeeded(&self) {
+        self.health
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .failing = false;
+    }
+
+    /// The health as of `now`.
+    #[must_use]
+    pub fn health(&self, now: DateTime<Utc>) -> GitHubApiHealthStatus {
+        let mut health = self.health.lock().unwrap_or_else(PoisonError::into_inner);
+        Self::expire(&mut health, now);
+        let state = if health.failing {
+            GitHubApiHealth::Failing
+        } else if health.retried_at.is_empty() {
+            GitHubApiHealth::Healthy
+        } else {
+            GitHubApiHealth::DegradedWithRetries
+        };
+        GitHubApiHealthStatus {
+            state,
+            retries: health.retried_at.len(),
+            last_retry: health.last_retry.clone(),
+        }
+    }
+
+    /// Forgets the retries out of the window ending at `now`.
+    fn expire(health: &mut HealthState, now: DateTime<Utc>) {
+        let window_start = now - TimeDelta::seconds(DEGRADED_WINDOW_SECONDS);
+        while health
+            .retried_at
+            .front()
+            .is_some_and(|retried_at| *retried_at < window_start)
+        {
+            health.retried_at.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(at: DateTime<Utc>, next_wait_ms: Option<u64>) -> RetryEvent {
+        RetryEvent {
+            at,
+            operation: GitHubOperation::WorkflowRuns,
+            attempt: 1,
+            category: "network",
+            next_wait_ms,
+        }
+    }
+
+    #[test]
+    fn test_health_follows_retries_and_successes() -> anyhow::Result<()> {
+        let events = GitHubRetryEvents::default();
+        let now: DateTime<Utc> = DateTime::parse_from_rfc3339("2024-08-01T10:00:00Z")?.into();
+        assert_eq!(events.health(now).state, GitHubApiHealth::Healthy);
+
+        events.publish(event(now, Some(1000)));
+        assert_eq!(
+            events.health(now).state,
+            GitHubApiHealth::DegradedWithRetries
+        );
+        events.publish(event(now, None));
+        assert_eq!(events.health(now).state, GitHubApiHealth::Failing);
+        assert_eq!(events.health(now).retries, 2);
+
+        events.succeeded();
+        assert_eq!(
+            events.health(now).state,
+            GitHubApiHealth::DegradedWithRetries
+        );
+        let later = events.health(now + TimeDelta::minutes(6));
+        assert_eq!(later.state, GitHubApiHealth::Healthy);
+        assert_eq!(later.last_retry.map(|retry| retry.at), Some(now));
+        Ok(())
+    }
+
+    #[test]
+    fn test_events_are_dropped_rather_than_waited_for() -> anyhow::Result<()> {
+        let events = GitHubRetryEvents::new(NonZeroUsize::new(4).unwrap_or(NonZeroUsize::MIN));
+        let mut slow = events.subscribe();
+        let now = Utc::now();
+
+        // Nobody reads while 100 events are published; none of them waits
+        for _ in 0..100 {
+            events.publish(event(now, Some(1000)));
+        }
+
+        assert!(matches!(
+            slow.try_recv(),
+            Err(broadcast::error::TryRecvError::Lagged(96))
+        ));
+        assert_eq!(slow.try_recv()?, event(now, Some(1000)));
+        assert_eq!(events.health(now).retries, 100);
+        Ok(())
+    }
+}
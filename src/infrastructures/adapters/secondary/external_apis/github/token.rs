@@ -0,0 +1,132 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// Interval between two reads of a token file (seconds)
+pub const TOKEN_FILE_RELOAD_SECONDS: u64 = 60;
+
+/// Where [`GitHubApiAdapter`](super::GitHubApiAdapter) gets the token of each request from.
+pub trait TokenProvider: Send + Sync {
+    /// The token to send now.
+    fn token(&self) -> String;
+
+    /// Looks for a new token after GitHub rejected the current one; `true` when it changed,
+    /// so the request is worth retrying.
+    fn reload(&self) -> bool {
+        false
+    }
+}
+
+/// A token that never changes.
+impl TokenProvider for String {
+    fn token(&self) -> String {
+        self.clone()
+    }
+}
+
+/// A token read from a file, e.g. a mounted Docker or Kubernetes secret, and read again
+/// whenever it may have been rotated.
+#[derive(Debug)]
+pub struct FileToken {
+    path: PathBuf,
+    token: RwLock<String>,
+}
+
+impl FileToken {
+    /// Reads the token at `path`, trimming surrounding whitespace and newlines.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or holds no token.
+    pub fn open(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let token = read_token(&path)?;
+        Ok(Self {
+            path,
+            token: RwLock::new(token),
+        })
+    }
+
+    /// Reads the file again right away and then every `interval`, until aborted.
+    pub fn spawn(self: &Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        let token = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            loop {
+                interval.tick().await;
+                token.reload();
+            }
+        })
+    }
+}
+
+impl TokenProvider for FileToken {
+    fn token(&self) -> String {
+        self.token
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Keeps the current token when the file cannot be read, e.g. midway through a rotation.
+    fn reload(&self) -> bool {
+        let token = match read_token(&self.path) {
+            Ok(token) => token,
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to reload the GitHub token from {}: {}",
+                    self.path.display(),
+                    e
+                );
+                return false;
+            }
+        };
+        let mut current = self.token.write().unwrap_or_else(PoisonError::into_inner);
+        if *current == token {
+            return false;
+        }
+        *current = token;
+        tracing::info!("Reloaded the GitHub token from {}", self.path.display());
+        true
+    }
+}
+
+/// The trimmed content of a token file.
+fn read_token(path: &Path) -> io::Result<String> {
+    let token = std::fs::read_to_string(path)?.trim().to_string();
+    if token.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "the file holds no token",
+        ));
+    }
+    Ok(token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_token_is_trimmed_and_reloaded() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("token");
+        std::fs::write(&path, "  first\n")?;
+        let token = FileToken::open(&path)?;
+
+        assert_eq!(token.token(), "first");
+        assert!(!token.reload());
+
+        std::fs::write(&path, "second\r\n")?;
+        assert!(token.reload());
+        assert_eq!(token.token(), "second");
+
+        std::fs::write(&path, "\n")?;
+        assert!(!token.reload());
+        assert_eq!(token.token(), "second");
+        assert!(FileToken::open(&path).is_err());
+        Ok(())
+    }
+}
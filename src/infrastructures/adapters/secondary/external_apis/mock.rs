@@ -0,0 +1,844 @@
+use crate::domain::external_apis::github::{GitHubApi, GitHubApiError, RateLimit, Repository};
+use crate::domain::models::actor::RunActor;
+use crate::domain::models::commit_status::{CommitStatus, CommitStatusState};
+use crate::domain::models::deployment::{Deployment, DeploymentStatus, DeploymentStatusState};
+use crate::domain::models::dispatch::{
+    DispatchInput, DispatchInputType, DispatchValue, WorkflowDispatchInputs,
+};
+use crate::domain::models::ids::{DeploymentId, JobId, RepoFullName, RunId, RunnerId, WorkflowId};
+use crate::domain::models::job::{Job, Step};
+use crate::domain::models::run::WorkflowRun;
+use crate::domain::models::runner::{RunnerStatus, SelfHostedRunner};
+use crate::domain::models::status::{RunConclusion, RunStatus};
+use crate::domain::models::timing::{BillableTime, JobTiming, RunTiming, RunnerOs};
+use crate::domain::models::workflow::{Workflow, WorkflowState};
+use crate::domain::models::workflow_definition::WorkflowDefinition;
+use async_trait::async_trait;
+use chrono::{DateTime, TimeDelta, Utc};
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+
+/// Owner of every repository served by the mock
+const OWNER: &str = "demo-org";
+
+/// Repositories served by the mock
+const REPOSITORIES: [&str; 3] = ["web", "api", "infra"];
+
+/// Workflows defined in every repository
+const WORKFLOWS: [&str; 2] = ["CI", "Deploy"];
+
+const TITLES: [&str; 6] = [
+    "Fix flaky login test",
+    "Bump dependencies",
+    "Add dark mode toggle",
+    "Refactor payment service",
+    "Update README",
+    "Speed up Docker build",
+];
+const BRANCHES: [&str; 4] = ["main", "feature/dark-mode", "fix/login", "renovate/deps"];
+const ACTORS: [&str; 3] = ["octocat", "hubot", "monalisa"];
+
+/// Runs kept per repository; older ones drop out like on a busy repository
+const MAX_RUNS_PER_REPOSITORY: usize = 20;
+
+/// Default chance, in percent, that a call to list runs fails
+const DEFAULT_ERROR_RATE_PERCENT: u64 = 4;
+
+/// Rate limit the mock pretends to have
+const RATE_LIMIT: u64 = 5_000;
+
+/// The only repository with self-hosted runners
+const SELF_HOSTED_REPOSITORY: &str = "infra";
+
+/// Self-hosted runners of that repository; the last one is offline every third listing
+const SELF_HOSTED_RUNNERS: u64 = 3;
+
+/// Commit status contexts of the repository with self-hosted runners, whose builds still
+/// also run on Jenkins
+const JENKINS_CONTEXTS: [&str; 2] = ["continuous-integration/jenkins", "jenkins/integration"];
+
+/// Environments every repository deploys to, most recently deployed first
+const ENVIRONMENTS: [&str; 2] = ["staging", "production"];
+
+/// `GitHubApi` generating evolving fake runs, for demos and frontend work without a token.
+///
+/// Each call listing a repository's runs advances it by one step: queued runs start,
+/// running ones finish after a few steps, and new runs are queued now and then. The same
+/// seed always produces the same runs and errors.
+pub struct MockGitHubApi {
+    repositories: Vec<RepoFullName>,
+    error_rate_percent: u64,
+    state: Mutex<MockState>,
+}
+
+struct MockState {
+    rng: SplitMix64,
+    next_run_id: u64,
+    calls: u64,
+    /// Times the self-hosted runners were listed
+    runner_listings: u64,
+    runs: HashMap<RepoFullName, Vec<WorkflowRun>>,
+    /// Steps left before each running run completes
+    remaining_steps: HashMap<RunId, u64>,
+}
+
+/// Small, seedable generator; demos only need reproducibility, not statistical quality.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform value in `0..n`.
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    fn pick<'a>(&mut self, choices: &[&'a str]) -> &'a str {
+        choices[usize::try_from(self.below(choices.len() as u64)).unwrap_or_default()]
+    }
+}
+
+fn actor(login: &str) -> RunActor {
+    RunActor {
+        login: login.to_string(),
+        avatar_url: format!("https://avatars.githubusercontent.com/{login}"),
+        html_url: format!("https://github.com/{login}"),
+    }
+}
+
+impl MockGitHubApi {
+    /// Starts every repository with a few completed runs, drawn from `seed`.
+    #[must_use]
+    pub fn new(seed: u64) -> Self {
+        Self::with_start(seed, Utc::now())
+    }
+
+    /// Like [`MockGitHubApi::new`], with the history ending at `now`.
+    #[must_use]
+    pub fn with_start(seed: u64, now: DateTime<Utc>) -> Self {
+        let mut state = MockState {
+            rng: SplitMix64(seed),
+            next_run_id: 1,
+            calls: 0,
+            runner_listings: 0,
+            runs: HashMap::new(),
+            remaining_steps: HashMap::new(),
+        };
+        let repositories: Vec<RepoFullName> = REPOSITORIES
+            .iter()
+            .filter_map(|name| RepoFullName::new(OWNER, *name).ok())
+            .collect();
+        for repo in &repositories {
+            for hours_ago in (1..=3).rev() {
+                let created_at = now - TimeDelta::hours(hours_ago);
+                let mut run = state.new_run(repo, created_at);
+                let conclusion = state.conclusion();
+                run.status = RunStatus::Completed;
+                run.conclusion = Some(conclusion);
+                run.updated_at = created_at + TimeDelta::minutes(4);
+                state.runs.entry(repo.clone()).or_default().insert(0, run);
+            }
+        }
+        Self {
+            repositories,
+            error_rate_percent: DEFAULT_ERROR_RATE_PERCENT,
+            state: Mutex::new(state),
+        }
+    }
+
+    /// Chance, in percent, that listing runs fails with a network error.
+    #[must_use]
+    pub fn with_error_rate_percent(mut self, error_rate_percent: u64) -> Self {
+        self.error_rate_percent = error_rate_percent;
+        self
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl MockState {
+    fn new_run(&mut self, repo: &RepoFullName, created_at: DateTime<Utc>) -> WorkflowRun {
+        let id = self.next_run_id;
+        self.next_run_id += 1;
+        let workflow = self.below(WORKFLOWS.len() as u64);
+        let actor_login = self.rng.pick(&ACTORS);
+        WorkflowRun {
+            repository_name: repo.clone(),
+            id: RunId(id),
+            run_number: id,
+            workflow_id: WorkflowId(workflow + 1),
+            workflow_name: WORKFLOWS[usize::try_from(workflow).unwrap_or_default()].to_string(),
+            display_title: self.rng.pick(&TITLES).to_string(),
+            full_display_title: None,
+            event: "push".to_string(),
+            head_branch: Some(self.rng.pick(&BRANCHES).to_string()),
+            head_sha: format!("{:040x}", u128::from(self.rng.next())),
+            actor: actor(actor_login),
+            triggering_actor: Some(actor(actor_login)),
+            status: RunStatus::Queued,
+            conclusion: None,
+            created_at,
+            updated_at: created_at,
+            html_url: format!("https://github.com/{repo}/actions/runs/{id}"),
+            labels: Vec::new(),
+            repository_active: true,
+            is_required_check: None,
+            failed_jobs: Vec::new(),
+            failed_job_url: None,
+        }
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.rng.below(n)
+    }
+
+    /// Mostly success, sometimes failure, rarely cancelled.
+    fn conclusion(&mut self) -> RunConclusion {
+        match self.below(10) {
+            0..=6 => RunConclusion::Success,
+            7 | 8 => RunConclusion::Failure,
+            _ => RunConclusion::Cancelled,
+        }
+    }
+
+    /// Moves every run of `repo` one step forward and sometimes queues a new one.
+    fn advance(&mut self, repo: &RepoFullName, now: DateTime<Utc>) {
+        let mut runs = self.runs.remove(repo).unwrap_or_default();
+        for run in &mut runs {
+            match run.status {
+                RunStatus::Queued => {
+                    run.status = RunStatus::InProgress;
+                    run.updated_at = now;
+                    let steps = 1 + self.below(3);
+                    self.remaining_steps.insert(run.id, steps);
+                }
+                RunStatus::InProgress => {
+                    let steps = self.remaining_steps.entry(run.id).or_default();
+                    *steps = steps.saturating_sub(1);
+                    if *steps == 0 {
+                        self.remaining_steps.remove(&run.id);
+                        run.status = RunStatus::Completed;
+                        run.conclusion = Some(self.conclusion());
+                        run.updated_at = now;
+                    }
+                }
+                _ => {}
+            }
+        }
+        if self.below(3) == 0 {
+            let run = self.new_run(repo, now);
+            runs.insert(0, run);
+        }
+        runs.truncate(MAX_RUNS_PER_REPOSITORY);
+        self.runs.insert(repo.clone(), runs);
+    }
+}
+
+/// A generated repository as listed and fetched.
+fn repository(repo: &RepoFullName) -> Repository {
+    Repository::from_full_name(repo)
+        .with_owner_avatar_url(Some(format!(
+            "https://avatars.githubusercontent.com/{OWNER}"
+        )))
+        .with_default_branch(Some("main".to_string()))
+}
+
+#[async_trait]
+impl GitHubApi for MockGitHubApi {
+    async fn fetch_repositories(&self, count: u8) -> Result<Vec<Repository>, GitHubApiError> {
+        self.state().calls += 1;
+        Ok(self
+            .repositories
+            .iter()
+            .take(usize::from(count))
+            .map(repository)
+            .collect())
+    }
+
+    async fn fetch_repository(&self, repo: &RepoFullName) -> Result<Repository, GitHubApiError> {
+        self.state().calls += 1;
+        if !self.repositories.contains(repo) {
+            return Err(GitHubApiError::NotFound {
+                resource: format!("repository {repo}"),
+            });
+        }
+        Ok(repository(repo))
+    }
+
+    async fn fetch_workflow_runs(
+        &self,
+        repo: &RepoFullName,
+        count: u8,
+    ) -> Result<Vec<WorkflowRun>, GitHubApiError> {
+        let mut state = self.state();
+        state.calls += 1;
+        if state.below(100) < self.error_rate_percent {
+            return Err(GitHubApiError::Network {
+                resource: format!("workflow runs for {repo}"),
+                message: "Simulated outage of the mock GitHub API".to_string(),
+            });
+        }
+        if !state.runs.contains_key(repo) {
+            return Err(GitHubApiError::NotFound {
+                resource: format!("workflow runs for {repo}"),
+            });
+        }
+        state.advance(repo, Utc::now());
+        Ok(state.runs[repo]
+            .iter()
+            .take(usize::from(count))
+            .cloned()
+            .collect())
+    }
+
+    /// Advances the runs like [`Self::fetch_workflow_runs`], which it stands in for when only
+    /// one workflow is shown.
+    async fn fetch_workflow_runs_of_workflow(
+        &self,
+        repo: &RepoFullName,
+        workflow_id: WorkflowId,
+        count: u8,
+    ) -> Result<Vec<WorkflowRun>, GitHubApiError> {
+        let mut state = self.state();
+        state.calls += 1;
+        if state.below(100) < self.error_rate_percent {
+            return Err(GitHubApiError::Network {
+                resource: format!("workflow {workflow_id} runs for {repo}"),
+                message: "Simulated outage of the mock GitHub API".to_string(),
+            });
+        }
+        if !state.runs.contains_key(repo) {
+            return Err(GitHubApiError::NotFound {
+                resource: format!("workflow {workflow_id} runs for {repo}"),
+            });
+        }
+        state.advance(repo, Utc::now());
+        Ok(state.runs[repo]
+            .iter()
+            .filter(|run| run.workflow_id == workflow_id)
+            .take(usize::from(count))
+            .cloned()
+            .collect())
+    }
+
+    /// Filters the runs generated so far without advancing them, as one poll lists both.
+    async fn fetch_workflow_runs_by_status(
+        &self,
+        repo: &RepoFullName,
+        status: RunStatus,
+        count: u8,
+    ) -> Result<Vec<WorkflowRun>, GitHubApiError> {
+        let mut state = self.state();
+        state.calls += 1;
+        let Some(runs) = state.runs.get(repo) else {
+            return Err(GitHubApiError::NotFound {
+                resource: format!("workflow runs for {repo}"),
+            });
+        };
+        Ok(runs
+            .iter()
+            .filter(|run| run.status == status)
+            .take(usize::from(count))
+            .cloned()
+            .collect())
+    }
+
+    /// Filters the runs generated so far without advancing them, like runs by status.
+    async fn fetch_workflow_runs_on_branch(
+        &self,
+        repo: &RepoFullName,
+        branch: &str,
+        count: u8,
+    ) -> Result<Vec<WorkflowRun>, GitHubApiError> {
+        let mut state = self.state();
+        state.calls += 1;
+        let Some(runs) = state.runs.get(repo) else {
+            return Err(GitHubApiError::NotFound {
+                resource: format!("workflow runs for {repo}"),
+            });
+        };
+        Ok(runs
+            .iter()
+            .filter(|run| run.head_branch.as_deref() == Some(branch))
+            .take(usize::from(count))
+            .cloned()
+            .collect())
+    }
+
+    /// Finds the run among those generated so far without advancing them, like runs by
+    /// status.
+    async fn fetch_workflow_run(
+        &self,
+        repo: &RepoFullName,
+        run_id: RunId,
+    ) -> Result<WorkflowRun, GitHubApiError> {
+        let mut state = self.state();
+        state.calls += 1;
+        state
+            .runs
+            .get(repo)
+            .and_then(|runs| runs.iter().find(|run| run.id == run_id))
+            .cloned()
+            .ok_or_else(|| GitHubApiError::NotFound {
+                resource: format!("run {run_id} of {repo}"),
+            })
+    }
+
+    async fn fetch_workflow_jobs(
+        &self,
+        repo: &RepoFullName,
+        run_id: RunId,
+    ) -> Result<Vec<Job>, GitHubApiError> {
+        let mut state = self.state();
+        state.calls += 1;
+        let run = state
+            .runs
+            .get(repo)
+            .and_then(|runs| runs.iter().find(|run| run.id == run_id))
+            .ok_or_else(|| GitHubApiError::NotFound {
+                resource: format!("jobs for {repo} run {run_id}"),
+            })?;
+        Ok(mock_jobs(run))
+    }
+
+    async fn fetch_workflow_job(
+        &self,
+        repo: &RepoFullName,
+        job_id: JobId,
+    ) -> Result<Job, GitHubApiError> {
+        let mut state = self.state();
+        state.calls += 1;
+        mock_job(&state, repo, job_id)
+    }
+
+    async fn fetch_job_logs(
+        &self,
+        repo: &RepoFullName,
+        job_id: JobId,
+    ) -> Result<String, GitHubApiError> {
+        let mut state = self.state();
+        state.calls += 1;
+        let job = mock_job(&state, repo, job_id)?;
+        let not_found = || GitHubApiError::NotFound {
+            resource: format!("logs of job {job_id} of {repo}"),
+        };
+        let started_at = job.started_at.ok_or_else(not_found)?;
+        let mut lines = vec![format!(
+            "{}Z Starting {}",
+            started_at.format("%FT%T"),
+            job.name
+        )];
+        lines.extend(
+            job.steps
+                .iter()
+                .map(|step| format!("##[group]Run {}\n##[endgroup]", step.name)),
+        );
+        if let (Some(completed_at), Some(conclusion)) = (job.completed_at, job.conclusion) {
+            lines.push(format!(
+                "{}Z Finished: {conclusion:?}",
+                completed_at.format("%FT%T")
+            ));
+        }
+        lines.push(String::new());
+        Ok(lines.join("\n"))
+    }
+
+    async fn fetch_workflows(&self, repo: &RepoFullName) -> Result<Vec<Workflow>, GitHubApiError> {
+        self.state().calls += 1;
+        Ok(WORKFLOWS
+            .iter()
+            .zip(1..)
+            .map(|(name, id)| {
+                let file = name.to_lowercase();
+                Workflow {
+                    id: WorkflowId(id),
+                    name: (*name).to_string(),
+                    path: format!(".github/workflows/{file}.yml"),
+                    state: WorkflowState::Active,
+                    html_url: format!(
+                        "https://github.com/{repo}/blob/main/.github/workflows/{file}.yml"
+                    ),
+                    badge_url: format!(
+                        "https://github.com/{repo}/actions/workflows/{file}.yml/badge.svg"
+                    ),
+                }
+            })
+            .collect())
+    }
+
+    async fn fetch_run_timing(
+        &self,
+        repo: &RepoFullName,
+        run_id: RunId,
+    ) -> Result<RunTiming, GitHubApiError> {
+        let mut state = self.state();
+        state.calls += 1;
+        let run = state
+            .runs
+            .get(repo)
+            .and_then(|runs| runs.iter().find(|run| run.id == run_id))
+            .ok_or_else(|| GitHubApiError::NotFound {
+                resource: format!("timing of {repo} run {run_id}"),
+            })?;
+        Ok(mock_timing(run))
+    }
+
+    async fn fetch_runners(
+        &self,
+        repo: &RepoFullName,
+    ) -> Result<Vec<SelfHostedRunner>, GitHubApiError> {
+        let mut state = self.state();
+        state.calls += 1;
+        if repo.name() != SELF_HOSTED_REPOSITORY {
+            return Ok(Vec::new());
+        }
+        state.runner_listings += 1;
+        // Runners pick up the running runs in order
+        let running = state.runs.get(repo).map_or(0, |runs| {
+            runs.iter()
+                .filter(|run| run.status == RunStatus::InProgress)
+                .count() as u64
+        });
+        let flapping = state.runner_listings.is_multiple_of(3);
+        Ok((1..=SELF_HOSTED_RUNNERS)
+            .map(|index| {
+                let online = !(flapping && index == SELF_HOSTED_RUNNERS);
+                SelfHostedRunner {
+                    id: RunnerId(index),
+                    name: format!("{}-runner-{index}", repo.name()),
+                    os: "Linux".to_string(),
+                    status: if online {
+                        RunnerStatus::Online
+                    } else {
+                        RunnerStatus::Offline
+                    },
+                    busy: online && index <= running,
+                    labels: vec!["self-hosted".to_string(), "linux".to_string()],
+                }
+            })
+            .collect())
+    }
+
+    async fn fetch_deployments(
+        &self,
+        repo: &RepoFullName,
+        environment: Option<&str>,
+    ) -> Result<Vec<Deployment>, GitHubApiError> {
+        self.state().calls += 1;
+        let repository = REPOSITORIES
+            .iter()
+            .position(|name| *name == repo.name())
+            .map_or(0, |index| index as u64);
+        let now = Utc::now();
+        Ok(ENVIRONMENTS
+            .iter()
+            .zip(1..)
+            .filter(|(name, _)| environment.is_none_or(|environment| environment == **name))
+            .map(|(name, index)| {
+                let id = repository * 10 + index;
+                let deployed_at = now - TimeDelta::hours(i64::try_from(index).unwrap_or(1));
+                Deployment {
+                    id: DeploymentId(id),
+                    environment: (*name).to_string(),
+                    git_ref: "main".to_string(),
+                    sha: format!("{id:040x}"),
+                    creator: Some(actor(ACTORS[0])),
+                    created_at: deployed_at,
+                    updated_at: deployed_at,
+                }
+            })
+            .collect())
+    }
+
+    async fn fetch_deployment_statuses(
+        &self,
+        _repo: &RepoFullName,
+        deployment_id: DeploymentId,
+    ) -> Result<Vec<DeploymentStatus>, GitHubApiError> {
+        self.state().calls += 1;
+        let status = |state, minutes_ago| DeploymentStatus {
+            state,
+            target_url: None,
+            created_at: Utc::now() - TimeDelta::minutes(minutes_ago),
+        };
+        // Staging is being deployed; production succeeded, then was marked inactive
+        Ok(if deployment_id.0 % 10 == 1 {
+            vec![
+                status(DeploymentStatusState::InProgress, 50),
+                status(DeploymentStatusState::Queued, 55),
+            ]
+        } else {
+            vec![
+                status(DeploymentStatusState::Inactive, 10),
+                status(DeploymentStatusState::Success, 100),
+            ]
+        })
+    }
+
+    async fn fetch_combined_status(
+        &self,
+        repo: &RepoFullName,
+        sha: &str,
+    ) -> Result<Vec<CommitStatus>, GitHubApiError> {
+        self.state().calls += 1;
+        if repo.name() != SELF_HOSTED_REPOSITORY {
+            return Ok(Vec::new());
+        }
+        // Jenkins builds every commit; the outcome follows from the SHA so it stays put
+        let seed = sha.bytes().map(u64::from).sum::<u64>();
+        let status = |context: &str, state, minutes_ago| CommitStatus {
+            context: context.to_string(),
+            state,
+            description: Some(format!("Build #{}", seed % 1000)),
+            target_url: Some(format!(
+                "https://jenkins.example.com/job/{}/{}",
+                repo.name(),
+                seed % 1000
+            )),
+            created_at: Utc::now() - TimeDelta::minutes(minutes_ago),
+            updated_at: Utc::now() - TimeDelta::minutes(minutes_ago),
+        };
+        Ok(vec![
+            status(
+                JENKINS_CONTEXTS[0],
+                match seed % 3 {
+                    0 => CommitStatusState::Pending,
+                    1 => CommitStatusState::Success,
+                    _ => CommitStatusState::Failure,
+                },
+                2,
+            ),
+            status(JENKINS_CONTEXTS[1], CommitStatusState::Success, 5),
+        ])
+    }
+
+    async fn fetch_branch_protection_required_checks(
+        &self,
+        repo: &RepoFullName,
+        branch: &str,
+    ) -> Result<Vec<String>, GitHubApiError> {
+        self.state().calls += 1;
+        // Merges into main need CI to pass; deploys are optional
+        if branch == "main" {
+            Ok(vec!["CI".to_string()])
+        } else {
+            Err(GitHubApiError::NotFound {
+                resource: format!("required checks of {repo} branch {branch}"),
+            })
+        }
+    }
+
+    async fn fetch_workflow_definition(
+        &self,
+        repo: &RepoFullName,
+        path: &str,
+        _git_ref: Option<&str>,
+    ) -> Result<WorkflowDefinition, GitHubApiError> {
+        self.state().calls += 1;
+        match path {
+            // CI also runs nightly
+            ".github/workflows/ci.yml" => Ok(WorkflowDefinition {
+                dispatch: WorkflowDispatchInputs::default(),
+                schedules: vec!["0 3 * * *".to_string()],
+            }),
+            // Deploys are started by hand
+            ".github/workflows/deploy.yml" => Ok(WorkflowDefinition {
+                dispatch: WorkflowDispatchInputs {
+                    dispatchable: true,
+                    inputs: vec![DispatchInput {
+                        name: "environment".to_string(),
+                        description: Some("Where to deploy".to_string()),
+                        input_type: DispatchInputType::Choice,
+                        required: true,
+                        default: Some(DispatchValue::String("staging".to_string())),
+                        options: vec!["staging".to_string(), "production".to_string()],
+                    }],
+                },
+                schedules: Vec::new(),
+            }),
+            _ => Err(GitHubApiError::NotFound {
+                resource: format!("{path} of {repo}"),
+            }),
+        }
+    }
+
+    async fn fetch_rate_limit(&self) -> Result<RateLimit, GitHubApiError> {
+        let used = self.state().calls.min(RATE_LIMIT);
+        Ok(RateLimit {
+            limit: RATE_LIMIT,
+            remaining: RATE_LIMIT - used,
+            used,
+            reset: Utc::now() + TimeDelta::hours(1),
+        })
+    }
+}
+
+/// A build job that passes, then a test job ending like `run`.
+fn mock_jobs(run: &WorkflowRun) -> Vec<Job> {
+    let started = (run.status != RunStatus::Queued).then_some(run.created_at);
+    let job = |index: u64, name: &str, status, conclusion| {
+        let completed_at = (status == RunStatus::Completed).then_some(run.updated_at);
+        Job {
+            id: JobId(run.id.0 * 10 + index),
+            run_id: run.id,
+            name: name.to_string(),
+            status,
+            conclusion,
+            started_at: started,
+            completed_at,
+            runner_name: started.map(|_| format!("mock-runner-{index}")),
+            labels: vec!["ubuntu-latest".to_string()],
+            html_url: format!("{}/job/{}", run.html_url, run.id.0 * 10 + index),
+            steps: vec![Step {
+                number: 1,
+                name: format!("Run {name}"),
+                status,
+                conclusion,
+                started_at: started,
+                completed_at,
+            }],
+        }
+    };
+    let build = match run.status {
+        RunStatus::Queued => job(1, "build", RunStatus::Queued, None),
+        _ => job(
+            1,
+            "build",
+            RunStatus::Completed,
+            Some(RunConclusion::Success),
+        ),
+    };
+    vec![build, job(2, "test", run.status, run.conclusion)]
+}
+
+/// The job of [`mock_jobs`] with id `job_id`, as of the current step of its run.
+fn mock_job(state: &MockState, repo: &RepoFullName, job_id: JobId) -> Result<Job, GitHubApiError> {
+    state
+        .runs
+        .get(repo)
+        .and_then(|runs| runs.iter().find(|run| run.id.0 == job_id.0 / 10))
+        .and_then(|run| mock_jobs(run).into_iter().find(|job| job.id == job_id))
+        .ok_or_else(|| GitHubApiError::NotFound {
+            resource: format!("job {job_id} of {repo}"),
+        })
+}
+
+/// Timing of the jobs of [`mock_jobs`], all of which run on Ubuntu.
+fn mock_timing(run: &WorkflowRun) -> RunTiming {
+    let job_runs: Vec<_> = mock_jobs(run)
+        .iter()
+        .filter_map(|job| {
+            Some(JobTiming {
+                job_id: job.id,
+                duration_ms: u64::try_from(job.duration()?.as_millis()).unwrap_or(u64::MAX),
+            })
+        })
+        .collect();
+    RunTiming {
+        billable: [(
+            RunnerOs::Ubuntu,
+            BillableTime {
+                total_ms: job_runs.iter().map(|job_run| job_run.duration_ms).sum(),
+                job_runs,
+            },
+        )]
+        .into(),
+        run_duration_ms: run.duration_seconds().map(|seconds| seconds * 1000),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{base_time, repo_full_name};
+
+    /// Lists the runs of `demo-org/web` `iterations` times, without simulated outages.
+    async fn snapshots(seed: u64, iterations: usize) -> anyhow::Result<Vec<Vec<WorkflowRun>>> {
+        let api = MockGitHubApi::with_start(seed, base_time()).with_error_rate_percent(0);
+        let mut snapshots = Vec::new();
+        for _ in 0..iterations {
+            snapshots.push(
+                api.fetch_workflow_runs(&repo_full_name("demo-org/web"), 100)
+                    .await?,
+            );
+        }
+        Ok(snapshots)
+    }
+
+    fn rank(run: &WorkflowRun) -> u8 {
+        match run.status {
+            RunStatus::Queued => 0,
+            RunStatus::InProgress => 1,
+            _ => 2,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_runs_progress_from_queued_to_a_conclusion() -> anyhow::Result<()> {
+        let snapshots = snapshots(7, 30).await?;
+
+        let mut seen: HashMap<RunId, Vec<RunStatus>> = HashMap::new();
+        let mut last_rank: HashMap<RunId, u8> = HashMap::new();
+        for run in snapshots.iter().flatten() {
+            let previous = last_rank.insert(run.id, rank(run)).unwrap_or_default();
+            assert!(rank(run) >= previous, "run {} went backwards", run.id);
+            assert_eq!(run.status == RunStatus::Completed, run.conclusion.is_some());
+            let statuses = seen.entry(run.id).or_default();
+            if statuses.last() != Some(&run.status) {
+                statuses.push(run.status);
+            }
+        }
+        // Runs queued during the demo go through every status
+        assert!(seen.values().any(|statuses| {
+            statuses.as_slice()
+                == [
+                    RunStatus::Queued,
+                    RunStatus::InProgress,
+                    RunStatus::Completed,
+                ]
+        }));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_same_seed_replays_the_same_demo() -> anyhow::Result<()> {
+        let outline = |snapshots: Vec<Vec<WorkflowRun>>| {
+            snapshots
+                .into_iter()
+                .flatten()
+                .map(|run| (run.id, run.status, run.conclusion, run.display_title))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            outline(snapshots(1, 10).await?),
+            outline(snapshots(1, 10).await?)
+        );
+        assert_ne!(
+            outline(snapshots(1, 10).await?),
+            outline(snapshots(2, 10).await?)
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_injected_errors_fail_run_listing() -> anyhow::Result<()> {
+        let api = MockGitHubApi::new(0).with_error_rate_percent(100);
+
+        let result = api
+            .fetch_workflow_runs(&repo_full_name("demo-org/web"), 5)
+            .await;
+
+        assert!(matches!(result, Err(GitHubApiError::Network { .. })));
+        assert_eq!(api.fetch_repositories(5).await?.len(), REPOSITORIES.len());
+        Ok(())
+    }
+}
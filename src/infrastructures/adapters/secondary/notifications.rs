@@ -0,0 +1,56 @@
+pub mod discord;
+pub mod slack;
+pub mod web_push;
+pub mod webhook;
+
+pub use discord::DiscordWebhook;
+pub use slack::SlackWebhook;
+pub use web_push::{InvalidVapidKey, SubscribeError, VapidKeys, WebPushNotifier};
+pub use webhook::{BodyTemplate, GenericWebhook, InvalidWebhook};
+
+use crate::domain::external_apis::notifications::NotificationError;
+use crate::domain::models::transition::RunTransition;
+use reqwest::{Client, RequestBuilder};
+use std::time::Duration;
+
+/// Time allowed for one webhook delivery before it counts as unreachable (seconds)
+const REQUEST_TIMEOUT_SECONDS: u64 = 10;
+
+/// A client that gives up on slow webhooks instead of piling up deliveries.
+fn client() -> Client {
+    Client::builder()
+        .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECONDS))
+        .build()
+        .unwrap_or_default()
+}
+
+/// One line naming the run and how it ended, e.g. `CI #12 failure in owner/repo`.
+fn headline(transition: &RunTransition) -> String {
+    let run = &transition.run;
+    format!(
+        "{} #{} {} in {}",
+        run.workflow_name,
+        run.run_number,
+        run.display_status(),
+        run.repository_name
+    )
+}
+
+/// Sends `request`; `target` names the receiving service in errors.
+async fn send(request: RequestBuilder, target: &str) -> Result<(), NotificationError> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| NotificationError::Unreachable {
+            target: target.to_string(),
+            message: e.to_string(),
+        })?;
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(NotificationError::Rejected {
+            target: target.to_string(),
+            status: response.status().as_u16(),
+        })
+    }
+}
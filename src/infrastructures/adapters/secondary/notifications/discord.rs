@@ -0,0 +1,124 @@
+use super::{client, headline, send};
+use crate::domain::external_apis::notifications::{NotificationError, NotificationPort};
+use crate::domain::models::repository_change::RepositoryChange;
+use crate::domain::models::transition::RunTransition;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::{Value, json};
+
+/// Embed colour of runs needing attention
+const FAILURE_COLOR: u32 = 0x00d7_3a49;
+
+/// Embed colour of successful runs
+const SUCCESS_COLOR: u32 = 0x0028_a745;
+
+/// Embed colour of every other state
+const NEUTRAL_COLOR: u32 = 0x006a_737d;
+
+/// Posts run transitions to a Discord webhook as embeds.
+pub struct DiscordWebhook {
+    client: Client,
+    url: String,
+}
+
+impl DiscordWebhook {
+    #[must_use]
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            client: client(),
+            url: url.into(),
+        }
+    }
+}
+
+/// An embed linking the run, coloured by outcome, with its repository, workflow, branch and
+/// actor as inline fields.
+fn payload(transition: &RunTransition) -> Value {
+    let run = &transition.run;
+    let actor = run.triggering_actor.as_ref().unwrap_or(&run.actor);
+    let color = if transition.is_failure() {
+        FAILURE_COLOR
+    } else if run.display_status() == "success" {
+        SUCCESS_COLOR
+    } else {
+        NEUTRAL_COLOR
+    };
+    let field = |name: &str, value: &str| json!({"name": name, "value": value, "inline": true});
+    json!({
+        "embeds": [{
+            "title": headline(transition),
+            "url": run.html_url,
+            "description": run.display_title,
+            "color": color,
+            "fields": [
+                field("Repository", &run.repository_name.to_string()),
+                field("Workflow", &run.workflow_name),
+                field("Branch", run.head_branch.as_deref().unwrap_or("-")),
+                field("Actor", &actor.login),
+            ],
+            "timestamp": run.updated_at.to_rfc3339(),
+        }],
+    })
+}
+
+#[async_trait]
+impl NotificationPort for DiscordWebhook {
+    async fn notify(&self, event: &RunTransition) -> Result<(), NotificationError> {
+        send(self.client.post(&self.url).json(&payload(event)), "Discord").await
+    }
+
+    async fn notify_repository(&self, change: &RepositoryChange) -> Result<(), NotificationError> {
+        let payload = json!({"content": change.to_string()});
+        send(self.client.post(&self.url).json(&payload), "Discord").await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::workflow_run;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_posts_an_embed_coloured_by_outcome() -> anyhow::Result<()> {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/webhooks/1/token"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let discord = DiscordWebhook::new(format!("{}/api/webhooks/1/token", server.uri()));
+
+        discord
+            .notify(&RunTransition {
+                run: workflow_run("owner/repo", 7, "timed_out"),
+                previous_status: None,
+                previous_conclusion: None,
+            })
+            .await?;
+
+        let requests = server.received_requests().await.unwrap_or_default();
+        let body: Value = serde_json::from_slice(&requests[0].body)?;
+        assert_eq!(
+            body,
+            json!({
+                "embeds": [{
+                    "title": "CI #7 timed_out in owner/repo",
+                    "url": "https://github.com/owner/repo/actions/runs/7",
+                    "description": "Fix bug",
+                    "color": FAILURE_COLOR,
+                    "fields": [
+                        {"name": "Repository", "value": "owner/repo", "inline": true},
+                        {"name": "Workflow", "value": "CI", "inline": true},
+                        {"name": "Branch", "value": "main", "inline": true},
+                        {"name": "Actor", "value": "octocat", "inline": true},
+                    ],
+                    "timestamp": "2024-08-01T10:07:00+00:00",
+                }],
+            })
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,181 @@
+use super::{client, headline, send};
+use crate::domain::external_apis::notifications::{NotificationError, NotificationPort};
+use crate::domain::models::repository_change::RepositoryChange;
+use crate::domain::models::transition::RunTransition;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::{Value, json};
+
+/// Posts run transitions to a Slack incoming webhook as Block Kit messages.
+pub struct SlackWebhook {
+    client: Client,
+    url: String,
+}
+
+impl SlackWebhook {
+    #[must_use]
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            client: client(),
+            url: url.into(),
+        }
+    }
+}
+
+/// Escapes the characters Slack's `mrkdwn` treats as control sequences.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// A message linking the run, with its repository, workflow, branch and actor as fields.
+///
+/// `text` is the fallback shown in push notifications.
+fn payload(transition: &RunTransition) -> Value {
+    let run = &transition.run;
+    let actor = run.triggering_actor.as_ref().unwrap_or(&run.actor);
+    let field = |name: &str, value: &str| json!({"type": "mrkdwn", "text": format!("*{name}*\n{}", escape(value))});
+    json!({
+        "text": headline(transition),
+        "blocks": [
+            {
+                "type": "section",
+                "text": {
+                    "type": "mrkdwn",
+                    "text": format!(
+                        "*<{}|{}>*\n{}",
+                        run.html_url,
+                        escape(&headline(transition)),
+                        escape(&run.display_title)
+                    ),
+                },
+            },
+            {
+                "type": "section",
+                "fields": [
+                    field("Repository", &run.repository_name.to_string()),
+                    field("Workflow", &run.workflow_name),
+                    field("Branch", run.head_branch.as_deref().unwrap_or("-")),
+                    field("Actor", &actor.login),
+                ],
+            },
+            {
+                "type": "actions",
+                "elements": [{
+                    "type": "button",
+                    "text": {"type": "plain_text", "text": "View run"},
+                    "url": run.html_url,
+                }],
+            },
+        ],
+    })
+}
+
+#[async_trait]
+impl NotificationPort for SlackWebhook {
+    async fn notify(&self, event: &RunTransition) -> Result<(), NotificationError> {
+        send(self.client.post(&self.url).json(&payload(event)), "Slack").await
+    }
+
+    async fn notify_repository(&self, change: &RepositoryChange) -> Result<(), NotificationError> {
+        let payload = json!({"text": escape(&change.to_string())});
+        send(self.client.post(&self.url).json(&payload), "Slack").await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::workflow_run;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn failed() -> RunTransition {
+        let mut run = workflow_run("owner/repo", 7, "failure");
+        run.display_title = "Fix <script> & co".to_string();
+        RunTransition {
+            run,
+            previous_status: None,
+            previous_conclusion: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_posts_blocks_with_repository_workflow_actor_and_link() -> anyhow::Result<()> {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/services/T000/B000/XXXX"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let slack = SlackWebhook::new(format!("{}/services/T000/B000/XXXX", server.uri()));
+
+        slack.notify(&failed()).await?;
+
+        let requests = server.received_requests().await.unwrap_or_default();
+        let body: Value = serde_json::from_slice(&requests[0].body)?;
+        assert_eq!(body["text"], "CI #7 failure in owner/repo");
+        assert_eq!(
+            body["blocks"][0]["text"]["text"],
+            "*<https://github.com/owner/repo/actions/runs/7|CI #7 failure in owner/repo>*\nFix &lt;script&gt; &amp; co"
+        );
+        assert_eq!(
+            body["blocks"][1]["fields"],
+            json!([
+                {"type": "mrkdwn", "text": "*Repository*\nowner/repo"},
+                {"type": "mrkdwn", "text": "*Workflow*\nCI"},
+                {"type": "mrkdwn", "text": "*Branch*\nmain"},
+                {"type": "mrkdwn", "text": "*Actor*\noctocat"},
+            ])
+        );
+        assert_eq!(
+            body["blocks"][2]["elements"][0]["url"],
+            "https://github.com/owner/repo/actions/runs/7"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_error_statuses_are_rejections() -> anyhow::Result<()> {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        let result = SlackWebhook::new(server.uri()).notify(&failed()).await;
+
+        assert_eq!(
+            result,
+            Err(NotificationError::Rejected {
+                target: "Slack".to_string(),
+                status: 404
+            })
+        );
+        assert!(!result.is_err_and(|e| e.is_retryable()));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_repository_changes_are_posted_as_text() -> anyhow::Result<()> {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        SlackWebhook::new(server.uri())
+            .notify_repository(&RepositoryChange::Discovered {
+                repository: "owner/new".parse()?,
+            })
+            .await?;
+
+        let requests = server.received_requests().await.unwrap_or_default();
+        let body: Value = serde_json::from_slice(&requests[0].body)?;
+        assert_eq!(body, json!({"text": "new repository detected: owner/new"}));
+        Ok(())
+    }
+}
@@ -0,0 +1,391 @@
+use super::{client, headline, send};
+use crate::domain::external_apis::notifications::{NotificationError, NotificationPort};
+use crate::domain::models::push_subscription::PushSubscription;
+use crate::domain::models::transition::RunTransition;
+use crate::domain::repositories::{PushSubscriptionRepository, RunRepositoryError};
+use async_trait::async_trait;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use futures_util::future::join_all;
+use jwt_simple::algorithms::ES256KeyPair;
+use reqwest::Client;
+use reqwest::header::{CONTENT_ENCODING, CONTENT_TYPE};
+use serde_json::json;
+use std::fmt;
+use std::sync::Arc;
+use web_push::{
+    ContentEncoding, PartialVapidSignatureBuilder, SubscriptionInfo, Urgency,
+    VapidSignatureBuilder, WebPushError, WebPushMessage, WebPushMessageBuilder,
+};
+
+/// How long a push service keeps a notification for a browser that is offline (seconds)
+const PUSH_TTL_SECONDS: u32 = 24 * 60 * 60;
+
+/// Why a VAPID private key cannot be used.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("VAPID_PRIVATE_KEY must be a base64url encoded P-256 private key")]
+pub struct InvalidVapidKey;
+
+/// Why `POST /push/subscribe` refused a subscription.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum SubscribeError {
+    #[error("Invalid push subscription: {0}")]
+    Invalid(String),
+    #[error(transparent)]
+    Store(#[from] RunRepositoryError),
+}
+
+/// The key pair push services know the dashboard by, from `VAPID_PRIVATE_KEY`.
+#[derive(Clone)]
+pub struct VapidKeys {
+    signer: PartialVapidSignatureBuilder,
+    /// Uncompressed public key, base64url encoded as `PushManager.subscribe()` expects it
+    public_key: String,
+}
+
+impl fmt::Debug for VapidKeys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VapidKeys")
+            .field("public_key", &self.public_key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl VapidKeys {
+    /// Keys of the base64url encoded private key `private_key`, the public one derived from it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `private_key` is not a P-256 private key.
+    pub fn from_private_key(private_key: &str) -> Result<Self, InvalidVapidKey> {
+        let signer = VapidSignatureBuilder::from_base64_no_sub(
+            private_key.trim(),
+            web_push::URL_SAFE_NO_PAD,
+        )
+        .map_err(|_| InvalidVapidKey)?;
+        let public_key = URL_SAFE_NO_PAD.encode(signer.get_public_key());
+        Ok(Self { signer, public_key })
+    }
+
+    /// A new random private key, base64url encoded for `VAPID_PRIVATE_KEY`.
+    #[must_use]
+    pub fn generate_private_key() -> String {
+        URL_SAFE_NO_PAD.encode(ES256KeyPair::generate().to_bytes())
+    }
+
+    #[must_use]
+    pub fn public_key(&self) -> &str {
+        &self.public_key
+    }
+}
+
+/// Pushes run transitions to the browsers subscribed through `POST /push/subscribe`, each
+/// told about those passing its own filter.
+///
+/// Subscriptions their push service reports gone, with a 404 or 410, are deleted.
+pub struct WebPushNotifier {
+    client: Client,
+    subscriptions: Arc<dyn PushSubscriptionRepository + Send + Sync>,
+    keys: VapidKeys,
+    /// Contact of the operator sent to push services, e.g. `mailto:ops@example.com`
+    subject: Option<String>,
+}
+
+impl WebPushNotifier {
+    #[must_use]
+    pub fn new(
+        subscriptions: Arc<dyn PushSubscriptionRepository + Send + Sync>,
+        keys: VapidKeys,
+    ) -> Self {
+        Self {
+            client: client(),
+            subscriptions,
+            keys,
+            subject: None,
+        }
+    }
+
+    /// Sends `subject` as the `sub` claim push services may contact the operator through.
+    #[must_use]
+    pub fn with_subject(mut self, subject: Option<String>) -> Self {
+        self.subject = subject;
+        self
+    }
+
+    /// The public key browsers subscribe with.
+    #[must_use]
+    pub fn public_key(&self) -> &str {
+        self.keys.public_key()
+    }
+
+    /// Stores `subscription` once its endpoint and keys are known to be usable.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the subscription is invalid or cannot be stored.
+    pub async fn subscribe(&self, subscription: &PushSubscription) -> Result<(), SubscribeError> {
+        if !(subscription.endpoint.starts_with("https://")
+            || subscription.endpoint.starts_with("http://"))
+        {
+            return Err(SubscribeError::Invalid(
+                "endpoint must be an http(s) URL".to_string(),
+            ));
+        }
+        self.message(subscription, b"{}", false)
+            .map_err(|e| SubscribeError::Invalid(e.short_description().to_string()))?;
+        self.subscriptions.save_subscription(subscription).await?;
+        Ok(())
+    }
+
+    /// The encrypted, signed message carrying `payload` to `subscription`.
+    fn message(
+        &self,
+        subscription: &PushSubscription,
+        payload: &[u8],
+        urgent: bool,
+    ) -> Result<WebPushMessage, WebPushError> {
+        let info = SubscriptionInfo::new(
+            subscription.endpoint.as_str(),
+            subscription.keys.p256dh.as_str(),
+            subscription.keys.auth.as_str(),
+        );
+        let mut signature = self.keys.signer.clone().add_sub_info(&info);
+        if let Some(subject) = &self.subject {
+            signature.add_claim("sub", subject.as_str());
+        }
+        let mut builder = WebPushMessageBuilder::new(&info);
+        builder.set_payload(ContentEncoding::Aes128Gcm, payload);
+        builder.set_ttl(PUSH_TTL_SECONDS);
+        builder.set_urgency(if urgent {
+            Urgency::High
+        } else {
+            Urgency::Normal
+        });
+        builder.set_vapid_signature(signature.build()?);
+        builder.build()
+    }
+
+    /// Pushes `payload` to `subscription`, deleting the subscription if it is gone.
+    async fn push(&self, subscription: &PushSubscription, payload: &[u8], urgent: bool) {
+        let message = match self.message(subscription, payload, urgent) {
+            Ok(message) => message,
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to build a push for {}: {}",
+                    subscription.endpoint,
+                    e
+                );
+                return;
+            }
+        };
+        let mut request = self
+            .client
+            .post(&subscription.endpoint)
+            .header("TTL", message.ttl);
+        if let Some(urgency) = message.urgency {
+            request = request.header("Urgency", urgency.to_string());
+        }
+        if let Some(payload) = message.payload {
+            request = request
+                .header(CONTENT_ENCODING, payload.content_encoding.to_str())
+                .header(CONTENT_TYPE, "application/octet-stream");
+            for (name, value) in payload.crypto_headers {
+                request = request.header(name, value);
+            }
+            request = request.body(payload.content);
+        }
+        match send(request, "Web Push").await {
+            Ok(()) => {}
+            Err(NotificationError::Rejected {
+                status: 404 | 410, ..
+            }) => match self
+                .subscriptions
+                .delete_subscription(&subscription.endpoint)
+                .await
+            {
+                Ok(_) => tracing::info!(
+                    "Deleted the expired push subscription {}",
+                    subscription.endpoint
+                ),
+                Err(e) => tracing::warn!(
+                    "Failed to delete the expired push subscription {}: {}",
+                    subscription.endpoint,
+                    e
+                ),
+            },
+            Err(e) => tracing::warn!("Failed to push to {}: {}", subscription.endpoint, e),
+        }
+    }
+}
+
+/// What the service worker shows: the run, how it ended and the page to open.
+fn payload(transition: &RunTransition) -> serde_json::Value {
+    let run = &transition.run;
+    json!({
+        "title": headline(transition),
+        "body": run.display_title,
+        "url": run.failed_job_url.as_ref().unwrap_or(&run.html_url),
+        "tag": format!("run-{}", run.id),
+    })
+}
+
+#[async_trait]
+impl NotificationPort for WebPushNotifier {
+    /// Pushes `event` to every subscription whose filter it passes.
+    ///
+    /// Only reading the subscriptions fails the delivery: retrying it would push again to
+    /// the browsers already notified, so failed pushes are logged instead.
+    async fn notify(&self, event: &RunTransition) -> Result<(), NotificationError> {
+        let subscriptions = self.subscriptions.subscriptions().await.map_err(|e| {
+            NotificationError::Unreachable {
+                target: "push subscriptions".to_string(),
+                message: e.to_string(),
+            }
+        })?;
+        let payload = payload(event).to_string();
+        join_all(
+            subscriptions
+                .iter()
+                .filter(|subscription| subscription.filter.matches(event))
+                .map(|subscription| {
+                    self.push(subscription, payload.as_bytes(), event.is_failure())
+                }),
+        )
+        .await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::push_subscription::{PushFilter, PushKeys};
+    use crate::infrastructures::adapters::secondary::persistence::sqlite::SqliteRunRepository;
+    use crate::test_support::workflow_run;
+    use jwt_simple::algorithms::ECDSAP256PublicKeyLike;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    /// A subscription to `endpoint` with the keys of a real browser's.
+    fn subscription(endpoint: String) -> PushSubscription {
+        let browser = ES256KeyPair::generate().public_key();
+        PushSubscription {
+            endpoint,
+            keys: PushKeys {
+                p256dh: URL_SAFE_NO_PAD.encode(browser.public_key().to_bytes_uncompressed()),
+                auth: URL_SAFE_NO_PAD.encode([7_u8; 16]),
+            },
+            filter: PushFilter::default(),
+        }
+    }
+
+    fn notifier() -> anyhow::Result<(WebPushNotifier, Arc<SqliteRunRepository>)> {
+        let store = Arc::new(SqliteRunRepository::connect("sqlite::memory:")?);
+        let keys = VapidKeys::from_private_key(&VapidKeys::generate_private_key())?;
+        let notifier = WebPushNotifier::new(Arc::clone(&store) as _, keys)
+            .with_subject(Some("mailto:ops@example.com".to_string()));
+        Ok((notifier, store))
+    }
+
+    fn transition(conclusion: &str) -> RunTransition {
+        RunTransition {
+            run: workflow_run("owner/repo", 7, conclusion),
+            previous_status: None,
+            previous_conclusion: None,
+        }
+    }
+
+    #[test]
+    fn test_public_key_is_derived_from_the_private_key() -> anyhow::Result<()> {
+        let private_key = VapidKeys::generate_private_key();
+        let keys = VapidKeys::from_private_key(&private_key)?;
+
+        assert_eq!(URL_SAFE_NO_PAD.decode(keys.public_key())?.len(), 65);
+        assert_eq!(
+            VapidKeys::from_private_key(&private_key)?.public_key(),
+            keys.public_key()
+        );
+        assert!(VapidKeys::from_private_key("not a key").is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_gone_subscriptions_are_pruned() -> anyhow::Result<()> {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/gone"))
+            .respond_with(ResponseTemplate::new(410))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/live"))
+            .and(header("content-encoding", "aes128gcm"))
+            .and(header("urgency", "high"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let (notifier, store) = notifier()?;
+        let live = subscription(format!("{}/live", server.uri()));
+        notifier
+            .subscribe(&subscription(format!("{}/gone", server.uri())))
+            .await?;
+        notifier.subscribe(&live).await?;
+
+        notifier.notify(&transition("failure")).await?;
+
+        assert_eq!(store.subscriptions().await?, [live]);
+        let requests = server.received_requests().await.unwrap_or_default();
+        let authorization = requests[0]
+            .headers
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        assert!(authorization.starts_with("vapid t="), "{authorization}");
+        assert!(authorization.ends_with(&format!("k={}", notifier.public_key())));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_only_transitions_passing_the_filter_are_pushed() -> anyhow::Result<()> {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let (notifier, _store) = notifier()?;
+        notifier
+            .subscribe(&subscription(format!("{}/failures", server.uri())))
+            .await?;
+
+        // Subscriptions default to failures only
+        notifier.notify(&transition("success")).await?;
+        notifier.notify(&transition("failure")).await?;
+
+        let requests = server.received_requests().await.unwrap_or_default();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url.path(), "/failures");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_invalid_subscriptions_are_refused() -> anyhow::Result<()> {
+        let (notifier, store) = notifier()?;
+        let mut invalid = subscription("https://push.example.com/send".to_string());
+        invalid.keys.p256dh = "short".to_string();
+
+        assert!(matches!(
+            notifier.subscribe(&invalid).await,
+            Err(SubscribeError::Invalid(_))
+        ));
+        invalid = subscription("ftp://push.example.com/send".to_string());
+        assert!(matches!(
+            notifier.subscribe(&invalid).await,
+            Err(SubscribeError::Invalid(_))
+        ));
+        assert!(store.subscriptions().await?.is_empty());
+        Ok(())
+    }
+}
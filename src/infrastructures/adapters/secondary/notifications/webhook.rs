@@ -0,0 +1,311 @@
+use super::{client, headline, send};
+use crate::domain::external_apis::notifications::{NotificationError, NotificationPort};
+use crate::domain::models::transition::RunTransition;
+use async_trait::async_trait;
+use reqwest::Client;
+use reqwest::header::{CONTENT_TYPE, HeaderMap, HeaderName, HeaderValue};
+
+/// Content type of bodies when no `Content-Type` header is configured
+const DEFAULT_CONTENT_TYPE: &str = "text/plain; charset=utf-8";
+
+/// Why a webhook target is misconfigured.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum InvalidWebhook {
+    #[error("unknown template field {{{{{0}}}}}, expected one of {fields}", fields = TemplateField::NAMES.join(", "))]
+    UnknownField(String),
+    #[error("unclosed {{{{ in template")]
+    UnclosedPlaceholder,
+    #[error("invalid header {0:?}")]
+    InvalidHeader(String),
+}
+
+/// A transition field a template can refer to as `{{name}}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TemplateField {
+    Repository,
+    Workflow,
+    RunNumber,
+    Status,
+    PreviousStatus,
+    Branch,
+    Title,
+    Actor,
+    HtmlUrl,
+}
+
+impl TemplateField {
+    const NAMES: [&str; 9] = [
+        "repository",
+        "workflow",
+        "run_number",
+        "status",
+        "previous_status",
+        "branch",
+        "title",
+        "actor",
+        "html_url",
+    ];
+
+    fn parse(name: &str) -> Result<Self, InvalidWebhook> {
+        match name {
+            "repository" => Ok(Self::Repository),
+            "workflow" => Ok(Self::Workflow),
+            "run_number" => Ok(Self::RunNumber),
+            "status" => Ok(Self::Status),
+            "previous_status" => Ok(Self::PreviousStatus),
+            "branch" => Ok(Self::Branch),
+            "title" => Ok(Self::Title),
+            "actor" => Ok(Self::Actor),
+            "html_url" => Ok(Self::HtmlUrl),
+            _ => Err(InvalidWebhook::UnknownField(name.to_string())),
+        }
+    }
+
+    /// The field of `transition`; missing values such as the branch of a tag push are empty.
+    fn value(self, transition: &RunTransition) -> String {
+        let run = &transition.run;
+        match self {
+            Self::Repository => run.repository_name.to_string(),
+            Self::Workflow => run.workflow_name.clone(),
+            Self::RunNumber => run.run_number.to_string(),
+            Self::Status => run.display_status().to_string(),
+            Self::PreviousStatus => transition
+                .previous_display_status()
+                .unwrap_or_default()
+                .to_string(),
+            Self::Branch => run.head_branch.clone().unwrap_or_default(),
+            Self::Title => run.display_title.clone(),
+            Self::Actor => run
+                .triggering_actor
+                .as_ref()
+                .unwrap_or(&run.actor)
+                .login
+                .clone(),
+            Self::HtmlUrl => run.html_url.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Field(TemplateField),
+}
+
+/// A request body with `{{field}}` placeholders, checked when parsed so a typo fails at
+/// startup rather than at the first notification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BodyTemplate {
+    segments: Vec<Segment>,
+}
+
+impl BodyTemplate {
+    /// Parses `template`; whitespace inside the braces is ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InvalidWebhook`] for an unknown field or a `{{` that is never closed.
+    pub fn parse(template: &str) -> Result<Self, InvalidWebhook> {
+        let mut segments = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find("{{") {
+            let end = rest[start..]
+                .find("}}")
+                .ok_or(InvalidWebhook::UnclosedPlaceholder)?;
+            if start > 0 {
+                segments.push(Segment::Literal(rest[..start].to_string()));
+            }
+            let name = rest[start + 2..start + end].trim();
+            segments.push(Segment::Field(TemplateField::parse(name)?));
+            rest = &rest[start + end + 2..];
+        }
+        if !rest.is_empty() {
+            segments.push(Segment::Literal(rest.to_string()));
+        }
+        Ok(Self { segments })
+    }
+
+    /// Fills in the fields of `transition`, escaped as JSON string contents when `json` is set
+    /// so titles with quotes keep the body valid.
+    #[must_use]
+    pub fn render(&self, transition: &RunTransition, json: bool) -> String {
+        self.segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Literal(text) => text.clone(),
+                Segment::Field(field) if json => json_escape(&field.value(transition)),
+                Segment::Field(field) => field.value(transition),
+            })
+            .collect()
+    }
+}
+
+/// `value` as it appears between the quotes of a JSON string.
+fn json_escape(value: &str) -> String {
+    let quoted = serde_json::Value::from(value).to_string();
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+/// Parses configured headers, e.g. an `Authorization` token.
+///
+/// # Errors
+///
+/// Returns [`InvalidWebhook::InvalidHeader`] naming the first header that is not valid HTTP.
+pub fn header_map<'a>(
+    headers: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> Result<HeaderMap, InvalidWebhook> {
+    headers
+        .into_iter()
+        .map(|(name, value)| {
+            let invalid = || InvalidWebhook::InvalidHeader(name.to_string());
+            Ok((
+                HeaderName::try_from(name).map_err(|_| invalid())?,
+                HeaderValue::try_from(value).map_err(|_| invalid())?,
+            ))
+        })
+        .collect()
+}
+
+/// Posts run transitions to any HTTP endpoint, such as ntfy or a PagerDuty-compatible
+/// receiver, with configured headers and a templated body.
+pub struct GenericWebhook {
+    client: Client,
+    url: String,
+    /// The host of `url`, naming the webhook in errors without leaking tokens in its path
+    target: String,
+    headers: HeaderMap,
+    template: Option<BodyTemplate>,
+}
+
+impl GenericWebhook {
+    /// Posts the rendered `template`, or a one-line summary of the run without one.
+    #[must_use]
+    pub fn new(url: impl Into<String>, template: Option<BodyTemplate>) -> Self {
+        let url = url.into();
+        let host = reqwest::Url::parse(&url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string));
+        Self {
+            client: client(),
+            target: format!("webhook {}", host.unwrap_or_default()),
+            url,
+            headers: HeaderMap::new(),
+            template,
+        }
+    }
+
+    /// Sends `headers` with every request; a `Content-Type` replaces the plain text default.
+    #[must_use]
+    pub fn with_headers(mut self, headers: HeaderMap) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    fn content_type(&self) -> HeaderValue {
+        self.headers
+            .get(CONTENT_TYPE)
+            .cloned()
+            .unwrap_or(HeaderValue::from_static(DEFAULT_CONTENT_TYPE))
+    }
+
+    fn body(&self, transition: &RunTransition) -> String {
+        let json = self
+            .content_type()
+            .to_str()
+            .is_ok_and(|content_type| content_type.contains("json"));
+        self.template.as_ref().map_or_else(
+            || headline(transition),
+            |template| template.render(transition, json),
+        )
+    }
+}
+
+#[async_trait]
+impl NotificationPort for GenericWebhook {
+    async fn notify(&self, event: &RunTransition) -> Result<(), NotificationError> {
+        let request = self
+            .client
+            .post(&self.url)
+            .headers(self.headers.clone())
+            .header(CONTENT_TYPE, self.content_type())
+            .body(self.body(event));
+        send(request, &self.target).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::status::RunStatus;
+    use crate::test_support::workflow_run;
+    use wiremock::matchers::{body_string, header, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn failed() -> RunTransition {
+        let mut run = workflow_run("owner/repo", 7, "failure");
+        run.display_title = "Say \"hi\"".to_string();
+        RunTransition {
+            run,
+            previous_status: Some(RunStatus::InProgress),
+            previous_conclusion: None,
+        }
+    }
+
+    #[test]
+    fn test_fields_are_substituted() -> anyhow::Result<()> {
+        let template = BodyTemplate::parse(
+            "{{workflow}} #{{ run_number }} {{previous_status}} -> {{status}} on {{branch}} \
+             by {{actor}}: {{title}} {{html_url}} ({{repository}})",
+        )?;
+
+        assert_eq!(
+            template.render(&failed(), false),
+            "CI #7 in_progress -> failure on main by octocat: Say \"hi\" \
+             https://github.com/owner/repo/actions/runs/7 (owner/repo)"
+        );
+        let json = BodyTemplate::parse(r#"{"summary": "{{title}}"}"#)?;
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&json.render(&failed(), true))?,
+            serde_json::json!({"summary": "Say \"hi\""})
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_templates_are_rejected() {
+        assert_eq!(
+            BodyTemplate::parse("{{repo}} failed"),
+            Err(InvalidWebhook::UnknownField("repo".to_string()))
+        );
+        assert_eq!(
+            BodyTemplate::parse("{{status"),
+            Err(InvalidWebhook::UnclosedPlaceholder)
+        );
+        assert!(header_map([("Bad Header", "x")]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_configured_headers_are_sent_with_the_body() -> anyhow::Result<()> {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(header("authorization", "Bearer tk_123"))
+            .and(header("title", "Run failed"))
+            .and(header("content-type", DEFAULT_CONTENT_TYPE))
+            .and(body_string("owner/repo: failure"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let webhook = GenericWebhook::new(
+            format!("{}/alerts", server.uri()),
+            Some(BodyTemplate::parse("{{repository}}: {{status}}")?),
+        )
+        .with_headers(header_map([
+            ("Authorization", "Bearer tk_123"),
+            ("Title", "Run failed"),
+        ])?);
+
+        webhook.notify(&failed()).await?;
+        Ok(())
+    }
+}
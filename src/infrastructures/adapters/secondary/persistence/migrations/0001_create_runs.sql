@@ -0,0 +1,22 @@
+-- Every run the poller has seen, keyed by GitHub's run id
+CREATE TABLE runs (
+    run_id INTEGER PRIMARY KEY,
+    repository TEXT NOT NULL COLLATE NOCASE,
+    status TEXT NOT NULL,
+    conclusion TEXT,
+    -- Milliseconds since the Unix epoch
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    -- The run as serialized in snapshots
+    data TEXT NOT NULL
+);
+
+CREATE INDEX runs_by_repository ON runs (repository, created_at);
+CREATE INDEX runs_by_created_at ON runs (created_at);
+
+-- The most recently saved snapshot, as the ids of its runs in order
+CREATE TABLE latest_snapshot (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    generated_at INTEGER NOT NULL,
+    run_ids TEXT NOT NULL
+);
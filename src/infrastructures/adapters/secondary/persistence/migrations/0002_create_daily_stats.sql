@@ -0,0 +1,15 @@
+-- Completed runs rolled up per UTC day, workflow and conclusion, kept after the runs are compacted
+CREATE TABLE daily_stats (
+    -- UTC date of the run's creation, as YYYY-MM-DD
+    day TEXT NOT NULL,
+    repository TEXT NOT NULL COLLATE NOCASE,
+    workflow_id INTEGER NOT NULL,
+    -- Name of the latest run of the day, in case the workflow was renamed
+    workflow_name TEXT NOT NULL,
+    conclusion TEXT NOT NULL,
+    runs INTEGER NOT NULL,
+    total_duration_seconds INTEGER NOT NULL,
+    PRIMARY KEY (repository, day, workflow_id, conclusion)
+);
+
+CREATE INDEX daily_stats_by_day ON daily_stats (day);
@@ -0,0 +1,6 @@
+-- Browser subscriptions to Web Push notifications, keyed by their push service URL
+CREATE TABLE push_subscriptions (
+    endpoint TEXT PRIMARY KEY,
+    -- The subscription as `POST /push/subscribe` received it, filter included
+    data TEXT NOT NULL
+);
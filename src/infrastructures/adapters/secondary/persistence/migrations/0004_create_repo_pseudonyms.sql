@@ -0,0 +1,6 @@
+-- Numbers standing in for repository names when the dashboard runs anonymized
+CREATE TABLE repo_pseudonyms (
+    -- Lowercase `owner/name`, as GitHub compares names ignoring case
+    repository TEXT PRIMARY KEY,
+    number INTEGER NOT NULL UNIQUE
+);
@@ -0,0 +1,5 @@
+-- Repositories seen when listing those the token can see, to report the ones that are new
+CREATE TABLE discovered_repositories (
+    -- `owner/name` as GitHub lists it
+    repository TEXT PRIMARY KEY
+);
@@ -0,0 +1,13 @@
+-- Every saved snapshot, for looking back at what the dashboard showed at a given time
+CREATE TABLE snapshots (
+    sequence INTEGER PRIMARY KEY AUTOINCREMENT,
+    -- Milliseconds since the Unix epoch
+    generated_at INTEGER NOT NULL,
+    -- The id, status, conclusion and updatedAt of each run then, in snapshot order; the rest
+    -- of each run is joined from runs
+    runs TEXT NOT NULL,
+    -- Runs per display status, for charting without decoding the runs
+    status_counts TEXT NOT NULL
+);
+
+CREATE INDEX snapshots_by_generated_at ON snapshots (generated_at);
@@ -0,0 +1,1492 @@
+use crate::domain::models::ids::{RepoFullName, RunId, WorkflowId};
+use crate::domain::models::push_subscription::PushSubscription;
+use crate::domain::models::run::WorkflowRun;
+use crate::domain::models::status::RunConclusion;
+use crate::domain::repositories::{
+    CompactionReport, DailyStats, DiscoveredRepositoryRepository, HistoricalSnapshot,
+    PseudonymRepository, PushSubscriptionRepository, RetentionPolicy, RunQuery, RunRepository,
+    RunRepositoryError, SnapshotRange, SnapshotStatusCounts, StoredSnapshot,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, NaiveTime, TimeDelta, Utc};
+use rusqlite::types::Value;
+use rusqlite::{Connection, OptionalExtension, params, params_from_iter};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::path::Path;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Schema changes in the order they are applied; `PRAGMA user_version` counts those already run
+const MIGRATIONS: &[&str] = &[
+    include_str!("migrations/0001_create_runs.sql"),
+    include_str!("migrations/0002_create_daily_stats.sql"),
+    include_str!("migrations/0003_create_push_subscriptions.sql"),
+    include_str!("migrations/0004_create_repo_pseudonyms.sql"),
+    include_str!("migrations/0005_create_discovered_repositories.sql"),
+    include_str!("migrations/0006_create_snapshots.sql"),
+];
+
+/// Runs ranked within their repository (`rank`, 1 for the latest) and overall (`position`)
+const RANKED_RUNS: &str = "SELECT run_id, created_at,
+        ROW_NUMBER() OVER (PARTITION BY repository ORDER BY created_at DESC, run_id DESC) AS rank,
+        ROW_NUMBER() OVER (ORDER BY created_at DESC, run_id DESC) AS position
+    FROM runs";
+
+/// Completed runs created at or after `?1` grouped into `daily_stats` rows
+const DAILY_STATS: &str = "SELECT day, repository, workflow_id, workflow_name, conclusion,
+        COUNT(*), SUM(duration_seconds)
+    FROM (SELECT date(created_at / 1000, 'unixepoch') AS day, repository, conclusion,
+            json_extract(data, '$.workflowId') AS workflow_id,
+            FIRST_VALUE(json_extract(data, '$.workflowName')) OVER (
+                PARTITION BY repository, date(created_at / 1000, 'unixepoch'),
+                    json_extract(data, '$.workflowId')
+                ORDER BY created_at DESC
+            ) AS workflow_name,
+            MAX(updated_at - created_at, 0) / 1000 AS duration_seconds
+        FROM runs WHERE status = 'completed' AND conclusion IS NOT NULL AND created_at >= ?1)
+    GROUP BY day, repository, workflow_id, workflow_name, conclusion";
+
+/// Run history in a `SQLite` database.
+#[derive(Clone)]
+pub struct SqliteRunRepository {
+    connection: Arc<Mutex<Connection>>,
+}
+
+/// A run flattened into the columns of the `runs` table.
+struct RunRow {
+    run_id: i64,
+    repository: String,
+    status: &'static str,
+    conclusion: Option<&'static str>,
+    created_at: i64,
+    updated_at: i64,
+    data: String,
+}
+
+impl TryFrom<&WorkflowRun> for RunRow {
+    type Error = RunRepositoryError;
+
+    fn try_from(run: &WorkflowRun) -> Result<Self, Self::Error> {
+        Ok(Self {
+            run_id: sql_id(run.id)?,
+            repository: run.repository_name.to_string(),
+            status: run.status.as_str(),
+            conclusion: run.conclusion.map(RunConclusion::as_str),
+            created_at: run.created_at.timestamp_millis(),
+            updated_at: run.updated_at.timestamp_millis(),
+            data: serde_json::to_string(run).map_err(storage_error)?,
+        })
+    }
+}
+
+fn storage_error(error: impl Display) -> RunRepositoryError {
+    RunRepositoryError {
+        message: error.to_string(),
+    }
+}
+
+/// GitHub ids are far below `i64::MAX`, the largest integer `SQLite` stores.
+fn sql_id(id: RunId) -> Result<i64, RunRepositoryError> {
+    i64::try_from(id.0).map_err(|_| storage_error(format!("Run id {id} is out of range")))
+}
+
+fn decode_stats(row: &rusqlite::Row<'_>) -> Result<DailyStats, RunRepositoryError> {
+    let day: String = row.get(0).map_err(storage_error)?;
+    let workflow_id: i64 = row.get(1).map_err(storage_error)?;
+    let runs: i64 = row.get(3).map_err(storage_error)?;
+    let conclusions: String = row.get(4).map_err(storage_error)?;
+    Ok(DailyStats {
+        day: day
+            .parse()
+            .map_err(|e| storage_error(format!("Invalid stats day {day:?}: {e}")))?,
+        workflow_id: WorkflowId(u64::try_from(workflow_id).map_err(storage_error)?),
+        workflow_name: row.get(2).map_err(storage_error)?,
+        runs: u64::try_from(runs).map_err(storage_error)?,
+        conclusions: serde_json::from_str(&conclusions)
+            .map_err(|e| storage_error(format!("Failed to decode stats: {e}")))?,
+        success_rate: row.get(5).map_err(storage_error)?,
+        mean_duration_seconds: row.get(6).map_err(storage_error)?,
+    })
+}
+
+fn decode_run(data: &str) -> Result<WorkflowRun, RunRepositoryError> {
+    serde_json::from_str(data).map_err(|e| storage_error(format!("Failed to decode run: {e}")))
+}
+
+/// The state of each run of a snapshot, to patch onto the stored run when the snapshot is
+/// looked up, and the runs per display status, both as JSON.
+fn snapshot_states(
+    runs: &[WorkflowRun],
+    rows: &[RunRow],
+) -> Result<(String, String), RunRepositoryError> {
+    let states: Vec<serde_json::Value> = runs
+        .iter()
+        .zip(rows)
+        .map(|(run, row)| {
+            serde_json::json!({
+                "id": row.run_id,
+                "status": run.status,
+                "conclusion": run.conclusion,
+                "updatedAt": run.updated_at,
+            })
+        })
+        .collect();
+    let mut status_counts = BTreeMap::<&str, u64>::new();
+    for run in runs {
+        *status_counts.entry(run.display_status()).or_default() += 1;
+    }
+    Ok((
+        serde_json::to_string(&states).map_err(storage_error)?,
+        serde_json::to_string(&status_counts).map_err(storage_error)?,
+    ))
+}
+
+fn decode_time(millis: i64) -> Result<DateTime<Utc>, RunRepositoryError> {
+    DateTime::from_timestamp_millis(millis)
+        .ok_or_else(|| storage_error(format!("Invalid snapshot time {millis}")))
+}
+
+fn decode_sequence(sequence: i64) -> Result<u64, RunRepositoryError> {
+    u64::try_from(sequence).map_err(storage_error)
+}
+
+/// `SELECT data FROM runs` with a `WHERE` clause for the filters of `query`, and the
+/// values of its parameters.
+fn run_filters(query: &RunQuery) -> Result<(String, Vec<Value>), RunRepositoryError> {
+    let mut sql = "SELECT data FROM runs WHERE 1 = 1".to_string();
+    let mut values = Vec::new();
+    if let Some(repository) = &query.repository {
+        sql.push_str(" AND repository = ?");
+        values.push(Value::Text(repository.to_string()));
+    }
+    if let Some(since) = query.since {
+        sql.push_str(" AND created_at >= ?");
+        values.push(Value::Integer(since.timestamp_millis()));
+    }
+    if let Some(until) = query.until {
+        sql.push_str(" AND created_at < ?");
+        values.push(Value::Integer(until.timestamp_millis()));
+    }
+    if let Some(status) = query.status {
+        sql.push_str(" AND status = ?");
+        values.push(Value::Text(status.as_str().to_string()));
+    }
+    if let Some(conclusion) = query.conclusion {
+        sql.push_str(" AND conclusion = ?");
+        values.push(Value::Text(conclusion.as_str().to_string()));
+    }
+    if let Some(workflow) = &query.workflow {
+        sql.push_str(" AND json_extract(data, '$.workflowName') = ?");
+        values.push(Value::Text(workflow.clone()));
+    }
+    if let Some(actor) = &query.actor {
+        sql.push_str(" AND json_extract(data, '$.actor.login') = ? COLLATE NOCASE");
+        values.push(Value::Text(actor.clone()));
+    }
+    if let Some(branch) = &query.branch {
+        sql.push_str(" AND json_extract(data, '$.headBranch') = ?");
+        values.push(Value::Text(branch.clone()));
+    }
+    if let Some(text) = &query.text {
+        // LIKE ignores ASCII case; its wildcards in the text are escaped to match literally
+        sql.push_str(
+            " AND (json_extract(data, '$.displayTitle') LIKE ? ESCAPE '\\'
+                OR json_extract(data, '$.fullDisplayTitle') LIKE ? ESCAPE '\\')",
+        );
+        let pattern = text
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        let pattern = Value::Text(format!("%{pattern}%"));
+        values.push(pattern.clone());
+        values.push(pattern);
+    }
+    if let Some(before) = query.before {
+        sql.push_str(" AND (created_at < ? OR (created_at = ? AND run_id < ?))");
+        let created_at = before.created_at.timestamp_millis();
+        values.push(Value::Integer(created_at));
+        values.push(Value::Integer(created_at));
+        values.push(Value::Integer(sql_id(before.id)?));
+    }
+    if !query.ids.is_empty() {
+        sql.push_str(" AND run_id IN (");
+        sql.push_str(&vec!["?"; query.ids.len()].join(", "));
+        sql.push(')');
+        for id in &query.ids {
+            values.push(Value::Integer(sql_id(*id)?));
+        }
+    }
+    Ok((sql, values))
+}
+
+/// Deletes the snapshots taken before `cutoff` and those beyond the `max_rows` newest,
+/// always keeping the latest, and returns how many were deleted.
+fn compact_snapshots(
+    connection: &Connection,
+    cutoff: Option<i64>,
+    max_rows: Option<i64>,
+) -> rusqlite::Result<usize> {
+    let mut deleted = 0;
+    if let Some(cutoff) = cutoff {
+        deleted += connection.execute(
+            "DELETE FROM snapshots WHERE generated_at < ?1
+                 AND sequence < (SELECT MAX(sequence) FROM snapshots)",
+            [cutoff],
+        )?;
+    }
+    if let Some(max_rows) = max_rows {
+        deleted += connection.execute(
+            "DELETE FROM snapshots WHERE sequence NOT IN
+                 (SELECT sequence FROM snapshots ORDER BY sequence DESC LIMIT MAX(?1, 1))",
+            [max_rows],
+        )?;
+    }
+    Ok(deleted)
+}
+
+/// Applies the migrations the database has not seen yet, each in its own transaction.
+fn migrate(connection: &mut Connection) -> rusqlite::Result<()> {
+    let applied: i64 = connection.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    for (version, migration) in (1_i64..).zip(MIGRATIONS) {
+        if version <= applied {
+            continue;
+        }
+        let transaction = connection.transaction()?;
+        transaction.execute_batch(migration)?;
+        transaction.pragma_update(None, "user_version", version)?;
+        transaction.commit()?;
+        tracing::info!("Applied database migration {}", version);
+    }
+    Ok(())
+}
+
+impl SqliteRunRepository {
+    /// Opens the database named by a `sqlite://path` URL, creating the file if needed.
+    ///
+    /// `sqlite::memory:` opens a database that lives as long as the process.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the URL is not a `sqlite:` URL, or the database cannot be opened
+    /// or migrated.
+    pub fn connect(url: &str) -> Result<Self, RunRepositoryError> {
+        let path = url
+            .strip_prefix("sqlite://")
+            .or_else(|| url.strip_prefix("sqlite:"))
+            .ok_or_else(|| {
+                storage_error(format!(
+                    "Unsupported database URL {url:?}, expected sqlite://path"
+                ))
+            })?;
+        if path == ":memory:" {
+            Self::from_connection(Connection::open_in_memory())
+        } else {
+            Self::open(path)
+        }
+    }
+
+    /// Opens the database file at `path`, creating it if needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be opened or migrated.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, RunRepositoryError> {
+        Self::from_connection(Connection::open(path))
+    }
+
+    fn from_connection(
+        connection: rusqlite::Result<Connection>,
+    ) -> Result<Self, RunRepositoryError> {
+        let mut connection = connection.map_err(storage_error)?;
+        migrate(&mut connection).map_err(storage_error)?;
+        Ok(Self {
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    /// Runs `f` on the connection without blocking the async runtime.
+    async fn with_connection<T, F>(&self, f: F) -> Result<T, RunRepositoryError>
+    where
+        F: FnOnce(&mut Connection) -> Result<T, RunRepositoryError> + Send + 'static,
+        T: Send + 'static,
+    {
+        let connection = Arc::clone(&self.connection);
+        tokio::task::spawn_blocking(move || {
+            f(&mut connection.lock().unwrap_or_else(PoisonError::into_inner))
+        })
+        .await
+        .map_err(storage_error)?
+    }
+}
+
+#[async_trait]
+impl RunRepository for SqliteRunRepository {
+    #[tracing::instrument(name = "SqliteRunRepository::save_runs", skip_all)]
+    async fn save_runs(
+        &self,
+        runs: &[WorkflowRun],
+        generated_at: DateTime<Utc>,
+    ) -> Result<(), RunRepositoryError> {
+        let rows = runs
+            .iter()
+            .map(RunRow::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        let run_ids = serde_json::to_string(&rows.iter().map(|row| row.run_id).collect::<Vec<_>>())
+            .map_err(storage_error)?;
+        let (states, status_counts) = snapshot_states(runs, &rows)?;
+
+        self.with_connection(move |connection| {
+            let transaction = connection.transaction().map_err(storage_error)?;
+            {
+                let mut upsert = transaction
+                    .prepare_cached(
+                        "INSERT INTO runs
+                             (run_id, repository, status, conclusion, created_at, updated_at, data)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                         ON CONFLICT (run_id) DO UPDATE SET
+                             repository = excluded.repository,
+                             status = excluded.status,
+                             conclusion = excluded.conclusion,
+                             created_at = excluded.created_at,
+                             updated_at = excluded.updated_at,
+                             data = excluded.data
+                         WHERE excluded.updated_at >= runs.updated_at",
+                    )
+                    .map_err(storage_error)?;
+                for row in &rows {
+                    upsert
+                        .execute(params![
+                            row.run_id,
+                            row.repository,
+                            row.status,
+                            row.conclusion,
+                            row.created_at,
+                            row.updated_at,
+                            row.data,
+                        ])
+                        .map_err(storage_error)?;
+                }
+            }
+            transaction
+                .execute(
+                    "INSERT INTO latest_snapshot (id, generated_at, run_ids) VALUES (1, ?1, ?2)
+                     ON CONFLICT (id) DO UPDATE SET
+                         generated_at = excluded.generated_at,
+                         run_ids = excluded.run_ids",
+                    params![generated_at.timestamp_millis(), run_ids],
+                )
+                .map_err(storage_error)?;
+            transaction
+                .execute(
+                    "INSERT INTO snapshots (generated_at, runs, status_counts) VALUES (?1, ?2, ?3)",
+                    params![generated_at.timestamp_millis(), states, status_counts],
+                )
+                .map_err(storage_error)?;
+            transaction.commit().map_err(storage_error)
+        })
+        .await
+    }
+
+    #[tracing::instrument(name = "SqliteRunRepository::query_runs", skip(self))]
+    async fn query_runs(&self, query: &RunQuery) -> Result<Vec<WorkflowRun>, RunRepositoryError> {
+        let (mut sql, mut values) = run_filters(query)?;
+        sql.push_str(" ORDER BY created_at DESC, run_id DESC LIMIT ?");
+        values.push(Value::Integer(
+            i64::try_from(query.limit).unwrap_or(i64::MAX),
+        ));
+
+        self.with_connection(move |connection| {
+            let mut statement = connection.prepare(&sql).map_err(storage_error)?;
+            let rows = statement
+                .query_map(params_from_iter(values), |row| row.get::<_, String>(0))
+                .map_err(storage_error)?;
+            rows.map(|data| decode_run(&data.map_err(storage_error)?))
+                .collect()
+        })
+        .await
+    }
+
+    #[tracing::instrument(name = "SqliteRunRepository::latest_snapshot", skip_all)]
+    async fn latest_snapshot(&self) -> Result<Option<StoredSnapshot>, RunRepositoryError> {
+        self.with_connection(|connection| {
+            let Some((generated_at, run_ids)) = connection
+                .query_row(
+                    "SELECT generated_at, run_ids FROM latest_snapshot WHERE id = 1",
+                    [],
+                    |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)),
+                )
+                .optional()
+                .map_err(storage_error)?
+            else {
+                return Ok(None);
+            };
+            let generated_at = DateTime::from_timestamp_millis(generated_at)
+                .ok_or_else(|| storage_error(format!("Invalid snapshot time {generated_at}")))?;
+
+            // Runs deleted since the snapshot was saved are left out
+            let mut statement = connection
+                .prepare(
+                    "SELECT runs.data FROM json_each(?1) AS ids
+                     JOIN runs ON runs.run_id = ids.value
+                     ORDER BY ids.key",
+                )
+                .map_err(storage_error)?;
+            let runs = statement
+                .query_map([run_ids], |row| row.get::<_, String>(0))
+                .map_err(storage_error)?
+                .map(|data| decode_run(&data.map_err(storage_error)?))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(Some(StoredSnapshot { runs, generated_at }))
+        })
+        .await
+    }
+
+    #[tracing::instrument(name = "SqliteRunRepository::snapshot_at", skip(self))]
+    async fn snapshot_at(
+        &self,
+        at: DateTime<Utc>,
+    ) -> Result<Option<HistoricalSnapshot>, RunRepositoryError> {
+        self.with_connection(move |connection| {
+            let Some((sequence, generated_at, states)) = connection
+                .query_row(
+                    "SELECT sequence, generated_at, runs FROM snapshots WHERE generated_at <= ?1
+                     ORDER BY generated_at DESC, sequence DESC LIMIT 1",
+                    [at.timestamp_millis()],
+                    |row| {
+                        Ok((
+                            row.get::<_, i64>(0)?,
+                            row.get::<_, i64>(1)?,
+                            row.get::<_, String>(2)?,
+                        ))
+                    },
+                )
+                .optional()
+                .map_err(storage_error)?
+            else {
+                return Ok(None);
+            };
+
+            // Each run as last stored, with the state it was in at the time
+            let mut statement = connection
+                .prepare(
+                    "SELECT json_patch(runs.data, states.value) FROM json_each(?1) AS states
+                     JOIN runs ON runs.run_id = json_extract(states.value, '$.id')
+                     ORDER BY states.key",
+                )
+                .map_err(storage_error)?;
+            let runs = statement
+                .query_map([states], |row| row.get::<_, String>(0))
+                .map_err(storage_error)?
+                .map(|data| decode_run(&data.map_err(storage_error)?))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(Some(HistoricalSnapshot {
+                sequence: decode_sequence(sequence)?,
+                generated_at: decode_time(generated_at)?,
+                runs,
+            }))
+        })
+        .await
+    }
+
+    #[tracing::instrument(name = "SqliteRunRepository::snapshot_range", skip_all)]
+    async fn snapshot_range(&self) -> Result<Option<SnapshotRange>, RunRepositoryError> {
+        self.with_connection(|connection| {
+            let (oldest, newest) = connection
+                .query_row(
+                    "SELECT MIN(generated_at), MAX(generated_at) FROM snapshots",
+                    [],
+                    |row| Ok((row.get::<_, Option<i64>>(0)?, row.get::<_, Option<i64>>(1)?)),
+                )
+                .map_err(storage_error)?;
+            let (Some(oldest), Some(newest)) = (oldest, newest) else {
+                return Ok(None);
+            };
+            Ok(Some(SnapshotRange {
+                oldest: decode_time(oldest)?,
+                newest: decode_time(newest)?,
+            }))
+        })
+        .await
+    }
+
+    #[tracing::instrument(name = "SqliteRunRepository::snapshot_series", skip(self))]
+    async fn snapshot_series(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        step: TimeDelta,
+    ) -> Result<Vec<SnapshotStatusCounts>, RunRepositoryError> {
+        let step = step.num_milliseconds().max(1);
+        self.with_connection(move |connection| {
+            // Integer division puts every snapshot in the interval it was taken in
+            let mut statement = connection
+                .prepare(
+                    "SELECT sequence, generated_at, status_counts FROM (
+                         SELECT sequence, generated_at, status_counts, ROW_NUMBER() OVER (
+                             PARTITION BY (generated_at - ?1) / ?3
+                             ORDER BY generated_at DESC, sequence DESC
+                         ) AS rank
+                         FROM snapshots WHERE generated_at >= ?1 AND generated_at < ?2)
+                     WHERE rank = 1 ORDER BY generated_at",
+                )
+                .map_err(storage_error)?;
+            let rows = statement
+                .query_map(
+                    params![from.timestamp_millis(), to.timestamp_millis(), step],
+                    |row| {
+                        Ok((
+                            row.get::<_, i64>(0)?,
+                            row.get::<_, i64>(1)?,
+                            row.get::<_, String>(2)?,
+                        ))
+                    },
+                )
+                .map_err(storage_error)?;
+            rows.map(|row| {
+                let (sequence, generated_at, counts) = row.map_err(storage_error)?;
+                Ok(SnapshotStatusCounts {
+                    sequence: decode_sequence(sequence)?,
+                    generated_at: decode_time(generated_at)?,
+                    counts: serde_json::from_str(&counts).map_err(|e| {
+                        storage_error(format!("Failed to decode status counts: {e}"))
+                    })?,
+                })
+            })
+            .collect()
+        })
+        .await
+    }
+
+    #[tracing::instrument(name = "SqliteRunRepository::compact", skip(self))]
+    async fn compact(
+        &self,
+        policy: &RetentionPolicy,
+        now: DateTime<Utc>,
+    ) -> Result<CompactionReport, RunRepositoryError> {
+        let cutoff = policy
+            .max_age
+            .and_then(|max_age| now.checked_sub_signed(max_age))
+            .map(|cutoff| cutoff.timestamp_millis());
+        let max_rows = policy
+            .max_rows
+            .map(|max_rows| i64::try_from(max_rows).unwrap_or(i64::MAX));
+
+        self.with_connection(move |connection| {
+            let transaction = connection.transaction().map_err(storage_error)?;
+            let mut deleted = 0;
+            if let Some(cutoff) = cutoff {
+                deleted += transaction
+                    .execute(
+                        &format!(
+                            "DELETE FROM runs WHERE run_id IN
+                                 (SELECT run_id FROM ({RANKED_RUNS}) WHERE rank > 1 AND created_at < ?1)"
+                        ),
+                        [cutoff],
+                    )
+                    .map_err(storage_error)?;
+            }
+            if let Some(max_rows) = max_rows {
+                deleted += transaction
+                    .execute(
+                        &format!(
+                            "DELETE FROM runs WHERE run_id IN
+                                 (SELECT run_id FROM ({RANKED_RUNS}) WHERE rank > 1 AND position > ?1)"
+                        ),
+                        [max_rows],
+                    )
+                    .map_err(storage_error)?;
+            }
+            let remaining: i64 = transaction
+                .query_row("SELECT COUNT(*) FROM runs", [], |row| row.get(0))
+                .map_err(storage_error)?;
+            let snapshots_deleted =
+                compact_snapshots(&transaction, cutoff, max_rows).map_err(storage_error)?;
+            transaction.commit().map_err(storage_error)?;
+
+            // VACUUM rewrites the whole file, so only pay for it when it frees space
+            if deleted > 0 || snapshots_deleted > 0 {
+                connection.execute_batch("VACUUM").map_err(storage_error)?;
+            }
+            Ok(CompactionReport {
+                deleted: u64::try_from(deleted).unwrap_or_default(),
+                remaining: u64::try_from(remaining).unwrap_or_default(),
+            })
+        })
+        .await
+    }
+
+    #[tracing::instrument(name = "SqliteRunRepository::aggregate_daily_stats", skip(self))]
+    async fn aggregate_daily_stats(&self, since: NaiveDate) -> Result<(), RunRepositoryError> {
+        let since_millis = since.and_time(NaiveTime::MIN).and_utc().timestamp_millis();
+        self.with_connection(move |connection| {
+            let transaction = connection.transaction().map_err(storage_error)?;
+            transaction
+                .execute(
+                    "DELETE FROM daily_stats WHERE day >= ?1",
+                    [since.to_string()],
+                )
+                .map_err(storage_error)?;
+            transaction
+                .execute(
+                    &format!(
+                        "INSERT INTO daily_stats (day, repository, workflow_id, workflow_name,
+                             conclusion, runs, total_duration_seconds) {DAILY_STATS}"
+                    ),
+                    [since_millis],
+                )
+                .map_err(storage_error)?;
+            transaction.commit().map_err(storage_error)
+        })
+        .await
+    }
+
+    #[tracing::instrument(name = "SqliteRunRepository::daily_stats", skip(self))]
+    async fn daily_stats(
+        &self,
+        repository: &RepoFullName,
+        since: NaiveDate,
+    ) -> Result<Vec<DailyStats>, RunRepositoryError> {
+        let repository = repository.to_string();
+        self.with_connection(move |connection| {
+            let mut statement = connection
+                .prepare(
+                    "SELECT day, workflow_id, MAX(workflow_name), SUM(runs),
+                         json_group_object(conclusion, runs),
+                         CAST(SUM(IIF(conclusion = 'success', runs, 0)) AS REAL) / SUM(runs),
+                         CAST(SUM(total_duration_seconds) AS REAL) / SUM(runs)
+                     FROM daily_stats WHERE repository = ?1 AND day >= ?2
+                     GROUP BY day, workflow_id
+                     ORDER BY day, MAX(workflow_name), workflow_id",
+                )
+                .map_err(storage_error)?;
+            let mut rows = statement
+                .query(params![repository, since.to_string()])
+                .map_err(storage_error)?;
+            let mut stats = Vec::new();
+            while let Some(row) = rows.next().map_err(storage_error)? {
+                stats.push(decode_stats(row)?);
+            }
+            Ok(stats)
+        })
+        .await
+    }
+
+    #[tracing::instrument(name = "SqliteRunRepository::stats_repositories", skip(self))]
+    async fn stats_repositories(
+        &self,
+        since: NaiveDate,
+    ) -> Result<Vec<RepoFullName>, RunRepositoryError> {
+        self.with_connection(move |connection| {
+            let mut statement = connection
+                .prepare(
+                    "SELECT DISTINCT repository FROM daily_stats WHERE day >= ?1
+                     ORDER BY repository",
+                )
+                .map_err(storage_error)?;
+            let mut rows = statement
+                .query([since.to_string()])
+                .map_err(storage_error)?;
+            let mut repositories = Vec::new();
+            while let Some(row) = rows.next().map_err(storage_error)? {
+                let repository: String = row.get(0).map_err(storage_error)?;
+                repositories.push(repository.parse().map_err(|e| {
+                    storage_error(format!("Invalid stats repository {repository:?}: {e}"))
+                })?);
+            }
+            Ok(repositories)
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl PushSubscriptionRepository for SqliteRunRepository {
+    #[tracing::instrument(name = "SqliteRunRepository::save_subscription", skip_all)]
+    async fn save_subscription(
+        &self,
+        subscription: &PushSubscription,
+    ) -> Result<(), RunRepositoryError> {
+        let endpoint = subscription.endpoint.clone();
+        let data = serde_json::to_string(subscription).map_err(storage_error)?;
+        self.with_connection(move |connection| {
+            connection
+                .execute(
+                    "INSERT INTO push_subscriptions (endpoint, data) VALUES (?1, ?2)
+                     ON CONFLICT (endpoint) DO UPDATE SET data = excluded.data",
+                    params![endpoint, data],
+                )
+                .map_err(storage_error)?;
+            Ok(())
+        })
+        .await
+    }
+
+    #[tracing::instrument(name = "SqliteRunRepository::subscriptions", skip(self))]
+    async fn subscriptions(&self) -> Result<Vec<PushSubscription>, RunRepositoryError> {
+        self.with_connection(|connection| {
+            let mut statement = connection
+                .prepare("SELECT data FROM push_subscriptions ORDER BY rowid")
+                .map_err(storage_error)?;
+            let rows = statement
+                .query_map([], |row| row.get::<_, String>(0))
+                .map_err(storage_error)?;
+            rows.map(|data| {
+                serde_json::from_str(&data.map_err(storage_error)?)
+                    .map_err(|e| storage_error(format!("Failed to decode push subscription: {e}")))
+            })
+            .collect()
+        })
+        .await
+    }
+
+    #[tracing::instrument(name = "SqliteRunRepository::delete_subscription", skip(self))]
+    async fn delete_subscription(&self, endpoint: &str) -> Result<bool, RunRepositoryError> {
+        let endpoint = endpoint.to_string();
+        self.with_connection(move |connection| {
+            let deleted = connection
+                .execute(
+                    "DELETE FROM push_subscriptions WHERE endpoint = ?1",
+                    [endpoint],
+                )
+                .map_err(storage_error)?;
+            Ok(deleted > 0)
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl PseudonymRepository for SqliteRunRepository {
+    #[tracing::instrument(name = "SqliteRunRepository::pseudonyms", skip(self))]
+    async fn pseudonyms(&self) -> Result<Vec<(RepoFullName, u64)>, RunRepositoryError> {
+        self.with_connection(|connection| {
+            let mut statement = connection
+                .prepare("SELECT repository, number FROM repo_pseudonyms ORDER BY number")
+                .map_err(storage_error)?;
+            let rows = statement
+                .query_map([], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+                })
+                .map_err(storage_error)?;
+            rows.map(|row| {
+                let (repository, number) = row.map_err(storage_error)?;
+                Ok((
+                    repository.parse().map_err(storage_error)?,
+                    u64::try_from(number).map_err(storage_error)?,
+                ))
+            })
+            .collect()
+        })
+        .await
+    }
+
+    #[tracing::instrument(name = "SqliteRunRepository::save_pseudonym", skip(self))]
+    async fn save_pseudonym(
+        &self,
+        repository: &RepoFullName,
+        number: u64,
+    ) -> Result<(), RunRepositoryError> {
+        let repository = repository.to_string().to_ascii_lowercase();
+        let number = i64::try_from(number).map_err(storage_error)?;
+        self.with_connection(move |connection| {
+            connection
+                .execute(
+                    "INSERT INTO repo_pseudonyms (repository, number) VALUES (?1, ?2)
+                     ON CONFLICT (repository) DO NOTHING",
+                    params![repository, number],
+                )
+                .map_err(storage_error)?;
+            Ok(())
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl DiscoveredRepositoryRepository for SqliteRunRepository {
+    #[tracing::instrument(name = "SqliteRunRepository::discovered_repositories", skip(self))]
+    async fn discovered_repositories(&self) -> Result<Vec<RepoFullName>, RunRepositoryError> {
+        self.with_connection(|connection| {
+            let mut statement = connection
+                .prepare("SELECT repository FROM discovered_repositories ORDER BY repository")
+                .map_err(storage_error)?;
+            let rows = statement
+                .query_map([], |row| row.get::<_, String>(0))
+                .map_err(storage_error)?;
+            rows.map(|row| row.map_err(storage_error)?.parse().map_err(storage_error))
+                .collect()
+        })
+        .await
+    }
+
+    #[tracing::instrument(name = "SqliteRunRepository::save_discovered_repository", skip(self))]
+    async fn save_discovered_repository(
+        &self,
+        repository: &RepoFullName,
+    ) -> Result<(), RunRepositoryError> {
+        let repository = repository.to_string();
+        self.with_connection(move |connection| {
+            connection
+                .execute(
+                    "INSERT INTO discovered_repositories (repository) VALUES (?1)
+                     ON CONFLICT (repository) DO NOTHING",
+                    params![repository],
+                )
+                .map_err(storage_error)?;
+            Ok(())
+        })
+        .await
+    }
+
+    #[tracing::instrument(name = "SqliteRunRepository::remove_discovered_repository", skip(self))]
+    async fn remove_discovered_repository(
+        &self,
+        repository: &RepoFullName,
+    ) -> Result<(), RunRepositoryError> {
+        let repository = repository.to_string();
+        self.with_connection(move |connection| {
+            connection
+                .execute(
+                    "DELETE FROM discovered_repositories WHERE repository = ?1",
+                    params![repository],
+                )
+                .map_err(storage_error)?;
+            Ok(())
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::services::{PollerEvent, SharedPoller};
+    use crate::application::use_cases::{
+        StreamGitHubActionsRunsInteractor, StreamGitHubActionsRunsUseCaseOutput,
+    };
+    use crate::domain::models::push_subscription::{PushFilter, PushKeys};
+    use crate::domain::models::status::RunStatus;
+    use crate::domain::repositories::RunCursor;
+    use crate::test_support::{StubGitHubApi, base_time, repo_full_name, workflow_run};
+    use chrono::TimeDelta;
+    use std::time::Duration;
+    use tokio::sync::{Notify, broadcast};
+    use tokio::time::timeout;
+
+    fn temp_repository() -> anyhow::Result<(tempfile::TempDir, SqliteRunRepository)> {
+        let dir = tempfile::tempdir()?;
+        let url = format!("sqlite://{}", dir.path().join("runs.db").display());
+        let repository = SqliteRunRepository::connect(&url)?;
+        Ok((dir, repository))
+    }
+
+    #[tokio::test]
+    async fn test_upsert_replaces_run_when_status_changes() -> anyhow::Result<()> {
+        let (_dir, repository) = temp_repository()?;
+        let mut run = workflow_run("owner/repo", 1, "in_progress");
+        repository.save_runs(&[run.clone()], base_time()).await?;
+
+        run.status = RunStatus::Completed;
+        run.conclusion = Some(RunConclusion::Failure);
+        run.updated_at += TimeDelta::minutes(5);
+        repository.save_runs(&[run.clone()], base_time()).await?;
+
+        let stored = repository.query_runs(&RunQuery::default()).await?;
+        assert_eq!(stored, vec![run.clone()]);
+
+        // An older copy arriving late does not overwrite the newer one
+        let mut stale = run.clone();
+        stale.status = RunStatus::InProgress;
+        stale.conclusion = None;
+        stale.updated_at -= TimeDelta::minutes(10);
+        repository.save_runs(&[stale], base_time()).await?;
+        assert_eq!(
+            repository.query_runs(&RunQuery::default()).await?,
+            vec![run]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_query_filters_by_repository_time_and_status() -> anyhow::Result<()> {
+        let (_dir, repository) = temp_repository()?;
+        let mut old = workflow_run("owner/repo", 1, "success");
+        old.created_at = base_time() - TimeDelta::days(2);
+        let failed = workflow_run("owner/repo", 2, "failure");
+        let other = workflow_run("owner/other", 3, "success");
+        repository
+            .save_runs(&[old, failed.clone(), other], base_time())
+            .await?;
+
+        let ids = |runs: Vec<WorkflowRun>| runs.iter().map(|run| run.id.0).collect::<Vec<_>>();
+        let query = RunQuery {
+            repository: Some(repo_full_name("OWNER/Repo")),
+            ..RunQuery::default()
+        };
+        assert_eq!(ids(repository.query_runs(&query).await?), vec![2, 1]);
+
+        let query = RunQuery {
+            since: Some(base_time() - TimeDelta::days(1)),
+            ..RunQuery::default()
+        };
+        assert_eq!(ids(repository.query_runs(&query).await?), vec![3, 2]);
+
+        let query = RunQuery {
+            until: Some(base_time() - TimeDelta::days(1)),
+            ..RunQuery::default()
+        };
+        assert_eq!(ids(repository.query_runs(&query).await?), vec![1]);
+
+        let query = RunQuery {
+            conclusion: Some(RunConclusion::Failure),
+            ..RunQuery::default()
+        };
+        assert_eq!(repository.query_runs(&query).await?, vec![failed]);
+
+        let query = RunQuery {
+            ids: vec![RunId(1), RunId(3)],
+            ..RunQuery::default()
+        };
+        assert_eq!(ids(repository.query_runs(&query).await?), vec![3, 1]);
+
+        let query = RunQuery {
+            limit: 1,
+            ..RunQuery::default()
+        };
+        assert_eq!(repository.query_runs(&query).await?.len(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_search_filters_and_pages_by_keyset() -> anyhow::Result<()> {
+        let (_dir, repository) = temp_repository()?;
+        let mut runs: Vec<_> = (1..=4)
+            .map(|id| WorkflowRun {
+                created_at: base_time(),
+                updated_at: base_time(),
+                ..workflow_run("owner/repo", id, "success")
+            })
+            .collect();
+        runs[0].display_title = "Bump 100% of deps".to_string();
+        runs[1].display_title = "Bump 100 deps".to_string();
+        runs[2].full_display_title = Some("Truncated BUMP title".to_string());
+        runs[3].head_branch = Some("release".to_string());
+        repository.save_runs(&runs, base_time()).await?;
+
+        let ids = |runs: Vec<WorkflowRun>| runs.iter().map(|run| run.id.0).collect::<Vec<_>>();
+        let query = RunQuery {
+            text: Some("bump".to_string()),
+            actor: Some(runs[0].actor.login.to_ascii_uppercase()),
+            workflow: Some(runs[0].workflow_name.clone()),
+            ..RunQuery::default()
+        };
+        assert_eq!(ids(repository.query_runs(&query).await?), vec![3, 2, 1]);
+        let query = RunQuery {
+            text: Some("100%".to_string()),
+            ..RunQuery::default()
+        };
+        assert_eq!(ids(repository.query_runs(&query).await?), vec![1]);
+        let query = RunQuery {
+            branch: Some("release".to_string()),
+            ..RunQuery::default()
+        };
+        assert_eq!(ids(repository.query_runs(&query).await?), vec![4]);
+
+        // All four share a creation time, so pages are split by id
+        let mut query = RunQuery {
+            limit: 3,
+            ..RunQuery::default()
+        };
+        let page = repository.query_runs(&query).await?;
+        assert_eq!(ids(page.clone()), vec![4, 3, 2]);
+        query.before = page.last().map(RunCursor::of);
+        assert_eq!(ids(repository.query_runs(&query).await?), vec![1]);
+        query.before = Some(RunCursor::of(&runs[0]));
+        assert!(repository.query_runs(&query).await?.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_latest_snapshot_survives_reopening() -> anyhow::Result<()> {
+        let (dir, repository) = temp_repository()?;
+        assert_eq!(repository.latest_snapshot().await?, None);
+        let runs = vec![
+            workflow_run("owner/repo", 2, "failure"),
+            workflow_run("owner/repo", 1, "success"),
+        ];
+        repository.save_runs(&runs, base_time()).await?;
+        drop(repository);
+
+        let reopened = SqliteRunRepository::open(dir.path().join("runs.db"))?;
+
+        assert_eq!(
+            reopened.latest_snapshot().await?,
+            Some(StoredSnapshot {
+                runs,
+                generated_at: base_time(),
+            })
+        );
+        Ok(())
+    }
+
+    /// Saves a run of `repository` created `days` before the base time.
+    async fn save_backdated(
+        repository: &SqliteRunRepository,
+        repository_name: &str,
+        id: u64,
+        days: i64,
+    ) -> anyhow::Result<()> {
+        let mut run = workflow_run(repository_name, id, "success");
+        run.created_at = base_time() - TimeDelta::days(days);
+        run.updated_at = run.created_at;
+        repository.save_runs(&[run], base_time()).await?;
+        Ok(())
+    }
+
+    async fn stored_ids(repository: &SqliteRunRepository) -> anyhow::Result<Vec<u64>> {
+        let query = RunQuery {
+            limit: 1000,
+            ..RunQuery::default()
+        };
+        Ok(repository
+            .query_runs(&query)
+            .await?
+            .iter()
+            .map(|run| run.id.0)
+            .collect())
+    }
+
+    #[tokio::test]
+    async fn test_compaction_deletes_runs_older_than_the_cutoff() -> anyhow::Result<()> {
+        let (_dir, repository) = temp_repository()?;
+        save_backdated(&repository, "owner/repo", 1, 1).await?;
+        save_backdated(&repository, "owner/repo", 2, 100).await?;
+        save_backdated(&repository, "owner/repo", 3, 200).await?;
+        let policy = RetentionPolicy {
+            max_age: Some(TimeDelta::days(90)),
+            max_rows: None,
+        };
+
+        let report = repository.compact(&policy, base_time()).await?;
+
+        assert_eq!(
+            report,
+            CompactionReport {
+                deleted: 2,
+                remaining: 1
+            }
+        );
+        assert_eq!(stored_ids(&repository).await?, vec![1]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_compaction_keeps_the_latest_run_of_each_repository() -> anyhow::Result<()> {
+        let (_dir, repository) = temp_repository()?;
+        save_backdated(&repository, "owner/active", 1, 1).await?;
+        save_backdated(&repository, "owner/dormant", 2, 300).await?;
+        save_backdated(&repository, "owner/dormant", 3, 400).await?;
+        let policy = RetentionPolicy {
+            max_age: Some(TimeDelta::days(90)),
+            max_rows: Some(1),
+        };
+
+        let report = repository.compact(&policy, base_time()).await?;
+
+        // Run 2 is past the cutoff and beyond max_rows, but the newest of its repository
+        assert_eq!(report.deleted, 1);
+        assert_eq!(stored_ids(&repository).await?, vec![1, 2]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_compaction_trims_to_max_rows() -> anyhow::Result<()> {
+        let (_dir, repository) = temp_repository()?;
+        for (id, days) in [(1, 1), (2, 2), (3, 3), (4, 4)] {
+            save_backdated(&repository, "owner/repo", id, days).await?;
+        }
+        let policy = RetentionPolicy {
+            max_age: None,
+            max_rows: Some(2),
+        };
+
+        let report = repository.compact(&policy, base_time()).await?;
+
+        assert_eq!(report.remaining, 2);
+        assert_eq!(stored_ids(&repository).await?, vec![1, 2]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_lookups_return_the_runs_as_they_were() -> anyhow::Result<()> {
+        let (_dir, repository) = temp_repository()?;
+        let mut run = workflow_run("owner/repo", 1, "in_progress");
+        repository.save_runs(&[run.clone()], base_time()).await?;
+        run.status = RunStatus::Completed;
+        run.conclusion = Some(RunConclusion::Failure);
+        run.updated_at += TimeDelta::minutes(1);
+        let later = base_time() + TimeDelta::minutes(1);
+        repository.save_runs(&[run.clone()], later).await?;
+
+        let before = repository
+            .snapshot_at(base_time() - TimeDelta::milliseconds(1))
+            .await?;
+        let first = repository.snapshot_at(base_time()).await?;
+        let between = repository
+            .snapshot_at(later - TimeDelta::milliseconds(1))
+            .await?;
+        let last = repository.snapshot_at(later).await?;
+
+        assert_eq!(before, None);
+        let first = first.ok_or_else(|| anyhow::anyhow!("no snapshot"))?;
+        assert_eq!(first.generated_at, base_time());
+        assert_eq!(first.runs[0].display_status(), "in_progress");
+        assert_eq!(first.runs[0].conclusion, None);
+        assert_eq!(
+            between.map(|snapshot| snapshot.sequence),
+            Some(first.sequence)
+        );
+        let last = last.ok_or_else(|| anyhow::anyhow!("no snapshot"))?;
+        assert_eq!(last.sequence, first.sequence + 1);
+        assert_eq!(last.runs, vec![run]);
+        assert_eq!(
+            repository.snapshot_range().await?,
+            Some(SnapshotRange {
+                oldest: base_time(),
+                newest: later
+            })
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_series_keeps_the_latest_of_each_step() -> anyhow::Result<()> {
+        let (_dir, repository) = temp_repository()?;
+        assert_eq!(repository.snapshot_range().await?, None);
+        for (minutes, conclusion) in [
+            (0, "success"),
+            (4, "failure"),
+            (5, "in_progress"),
+            (9, "success"),
+            (20, "failure"),
+        ] {
+            let run = workflow_run("owner/repo", 1, conclusion);
+            repository
+                .save_runs(&[run], base_time() + TimeDelta::minutes(minutes))
+                .await?;
+        }
+
+        let series = repository
+            .snapshot_series(
+                base_time(),
+                base_time() + TimeDelta::minutes(20),
+                TimeDelta::minutes(5),
+            )
+            .await?;
+
+        // The interval from 10 to 15 minutes has no snapshot, and `to` is exclusive
+        let points: Vec<_> = series
+            .iter()
+            .map(|point| {
+                (
+                    (point.generated_at - base_time()).num_minutes(),
+                    point.counts.keys().cloned().collect::<Vec<_>>(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            points,
+            vec![
+                (4, vec!["failure".to_string()]),
+                (9, vec!["success".to_string()]),
+            ]
+        );
+        assert_eq!(series[0].counts["failure"], 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_compaction_bounds_the_snapshots() -> anyhow::Result<()> {
+        let (_dir, repository) = temp_repository()?;
+        for days in [200, 100, 3, 2, 1] {
+            let run = workflow_run("owner/repo", 1, "success");
+            repository
+                .save_runs(&[run], base_time() - TimeDelta::days(days))
+                .await?;
+        }
+
+        repository
+            .compact(
+                &RetentionPolicy {
+                    max_age: Some(TimeDelta::days(90)),
+                    max_rows: None,
+                },
+                base_time(),
+            )
+            .await?;
+        let aged = repository.snapshot_range().await?;
+        repository
+            .compact(
+                &RetentionPolicy {
+                    max_age: None,
+                    max_rows: Some(2),
+                },
+                base_time(),
+            )
+            .await?;
+        let trimmed = repository.snapshot_range().await?;
+        repository
+            .compact(
+                &RetentionPolicy {
+                    max_age: Some(TimeDelta::hours(1)),
+                    max_rows: None,
+                },
+                base_time(),
+            )
+            .await?;
+
+        let oldest = |range: Option<SnapshotRange>| range.map(|range| base_time() - range.oldest);
+        assert_eq!(oldest(aged), Some(TimeDelta::days(3)));
+        assert_eq!(oldest(trimmed), Some(TimeDelta::days(2)));
+        // The latest snapshot outlives any policy
+        assert_eq!(
+            oldest(repository.snapshot_range().await?),
+            Some(TimeDelta::days(1))
+        );
+        Ok(())
+    }
+
+    /// A completed run of `workflow` created at `created_at` that took `minutes`.
+    fn finished_run(
+        id: u64,
+        workflow: u64,
+        conclusion: &str,
+        created_at: DateTime<Utc>,
+        minutes: i64,
+    ) -> WorkflowRun {
+        let mut run = workflow_run("owner/repo", id, conclusion);
+        run.workflow_id = WorkflowId(workflow);
+        run.created_at = created_at;
+        run.updated_at = created_at + TimeDelta::minutes(minutes);
+        run
+    }
+
+    #[tokio::test]
+    async fn test_daily_stats_are_bucketed_by_utc_day() -> anyhow::Result<()> {
+        let (_dir, repository) = temp_repository()?;
+        let midnight = NaiveDate::from_ymd_opt(2024, 8, 1)
+            .unwrap_or_default()
+            .and_time(NaiveTime::MIN)
+            .and_utc();
+        let mut in_progress = workflow_run("owner/repo", 5, "in_progress");
+        in_progress.created_at = midnight;
+        let runs = [
+            finished_run(1, 1, "success", midnight - TimeDelta::minutes(10), 20),
+            finished_run(2, 1, "failure", midnight + TimeDelta::minutes(10), 4),
+            finished_run(3, 1, "success", midnight + TimeDelta::minutes(20), 8),
+            finished_run(4, 2, "success", midnight + TimeDelta::minutes(30), 1),
+            in_progress,
+            workflow_run("owner/other", 6, "failure"),
+        ];
+        repository.save_runs(&runs, base_time()).await?;
+        let day_before = midnight.date_naive() - TimeDelta::days(1);
+
+        // Recomputing the same days must not count any run twice
+        repository.aggregate_daily_stats(day_before).await?;
+        repository.aggregate_daily_stats(day_before).await?;
+        let stats = repository
+            .daily_stats(&repo_full_name("owner/repo"), day_before)
+            .await?;
+
+        let buckets = stats
+            .iter()
+            .map(|stats| (stats.day.to_string(), stats.workflow_id.0, stats.runs))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            buckets,
+            vec![
+                ("2024-07-31".to_string(), 1, 1),
+                ("2024-08-01".to_string(), 1, 2),
+                ("2024-08-01".to_string(), 2, 1),
+            ]
+        );
+        assert_eq!(stats[1].conclusions.get(&RunConclusion::Failure), Some(&1));
+        assert!((stats[1].success_rate - 0.5).abs() < f64::EPSILON);
+        assert!((stats[1].mean_duration_seconds - 360.0).abs() < f64::EPSILON);
+        assert_eq!(
+            repository.stats_repositories(day_before).await?,
+            vec![repo_full_name("owner/other"), repo_full_name("owner/repo")]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_days_before_the_recomputed_range_are_kept() -> anyhow::Result<()> {
+        let (_dir, repository) = temp_repository()?;
+        let day = base_time().date_naive();
+        repository
+            .save_runs(&[workflow_run("owner/repo", 1, "success")], base_time())
+            .await?;
+        repository.aggregate_daily_stats(day).await?;
+
+        let mut late = workflow_run("owner/repo", 2, "failure");
+        late.created_at -= TimeDelta::hours(1);
+        repository.save_runs(&[late], base_time()).await?;
+        repository
+            .aggregate_daily_stats(day + TimeDelta::days(1))
+            .await?;
+
+        let stats = repository
+            .daily_stats(&repo_full_name("owner/repo"), day)
+            .await?;
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].runs, 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_repositories_keep_their_first_pseudonym() -> anyhow::Result<()> {
+        let (dir, repository) = temp_repository()?;
+        repository
+            .save_pseudonym(&repo_full_name("Owner/Repo"), 1)
+            .await?;
+        repository
+            .save_pseudonym(&repo_full_name("owner/other"), 2)
+            .await?;
+        // Names differing only in case are the same repository
+        repository
+            .save_pseudonym(&repo_full_name("owner/repo"), 3)
+            .await?;
+
+        let reopened = SqliteRunRepository::open(dir.path().join("runs.db"))?;
+        assert_eq!(
+            reopened.pseudonyms().await?,
+            [
+                (repo_full_name("owner/repo"), 1),
+                (repo_full_name("owner/other"), 2),
+            ]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_discovered_repositories_are_kept_until_removed() -> anyhow::Result<()> {
+        let (dir, repository) = temp_repository()?;
+        for name in ["owner/b", "owner/a", "owner/b", "owner/gone"] {
+            repository
+                .save_discovered_repository(&repo_full_name(name))
+                .await?;
+        }
+        repository
+            .remove_discovered_repository(&repo_full_name("owner/gone"))
+            .await?;
+
+        let reopened = SqliteRunRepository::open(dir.path().join("runs.db"))?;
+        assert_eq!(
+            reopened.discovered_repositories().await?,
+            [repo_full_name("owner/a"), repo_full_name("owner/b")]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_push_subscriptions_are_replaced_by_endpoint() -> anyhow::Result<()> {
+        let (dir, repository) = temp_repository()?;
+        let subscription = |endpoint: &str, failures_only: bool| PushSubscription {
+            endpoint: endpoint.to_string(),
+            keys: PushKeys {
+                p256dh: "BKey".to_string(),
+                auth: "secret".to_string(),
+            },
+            filter: PushFilter {
+                failures_only,
+                ..PushFilter::default()
+            },
+        };
+        repository
+            .save_subscription(&subscription("https://push.example.com/a", true))
+            .await?;
+        repository
+            .save_subscription(&subscription("https://push.example.com/b", true))
+            .await?;
+        // Subscribing again updates the filter in place
+        repository
+            .save_subscription(&subscription("https://push.example.com/a", false))
+            .await?;
+
+        let reopened = SqliteRunRepository::open(dir.path().join("runs.db"))?;
+        assert_eq!(
+            reopened.subscriptions().await?,
+            [
+                subscription("https://push.example.com/a", false),
+                subscription("https://push.example.com/b", true),
+            ]
+        );
+
+        assert!(
+            reopened
+                .delete_subscription("https://push.example.com/a")
+                .await?
+        );
+        assert!(
+            !reopened
+                .delete_subscription("https://push.example.com/a")
+                .await?
+        );
+        assert_eq!(
+            reopened.subscriptions().await?,
+            [subscription("https://push.example.com/b", true)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_only_sqlite_urls_are_accepted() {
+        assert!(SqliteRunRepository::connect("sqlite::memory:").is_ok());
+        assert!(SqliteRunRepository::connect("postgres://localhost/runs").is_err());
+    }
+
+    async fn next_snapshot(
+        events: &mut broadcast::Receiver<PollerEvent>,
+    ) -> anyhow::Result<Arc<StreamGitHubActionsRunsUseCaseOutput>> {
+        loop {
+            if let PollerEvent::Snapshot(output) = events.recv().await? {
+                return Ok(output);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_snapshots_are_saved_and_restored_from_the_run_store() -> anyhow::Result<()> {
+        let run_repository = Arc::new(SqliteRunRepository::connect("sqlite::memory:")?);
+        let stored = vec![workflow_run("owner/repo", 1, "success")];
+        run_repository.save_runs(&stored, base_time()).await?;
+        let mut github_api = StubGitHubApi::with_repository("owner", "repo");
+        github_api.runs = vec![workflow_run("owner/repo", 2, "failure")];
+        let poller = Arc::new(
+            SharedPoller::new(Arc::new(Notify::new()))
+                .with_run_repository(Arc::clone(&run_repository) as _),
+        );
+        let mut events = poller.subscribe();
+
+        let handle = poller.spawn(Arc::new(StreamGitHubActionsRunsInteractor::new(Arc::new(
+            github_api,
+        ))));
+
+        let restored = timeout(Duration::from_secs(5), next_snapshot(&mut events)).await??;
+        assert_eq!(restored.runs, stored);
+        assert_eq!(restored.generated_at, base_time());
+        let fetched = timeout(Duration::from_secs(5), next_snapshot(&mut events)).await??;
+        assert_eq!(fetched.runs.len(), 1);
+        handle.abort();
+
+        let latest = run_repository.latest_snapshot().await?;
+        assert_eq!(
+            latest.map(|snapshot| snapshot.runs),
+            Some(fetched.runs.clone())
+        );
+        Ok(())
+    }
+}
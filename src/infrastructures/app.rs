@@ -0,0 +1,573 @@
+use crate::application::services::{
+    ApiStatusMonitor, DailyStatsAggregator, HistoryCompactor, NotificationFilter,
+    RepositoryDiscovery, RunNotifier, SharedPoller,
+};
+use crate::application::use_cases::cost_estimation::PriceTable;
+use crate::application::use_cases::stream_github_actions_runs::StreamGitHubActionsRunsInteractor;
+use crate::application::use_cases::stream_github_actions_runs::config::StreamConfig;
+use crate::domain::external_apis::github::GitHubApi;
+use crate::domain::models::slo::Slo;
+use crate::domain::repositories::{RetentionPolicy, RunRepository};
+use crate::infrastructures::adapters::primary::web::auth::ForwardedIdentityAuth;
+use crate::infrastructures::adapters::primary::web::ip_allowlist::IpCidr;
+use crate::infrastructures::adapters::primary::web::limits::RequestLimits;
+use crate::infrastructures::adapters::primary::web::presenter::TimestampFormat;
+use crate::infrastructures::adapters::primary::web::presenter::anonymizer::Anonymizer;
+use crate::infrastructures::adapters::primary::web::webhooks::GitHubWebhooks;
+use crate::infrastructures::adapters::primary::web::{AppState, create_router, json_error};
+use crate::infrastructures::adapters::secondary::external_apis::github::request_log::GitHubRequestLog;
+use crate::infrastructures::adapters::secondary::external_apis::github::retry_events::GitHubRetryEvents;
+use crate::infrastructures::adapters::secondary::notifications::WebPushNotifier;
+use crate::infrastructures::metrics::Metrics;
+use crate::infrastructures::replication::RedisReplication;
+use crate::infrastructures::telemetry::{
+    Redactor, TelemetryConfig, TelemetryGuard, init_telemetry,
+};
+use axum::Router;
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum::response::Response;
+use axum::routing::any;
+use std::io;
+use std::sync::Arc;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+
+/// Wires the poller, its background tasks and the HTTP routes of the dashboard.
+///
+/// Everything but the GitHub API is optional, so a host application can embed the dashboard
+/// with only the parts it needs:
+///
+/// ```no_run
+/// # async fn run(github_api: std::sync::Arc<dyn gha_dashboard::domain::external_apis::github::GitHubApi + Send + Sync>) -> anyhow::Result<()> {
+/// use gha_dashboard::infrastructures::app::AppBuilder;
+///
+/// let app = AppBuilder::new(github_api).with_auth_token("secret").build()?;
+/// let router = axum::Router::new().nest("/gha", app.router);
+/// # Ok(())
+/// # }
+/// ```
+pub struct AppBuilder {
+    github_api: Arc<dyn GitHubApi + Send + Sync>,
+    stream_config: StreamConfig,
+    auth_token: Option<String>,
+    forwarded_identity: Option<ForwardedIdentityAuth>,
+    telemetry: Option<TelemetryConfig>,
+    metrics: Option<Arc<Metrics>>,
+    trust_proxy: bool,
+    allowed_cidrs: Vec<IpCidr>,
+    request_limits: RequestLimits,
+    timestamp_format: TimestampFormat,
+    redactor: Redactor,
+    run_repository: Option<(Arc<dyn RunRepository + Send + Sync>, RetentionPolicy)>,
+    github_webhooks: Option<GitHubWebhooks>,
+    redis_url: Option<String>,
+    notifier: Option<RunNotifier>,
+    web_push: Option<WebPushNotifier>,
+    anonymizer: Option<Anonymizer>,
+    prices: PriceTable,
+    slos: Vec<Slo>,
+    github_requests: Option<Arc<GitHubRequestLog>>,
+    github_retries: Option<Arc<GitHubRetryEvents>>,
+    repository_discovery: RepositoryDiscovery,
+    routes: bool,
+}
+
+/// A built dashboard: routes to serve and the tasks keeping its snapshots fresh.
+pub struct App {
+    /// Routes relative to wherever they are mounted, e.g. `/ws` becomes `/gha/ws` when nested
+    /// under `/gha`
+    pub router: Router,
+    /// Publishes snapshots to the streams; drain it with `notify_clients` and
+    /// `disconnect_clients` before shutting down
+    pub poller: Arc<SharedPoller>,
+    /// The polling loop, or the replication loop electing a poller when Redis is set
+    pub poller_task: JoinHandle<()>,
+    /// The GitHub API status monitor, the runner inventory and, with a history, the compactor
+    /// and the daily stats aggregator
+    pub background_tasks: Vec<JoinHandle<()>>,
+    /// Notification target of the browsers subscribed through `/push/subscribe`, to keep
+    /// when the notifier is replaced; `None` unless built with Web Push
+    pub web_push: Option<Arc<WebPushNotifier>>,
+    /// Flushes exported traces when dropped; `None` unless built with telemetry
+    pub telemetry: Option<TelemetryGuard>,
+}
+
+impl AppBuilder {
+    /// A dashboard polling `github_api` with the default stream configuration.
+    #[must_use]
+    pub fn new(github_api: Arc<dyn GitHubApi + Send + Sync>) -> Self {
+        Self {
+            github_api,
+            stream_config: StreamConfig::default(),
+            auth_token: None,
+            forwarded_identity: None,
+            telemetry: None,
+            metrics: None,
+            trust_proxy: false,
+            allowed_cidrs: Vec::new(),
+            request_limits: RequestLimits::default(),
+            timestamp_format: TimestampFormat::default(),
+            redactor: Redactor::default(),
+            run_repository: None,
+            github_webhooks: None,
+            redis_url: None,
+            notifier: None,
+            web_push: None,
+            anonymizer: None,
+            prices: PriceTable::default(),
+            slos: Vec::new(),
+            github_requests: None,
+            github_retries: None,
+            repository_discovery: RepositoryDiscovery::default(),
+            routes: true,
+        }
+    }
+
+    /// What is polled and how often; still adjustable at runtime through `/admin/config`.
+    #[must_use]
+    pub fn with_stream_config(mut self, stream_config: StreamConfig) -> Self {
+        self.stream_config = stream_config;
+        self
+    }
+
+    /// Bearer token protecting mutating endpoints such as `/refresh`; open to everyone without.
+    #[must_use]
+    pub fn with_auth_token(mut self, auth_token: impl Into<String>) -> Self {
+        self.auth_token = Some(auth_token.into());
+        self
+    }
+
+    /// Requires on every route the user an authenticating proxy such as oauth2-proxy forwards
+    /// in `X-Forwarded-User`, member of one of `allowed_groups` in `X-Forwarded-Groups` unless
+    /// empty, instead of the bearer token. Only safe with `with_trust_proxy(true)`.
+    #[must_use]
+    pub fn with_forwarded_identity(mut self, allowed_groups: Vec<String>) -> Self {
+        self.forwarded_identity = Some(ForwardedIdentityAuth { allowed_groups });
+        self
+    }
+
+    /// Installs the global tracing subscriber on build; leave unset when the host has its own.
+    #[must_use]
+    pub fn with_telemetry(mut self, telemetry: TelemetryConfig) -> Self {
+        self.telemetry = Some(telemetry);
+        self
+    }
+
+    /// Shares a registry with the GitHub API adapter instead of creating one on build.
+    #[must_use]
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    #[must_use]
+    pub fn with_trust_proxy(mut self, trust_proxy: bool) -> Self {
+        self.trust_proxy = trust_proxy;
+        self
+    }
+
+    /// Only serves clients whose address is in one of `allowed_cidrs`; serve the router with
+    /// `into_make_service_with_connect_info::<SocketAddr>()` so the peer address is known.
+    #[must_use]
+    pub fn with_allowed_cidrs(mut self, allowed_cidrs: Vec<IpCidr>) -> Self {
+        self.allowed_cidrs = allowed_cidrs;
+        self
+    }
+
+    /// Cuts off slow handlers with 504 and sheds requests beyond a number in flight with 503.
+    #[must_use]
+    pub fn with_request_limits(mut self, request_limits: RequestLimits) -> Self {
+        self.request_limits = request_limits;
+        self
+    }
+
+    #[must_use]
+    pub fn with_timestamp_format(mut self, timestamp_format: TimestampFormat) -> Self {
+        self.timestamp_format = timestamp_format;
+        self
+    }
+
+    /// Redacts the error frames sent to websocket clients when `redactor` is strict.
+    #[must_use]
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    /// Records snapshots into `run_repository`, compacted hourly by `retention_policy` and
+    /// rolled up into the daily stats of `/trends`.
+    #[must_use]
+    pub fn with_run_repository(
+        mut self,
+        run_repository: Arc<dyn RunRepository + Send + Sync>,
+        retention_policy: RetentionPolicy,
+    ) -> Self {
+        self.run_repository = Some((run_repository, retention_policy));
+        self
+    }
+
+    /// Accepts `workflow_run` events on `/webhooks/github`.
+    #[must_use]
+    pub fn with_github_webhooks(mut self, github_webhooks: GitHubWebhooks) -> Self {
+        self.github_webhooks = Some(github_webhooks);
+        self
+    }
+
+    /// Elects one poller among the replicas sharing `redis_url`.
+    #[must_use]
+    pub fn with_redis_replication(mut self, redis_url: impl Into<String>) -> Self {
+        self.redis_url = Some(redis_url.into());
+        self
+    }
+
+    /// Reports runs that change between two polls to the targets of `notifier`.
+    #[must_use]
+    pub fn with_notifier(mut self, notifier: RunNotifier) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    /// Serves `/push/subscribe` and `/push/vapid-public-key`, and pushes the runs that change
+    /// to the subscribed browsers along with the targets of [`Self::with_notifier`].
+    #[must_use]
+    pub fn with_web_push(mut self, web_push: WebPushNotifier) -> Self {
+        self.web_push = Some(web_push);
+        self
+    }
+
+    /// Serves `/ws`, `/sse`, `/runs` and `/history` through `anonymizer` for a public demo,
+    /// and requires the bearer token on every other route naming repositories.
+    #[must_use]
+    pub fn with_anonymizer(mut self, anonymizer: Anonymizer) -> Self {
+        self.anonymizer = Some(anonymizer);
+        self
+    }
+
+    /// Prices `/costs` estimates with `prices` instead of GitHub's list prices.
+    #[must_use]
+    pub fn with_price_table(mut self, prices: PriceTable) -> Self {
+        self.prices = prices;
+        self
+    }
+
+    /// Evaluates `slos` over the run history for `/slos` and the SLO gauges of `/metrics`;
+    /// they need [`Self::with_run_repository`].
+    #[must_use]
+    pub fn with_slos(mut self, slos: Vec<Slo>) -> Self {
+        self.slos = slos;
+        self
+    }
+
+    /// Serves `github_requests` at `/admin/github-requests`; give the GitHub adapter the same
+    /// log so it fills it.
+    #[must_use]
+    pub fn with_github_requests(mut self, github_requests: Arc<GitHubRequestLog>) -> Self {
+        self.github_requests = Some(github_requests);
+        self
+    }
+
+    /// Serves the health `github_retries` tracks at `/status` and its events to websocket
+    /// clients asking for them; give the GitHub adapter the same events so it publishes them.
+    #[must_use]
+    pub fn with_github_retries(mut self, github_retries: Arc<GitHubRetryEvents>) -> Self {
+        self.github_retries = Some(github_retries);
+        self
+    }
+
+    /// Tracks the repositories listed while polling, announcing the ones that appear or go
+    /// away to the clients and the targets of [`Self::with_notifier`].
+    #[must_use]
+    pub fn with_repository_discovery(mut self, repository_discovery: RepositoryDiscovery) -> Self {
+        self.repository_discovery = repository_discovery;
+        self
+    }
+
+    /// Only polls, e.g. to export the snapshots elsewhere: the router is left empty, and the
+    /// runner and deployment inventories only its routes serve are not taken.
+    #[must_use]
+    pub fn without_routes(mut self) -> Self {
+        self.routes = false;
+        self
+    }
+
+    /// Spawns the poller and its background tasks and builds the router.
+    ///
+    /// Must be called from within a tokio runtime; the tasks run until aborted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if telemetry is requested while a subscriber is already installed, or
+    /// the metrics registry or the Redis client cannot be created.
+    pub fn build(self) -> anyhow::Result<App> {
+        let telemetry = self
+            .telemetry
+            .as_ref()
+            .map(|telemetry| init_telemetry(telemetry, io::stdout))
+            .transpose()?;
+        let metrics = match self.metrics {
+            Some(metrics) => metrics,
+            None => Arc::new(Metrics::new()?),
+        };
+        if self.auth_token.is_none() && self.forwarded_identity.is_none() {
+            tracing::warn!("No auth token is set, authenticated endpoints are open to everyone");
+        }
+        let stream_use_case = Arc::new(StreamGitHubActionsRunsInteractor::new(Arc::clone(
+            &self.github_api,
+        )));
+
+        let refresh = Arc::new(Notify::new());
+        let mut poller = SharedPoller::new(Arc::clone(&refresh))
+            .with_config(self.stream_config)
+            .with_repository_discovery(self.repository_discovery);
+        poller = with_metrics_hooks(poller, &metrics);
+        if let Some((run_repository, _)) = &self.run_repository {
+            poller = poller.with_run_repository(Arc::clone(run_repository));
+        }
+        let web_push = self.web_push.map(Arc::new);
+        if let Some(notifier) = notifier_with_web_push(self.notifier, web_push.as_ref()) {
+            poller = poller.with_notifier(Arc::new(notifier));
+        }
+        let poller = Arc::new(poller);
+        let poller_task = match &self.redis_url {
+            Some(url) => {
+                Arc::new(RedisReplication::new(url)?).spawn(Arc::clone(&poller), stream_use_case)
+            }
+            None => poller.spawn(stream_use_case),
+        };
+        let mut background_tasks = vec![
+            ApiStatusMonitor::default().spawn(Arc::clone(&self.github_api), Arc::clone(&poller)),
+        ];
+        let history = self
+            .run_repository
+            .map(|(run_repository, retention_policy)| {
+                let (history_compactor, tasks) =
+                    spawn_history(&run_repository, retention_policy, Arc::clone(&metrics));
+                background_tasks.extend(tasks);
+                (run_repository, history_compactor)
+            });
+        let mut app = App {
+            router: Router::new(),
+            poller,
+            poller_task,
+            background_tasks,
+            web_push,
+            telemetry,
+        };
+        if !self.routes {
+            return Ok(app);
+        }
+
+        let mut app_state = AppState::new(
+            Arc::clone(&app.poller),
+            refresh,
+            self.github_api,
+            self.auth_token,
+            metrics,
+        )
+        .with_trust_proxy(self.trust_proxy)
+        .with_allowed_cidrs(self.allowed_cidrs)
+        .with_request_limits(self.request_limits)
+        .with_timestamp_format(self.timestamp_format)
+        .with_redactor(self.redactor)
+        .with_price_table(self.prices)
+        .with_slos(self.slos);
+        if let Some(forwarded_identity) = self.forwarded_identity {
+            app_state = app_state.with_forwarded_identity(forwarded_identity);
+        }
+        if let Some(github_requests) = self.github_requests {
+            app_state = app_state.with_github_requests(github_requests);
+        }
+        if let Some(github_retries) = self.github_retries {
+            app_state = app_state.with_github_retries(github_retries);
+        }
+        app.background_tasks.extend([
+            Arc::clone(&app_state.runner_inventory).spawn(Arc::clone(&app.poller)),
+            Arc::clone(&app_state.deployment_inventory).spawn(Arc::clone(&app.poller)),
+        ]);
+        if let Some((run_repository, history_compactor)) = history {
+            app_state = app_state
+                .with_run_repository(run_repository)
+                .with_history_compactor(history_compactor);
+        }
+        if let Some(github_webhooks) = self.github_webhooks {
+            app_state = app_state.with_github_webhooks(Arc::new(github_webhooks));
+        }
+        if let Some(web_push) = &app.web_push {
+            app_state = app_state.with_web_push(Arc::clone(web_push));
+        }
+        if let Some(anonymizer) = self.anonymizer {
+            app_state = app_state.with_anonymizer(Arc::new(anonymizer));
+        }
+
+        app.router = create_router(Arc::new(app_state));
+        Ok(app)
+    }
+}
+
+/// `poller` recording its reconciliations and panics in `metrics`.
+fn with_metrics_hooks(poller: SharedPoller, metrics: &Arc<Metrics>) -> SharedPoller {
+    let (reconciliation_metrics, panic_metrics) = (Arc::clone(metrics), Arc::clone(metrics));
+    poller
+        .with_reconciliation_hook(move |report| {
+            reconciliation_metrics.record_reconciliation(report);
+        })
+        .with_panic_hook(move || panic_metrics.panics.with_label_values(&["poller"]).inc())
+}
+
+/// Spawns the compaction of `run_repository` by `retention_policy`, recorded in `metrics`,
+/// and the aggregation of its daily stats.
+fn spawn_history(
+    run_repository: &Arc<dyn RunRepository + Send + Sync>,
+    retention_policy: RetentionPolicy,
+    metrics: Arc<Metrics>,
+) -> (Arc<HistoryCompactor>, [JoinHandle<()>; 2]) {
+    let history_compactor = Arc::new(HistoryCompactor::new(
+        Arc::clone(run_repository),
+        retention_policy,
+    ));
+    let tasks = [
+        history_compactor.spawn(move |report| metrics.record_compaction(report)),
+        Arc::new(DailyStatsAggregator::new(Arc::clone(run_repository))).spawn(),
+    ];
+    (history_compactor, tasks)
+}
+
+/// `notifier` with the browsers subscribed to `web_push` as one more target, if any.
+fn notifier_with_web_push(
+    notifier: Option<RunNotifier>,
+    web_push: Option<&Arc<WebPushNotifier>>,
+) -> Option<RunNotifier> {
+    match web_push {
+        // Each subscription filters the transitions itself
+        Some(web_push) => Some(
+            notifier
+                .unwrap_or_default()
+                .with_target(Arc::clone(web_push) as _, NotificationFilter::default()),
+        ),
+        None => notifier,
+    }
+}
+
+/// Serves each tenant's routes under `/t/{name}`, e.g. `/t/frontend/ws`; a path of any other
+/// tenant is a 404.
+pub fn tenant_router(tenants: impl IntoIterator<Item = (String, Router)>) -> Router {
+    tenants
+        .into_iter()
+        .fold(Router::new(), |router, (name, tenant)| {
+            router.nest(&format!("/t/{name}"), tenant)
+        })
+        .route("/t/{tenant}", any(unknown_tenant_handler))
+        .route("/t/{tenant}/{*path}", any(unknown_tenant_handler))
+}
+
+async fn unknown_tenant_handler(Path(params): Path<Vec<(String, String)>>) -> Response {
+    let tenant = params
+        .into_iter()
+        .find_map(|(key, value)| (key == "tenant").then_some(value))
+        .unwrap_or_default();
+    json_error(StatusCode::NOT_FOUND, &format!("Unknown tenant {tenant:?}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::external_apis::github::Repository;
+    use crate::domain::models::ids::RepoFullName;
+    use crate::testing::{ScriptedGitHubApi, WorkflowRunBuilder};
+    use futures_util::StreamExt;
+    use std::net::SocketAddr;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_router_nested_under_a_prefix_streams_snapshots() -> anyhow::Result<()> {
+        let repo = RepoFullName::new("owner", "repo")?;
+        let github_api = ScriptedGitHubApi::new();
+        github_api
+            .repositories(Ok(vec![Repository::from_full_name(&repo)]))
+            .workflow_runs(
+                &repo,
+                Ok(vec![WorkflowRunBuilder::new("owner/repo").build()]),
+            );
+        let app = AppBuilder::new(Arc::new(github_api)).build()?;
+        let router = Router::new().nest("/gha", app.router);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let server = tokio::spawn(async move {
+            axum::serve(
+                listener,
+                router.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .await
+        });
+
+        let health = reqwest::get(format!("http://{addr}/gha/health")).await?;
+        let unprefixed = reqwest::get(format!("http://{addr}/health")).await?;
+        let (mut socket, _) =
+            tokio_tungstenite::connect_async(format!("ws://{addr}/gha/ws")).await?;
+        let message = tokio::time::timeout(Duration::from_secs(5), socket.next())
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("websocket closed"))??;
+
+        assert_eq!(health.status(), reqwest::StatusCode::OK);
+        assert_eq!(unprefixed.status(), reqwest::StatusCode::NOT_FOUND);
+        assert!(message.into_text()?.contains("owner/repo"));
+        server.abort();
+        app.poller_task.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_tenants_are_served_apart() -> anyhow::Result<()> {
+        let mut apis = Vec::new();
+        let mut tenants = Vec::new();
+        for name in ["frontend", "backend"] {
+            let repo = RepoFullName::new("owner", name)?;
+            let github_api = Arc::new(ScriptedGitHubApi::new());
+            github_api
+                .repositories(Ok(vec![Repository::from_full_name(&repo)]))
+                .workflow_runs(
+                    &repo,
+                    Ok(vec![
+                        WorkflowRunBuilder::new(&format!("owner/{name}")).build(),
+                    ]),
+                );
+            let app = AppBuilder::new(Arc::clone(&github_api) as Arc<dyn GitHubApi + Send + Sync>)
+                .with_metrics(Arc::new(Metrics::for_tenant(name)?))
+                .build()?;
+            apis.push(github_api);
+            tenants.push((name.to_string(), app.router));
+        }
+        let router = tenant_router(tenants);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let server = tokio::spawn(async move {
+            axum::serve(
+                listener,
+                router.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .await
+        });
+
+        let mut messages = Vec::new();
+        for name in ["frontend", "backend"] {
+            let (mut socket, _) =
+                tokio_tungstenite::connect_async(format!("ws://{addr}/t/{name}/ws")).await?;
+            let message = tokio::time::timeout(Duration::from_secs(5), socket.next())
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("websocket closed"))??;
+            messages.push(message.into_text()?.to_string());
+        }
+        let unknown = reqwest::get(format!("http://{addr}/t/unknown/ws")).await?;
+
+        assert!(messages[0].contains("owner/frontend") && !messages[0].contains("owner/backend"));
+        assert!(messages[1].contains("owner/backend") && !messages[1].contains("owner/frontend"));
+        assert_eq!(unknown.status(), reqwest::StatusCode::NOT_FOUND);
+        let calls: Vec<Vec<String>> = apis.iter().map(|api| api.calls()).collect();
+        assert!(calls[0].iter().all(|call| !call.contains("owner/backend")));
+        assert!(calls[1].iter().all(|call| !call.contains("owner/frontend")));
+        assert!(calls[0].iter().any(|call| call.contains("owner/frontend")));
+        server.abort();
+        Ok(())
+    }
+}
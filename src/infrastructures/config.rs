@@ -0,0 +1,2258 @@
+use crate::application::services::NotificationFilter;
+use crate::application::services::log_tail::{
+    DEFAULT_LOG_TAIL_CHUNK_BYTES, DEFAULT_LOG_TAIL_MAX_BYTES,
+};
+use crate::application::use_cases::cost_estimation::PriceTable;
+use crate::application::use_cases::stream_github_actions_runs::config::{
+    StreamConfig, StreamConfigPatch, WorkflowFilterPatch,
+};
+use crate::domain::models::glob::GlobPattern;
+use crate::domain::models::ids::RepoFullName;
+use crate::domain::models::label::LabelRule;
+use crate::domain::models::slo::Slo;
+use crate::domain::repositories::RetentionPolicy;
+use crate::infrastructures::adapters::primary::web::heartbeat::DEFAULT_WEBSOCKET_HEARTBEAT_INTERVAL;
+use crate::infrastructures::adapters::primary::web::ip_allowlist::{InvalidCidr, IpCidr};
+use crate::infrastructures::adapters::primary::web::limits::{
+    DEFAULT_LATEST_RUNS_MAX_REPOS, DEFAULT_MAX_CONCURRENT_REQUESTS, DEFAULT_REQUEST_TIMEOUT,
+    DEFAULT_SLOW_REQUEST_TIMEOUT, DEFAULT_WEBSOCKET_MAX_FRAME_BYTES,
+    DEFAULT_WEBSOCKET_MAX_MESSAGE_BYTES, RequestLimits,
+};
+use crate::infrastructures::adapters::primary::web::pins::DEFAULT_WEBSOCKET_MAX_PINNED_RUNS;
+use crate::infrastructures::adapters::primary::web::presenter::TimestampFormat;
+use crate::infrastructures::adapters::primary::web::server::{
+    BindAddr, DEFAULT_SOCKET_MODE, parse_socket_mode,
+};
+use crate::infrastructures::adapters::secondary::external_apis::github::DEFAULT_RETRY_DEADLINE;
+use crate::infrastructures::adapters::secondary::notifications::webhook::header_map;
+use crate::infrastructures::adapters::secondary::notifications::{BodyTemplate, InvalidWebhook};
+use crate::infrastructures::telemetry::{TelemetryConfig, otlp};
+use chrono::TimeDelta;
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Deserializer};
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::fmt::{self, Display};
+use std::net::SocketAddr;
+use std::num::{NonZeroU64, NonZeroUsize};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Config file read from the working directory when neither `--config` nor `GHA_CONFIG` is given
+pub const DEFAULT_CONFIG_PATH: &str = "gha-dashboard.toml";
+
+/// Days stored runs are kept unless configured otherwise
+const DEFAULT_HISTORY_RETENTION_DAYS: u32 = 90;
+
+/// Minimum wait between two notifications about one workflow unless configured otherwise
+const DEFAULT_NOTIFY_COOLDOWN_SECONDS: u64 = 600;
+
+/// Looks up an environment variable; a parameter so tests need not touch the process environment.
+pub type Env<'a> = &'a dyn Fn(&str) -> Option<String>;
+
+/// Why the configuration could not be loaded.
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("Failed to read config file {}: {source}", .path.display())]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// The file is not valid TOML or a value has the wrong type; `message` locates it
+    #[error("Invalid config file {}: {message}", .path.display())]
+    Parse { path: PathBuf, message: String },
+    /// `key` is a file key such as `polling` or the environment variable that set the value
+    #[error("Invalid {key}: {message}")]
+    Invalid { key: String, message: String },
+    #[error("{key} is required, set it in the config file or with {env}")]
+    Missing {
+        key: &'static str,
+        env: &'static str,
+    },
+}
+
+/// A credential whose `Debug` output is redacted, so the effective config can be logged.
+#[derive(Deserialize, Clone, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct Secret(String);
+
+impl Secret {
+    #[must_use]
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for Secret {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("\"[redacted]\"")
+    }
+}
+
+/// Every setting of the application: built-in defaults, overridden by the config file,
+/// overridden in turn by environment variables.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub github: GitHubConfig,
+    pub polling: PollingConfig,
+    pub filters: FiltersConfig,
+    pub history: HistoryConfig,
+    pub replication: ReplicationConfig,
+    pub push: PushConfig,
+    /// `[[notifications]]` targets, plus those set by environment variables
+    pub notifications: Vec<NotificationTargetConfig>,
+    /// `[[labels]]`: rules labelling runs, e.g. `name = "release"` with
+    /// `match = { branch = "release/*" }`
+    pub labels: Vec<LabelRule>,
+    pub telemetry: TelemetryConfig,
+    /// Prices `/costs` estimates with
+    pub costs: PriceTable,
+    /// `[[slos]]`: objectives evaluated over the run history by `/slos`, e.g. `repo`, `workflow`
+    /// and `branch = "main"` with `target_duration_minutes = 30`
+    pub slos: Vec<Slo>,
+    /// `[[tenants]]`: dashboards served side by side under `/t/{name}` by `serve`, each with
+    /// its own token and polling; `serve` then polls nothing for the top-level settings
+    pub tenants: Vec<TenantConfig>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct ServerConfig {
+    /// `host:port` or `unix:/path` (`BIND_ADDR`)
+    #[serde(deserialize_with = "from_str")]
+    pub bind_addr: BindAddr,
+    /// Octal permissions of a Unix socket such as `"660"` (`BIND_SOCKET_MODE`)
+    #[serde(deserialize_with = "socket_mode")]
+    pub socket_mode: u32,
+    /// `bearer` (default) protects mutating endpoints with `auth_token`; `forward_headers`
+    /// requires on every route the user an authenticating proxy forwards, and `trust_proxy`
+    /// (`AUTH_MODE`)
+    pub auth_mode: AuthMode,
+    /// Bearer token required by mutating endpoints; unset leaves them open (`AUTH_TOKEN`)
+    pub auth_token: Option<Secret>,
+    /// Groups in `X-Forwarded-Groups` let in with `auth_mode = "forward_headers"`; empty
+    /// lets in every forwarded user (`ALLOWED_GROUPS`, comma-separated)
+    pub allowed_groups: Vec<String>,
+    /// Trust `Forwarded` and `X-Forwarded-For`; enable only behind a reverse proxy
+    /// (`TRUST_PROXY`)
+    pub trust_proxy: bool,
+    /// Client addresses allowed to connect, e.g. `["10.0.0.0/8", "fd00::/8"]`; empty allows
+    /// everyone (`ALLOWED_CIDRS`, comma-separated)
+    pub allowed_cidrs: Vec<IpCidr>,
+    /// `rfc3339` or `millis`; clients may still override it with `?ts=` (`TIMESTAMP_FORMAT`)
+    pub timestamp_format: TimestampFormat,
+    /// Time handlers of snapshot routes such as `/runs` may take before a 504
+    /// (`REQUEST_TIMEOUT_SECONDS`)
+    pub request_timeout_seconds: NonZeroU64,
+    /// Time handlers of `/history` and routes calling GitHub, such as job details, may take
+    /// before a 504 (`SLOW_REQUEST_TIMEOUT_SECONDS`)
+    pub slow_request_timeout_seconds: NonZeroU64,
+    /// Requests handled at once; more get 503 (`MAX_CONCURRENT_REQUESTS`)
+    pub max_concurrent_requests: NonZeroUsize,
+    /// Largest message a websocket client may send before it is disconnected
+    /// (`WEBSOCKET_MAX_MESSAGE_BYTES`)
+    pub websocket_max_message_bytes: NonZeroUsize,
+    /// Largest frame a websocket client may send before it is disconnected
+    /// (`WEBSOCKET_MAX_FRAME_BYTES`)
+    pub websocket_max_frame_bytes: NonZeroUsize,
+    /// Seconds between two heartbeats sent to a websocket client, which closes it with 1001
+    /// after three unacknowledged ones (`WEBSOCKET_HEARTBEAT_SECONDS`)
+    pub websocket_heartbeat_seconds: NonZeroU64,
+    /// Runs one websocket client may pin at once (`WEBSOCKET_MAX_PINNED_RUNS`)
+    pub websocket_max_pinned_runs: NonZeroUsize,
+    /// Largest `log_chunk` frame of a websocket log tail, in bytes of log
+    /// (`LOG_TAIL_CHUNK_BYTES`)
+    pub log_tail_chunk_bytes: NonZeroUsize,
+    /// Bytes of log a websocket log tail sends before it ends (`LOG_TAIL_MAX_BYTES`)
+    pub log_tail_max_bytes: NonZeroUsize,
+    /// Repositories one `POST /runs/latest` request may name; more get 400
+    /// (`LATEST_RUNS_MAX_REPOS`)
+    pub latest_runs_max_repos: NonZeroUsize,
+    /// `host:port` of the gRPC server; unset serves no gRPC. Needs the `grpc` feature
+    /// (`GRPC_BIND_ADDR`)
+    pub grpc_bind_addr: Option<SocketAddr>,
+    /// What `serve` does when the configured repositories and workflow filters do not match
+    /// GitHub: `strict` exits, `warn` logs and starts anyway, `off` skips the checks
+    /// (`PREFLIGHT`)
+    pub preflight: PreflightMode,
+    /// Serve `/ws`, `/sse`, `/runs` and `/history` with pseudonyms instead of repository
+    /// names, logins, titles and links, for a public demo; every other route then requires
+    /// `auth_token` (`ANONYMIZE`)
+    pub anonymize: bool,
+    /// `dashboard` (default) serves the dashboard; `exporter` only polls, writing every
+    /// snapshot to `exporter_output`, and serves `/livez` and `/metrics` (`MODE`)
+    pub mode: ServerMode,
+    /// Where `mode = "exporter"` writes snapshots: `stdout` (default) as JSON lines, or
+    /// `redis` on the channel of `replication.redis_url` (`EXPORTER_OUTPUT`)
+    pub exporter_output: ExporterOutput,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            bind_addr: BindAddr::default(),
+            socket_mode: DEFAULT_SOCKET_MODE,
+            auth_mode: AuthMode::default(),
+            auth_token: None,
+            allowed_groups: Vec::new(),
+            trust_proxy: false,
+            allowed_cidrs: Vec::new(),
+            timestamp_format: TimestampFormat::default(),
+            request_timeout_seconds: NonZeroU64::new(DEFAULT_REQUEST_TIMEOUT.as_secs())
+                .unwrap_or(NonZeroU64::MIN),
+            slow_request_timeout_seconds: NonZeroU64::new(DEFAULT_SLOW_REQUEST_TIMEOUT.as_secs())
+                .unwrap_or(NonZeroU64::MIN),
+            max_concurrent_requests: NonZeroUsize::new(DEFAULT_MAX_CONCURRENT_REQUESTS)
+                .unwrap_or(NonZeroUsize::MIN),
+            websocket_max_message_bytes: NonZeroUsize::new(DEFAULT_WEBSOCKET_MAX_MESSAGE_BYTES)
+                .unwrap_or(NonZeroUsize::MIN),
+            websocket_max_frame_bytes: NonZeroUsize::new(DEFAULT_WEBSOCKET_MAX_FRAME_BYTES)
+                .unwrap_or(NonZeroUsize::MIN),
+            websocket_heartbeat_seconds: NonZeroU64::new(
+                DEFAULT_WEBSOCKET_HEARTBEAT_INTERVAL.as_secs(),
+            )
+            .unwrap_or(NonZeroU64::MIN),
+            websocket_max_pinned_runs: NonZeroUsize::new(DEFAULT_WEBSOCKET_MAX_PINNED_RUNS)
+                .unwrap_or(NonZeroUsize::MIN),
+            log_tail_chunk_bytes: NonZeroUsize::new(DEFAULT_LOG_TAIL_CHUNK_BYTES)
+                .unwrap_or(NonZeroUsize::MIN),
+            log_tail_max_bytes: NonZeroUsize::new(DEFAULT_LOG_TAIL_MAX_BYTES)
+                .unwrap_or(NonZeroUsize::MIN),
+            latest_runs_max_repos: NonZeroUsize::new(DEFAULT_LATEST_RUNS_MAX_REPOS)
+                .unwrap_or(NonZeroUsize::MIN),
+            grpc_bind_addr: None,
+            preflight: PreflightMode::default(),
+            anonymize: false,
+            mode: ServerMode::default(),
+            exporter_output: ExporterOutput::default(),
+        }
+    }
+}
+
+impl ServerConfig {
+    fn apply_env(&mut self, env: Env) -> Result<(), ConfigError> {
+        let server = self;
+        override_from_env(env, "BIND_ADDR", &mut server.bind_addr, str::parse)?;
+        override_from_env(
+            env,
+            "BIND_SOCKET_MODE",
+            &mut server.socket_mode,
+            parse_socket_mode,
+        )?;
+        override_from_env(env, "AUTH_MODE", &mut server.auth_mode, str::parse)?;
+        override_secret_from_env(env, "AUTH_TOKEN", &mut server.auth_token);
+        override_from_env(env, "ALLOWED_GROUPS", &mut server.allowed_groups, |value| {
+            Ok::<_, Infallible>(string_list(value))
+        })?;
+        override_from_env(env, "TRUST_PROXY", &mut server.trust_proxy, str::parse)?;
+        override_from_env(env, "ALLOWED_CIDRS", &mut server.allowed_cidrs, cidr_list)?;
+        override_from_env(
+            env,
+            "TIMESTAMP_FORMAT",
+            &mut server.timestamp_format,
+            str::parse,
+        )?;
+        override_from_env(
+            env,
+            "REQUEST_TIMEOUT_SECONDS",
+            &mut server.request_timeout_seconds,
+            str::parse,
+        )?;
+        override_from_env(
+            env,
+            "SLOW_REQUEST_TIMEOUT_SECONDS",
+            &mut server.slow_request_timeout_seconds,
+            str::parse,
+        )?;
+        override_from_env(
+            env,
+            "MAX_CONCURRENT_REQUESTS",
+            &mut server.max_concurrent_requests,
+            str::parse,
+        )?;
+        override_from_env(
+            env,
+            "WEBSOCKET_MAX_MESSAGE_BYTES",
+            &mut server.websocket_max_message_bytes,
+            str::parse,
+        )?;
+        override_from_env(
+            env,
+            "WEBSOCKET_MAX_FRAME_BYTES",
+            &mut server.websocket_max_frame_bytes,
+            str::parse,
+        )?;
+        override_from_env(
+            env,
+            "WEBSOCKET_HEARTBEAT_SECONDS",
+            &mut server.websocket_heartbeat_seconds,
+            str::parse,
+        )?;
+        override_from_env(
+            env,
+            "WEBSOCKET_MAX_PINNED_RUNS",
+            &mut server.websocket_max_pinned_runs,
+            str::parse,
+        )?;
+        override_from_env(
+            env,
+            "LOG_TAIL_CHUNK_BYTES",
+            &mut server.log_tail_chunk_bytes,
+            str::parse,
+        )?;
+        override_from_env(
+            env,
+            "LOG_TAIL_MAX_BYTES",
+            &mut server.log_tail_max_bytes,
+            str::parse,
+        )?;
+        override_from_env(
+            env,
+            "LATEST_RUNS_MAX_REPOS",
+            &mut server.latest_runs_max_repos,
+            str::parse,
+        )?;
+        override_from_env(env, "GRPC_BIND_ADDR", &mut server.grpc_bind_addr, some)?;
+        override_from_env(env, "PREFLIGHT", &mut server.preflight, str::parse)?;
+        override_from_env(env, "ANONYMIZE", &mut server.anonymize, str::parse)?;
+        override_from_env(env, "MODE", &mut server.mode, str::parse)?;
+        override_from_env(
+            env,
+            "EXPORTER_OUTPUT",
+            &mut server.exporter_output,
+            str::parse,
+        )?;
+        Ok(())
+    }
+
+    /// Handler timeouts, concurrency and websocket message sizes to serve the routes with.
+    #[must_use]
+    pub fn request_limits(&self) -> RequestLimits {
+        RequestLimits {
+            timeout: Duration::from_secs(self.request_timeout_seconds.get()),
+            slow_timeout: Duration::from_secs(self.slow_request_timeout_seconds.get()),
+            max_concurrent: self.max_concurrent_requests.get(),
+            websocket_max_message_bytes: self.websocket_max_message_bytes.get(),
+            websocket_max_frame_bytes: self.websocket_max_frame_bytes.get(),
+            websocket_heartbeat_interval: Duration::from_secs(
+                self.websocket_heartbeat_seconds.get(),
+            ),
+            websocket_max_pinned_runs: self.websocket_max_pinned_runs.get(),
+            log_tail_chunk_bytes: self.log_tail_chunk_bytes.get(),
+            log_tail_max_bytes: self.log_tail_max_bytes.get(),
+            latest_runs_max_repos: self.latest_runs_max_repos.get(),
+        }
+    }
+}
+
+/// Where GitHub data comes from.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum GitHubApiMode {
+    /// The GitHub REST API
+    #[default]
+    Live,
+    /// Generated runs that evolve over time; needs no token or network access
+    Mock,
+}
+
+impl FromStr for GitHubApiMode {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "live" => Ok(Self::Live),
+            "mock" => Ok(Self::Mock),
+            _ => anyhow::bail!("Invalid GitHub API mode {value:?}, expected live or mock"),
+        }
+    }
+}
+
+/// How requests are authenticated.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthMode {
+    /// `AUTH_TOKEN` as a bearer token on mutating endpoints, or no authentication without it
+    #[default]
+    Bearer,
+    /// `X-Forwarded-User` and `X-Forwarded-Groups` of an authenticating proxy on every route
+    ForwardHeaders,
+}
+
+impl FromStr for AuthMode {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "bearer" => Ok(Self::Bearer),
+            "forward_headers" => Ok(Self::ForwardHeaders),
+            _ => anyhow::bail!("Invalid auth mode {value:?}, expected bearer or forward_headers"),
+        }
+    }
+}
+
+/// What the startup preflight does with the problems it finds.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PreflightMode {
+    /// Report them all and exit non-zero
+    #[default]
+    Strict,
+    /// Report them all and start anyway
+    Warn,
+    /// Check nothing
+    Off,
+}
+
+impl FromStr for PreflightMode {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "strict" => Ok(Self::Strict),
+            "warn" => Ok(Self::Warn),
+            "off" => Ok(Self::Off),
+            _ => anyhow::bail!("Invalid preflight mode {value:?}, expected strict, warn or off"),
+        }
+    }
+}
+
+/// What `serve` runs.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ServerMode {
+    /// The poller and every route of the dashboard
+    #[default]
+    Dashboard,
+    /// The poller exporting its snapshots, with only `/livez` and `/metrics` served
+    Exporter,
+}
+
+impl FromStr for ServerMode {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "dashboard" => Ok(Self::Dashboard),
+            "exporter" => Ok(Self::Exporter),
+            _ => anyhow::bail!("Invalid mode {value:?}, expected dashboard or exporter"),
+        }
+    }
+}
+
+/// Where the exporter writes snapshots.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ExporterOutput {
+    /// One JSON line per snapshot on stdout, logs going to stderr
+    #[default]
+    Stdout,
+    /// The Redis channel replicas share snapshots on
+    Redis,
+}
+
+impl FromStr for ExporterOutput {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "stdout" => Ok(Self::Stdout),
+            "redis" => Ok(Self::Redis),
+            _ => anyhow::bail!("Invalid exporter output {value:?}, expected stdout or redis"),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct GitHubConfig {
+    /// `live` or `mock` (`GITHUB_API_MODE`)
+    pub api_mode: GitHubApiMode,
+    /// Seed of the generated data in `mock` mode, so demos are reproducible (`GITHUB_API_MOCK_SEED`)
+    pub mock_seed: u64,
+    /// Directory every GitHub API call is recorded into; needs the `fixtures` feature
+    /// (`GITHUB_API_RECORD_DIR`)
+    pub record_dir: Option<PathBuf>,
+    /// Directory of recorded calls served instead of GitHub's; needs the `fixtures` feature
+    /// (`GITHUB_API_REPLAY_DIR`)
+    pub replay_dir: Option<PathBuf>,
+    /// Root of the REST API in `live` mode, e.g. `https://github.example.com/api/v3` for
+    /// GitHub Enterprise Server; unset uses `https://api.github.com` (`GITHUB_API_URL`)
+    pub api_url: Option<String>,
+    /// Required in `live` mode unless replaying or read from `token_file` (`GITHUB_TOKEN`)
+    pub token: Option<Secret>,
+    /// File holding the token, e.g. a mounted secret; read again every minute and whenever
+    /// GitHub rejects the token, so rotations need no restart (`GITHUB_TOKEN_FILE`)
+    pub token_file: Option<PathBuf>,
+    /// Secret of a webhook sending `workflow_run` events; unset disables them (`GITHUB_WEBHOOK_SECRET`)
+    pub webhook_secret: Option<Secret>,
+    /// Limit of run display titles in grapheme clusters (`MAX_TITLE_LENGTH`)
+    pub max_title_length: Option<NonZeroUsize>,
+    /// Requests sent to GitHub at a time, by polling and everything else alike; unset allows
+    /// 10 (`GITHUB_MAX_CONCURRENT_REQUESTS`)
+    pub max_concurrent_requests: Option<NonZeroUsize>,
+    /// Latest requests to GitHub kept for `/admin/github-requests`; unset keeps 1000
+    /// (`GITHUB_REQUEST_LOG_SIZE`)
+    pub request_log_size: Option<NonZeroUsize>,
+    /// Time the attempts of one call to GitHub get, backoff included; unset allows 20, and
+    /// it is kept below the poll interval (`GITHUB_RETRY_DEADLINE_SECONDS`)
+    pub retry_deadline_seconds: Option<NonZeroU64>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct PollingConfig {
+    /// `POLL_INTERVAL_SECONDS`
+    pub interval_seconds: u64,
+    /// Recently updated repositories polled without an allowlist (`MAX_REPOSITORIES`)
+    pub max_repositories: u8,
+    /// `MAX_RUNS_PER_REPO`
+    pub max_runs_per_repo: u8,
+    /// Age at which runs of repositories no longer polled are dropped
+    /// (`INACTIVE_REPO_RUN_MAX_AGE_HOURS`)
+    pub inactive_repo_run_max_age_hours: u32,
+    /// Most runs of repositories no longer polled kept (`MAX_INACTIVE_REPO_RUNS`)
+    pub max_inactive_repo_runs: u32,
+    /// List the next run of each scheduled workflow in snapshots (`UPCOMING_SCHEDULED_RUNS`)
+    pub upcoming_scheduled_runs: bool,
+    /// Repositories whose commit statuses are listed next to their runs; comma-separated in
+    /// `COMMIT_STATUS_REPOS`
+    pub commit_status_repos: Vec<RepoFullName>,
+    /// Poll repositories seen for the first time, dropping the least recently pushed beyond
+    /// `max_repositories` (`AUTO_MONITOR_NEW_REPOS`)
+    pub auto_monitor_new_repos: bool,
+}
+
+impl Default for PollingConfig {
+    fn default() -> Self {
+        let defaults = StreamConfig::default();
+        Self {
+            interval_seconds: defaults.poll_interval_seconds,
+            max_repositories: defaults.max_repositories,
+            max_runs_per_repo: defaults.max_runs_per_repo,
+            inactive_repo_run_max_age_hours: defaults.inactive_repo_run_max_age_hours,
+            max_inactive_repo_runs: defaults.max_inactive_repo_runs,
+            upcoming_scheduled_runs: defaults.upcoming_scheduled_runs,
+            commit_status_repos: defaults.commit_status_repos,
+            auto_monitor_new_repos: false,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct FiltersConfig {
+    /// Repositories to poll instead of the most recently updated ones; comma-separated in
+    /// `REPO_ALLOWLIST`
+    pub repo_allowlist: Vec<RepoFullName>,
+    /// Only publish runs that failed or need attention (`PROBLEM_ONLY`)
+    pub problem_only: bool,
+    /// Collapse the pull request runs of bots into one group per repository (`GROUP_BOT_RUNS`)
+    pub group_bot_runs: bool,
+    /// Workflows whose runs are hidden, by name or `*`/`?` pattern; comma-separated in
+    /// `IGNORE_WORKFLOWS`
+    pub ignore_workflows: Vec<GlobPattern>,
+    /// Only show runs of these workflows unless ignored; comma-separated in `ONLY_WORKFLOWS`
+    pub only_workflows: Vec<GlobPattern>,
+    /// `[filters.repos."owner/name"]`: ignore patterns added to the ones above, and `only`
+    /// patterns replacing them, for one repository
+    pub repos: BTreeMap<RepoFullName, RepoFiltersConfig>,
+}
+
+impl FiltersConfig {
+    fn apply_env(&mut self, env: Env) -> Result<(), ConfigError> {
+        let filters = self;
+        override_from_env(
+            env,
+            "REPO_ALLOWLIST",
+            &mut filters.repo_allowlist,
+            repo_list,
+        )?;
+        override_from_env(env, "PROBLEM_ONLY", &mut filters.problem_only, str::parse)?;
+        override_from_env(
+            env,
+            "GROUP_BOT_RUNS",
+            &mut filters.group_bot_runs,
+            str::parse,
+        )?;
+        override_from_env(
+            env,
+            "IGNORE_WORKFLOWS",
+            &mut filters.ignore_workflows,
+            workflow_pattern_list,
+        )?;
+        override_from_env(
+            env,
+            "ONLY_WORKFLOWS",
+            &mut filters.only_workflows,
+            workflow_pattern_list,
+        )?;
+        Ok(())
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct RepoFiltersConfig {
+    pub ignore_workflows: Vec<GlobPattern>,
+    pub only_workflows: Vec<GlobPattern>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct HistoryConfig {
+    /// `sqlite://path/to/runs.db`; unset disables run history (`DATABASE_URL`)
+    pub database_url: Option<String>,
+    /// `0` keeps runs forever (`HISTORY_RETENTION_DAYS`)
+    pub retention_days: u32,
+    /// `HISTORY_MAX_ROWS`
+    pub max_rows: Option<usize>,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            database_url: None,
+            retention_days: DEFAULT_HISTORY_RETENTION_DAYS,
+            max_rows: None,
+        }
+    }
+}
+
+impl HistoryConfig {
+    #[must_use]
+    pub fn retention_policy(&self) -> RetentionPolicy {
+        RetentionPolicy {
+            max_age: (self.retention_days > 0)
+                .then(|| TimeDelta::days(i64::from(self.retention_days))),
+            max_rows: self.max_rows,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct ReplicationConfig {
+    /// Redis shared by replicas, which may embed a password (`REDIS_URL`)
+    pub redis_url: Option<Secret>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct PushConfig {
+    /// Base64url encoded P-256 key signing Web Push requests, as printed by
+    /// `gha-dashboard generate-vapid-keys`; unset disables `/push` (`VAPID_PRIVATE_KEY`)
+    pub vapid_private_key: Option<Secret>,
+    /// Contact push services may reach the operator at, e.g. `mailto:ops@example.com`
+    /// (`VAPID_SUBJECT`)
+    pub subject: Option<String>,
+}
+
+/// One `[[tenants]]` entry: a dashboard of its own, isolated from the other tenants.
+///
+/// Server, telemetry, label and cost settings are shared; the rest is the tenant's alone and
+/// starts from the defaults rather than the top-level settings.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct TenantConfig {
+    /// Path segment the tenant is served under: letters, digits, `-` and `_`
+    pub name: String,
+    /// Token and source of the tenant's runs, as in `[github]`
+    pub github: GitHubConfig,
+    pub polling: PollingConfig,
+    pub filters: FiltersConfig,
+    /// Run history of the tenant; unset disables it
+    pub history: HistoryConfig,
+    /// `[[tenants.notifications]]`
+    pub notifications: Vec<NotificationTargetConfig>,
+    /// `[[tenants.slos]]`
+    pub slos: Vec<Slo>,
+}
+
+/// Service a notification target posts to.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NotificationKind {
+    /// Block Kit message to an incoming webhook
+    Slack,
+    /// Embed to a channel webhook
+    Discord,
+    /// Any endpoint, with custom headers and a templated body
+    Webhook,
+}
+
+/// One `[[notifications]]` entry: where run transitions are sent and which ones.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct NotificationTargetConfig {
+    pub kind: NotificationKind,
+    /// Redacted in logs since webhook URLs usually embed a token
+    pub url: Secret,
+    /// `webhook` only, e.g. `Authorization = "Bearer ..."`
+    #[serde(default)]
+    pub headers: BTreeMap<String, Secret>,
+    /// `webhook` only: body with `{{field}}` placeholders; a one-line summary when unset
+    #[serde(default)]
+    pub body: Option<String>,
+    /// Only runs that failed or need attention
+    #[serde(default = "default_true")]
+    pub failures_only: bool,
+    /// Repositories notified about, all when empty
+    #[serde(default)]
+    pub repos: Vec<RepoFullName>,
+    /// Branches notified about, all when empty
+    #[serde(default)]
+    pub branches: Vec<String>,
+    /// Minimum wait between two notifications about one workflow
+    #[serde(default = "default_notify_cooldown_seconds")]
+    pub cooldown_seconds: u64,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_notify_cooldown_seconds() -> u64 {
+    DEFAULT_NOTIFY_COOLDOWN_SECONDS
+}
+
+impl NotificationTargetConfig {
+    /// A target of `kind` at `url` with the default filter.
+    #[must_use]
+    pub fn new(kind: NotificationKind, url: Secret) -> Self {
+        Self {
+            kind,
+            url,
+            headers: BTreeMap::new(),
+            body: None,
+            failures_only: true,
+            repos: Vec::new(),
+            branches: Vec::new(),
+            cooldown_seconds: DEFAULT_NOTIFY_COOLDOWN_SECONDS,
+        }
+    }
+
+    #[must_use]
+    pub fn filter(&self) -> NotificationFilter {
+        NotificationFilter {
+            failures_only: self.failures_only,
+            repos: self.repos.clone(),
+            branches: self.branches.clone(),
+            cooldown: Duration::from_secs(self.cooldown_seconds),
+        }
+    }
+
+    /// The parsed headers and body template.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a header or the template is invalid.
+    pub fn webhook(&self) -> Result<(HeaderMap, Option<BodyTemplate>), InvalidWebhook> {
+        let headers = header_map(
+            self.headers
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.expose())),
+        )?;
+        let template = self.body.as_deref().map(BodyTemplate::parse).transpose()?;
+        Ok((headers, template))
+    }
+
+    /// Checks the target so mistakes fail startup instead of the first notification.
+    fn validate(&self) -> Result<(), String> {
+        if self.kind != NotificationKind::Webhook
+            && (!self.headers.is_empty() || self.body.is_some())
+        {
+            return Err("headers and body only apply to webhook targets".to_string());
+        }
+        self.webhook().map(|_| ()).map_err(|e| e.to_string())
+    }
+}
+
+/// The Slack and Discord targets set by `SLACK_WEBHOOK_URL` and `DISCORD_WEBHOOK_URL`,
+/// filtered by the `NOTIFY_*` variables.
+fn notification_targets_from_env(env: Env) -> Result<Vec<NotificationTargetConfig>, ConfigError> {
+    let mut base = NotificationTargetConfig::new(NotificationKind::Webhook, Secret(String::new()));
+    override_from_env(
+        env,
+        "NOTIFY_FAILURES_ONLY",
+        &mut base.failures_only,
+        str::parse,
+    )?;
+    override_from_env(env, "NOTIFY_REPOS", &mut base.repos, repo_list)?;
+    override_from_env(env, "NOTIFY_BRANCHES", &mut base.branches, |value| {
+        Ok::<_, Infallible>(string_list(value))
+    })?;
+    override_from_env(
+        env,
+        "NOTIFY_COOLDOWN_SECONDS",
+        &mut base.cooldown_seconds,
+        str::parse,
+    )?;
+    Ok([
+        (NotificationKind::Slack, "SLACK_WEBHOOK_URL"),
+        (NotificationKind::Discord, "DISCORD_WEBHOOK_URL"),
+    ]
+    .into_iter()
+    .filter_map(|(kind, name)| {
+        let url = env(name).filter(|url| !url.is_empty())?;
+        Some(NotificationTargetConfig {
+            kind,
+            url: Secret(url),
+            ..base.clone()
+        })
+    })
+    .collect())
+}
+
+fn from_str<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    String::deserialize(deserializer)?
+        .parse()
+        .map_err(serde::de::Error::custom)
+}
+
+fn socket_mode<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+    parse_socket_mode(&String::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+}
+
+/// Replaces `target` with the parsed value of the environment variable `name`, if set.
+fn override_from_env<T, E: Display>(
+    env: Env,
+    name: &str,
+    target: &mut T,
+    parse: impl FnOnce(&str) -> Result<T, E>,
+) -> Result<(), ConfigError> {
+    if let Some(value) = env(name) {
+        *target = parse(&value).map_err(|e| ConfigError::Invalid {
+            key: name.to_string(),
+            message: e.to_string(),
+        })?;
+    }
+    Ok(())
+}
+
+/// Replaces `target` with the secret in the environment variable `name`; an empty value unsets it.
+fn override_secret_from_env(env: Env, name: &str, target: &mut Option<Secret>) {
+    if let Some(value) = env(name) {
+        *target = (!value.is_empty()).then_some(Secret(value));
+    }
+}
+
+fn some<T: FromStr>(value: &str) -> Result<Option<T>, T::Err> {
+    value.parse().map(Some)
+}
+
+/// Overrides the `[telemetry]` settings from `RUST_LOG`, the `LOG_` variables and the standard
+/// OpenTelemetry variables.
+fn apply_telemetry_env(telemetry: &mut TelemetryConfig, env: Env) -> Result<(), ConfigError> {
+    override_from_env(env, "RUST_LOG", &mut telemetry.filter, some)?;
+    override_from_env(env, "LOG_FORMAT", &mut telemetry.log_format, str::parse)?;
+    override_from_env(
+        env,
+        "LOG_REDACTION",
+        &mut telemetry.log_redaction,
+        str::parse,
+    )?;
+    override_from_env(
+        env,
+        "LOG_REDACTION_REPOSITORIES",
+        &mut telemetry.redact_repositories,
+        str::parse,
+    )?;
+    override_from_env(
+        env,
+        "OTEL_EXPORTER_OTLP_ENDPOINT",
+        &mut telemetry.otlp_endpoint,
+        |value| Ok::<_, Infallible>((!value.is_empty()).then(|| value.to_string())),
+    )?;
+    override_from_env(
+        env,
+        "OTEL_EXPORTER_OTLP_PROTOCOL",
+        &mut telemetry.otlp_protocol,
+        str::parse,
+    )?;
+    override_from_env(
+        env,
+        "OTEL_EXPORTER_OTLP_HEADERS",
+        &mut telemetry.otlp_headers,
+        otlp::parse_headers,
+    )?;
+    override_from_env(
+        env,
+        "OTEL_TRACES_SAMPLER",
+        &mut telemetry.traces_sampler,
+        str::parse,
+    )?;
+    override_from_env(
+        env,
+        "OTEL_TRACES_SAMPLER_ARG",
+        &mut telemetry.traces_sampler_arg,
+        some,
+    )?;
+    override_from_env(
+        env,
+        "OTEL_PROPAGATORS",
+        &mut telemetry.propagator,
+        str::parse,
+    )?;
+    Ok(())
+}
+
+/// Checks that the token is set in live mode, and only once, and that the API URL parses.
+///
+/// `prefix` is prepended to the keys of errors, e.g. `tenants[0].`.
+fn validate_github(github: &GitHubConfig, prefix: &str) -> Result<(), ConfigError> {
+    if github.api_mode == GitHubApiMode::Live
+        && github.replay_dir.is_none()
+        && github.token.is_none()
+        && github.token_file.is_none()
+    {
+        return Err(ConfigError::Invalid {
+            key: format!("{prefix}github.token"),
+            message: "is required in live mode, set it, token_file or api_mode = \"mock\""
+                .to_string(),
+        });
+    }
+    if github.token.is_some() && github.token_file.is_some() {
+        return Err(ConfigError::Invalid {
+            key: format!("{prefix}github.token_file"),
+            message: "set either the token or the file holding it, not both".to_string(),
+        });
+    }
+    if let Some(api_url) = &github.api_url
+        && let Err(e) = reqwest::Url::parse(api_url)
+    {
+        return Err(ConfigError::Invalid {
+            key: format!("{prefix}github.api_url"),
+            message: format!("is not a valid URL: {e}"),
+        });
+    }
+    Ok(())
+}
+
+/// Checks that forwarded identities come from a trusted proxy, and that each setting is
+/// used by the auth mode picked.
+fn validate_auth(server: &ServerConfig) -> Result<(), ConfigError> {
+    match server.auth_mode {
+        AuthMode::Bearer if !server.allowed_groups.is_empty() => Err(ConfigError::Invalid {
+            key: "server.allowed_groups".to_string(),
+            message: "only applies with server.auth_mode = \"forward_headers\"".to_string(),
+        }),
+        AuthMode::ForwardHeaders if !server.trust_proxy => Err(ConfigError::Invalid {
+            key: "server.auth_mode".to_string(),
+            message: "forward_headers requires server.trust_proxy, anyone could send the headers otherwise"
+                .to_string(),
+        }),
+        AuthMode::ForwardHeaders if server.auth_token.is_some() => Err(ConfigError::Invalid {
+            key: "server.auth_token".to_string(),
+            message: "is not used with server.auth_mode = \"forward_headers\"".to_string(),
+        }),
+        AuthMode::Bearer | AuthMode::ForwardHeaders => Ok(()),
+    }
+}
+
+/// Checks that an anonymized deployment has a token keeping the routes that show real names
+/// private, and no gRPC server, which would stream them to anyone.
+fn validate_anonymized(server: &ServerConfig) -> Result<(), ConfigError> {
+    if server.auth_token.is_none() {
+        return Err(ConfigError::Invalid {
+            key: "server.auth_token".to_string(),
+            message: "is required with server.anonymize to protect /status and /admin".to_string(),
+        });
+    }
+    if server.grpc_bind_addr.is_some() {
+        return Err(ConfigError::Invalid {
+            key: "server.grpc_bind_addr".to_string(),
+            message: "cannot be combined with server.anonymize".to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Checks that an exporter has somewhere to write its snapshots and serves nothing but them.
+fn validate_exporter(config: &Config) -> Result<(), ConfigError> {
+    if config.server.mode != ServerMode::Exporter {
+        return Ok(());
+    }
+    if config.server.exporter_output == ExporterOutput::Redis
+        && config.replication.redis_url.is_none()
+    {
+        return Err(ConfigError::Invalid {
+            key: "server.exporter_output".to_string(),
+            message: "redis requires replication.redis_url".to_string(),
+        });
+    }
+    if !config.tenants.is_empty() {
+        return Err(ConfigError::Invalid {
+            key: "server.mode".to_string(),
+            message: "exporter cannot be combined with [[tenants]]".to_string(),
+        });
+    }
+    if config.server.grpc_bind_addr.is_some() {
+        return Err(ConfigError::Invalid {
+            key: "server.grpc_bind_addr".to_string(),
+            message: "cannot be combined with server.mode = \"exporter\"".to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Checks every tenant as the top-level settings are checked, and that their names are
+/// distinct path segments.
+fn validate_tenants(config: &Config) -> Result<(), ConfigError> {
+    if config.tenants.is_empty() {
+        return Ok(());
+    }
+    if config.replication.redis_url.is_some() {
+        return Err(ConfigError::Invalid {
+            key: "replication.redis_url".to_string(),
+            message: "cannot be combined with [[tenants]]".to_string(),
+        });
+    }
+    if config.server.grpc_bind_addr.is_some() {
+        return Err(ConfigError::Invalid {
+            key: "server.grpc_bind_addr".to_string(),
+            message: "cannot be combined with [[tenants]]".to_string(),
+        });
+    }
+    for (index, tenant) in config.tenants.iter().enumerate() {
+        let prefix = format!("tenants[{index}].");
+        let invalid = |key: &str, message: String| ConfigError::Invalid {
+            key: format!("{prefix}{key}"),
+            message,
+        };
+        if tenant.name.is_empty()
+            || !tenant
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(invalid(
+                "name",
+                format!("must be letters, digits, - and _, got {:?}", tenant.name),
+            ));
+        }
+        if let Some(other) = config.tenants[..index]
+            .iter()
+            .position(|other| other.name == tenant.name)
+        {
+            return Err(invalid(
+                "name",
+                format!("{:?} is already the name of tenants[{other}]", tenant.name),
+            ));
+        }
+        validate_github(&tenant.github, &prefix)?;
+        for (target_index, target) in tenant.notifications.iter().enumerate() {
+            target
+                .validate()
+                .map_err(|message| invalid(&format!("notifications[{target_index}]"), message))?;
+        }
+        for (slo_index, slo) in tenant.slos.iter().enumerate() {
+            slo.validate()
+                .map_err(|message| invalid(&format!("slos[{slo_index}]"), message))?;
+        }
+        config.for_tenant(tenant).stream_config()?;
+    }
+    Ok(())
+}
+
+/// Checks the OTLP headers and the sampling ratio, which the exporter would otherwise
+/// drop or clamp silently.
+fn validate_telemetry(telemetry: &TelemetryConfig) -> Result<(), ConfigError> {
+    for (name, value) in &telemetry.otlp_headers {
+        otlp::validate_header(name, value.expose()).map_err(|message| ConfigError::Invalid {
+            key: "telemetry.otlp_headers".to_string(),
+            message,
+        })?;
+    }
+    if let Some(ratio) = telemetry.traces_sampler_arg
+        && !(0.0..=1.0).contains(&ratio)
+    {
+        return Err(ConfigError::Invalid {
+            key: "telemetry.traces_sampler_arg".to_string(),
+            message: format!("must be a ratio between 0 and 1, got {ratio}"),
+        });
+    }
+    Ok(())
+}
+
+fn string_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn repo_list(value: &str) -> Result<Vec<RepoFullName>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| entry.parse().map_err(|e| format!("{entry:?}: {e}")))
+        .collect()
+}
+
+fn pattern_strings(patterns: &[GlobPattern]) -> Vec<String> {
+    patterns.iter().map(ToString::to_string).collect()
+}
+
+fn workflow_pattern_list(value: &str) -> Result<Vec<GlobPattern>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| entry.parse().map_err(|e| format!("{entry:?}: {e}")))
+        .collect()
+}
+
+fn cidr_list(value: &str) -> Result<Vec<IpCidr>, InvalidCidr> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+impl Config {
+    /// Finds the config file: `explicit` (from `--config`), then `GHA_CONFIG`, then
+    /// [`DEFAULT_CONFIG_PATH`] if it exists.
+    #[must_use]
+    pub fn path(explicit: Option<PathBuf>, env: Env) -> Option<PathBuf> {
+        explicit
+            .or_else(|| env("GHA_CONFIG").map(PathBuf::from))
+            .or_else(|| {
+                Path::new(DEFAULT_CONFIG_PATH)
+                    .exists()
+                    .then(|| PathBuf::from(DEFAULT_CONFIG_PATH))
+            })
+    }
+
+    /// Reads the file at `path`, if any, and applies environment variables on top.
+    ///
+    /// Also returns the keys of the file that are not settings, e.g. `server.colour`, so
+    /// they can be reported once logging is set up.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed, a value is invalid, or the
+    /// GitHub token is missing when calling GitHub.
+    pub fn load(path: Option<&Path>, env: Env) -> Result<(Self, Vec<String>), ConfigError> {
+        let (mut config, unknown_keys) = match path {
+            Some(path) => {
+                let text = std::fs::read_to_string(path).map_err(|source| ConfigError::Read {
+                    path: path.to_path_buf(),
+                    source,
+                })?;
+                Self::from_toml(&text).map_err(|e| ConfigError::Parse {
+                    path: path.to_path_buf(),
+                    message: e.to_string(),
+                })?
+            }
+            None => (Self::default(), Vec::new()),
+        };
+        config.apply_env(env)?;
+        if !cfg!(feature = "fixtures") {
+            for (key, dir) in [
+                ("github.record_dir", &config.github.record_dir),
+                ("github.replay_dir", &config.github.replay_dir),
+            ] {
+                if dir.is_some() {
+                    return Err(ConfigError::Invalid {
+                        key: key.to_string(),
+                        message: "requires a build with the fixtures feature".to_string(),
+                    });
+                }
+            }
+        }
+        if !cfg!(feature = "grpc") && config.server.grpc_bind_addr.is_some() {
+            return Err(ConfigError::Invalid {
+                key: "server.grpc_bind_addr".to_string(),
+                message: "requires a build with the grpc feature".to_string(),
+            });
+        }
+        // Tenants poll with their own tokens
+        if config.tenants.is_empty()
+            && config.github.api_mode == GitHubApiMode::Live
+            && config.github.replay_dir.is_none()
+            && config.github.token.is_none()
+            && config.github.token_file.is_none()
+        {
+            return Err(ConfigError::Missing {
+                key: "github.token",
+                env: "GITHUB_TOKEN",
+            });
+        }
+        if config.github.token.is_some() && config.github.token_file.is_some() {
+            return Err(ConfigError::Invalid {
+                key: "github.token_file".to_string(),
+                message: "set either the token or the file holding it, not both".to_string(),
+            });
+        }
+        for (index, target) in config.notifications.iter().enumerate() {
+            target.validate().map_err(|message| ConfigError::Invalid {
+                key: format!("notifications[{index}]"),
+                message,
+            })?;
+        }
+        for (key, price) in [
+            ("costs.ubuntu", config.costs.ubuntu),
+            ("costs.windows", config.costs.windows),
+            ("costs.macos", config.costs.macos),
+            ("costs.self_hosted", config.costs.self_hosted),
+        ] {
+            if !(price.is_finite() && price >= 0.0) {
+                return Err(ConfigError::Invalid {
+                    key: key.to_string(),
+                    message: format!("must be a non-negative price per minute, got {price}"),
+                });
+            }
+        }
+        for (index, rule) in config.labels.iter().enumerate() {
+            rule.validate().map_err(|message| ConfigError::Invalid {
+                key: format!("labels[{index}]"),
+                message,
+            })?;
+        }
+        for (index, slo) in config.slos.iter().enumerate() {
+            slo.validate().map_err(|message| ConfigError::Invalid {
+                key: format!("slos[{index}]"),
+                message,
+            })?;
+        }
+        // Tenants store the subscriptions in their own history
+        if config.tenants.is_empty()
+            && config.push.vapid_private_key.is_some()
+            && config.history.database_url.is_none()
+        {
+            return Err(ConfigError::Invalid {
+                key: "push.vapid_private_key".to_string(),
+                message: "requires history.database_url to store the subscriptions".to_string(),
+            });
+        }
+        validate_auth(&config.server)?;
+        if config.server.anonymize {
+            validate_anonymized(&config.server)?;
+        }
+        validate_exporter(&config)?;
+        validate_telemetry(&config.telemetry)?;
+        validate_tenants(&config)?;
+        config.stream_config()?;
+        Ok((config, unknown_keys))
+    }
+
+    /// Parses a config file, collecting the keys that are not settings instead of failing.
+    fn from_toml(text: &str) -> Result<(Self, Vec<String>), toml::de::Error> {
+        let mut unknown_keys = Vec::new();
+        let config = serde_ignored::deserialize(toml::Deserializer::parse(text)?, |key| {
+            unknown_keys.push(key.to_string());
+        })?;
+        unknown_keys.sort();
+        Ok((config, unknown_keys))
+    }
+
+    fn apply_env(&mut self, env: Env) -> Result<(), ConfigError> {
+        self.server.apply_env(env)?;
+
+        let github = &mut self.github;
+        override_from_env(env, "GITHUB_API_MODE", &mut github.api_mode, str::parse)?;
+        override_from_env(
+            env,
+            "GITHUB_API_MOCK_SEED",
+            &mut github.mock_seed,
+            str::parse,
+        )?;
+        override_from_env(env, "GITHUB_API_URL", &mut github.api_url, some)?;
+        override_secret_from_env(env, "GITHUB_TOKEN", &mut github.token);
+        override_from_env(env, "GITHUB_TOKEN_FILE", &mut github.token_file, some)?;
+        override_secret_from_env(env, "GITHUB_WEBHOOK_SECRET", &mut github.webhook_secret);
+        override_from_env(env, "MAX_TITLE_LENGTH", &mut github.max_title_length, some)?;
+        override_from_env(
+            env,
+            "GITHUB_MAX_CONCURRENT_REQUESTS",
+            &mut github.max_concurrent_requests,
+            some,
+        )?;
+        override_from_env(
+            env,
+            "GITHUB_REQUEST_LOG_SIZE",
+            &mut github.request_log_size,
+            some,
+        )?;
+        override_from_env(
+            env,
+            "GITHUB_RETRY_DEADLINE_SECONDS",
+            &mut github.retry_deadline_seconds,
+            some,
+        )?;
+
+        let polling = &mut self.polling;
+        override_from_env(
+            env,
+            "POLL_INTERVAL_SECONDS",
+            &mut polling.interval_seconds,
+            str::parse,
+        )?;
+        override_from_env(
+            env,
+            "MAX_REPOSITORIES",
+            &mut polling.max_repositories,
+            str::parse,
+        )?;
+        override_from_env(
+            env,
+            "MAX_RUNS_PER_REPO",
+            &mut polling.max_runs_per_repo,
+            str::parse,
+        )?;
+        override_from_env(
+            env,
+            "INACTIVE_REPO_RUN_MAX_AGE_HOURS",
+            &mut polling.inactive_repo_run_max_age_hours,
+            str::parse,
+        )?;
+        override_from_env(
+            env,
+            "MAX_INACTIVE_REPO_RUNS",
+            &mut polling.max_inactive_repo_runs,
+            str::parse,
+        )?;
+        override_from_env(
+            env,
+            "UPCOMING_SCHEDULED_RUNS",
+            &mut polling.upcoming_scheduled_runs,
+            str::parse,
+        )?;
+        override_from_env(
+            env,
+            "COMMIT_STATUS_REPOS",
+            &mut polling.commit_status_repos,
+            repo_list,
+        )?;
+        override_from_env(
+            env,
+            "AUTO_MONITOR_NEW_REPOS",
+            &mut polling.auto_monitor_new_repos,
+            str::parse,
+        )?;
+
+        self.filters.apply_env(env)?;
+
+        let history = &mut self.history;
+        override_from_env(env, "DATABASE_URL", &mut history.database_url, some)?;
+        override_from_env(
+            env,
+            "HISTORY_RETENTION_DAYS",
+            &mut history.retention_days,
+            str::parse,
+        )?;
+        override_from_env(env, "HISTORY_MAX_ROWS", &mut history.max_rows, some)?;
+
+        override_secret_from_env(env, "REDIS_URL", &mut self.replication.redis_url);
+
+        override_secret_from_env(env, "VAPID_PRIVATE_KEY", &mut self.push.vapid_private_key);
+        override_from_env(env, "VAPID_SUBJECT", &mut self.push.subject, some)?;
+
+        self.notifications
+            .extend(notification_targets_from_env(env)?);
+
+        apply_telemetry_env(&mut self.telemetry, env)
+    }
+
+    /// The settings `tenant` is served with: its own where it has them, the shared ones
+    /// otherwise, and no replication.
+    #[must_use]
+    pub fn for_tenant(&self, tenant: &TenantConfig) -> Self {
+        Self {
+            github: tenant.github.clone(),
+            polling: tenant.polling.clone(),
+            filters: tenant.filters.clone(),
+            history: tenant.history.clone(),
+            replication: ReplicationConfig::default(),
+            notifications: tenant.notifications.clone(),
+            slos: tenant.slos.clone(),
+            tenants: Vec::new(),
+            ..self.clone()
+        }
+    }
+
+    /// Time the attempts of one call to GitHub get: the configured deadline, cut to a second
+    /// below the poll interval so a poll's retries never run into the next poll.
+    #[must_use]
+    pub fn github_retry_deadline(&self) -> Duration {
+        let configured = self
+            .github
+            .retry_deadline_seconds
+            .map_or(DEFAULT_RETRY_DEADLINE, |seconds| {
+                Duration::from_secs(seconds.get())
+            });
+        let below_poll_interval =
+            Duration::from_secs(self.polling.interval_seconds.saturating_sub(1).max(1));
+        configured.min(below_poll_interval)
+    }
+
+    /// Settings the polling loop starts with, checked like a runtime config change.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigError::Invalid`] listing each violated constraint.
+    pub fn stream_config(&self) -> Result<StreamConfig, ConfigError> {
+        let base = StreamConfig {
+            max_repositories: self.polling.max_repositories,
+            labels: self.labels.clone(),
+            inactive_repo_run_max_age_hours: self.polling.inactive_repo_run_max_age_hours,
+            max_inactive_repo_runs: self.polling.max_inactive_repo_runs,
+            ..StreamConfig::default()
+        };
+        let patch = StreamConfigPatch {
+            poll_interval_seconds: Some(self.polling.interval_seconds),
+            max_runs_per_repo: Some(u64::from(self.polling.max_runs_per_repo)),
+            repo_allowlist: Some(
+                self.filters
+                    .repo_allowlist
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect(),
+            ),
+            problem_only: Some(self.filters.problem_only),
+            group_bot_runs: Some(self.filters.group_bot_runs),
+            upcoming_scheduled_runs: Some(self.polling.upcoming_scheduled_runs),
+            commit_status_repos: Some(
+                self.polling
+                    .commit_status_repos
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect(),
+            ),
+            ignore_workflows: Some(pattern_strings(&self.filters.ignore_workflows)),
+            only_workflows: Some(pattern_strings(&self.filters.only_workflows)),
+            repo_workflow_filters: Some(
+                self.filters
+                    .repos
+                    .iter()
+                    .map(|(repo, filters)| {
+                        let patch = WorkflowFilterPatch {
+                            ignore_workflows: pattern_strings(&filters.ignore_workflows),
+                            only_workflows: pattern_strings(&filters.only_workflows),
+                        };
+                        (repo.to_string(), patch)
+                    })
+                    .collect(),
+            ),
+        };
+        base.apply(&patch).map_err(|e| ConfigError::Invalid {
+            key: "polling".to_string(),
+            message: e.violations.join("; "),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructures::telemetry::{
+        LogFormat, OtlpProtocol, TracePropagator, TraceSampler,
+    };
+    use std::collections::HashMap;
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(name, value)| ((*name).to_string(), (*value).to_string()))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn test_env_overrides_file_which_overrides_defaults() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("gha-dashboard.toml");
+        std::fs::write(
+            &path,
+            r#"
+[server]
+trust_proxy = true
+bind_addr = "127.0.0.1:8080"
+
+[github]
+token = "file-token"
+
+[polling]
+interval_seconds = 60
+
+[telemetry]
+log_format = "json"
+"#,
+        )?;
+
+        let (config, unknown_keys) = Config::load(
+            Some(&path),
+            &env(&[
+                ("POLL_INTERVAL_SECONDS", "120"),
+                ("AUTH_TOKEN", "env-token"),
+                ("PREFLIGHT", "warn"),
+            ]),
+        )?;
+
+        assert!(unknown_keys.is_empty());
+        // Environment over file
+        assert_eq!(config.polling.interval_seconds, 120);
+        assert_eq!(
+            config.server.auth_token,
+            Some(Secret::from("env-token".to_string()))
+        );
+        assert_eq!(config.server.preflight, PreflightMode::Warn);
+        // File over default
+        assert!(config.server.trust_proxy);
+        assert_eq!(config.server.bind_addr, "127.0.0.1:8080".parse()?);
+        assert_eq!(
+            config.github.token.as_ref().map(Secret::expose),
+            Some("file-token")
+        );
+        assert_eq!(config.telemetry.log_format, LogFormat::Json);
+        // Defaults
+        assert_eq!(
+            config.polling.max_runs_per_repo,
+            PollingConfig::default().max_runs_per_repo
+        );
+        assert_eq!(
+            config.history.retention_days,
+            DEFAULT_HISTORY_RETENTION_DAYS
+        );
+        assert_eq!(config.stream_config()?.poll_interval_seconds, 120);
+        Ok(())
+    }
+
+    #[test]
+    fn test_notification_settings_from_env() -> anyhow::Result<()> {
+        let (config, _) = Config::load(
+            None,
+            &env(&[
+                ("GITHUB_TOKEN", "token"),
+                (
+                    "SLACK_WEBHOOK_URL",
+                    "https://hooks.slack.com/services/T0/B0/X",
+                ),
+                ("NOTIFY_REPOS", "owner/repo, owner/other"),
+                ("NOTIFY_BRANCHES", "main,release"),
+                ("NOTIFY_COOLDOWN_SECONDS", "60"),
+            ]),
+        )?;
+
+        let [slack] = config.notifications.as_slice() else {
+            anyhow::bail!("expected one target, got {:?}", config.notifications);
+        };
+        assert_eq!(slack.kind, NotificationKind::Slack);
+        assert_eq!(
+            slack.url.expose(),
+            "https://hooks.slack.com/services/T0/B0/X"
+        );
+        assert_eq!(
+            slack.filter(),
+            NotificationFilter {
+                failures_only: true,
+                repos: vec!["owner/repo".parse()?, "owner/other".parse()?],
+                branches: vec!["main".to_string(), "release".to_string()],
+                cooldown: Duration::from_mins(1),
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_notification_targets_from_file_keep_their_own_filters() -> anyhow::Result<()> {
+        let (config, unknown_keys) = Config::from_toml(
+            r#"
+[[notifications]]
+kind = "webhook"
+url = "https://ntfy.sh/ci-alerts"
+headers = { Authorization = "Bearer tk_123" }
+body = "{{repository}}: {{status}}"
+branches = ["main"]
+
+[[notifications]]
+kind = "discord"
+url = "https://discord.com/api/webhooks/1/token"
+failures_only = false
+"#,
+        )?;
+
+        assert!(unknown_keys.is_empty());
+        let [ntfy, discord] = config.notifications.as_slice() else {
+            anyhow::bail!("expected two targets, got {:?}", config.notifications);
+        };
+        assert_eq!(ntfy.kind, NotificationKind::Webhook);
+        assert!(ntfy.filter().failures_only);
+        assert_eq!(ntfy.filter().branches, vec!["main".to_string()]);
+        assert!(ntfy.webhook()?.0.contains_key("authorization"));
+        assert!(!discord.filter().failures_only);
+        assert_eq!(discord.filter().cooldown, Duration::from_mins(10));
+        assert!(!format!("{config:?}").contains("tk_123"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_notification_templates_fail_loading() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("gha-dashboard.toml");
+        for (target, expected) in [
+            (
+                r#"kind = "webhook"
+body = "{{repo}} failed""#,
+                "unknown template field {{repo}}",
+            ),
+            (
+                r#"kind = "slack"
+body = "{{repository}}""#,
+                "only apply to webhook targets",
+            ),
+        ] {
+            std::fs::write(
+                &path,
+                format!("[[notifications]]\nurl = \"https://example.com\"\n{target}\n"),
+            )?;
+
+            let error = Config::load(Some(&path), &env(&[("GITHUB_TOKEN", "token")]))
+                .err()
+                .ok_or_else(|| anyhow::anyhow!("{target} was accepted"))?;
+
+            assert!(
+                matches!(&error, ConfigError::Invalid { key, message }
+                    if key == "notifications[0]" && message.contains(expected)),
+                "{error}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_costs_override_list_prices() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("gha-dashboard.toml");
+        std::fs::write(&path, "[costs]\nmacos = 0.062\nself_hosted = 0.002\n")?;
+
+        let (config, unknown_keys) = Config::load(Some(&path), &env(&[("GITHUB_TOKEN", "t")]))?;
+        std::fs::write(&path, "[costs]\nwindows = -1.0\n")?;
+        let error = Config::load(Some(&path), &env(&[("GITHUB_TOKEN", "t")]));
+
+        assert!(unknown_keys.is_empty());
+        assert_eq!(
+            config.costs,
+            PriceTable {
+                macos: 0.062,
+                self_hosted: 0.002,
+                ..PriceTable::default()
+            }
+        );
+        assert!(matches!(error, Err(ConfigError::Invalid { key, .. }) if key == "costs.windows"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_allowed_cidrs_from_file_and_env() -> anyhow::Result<()> {
+        let (config, _) =
+            Config::from_toml("[server]\nallowed_cidrs = [\"10.0.0.0/8\", \"fd00::/8\"]\n")?;
+        assert_eq!(
+            config.server.allowed_cidrs,
+            vec!["10.0.0.0/8".parse()?, "fd00::/8".parse()?]
+        );
+        assert!(Config::from_toml("[server]\nallowed_cidrs = [\"10.0.0.0/40\"]\n").is_err());
+
+        let (config, _) = Config::load(
+            None,
+            &env(&[
+                ("GITHUB_TOKEN", "token"),
+                ("ALLOWED_CIDRS", "192.168.0.0/16, 2001:db8::/32"),
+            ]),
+        )?;
+        assert_eq!(
+            config.server.allowed_cidrs,
+            vec!["192.168.0.0/16".parse()?, "2001:db8::/32".parse()?]
+        );
+        let error = Config::load(
+            None,
+            &env(&[
+                ("GITHUB_TOKEN", "token"),
+                ("ALLOWED_CIDRS", "10.0.0.0/8,vpn"),
+            ]),
+        );
+        assert!(matches!(error, Err(ConfigError::Invalid { key, .. }) if key == "ALLOWED_CIDRS"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_request_limits_from_file_and_env() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("gha-dashboard.toml");
+        std::fs::write(
+            &path,
+            "[server]\nslow_request_timeout_seconds = 120\nmax_concurrent_requests = 32\n",
+        )?;
+
+        let (config, _) = Config::load(
+            Some(&path),
+            &env(&[
+                ("GITHUB_TOKEN", "token"),
+                ("REQUEST_TIMEOUT_SECONDS", "5"),
+                ("WEBSOCKET_MAX_MESSAGE_BYTES", "4096"),
+            ]),
+        )?;
+        assert_eq!(
+            config.server.request_limits(),
+            RequestLimits {
+                timeout: Duration::from_secs(5),
+                slow_timeout: Duration::from_mins(2),
+                max_concurrent: 32,
+                websocket_max_message_bytes: 4096,
+                websocket_max_frame_bytes: DEFAULT_WEBSOCKET_MAX_FRAME_BYTES,
+                websocket_heartbeat_interval: DEFAULT_WEBSOCKET_HEARTBEAT_INTERVAL,
+                websocket_max_pinned_runs: DEFAULT_WEBSOCKET_MAX_PINNED_RUNS,
+                log_tail_chunk_bytes: DEFAULT_LOG_TAIL_CHUNK_BYTES,
+                log_tail_max_bytes: DEFAULT_LOG_TAIL_MAX_BYTES,
+                latest_runs_max_repos: DEFAULT_LATEST_RUNS_MAX_REPOS,
+            }
+        );
+        assert_eq!(
+            Config::default().server.request_limits(),
+            RequestLimits::default()
+        );
+        let error = Config::load(
+            None,
+            &env(&[("GITHUB_TOKEN", "token"), ("MAX_CONCURRENT_REQUESTS", "0")]),
+        );
+        assert!(
+            matches!(error, Err(ConfigError::Invalid { key, .. }) if key == "MAX_CONCURRENT_REQUESTS")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_grpc_bind_addr_needs_the_grpc_feature() -> anyhow::Result<()> {
+        let loaded = Config::load(
+            None,
+            &env(&[
+                ("GITHUB_TOKEN", "token"),
+                ("GRPC_BIND_ADDR", "127.0.0.1:50051"),
+            ]),
+        );
+
+        if cfg!(feature = "grpc") {
+            assert_eq!(
+                loaded?.0.server.grpc_bind_addr,
+                Some("127.0.0.1:50051".parse()?)
+            );
+        } else {
+            assert!(
+                matches!(loaded, Err(ConfigError::Invalid { key, .. }) if key == "server.grpc_bind_addr")
+            );
+        }
+        let (config, _) = Config::load(None, &env(&[("GITHUB_TOKEN", "token")]))?;
+        assert_eq!(config.server.grpc_bind_addr, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_keys_are_collected_instead_of_failing() -> anyhow::Result<()> {
+        let (config, unknown_keys) = Config::from_toml(
+            r#"
+[server]
+trust_proxy = true
+colour = "blue"
+
+[experimental]
+enabled = true
+"#,
+        )?;
+
+        assert!(config.server.trust_proxy);
+        assert_eq!(unknown_keys, vec!["experimental", "server.colour"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_values_name_their_key_and_line() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("gha-dashboard.toml");
+        std::fs::write(
+            &path,
+            "[server]\ntrust_proxy = true\nbind_addr = \"unix:\"\n",
+        )?;
+
+        let error = Config::load(Some(&path), &env(&[("GITHUB_TOKEN", "token")]))
+            .err()
+            .ok_or_else(|| anyhow::anyhow!("invalid bind_addr accepted"))?
+            .to_string();
+        assert!(error.contains("line 3"), "{error}");
+        assert!(error.contains("bind_addr"), "{error}");
+
+        let error = Config::load(
+            None,
+            &env(&[("GITHUB_TOKEN", "token"), ("MAX_RUNS_PER_REPO", "lots")]),
+        )
+        .err()
+        .map(|e| e.to_string());
+        assert!(error.is_some_and(|error| error.starts_with("Invalid MAX_RUNS_PER_REPO")));
+
+        let error = Config::load(
+            None,
+            &env(&[("GITHUB_TOKEN", "token"), ("POLL_INTERVAL_SECONDS", "1")]),
+        )
+        .err()
+        .map(|e| e.to_string());
+        assert!(error.is_some_and(|error| error.starts_with("Invalid polling")));
+
+        assert!(matches!(
+            Config::load(None, &env(&[])),
+            Err(ConfigError::Missing {
+                key: "github.token",
+                ..
+            })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_secrets_are_redacted_in_debug_output() -> anyhow::Result<()> {
+        let (config, _) = Config::load(
+            None,
+            &env(&[
+                ("GITHUB_TOKEN", "ghp_secret"),
+                ("REDIS_URL", "redis://:hunter2@redis:6379"),
+            ]),
+        )?;
+
+        let logged = format!("{config:?}");
+
+        assert!(!logged.contains("ghp_secret"), "{logged}");
+        assert!(!logged.contains("hunter2"), "{logged}");
+        assert!(logged.contains("[redacted]"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_otlp_exporter_settings_from_env() -> anyhow::Result<()> {
+        let (config, _) = Config::load(
+            None,
+            &env(&[
+                ("GITHUB_TOKEN", "token"),
+                (
+                    "OTEL_EXPORTER_OTLP_ENDPOINT",
+                    "https://otel.example.com:4318",
+                ),
+                ("OTEL_EXPORTER_OTLP_PROTOCOL", "http/protobuf"),
+                (
+                    "OTEL_EXPORTER_OTLP_HEADERS",
+                    "authorization=Bearer%20otel-secret",
+                ),
+                ("OTEL_TRACES_SAMPLER", "parentbased_traceidratio"),
+                ("OTEL_TRACES_SAMPLER_ARG", "0.1"),
+                ("OTEL_PROPAGATORS", "none"),
+            ]),
+        )?;
+
+        let telemetry = &config.telemetry;
+        assert_eq!(
+            telemetry.otlp_endpoint.as_deref(),
+            Some("https://otel.example.com:4318")
+        );
+        assert_eq!(telemetry.otlp_protocol, OtlpProtocol::HttpProtobuf);
+        assert_eq!(
+            telemetry
+                .otlp_headers
+                .get("authorization")
+                .map(Secret::expose),
+            Some("Bearer otel-secret")
+        );
+        assert_eq!(
+            telemetry.traces_sampler,
+            TraceSampler::ParentBasedTraceIdRatio
+        );
+        assert_eq!(telemetry.traces_sampler_arg, Some(0.1));
+        assert_eq!(telemetry.propagator, TracePropagator::None);
+        assert!(!format!("{config:?}").contains("otel-secret"));
+
+        for (name, value) in [
+            ("OTEL_EXPORTER_OTLP_PROTOCOL", "http/json"),
+            ("OTEL_EXPORTER_OTLP_HEADERS", "authorization"),
+            ("OTEL_TRACES_SAMPLER", "xray"),
+            ("OTEL_TRACES_SAMPLER_ARG", "1.5"),
+            ("OTEL_PROPAGATORS", "b3"),
+        ] {
+            let result = Config::load(None, &env(&[("GITHUB_TOKEN", "token"), (name, value)]));
+            assert!(
+                matches!(result, Err(ConfigError::Invalid { .. })),
+                "{name}={value}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_mock_mode_needs_no_token() -> anyhow::Result<()> {
+        let (config, _) = Config::load(
+            None,
+            &env(&[("GITHUB_API_MODE", "mock"), ("GITHUB_API_MOCK_SEED", "42")]),
+        )?;
+
+        assert_eq!(config.github.api_mode, GitHubApiMode::Mock);
+        assert_eq!(config.github.mock_seed, 42);
+        assert!(matches!(
+            Config::load(None, &env(&[("GITHUB_API_MODE", "live")])),
+            Err(ConfigError::Missing { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_push_needs_the_run_history() -> anyhow::Result<()> {
+        let vars = [
+            ("GITHUB_TOKEN", "t"),
+            ("VAPID_PRIVATE_KEY", "key"),
+            ("VAPID_SUBJECT", "mailto:ops@example.com"),
+        ];
+        let error = Config::load(None, &env(&vars));
+        assert!(
+            matches!(&error, Err(ConfigError::Invalid { key, .. }) if key == "push.vapid_private_key"),
+            "{error:?}"
+        );
+
+        let (config, _) = Config::load(
+            None,
+            &env(&[vars.as_slice(), &[("DATABASE_URL", "sqlite::memory:")]].concat()),
+        )?;
+        assert_eq!(
+            config.push.vapid_private_key.as_ref().map(Secret::expose),
+            Some("key")
+        );
+        assert_eq!(
+            config.push.subject.as_deref(),
+            Some("mailto:ops@example.com")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_anonymize_needs_an_auth_token() -> anyhow::Result<()> {
+        let vars = [("GITHUB_TOKEN", "t"), ("ANONYMIZE", "true")];
+        let error = Config::load(None, &env(&vars));
+        assert!(
+            matches!(&error, Err(ConfigError::Invalid { key, .. }) if key == "server.auth_token"),
+            "{error:?}"
+        );
+
+        let (config, _) = Config::load(
+            None,
+            &env(&[vars.as_slice(), &[("AUTH_TOKEN", "secret")]].concat()),
+        )?;
+        assert!(config.server.anonymize);
+        Ok(())
+    }
+
+    #[test]
+    fn test_exporting_to_redis_needs_a_redis_url() -> anyhow::Result<()> {
+        let vars = [
+            ("GITHUB_TOKEN", "t"),
+            ("MODE", "exporter"),
+            ("EXPORTER_OUTPUT", "redis"),
+        ];
+        let error = Config::load(None, &env(&vars));
+        assert!(
+            matches!(&error, Err(ConfigError::Invalid { key, .. }) if key == "server.exporter_output"),
+            "{error:?}"
+        );
+
+        let (config, _) = Config::load(
+            None,
+            &env(&[vars.as_slice(), &[("REDIS_URL", "redis://redis:6379")]].concat()),
+        )?;
+        assert_eq!(config.server.mode, ServerMode::Exporter);
+        assert_eq!(config.server.exporter_output, ExporterOutput::Redis);
+        Ok(())
+    }
+
+    #[test]
+    fn test_forwarded_identities_need_a_trusted_proxy() -> anyhow::Result<()> {
+        let vars = [("GITHUB_TOKEN", "t"), ("AUTH_MODE", "forward_headers")];
+        let error = Config::load(None, &env(&vars));
+        assert!(
+            matches!(&error, Err(ConfigError::Invalid { key, .. }) if key == "server.auth_mode"),
+            "{error:?}"
+        );
+        let error = Config::load(
+            None,
+            &env(&[("GITHUB_TOKEN", "t"), ("ALLOWED_GROUPS", "sre")]),
+        );
+        assert!(
+            matches!(&error, Err(ConfigError::Invalid { key, .. }) if key == "server.allowed_groups"),
+            "{error:?}"
+        );
+
+        let (config, _) = Config::load(
+            None,
+            &env(&[
+                vars.as_slice(),
+                &[
+                    ("TRUST_PROXY", "true"),
+                    ("ALLOWED_GROUPS", "sre, ci-admins"),
+                ],
+            ]
+            .concat()),
+        )?;
+        assert_eq!(config.server.auth_mode, AuthMode::ForwardHeaders);
+        assert_eq!(config.server.allowed_groups, ["sre", "ci-admins"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_retry_deadline_stays_below_the_poll_interval() -> anyhow::Result<()> {
+        let load = |vars: &[(&str, &str)]| -> anyhow::Result<Duration> {
+            let mut vars = vars.to_vec();
+            vars.push(("GITHUB_TOKEN", "token"));
+            Ok(Config::load(None, &env(&vars))?.0.github_retry_deadline())
+        };
+
+        assert_eq!(load(&[])?, DEFAULT_RETRY_DEADLINE);
+        assert_eq!(
+            load(&[
+                ("GITHUB_RETRY_DEADLINE_SECONDS", "45"),
+                ("POLL_INTERVAL_SECONDS", "60")
+            ])?,
+            Duration::from_secs(45)
+        );
+        assert_eq!(
+            load(&[("GITHUB_RETRY_DEADLINE_SECONDS", "45")])?,
+            Duration::from_secs(29)
+        );
+        assert_eq!(
+            load(&[("POLL_INTERVAL_SECONDS", "10")])?,
+            Duration::from_secs(9)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_token_file_replaces_the_token() -> anyhow::Result<()> {
+        let (config, _) = Config::load(
+            None,
+            &env(&[("GITHUB_TOKEN_FILE", "/run/secrets/github-token")]),
+        )?;
+
+        assert_eq!(
+            config.github.token_file,
+            Some(PathBuf::from("/run/secrets/github-token"))
+        );
+        assert!(matches!(
+            Config::load(
+                None,
+                &env(&[
+                    ("GITHUB_TOKEN", "token"),
+                    ("GITHUB_TOKEN_FILE", "/run/secrets/github-token"),
+                ]),
+            ),
+            Err(ConfigError::Invalid { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_workflow_filters_from_file_and_env() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("gha-dashboard.toml");
+        std::fs::write(
+            &path,
+            r#"
+[filters]
+ignore_workflows = ["stale*"]
+
+[filters.repos."owner/repo"]
+only_workflows = ["CI"]
+"#,
+        )?;
+
+        let (config, unknown_keys) = Config::load(
+            Some(&path),
+            &env(&[("GITHUB_TOKEN", "token"), ("ONLY_WORKFLOWS", "Deploy*, CI")]),
+        )?;
+
+        assert!(unknown_keys.is_empty());
+        let stream_config = config.stream_config()?;
+        let repo = stream_config.workflow_filter_for(&"owner/repo".parse()?);
+        assert!(repo.allows("CI"));
+        assert!(!repo.allows("Deploy production"));
+        let other = stream_config.workflow_filter_for(&"owner/other".parse()?);
+        assert!(other.allows("Deploy production"));
+        assert!(!other.allows("stale-bot"));
+
+        std::fs::write(&path, "[filters]\nignore_workflows = [\"build-[ab]\"]\n")?;
+        let error = Config::load(Some(&path), &env(&[("GITHUB_TOKEN", "token")]))
+            .err()
+            .map(|e| e.to_string());
+        assert!(error.is_some_and(|error| error.contains("line 2")));
+        let error = Config::load(
+            None,
+            &env(&[("GITHUB_TOKEN", "token"), ("IGNORE_WORKFLOWS", "ok,{a,b}")]),
+        )
+        .err()
+        .map(|e| e.to_string());
+        assert!(error.is_some_and(|error| error.starts_with("Invalid IGNORE_WORKFLOWS")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_label_rules_are_checked_at_startup() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("gha-dashboard.toml");
+        let token = env(&[("GITHUB_TOKEN", "token")]);
+        std::fs::write(
+            &path,
+            r#"
+[[labels]]
+name = "release"
+match = { branch = "release/*" }
+
+[[labels]]
+name = "bot"
+match = { actor = '*\[bot\]', event = "pull_request" }
+"#,
+        )?;
+
+        let (config, _) = Config::load(Some(&path), &token)?;
+        assert_eq!(config.stream_config()?.labels, config.labels);
+        assert_eq!(config.labels.len(), 2);
+
+        std::fs::write(
+            &path,
+            "[[labels]]\nname = \"release\"\nmatch = { branch = \"release/[0-9]*\" }\n",
+        )?;
+        let error = Config::load(Some(&path), &token)
+            .err()
+            .map(|e| e.to_string());
+        assert!(error.is_some_and(|error| error.contains("line 3")));
+
+        std::fs::write(&path, "[[labels]]\nname = \"everything\"\nmatch = {}\n")?;
+        assert!(matches!(
+            Config::load(Some(&path), &token),
+            Err(ConfigError::Invalid { key, .. }) if key == "labels[0]"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_slos_are_checked_at_startup() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("gha-dashboard.toml");
+        let token = env(&[("GITHUB_TOKEN", "token")]);
+        let slo = |rate: &str| {
+            format!(
+                "[[slos]]\nrepo = \"owner/repo\"\nworkflow = \"CI\"\nbranch = \"main\"\n\
+                 target_duration_minutes = 30\ntarget_success_rate = {rate}\nwindow_days = 7\n"
+            )
+        };
+
+        std::fs::write(&path, slo("0.95"))?;
+        let (config, _) = Config::load(Some(&path), &token)?;
+        let [slo_config] = config.slos.as_slice() else {
+            anyhow::bail!("expected one SLO, got {:?}", config.slos);
+        };
+        assert_eq!(
+            slo_config.branch.as_ref().map(GlobPattern::as_str),
+            Some("main")
+        );
+        assert!((slo_config.target_success_rate - 0.95).abs() < f64::EPSILON);
+
+        std::fs::write(&path, slo("95"))?;
+        assert!(matches!(
+            Config::load(Some(&path), &token),
+            Err(ConfigError::Invalid { key, .. }) if key == "slos[0]"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_tenants_are_loaded_with_their_own_settings() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("gha-dashboard.toml");
+        let tenant = |name: &str| {
+            format!(
+                "[[tenants]]\nname = \"{name}\"\n[tenants.github]\ntoken = \"token-{name}\"\n\
+                 [tenants.filters]\nrepo_allowlist = [\"owner/{name}\"]\n"
+            )
+        };
+
+        std::fs::write(
+            &path,
+            format!(
+                "[server]\nbind_addr = \"0.0.0.0:8080\"\n{}{}",
+                tenant("frontend"),
+                tenant("backend")
+            ),
+        )?;
+        // The top-level token is not needed when every tenant has its own
+        let (config, _) = Config::load(Some(&path), &env(&[]))?;
+        let [frontend, backend] = config.tenants.as_slice() else {
+            anyhow::bail!("expected two tenants, got {:?}", config.tenants);
+        };
+        let frontend = config.for_tenant(frontend);
+        assert_eq!(frontend.server, config.server);
+        assert_eq!(
+            frontend.github.token.as_ref().map(Secret::expose),
+            Some("token-frontend")
+        );
+        assert_eq!(
+            config.for_tenant(backend).filters.repo_allowlist,
+            [RepoFullName::from_str("owner/backend")?]
+        );
+        assert!(frontend.tenants.is_empty());
+
+        std::fs::write(
+            &path,
+            format!("{}{}", tenant("frontend"), tenant("frontend")),
+        )?;
+        assert!(matches!(
+            Config::load(Some(&path), &env(&[])),
+            Err(ConfigError::Invalid { key, .. }) if key == "tenants[1].name"
+        ));
+        std::fs::write(&path, "[[tenants]]\nname = \"no-token\"\n")?;
+        assert!(matches!(
+            Config::load(Some(&path), &env(&[])),
+            Err(ConfigError::Invalid { key, .. }) if key == "tenants[0].github.token"
+        ));
+        std::fs::write(
+            &path,
+            "[[tenants]]\nname = \"a/b\"\ngithub.api_mode = \"mock\"\n",
+        )?;
+        assert!(matches!(
+            Config::load(Some(&path), &env(&[])),
+            Err(ConfigError::Invalid { key, .. }) if key == "tenants[0].name"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_path_prefers_argument_over_env() {
+        let from_env = env(&[("GHA_CONFIG", "/etc/gha-dashboard.toml")]);
+
+        assert_eq!(
+            Config::path(Some(PathBuf::from("a.toml")), &from_env),
+            Some(PathBuf::from("a.toml"))
+        );
+        assert_eq!(
+            Config::path(None, &from_env),
+            Some(PathBuf::from("/etc/gha-dashboard.toml"))
+        );
+    }
+}
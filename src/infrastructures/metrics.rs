@@ -0,0 +1,567 @@
+use crate::application::services::ReconciliationReport;
+use crate::application::use_cases::slo_evaluation::SloCompliance;
+use crate::domain::models::glob::GlobPattern;
+use crate::domain::models::queue::RepositoryQueue;
+use crate::domain::models::runner::RepositoryRunners;
+use crate::domain::repositories::CompactionReport;
+use crate::infrastructures::telemetry::otlp::SERVICE_NAME;
+use opentelemetry::KeyValue;
+use opentelemetry::global;
+use opentelemetry::metrics::{Counter, Meter, ObservableGauge, UpDownCounter};
+use prometheus::{
+    Encoder, GaugeVec, Histogram, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge,
+    IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::Duration;
+
+/// Reads the requests left in the current GitHub rate limit window, if known.
+type RateLimitSource = Box<dyn Fn() -> Option<u64> + Send + Sync>;
+
+/// Prometheus metrics exposed at `/metrics`, some of them also recorded through
+/// OpenTelemetry in [`Metrics::otel`].
+pub struct Metrics {
+    registry: Registry,
+    /// Requests rejected by the HTTP rate limiter, labeled by route
+    pub http_requests_throttled: IntCounterVec,
+    /// Response body bytes, labeled by route and `uncompressed` or `sent` stage
+    pub http_response_body_bytes: IntCounterVec,
+    /// Attempts of GitHub API calls, labeled by operation and status class
+    pub github_api_requests: IntCounterVec,
+    /// Latency of GitHub API attempts, labeled by operation
+    pub github_api_request_duration: HistogramVec,
+    /// Requests left in the current GitHub rate limit window, as last reported
+    pub github_rate_limit_remaining: IntGauge,
+    /// Currently connected websocket clients
+    pub websocket_clients: IntGauge,
+    /// Round trips of acknowledged websocket heartbeats
+    pub websocket_heartbeat_rtt: Histogram,
+    /// Runs deleted from the run store by compaction
+    pub history_runs_deleted: IntCounter,
+    /// Runs in the run store after the last compaction
+    pub history_runs: IntGauge,
+    /// Runs waiting for a runner in the latest snapshot, labeled by repository
+    pub workflow_runs_queued: IntGaugeVec,
+    /// Self-hosted runners in the last inventory, labeled by repository and `online`,
+    /// `offline` or `busy`
+    pub selfhosted_runners: IntGaugeVec,
+    /// Polled runs that webhooks never pushed, found by reconciliation
+    pub reconciliation_missing_runs: IntCounter,
+    /// Runs shown with a stale status, found by reconciliation
+    pub reconciliation_status_mismatches: IntCounter,
+    /// Panics caught before they could stop a task, labeled by component
+    pub panics: IntCounterVec,
+    /// Signed webhook deliveries of events the dashboard does not handle, labeled by event
+    pub webhook_events_unhandled: IntCounterVec,
+    /// Share of runs meeting each objective with data, labeled by repository, workflow and
+    /// branch
+    pub slo_compliance_ratio: GaugeVec,
+    /// Share of the error budget of each objective with data still left, labeled like
+    /// `slo_compliance_ratio`
+    pub slo_error_budget_remaining_ratio: GaugeVec,
+    pub otel: OtelInstruments,
+}
+
+/// Instruments exported through the OTLP meter provider when a collector is configured.
+pub struct OtelInstruments {
+    /// Attributes recorded with every measurement, the `tenant` of a tenant's metrics
+    attributes: Vec<KeyValue>,
+    /// Attempts of GitHub API calls, with `operation` and `status` attributes
+    pub github_api_requests: Counter<u64>,
+    /// Latency of GitHub API attempts in seconds, with an `operation` attribute
+    pub github_api_request_duration: opentelemetry::metrics::Histogram<f64>,
+    /// Currently connected websocket clients
+    pub websocket_clients: UpDownCounter<i64>,
+    /// Reports what `rate_limit_source` reads whenever the metrics are collected
+    _github_rate_limit_remaining: ObservableGauge<u64>,
+    rate_limit_source: Arc<RwLock<Option<RateLimitSource>>>,
+}
+
+impl OtelInstruments {
+    fn new(meter: &Meter, attributes: Vec<KeyValue>) -> Self {
+        let rate_limit_source: Arc<RwLock<Option<RateLimitSource>>> = Arc::default();
+        let github_rate_limit_remaining = meter
+            .u64_observable_gauge("github.rate_limit.remaining")
+            .with_description("Requests left in the current GitHub rate limit window")
+            .with_callback({
+                let rate_limit_source = Arc::clone(&rate_limit_source);
+                let attributes = attributes.clone();
+                move |observer| {
+                    let remaining = rate_limit_source
+                        .read()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .as_ref()
+                        .and_then(|source| source());
+                    if let Some(remaining) = remaining {
+                        observer.observe(remaining, &attributes);
+                    }
+                }
+            })
+            .build();
+        Self {
+            github_api_requests: meter
+                .u64_counter("github.api.requests")
+                .with_description("Number of GitHub API requests, including retries")
+                .build(),
+            github_api_request_duration: meter
+                .f64_histogram("github.api.request.duration")
+                .with_description("Latency of GitHub API requests")
+                .with_unit("s")
+                .build(),
+            websocket_clients: meter
+                .i64_up_down_counter("websocket.clients")
+                .with_description("Number of connected websocket clients")
+                .build(),
+            _github_rate_limit_remaining: github_rate_limit_remaining,
+            rate_limit_source,
+            attributes,
+        }
+    }
+
+    /// [`Self::attributes`] followed by `extra`.
+    fn attributes(&self, extra: &[KeyValue]) -> Vec<KeyValue> {
+        self.attributes.iter().chain(extra).cloned().collect()
+    }
+}
+
+impl Metrics {
+    /// Creates the metrics and registers them on a fresh registry.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a metric definition is invalid.
+    pub fn new() -> Result<Self, prometheus::Error> {
+        Self::with_registry(Registry::new(), &global::meter(SERVICE_NAME), Vec::new())
+    }
+
+    /// Creates the metrics with the OpenTelemetry instruments on `meter` rather than the
+    /// global meter provider, e.g. to read them back from an in-memory exporter.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a metric definition is invalid.
+    pub fn with_meter(meter: &Meter) -> Result<Self, prometheus::Error> {
+        Self::with_registry(Registry::new(), meter, Vec::new())
+    }
+
+    /// Creates the metrics of one tenant, each labeled `tenant="{name}"`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a metric definition or the name is invalid.
+    pub fn for_tenant(name: &str) -> Result<Self, prometheus::Error> {
+        let labels = std::collections::HashMap::from([("tenant".to_string(), name.to_string())]);
+        Self::with_registry(
+            Registry::new_custom(None, Some(labels))?,
+            &global::meter(SERVICE_NAME),
+            vec![KeyValue::new("tenant", name.to_string())],
+        )
+    }
+
+    fn with_registry(
+        registry: Registry,
+        meter: &Meter,
+        attributes: Vec<KeyValue>,
+    ) -> Result<Self, prometheus::Error> {
+        let (http_requests_throttled, http_response_body_bytes) = http_counters(&registry)?;
+
+        let github_api_requests = IntCounterVec::new(
+            Opts::new(
+                "github_api_requests_total",
+                "Number of GitHub API requests, including retries",
+            ),
+            &["operation", "status"],
+        )?;
+        registry.register(Box::new(github_api_requests.clone()))?;
+
+        let github_api_request_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "github_api_request_duration_seconds",
+                "Latency of GitHub API requests",
+            ),
+            &["operation"],
+        )?;
+        registry.register(Box::new(github_api_request_duration.clone()))?;
+
+        let github_rate_limit_remaining = IntGauge::new(
+            "github_rate_limit_remaining",
+            "Requests left in the current GitHub rate limit window",
+        )?;
+        registry.register(Box::new(github_rate_limit_remaining.clone()))?;
+
+        let (websocket_clients, websocket_heartbeat_rtt) = websocket_metrics(&registry)?;
+
+        let history_runs_deleted = IntCounter::new(
+            "history_runs_deleted_total",
+            "Number of runs deleted from the run store by compaction",
+        )?;
+        registry.register(Box::new(history_runs_deleted.clone()))?;
+
+        let history_runs = IntGauge::new(
+            "history_runs",
+            "Number of runs in the run store after the last compaction",
+        )?;
+        registry.register(Box::new(history_runs.clone()))?;
+
+        let workflow_runs_queued = IntGaugeVec::new(
+            Opts::new(
+                "workflow_runs_queued",
+                "Number of queued workflow runs in the latest snapshot",
+            ),
+            &["repository"],
+        )?;
+        registry.register(Box::new(workflow_runs_queued.clone()))?;
+
+        let selfhosted_runners = IntGaugeVec::new(
+            Opts::new(
+                "selfhosted_runners",
+                "Number of self-hosted runners in the last inventory; busy ones are also online",
+            ),
+            &["repository", "status"],
+        )?;
+        registry.register(Box::new(selfhosted_runners.clone()))?;
+
+        let reconciliation_missing_runs = IntCounter::new(
+            "reconciliation_missing_runs_total",
+            "Number of polled runs that no webhook had pushed",
+        )?;
+        registry.register(Box::new(reconciliation_missing_runs.clone()))?;
+
+        let reconciliation_status_mismatches = IntCounter::new(
+            "reconciliation_status_mismatches_total",
+            "Number of runs shown with a different status than polled",
+        )?;
+        registry.register(Box::new(reconciliation_status_mismatches.clone()))?;
+
+        let panics = IntCounterVec::new(
+            Opts::new("panics_total", "Number of panics caught and recovered from"),
+            &["component"],
+        )?;
+        registry.register(Box::new(panics.clone()))?;
+
+        let webhook_events_unhandled = IntCounterVec::new(
+            Opts::new(
+                "webhook_events_unhandled_total",
+                "Number of signed webhook deliveries of events that are not handled",
+            ),
+            &["event"],
+        )?;
+        registry.register(Box::new(webhook_events_unhandled.clone()))?;
+
+        let (slo_compliance_ratio, slo_error_budget_remaining_ratio) = slo_gauges(&registry)?;
+
+        Ok(Self {
+            registry,
+            http_requests_throttled,
+            http_response_body_bytes,
+            github_api_requests,
+            github_api_request_duration,
+            github_rate_limit_remaining,
+            websocket_clients,
+            websocket_heartbeat_rtt,
+            history_runs_deleted,
+            history_runs,
+            workflow_runs_queued,
+            selfhosted_runners,
+            reconciliation_missing_runs,
+            reconciliation_status_mismatches,
+            panics,
+            webhook_events_unhandled,
+            slo_compliance_ratio,
+            slo_error_budget_remaining_ratio,
+            otel: OtelInstruments::new(meter, attributes),
+        })
+    }
+
+    /// Records one attempt of a GitHub API call; `status` is `None` when no response arrived.
+    pub fn observe_github_api_request(
+        &self,
+        operation: &str,
+        status: Option<u16>,
+        elapsed: Duration,
+    ) {
+        let status_class = match status {
+            Some(status) => format!("{}xx", status / 100),
+            None => "error".to_string(),
+        };
+        self.github_api_requests
+            .with_label_values(&[operation, status_class.as_str()])
+            .inc();
+        self.github_api_request_duration
+            .with_label_values(&[operation])
+            .observe(elapsed.as_secs_f64());
+
+        let operation = KeyValue::new("operation", operation.to_string());
+        self.otel.github_api_requests.add(
+            1,
+            &self
+                .otel
+                .attributes(&[operation.clone(), KeyValue::new("status", status_class)]),
+        );
+        self.otel
+            .github_api_request_duration
+            .record(elapsed.as_secs_f64(), &self.otel.attributes(&[operation]));
+    }
+
+    /// Counts a websocket client in until [`Self::websocket_disconnected`].
+    pub fn websocket_connected(&self) {
+        self.websocket_clients.inc();
+        self.otel
+            .websocket_clients
+            .add(1, &self.otel.attributes(&[]));
+    }
+
+    pub fn websocket_disconnected(&self) {
+        self.websocket_clients.dec();
+        self.otel
+            .websocket_clients
+            .add(-1, &self.otel.attributes(&[]));
+    }
+
+    /// Reports what `remaining` reads as the OpenTelemetry rate limit gauge, replacing the
+    /// previous source; nothing is reported while it reads `None`.
+    pub fn observe_rate_limit(&self, remaining: impl Fn() -> Option<u64> + Send + Sync + 'static) {
+        *self
+            .otel
+            .rate_limit_source
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = Some(Box::new(remaining));
+    }
+
+    pub fn record_compaction(&self, report: &CompactionReport) {
+        self.history_runs_deleted.inc_by(report.deleted);
+        self.history_runs
+            .set(i64::try_from(report.remaining).unwrap_or(i64::MAX));
+    }
+
+    /// Replaces the queue depths, dropping repositories no longer polled.
+    pub fn record_queue(&self, queue: &[RepositoryQueue]) {
+        self.workflow_runs_queued.reset();
+        for repository in queue {
+            self.workflow_runs_queued
+                .with_label_values(&[repository.repository.to_string().as_str()])
+                .set(i64::try_from(repository.queued).unwrap_or(i64::MAX));
+        }
+    }
+
+    /// Replaces the runner counts, dropping repositories no longer listed.
+    pub fn record_runners(&self, repositories: &[RepositoryRunners]) {
+        self.selfhosted_runners.reset();
+        for runners in repositories {
+            let repository = runners.repository.to_string();
+            for (status, count) in [
+                ("online", runners.online),
+                ("offline", runners.offline),
+                ("busy", runners.busy),
+            ] {
+                self.selfhosted_runners
+                    .with_label_values(&[repository.as_str(), status])
+                    .set(i64::try_from(count).unwrap_or(i64::MAX));
+            }
+        }
+    }
+
+    pub fn record_reconciliation(&self, report: &ReconciliationReport) {
+        self.reconciliation_missing_runs
+            .inc_by(report.missing_runs.len() as u64);
+        self.reconciliation_status_mismatches
+            .inc_by(report.status_mismatches.len() as u64);
+    }
+
+    /// Replaces the SLO gauges, leaving out objectives without data rather than reporting
+    /// them as met.
+    pub fn record_slos(&self, slos: &[SloCompliance]) {
+        self.slo_compliance_ratio.reset();
+        self.slo_error_budget_remaining_ratio.reset();
+        for compliance in slos {
+            let slo = &compliance.slo;
+            let repository = slo.repo.to_string();
+            let labels = [
+                repository.as_str(),
+                slo.workflow.as_ref().map_or("", GlobPattern::as_str),
+                slo.branch.as_ref().map_or("", GlobPattern::as_str),
+            ];
+            if let Some(ratio) = compliance.compliance {
+                self.slo_compliance_ratio
+                    .with_label_values(&labels)
+                    .set(ratio);
+            }
+            if let Some(ratio) = compliance.error_budget_remaining {
+                self.slo_error_budget_remaining_ratio
+                    .with_label_values(&labels)
+                    .set(ratio);
+            }
+        }
+    }
+
+    /// Renders all metrics in the Prometheus text exposition format.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if encoding fails.
+    pub fn render(&self) -> Result<String, prometheus::Error> {
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        String::from_utf8(buffer).map_err(|e| prometheus::Error::Msg(e.to_string()))
+    }
+}
+
+/// The throttled request and response body byte counters of the HTTP routes, registered on
+/// `registry`.
+fn http_counters(registry: &Registry) -> Result<(IntCounterVec, IntCounterVec), prometheus::Error> {
+    let requests_throttled = IntCounterVec::new(
+        Opts::new(
+            "http_requests_throttled_total",
+            "Number of HTTP requests rejected by the rate limiter",
+        ),
+        &["route"],
+    )?;
+    registry.register(Box::new(requests_throttled.clone()))?;
+
+    let response_body_bytes = IntCounterVec::new(
+        Opts::new(
+            "http_response_body_bytes_total",
+            "Bytes of HTTP response bodies, before compression and as sent",
+        ),
+        &["route", "stage"],
+    )?;
+    registry.register(Box::new(response_body_bytes.clone()))?;
+    Ok((requests_throttled, response_body_bytes))
+}
+
+/// The connected client gauge and heartbeat round trip histogram of the websocket, registered
+/// on `registry`.
+fn websocket_metrics(registry: &Registry) -> Result<(IntGauge, Histogram), prometheus::Error> {
+    let clients = IntGauge::new("websocket_clients", "Number of connected websocket clients")?;
+    registry.register(Box::new(clients.clone()))?;
+
+    let heartbeat_rtt = Histogram::with_opts(HistogramOpts::new(
+        "websocket_heartbeat_rtt_seconds",
+        "Round trip of websocket heartbeats, from sending one to its acknowledgment",
+    ))?;
+    registry.register(Box::new(heartbeat_rtt.clone()))?;
+    Ok((clients, heartbeat_rtt))
+}
+
+/// The compliance and error budget gauges of the SLOs, registered on `registry`.
+fn slo_gauges(registry: &Registry) -> Result<(GaugeVec, GaugeVec), prometheus::Error> {
+    // Unset workflow and branch filters are empty
+    let labels = ["repository", "workflow", "branch"];
+    let compliance = GaugeVec::new(
+        Opts::new(
+            "slo_compliance_ratio",
+            "Share of the runs in the window of an SLO that met it",
+        ),
+        &labels,
+    )?;
+    registry.register(Box::new(compliance.clone()))?;
+
+    let error_budget_remaining = GaugeVec::new(
+        Opts::new(
+            "slo_error_budget_remaining_ratio",
+            "Share of the failures an SLO tolerates still left, negative once overspent",
+        ),
+        &labels,
+    )?;
+    registry.register(Box::new(error_budget_remaining.clone()))?;
+    Ok((compliance, error_budget_remaining))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{exported_value, in_memory_metrics};
+    use opentelemetry_sdk::metrics::data::{
+        AggregatedMetrics, HistogramDataPoint, MetricData, ResourceMetrics, ScopeMetrics,
+    };
+
+    #[test]
+    fn test_render_includes_labeled_counter() -> anyhow::Result<()> {
+        let metrics = Metrics::new()?;
+        metrics
+            .http_requests_throttled
+            .with_label_values(&["/refresh"])
+            .inc();
+
+        let rendered = metrics.render()?;
+
+        assert!(rendered.contains(r#"http_requests_throttled_total{route="/refresh"} 1"#));
+        Ok(())
+    }
+
+    #[test]
+    fn test_github_api_requests_are_labeled_by_status_class() -> anyhow::Result<()> {
+        let metrics = Metrics::new()?;
+        let elapsed = Duration::from_millis(120);
+        metrics.observe_github_api_request("workflow_runs", Some(200), elapsed);
+        metrics.observe_github_api_request("workflow_runs", Some(204), elapsed);
+        metrics.observe_github_api_request("workflow_runs", Some(502), elapsed);
+        metrics.observe_github_api_request("workflow_runs", None, elapsed);
+
+        let rendered = metrics.render()?;
+
+        for line in [
+            r#"github_api_requests_total{operation="workflow_runs",status="2xx"} 2"#,
+            r#"github_api_requests_total{operation="workflow_runs",status="5xx"} 1"#,
+            r#"github_api_requests_total{operation="workflow_runs",status="error"} 1"#,
+            r#"github_api_request_duration_seconds_count{operation="workflow_runs"} 4"#,
+        ] {
+            assert!(rendered.contains(line), "{line} missing");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_otel_instruments_reach_the_exporter() -> anyhow::Result<()> {
+        let (metrics, provider, exporter) = in_memory_metrics()?;
+        let elapsed = Duration::from_millis(120);
+        metrics.observe_github_api_request("workflow_runs", Some(200), elapsed);
+        metrics.observe_github_api_request("workflow_runs", Some(204), elapsed);
+        metrics.observe_github_api_request("workflow_runs", Some(502), elapsed);
+        metrics.websocket_connected();
+        metrics.websocket_connected();
+        metrics.websocket_disconnected();
+        metrics.observe_rate_limit(|| Some(4321));
+
+        provider.force_flush()?;
+
+        let requests = |status: &str| {
+            exported_value(
+                &exporter,
+                "github.api.requests",
+                &[
+                    KeyValue::new("operation", "workflow_runs"),
+                    KeyValue::new("status", status.to_string()),
+                ],
+            )
+        };
+        assert_eq!(requests("2xx")?, Some(2));
+        assert_eq!(requests("5xx")?, Some(1));
+        assert_eq!(
+            exported_value(&exporter, "websocket.clients", &[])?,
+            Some(1)
+        );
+        assert_eq!(
+            exported_value(&exporter, "github.rate_limit.remaining", &[])?,
+            Some(4321)
+        );
+        let finished = exporter.get_finished_metrics()?;
+        let latencies: u64 = finished
+            .iter()
+            .flat_map(ResourceMetrics::scope_metrics)
+            .flat_map(ScopeMetrics::metrics)
+            .filter(|metric| metric.name() == "github.api.request.duration")
+            .filter_map(|metric| match metric.data() {
+                AggregatedMetrics::F64(MetricData::Histogram(histogram)) => Some(
+                    histogram
+                        .data_points()
+                        .map(HistogramDataPoint::count)
+                        .sum::<u64>(),
+                ),
+                _ => None,
+            })
+            .sum();
+        assert_eq!(latencies, 3);
+        Ok(())
+    }
+}
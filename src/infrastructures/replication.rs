@@ -0,0 +1,398 @@
+use crate::application::services::{PollerEvent, SharedPoller};
+use crate::application::use_cases::StreamGitHubActionsRunsUseCaseOutput;
+use crate::application::use_cases::stream_github_actions_runs::StreamGitHubActionsRunsUseCase;
+use crate::application::use_cases::stream_github_actions_runs::document::SnapshotDocument;
+use chrono::Utc;
+use futures_util::StreamExt;
+use redis::aio::ConnectionManager;
+use redis::{Client, RedisResult, Script};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+/// How long the leader lock outlives its last renewal (seconds)
+const LOCK_TTL_SECONDS: u64 = 15;
+
+/// Wait before resubscribing after the subscription to snapshots drops (seconds)
+const RESUBSCRIBE_WAIT_SECONDS: u64 = 5;
+
+/// Extends the lock only while this replica still holds it
+const RENEW_SCRIPT: &str = r"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('PEXPIRE', KEYS[1], ARGV[2])
+end
+return 0
+";
+
+/// Aborts the task when dropped, so the tasks of an aborted replication stop with it.
+struct AbortOnDrop(JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// A snapshot as published on the channel.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct SnapshotMessage {
+    /// Replica that polled the snapshot, which ignores its own messages
+    replica: String,
+    #[serde(flatten)]
+    snapshot: SnapshotDocument,
+}
+
+impl SnapshotMessage {
+    fn new(replica: &str, output: &StreamGitHubActionsRunsUseCaseOutput) -> Self {
+        Self {
+            replica: replica.to_string(),
+            snapshot: SnapshotDocument::from(output),
+        }
+    }
+
+    fn into_output(self) -> StreamGitHubActionsRunsUseCaseOutput {
+        self.snapshot.into()
+    }
+}
+
+/// Shares one poller between replicas through Redis.
+///
+/// The replica holding a lock in Redis polls GitHub and publishes each snapshot on a
+/// channel; every other replica feeds the snapshots it receives to its local poller's
+/// subscribers. The lock expires unless renewed, so another replica takes over within
+/// about `lock_ttl` plus a third of it when the leader dies.
+pub struct RedisReplication {
+    client: Client,
+    replica: String,
+    lock_key: String,
+    channel: String,
+    lock_ttl: Duration,
+}
+
+impl RedisReplication {
+    /// Connects lazily to the Redis server at `url`, e.g. `redis://redis:6379`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `url` is not a valid Redis URL.
+    pub fn new(url: &str) -> RedisResult<Self> {
+        Ok(Self {
+            client: Client::open(url)?,
+            replica: format!(
+                "{}-{}",
+                std::process::id(),
+                Utc::now().timestamp_nanos_opt().unwrap_or_default()
+            ),
+            lock_key: "gha-dashboard:poller-leader".to_string(),
+            channel: "gha-dashboard:snapshots".to_string(),
+            lock_ttl: Duration::from_secs(LOCK_TTL_SECONDS),
+        })
+    }
+
+    /// Prefixes the lock key and channel with `namespace` instead of `gha-dashboard`.
+    #[must_use]
+    pub fn with_namespace(mut self, namespace: &str) -> Self {
+        self.lock_key = format!("{namespace}:poller-leader");
+        self.channel = format!("{namespace}:snapshots");
+        self
+    }
+
+    /// Lets the lock expire `lock_ttl` after its last renewal; renewals happen every third of it.
+    #[must_use]
+    pub fn with_lock_ttl(mut self, lock_ttl: Duration) -> Self {
+        self.lock_ttl = lock_ttl;
+        self
+    }
+
+    /// Identifier of this replica, stored in the lock while it leads.
+    #[must_use]
+    pub fn replica(&self) -> &str {
+        &self.replica
+    }
+
+    /// Starts following the channel and competing for the lock.
+    ///
+    /// While this replica holds the lock, `poller` runs `use_case` and its snapshots are
+    /// published; the poller stops as soon as the lock cannot be renewed. Aborting the
+    /// returned task stops following, polling and publishing too.
+    pub fn spawn<U>(self: &Arc<Self>, poller: Arc<SharedPoller>, use_case: Arc<U>) -> JoinHandle<()>
+    where
+        U: StreamGitHubActionsRunsUseCase + Send + Sync + 'static,
+    {
+        let subscriber = AbortOnDrop(tokio::spawn(Arc::clone(self).follow(Arc::clone(&poller))));
+        let replication = Arc::clone(self);
+        tokio::spawn(async move {
+            let _subscriber = subscriber;
+            replication.elect(poller, use_case).await;
+        })
+    }
+
+    /// Feeds snapshots published by other replicas to `poller`.
+    async fn follow(self: Arc<Self>, poller: Arc<SharedPoller>) {
+        loop {
+            match self.client.get_async_pubsub().await {
+                Ok(mut pubsub) => match pubsub.subscribe(&self.channel).await {
+                    Ok(()) => {
+                        let mut messages = pubsub.on_message();
+                        while let Some(message) = messages.next().await {
+                            match serde_json::from_slice::<SnapshotMessage>(
+                                message.get_payload_bytes(),
+                            ) {
+                                Ok(snapshot) if snapshot.replica == self.replica => {}
+                                Ok(snapshot) => poller.publish(snapshot.into_output()),
+                                Err(e) => tracing::warn!("Ignoring malformed snapshot: {}", e),
+                            }
+                        }
+                        tracing::warn!("Snapshot subscription closed");
+                    }
+                    Err(e) => tracing::warn!("Failed to subscribe to snapshots: {}", e),
+                },
+                Err(e) => tracing::warn!("Failed to connect to Redis for snapshots: {}", e),
+            }
+            tokio::time::sleep(Duration::from_secs(RESUBSCRIBE_WAIT_SECONDS)).await;
+        }
+    }
+
+    /// Competes for the lock forever, running the poller while it is held.
+    async fn elect<U>(&self, poller: Arc<SharedPoller>, use_case: Arc<U>)
+    where
+        U: StreamGitHubActionsRunsUseCase + Send + Sync + 'static,
+    {
+        let mut connection = None;
+        // Polling and publishing tasks, aborted when dropped
+        let mut leading: Option<(AbortOnDrop, AbortOnDrop)> = None;
+        loop {
+            let held = match self.connection(&mut connection).await {
+                Ok(connection) if leading.is_some() => self.renew(connection).await,
+                Ok(connection) => self.acquire(connection).await,
+                Err(e) => Err(e),
+            };
+            match (held, leading.take()) {
+                (Ok(true), Some(tasks)) => leading = Some(tasks),
+                (Ok(true), None) => {
+                    tracing::info!("Replica {} became the poller leader", self.replica);
+                    leading = Some((
+                        AbortOnDrop(poller.spawn(Arc::clone(&use_case))),
+                        AbortOnDrop(self.spawn_publisher(&poller)),
+                    ));
+                }
+                // Without a renewed lock another replica may lead, so stop polling by dropping
+                // the tasks
+                (held, Some(_)) => {
+                    if let Err(e) = held {
+                        tracing::warn!("Failed to renew the poller lock: {}", e);
+                    }
+                    tracing::warn!("Replica {} stopped leading the poller", self.replica);
+                }
+                (Ok(false), None) => {}
+                (Err(e), None) => tracing::warn!("Failed to acquire the poller lock: {}", e),
+            }
+            tokio::time::sleep(self.lock_ttl / 3).await;
+        }
+    }
+
+    /// Connects on first use; the manager reconnects by itself afterwards.
+    async fn connection<'a>(
+        &self,
+        connection: &'a mut Option<ConnectionManager>,
+    ) -> RedisResult<&'a mut ConnectionManager> {
+        if connection.is_none() {
+            *connection = Some(self.client.get_connection_manager().await?);
+        }
+        connection
+            .as_mut()
+            .ok_or_else(|| redis::RedisError::from((redis::ErrorKind::Client, "No connection")))
+    }
+
+    fn lock_ttl_millis(&self) -> u64 {
+        u64::try_from(self.lock_ttl.as_millis()).unwrap_or(u64::MAX)
+    }
+
+    async fn acquire(&self, connection: &mut ConnectionManager) -> RedisResult<bool> {
+        let reply: Option<String> = redis::cmd("SET")
+            .arg(&self.lock_key)
+            .arg(&self.replica)
+            .arg("NX")
+            .arg("PX")
+            .arg(self.lock_ttl_millis())
+            .query_async(connection)
+            .await?;
+        Ok(reply.is_some())
+    }
+
+    async fn renew(&self, connection: &mut ConnectionManager) -> RedisResult<bool> {
+        let renewed: i64 = Script::new(RENEW_SCRIPT)
+            .key(&self.lock_key)
+            .arg(&self.replica)
+            .arg(self.lock_ttl_millis())
+            .invoke_async(connection)
+            .await?;
+        Ok(renewed == 1)
+    }
+
+    /// Publishes the snapshots of the local poller on the channel.
+    fn spawn_publisher(&self, poller: &SharedPoller) -> JoinHandle<()> {
+        let mut events = poller.subscribe();
+        let client = self.client.clone();
+        let channel = self.channel.clone();
+        let replica = self.replica.clone();
+        tokio::spawn(async move {
+            let mut connection = None;
+            loop {
+                let output = match events.recv().await {
+                    Ok(PollerEvent::Snapshot(output)) => output,
+                    Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                let payload = match serde_json::to_string(&SnapshotMessage::new(&replica, &output))
+                {
+                    Ok(payload) => payload,
+                    Err(e) => {
+                        tracing::error!("Failed to serialize snapshot for Redis: {:?}", e);
+                        continue;
+                    }
+                };
+                if connection.is_none() {
+                    connection = client.get_connection_manager().await.ok();
+                }
+                let Some(connection) = connection.as_mut() else {
+                    tracing::warn!("Failed to connect to Redis, snapshot not shared");
+                    continue;
+                };
+                let published: RedisResult<i64> = redis::cmd("PUBLISH")
+                    .arg(&channel)
+                    .arg(payload)
+                    .query_async(connection)
+                    .await;
+                if let Err(e) = published {
+                    tracing::warn!("Failed to publish snapshot: {}", e);
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::use_cases::StreamGitHubActionsRunsInteractor;
+    use crate::domain::external_apis::github::Repository;
+    use crate::test_support::{StubGitHubApi, base_time, workflow_run};
+    use tokio::sync::Notify;
+    use tokio::time::timeout;
+
+    #[test]
+    fn test_snapshot_message_round_trips() -> anyhow::Result<()> {
+        let output = StreamGitHubActionsRunsUseCaseOutput::new(
+            vec![workflow_run("owner/repo", 1, "success")],
+            base_time(),
+        )
+        .with_repositories(vec![Repository::new("owner", "repo")?]);
+        let message = SnapshotMessage::new("replica-1", &output);
+
+        let decoded: SnapshotMessage = serde_json::from_str(&serde_json::to_string(&message)?)?;
+
+        assert_eq!(decoded, message);
+        let restored = decoded.into_output();
+        assert_eq!(restored.runs, output.runs);
+        assert_eq!(restored.repositories, output.repositories);
+        assert_eq!(restored.generated_at, output.generated_at);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_aborting_the_replication_stops_its_tasks() -> anyhow::Result<()> {
+        // Nothing listens there, so the replica keeps retrying to follow and to lead
+        let replication = Arc::new(RedisReplication::new("redis://127.0.0.1:1")?);
+        let poller = Arc::new(SharedPoller::new(Arc::new(Notify::new())));
+        let use_case = Arc::new(StreamGitHubActionsRunsInteractor::new(Arc::new(
+            StubGitHubApi::default(),
+        )));
+        let handle = replication.spawn(Arc::clone(&poller), use_case);
+        timeout(Duration::from_secs(5), async {
+            while Arc::strong_count(&poller) < 3 {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await?;
+
+        handle.abort();
+
+        // Both the election and the subscriber let go of the poller
+        timeout(Duration::from_secs(5), async {
+            while Arc::strong_count(&poller) > 1 {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// A replica following `namespace` on the Redis server at `REDIS_URL`.
+    fn replica(
+        namespace: &str,
+    ) -> anyhow::Result<(Arc<RedisReplication>, Arc<SharedPoller>, JoinHandle<()>)> {
+        let url = std::env::var("REDIS_URL")?;
+        let replication = Arc::new(
+            RedisReplication::new(&url)?
+                .with_namespace(namespace)
+                .with_lock_ttl(Duration::from_millis(900)),
+        );
+        let poller = Arc::new(SharedPoller::new(Arc::new(Notify::new())));
+        let mut github_api = StubGitHubApi::with_repository("owner", "repo");
+        github_api.runs = vec![workflow_run("owner/repo", 1, "success")];
+        let use_case = Arc::new(StreamGitHubActionsRunsInteractor::new(Arc::new(github_api)));
+        let handle = replication.spawn(Arc::clone(&poller), use_case);
+        Ok((replication, poller, handle))
+    }
+
+    async fn leader(client: &Client, namespace: &str) -> anyhow::Result<Option<String>> {
+        let mut connection = client.get_multiplexed_async_connection().await?;
+        Ok(redis::cmd("GET")
+            .arg(format!("{namespace}:poller-leader"))
+            .query_async(&mut connection)
+            .await?)
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a Redis server at REDIS_URL"]
+    async fn test_followers_receive_snapshots_and_take_over() -> anyhow::Result<()> {
+        let namespace = format!("gha-dashboard-test-{}", std::process::id());
+        let (first, first_poller, first_handle) = replica(&namespace)?;
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        let (second, second_poller, second_handle) = replica(&namespace)?;
+        let client = Client::open(std::env::var("REDIS_URL")?)?;
+
+        // The first replica leads and the second one sees its snapshots
+        let mut events = second_poller.subscribe();
+        let snapshot = timeout(Duration::from_secs(5), async {
+            loop {
+                if let PollerEvent::Snapshot(output) = events.recv().await? {
+                    return anyhow::Ok(output);
+                }
+            }
+        })
+        .await??;
+        assert_eq!(snapshot.runs.len(), 1);
+        assert!(first_poller.snapshots().latest().is_some());
+        assert_eq!(
+            leader(&client, &namespace).await?.as_deref(),
+            Some(first.replica())
+        );
+
+        // Once the leader dies, the second replica acquires the lock within the TTL bound
+        first_handle.abort();
+        timeout(Duration::from_secs(3), async {
+            while leader(&client, &namespace).await?.as_deref() != Some(second.replica()) {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+            anyhow::Ok(())
+        })
+        .await??;
+
+        second_handle.abort();
+        Ok(())
+    }
+}
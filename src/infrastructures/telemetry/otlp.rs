@@ -0,0 +1,363 @@
+use super::TelemetryConfig;
+use crate::infrastructures::config::Secret;
+use axum::http::{HeaderMap, HeaderName, HeaderValue};
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::tonic_types::metadata::MetadataMap;
+use opentelemetry_otlp::{
+    ExporterBuildError, MetricExporter, Protocol, SpanExporter, WithExportConfig, WithHttpConfig,
+    WithTonicConfig,
+};
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::metrics::SdkMeterProvider;
+use opentelemetry_sdk::metrics::exporter::PushMetricExporter;
+use opentelemetry_sdk::trace::{Sampler, SdkTracerProvider};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+
+/// `service.name` of the exported traces and metrics
+pub const SERVICE_NAME: &str = "gha-dashboard";
+
+/// Paths the HTTP exporters append to the collector endpoint, as the OTLP spec requires
+const HTTP_TRACES_PATH: &str = "/v1/traces";
+const HTTP_METRICS_PATH: &str = "/v1/metrics";
+
+/// How spans are sent to the collector (`OTEL_EXPORTER_OTLP_PROTOCOL`).
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OtlpProtocol {
+    /// Protobuf over gRPC, usually on port 4317
+    #[default]
+    #[serde(rename = "grpc")]
+    Grpc,
+    /// Protobuf over HTTP, usually on port 4318
+    #[serde(rename = "http/protobuf")]
+    HttpProtobuf,
+}
+
+impl FromStr for OtlpProtocol {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "grpc" => Ok(Self::Grpc),
+            "http/protobuf" => Ok(Self::HttpProtobuf),
+            _ => {
+                anyhow::bail!("Unsupported OTLP protocol {value:?}, expected grpc or http/protobuf")
+            }
+        }
+    }
+}
+
+/// Which traces are sampled (`OTEL_TRACES_SAMPLER`), named as in the OpenTelemetry spec.
+///
+/// The ratio of the `traceidratio` samplers is the separate `traces_sampler_arg`.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TraceSampler {
+    #[serde(rename = "always_on")]
+    AlwaysOn,
+    #[serde(rename = "always_off")]
+    AlwaysOff,
+    #[serde(rename = "traceidratio")]
+    TraceIdRatio,
+    /// Follows the caller's decision, sampling every trace started here
+    #[default]
+    #[serde(rename = "parentbased_always_on")]
+    ParentBasedAlwaysOn,
+    #[serde(rename = "parentbased_always_off")]
+    ParentBasedAlwaysOff,
+    #[serde(rename = "parentbased_traceidratio")]
+    ParentBasedTraceIdRatio,
+}
+
+impl FromStr for TraceSampler {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "always_on" => Ok(Self::AlwaysOn),
+            "always_off" => Ok(Self::AlwaysOff),
+            "traceidratio" => Ok(Self::TraceIdRatio),
+            "parentbased_always_on" => Ok(Self::ParentBasedAlwaysOn),
+            "parentbased_always_off" => Ok(Self::ParentBasedAlwaysOff),
+            "parentbased_traceidratio" => Ok(Self::ParentBasedTraceIdRatio),
+            _ => anyhow::bail!("Unsupported trace sampler {value:?}"),
+        }
+    }
+}
+
+impl TraceSampler {
+    /// The SDK sampler, sampling `ratio` of the traces for the ratio samplers (all when unset).
+    #[must_use]
+    pub fn sampler(self, ratio: Option<f64>) -> Sampler {
+        let ratio = Sampler::TraceIdRatioBased(ratio.unwrap_or(1.0));
+        match self {
+            Self::AlwaysOn => Sampler::AlwaysOn,
+            Self::AlwaysOff => Sampler::AlwaysOff,
+            Self::TraceIdRatio => ratio,
+            Self::ParentBasedAlwaysOn => Sampler::ParentBased(Box::new(Sampler::AlwaysOn)),
+            Self::ParentBasedAlwaysOff => Sampler::ParentBased(Box::new(Sampler::AlwaysOff)),
+            Self::ParentBasedTraceIdRatio => Sampler::ParentBased(Box::new(ratio)),
+        }
+    }
+}
+
+/// Parses `OTEL_EXPORTER_OTLP_HEADERS`: comma-separated `name=value` pairs with
+/// percent-encoded values, e.g. `authorization=Bearer%20secret`.
+///
+/// # Errors
+///
+/// Returns a message naming the first pair that is not a valid HTTP header.
+pub fn parse_headers(value: &str) -> Result<BTreeMap<String, Secret>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair
+                .split_once('=')
+                .ok_or_else(|| format!("{pair:?} is not of the form name=value"))?;
+            let name = name.trim().to_ascii_lowercase();
+            let value = percent_decode(value.trim()).ok_or_else(|| {
+                format!("The value of {name:?} is not valid percent-encoded UTF-8")
+            })?;
+            validate_header(&name, &value)?;
+            Ok((name, Secret::from(value)))
+        })
+        .collect()
+}
+
+/// Checks that `name: value` can be sent as an HTTP header and as gRPC metadata.
+///
+/// # Errors
+///
+/// Returns a message naming the header otherwise.
+pub fn validate_header(name: &str, value: &str) -> Result<(), String> {
+    HeaderName::from_bytes(name.as_bytes())
+        .map_err(|_| format!("{name:?} is not a valid header name"))?;
+    HeaderValue::from_str(value).map_err(|_| format!("The value of {name:?} is not valid"))?;
+    Ok(())
+}
+
+/// Decodes `%XX` escapes; `None` when an escape is malformed or the result is not UTF-8.
+fn percent_decode(value: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut rest = value.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// The URL spans or metrics are posted to over HTTP, for the base `endpoint` of the
+/// collector and the `path` of the signal.
+fn http_url(endpoint: &str, path: &str) -> String {
+    format!("{}{path}", endpoint.trim_end_matches('/'))
+}
+
+/// The configured headers as the HTTP exporters take them.
+fn http_headers(headers: &BTreeMap<String, Secret>) -> HashMap<String, String> {
+    headers
+        .iter()
+        .map(|(name, value)| (name.clone(), value.expose().to_string()))
+        .collect()
+}
+
+/// Headers valid per [`validate_header`] as gRPC metadata; others are left out.
+fn metadata(headers: &BTreeMap<String, Secret>) -> MetadataMap {
+    let headers: HeaderMap = headers
+        .iter()
+        .filter_map(|(name, value)| {
+            Some((
+                HeaderName::from_bytes(name.as_bytes()).ok()?,
+                HeaderValue::from_str(value.expose()).ok()?,
+            ))
+        })
+        .collect();
+    MetadataMap::from_headers(headers)
+}
+
+/// Identifies this service in the collector, whatever the tracer is named.
+fn resource() -> Resource {
+    Resource::builder()
+        .with_service_name(SERVICE_NAME)
+        .with_attribute(KeyValue::new("service.version", env!("CARGO_PKG_VERSION")))
+        .build()
+}
+
+/// A provider sampling as configured and batching spans to `exporter`.
+pub(super) fn tracer_provider_with<E>(config: &TelemetryConfig, exporter: E) -> SdkTracerProvider
+where
+    E: opentelemetry_sdk::trace::SpanExporter + 'static,
+{
+    SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_sampler(config.traces_sampler.sampler(config.traces_sampler_arg))
+        .with_resource(resource())
+        .build()
+}
+
+/// A provider exporting to the configured collector, or `None` when there is none.
+///
+/// The gRPC exporter must be built within a tokio runtime.
+///
+/// # Errors
+///
+/// Returns an error if the exporter cannot be built, e.g. for an invalid endpoint.
+pub(super) fn tracer_provider(
+    config: &TelemetryConfig,
+) -> Result<Option<SdkTracerProvider>, ExporterBuildError> {
+    let Some(endpoint) = &config.otlp_endpoint else {
+        return Ok(None);
+    };
+    let exporter = match config.otlp_protocol {
+        OtlpProtocol::Grpc => SpanExporter::builder()
+            .with_tonic()
+            .with_endpoint(endpoint)
+            .with_metadata(metadata(&config.otlp_headers))
+            .build()?,
+        OtlpProtocol::HttpProtobuf => SpanExporter::builder()
+            .with_http()
+            .with_protocol(Protocol::HttpBinary)
+            .with_endpoint(http_url(endpoint, HTTP_TRACES_PATH))
+            .with_headers(http_headers(&config.otlp_headers))
+            .build()?,
+    };
+    Ok(Some(tracer_provider_with(config, exporter)))
+}
+
+/// A provider exporting metrics to `exporter` every `OTEL_METRIC_EXPORT_INTERVAL`
+/// milliseconds, a minute by default.
+pub(crate) fn meter_provider_with<E>(exporter: E) -> SdkMeterProvider
+where
+    E: PushMetricExporter + 'static,
+{
+    SdkMeterProvider::builder()
+        .with_periodic_exporter(exporter)
+        .with_resource(resource())
+        .build()
+}
+
+/// A provider exporting metrics to the collector traces go to, or `None` when there is none.
+///
+/// The gRPC exporter must be built within a tokio runtime.
+///
+/// # Errors
+///
+/// Returns an error if the exporter cannot be built, e.g. for an invalid endpoint.
+pub(super) fn meter_provider(
+    config: &TelemetryConfig,
+) -> Result<Option<SdkMeterProvider>, ExporterBuildError> {
+    let Some(endpoint) = &config.otlp_endpoint else {
+        return Ok(None);
+    };
+    let exporter = match config.otlp_protocol {
+        OtlpProtocol::Grpc => MetricExporter::builder()
+            .with_tonic()
+            .with_endpoint(endpoint)
+            .with_metadata(metadata(&config.otlp_headers))
+            .build()?,
+        OtlpProtocol::HttpProtobuf => MetricExporter::builder()
+            .with_http()
+            .with_protocol(Protocol::HttpBinary)
+            .with_endpoint(http_url(endpoint, HTTP_METRICS_PATH))
+            .with_headers(http_headers(&config.otlp_headers))
+            .build()?,
+    };
+    Ok(Some(meter_provider_with(exporter)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_headers_are_percent_decoded() -> anyhow::Result<()> {
+        let headers = parse_headers("Authorization=Bearer%20s3cr%2Ft, x-scope=team-a ,")
+            .map_err(anyhow::Error::msg)?;
+
+        assert_eq!(
+            headers
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.expose()))
+                .collect::<Vec<_>>(),
+            vec![("authorization", "Bearer s3cr/t"), ("x-scope", "team-a")]
+        );
+        assert!(parse_headers("").map_err(anyhow::Error::msg)?.is_empty());
+        for invalid in [
+            "authorization",
+            "bad name=1",
+            "key=%zz",
+            "key=%2",
+            "key=%ff",
+        ] {
+            assert!(parse_headers(invalid).is_err(), "{invalid}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_protocol_and_sampler_parse_spec_names() -> anyhow::Result<()> {
+        assert_eq!("grpc".parse::<OtlpProtocol>()?, OtlpProtocol::Grpc);
+        assert_eq!(
+            "http/protobuf".parse::<OtlpProtocol>()?,
+            OtlpProtocol::HttpProtobuf
+        );
+        assert!("http/json".parse::<OtlpProtocol>().is_err());
+
+        assert_eq!(
+            "parentbased_traceidratio".parse::<TraceSampler>()?,
+            TraceSampler::ParentBasedTraceIdRatio
+        );
+        assert!("jaeger_remote".parse::<TraceSampler>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sampler_takes_the_ratio_argument() {
+        assert!(matches!(
+            TraceSampler::TraceIdRatio.sampler(Some(0.25)),
+            Sampler::TraceIdRatioBased(ratio) if (ratio - 0.25).abs() < f64::EPSILON
+        ));
+        assert!(matches!(
+            TraceSampler::TraceIdRatio.sampler(None),
+            Sampler::TraceIdRatioBased(ratio) if (ratio - 1.0).abs() < f64::EPSILON
+        ));
+        assert!(matches!(
+            TraceSampler::AlwaysOff.sampler(Some(0.25)),
+            Sampler::AlwaysOff
+        ));
+    }
+
+    #[test]
+    fn test_resource_names_the_service_and_its_version() {
+        let resource = resource();
+
+        assert_eq!(
+            resource.get(&"service.name".into()),
+            Some(SERVICE_NAME.into())
+        );
+        assert_eq!(
+            resource.get(&"service.version".into()),
+            Some(env!("CARGO_PKG_VERSION").into())
+        );
+    }
+
+    #[test]
+    fn test_http_signals_are_posted_to_their_paths() {
+        assert_eq!(
+            http_url("https://otel.example.com:4318/", HTTP_TRACES_PATH),
+            "https://otel.example.com:4318/v1/traces"
+        );
+        assert_eq!(
+            http_url("https://otel.example.com:4318", HTTP_METRICS_PATH),
+            "https://otel.example.com:4318/v1/metrics"
+        );
+    }
+}
@@ -0,0 +1,63 @@
+use opentelemetry::global;
+use opentelemetry::propagation::Injector;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde::Deserialize;
+use std::str::FromStr;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// Whether outgoing requests carry the trace context (`OTEL_PROPAGATORS`).
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TracePropagator {
+    /// W3C `traceparent` and `tracestate` headers
+    #[default]
+    #[serde(rename = "tracecontext")]
+    TraceContext,
+    /// No headers, for collectors outside the services called
+    #[serde(rename = "none")]
+    None,
+}
+
+impl FromStr for TracePropagator {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "tracecontext" => Ok(Self::TraceContext),
+            "none" => Ok(Self::None),
+            _ => anyhow::bail!("Unsupported propagator {value:?}, expected tracecontext or none"),
+        }
+    }
+}
+
+/// Sets the W3C trace context propagator used by [`trace_context_headers`].
+pub fn install_propagator() {
+    global::set_text_map_propagator(TraceContextPropagator::new());
+}
+
+/// Writes propagated fields into request headers, skipping any that are not valid headers.
+struct HeaderInjector<'a>(&'a mut HeaderMap);
+
+impl Injector for HeaderInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(key.as_bytes()),
+            HeaderValue::from_str(&value),
+        ) {
+            self.0.insert(name, value);
+        }
+    }
+}
+
+/// The context of the current span as headers, by the global propagator.
+///
+/// Empty unless a propagator is installed and the span is exported.
+#[must_use]
+pub fn trace_context_headers() -> HeaderMap {
+    let context = tracing::Span::current().context();
+    let mut headers = HeaderMap::new();
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut HeaderInjector(&mut headers));
+    });
+    headers
+}
@@ -1,3 +1,8 @@
-pub mod application;
-pub mod domain;
+pub use gha_dashboard_core::{application, domain};
 pub mod infrastructures;
+/// Builders, a scripted fake `GitHubApi` and a test server for code built on this crate.
+#[cfg(any(test, feature = "test-util"))]
+pub mod testing;
+
+#[cfg(test)]
+mod test_support;
@@ -1,51 +1,120 @@
-use gha_dashboard::application::use_cases::stream_github_actions_runs::StreamGitHubActionsRunsInteractor;
-use gha_dashboard::infrastructures::adapters::primary::web::{AppState, create_router};
-use gha_dashboard::infrastructures::adapters::secondary::external_apis::github::GitHubApiAdapter;
+use clap::Parser;
+use gha_dashboard::infrastructures::adapters::primary::cli::reload::ConfigReloader;
+use gha_dashboard::infrastructures::adapters::primary::cli::{self, Cli, Command, github_api};
+use gha_dashboard::infrastructures::config::{Config, ExporterOutput, PreflightMode, ServerMode};
+use gha_dashboard::infrastructures::telemetry::init_telemetry;
 use std::env;
-use std::net::SocketAddr;
+use std::io;
 use std::sync::Arc;
 use tracing::info;
-use tracing_subscriber::{EnvFilter, fmt, prelude::*};
+
+/// Asks `reloader` to apply the config file again on every SIGHUP, like classic daemons.
+#[cfg(unix)]
+async fn reload_on_sighup(reloader: Arc<ConfigReloader>) {
+    use tokio::signal::unix::{SignalKind, signal};
+    let mut hangups = match signal(SignalKind::hangup()) {
+        Ok(hangups) => hangups,
+        Err(e) => {
+            tracing::error!("Failed to listen for SIGHUP: {:?}", e);
+            return;
+        }
+    };
+    while hangups.recv().await.is_some() {
+        info!("SIGHUP received, reloading the configuration");
+        reloader.request();
+    }
+}
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    // Initialize tracing
-    let fmt_layer = fmt::layer()
-        .with_target(true)
-        .with_level(true)
-        .with_file(true)
-        .with_line_number(true);
+    let cli = Cli::parse();
+    #[cfg(feature = "tui")]
+    if let Some(Command::Tui(args)) = &cli.command {
+        use gha_dashboard::infrastructures::telemetry::TelemetryConfig;
+        use std::io::IsTerminal;
+        // A client of another server, so none of the settings apply; logs would draw over
+        // the table, so they are only written when the output is piped
+        let _telemetry = (!io::stdout().is_terminal())
+            .then(|| init_telemetry(&TelemetryConfig::default(), io::stderr))
+            .transpose()?;
+        return cli::tui(args, &mut io::stdout().lock()).await;
+    }
+    // Needs no settings, so it runs before a missing token could fail loading them
+    if let Some(Command::GenerateVapidKeys) = &cli.command {
+        return cli::generate_vapid_keys(&mut io::stdout().lock());
+    }
+    // Flags take precedence over the environment variables they mirror
+    let env = |name: &str| cli.settings.get(name).or_else(|| env::var(name).ok());
+    let config_path = Config::path(cli.settings.config.clone(), &env);
+    let (mut config, unknown_keys) = Config::load(config_path.as_deref(), &env)?;
+    if cli.skip_preflight {
+        config.server.preflight = PreflightMode::Off;
+    }
+    let command = cli.command.clone().unwrap_or(Command::Serve);
+    let check = cli.check || env::var("GHA_SELFTEST").is_ok_and(|value| value == "1");
 
-    tracing_subscriber::registry()
-        .with(fmt_layer)
-        .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")))
-        .init();
+    // Only the server logs to stdout; the other commands, and an exporter writing snapshots,
+    // print their results there
+    // Spans still buffered for the collector are flushed when the guard drops at exit
+    let exports_to_stdout = config.server.mode == ServerMode::Exporter
+        && config.server.exporter_output == ExporterOutput::Stdout;
+    let telemetry = if command == Command::Serve && !check && !exports_to_stdout {
+        init_telemetry(&config.telemetry, io::stdout)?
+    } else {
+        init_telemetry(&config.telemetry, io::stderr)?
+    };
 
     info!("Application starting");
+    if let Some(path) = &config_path {
+        info!("Loaded config file {}", path.display());
+        for key in &unknown_keys {
+            tracing::warn!("Ignoring unknown key {} in {}", key, path.display());
+        }
+    }
+    info!("Effective configuration: {:?}", config);
 
-    // GitHub Token の読み込み
-    let github_token = env::var("GITHUB_TOKEN")
-        .map_err(|e| anyhow::anyhow!("Failed to read GITHUB_TOKEN: {e}"))?;
-
-    // Build dependencies
-    let github_api_adapter = Arc::new(GitHubApiAdapter::new(
-        "https://api.github.com".to_string(),
-        github_token,
-    ));
-    let stream_use_case = Arc::new(StreamGitHubActionsRunsInteractor::new(github_api_adapter));
-    let app_state = Arc::new(AppState {
-        use_case: stream_use_case,
-    });
-
-    // Create router
-    let app = create_router(app_state);
-
-    // Start server
-    let addr = SocketAddr::from(([0, 0, 0, 0], 3000));
-    info!("Listening on {}", addr);
-
-    let listener = tokio::net::TcpListener::bind(addr).await?; // Added
-    axum::serve(listener, app.into_make_service()).await?; // Modified
-
-    Ok(())
+    let mut out = io::stdout().lock();
+    // Never binds a listener, whatever the command
+    if check {
+        return cli::self_test::run(github_api(&config, None, None, None)?, &config, &mut out)
+            .await;
+    }
+    match command {
+        Command::Serve => {
+            let settings = cli.settings.clone();
+            let reloader = ConfigReloader::new(
+                config_path,
+                Box::new(move |name| settings.get(name).or_else(|| env::var(name).ok())),
+                config.clone(),
+            )
+            .with_log_filter(telemetry.log_filter());
+            let reloader = Arc::new(reloader);
+            #[cfg(unix)]
+            tokio::spawn(reload_on_sighup(Arc::clone(&reloader)));
+            cli::serve(config, reloader).await
+        }
+        Command::Fetch(args) => {
+            cli::fetch(
+                github_api(&config, None, None, None)?,
+                &config,
+                &args,
+                &mut out,
+            )
+            .await
+        }
+        Command::ValidateConfig => {
+            cli::validate_config(github_api(&config, None, None, None)?.as_ref(), &mut out).await
+        }
+        Command::CheckQuota => {
+            cli::check_quota(
+                github_api(&config, None, None, None)?.as_ref(),
+                chrono::Utc::now(),
+                &mut out,
+            )
+            .await
+        }
+        Command::GenerateVapidKeys => unreachable!("handled before the config is loaded"),
+        #[cfg(feature = "tui")]
+        Command::Tui(_) => unreachable!("handled before the config is loaded"),
+    }
 }
@@ -0,0 +1,72 @@
+pub use gha_dashboard_core::test_support::*;
+
+use crate::infrastructures::adapters::primary::web::AppState;
+use crate::infrastructures::metrics::Metrics;
+use crate::infrastructures::telemetry::otlp;
+use opentelemetry::KeyValue;
+use opentelemetry::metrics::MeterProvider;
+use opentelemetry_sdk::metrics::data::{AggregatedMetrics, MetricData, ScopeMetrics, SumDataPoint};
+use opentelemetry_sdk::metrics::{InMemoryMetricExporter, SdkMeterProvider};
+use std::sync::Arc;
+
+/// Builds an `AppState` backed by `github_api` whose poller is not running.
+pub fn app_state(
+    github_api: StubGitHubApi,
+    auth_token: Option<&str>,
+) -> anyhow::Result<Arc<AppState>> {
+    crate::testing::app_state(github_api, auth_token)
+}
+
+/// Metrics whose OpenTelemetry instruments export to the returned exporter when the returned
+/// provider is flushed.
+pub fn in_memory_metrics()
+-> anyhow::Result<(Arc<Metrics>, SdkMeterProvider, InMemoryMetricExporter)> {
+    let exporter = InMemoryMetricExporter::default();
+    let provider = otlp::meter_provider_with(exporter.clone());
+    let metrics = Metrics::with_meter(&provider.meter(otlp::SERVICE_NAME))?;
+    Ok((Arc::new(metrics), provider, exporter))
+}
+
+/// The latest value of the integer sum or gauge `name` exported with exactly `attributes`.
+pub fn exported_value(
+    exporter: &InMemoryMetricExporter,
+    name: &str,
+    attributes: &[KeyValue],
+) -> anyhow::Result<Option<i64>> {
+    fn matches<'a>(point: impl Iterator<Item = &'a KeyValue>, attributes: &[KeyValue]) -> bool {
+        let point: Vec<_> = point.collect();
+        point.len() == attributes.len() && attributes.iter().all(|kv| point.contains(&kv))
+    }
+
+    let mut value = None;
+    for resource_metrics in exporter.get_finished_metrics()? {
+        for metric in resource_metrics
+            .scope_metrics()
+            .flat_map(ScopeMetrics::metrics)
+            .filter(|metric| metric.name() == name)
+        {
+            let found = match metric.data() {
+                AggregatedMetrics::U64(MetricData::Sum(sum)) => sum
+                    .data_points()
+                    .filter(|point| matches(point.attributes(), attributes))
+                    .map(|point| i64::try_from(point.value()))
+                    .last()
+                    .transpose()?,
+                AggregatedMetrics::U64(MetricData::Gauge(gauge)) => gauge
+                    .data_points()
+                    .filter(|point| matches(point.attributes(), attributes))
+                    .map(|point| i64::try_from(point.value()))
+                    .last()
+                    .transpose()?,
+                AggregatedMetrics::I64(MetricData::Sum(sum)) => sum
+                    .data_points()
+                    .filter(|point| matches(point.attributes(), attributes))
+                    .map(SumDataPoint::value)
+                    .last(),
+                _ => None,
+            };
+            value = found.or(value);
+        }
+    }
+    Ok(value)
+}
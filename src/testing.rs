@@ -0,0 +1,4 @@
+pub mod server;
+
+pub use gha_dashboard_core::testing::{Scripted, ScriptedGitHubApi, WorkflowRunBuilder, run_actor};
+pub use server::{TestServer, app_state};
@@ -0,0 +1,121 @@
+use crate::application::services::SharedPoller;
+use crate::domain::external_apis::github::GitHubApi;
+use crate::infrastructures::adapters::primary::web::{AppState, create_router};
+use crate::infrastructures::metrics::Metrics;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+
+/// Builds an `AppState` backed by `github_api` whose poller is not running.
+///
+/// # Errors
+///
+/// Returns an error if the metrics registry cannot be created.
+pub fn app_state<G>(github_api: G, auth_token: Option<&str>) -> anyhow::Result<Arc<AppState>>
+where
+    G: GitHubApi + Send + Sync + 'static,
+{
+    let refresh = Arc::new(Notify::new());
+    Ok(Arc::new(AppState::new(
+        Arc::new(SharedPoller::new(Arc::clone(&refresh))),
+        refresh,
+        Arc::new(github_api),
+        auth_token.map(str::to_string),
+        Arc::new(Metrics::new()?),
+    )))
+}
+
+/// The dashboard's router served on an ephemeral port of `127.0.0.1`, stopped on drop.
+pub struct TestServer {
+    pub addr: SocketAddr,
+    pub state: Arc<AppState>,
+    task: JoinHandle<()>,
+}
+
+impl TestServer {
+    /// Serves an [`app_state`] backed by `github_api`.
+    ///
+    /// Nothing is polled; publish snapshots through `state.poller` instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the state cannot be built or no port is free.
+    pub async fn start<G>(github_api: G) -> anyhow::Result<Self>
+    where
+        G: GitHubApi + Send + Sync + 'static,
+    {
+        Self::with_state(app_state(github_api, None)?).await
+    }
+
+    /// Serves `state`, e.g. one extended with `with_*` builders.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no port is free.
+    pub async fn with_state(state: Arc<AppState>) -> anyhow::Result<Self> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let router = create_router(Arc::clone(&state));
+        let task = tokio::spawn(async move {
+            if let Err(e) = axum::serve(
+                listener,
+                router.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .await
+            {
+                tracing::error!("Test server stopped: {:?}", e);
+            }
+        });
+        Ok(Self { addr, state, task })
+    }
+
+    /// `http://` URL of `path`, e.g. `/runs`.
+    #[must_use]
+    pub fn url(&self, path: &str) -> String {
+        format!("http://{}{path}", self.addr)
+    }
+
+    /// `ws://` URL of `path`, e.g. `/ws`.
+    #[must_use]
+    pub fn ws_url(&self, path: &str) -> String {
+        format!("ws://{}{path}", self.addr)
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::ids::RunId;
+    use crate::testing::ScriptedGitHubApi;
+    use reqwest::StatusCode;
+
+    #[tokio::test]
+    async fn test_router_is_served_with_the_injected_api() -> anyhow::Result<()> {
+        let github_api = ScriptedGitHubApi::new();
+        github_api.workflow_jobs(RunId(42), Ok(Vec::new()));
+        let server = TestServer::start(github_api).await?;
+        let client = reqwest::Client::new();
+
+        let health = client.get(server.url("/health")).send().await?;
+        let jobs = client
+            .get(server.url("/runs/owner/repo/42/jobs"))
+            .send()
+            .await?;
+        let unscripted = client
+            .get(server.url("/runs/owner/repo/43/jobs"))
+            .send()
+            .await?;
+
+        assert_eq!(health.status(), StatusCode::OK);
+        assert_eq!(jobs.status(), StatusCode::OK);
+        assert_ne!(unscripted.status(), StatusCode::OK);
+        Ok(())
+    }
+}
@@ -0,0 +1,260 @@
+//! Runs the dashboard against a mocked GitHub API and watches it through a websocket client,
+//! as a browser would.
+
+use futures_util::{Stream, StreamExt};
+use gha_dashboard::infrastructures::adapters::primary::cli::{exporter, run_server};
+use gha_dashboard::infrastructures::adapters::primary::web::server::BindAddr;
+use gha_dashboard::infrastructures::config::{Config, PreflightMode, ServerMode};
+use std::io::Write;
+use std::num::NonZeroU64;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::{Error as WsError, Message};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Long enough for a poll on a loaded CI machine, short enough to fail fast
+const FRAME_TIMEOUT: Duration = Duration::from_secs(15);
+
+async fn mock_github() -> MockServer {
+    // Not from wiremock's pool, so dropping it takes the API down instead of resetting it
+    let server = MockServer::builder().start().await;
+    Mock::given(method("GET"))
+        .and(path("/user/repos"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(serde_json::json!([{
+                "name": "repo",
+                "owner": {"login": "owner", "avatar_url": null},
+                "html_url": "https://github.com/owner/repo"
+            }])),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/repo/actions/workflows"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({"total_count": 0, "workflows": []})),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/repo/actions/runs"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "total_count": 1,
+            "workflow_runs": [{
+                "id": 42,
+                "run_number": 7,
+                "workflow_id": 1,
+                "name": "CI",
+                "display_title": "Add e2e tests",
+                "event": "push",
+                "head_branch": "main",
+                "head_sha": "0123456789abcdef0123456789abcdef01234567",
+                "status": "completed",
+                "conclusion": "failure",
+                "created_at": "2024-08-01T10:00:00Z",
+                "updated_at": "2024-08-01T10:05:00Z",
+                "html_url": "https://github.com/owner/repo/actions/runs/42",
+                "repository": {"full_name": "owner/repo"},
+                "actor": {
+                    "login": "octocat",
+                    "id": 1,
+                    "avatar_url": "https://avatars.githubusercontent.com/octocat",
+                    "html_url": "https://github.com/octocat",
+                    "type": "User"
+                }
+            }]
+        })))
+        .mount(&server)
+        .await;
+    server
+}
+
+fn config(github: &MockServer) -> anyhow::Result<Config> {
+    let mut config = Config::default();
+    config.server.bind_addr = BindAddr::Tcp("127.0.0.1:0".parse()?);
+    config.server.preflight = PreflightMode::Off;
+    config.github.api_url = Some(github.uri());
+    config.github.token = Some("token".to_string().into());
+    // Give up on an unreachable API quickly, so its error reaches the client within a poll
+    config.github.retry_deadline_seconds = NonZeroU64::new(1);
+    // The shortest interval polling allows, within the API call budget for one repository
+    config.polling.interval_seconds = 5;
+    config.polling.max_repositories = 1;
+    Ok(config)
+}
+
+/// The next text frame, skipping pings.
+async fn next_text<S>(socket: &mut S) -> anyhow::Result<String>
+where
+    S: Stream<Item = Result<Message, WsError>> + Unpin,
+{
+    loop {
+        let message = tokio::time::timeout(FRAME_TIMEOUT, socket.next())
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("websocket closed"))??;
+        match message {
+            Message::Text(text) => return Ok(text.to_string()),
+            Message::Close(frame) => anyhow::bail!("websocket closed with {frame:?}"),
+            _ => {}
+        }
+    }
+}
+
+/// The next frame that is a snapshot, skipping notices and other events.
+async fn next_snapshot<S>(socket: &mut S) -> anyhow::Result<serde_json::Value>
+where
+    S: Stream<Item = Result<Message, WsError>> + Unpin,
+{
+    loop {
+        let text = next_text(socket).await?;
+        if let Ok(frame) = serde_json::from_str::<serde_json::Value>(&text)
+            && frame.get("runs").is_some()
+        {
+            return Ok(frame);
+        }
+    }
+}
+
+/// Every object key in `value` that is not camelCase, e.g. `html_url`.
+fn snake_case_keys(value: &serde_json::Value) -> Vec<String> {
+    match value {
+        serde_json::Value::Object(object) => object
+            .iter()
+            .flat_map(|(key, value)| {
+                let mut keys = snake_case_keys(value);
+                if key.contains('_') {
+                    keys.push(key.clone());
+                }
+                keys
+            })
+            .collect(),
+        serde_json::Value::Array(items) => items.iter().flat_map(snake_case_keys).collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[tokio::test]
+async fn test_dashboard_streams_snapshots_until_github_goes_away() -> anyhow::Result<()> {
+    let github = mock_github().await;
+    let server = run_server(config(&github)?).await?;
+    let (mut socket, _) =
+        tokio_tungstenite::connect_async(format!("ws://{}/ws", server.addr)).await?;
+
+    let snapshot = next_snapshot(&mut socket).await?;
+    assert_eq!(snapshot["schemaVersion"], 1);
+    assert!(snapshot["generatedAt"].is_string());
+    let run = &snapshot["runs"][0];
+    assert_eq!(run["id"], 42);
+    assert_eq!(run["repositoryName"], "owner/repo");
+    assert_eq!(run["workflowName"], "CI");
+    assert_eq!(run["runNumber"], 7);
+    assert_eq!(run["headBranch"], "main");
+    assert_eq!(run["conclusion"], "failure");
+    assert_eq!(
+        run["htmlUrl"],
+        "https://github.com/owner/repo/actions/runs/42"
+    );
+    assert_eq!(
+        run["actor"]["avatarUrl"],
+        "https://avatars.githubusercontent.com/octocat"
+    );
+    assert_eq!(snake_case_keys(&snapshot), Vec::<String>::new());
+
+    // Runs GitHub no longer answers for are kept, stale, until polling itself fails
+    drop(github);
+    loop {
+        let text = next_text(&mut socket).await?;
+        if let Some(error) = text.strip_prefix("Error: ") {
+            assert!(error.contains("repositories"), "{error}");
+            break;
+        }
+        let frame: serde_json::Value = serde_json::from_str(&text)?;
+        if frame.get("runs").is_some() {
+            assert_eq!(frame["runs"][0]["id"], 42);
+        }
+    }
+
+    server.shutdown().await?;
+    let notice: serde_json::Value = serde_json::from_str(&next_text(&mut socket).await?)?;
+    assert_eq!(notice["type"], "warning");
+    assert!(
+        notice["message"]
+            .as_str()
+            .is_some_and(|message| message.starts_with("Server draining")),
+        "{notice}"
+    );
+    loop {
+        match tokio::time::timeout(FRAME_TIMEOUT, socket.next()).await? {
+            Some(Ok(Message::Close(_))) | None => break,
+            Some(Ok(_)) => {}
+            Some(Err(error)) => anyhow::bail!(error),
+        }
+    }
+    Ok(())
+}
+
+/// Bytes written by the exporter, read back by the test.
+#[derive(Clone, Default)]
+struct CapturedWriter(Arc<Mutex<Vec<u8>>>);
+
+impl CapturedWriter {
+    fn lines(&self) -> Vec<String> {
+        String::from_utf8_lossy(&self.0.lock().unwrap_or_else(PoisonError::into_inner))
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+impl Write for CapturedWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_exporter_writes_snapshots_as_json_lines_and_serves_only_admin_routes()
+-> anyhow::Result<()> {
+    let github = mock_github().await;
+    let mut config = config(&github)?;
+    config.server.mode = ServerMode::Exporter;
+    let out = CapturedWriter::default();
+    let exporter = exporter::start(config, out.clone()).await?;
+
+    let line = tokio::time::timeout(FRAME_TIMEOUT, async {
+        loop {
+            if let Some(line) = out.lines().into_iter().next() {
+                return line;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    })
+    .await?;
+    let snapshot: serde_json::Value = serde_json::from_str(&line)?;
+    assert_eq!(snapshot["schemaVersion"], 1);
+    assert_eq!(snapshot["runs"][0]["id"], 42);
+    assert_eq!(snapshot["runs"][0]["repositoryName"], "owner/repo");
+
+    let url = |path: &str| format!("http://{}{path}", exporter.addr);
+    let livez = reqwest::get(url("/livez")).await?;
+    assert_eq!(livez.status(), 200);
+    let metrics = reqwest::get(url("/metrics")).await?;
+    assert_eq!(metrics.status(), 200);
+    assert!(metrics.text().await?.contains("github_api_requests_total"));
+    for path in ["/runs", "/ws", "/health", "/status"] {
+        assert_eq!(reqwest::get(url(path)).await?.status(), 404, "{path}");
+    }
+
+    exporter.shutdown().await?;
+    Ok(())
+}